@@ -2,24 +2,41 @@
 // SPDX-License-Identifier: Apache-2.0, MIT
 
 use fvm_ipld_encoding::ipld_block::IpldBlock;
-use fvm_shared::address::Protocol;
+use fvm_ipld_encoding::{BytesDe, RawBytes};
+use fvm_shared::address::{Address, Protocol};
 use fvm_shared::crypto::signature::SignatureType::{Secp256k1, BLS};
-use fvm_shared::crypto::signature::{Signature, SignatureType};
+use fvm_shared::crypto::signature::{Signature, SignatureType, BLS_PUB_LEN, BLS_SIG_LEN};
+use fvm_shared::econ::TokenAmount;
 use fvm_shared::error::ExitCode;
+use fvm_shared::sys::SendFlags;
 use fvm_shared::{MethodNum, METHOD_CONSTRUCTOR};
 use num_derive::FromPrimitive;
+use num_traits::Zero;
 
 use fil_actors_runtime::builtin::singletons::SYSTEM_ACTOR_ADDR;
+use fil_actors_runtime::cbor::serialize;
 use fil_actors_runtime::runtime::{ActorCode, Runtime};
-use fil_actors_runtime::{actor_dispatch, ActorDowncast, FIRST_EXPORTED_METHOD_NUMBER};
+use fil_actors_runtime::{
+    actor_dispatch, deserialize_block, extract_send_result, forward_call, ActorContext,
+    ActorDowncast, ForwardCallReturn, OnUpgradeParams, FIRST_EXPORTED_METHOD_NUMBER,
+};
 use fil_actors_runtime::{actor_error, ActorError};
-use types::{AuthenticateMessageReturn, ConstructorParams, PubkeyAddressReturn};
+use types::{
+    AuthenticateMessageReturn, ConstructorParams, KeyType, PubkeyAddressExportedReturn,
+    PubkeyAddressReturn,
+};
 
-use crate::types::AuthenticateMessageParams;
+use crate::types::{
+    AuthenticateMessageParams, ExecuteAsOwnerParams, ExecuteAsOwnerReturn, ExecutionPayload,
+    ForwardCallPayload, SetDelegateParams, SignedForwardCallParams,
+    SIGNATURE_DOMAIN_SEPARATION_FORWARD_CALL,
+};
 
 pub use self::state::State;
 
+pub mod spending;
 mod state;
+#[cfg(feature = "testing")]
 pub mod testing;
 pub mod types;
 
@@ -35,6 +52,10 @@ pub enum Method {
     // Deprecated in v10
     // AuthenticateMessage = 3,
     AuthenticateMessageExported = frc42_dispatch::method_hash!("AuthenticateMessage"),
+    SetDelegateExported = frc42_dispatch::method_hash!("SetDelegate"),
+    ExecuteAsOwnerExported = frc42_dispatch::method_hash!("ExecuteAsOwner"),
+    PubkeyAddressExported = frc42_dispatch::method_hash!("PubkeyAddress"),
+    ForwardCallExported = frc42_dispatch::method_hash!("ForwardCall"),
 }
 
 /// Account Actor
@@ -52,7 +73,7 @@ impl Actor {
                     "address must use BLS or SECP protocol, got {}", protocol));
             }
         }
-        rt.create(&State { address })?;
+        rt.create(&State::new(address))?;
         Ok(())
     }
 
@@ -63,8 +84,29 @@ impl Actor {
         Ok(PubkeyAddressReturn { address: st.address })
     }
 
+    /// Fetches the pubkey-type address from this actor, along with the type of key it
+    /// designates, so callers needn't decode the address themselves.
+    pub fn pubkey_address_exported(
+        rt: &impl Runtime,
+    ) -> Result<PubkeyAddressExportedReturn, ActorError> {
+        rt.validate_immediate_caller_accept_any()?;
+        let st: State = rt.state()?;
+        let key_type = match st.address.protocol() {
+            Protocol::Secp256k1 => KeyType::Secp256k1,
+            Protocol::BLS => KeyType::BLS,
+            Protocol::Delegated => KeyType::Delegated,
+            protocol => {
+                return Err(actor_error!(illegal_state;
+                    "account address must be a public key address, got {}", protocol));
+            }
+        };
+        Ok(PubkeyAddressExportedReturn { address: st.address, key_type })
+    }
+
     /// Authenticates whether the provided signature is valid for the provided message.
     /// Should be called with the raw bytes of a signature, NOT a serialized Signature object that includes a SignatureType.
+    /// If the parameters carry a batch, the signature must instead be a BLS aggregate signature
+    /// over the message and every message in the batch.
     /// Errors with USR_ILLEGAL_ARGUMENT if the authentication is invalid.
     pub fn authenticate_message(
         rt: &impl Runtime,
@@ -73,23 +115,147 @@ impl Actor {
         rt.validate_immediate_caller_accept_any()?;
         let st: State = rt.state()?;
         let address = st.address;
-        let sig_type: SignatureType = match address.protocol() {
-            Protocol::Secp256k1 => Secp256k1,
-            Protocol::BLS => BLS,
-            protocol => {
-                return Err(actor_error!(illegal_state;
-                    "account address must use BLS or SECP protocol, got {}", protocol));
+        if let Some(batch) = params.batch {
+            authenticate_batch(rt, &address, &params.signature, &params.message, &batch)?;
+            return Ok(AuthenticateMessageReturn { authenticated: true });
+        }
+        verify_signature(rt, &address, params.signature, &params.message)?;
+
+        Ok(AuthenticateMessageReturn { authenticated: true })
+    }
+
+    /// Registers a delegate, or replaces its allowances, permitting it to execute specific
+    /// methods on behalf of this account. May only be called by the account itself.
+    pub fn set_delegate(rt: &impl Runtime, params: SetDelegateParams) -> Result<(), ActorError> {
+        let receiver = rt.message().receiver();
+        rt.validate_immediate_caller_is(std::iter::once(&receiver))?;
+
+        let delegate = resolve_id(rt, &params.delegate, "delegate")?;
+        let policy = rt.policy();
+        let mut allowances = params.allowances;
+        if allowances.len() as u64 > policy.account_max_delegate_allowances {
+            return Err(actor_error!(illegal_argument;
+                "{} allowances exceeds maximum of {}",
+                allowances.len(), policy.account_max_delegate_allowances));
+        }
+        for allowance in allowances.iter_mut() {
+            if allowance.remaining.is_negative() {
+                return Err(actor_error!(illegal_argument;
+                    "negative allowance {} for method {}", allowance.remaining, allowance.method));
+            }
+            allowance.to = resolve_id(rt, &allowance.to, "allowance target")?;
+        }
+
+        rt.transaction(|st: &mut State, rt| {
+            st.set_delegate(rt.store(), delegate, allowances, policy.account_max_delegates)
+        })
+    }
+
+    /// Sends a message from this account on behalf of a delegate, charging the value
+    /// transferred against the delegate's allowance for the method.
+    /// The delegate must either be the immediate caller, or have signed an authorization
+    /// for the execution which any party may relay.
+    pub fn execute_as_owner(
+        rt: &impl Runtime,
+        params: ExecuteAsOwnerParams,
+    ) -> Result<ExecuteAsOwnerReturn, ActorError> {
+        rt.validate_immediate_caller_accept_any()?;
+        if params.value.is_negative() {
+            return Err(actor_error!(illegal_argument; "negative value {}", params.value));
+        }
+        let to = resolve_id(rt, &params.to, "recipient")?;
+
+        let delegate = match &params.authorization {
+            None => rt.message().caller(),
+            Some(auth) => {
+                let delegate = resolve_id(rt, &auth.delegate, "delegate")?;
+                let payload = serialize(
+                    &ExecutionPayload {
+                        account: rt.message().receiver(),
+                        nonce: auth.nonce,
+                        to: params.to,
+                        method: params.method,
+                        params: params.params.clone(),
+                        value: params.value.clone(),
+                    },
+                    "execution payload",
+                )?;
+                let authenticated: bool = extract_send_result(rt.send(
+                    &delegate,
+                    Method::AuthenticateMessageExported as MethodNum,
+                    IpldBlock::serialize_cbor(&AuthenticateMessageParams {
+                        signature: auth.signature.clone(),
+                        message: payload.to_vec(),
+                        batch: None,
+                    })?,
+                    TokenAmount::zero(),
+                    None,
+                    SendFlags::READ_ONLY,
+                ))
+                .and_then(deserialize_block)
+                .context("delegate authentication failed")?;
+                if !authenticated {
+                    return Err(actor_error!(forbidden; "delegate authentication failed"));
+                }
+                delegate
             }
         };
-        let sig = Signature { sig_type, bytes: params.signature };
-        rt.verify_signature(&sig, &address, &params.message).map_err(|e| {
-            e.downcast_default(
-                ExitCode::USR_ILLEGAL_ARGUMENT,
-                "failed to authenticate message, signature invalid",
-            )
+
+        rt.transaction(|st: &mut State, rt| {
+            let mut d = st.get_delegate(rt.store(), &delegate)?.ok_or_else(
+                || actor_error!(forbidden; "{} is not a delegate of this account", delegate),
+            )?;
+            if let Some(auth) = &params.authorization {
+                if auth.nonce != d.nonce {
+                    return Err(actor_error!(illegal_argument;
+                        "authorization nonce {} does not match expected {}", auth.nonce, d.nonce));
+                }
+                d.nonce += 1;
+            }
+            d.spend(&to, params.method, &params.value)?;
+            st.put_delegate(rt.store(), d)
         })?;
 
-        Ok(AuthenticateMessageReturn { authenticated: true })
+        let ret = extract_send_result(rt.send_simple(
+            &to,
+            params.method,
+            params.params.into(),
+            params.value,
+        ))?;
+        Ok(ExecuteAsOwnerReturn { ret: ret.map_or(RawBytes::default(), |b| RawBytes::new(b.data)) })
+    }
+
+    /// Forwards a call from this account, with parameters in any supported codec.
+    /// The call must be signed with the account's key, along with the account's current nonce,
+    /// so that any party may relay it but none may replay it.
+    pub fn forward_call(
+        rt: &impl Runtime,
+        params: SignedForwardCallParams,
+    ) -> Result<ForwardCallReturn, ActorError> {
+        rt.validate_immediate_caller_accept_any()?;
+        let st: State = rt.state()?;
+        let payload = serialize(
+            &ForwardCallPayload {
+                chain_id: rt.chain_id().into(),
+                account: rt.message().receiver(),
+                nonce: params.nonce,
+                call: params.call.clone(),
+            },
+            "forward call payload",
+        )?;
+        let message = [SIGNATURE_DOMAIN_SEPARATION_FORWARD_CALL, payload.bytes()].concat();
+        verify_signature(rt, &st.address, params.signature, &message)?;
+
+        rt.transaction(|st: &mut State, _| {
+            if params.nonce != st.nonce {
+                return Err(actor_error!(illegal_argument;
+                    "forward call nonce {} does not match expected {}", params.nonce, st.nonce));
+            }
+            st.nonce += 1;
+            Ok(())
+        })?;
+
+        forward_call(rt, params.call)
     }
 
     /// Fallback method for unimplemented method numbers.
@@ -114,10 +280,93 @@ impl ActorCode for Actor {
         "Account"
     }
 
+    fn on_upgrade<RT>(_rt: &RT, _params: OnUpgradeParams) -> Result<(), ActorError>
+    where
+        RT: Runtime,
+        RT::Blockstore: Clone,
+    {
+        // State from before delegates were introduced decodes as is.
+        Ok(())
+    }
+
     actor_dispatch! {
         Constructor => constructor,
         PubkeyAddress => pubkey_address,
         AuthenticateMessageExported => authenticate_message,
+        SetDelegateExported => set_delegate,
+        ExecuteAsOwnerExported => execute_as_owner,
+        PubkeyAddressExported => pubkey_address_exported,
+        ForwardCallExported => forward_call,
         _ => fallback,
     }
 }
+
+// Verifies a signature by the account's key over a message.
+fn verify_signature(
+    rt: &impl Runtime,
+    address: &Address,
+    signature: Vec<u8>,
+    message: &[u8],
+) -> Result<(), ActorError> {
+    let sig_type: SignatureType = match address.protocol() {
+        Protocol::Secp256k1 => Secp256k1,
+        Protocol::BLS => BLS,
+        protocol => {
+            return Err(actor_error!(illegal_state;
+                "account address must use BLS or SECP protocol, got {}", protocol));
+        }
+    };
+    let sig = Signature { sig_type, bytes: signature };
+    rt.verify_signature(&sig, address, message).map_err(|e| {
+        e.downcast_default(
+            ExitCode::USR_ILLEGAL_ARGUMENT,
+            "failed to authenticate message, signature invalid",
+        )
+    })
+}
+
+// Verifies a BLS aggregate signature by the account's key over a message and a batch of others.
+fn authenticate_batch(
+    rt: &impl Runtime,
+    address: &Address,
+    signature: &[u8],
+    message: &[u8],
+    batch: &[BytesDe],
+) -> Result<(), ActorError> {
+    if address.protocol() != Protocol::BLS {
+        return Err(actor_error!(illegal_argument;
+            "batch authentication requires a BLS account, got {}", address.protocol()));
+    }
+    let pub_key: [u8; BLS_PUB_LEN] = address.payload_bytes().try_into().map_err(
+        |_| actor_error!(illegal_state; "invalid BLS public key for account address {}", address),
+    )?;
+    let signature: &[u8; BLS_SIG_LEN] = signature.try_into().map_err(|_| {
+        actor_error!(illegal_argument;
+            "aggregate signature must be {} bytes, got {}", BLS_SIG_LEN, signature.len())
+    })?;
+
+    let mut plaintexts: Vec<&[u8]> = Vec::with_capacity(batch.len() + 1);
+    plaintexts.push(message);
+    plaintexts.extend(batch.iter().map(|m| m.0.as_slice()));
+    // Aggregating signatures by one key is only secure over distinct messages.
+    let mut distinct = plaintexts.clone();
+    distinct.sort_unstable();
+    distinct.dedup();
+    if distinct.len() != plaintexts.len() {
+        return Err(actor_error!(illegal_argument; "batch messages must be distinct"));
+    }
+
+    let pub_keys = vec![pub_key; plaintexts.len()];
+    rt.verify_bls_aggregate(signature, &pub_keys, &plaintexts).map_err(|e| {
+        e.downcast_default(
+            ExitCode::USR_ILLEGAL_ARGUMENT,
+            "failed to authenticate message batch, aggregate signature invalid",
+        )
+    })
+}
+
+fn resolve_id(rt: &impl Runtime, address: &Address, what: &str) -> Result<Address, ActorError> {
+    rt.resolve_address(address)
+        .map(Address::new_id)
+        .ok_or_else(|| actor_error!(not_found; "failed to resolve {} address {}", what, address))
+}
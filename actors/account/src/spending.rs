@@ -0,0 +1,51 @@
+use fil_actors_runtime::{actor_error, ActorError};
+use fvm_ipld_encoding::tuple::*;
+use fvm_shared::address::Address;
+use fvm_shared::econ::TokenAmount;
+use fvm_shared::MethodNum;
+
+/// Permission for a delegate to invoke one method on one actor on behalf of the account,
+/// transferring up to a cumulative value cap.
+#[derive(Serialize_tuple, Deserialize_tuple, Clone, Debug, PartialEq, Eq)]
+pub struct MethodAllowance {
+    /// The actor the delegate may send to.
+    pub to: Address,
+    /// The method the delegate may invoke.
+    pub method: MethodNum,
+    /// The total value the delegate may still transfer with calls to this method.
+    pub remaining: TokenAmount,
+}
+
+/// A delegate registered by the account owner along with its spending allowances.
+#[derive(Serialize_tuple, Deserialize_tuple, Clone, Debug, PartialEq, Eq)]
+pub struct Delegate {
+    /// ID address of the delegate.
+    pub address: Address,
+    /// Nonce expected by the next execution authorized with the delegate's signature.
+    pub nonce: u64,
+    pub allowances: Vec<MethodAllowance>,
+}
+
+impl Delegate {
+    /// Deducts value from the delegate's allowance for a method, failing if the method isn't
+    /// permitted or the remaining allowance is insufficient.
+    pub fn spend(
+        &mut self,
+        to: &Address,
+        method: MethodNum,
+        value: &TokenAmount,
+    ) -> Result<(), ActorError> {
+        let delegate = self.address;
+        let allowance =
+            self.allowances.iter_mut().find(|a| a.to == *to && a.method == method).ok_or_else(
+                || actor_error!(forbidden; "delegate {} may not invoke method {} on {}", delegate, method, to),
+            )?;
+        if *value > allowance.remaining {
+            return Err(actor_error!(insufficient_funds;
+                "value {} exceeds delegate {} remaining allowance {} for method {} on {}",
+                value, delegate, allowance.remaining, method, to));
+        }
+        allowance.remaining -= value;
+        Ok(())
+    }
+}
@@ -1,11 +1,141 @@
 // Copyright 2019-2022 ChainSafe Systems
 // SPDX-License-Identifier: Apache-2.0, MIT
 
-use fvm_ipld_encoding::tuple::*;
+use std::fmt;
+
+use cid::Cid;
+use fil_actors_runtime::{actor_error, ActorError, Config, Map2, DEFAULT_HAMT_CONFIG};
+use fvm_ipld_blockstore::Blockstore;
 use fvm_shared::address::Address;
+use serde::de::{self, SeqAccess, Visitor};
+use serde::ser::SerializeTuple;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::spending::{Delegate, MethodAllowance};
+
+pub type DelegateMap<BS> = Map2<BS, Address, Delegate>;
+pub const DELEGATES_CONFIG: Config = DEFAULT_HAMT_CONFIG;
 
 /// State includes the address for the actor
-#[derive(Serialize_tuple, Deserialize_tuple, Debug, Clone)]
+/// The delegates and nonce are encoded as optional trailing elements, written only once the
+/// account first registers a delegate or forwards a call, so other accounts keep the original
+/// single-element encoding.
+#[derive(Debug, Clone)]
 pub struct State {
     pub address: Address,
+    /// HAMT of the delegates permitted to execute messages on behalf of the account, subject to
+    /// allowances, keyed by ID address. Absent until the first delegate is registered.
+    /// Delegates are never removed, so their nonce continues to prevent replay of
+    /// previously signed executions after their allowances are revoked.
+    pub delegates: Option<Cid>,
+    /// Number of delegates registered.
+    pub delegate_count: u64,
+    /// Nonce expected by the next call forwarded with the signature of the account's key.
+    pub nonce: u64,
+}
+
+impl Serialize for State {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let extended = self.delegates.is_some() || self.delegate_count != 0 || self.nonce != 0;
+        let mut tuple = serializer.serialize_tuple(if extended { 4 } else { 1 })?;
+        tuple.serialize_element(&self.address)?;
+        if extended {
+            tuple.serialize_element(&self.delegates)?;
+            tuple.serialize_element(&self.delegate_count)?;
+            tuple.serialize_element(&self.nonce)?;
+        }
+        tuple.end()
+    }
+}
+
+impl<'de> Deserialize<'de> for State {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_seq(StateVisitor)
+    }
+}
+
+struct StateVisitor;
+
+impl<'de> Visitor<'de> for StateVisitor {
+    type Value = State;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("an address, optionally followed by delegates, delegate count and nonce")
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+        let address = seq.next_element()?.ok_or_else(|| de::Error::invalid_length(0, &self))?;
+        let mut state = State::new(address);
+        if let Some(delegates) = seq.next_element()? {
+            state.delegates = delegates;
+            state.delegate_count =
+                seq.next_element()?.ok_or_else(|| de::Error::invalid_length(2, &self))?;
+            state.nonce = seq.next_element()?.ok_or_else(|| de::Error::invalid_length(3, &self))?;
+        }
+        if seq.next_element::<de::IgnoredAny>()?.is_some() {
+            return Err(de::Error::invalid_length(5, &self));
+        }
+        Ok(state)
+    }
+}
+
+impl State {
+    pub fn new(address: Address) -> Self {
+        State { address, delegates: None, delegate_count: 0, nonce: 0 }
+    }
+
+    pub fn load_delegates<BS: Blockstore>(&self, store: BS) -> Result<DelegateMap<BS>, ActorError> {
+        match &self.delegates {
+            Some(root) => DelegateMap::load(store, root, DELEGATES_CONFIG, "delegates"),
+            None => Ok(DelegateMap::empty(store, DELEGATES_CONFIG, "delegates")),
+        }
+    }
+
+    /// Returns the delegate with the given ID address, if registered.
+    pub fn get_delegate<BS: Blockstore>(
+        &self,
+        store: BS,
+        address: &Address,
+    ) -> Result<Option<Delegate>, ActorError> {
+        Ok(self.load_delegates(store)?.get(address)?.cloned())
+    }
+
+    /// Stores a registered delegate after it has been modified.
+    pub fn put_delegate<BS: Blockstore>(
+        &mut self,
+        store: BS,
+        delegate: Delegate,
+    ) -> Result<(), ActorError> {
+        let mut delegates = self.load_delegates(store)?;
+        let address = delegate.address;
+        delegates.set(&address, delegate)?;
+        self.delegates = Some(delegates.flush()?);
+        Ok(())
+    }
+
+    /// Replaces a delegate's allowances, registering the delegate if it's new and the account
+    /// has fewer than `max_delegates`.
+    pub fn set_delegate<BS: Blockstore>(
+        &mut self,
+        store: BS,
+        address: Address,
+        allowances: Vec<MethodAllowance>,
+        max_delegates: u64,
+    ) -> Result<(), ActorError> {
+        let mut delegates = self.load_delegates(store)?;
+        let delegate = match delegates.get(&address)? {
+            Some(existing) => Delegate { allowances, ..existing.clone() },
+            None => {
+                if self.delegate_count >= max_delegates {
+                    return Err(actor_error!(illegal_argument;
+                        "account already has the maximum of {} delegates", max_delegates));
+                }
+                self.delegate_count += 1;
+                Delegate { address, nonce: 0, allowances }
+            }
+        };
+        delegates.set(&address, delegate)?;
+        self.delegates = Some(delegates.flush()?);
+        Ok(())
+    }
 }
@@ -1,4 +1,5 @@
 use fil_actors_runtime::{MessageAccumulator, FIRST_NON_SINGLETON_ADDR};
+use fvm_ipld_blockstore::Blockstore;
 use fvm_shared::address::{Address, Protocol};
 
 use crate::State;
@@ -8,8 +9,9 @@ pub struct StateSummary {
 }
 
 /// Checks internal invariants of account state.
-pub fn check_state_invariants(
+pub fn check_state_invariants<BS: Blockstore>(
     state: &State,
+    store: &BS,
     id_address: &Address,
 ) -> (StateSummary, MessageAccumulator) {
     let acc = MessageAccumulator::default();
@@ -26,5 +28,48 @@ pub fn check_state_invariants(
         _ => (),
     }
 
+    match state.load_delegates(store) {
+        Ok(delegates) => {
+            let mut count = 0;
+            let ret = delegates.for_each(|address, delegate| {
+                count += 1;
+                acc.require(
+                    address.protocol() == Protocol::ID,
+                    format!("delegate address {} must be ID protocol", address),
+                );
+                acc.require(
+                    delegate.address == address,
+                    format!("delegate {} is keyed by address {}", delegate.address, address),
+                );
+                for allowance in &delegate.allowances {
+                    acc.require(
+                        allowance.to.protocol() == Protocol::ID,
+                        format!(
+                            "delegate {} allowance target {} must be ID protocol",
+                            address, allowance.to
+                        ),
+                    );
+                    acc.require(
+                        !allowance.remaining.is_negative(),
+                        format!(
+                            "delegate {} has negative allowance {}",
+                            address, allowance.remaining
+                        ),
+                    );
+                }
+                Ok(())
+            });
+            acc.require_no_error(ret, "error iterating delegates");
+            acc.require(
+                count == state.delegate_count,
+                format!(
+                    "delegate count {} does not match {} delegates",
+                    state.delegate_count, count
+                ),
+            );
+        }
+        Err(e) => acc.add(format!("error loading delegates: {e}")),
+    }
+
     (StateSummary { pub_key_address: state.address }, acc)
 }
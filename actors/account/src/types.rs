@@ -1,6 +1,17 @@
-use fvm_ipld_encoding::strict_bytes;
+use std::fmt;
+
+use fil_actors_runtime::ForwardCallParams;
 use fvm_ipld_encoding::tuple::*;
+use fvm_ipld_encoding::{strict_bytes, BytesDe, BytesSer, RawBytes};
 use fvm_shared::address::Address;
+use fvm_shared::econ::TokenAmount;
+use fvm_shared::MethodNum;
+use serde::de::{self, SeqAccess, Visitor};
+use serde::ser::SerializeTuple;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use serde_repr::{Deserialize_repr, Serialize_repr};
+
+use crate::spending::MethodAllowance;
 
 #[derive(Debug, Serialize_tuple, Deserialize_tuple)]
 #[serde(transparent)]
@@ -14,12 +25,72 @@ pub struct PubkeyAddressReturn {
     pub address: Address,
 }
 
+/// The type of key an account signs with.
+/// Discriminants match the protocol of the corresponding address.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize_repr, Deserialize_repr)]
+#[repr(u8)]
+pub enum KeyType {
+    Secp256k1 = 1,
+    BLS = 3,
+    Delegated = 4,
+}
+
 #[derive(Debug, Serialize_tuple, Deserialize_tuple)]
+pub struct PubkeyAddressExportedReturn {
+    pub address: Address,
+    pub key_type: KeyType,
+}
+
+/// Parameters to authenticate a message, or a batch of messages.
+/// The batch is encoded as an optional third element, so parameters without a batch
+/// have the same encoding as the FRC-0042 `AuthenticateMessage` parameters.
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct AuthenticateMessageParams {
-    #[serde(with = "strict_bytes")]
     pub signature: Vec<u8>,
-    #[serde(with = "strict_bytes")]
     pub message: Vec<u8>,
+    /// Further messages covered, along with `message`, by a BLS aggregate `signature`.
+    /// Every message in the batch must be distinct.
+    pub batch: Option<Vec<BytesDe>>,
+}
+
+impl Serialize for AuthenticateMessageParams {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut tuple = serializer.serialize_tuple(if self.batch.is_some() { 3 } else { 2 })?;
+        tuple.serialize_element(&BytesSer(&self.signature))?;
+        tuple.serialize_element(&BytesSer(&self.message))?;
+        if let Some(batch) = &self.batch {
+            tuple.serialize_element(batch)?;
+        }
+        tuple.end()
+    }
+}
+
+impl<'de> Deserialize<'de> for AuthenticateMessageParams {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_seq(AuthenticateMessageParamsVisitor)
+    }
+}
+
+struct AuthenticateMessageParamsVisitor;
+
+impl<'de> Visitor<'de> for AuthenticateMessageParamsVisitor {
+    type Value = AuthenticateMessageParams;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("a signature and message, optionally followed by a batch of messages")
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+        let BytesDe(signature) =
+            seq.next_element()?.ok_or_else(|| de::Error::invalid_length(0, &self))?;
+        let BytesDe(message) =
+            seq.next_element()?.ok_or_else(|| de::Error::invalid_length(1, &self))?;
+        let batch = seq.next_element()?;
+        if seq.next_element::<de::IgnoredAny>()?.is_some() {
+            return Err(de::Error::invalid_length(4, &self));
+        }
+        Ok(AuthenticateMessageParams { signature, message, batch })
+    }
 }
 
 #[derive(Debug, Serialize_tuple, Deserialize_tuple)]
@@ -27,3 +98,75 @@ pub struct AuthenticateMessageParams {
 pub struct AuthenticateMessageReturn {
     pub authenticated: bool,
 }
+
+#[derive(Debug, Serialize_tuple, Deserialize_tuple)]
+pub struct SetDelegateParams {
+    pub delegate: Address,
+    /// The complete set of allowances for the delegate, replacing any existing ones.
+    /// An empty list revokes all of the delegate's permissions.
+    pub allowances: Vec<MethodAllowance>,
+}
+
+/// A delegate's signed authorization for an execution relayed by some other party.
+#[derive(Debug, Serialize_tuple, Deserialize_tuple)]
+pub struct DelegateAuthorization {
+    pub delegate: Address,
+    pub nonce: u64,
+    #[serde(with = "strict_bytes")]
+    pub signature: Vec<u8>,
+}
+
+#[derive(Debug, Serialize_tuple, Deserialize_tuple)]
+pub struct ExecuteAsOwnerParams {
+    pub to: Address,
+    pub method: MethodNum,
+    pub params: RawBytes,
+    pub value: TokenAmount,
+    /// Authorization for a relayed execution.
+    /// If absent, the immediate caller must be a delegate.
+    pub authorization: Option<DelegateAuthorization>,
+}
+
+#[derive(Debug, Serialize_tuple, Deserialize_tuple)]
+#[serde(transparent)]
+pub struct ExecuteAsOwnerReturn {
+    pub ret: RawBytes,
+}
+
+/// The message a delegate signs to authorize a relayed execution.
+#[derive(Debug, Serialize_tuple, Deserialize_tuple)]
+pub struct ExecutionPayload {
+    /// ID address of the account executing the message.
+    pub account: Address,
+    pub nonce: u64,
+    pub to: Address,
+    pub method: MethodNum,
+    pub params: RawBytes,
+    pub value: TokenAmount,
+}
+
+/// A call to forward from the account, signed with the account's key so that any party may
+/// relay it.
+#[derive(Debug, Serialize_tuple, Deserialize_tuple)]
+pub struct SignedForwardCallParams {
+    pub call: ForwardCallParams,
+    pub nonce: u64,
+    #[serde(with = "strict_bytes")]
+    pub signature: Vec<u8>,
+}
+
+/// Domain separation tag prefixed to the serialized [`ForwardCallPayload`] that an account's key
+/// signs, so the signature can't be mistaken for one over any other message.
+pub const SIGNATURE_DOMAIN_SEPARATION_FORWARD_CALL: &[u8] = b"fil_forwardcall:";
+
+/// The message the account's key signs to authorize a forwarded call, prefixed with
+/// [`SIGNATURE_DOMAIN_SEPARATION_FORWARD_CALL`].
+#[derive(Debug, Serialize_tuple, Deserialize_tuple)]
+pub struct ForwardCallPayload {
+    /// ID of the chain on which the call may be forwarded.
+    pub chain_id: u64,
+    /// ID address of the account forwarding the call.
+    pub account: Address,
+    pub nonce: u64,
+    pub call: ForwardCallParams,
+}
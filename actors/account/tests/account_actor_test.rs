@@ -4,21 +4,34 @@
 use anyhow::anyhow;
 use fvm_actor_utils::receiver::UniversalReceiverParams;
 use fvm_ipld_encoding::ipld_block::IpldBlock;
-use fvm_ipld_encoding::RawBytes;
+use fvm_ipld_encoding::{BytesDe, BytesSer, RawBytes, IPLD_RAW};
 use fvm_shared::address::Address;
-use fvm_shared::crypto::signature::Signature;
+use fvm_shared::chainid::ChainID;
+use fvm_shared::crypto::signature::{Signature, BLS_SIG_LEN};
+use fvm_shared::econ::TokenAmount;
 use fvm_shared::error::ExitCode;
+use fvm_shared::sys::SendFlags;
 use fvm_shared::MethodNum;
+use num_traits::Zero;
 
-use fil_actor_account::types::AuthenticateMessageParams;
+use fil_actor_account::spending::MethodAllowance;
+use fil_actor_account::types::{
+    AuthenticateMessageParams, DelegateAuthorization, ExecuteAsOwnerParams, ExecutionPayload,
+    ForwardCallPayload, KeyType, PubkeyAddressExportedReturn, SetDelegateParams,
+    SignedForwardCallParams, SIGNATURE_DOMAIN_SEPARATION_FORWARD_CALL,
+};
 use fil_actor_account::{testing::check_state_invariants, Actor as AccountActor, Method, State};
 use fil_actors_runtime::builtin::SYSTEM_ACTOR_ADDR;
+use fil_actors_runtime::cbor::serialize;
+use fil_actors_runtime::runtime::Runtime;
 use fil_actors_runtime::test_utils::*;
-use fil_actors_runtime::FIRST_EXPORTED_METHOD_NUMBER;
+use fil_actors_runtime::{
+    ActorError, ForwardCallParams, ForwardCallReturn, FIRST_EXPORTED_METHOD_NUMBER,
+};
 
 #[test]
 fn construction() {
-    fn construct(addr: Address, exit_code: ExitCode) {
+    fn construct(addr: Address, exit_code: ExitCode, key_type: Option<KeyType>) {
         let rt = MockRuntime { receiver: Address::new_id(100), ..Default::default() };
         rt.set_caller(*SYSTEM_ACTOR_CODE_ID, SYSTEM_ACTOR_ADDR);
         rt.expect_validate_caller_addr(vec![SYSTEM_ACTOR_ADDR]);
@@ -41,6 +54,16 @@ fn construction() {
                 .deserialize()
                 .unwrap();
             assert_eq!(pk, addr);
+
+            rt.expect_validate_caller_any();
+            let ret: PubkeyAddressExportedReturn = rt
+                .call::<AccountActor>(Method::PubkeyAddressExported as MethodNum, None)
+                .unwrap()
+                .unwrap()
+                .deserialize()
+                .unwrap();
+            assert_eq!(ret.address, addr);
+            assert_eq!(Some(ret.key_type), key_type);
             check_state(&rt);
         } else {
             expect_abort(
@@ -54,10 +77,15 @@ fn construction() {
     construct(
         Address::new_secp256k1(&[2; fvm_shared::address::SECP_PUB_LEN]).unwrap(),
         ExitCode::OK,
+        Some(KeyType::Secp256k1),
     );
-    construct(Address::new_bls(&[1; fvm_shared::address::BLS_PUB_LEN]).unwrap(), ExitCode::OK);
-    construct(Address::new_id(1), ExitCode::USR_ILLEGAL_ARGUMENT);
-    construct(Address::new_actor(&[1, 2, 3]), ExitCode::USR_ILLEGAL_ARGUMENT);
+    construct(
+        Address::new_bls(&[1; fvm_shared::address::BLS_PUB_LEN]).unwrap(),
+        ExitCode::OK,
+        Some(KeyType::BLS),
+    );
+    construct(Address::new_id(1), ExitCode::USR_ILLEGAL_ARGUMENT, None);
+    construct(Address::new_actor(&[1, 2, 3]), ExitCode::USR_ILLEGAL_ARGUMENT, None);
 }
 
 #[test]
@@ -107,6 +135,7 @@ fn authenticate_message() {
     let params = IpldBlock::serialize_cbor(&AuthenticateMessageParams {
         signature: vec![],
         message: vec![],
+        batch: None,
     })
     .unwrap();
 
@@ -159,6 +188,111 @@ fn authenticate_message() {
         .unwrap());
 }
 
+#[test]
+fn authenticate_message_batch() {
+    let rt = MockRuntime { receiver: Address::new_id(100), ..Default::default() };
+    rt.set_caller(*SYSTEM_ACTOR_CODE_ID, SYSTEM_ACTOR_ADDR);
+
+    let pub_key = [3; fvm_shared::address::BLS_PUB_LEN];
+    let addr = Address::new_bls(&pub_key).unwrap();
+    rt.expect_validate_caller_addr(vec![SYSTEM_ACTOR_ADDR]);
+    rt.call::<AccountActor>(
+        Method::Constructor as MethodNum,
+        IpldBlock::serialize_cbor(&addr).unwrap(),
+    )
+    .unwrap();
+
+    let signature = [7; BLS_SIG_LEN];
+    let authenticate = |message: &[u8], batch: &[&[u8]], signature: &[u8]| {
+        rt.expect_validate_caller_any();
+        let params = AuthenticateMessageParams {
+            signature: signature.to_vec(),
+            message: message.to_vec(),
+            batch: Some(batch.iter().map(|m| BytesDe(m.to_vec())).collect()),
+        };
+        let ret = rt.call::<AccountActor>(
+            Method::AuthenticateMessageExported as MethodNum,
+            IpldBlock::serialize_cbor(&params).unwrap(),
+        );
+        rt.verify();
+        ret
+    };
+
+    // Valid aggregate signature
+    rt.expect_verify_bls_aggregate(ExpectVerifyBlsAggregate {
+        sig: signature,
+        pub_keys: vec![pub_key; 3],
+        plaintexts: vec![b"a".to_vec(), b"b".to_vec(), b"c".to_vec()],
+        result: Ok(()),
+    });
+    let ret = authenticate(b"a", &[b"b", b"c"], &signature).unwrap().unwrap();
+    assert!(ret.deserialize::<bool>().unwrap());
+
+    // Invalid aggregate signature
+    rt.expect_verify_bls_aggregate(ExpectVerifyBlsAggregate {
+        sig: signature,
+        pub_keys: vec![pub_key; 2],
+        plaintexts: vec![b"a".to_vec(), b"b".to_vec()],
+        result: Err(anyhow!("bad aggregate")),
+    });
+    expect_abort_contains_message(
+        ExitCode::USR_ILLEGAL_ARGUMENT,
+        "bad aggregate",
+        authenticate(b"a", &[b"b"], &signature),
+    );
+
+    // Malformed batches are rejected without verification
+    expect_abort_contains_message(
+        ExitCode::USR_ILLEGAL_ARGUMENT,
+        "must be distinct",
+        authenticate(b"a", &[b"b", b"a"], &signature),
+    );
+    expect_abort_contains_message(
+        ExitCode::USR_ILLEGAL_ARGUMENT,
+        "aggregate signature must be",
+        authenticate(b"a", &[b"b"], &signature[1..]),
+    );
+
+    // Parameters without a batch keep the two-element encoding
+    let params = AuthenticateMessageParams { signature: vec![1], message: vec![2], batch: None };
+    assert_eq!(
+        RawBytes::serialize((BytesSer(&[1]), BytesSer(&[2]))).unwrap(),
+        RawBytes::serialize(&params).unwrap()
+    );
+    let batched = AuthenticateMessageParams { batch: Some(vec![BytesDe(vec![3])]), ..params };
+    assert_eq!(batched, RawBytes::serialize(&batched).unwrap().deserialize().unwrap());
+}
+
+#[test]
+fn secp_account_rejects_batch() {
+    let rt = MockRuntime { receiver: Address::new_id(100), ..Default::default() };
+    rt.set_caller(*SYSTEM_ACTOR_CODE_ID, SYSTEM_ACTOR_ADDR);
+
+    let addr = Address::new_secp256k1(&[2; fvm_shared::address::SECP_PUB_LEN]).unwrap();
+    rt.expect_validate_caller_addr(vec![SYSTEM_ACTOR_ADDR]);
+    rt.call::<AccountActor>(
+        Method::Constructor as MethodNum,
+        IpldBlock::serialize_cbor(&addr).unwrap(),
+    )
+    .unwrap();
+
+    rt.expect_validate_caller_any();
+    let params = AuthenticateMessageParams {
+        signature: vec![0; BLS_SIG_LEN],
+        message: vec![1],
+        batch: Some(vec![BytesDe(vec![2])]),
+    };
+    expect_abort_contains_message(
+        ExitCode::USR_ILLEGAL_ARGUMENT,
+        "requires a BLS account",
+        rt.call::<AccountActor>(
+            Method::AuthenticateMessageExported as MethodNum,
+            IpldBlock::serialize_cbor(&params).unwrap(),
+        ),
+    );
+    rt.verify();
+}
+
 #[test]
 fn test_fallback() {
     let rt = MockRuntime { receiver: Address::new_id(100), ..Default::default() };
@@ -197,6 +331,323 @@ fn test_fallback() {
 
 fn check_state(rt: &MockRuntime) {
     let test_address = Address::new_id(1000);
-    let (_, acc) = check_state_invariants(&rt.get_state(), &test_address);
+    let (_, acc) = check_state_invariants(&rt.get_state(), rt.store(), &test_address);
     acc.assert_empty();
 }
+
+#[test]
+fn delegate_execution() {
+    let rt = construct_account();
+    let receiver = rt.receiver;
+    let delegate = Address::new_id(1001);
+    let miner = Address::new_id(1002);
+    let submit_post: MethodNum = 5;
+    rt.set_balance(TokenAmount::from_atto(100));
+
+    // only the account itself may register delegates
+    rt.set_caller(*ACCOUNT_ACTOR_CODE_ID, delegate);
+    rt.expect_validate_caller_addr(vec![receiver]);
+    expect_abort(
+        ExitCode::USR_FORBIDDEN,
+        set_delegate(&rt, delegate, vec![allowance(miner, submit_post, 10)]),
+    );
+    rt.reset();
+
+    rt.set_caller(*ACCOUNT_ACTOR_CODE_ID, receiver);
+    rt.expect_validate_caller_addr(vec![receiver]);
+    set_delegate(&rt, delegate, vec![allowance(miner, submit_post, 10)]).unwrap();
+    rt.verify();
+
+    // delegate executes a permitted method within its allowance
+    rt.set_caller(*ACCOUNT_ACTOR_CODE_ID, delegate);
+    rt.expect_validate_caller_any();
+    rt.expect_send_simple(miner, submit_post, None, TokenAmount::from_atto(4), None, ExitCode::OK);
+    execute_as_owner(&rt, miner, submit_post, 4, None).unwrap();
+    rt.verify();
+    let st: State = rt.get_state();
+    let d = st.get_delegate(rt.store(), &delegate).unwrap().unwrap();
+    assert_eq!(TokenAmount::from_atto(6), d.allowances[0].remaining);
+
+    // exceeding the remaining allowance fails
+    rt.expect_validate_caller_any();
+    expect_abort(
+        ExitCode::USR_INSUFFICIENT_FUNDS,
+        execute_as_owner(&rt, miner, submit_post, 7, None),
+    );
+    rt.reset();
+
+    // methods without an allowance are forbidden
+    rt.expect_validate_caller_any();
+    expect_abort(ExitCode::USR_FORBIDDEN, execute_as_owner(&rt, miner, submit_post + 1, 0, None));
+    rt.reset();
+
+    // non-delegates are forbidden
+    rt.set_caller(*ACCOUNT_ACTOR_CODE_ID, Address::new_id(1003));
+    rt.expect_validate_caller_any();
+    expect_abort(ExitCode::USR_FORBIDDEN, execute_as_owner(&rt, miner, submit_post, 0, None));
+    rt.reset();
+    check_state(&rt);
+}
+
+#[test]
+fn relayed_delegate_execution() {
+    let rt = construct_account();
+    let receiver = rt.receiver;
+    let delegate = Address::new_id(1001);
+    let relayer = Address::new_id(1003);
+    let miner = Address::new_id(1002);
+    let submit_post: MethodNum = 5;
+
+    rt.set_caller(*ACCOUNT_ACTOR_CODE_ID, receiver);
+    rt.expect_validate_caller_addr(vec![receiver]);
+    set_delegate(&rt, delegate, vec![allowance(miner, submit_post, 0)]).unwrap();
+    rt.verify();
+
+    let signature = vec![1, 2, 3];
+    let auth = |nonce| DelegateAuthorization { delegate, nonce, signature: signature.clone() };
+    let expect_authenticate = |nonce, authenticated: bool| {
+        let payload = ExecutionPayload {
+            account: receiver,
+            nonce,
+            to: miner,
+            method: submit_post,
+            params: RawBytes::default(),
+            value: TokenAmount::zero(),
+        };
+        rt.expect_send(
+            delegate,
+            Method::AuthenticateMessageExported as MethodNum,
+            IpldBlock::serialize_cbor(&AuthenticateMessageParams {
+                signature: signature.clone(),
+                message: serialize(&payload, "payload").unwrap().to_vec(),
+                batch: None,
+            })
+            .unwrap(),
+            TokenAmount::zero(),
+            None,
+            SendFlags::READ_ONLY,
+            IpldBlock::serialize_cbor(&authenticated).unwrap(),
+            ExitCode::OK,
+            None,
+        );
+    };
+
+    // a relayer submits the delegate's signed execution
+    rt.set_caller(*ACCOUNT_ACTOR_CODE_ID, relayer);
+    rt.expect_validate_caller_any();
+    expect_authenticate(0, true);
+    rt.expect_send_simple(miner, submit_post, None, TokenAmount::zero(), None, ExitCode::OK);
+    execute_as_owner(&rt, miner, submit_post, 0, Some(auth(0))).unwrap();
+    rt.verify();
+    let st: State = rt.get_state();
+    assert_eq!(1, st.get_delegate(rt.store(), &delegate).unwrap().unwrap().nonce);
+
+    // replaying the same authorization fails
+    rt.expect_validate_caller_any();
+    expect_authenticate(0, true);
+    expect_abort(
+        ExitCode::USR_ILLEGAL_ARGUMENT,
+        execute_as_owner(&rt, miner, submit_post, 0, Some(auth(0))),
+    );
+    rt.reset();
+
+    // an invalid signature fails
+    rt.expect_validate_caller_any();
+    expect_authenticate(1, false);
+    expect_abort(
+        ExitCode::USR_FORBIDDEN,
+        execute_as_owner(&rt, miner, submit_post, 0, Some(auth(1))),
+    );
+    rt.reset();
+    check_state(&rt);
+}
+
+#[test]
+fn delegates_are_bounded() {
+    let mut rt = construct_account();
+    let receiver = rt.receiver;
+    let miner = Address::new_id(1002);
+    rt.policy.account_max_delegates = 2;
+    rt.policy.account_max_delegate_allowances = 1;
+    rt.set_caller(*ACCOUNT_ACTOR_CODE_ID, receiver);
+
+    for id in [1001, 1003] {
+        rt.expect_validate_caller_addr(vec![receiver]);
+        set_delegate(&rt, Address::new_id(id), vec![allowance(miner, 5, 1)]).unwrap();
+    }
+    rt.verify();
+
+    // no further delegates may be registered
+    rt.expect_validate_caller_addr(vec![receiver]);
+    expect_abort(
+        ExitCode::USR_ILLEGAL_ARGUMENT,
+        set_delegate(&rt, Address::new_id(1004), vec![allowance(miner, 5, 1)]),
+    );
+    rt.reset();
+
+    // but existing delegates' allowances may be replaced, within the allowance limit
+    rt.expect_validate_caller_addr(vec![receiver]);
+    set_delegate(&rt, Address::new_id(1001), vec![allowance(miner, 6, 1)]).unwrap();
+    rt.expect_validate_caller_addr(vec![receiver]);
+    expect_abort(
+        ExitCode::USR_ILLEGAL_ARGUMENT,
+        set_delegate(
+            &rt,
+            Address::new_id(1001),
+            vec![allowance(miner, 5, 1), allowance(miner, 6, 1)],
+        ),
+    );
+    rt.reset();
+
+    let st: State = rt.get_state();
+    assert_eq!(2, st.delegate_count);
+    check_state(&rt);
+}
+
+#[test]
+fn legacy_state_gains_delegates_when_first_added() {
+    let rt = MockRuntime { receiver: Address::new_id(1000), ..Default::default() };
+    let receiver = rt.receiver;
+    let addr = Address::new_secp256k1(&[2; fvm_shared::address::SECP_PUB_LEN]).unwrap();
+    // state from before delegates were introduced holds only the address
+    rt.replace_state(&(addr,));
+    let old_state = rt.get_state_root().unwrap();
+    assert_eq!(old_state, rt.upgrade::<AccountActor>());
+    let st: State = rt.get_state();
+    assert_eq!(addr, st.address);
+    assert_eq!((None, 0, 0), (st.delegates, st.delegate_count, st.nonce));
+    // an unchanged account keeps the legacy encoding
+    assert_eq!(serialize(&(addr,), "legacy").unwrap(), serialize(&st, "state").unwrap());
+
+    let delegate = Address::new_id(1001);
+    rt.set_caller(*ACCOUNT_ACTOR_CODE_ID, receiver);
+    rt.expect_validate_caller_addr(vec![receiver]);
+    set_delegate(&rt, delegate, vec![allowance(Address::new_id(1002), 5, 10)]).unwrap();
+    rt.verify();
+    assert_ne!(old_state, rt.get_state_root().unwrap());
+    let st: State = rt.get_state();
+    assert_eq!(addr, st.address);
+    assert!(st.delegates.is_some());
+    assert_eq!(1, st.delegate_count);
+    assert!(st.get_delegate(rt.store(), &delegate).unwrap().is_some());
+    check_state(&rt);
+}
+
+#[test]
+fn signed_forward_call() {
+    let mut rt = construct_account();
+    rt.chain_id = ChainID::from(314);
+    let st: State = rt.get_state();
+    let owner = st.address;
+    let receiver = rt.receiver;
+    let relayer = Address::new_id(1003);
+    let to = Address::new_id(1002);
+    let call = ForwardCallParams {
+        to,
+        method: 3844450837,
+        codec: IPLD_RAW,
+        params: vec![0xde, 0xad],
+        value: TokenAmount::from_atto(5),
+    };
+    let signature = vec![1, 2, 3];
+    let signed =
+        |nonce| SignedForwardCallParams { call: call.clone(), nonce, signature: signature.clone() };
+    let expect_verify = |nonce, result: anyhow::Result<()>| {
+        let payload =
+            ForwardCallPayload { chain_id: 314, account: receiver, nonce, call: call.clone() };
+        let payload = serialize(&payload, "payload").unwrap();
+        rt.expect_verify_signature(ExpectedVerifySig {
+            sig: Signature::new_secp256k1(signature.clone()),
+            signer: owner,
+            plaintext: [SIGNATURE_DOMAIN_SEPARATION_FORWARD_CALL, payload.bytes()].concat(),
+            result,
+        });
+    };
+    let forward = |params: SignedForwardCallParams| {
+        rt.call::<AccountActor>(
+            Method::ForwardCallExported as MethodNum,
+            IpldBlock::serialize_cbor(&params).unwrap(),
+        )
+    };
+
+    // a relayer submits the owner's signed call, which is sent with raw params
+    rt.set_balance(TokenAmount::from_atto(100));
+    rt.set_caller(*ACCOUNT_ACTOR_CODE_ID, relayer);
+    rt.expect_validate_caller_any();
+    expect_verify(0, Ok(()));
+    rt.expect_send_simple(
+        to,
+        call.method,
+        Some(IpldBlock { codec: IPLD_RAW, data: call.params.clone() }),
+        call.value.clone(),
+        Some(IpldBlock { codec: IPLD_RAW, data: vec![7] }),
+        ExitCode::OK,
+    );
+    let ret: ForwardCallReturn = forward(signed(0)).unwrap().unwrap().deserialize().unwrap();
+    assert_eq!(ForwardCallReturn { codec: IPLD_RAW, data: vec![7] }, ret);
+    rt.verify();
+    let st: State = rt.get_state();
+    assert_eq!(1, st.nonce);
+
+    // replaying the same call fails
+    rt.expect_validate_caller_any();
+    expect_verify(0, Ok(()));
+    expect_abort(ExitCode::USR_ILLEGAL_ARGUMENT, forward(signed(0)));
+    rt.reset();
+
+    // an invalid signature fails
+    rt.expect_validate_caller_any();
+    expect_verify(1, Err(anyhow!("bad signature")));
+    expect_abort(ExitCode::USR_ILLEGAL_ARGUMENT, forward(signed(1)));
+    rt.reset();
+    check_state(&rt);
+}
+
+fn construct_account() -> MockRuntime {
+    let rt = MockRuntime { receiver: Address::new_id(100), ..Default::default() };
+    rt.set_caller(*SYSTEM_ACTOR_CODE_ID, SYSTEM_ACTOR_ADDR);
+    rt.expect_validate_caller_addr(vec![SYSTEM_ACTOR_ADDR]);
+    let addr = Address::new_secp256k1(&[2; fvm_shared::address::SECP_PUB_LEN]).unwrap();
+    rt.call::<AccountActor>(
+        Method::Constructor as MethodNum,
+        IpldBlock::serialize_cbor(&addr).unwrap(),
+    )
+    .unwrap();
+    rt.verify();
+    rt
+}
+
+fn allowance(to: Address, method: MethodNum, remaining: u64) -> MethodAllowance {
+    MethodAllowance { to, method, remaining: TokenAmount::from_atto(remaining) }
+}
+
+fn set_delegate(
+    rt: &MockRuntime,
+    delegate: Address,
+    allowances: Vec<MethodAllowance>,
+) -> Result<Option<IpldBlock>, ActorError> {
+    rt.call::<AccountActor>(
+        Method::SetDelegateExported as MethodNum,
+        IpldBlock::serialize_cbor(&SetDelegateParams { delegate, allowances }).unwrap(),
+    )
+}
+
+fn execute_as_owner(
+    rt: &MockRuntime,
+    to: Address,
+    method: MethodNum,
+    value: u64,
+    authorization: Option<DelegateAuthorization>,
+) -> Result<Option<IpldBlock>, ActorError> {
+    rt.call::<AccountActor>(
+        Method::ExecuteAsOwnerExported as MethodNum,
+        IpldBlock::serialize_cbor(&ExecuteAsOwnerParams {
+            to,
+            method,
+            params: RawBytes::default(),
+            value: TokenAmount::from_atto(value),
+            authorization,
+        })
+        .unwrap(),
+    )
+}
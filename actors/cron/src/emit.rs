@@ -0,0 +1,25 @@
+use fil_actors_runtime::runtime::Runtime;
+use fil_actors_runtime::{ActorError, EventBuilder};
+use fvm_shared::error::ExitCode;
+use fvm_shared::{ActorID, MethodNum};
+
+/// Indicates a cron entry's invocation failed.
+pub fn cron_entry_failed(
+    rt: &impl Runtime,
+    receiver: ActorID,
+    method: MethodNum,
+    code: ExitCode,
+    consecutive_failures: u64,
+    gas_used: u64,
+) -> Result<(), ActorError> {
+    rt.emit_event(
+        &EventBuilder::new()
+            .typ("cron-entry-failed")
+            .field_indexed("receiver", &receiver)
+            .field("method", &method)
+            .field("exit-code", &code.value())
+            .field("consecutive-failures", &consecutive_failures)
+            .field("gas-used", &gas_used)
+            .build()?,
+    )
+}
@@ -3,19 +3,26 @@
 
 use fil_actors_runtime::runtime::{ActorCode, Runtime};
 use fil_actors_runtime::{
-    actor_dispatch, actor_error, extract_send_result, ActorError, SYSTEM_ACTOR_ADDR,
+    actor_dispatch, actor_error, extract_send_result, migrate_state, ActorError, OnUpgradeParams,
+    DATACAP_TOKEN_ACTOR_ADDR, EAM_ACTOR_ADDR, STORAGE_MARKET_ACTOR_ADDR, STORAGE_POWER_ACTOR_ADDR,
+    SYSTEM_ACTOR_ADDR, VERIFIED_REGISTRY_ACTOR_ADDR,
 };
 
 use fvm_ipld_encoding::tuple::*;
+use fvm_shared::address::{Address, Protocol};
 use fvm_shared::econ::TokenAmount;
 
 use fvm_shared::METHOD_CONSTRUCTOR;
 use num_derive::FromPrimitive;
 use num_traits::Zero;
 
-pub use self::state::{Entry, State};
+pub use self::state::{Entry, EntryStatus, State};
+use crate::migration::LegacyState;
 
+mod emit;
+pub mod migration;
 mod state;
+#[cfg(feature = "testing")]
 pub mod testing;
 
 #[cfg(feature = "fil-actor")]
@@ -29,8 +36,22 @@ fil_actors_runtime::wasm_trampoline!(Actor);
 pub enum Method {
     Constructor = METHOD_CONSTRUCTOR,
     EpochTick = 2,
+    EnableEntry = 3,
+    RegisterEntry = 4,
+    RemoveEntry = 5,
 }
 
+/// Singleton actors which may register and remove cron entries.
+/// Each may only manage entries that call itself, except for the system actor.
+pub const ENTRY_REGISTRARS: &[Address] = &[
+    SYSTEM_ACTOR_ADDR,
+    STORAGE_POWER_ACTOR_ADDR,
+    STORAGE_MARKET_ACTOR_ADDR,
+    VERIFIED_REGISTRY_ACTOR_ADDR,
+    DATACAP_TOKEN_ACTOR_ADDR,
+    EAM_ACTOR_ADDR,
+];
+
 /// Constructor parameters for Cron actor, contains entries
 /// of actors and methods to call on each epoch
 #[derive(Default, Debug, Serialize_tuple, Deserialize_tuple)]
@@ -39,6 +60,20 @@ pub struct ConstructorParams {
     pub entries: Vec<Entry>,
 }
 
+/// Parameters for re-enabling a cron entry
+#[derive(Debug, Serialize_tuple, Deserialize_tuple)]
+#[serde(transparent)]
+pub struct EnableEntryParams {
+    pub entry: Entry,
+}
+
+/// Parameters for registering or removing a cron entry
+#[derive(Debug, Serialize_tuple, Deserialize_tuple)]
+#[serde(transparent)]
+pub struct EntryParams {
+    pub entry: Entry,
+}
+
 /// Cron actor
 pub struct Actor;
 
@@ -46,7 +81,7 @@ impl Actor {
     /// Constructor for Cron actor
     fn constructor(rt: &impl Runtime, params: ConstructorParams) -> Result<(), ActorError> {
         rt.validate_immediate_caller_is(std::iter::once(&SYSTEM_ACTOR_ADDR))?;
-        rt.create(&State { entries: params.entries })?;
+        rt.create(&State::new(params.entries))?;
         Ok(())
     }
     /// Executes built-in periodic actions, run at every Epoch.
@@ -56,24 +91,153 @@ impl Actor {
         rt.validate_immediate_caller_is(std::iter::once(&SYSTEM_ACTOR_ADDR))?;
 
         let st: State = rt.state()?;
-        for entry in st.entries {
+        let max_failures = rt.policy().cron_entry_max_consecutive_failures;
+        let mut entry_status = st.entry_status;
+        entry_status.resize(st.entries.len(), EntryStatus::default());
+        let mut status_changed = false;
+        for (entry, status) in st.entries.iter().zip(entry_status.iter_mut()) {
+            if status.is_disabled(max_failures) {
+                log::debug!(
+                    "cron skipping entry to {} after {} consecutive failures",
+                    entry.receiver,
+                    status.consecutive_failures
+                );
+                continue;
+            }
+
             // Intentionally ignore any error when calling cron methods
+            let gas_before = rt.gas_available();
             let res = extract_send_result(rt.send_simple(
                 &entry.receiver,
                 entry.method_num,
                 None,
                 TokenAmount::zero(),
             ));
-            if let Err(e) = res {
-                log::error!(
-                    "cron failed to send entry to {}, send error code {}",
-                    entry.receiver,
-                    e
-                );
+            let gas_used = gas_before.saturating_sub(rt.gas_available());
+            match res {
+                Ok(_) => {
+                    if status.consecutive_failures > 0 {
+                        status.consecutive_failures = 0;
+                        status_changed = true;
+                    }
+                }
+                Err(e) => {
+                    log::error!(
+                        "cron failed to send entry to {}, send error code {}",
+                        entry.receiver,
+                        e
+                    );
+                    status.consecutive_failures += 1;
+                    status.last_failure_code = e.exit_code();
+                    status_changed = true;
+                    let receiver = entry.receiver.id().map_err(|e| {
+                        actor_error!(
+                            illegal_state,
+                            "cron entry receiver {} is not an ID address: {}",
+                            entry.receiver,
+                            e
+                        )
+                    })?;
+                    emit::cron_entry_failed(
+                        rt,
+                        receiver,
+                        entry.method_num,
+                        status.last_failure_code,
+                        status.consecutive_failures,
+                        gas_used,
+                    )?;
+                }
             }
         }
+
+        if status_changed {
+            rt.transaction(|st: &mut State, _| {
+                st.entry_status = entry_status;
+                Ok(())
+            })?;
+        }
         Ok(())
     }
+
+    /// Resets the failure count of an entry, so it is invoked again if it had been skipped.
+    fn enable_entry(rt: &impl Runtime, params: EnableEntryParams) -> Result<(), ActorError> {
+        rt.validate_immediate_caller_is(std::iter::once(&SYSTEM_ACTOR_ADDR))?;
+
+        rt.transaction(|st: &mut State, _| {
+            let idx = st
+                .entries
+                .iter()
+                .position(|entry| *entry == params.entry)
+                .ok_or_else(|| actor_error!(not_found, "no cron entry {:?}", params.entry))?;
+            st.entry_status.resize(st.entries.len(), EntryStatus::default());
+            st.entry_status[idx].consecutive_failures = 0;
+            Ok(())
+        })
+    }
+
+    /// Adds an entry to be invoked at every epoch tick, after the existing entries.
+    fn register_entry(rt: &impl Runtime, params: EntryParams) -> Result<(), ActorError> {
+        rt.validate_immediate_caller_is(ENTRY_REGISTRARS)?;
+        let entry = params.entry;
+        validate_entry(rt, &entry)?;
+
+        rt.transaction(|st: &mut State, _| {
+            if st.entries.contains(&entry) {
+                return Err(actor_error!(
+                    illegal_argument,
+                    "cron entry {:?} already registered",
+                    entry
+                ));
+            }
+            st.entry_status.resize(st.entries.len(), EntryStatus::default());
+            st.entries.push(entry);
+            st.entry_status.push(EntryStatus::default());
+            Ok(())
+        })
+    }
+
+    /// Removes an entry, so it is no longer invoked.
+    fn remove_entry(rt: &impl Runtime, params: EntryParams) -> Result<(), ActorError> {
+        rt.validate_immediate_caller_is(ENTRY_REGISTRARS)?;
+        let entry = params.entry;
+        validate_entry(rt, &entry)?;
+
+        rt.transaction(|st: &mut State, _| {
+            let idx = st
+                .entries
+                .iter()
+                .position(|e| *e == entry)
+                .ok_or_else(|| actor_error!(not_found, "no cron entry {:?}", entry))?;
+            st.entry_status.resize(st.entries.len(), EntryStatus::default());
+            st.entries.remove(idx);
+            st.entry_status.remove(idx);
+            Ok(())
+        })
+    }
+}
+
+// Checks that an entry is well formed, and that the caller may manage it.
+fn validate_entry(rt: &impl Runtime, entry: &Entry) -> Result<(), ActorError> {
+    if entry.receiver.protocol() != Protocol::ID {
+        return Err(actor_error!(
+            illegal_argument,
+            "cron entry receiver {} must be an ID address",
+            entry.receiver
+        ));
+    }
+    if entry.method_num == 0 {
+        return Err(actor_error!(illegal_argument, "cron entry method number must be positive"));
+    }
+    let caller = rt.message().caller();
+    if caller != SYSTEM_ACTOR_ADDR && caller != entry.receiver {
+        return Err(actor_error!(
+            forbidden,
+            "caller {} may not manage cron entries for {}",
+            caller,
+            entry.receiver
+        ));
+    }
+    Ok(())
 }
 
 impl ActorCode for Actor {
@@ -83,8 +247,19 @@ impl ActorCode for Actor {
         "Cron"
     }
 
+    fn on_upgrade<RT>(rt: &RT, params: OnUpgradeParams) -> Result<(), ActorError>
+    where
+        RT: Runtime,
+        RT::Blockstore: Clone,
+    {
+        migrate_state(rt, &params.old_state, |_, legacy: LegacyState| Ok(State::from(legacy)))
+    }
+
     actor_dispatch! {
         Constructor => constructor,
         EpochTick => epoch_tick,
+        EnableEntry => enable_entry,
+        RegisterEntry => register_entry,
+        RemoveEntry => remove_entry,
     }
 }
@@ -0,0 +1,19 @@
+// Copyright 2019-2022 ChainSafe Systems
+// SPDX-License-Identifier: Apache-2.0, MIT
+
+use fvm_ipld_encoding::tuple::*;
+
+use crate::{Entry, State};
+
+/// Cron state as stored before entry invocations were accounted.
+#[derive(Serialize_tuple, Deserialize_tuple, Clone, Debug)]
+pub struct LegacyState {
+    pub entries: Vec<Entry>,
+}
+
+impl From<LegacyState> for State {
+    /// Migrates to the current state schema, with no failures recorded for any entry.
+    fn from(legacy: LegacyState) -> Self {
+        State::new(legacy.entries)
+    }
+}
@@ -3,6 +3,7 @@
 
 use fvm_ipld_encoding::tuple::*;
 use fvm_shared::address::Address;
+use fvm_shared::error::ExitCode;
 use fvm_shared::MethodNum;
 
 /// Cron actor state which holds entries to call during epoch tick
@@ -10,6 +11,15 @@ use fvm_shared::MethodNum;
 pub struct State {
     /// Entries is a set of actors (and corresponding methods) to call during EpochTick.
     pub entries: Vec<Entry>,
+    /// Invocation accounting for each entry, in the same order as the entries.
+    pub entry_status: Vec<EntryStatus>,
+}
+
+impl State {
+    pub fn new(entries: Vec<Entry>) -> Self {
+        let entry_status = vec![EntryStatus::default(); entries.len()];
+        Self { entries, entry_status }
+    }
 }
 
 #[derive(Clone, PartialEq, Eq, Debug, Serialize_tuple, Deserialize_tuple)]
@@ -19,3 +29,24 @@ pub struct Entry {
     /// The method number to call (must accept empty parameters)
     pub method_num: MethodNum,
 }
+
+#[derive(Clone, PartialEq, Eq, Debug, Serialize_tuple, Deserialize_tuple)]
+pub struct EntryStatus {
+    /// Number of invocations of the entry that have failed since it last succeeded.
+    /// An entry is skipped once this reaches the policy's limit.
+    pub consecutive_failures: u64,
+    /// Exit code of the entry's most recent failed invocation, or OK if it has never failed.
+    pub last_failure_code: ExitCode,
+}
+
+impl Default for EntryStatus {
+    fn default() -> Self {
+        Self { consecutive_failures: 0, last_failure_code: ExitCode::OK }
+    }
+}
+
+impl EntryStatus {
+    pub fn is_disabled(&self, max_consecutive_failures: u64) -> bool {
+        self.consecutive_failures >= max_consecutive_failures
+    }
+}
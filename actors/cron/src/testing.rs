@@ -21,5 +21,16 @@ pub fn check_state_invariants(state: &State) -> (StateSummary, MessageAccumulato
         );
     });
 
+    acc.require(
+        state.entry_status.len() == state.entries.len(),
+        format!("{} entry statuses for {} entries", state.entry_status.len(), state.entries.len()),
+    );
+    state.entry_status.iter().enumerate().for_each(|(i, status)| {
+        acc.require(
+            (status.consecutive_failures == 0) || !status.last_failure_code.is_success(),
+            format!("entry {i} has failures but no failure code"),
+        );
+    });
+
     (StateSummary { entry_count: state.entries.len() }, acc)
 }
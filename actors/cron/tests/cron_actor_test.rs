@@ -3,10 +3,17 @@
 
 use std::cell::RefCell;
 
+use fil_actor_cron::migration::LegacyState;
 use fil_actor_cron::testing::check_state_invariants;
-use fil_actor_cron::{Actor as CronActor, ConstructorParams, Entry, State};
+use fil_actor_cron::{
+    Actor as CronActor, ConstructorParams, EnableEntryParams, Entry, EntryParams, EntryStatus,
+    Method, State, ENTRY_REGISTRARS,
+};
 use fil_actors_runtime::test_utils::*;
-use fil_actors_runtime::SYSTEM_ACTOR_ADDR;
+use fil_actors_runtime::{
+    ActorError, EventBuilder, STORAGE_MARKET_ACTOR_ADDR, STORAGE_POWER_ACTOR_ADDR,
+    SYSTEM_ACTOR_ADDR,
+};
 use fvm_ipld_encoding::ipld_block::IpldBlock;
 use fvm_shared::address::Address;
 use fvm_shared::econ::TokenAmount;
@@ -81,48 +88,209 @@ fn epoch_tick_with_entries() {
     construct_and_verify(&rt, &params);
 
     // ExitCodes dont matter here
-    rt.expect_send_simple(
-        entry1.receiver,
-        entry1.method_num,
-        None,
-        TokenAmount::zero(),
-        None,
-        ExitCode::OK,
+    expect_entry_send(&rt, &entry1, ExitCode::OK);
+    expect_entry_send(&rt, &entry2, ExitCode::USR_ILLEGAL_ARGUMENT);
+    expect_entry_failed_event(&rt, &entry2, ExitCode::USR_ILLEGAL_ARGUMENT, 1);
+    expect_entry_send(&rt, &entry3, ExitCode::OK);
+    expect_entry_send(&rt, &entry4, ExitCode::OK);
+
+    epoch_tick_and_verify(&rt);
+}
+
+#[test]
+fn failing_entry_skipped_until_enabled() {
+    let mut rt = construct_runtime();
+    rt.policy.cron_entry_max_consecutive_failures = 2;
+
+    let entry1 = Entry { receiver: Address::new_id(1001), method_num: 1001 };
+    let entry2 = Entry { receiver: Address::new_id(1002), method_num: 1002 };
+    let params = ConstructorParams { entries: vec![entry1.clone(), entry2.clone()] };
+    construct_and_verify(&rt, &params);
+
+    // The failing entry is isolated from the others, and its failures are counted.
+    for failures in 1..=2 {
+        expect_entry_send(&rt, &entry1, ExitCode::USR_ASSERTION_FAILED);
+        expect_entry_failed_event(&rt, &entry1, ExitCode::USR_ASSERTION_FAILED, failures);
+        expect_entry_send(&rt, &entry2, ExitCode::OK);
+        epoch_tick_and_verify(&rt);
+    }
+    let state: State = rt.get_state();
+    assert_eq!(
+        EntryStatus { consecutive_failures: 2, last_failure_code: ExitCode::USR_ASSERTION_FAILED },
+        state.entry_status[0]
     );
-    rt.expect_send_simple(
-        entry2.receiver,
-        entry2.method_num,
-        None,
-        TokenAmount::zero(),
-        None,
+    assert_eq!(EntryStatus::default(), state.entry_status[1]);
+
+    // Once the limit is reached, the entry is skipped.
+    expect_entry_send(&rt, &entry2, ExitCode::OK);
+    epoch_tick_and_verify(&rt);
+
+    // Only the system actor can re-enable it.
+    rt.set_caller(*ACCOUNT_ACTOR_CODE_ID, Address::new_id(1234));
+    rt.expect_validate_caller_addr(vec![SYSTEM_ACTOR_ADDR]);
+    expect_abort(
+        ExitCode::USR_FORBIDDEN,
+        rt.call::<CronActor>(
+            Method::EnableEntry as u64,
+            IpldBlock::serialize_cbor(&EnableEntryParams { entry: entry1.clone() }).unwrap(),
+        ),
+    );
+    rt.reset();
+    rt.set_caller(*SYSTEM_ACTOR_CODE_ID, SYSTEM_ACTOR_ADDR);
+
+    let unknown = Entry { receiver: Address::new_id(1003), method_num: 1003 };
+    expect_abort(ExitCode::USR_NOT_FOUND, enable_entry(&rt, unknown));
+    rt.reset();
+    enable_entry(&rt, entry1.clone()).unwrap();
+    rt.verify();
+
+    // A success after re-enabling clears the failure count but keeps the last code.
+    expect_entry_send(&rt, &entry1, ExitCode::OK);
+    expect_entry_send(&rt, &entry2, ExitCode::OK);
+    epoch_tick_and_verify(&rt);
+    let state: State = rt.get_state();
+    assert_eq!(
+        EntryStatus { consecutive_failures: 0, last_failure_code: ExitCode::USR_ASSERTION_FAILED },
+        state.entry_status[0]
+    );
+}
+
+#[test]
+fn register_and_remove_entries() {
+    let rt = construct_runtime();
+    let entry1 = Entry { receiver: Address::new_id(1001), method_num: 1001 };
+    construct_and_verify(&rt, &ConstructorParams { entries: vec![entry1.clone()] });
+
+    // A singleton registers an entry calling itself, which is invoked after existing entries.
+    let power_entry = Entry { receiver: STORAGE_POWER_ACTOR_ADDR, method_num: 7 };
+    rt.set_caller(*POWER_ACTOR_CODE_ID, STORAGE_POWER_ACTOR_ADDR);
+    manage_entry(&rt, Method::RegisterEntry, power_entry.clone()).unwrap();
+    let state: State = rt.get_state();
+    assert_eq!(vec![entry1.clone(), power_entry.clone()], state.entries);
+    check_state(&rt);
+
+    rt.set_caller(*SYSTEM_ACTOR_CODE_ID, SYSTEM_ACTOR_ADDR);
+    expect_entry_send(&rt, &entry1, ExitCode::OK);
+    expect_entry_send(&rt, &power_entry, ExitCode::OK);
+    epoch_tick_and_verify(&rt);
+
+    // The same entry can't be registered twice.
+    rt.set_caller(*POWER_ACTOR_CODE_ID, STORAGE_POWER_ACTOR_ADDR);
+    expect_abort_contains_message(
         ExitCode::USR_ILLEGAL_ARGUMENT,
+        "already registered",
+        manage_entry(&rt, Method::RegisterEntry, power_entry.clone()),
     );
-    rt.expect_send_simple(
-        entry3.receiver,
-        entry3.method_num,
-        None,
-        TokenAmount::zero(),
-        None,
-        ExitCode::OK,
+    rt.reset();
+
+    // A singleton can only manage entries calling itself.
+    rt.set_caller(*MARKET_ACTOR_CODE_ID, STORAGE_MARKET_ACTOR_ADDR);
+    expect_abort(
+        ExitCode::USR_FORBIDDEN,
+        manage_entry(&rt, Method::RemoveEntry, power_entry.clone()),
     );
+    rt.reset();
+
+    // Other actors can't manage entries at all.
+    rt.set_caller(*ACCOUNT_ACTOR_CODE_ID, Address::new_id(1001));
+    expect_abort(ExitCode::USR_FORBIDDEN, manage_entry(&rt, Method::RegisterEntry, entry1.clone()));
+    rt.reset();
+
+    // The system actor can manage any entry.
+    rt.set_caller(*SYSTEM_ACTOR_CODE_ID, SYSTEM_ACTOR_ADDR);
+    manage_entry(&rt, Method::RemoveEntry, entry1.clone()).unwrap();
+    expect_abort(ExitCode::USR_NOT_FOUND, manage_entry(&rt, Method::RemoveEntry, entry1));
+    rt.reset();
+    let state: State = rt.get_state();
+    assert_eq!(vec![power_entry.clone()], state.entries);
+    check_state(&rt);
+
+    rt.set_caller(*POWER_ACTOR_CODE_ID, STORAGE_POWER_ACTOR_ADDR);
+    manage_entry(&rt, Method::RemoveEntry, power_entry).unwrap();
+    rt.set_caller(*SYSTEM_ACTOR_CODE_ID, SYSTEM_ACTOR_ADDR);
+    epoch_tick_and_verify(&rt);
+}
+
+#[test]
+fn upgrade_migrates_legacy_state() {
+    let rt = construct_runtime();
+    let entry1 = Entry { receiver: Address::new_id(1001), method_num: 1001 };
+    let entry2 = Entry { receiver: Address::new_id(1002), method_num: 1002 };
+    rt.replace_state(&LegacyState { entries: vec![entry1.clone(), entry2.clone()] });
+    rt.upgrade::<CronActor>();
+    let state: State = rt.get_state();
+    assert_eq!(vec![entry1.clone(), entry2.clone()], state.entries);
+    assert_eq!(vec![EntryStatus::default(); 2], state.entry_status);
+    check_state(&rt);
+
+    // The migrated entries are invoked on the next tick.
+    expect_entry_send(&rt, &entry1, ExitCode::OK);
+    expect_entry_send(&rt, &entry2, ExitCode::OK);
+    epoch_tick_and_verify(&rt);
+    check_state(&rt);
+}
+
+fn construct_and_verify(rt: &MockRuntime, params: &ConstructorParams) {
+    rt.set_caller(*SYSTEM_ACTOR_CODE_ID, SYSTEM_ACTOR_ADDR);
+    rt.expect_validate_caller_addr(vec![SYSTEM_ACTOR_ADDR]);
+    let ret = rt.call::<CronActor>(1, IpldBlock::serialize_cbor(&params).unwrap()).unwrap();
+    assert!(ret.is_none());
+    rt.verify();
+}
+
+const GAS_USED: u64 = 10;
+
+fn expect_entry_send(rt: &MockRuntime, entry: &Entry, exit_code: ExitCode) {
+    rt.expect_gas_available(1000);
     rt.expect_send_simple(
-        entry4.receiver,
-        entry4.method_num,
+        entry.receiver,
+        entry.method_num,
         None,
         TokenAmount::zero(),
         None,
-        ExitCode::OK,
+        exit_code,
     );
+    rt.expect_gas_available(1000 - GAS_USED);
+}
 
-    epoch_tick_and_verify(&rt);
+fn expect_entry_failed_event(
+    rt: &MockRuntime,
+    entry: &Entry,
+    exit_code: ExitCode,
+    consecutive_failures: u64,
+) {
+    rt.expect_emitted_event(
+        EventBuilder::new()
+            .typ("cron-entry-failed")
+            .field_indexed("receiver", &entry.receiver.id().unwrap())
+            .field("method", &entry.method_num)
+            .field("exit-code", &exit_code.value())
+            .field("consecutive-failures", &consecutive_failures)
+            .field("gas-used", &GAS_USED)
+            .build()
+            .unwrap(),
+    );
 }
 
-fn construct_and_verify(rt: &MockRuntime, params: &ConstructorParams) {
-    rt.set_caller(*SYSTEM_ACTOR_CODE_ID, SYSTEM_ACTOR_ADDR);
+fn enable_entry(rt: &MockRuntime, entry: Entry) -> Result<(), ActorError> {
     rt.expect_validate_caller_addr(vec![SYSTEM_ACTOR_ADDR]);
-    let ret = rt.call::<CronActor>(1, IpldBlock::serialize_cbor(&params).unwrap()).unwrap();
+    let ret = rt.call::<CronActor>(
+        Method::EnableEntry as u64,
+        IpldBlock::serialize_cbor(&EnableEntryParams { entry }).unwrap(),
+    )?;
+    assert!(ret.is_none());
+    Ok(())
+}
+
+fn manage_entry(rt: &MockRuntime, method: Method, entry: Entry) -> Result<(), ActorError> {
+    rt.expect_validate_caller_addr(ENTRY_REGISTRARS.to_vec());
+    let ret = rt.call::<CronActor>(
+        method as u64,
+        IpldBlock::serialize_cbor(&EntryParams { entry }).unwrap(),
+    )?;
     assert!(ret.is_none());
     rt.verify();
+    Ok(())
 }
 
 fn epoch_tick_and_verify(rt: &MockRuntime) {
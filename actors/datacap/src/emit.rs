@@ -0,0 +1,9 @@
+// A namespace for helpers that build and emit datacap token events.
+
+use fil_actors_runtime::runtime::Runtime;
+use fil_actors_runtime::{ActorError, EventBuilder};
+
+/// Indicates the governor has suspended or resumed minting and transfers.
+pub fn paused(rt: &impl Runtime, paused: bool) -> Result<(), ActorError> {
+    rt.emit_event(&EventBuilder::new().typ("paused").field("paused", &paused).build()?)
+}
@@ -18,21 +18,26 @@ use fvm_shared::{ActorID, MethodNum, METHOD_CONSTRUCTOR};
 use lazy_static::lazy_static;
 use log::info;
 use num_derive::FromPrimitive;
+use num_traits::Zero;
 
 use fil_actors_runtime::runtime::{ActorCode, Runtime};
 use fil_actors_runtime::{
-    actor_dispatch, actor_error, extract_send_result, ActorContext, ActorError, AsActorError,
-    SYSTEM_ACTOR_ADDR,
+    actor_dispatch, actor_error, extract_send_result, migrate_state, ActorContext, ActorError,
+    AsActorError, BatchReturn, OnUpgradeParams, SYSTEM_ACTOR_ADDR,
 };
 use fvm_ipld_encoding::ipld_block::IpldBlock;
 
 pub use self::state::State;
 pub use self::types::*;
+use crate::migration::LegacyState;
 
 #[cfg(feature = "fil-actor")]
 fil_actors_runtime::wasm_trampoline!(Actor);
 
+mod emit;
+pub mod migration;
 mod state;
+#[cfg(feature = "testing")]
 pub mod testing;
 mod types;
 
@@ -82,6 +87,12 @@ pub enum Method {
     BurnExported = frc42_dispatch::method_hash!("Burn"),
     BurnFromExported = frc42_dispatch::method_hash!("BurnFrom"),
     AllowanceExported = frc42_dispatch::method_hash!("Allowance"),
+    SetPausedExported = frc42_dispatch::method_hash!("SetPaused"),
+    IsPausedExported = frc42_dispatch::method_hash!("IsPaused"),
+    TransferBatchExported = frc42_dispatch::method_hash!("TransferBatch"),
+    SetAllowanceExpirationExported = frc42_dispatch::method_hash!("SetAllowanceExpiration"),
+    SetTransferRulesExported = frc42_dispatch::method_hash!("SetTransferRules"),
+    GetTransferRulesExported = frc42_dispatch::method_hash!("GetTransferRules"),
 }
 
 pub struct Actor;
@@ -135,12 +146,17 @@ impl Actor {
         token.balance_of(&params.address).map(|balance| BalanceReturn { balance }).actor_result()
     }
 
+    /// Returns an operator's allowance for an owner.
+    /// An allowance that has expired is zero, even before it is revoked.
     pub fn allowance(
         rt: &impl Runtime,
         params: GetAllowanceParams,
     ) -> Result<GetAllowanceReturn, ActorError> {
         rt.validate_immediate_caller_accept_any()?;
         let mut st: State = rt.state()?;
+        if allowance_expired(rt, &st, &params.owner, &params.operator)? {
+            return Ok(GetAllowanceReturn { allowance: TokenAmount::zero() });
+        }
         let syscalls = SyscallProvider { rt };
         let runtime = ActorRuntime::new(&syscalls, syscalls.rt.store());
         let token = as_token(&mut st, &runtime);
@@ -159,7 +175,11 @@ impl Actor {
             .transaction(|st: &mut State, rt| {
                 // Only the governor can mint datacap tokens.
                 rt.validate_immediate_caller_is(std::iter::once(&st.governor))?;
+                st.check_not_paused()?;
                 let operator = st.governor;
+                for delegate in &params.operators {
+                    clear_expired_allowance(rt, st, &params.to, delegate)?;
+                }
 
                 let syscalls = SyscallProvider { rt };
                 let runtime = ActorRuntime::new(&syscalls, syscalls.rt.store());
@@ -212,60 +232,146 @@ impl Actor {
         .context("state transaction failed")
     }
 
+    /// Suspends or resumes minting and transfers of data cap tokens.
+    /// Only the governor can call this method.
+    /// This method is not part of the fungible token standard.
+    pub fn set_paused(rt: &impl Runtime, params: SetPausedParams) -> Result<(), ActorError> {
+        rt.transaction(|st: &mut State, rt| {
+            rt.validate_immediate_caller_is(std::iter::once(&st.governor))?;
+            st.paused = params.paused;
+            Ok(())
+        })?;
+        emit::paused(rt, params.paused)
+    }
+
+    /// Returns whether minting and transfers of data cap tokens are suspended.
+    pub fn is_paused(rt: &impl Runtime) -> Result<IsPausedReturn, ActorError> {
+        rt.validate_immediate_caller_accept_any()?;
+        let st: State = rt.state()?;
+        Ok(IsPausedReturn { paused: st.paused })
+    }
+
+    /// Sets or clears the epoch at which an operator's allowance for the caller expires.
+    /// From that epoch the allowance is treated as zero, and it is revoked when next used.
+    /// An allowance that has already expired is revoked before the new expiration is set.
+    /// This method is not part of the fungible token standard.
+    pub fn set_allowance_expiration(
+        rt: &impl Runtime,
+        params: SetAllowanceExpirationParams,
+    ) -> Result<(), ActorError> {
+        rt.validate_immediate_caller_accept_any()?;
+        let owner = rt.message().caller();
+        let operator = rt
+            .resolve_address(&params.operator)
+            .context_code(ExitCode::USR_ILLEGAL_ARGUMENT, "operator must be ID address")?;
+        let curr_epoch = rt.curr_epoch();
+        if let Some(expiration) = params.expiration {
+            if expiration <= curr_epoch {
+                return Err(actor_error!(
+                    illegal_argument,
+                    "allowance expiration {} must be after current epoch {}",
+                    expiration,
+                    curr_epoch
+                ));
+            }
+        }
+
+        rt.transaction(|st: &mut State, rt| {
+            clear_expired_allowance(rt, st, &owner, &params.operator)?;
+            st.set_allowance_expiration(
+                rt.store(),
+                owner.id().unwrap(),
+                operator,
+                params.expiration,
+            )
+        })
+        .context("state transaction failed")
+    }
+
+    /// Replaces the rules describing which transfers of data cap tokens are allowed.
+    /// Addresses named by rules must be resolvable, and are stored as ID addresses.
+    /// Only the governor can call this method.
+    /// This method is not part of the fungible token standard.
+    pub fn set_transfer_rules(
+        rt: &impl Runtime,
+        params: SetTransferRulesParams,
+    ) -> Result<(), ActorError> {
+        let st: State = rt.state()?;
+        rt.validate_immediate_caller_is(std::iter::once(&st.governor))?;
+
+        let resolve = |addr: Option<Address>| -> Result<Option<Address>, ActorError> {
+            addr.map(|addr| {
+                rt.resolve_address(&addr).map(Address::new_id).ok_or_else(|| {
+                    actor_error!(
+                        illegal_argument,
+                        "failed to resolve transfer rule address {}",
+                        addr
+                    )
+                })
+            })
+            .transpose()
+        };
+        let rules = params
+            .rules
+            .into_iter()
+            .map(|rule| {
+                Ok(TransferRule {
+                    from: resolve(rule.from)?,
+                    to: resolve(rule.to)?,
+                    operator: resolve(rule.operator)?,
+                })
+            })
+            .collect::<Result<Vec<_>, ActorError>>()?;
+
+        rt.transaction(|st: &mut State, _| {
+            st.transfer_rules = rules;
+            Ok(())
+        })
+    }
+
+    /// Returns the rules describing which transfers of data cap tokens are allowed.
+    pub fn get_transfer_rules(rt: &impl Runtime) -> Result<GetTransferRulesReturn, ActorError> {
+        rt.validate_immediate_caller_accept_any()?;
+        let st: State = rt.state()?;
+        Ok(GetTransferRulesReturn { rules: st.transfer_rules })
+    }
+
     /// Transfers data cap tokens to an address.
     /// Data cap tokens are not generally transferable.
-    /// Succeeds if the to or from address is the governor, otherwise always fails.
+    /// Succeeds only if the transfer matches one of the transfer rules set by the governor,
+    /// which by default allow transfers to or from the governor.
     pub fn transfer(
         rt: &impl Runtime,
         params: TransferParams,
     ) -> Result<TransferReturn, ActorError> {
         rt.validate_immediate_caller_accept_any()?;
-        let operator = &rt.message().caller();
-        let from = operator;
-        // Resolve to address for comparison with governor address.
-        let to = rt
-            .resolve_address(&params.to)
-            .context_code(ExitCode::USR_ILLEGAL_ARGUMENT, "to must be ID address")?;
-        let to_address = Address::new_id(to);
-
-        let mut hook = rt
-            .transaction(|st: &mut State, rt| {
-                let allowed = to_address == st.governor || *from == st.governor;
-                if !allowed {
-                    return Err(actor_error!(
-                        forbidden,
-                        "transfer not allowed from {} to {} (governor is {})",
-                        from,
-                        to_address,
-                        st.governor
-                    ));
-                }
-
-                let syscalls = SyscallProvider { rt };
-                let runtime = ActorRuntime::new(&syscalls, syscalls.rt.store());
-                let mut token = as_token(st, &runtime);
-                token
-                    .transfer(
-                        from,
-                        &to_address,
-                        &params.amount,
-                        params.operator_data.clone(),
-                        RawBytes::default(),
-                    )
-                    .actor_result()
-            })
-            .context("state transaction failed")?;
+        transfer_one(rt, params)
+    }
 
-        let mut st: State = rt.state()?;
-        let syscalls = SyscallProvider { rt };
-        let intermediate = hook.call(&as_actor_runtime(&syscalls)).actor_result()?;
-        let runtime = ActorRuntime::new(&syscalls, syscalls.rt.store());
-        as_token(&mut st, &runtime).transfer_return(intermediate).actor_result()
+    /// Transfers data cap tokens from the caller to each of a number of addresses, in order.
+    /// Each transfer is subject to the same restrictions as a single transfer.
+    /// The batch is atomic: if any transfer fails, including by its recipient rejecting it,
+    /// no transfer is made.
+    /// Returns the result of each transfer, including its recipient's receiver hook data.
+    pub fn transfer_batch(
+        rt: &impl Runtime,
+        params: TransferBatchParams,
+    ) -> Result<TransferBatchReturn, ActorError> {
+        rt.validate_immediate_caller_accept_any()?;
+        let mut results = Vec::with_capacity(params.transfers.len());
+        for (i, transfer) in params.transfers.into_iter().enumerate() {
+            results.push(
+                transfer_one(rt, transfer).with_context(|| format!("transfer {} failed", i))?,
+            );
+        }
+        // Partial success isn't supported, but the batch result makes space for it in the future.
+        Ok(TransferBatchReturn { batch_info: BatchReturn::ok(results.len() as u32), results })
     }
 
     /// Transfers data cap tokens between addresses.
     /// Data cap tokens are not generally transferable between addresses.
-    /// Succeeds if the to address is the governor, otherwise always fails.
+    /// Succeeds only if the transfer, including its operator, matches one of the transfer rules
+    /// set by the governor, which by default allow transfers to the governor.
     pub fn transfer_from(
         rt: &impl Runtime,
         params: TransferFromParams,
@@ -273,24 +379,20 @@ impl Actor {
         rt.validate_immediate_caller_accept_any()?;
         let operator = rt.message().caller();
         let from = params.from;
-        // Resolve to address for comparison with governor.
+        // Resolve to address for matching against transfer rules.
         let to = rt
             .resolve_address(&params.to)
             .context_code(ExitCode::USR_ILLEGAL_ARGUMENT, "to must be an ID address")?;
         let to_address = Address::new_id(to);
+        // The holder is resolved, if possible, only for matching against transfer rules.
+        let from_address = rt.resolve_address(&from).map(Address::new_id).unwrap_or(from);
 
         let mut hook = rt
             .transaction(|st: &mut State, rt| {
-                let allowed = to_address == st.governor;
-                if !allowed {
-                    return Err(actor_error!(
-                        forbidden,
-                        "transfer not allowed from {} to {} (governor is {})",
-                        from,
-                        to_address,
-                        st.governor
-                    ));
-                }
+                st.check_not_paused()?;
+                st.check_transfer_allowed(&from_address, &to_address, &operator)?;
+
+                clear_expired_allowance(rt, st, &from, &operator)?;
 
                 let syscalls = SyscallProvider { rt };
                 let runtime = ActorRuntime::new(&syscalls, syscalls.rt.store());
@@ -324,6 +426,8 @@ impl Actor {
         let operator = params.operator;
 
         rt.transaction(|st: &mut State, rt| {
+            clear_expired_allowance(rt, st, &owner, &operator)?;
+
             let syscalls = SyscallProvider { rt };
             let runtime = ActorRuntime::new(&syscalls, syscalls.rt.store());
             let mut token = as_token(st, &runtime);
@@ -344,6 +448,8 @@ impl Actor {
         let operator = &params.operator;
 
         rt.transaction(|st: &mut State, rt| {
+            clear_expired_allowance(rt, st, owner, operator)?;
+
             let syscalls = SyscallProvider { rt };
             let runtime = ActorRuntime::new(&syscalls, syscalls.rt.store());
             let mut token = as_token(st, &runtime);
@@ -364,6 +470,13 @@ impl Actor {
         let operator = &params.operator;
 
         rt.transaction(|st: &mut State, rt| {
+            clear_expired_allowance(rt, st, owner, operator)?;
+            if let (Some(owner), Some(operator)) =
+                (rt.resolve_address(owner), rt.resolve_address(operator))
+            {
+                st.set_allowance_expiration(rt.store(), owner, operator, None)?;
+            }
+
             let syscalls = SyscallProvider { rt };
             let runtime = ActorRuntime::new(&syscalls, syscalls.rt.store());
             let mut token = as_token(st, &runtime);
@@ -397,6 +510,8 @@ impl Actor {
         let owner = &params.owner;
 
         rt.transaction(|st: &mut State, rt| {
+            clear_expired_allowance(rt, st, owner, operator)?;
+
             let syscalls = SyscallProvider { rt };
             let runtime = ActorRuntime::new(&syscalls, syscalls.rt.store());
             let mut token = as_token(st, &runtime);
@@ -460,6 +575,80 @@ where
     }
 }
 
+// Transfers tokens from the caller, on the restrictions of Transfer.
+fn transfer_one(rt: &impl Runtime, params: TransferParams) -> Result<TransferReturn, ActorError> {
+    let operator = &rt.message().caller();
+    let from = operator;
+    // Resolve to address for matching against transfer rules.
+    let to = rt
+        .resolve_address(&params.to)
+        .context_code(ExitCode::USR_ILLEGAL_ARGUMENT, "to must be ID address")?;
+    let to_address = Address::new_id(to);
+
+    let mut hook = rt
+        .transaction(|st: &mut State, rt| {
+            st.check_not_paused()?;
+            st.check_transfer_allowed(from, &to_address, operator)?;
+
+            let syscalls = SyscallProvider { rt };
+            let runtime = ActorRuntime::new(&syscalls, syscalls.rt.store());
+            let mut token = as_token(st, &runtime);
+            token
+                .transfer(
+                    from,
+                    &to_address,
+                    &params.amount,
+                    params.operator_data.clone(),
+                    RawBytes::default(),
+                )
+                .actor_result()
+        })
+        .context("state transaction failed")?;
+
+    let mut st: State = rt.state()?;
+    let syscalls = SyscallProvider { rt };
+    let intermediate = hook.call(&as_actor_runtime(&syscalls)).actor_result()?;
+    let runtime = ActorRuntime::new(&syscalls, syscalls.rt.store());
+    as_token(&mut st, &runtime).transfer_return(intermediate).actor_result()
+}
+
+// Returns whether an operator's allowance for an owner has expired.
+fn allowance_expired(
+    rt: &impl Runtime,
+    st: &State,
+    owner: &Address,
+    operator: &Address,
+) -> Result<bool, ActorError> {
+    // Expirations are recorded only for resolvable addresses.
+    let (owner, operator) = match (rt.resolve_address(owner), rt.resolve_address(operator)) {
+        (Some(owner), Some(operator)) => (owner, operator),
+        _ => return Ok(false),
+    };
+    let expiration = st.allowance_expiration(rt.store(), owner, operator)?;
+    Ok(matches!(expiration, Some(epoch) if epoch <= rt.curr_epoch()))
+}
+
+// Revokes an operator's allowance for an owner if it has expired, along with its expiration.
+fn clear_expired_allowance(
+    rt: &impl Runtime,
+    st: &mut State,
+    owner: &Address,
+    operator: &Address,
+) -> Result<(), ActorError> {
+    if !allowance_expired(rt, st, owner, operator)? {
+        return Ok(());
+    }
+    // Both addresses resolved in order to find the expiration.
+    let owner_id = rt.resolve_address(owner).unwrap();
+    let operator_id = rt.resolve_address(operator).unwrap();
+    st.set_allowance_expiration(rt.store(), owner_id, operator_id, None)?;
+
+    let syscalls = SyscallProvider { rt };
+    let runtime = ActorRuntime::new(&syscalls, syscalls.rt.store());
+    as_token(st, &runtime).revoke_allowance(owner, operator).actor_result()?;
+    Ok(())
+}
+
 // Returns a token instance wrapping the token state.
 fn as_token<'st, RT>(
     st: &'st mut State,
@@ -504,6 +693,14 @@ impl ActorCode for Actor {
         "DataCap"
     }
 
+    fn on_upgrade<RT>(rt: &RT, params: OnUpgradeParams) -> Result<(), ActorError>
+    where
+        RT: Runtime,
+        RT::Blockstore: Clone,
+    {
+        migrate_state(rt, &params.old_state, |rt, legacy: LegacyState| legacy.migrate(rt.store()))
+    }
+
     actor_dispatch! {
         Constructor => constructor,
         MintExported => mint,
@@ -521,5 +718,11 @@ impl ActorCode for Actor {
         BurnExported => burn,
         BurnFromExported => burn_from,
         AllowanceExported => allowance,
+        SetPausedExported => set_paused,
+        IsPausedExported => is_paused,
+        TransferBatchExported => transfer_batch,
+        SetAllowanceExpirationExported => set_allowance_expiration,
+        SetTransferRulesExported => set_transfer_rules,
+        GetTransferRulesExported => get_transfer_rules,
     }
 }
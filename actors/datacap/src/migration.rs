@@ -0,0 +1,37 @@
+use frc46_token::token;
+use fvm_ipld_blockstore::Blockstore;
+use fvm_ipld_encoding::tuple::*;
+use fvm_shared::address::Address;
+use fvm_shared::clock::ChainEpoch;
+use fvm_shared::error::ExitCode;
+use fvm_shared::{ActorID, HAMT_BIT_WIDTH};
+
+use fil_actors_runtime::{ActorError, AsActorError, MapMap};
+
+use crate::{default_transfer_rules, State};
+
+/// DataCap actor state as stored before the governor could pause the token.
+#[derive(Serialize_tuple, Deserialize_tuple)]
+pub struct LegacyState {
+    pub governor: Address,
+    pub token: token::state::TokenState,
+}
+
+impl LegacyState {
+    /// Migrates to the current state schema, with the token unpaused and no allowance set to
+    /// expire. The default transfer rules permit exactly the transfers allowed before the rules
+    /// were configurable.
+    pub fn migrate<BS: Blockstore>(self, store: &BS) -> Result<State, ActorError> {
+        let empty_mapmap =
+            MapMap::<_, ChainEpoch, ActorID, ActorID>::new(store, HAMT_BIT_WIDTH, HAMT_BIT_WIDTH)
+                .flush()
+                .context_code(ExitCode::USR_ILLEGAL_STATE, "failed to create empty multi map")?;
+        Ok(State {
+            governor: self.governor,
+            token: self.token,
+            paused: false,
+            allowance_expirations: empty_mapmap,
+            transfer_rules: default_transfer_rules(self.governor),
+        })
+    }
+}
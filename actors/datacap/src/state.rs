@@ -1,24 +1,72 @@
+use cid::Cid;
 use frc46_token::token;
 use fvm_ipld_blockstore::Blockstore;
 use fvm_ipld_encoding::tuple::*;
 use fvm_shared::address::Address;
+use fvm_shared::clock::ChainEpoch;
 use fvm_shared::econ::TokenAmount;
 use fvm_shared::error::ExitCode;
-use fvm_shared::ActorID;
+use fvm_shared::{ActorID, HAMT_BIT_WIDTH};
 
-use fil_actors_runtime::{ActorError, AsActorError};
+use fil_actors_runtime::{actor_error, ActorError, ActorState, AsActorError, MapMap};
 
-#[derive(Serialize_tuple, Deserialize_tuple)]
+use crate::{default_transfer_rules, TransferRule};
+
+#[derive(Serialize_tuple, Deserialize_tuple, ActorState)]
 pub struct State {
     pub governor: Address,
     pub token: token::state::TokenState,
+    // Whether minting and transfers are suspended by the governor.
+    pub paused: bool,
+    // Epochs at which time-limited operator allowances expire, keyed by owner then operator.
+    pub allowance_expirations: Cid, // MapMap<ActorID, ActorID, ChainEpoch>
+    // Patterns of the transfers allowed, set by the governor.
+    pub transfer_rules: Vec<TransferRule>,
 }
 
 impl State {
     pub fn new<BS: Blockstore>(store: &BS, governor: Address) -> Result<State, ActorError> {
         let token_state = token::state::TokenState::new(store)
             .context_code(ExitCode::USR_ILLEGAL_STATE, "failed to create token state")?;
-        Ok(State { governor, token: token_state })
+        let empty_mapmap =
+            MapMap::<_, ChainEpoch, ActorID, ActorID>::new(store, HAMT_BIT_WIDTH, HAMT_BIT_WIDTH)
+                .flush()
+                .context_code(ExitCode::USR_ILLEGAL_STATE, "failed to create empty multi map")?;
+        Ok(State {
+            governor,
+            token: token_state,
+            paused: false,
+            allowance_expirations: empty_mapmap,
+            transfer_rules: default_transfer_rules(governor),
+        })
+    }
+
+    pub fn check_not_paused(&self) -> Result<(), ActorError> {
+        if self.paused {
+            return Err(actor_error!(forbidden, "datacap token is paused"));
+        }
+        Ok(())
+    }
+
+    /// Checks that a transfer matches some transfer rule.
+    /// Addresses must be ID addresses to match rules that name them.
+    pub fn check_transfer_allowed(
+        &self,
+        from: &Address,
+        to: &Address,
+        operator: &Address,
+    ) -> Result<(), ActorError> {
+        if !self.transfer_rules.iter().any(|rule| rule.matches(from, to, operator)) {
+            return Err(actor_error!(
+                forbidden,
+                "transfer not allowed from {} to {} by {} (governor is {})",
+                from,
+                to,
+                operator,
+                self.governor
+            ));
+        }
+        Ok(())
     }
 
     // Visible for testing
@@ -31,4 +79,46 @@ impl State {
             .get_balance(bs, owner)
             .context_code(ExitCode::USR_ILLEGAL_STATE, "failed to get balance")
     }
+
+    /// Returns the epoch at which an operator's allowance for an owner expires, if any.
+    pub fn allowance_expiration<BS: Blockstore>(
+        &self,
+        store: &BS,
+        owner: ActorID,
+        operator: ActorID,
+    ) -> Result<Option<ChainEpoch>, ActorError> {
+        let mut expirations = self.load_allowance_expirations(store)?;
+        let expiration = expirations
+            .get(owner, operator)
+            .context_code(ExitCode::USR_ILLEGAL_STATE, "failed to get allowance expiration")?;
+        Ok(expiration.copied())
+    }
+
+    /// Sets or clears the epoch at which an operator's allowance for an owner expires.
+    pub fn set_allowance_expiration<BS: Blockstore>(
+        &mut self,
+        store: &BS,
+        owner: ActorID,
+        operator: ActorID,
+        expiration: Option<ChainEpoch>,
+    ) -> Result<(), ActorError> {
+        let mut expirations = self.load_allowance_expirations(store)?;
+        let result = match expiration {
+            Some(epoch) => expirations.put(owner, operator, epoch).map(|_| ()),
+            None => expirations.remove(owner, operator).map(|_| ()),
+        };
+        result.context_code(ExitCode::USR_ILLEGAL_STATE, "failed to set allowance expiration")?;
+        self.allowance_expirations = expirations
+            .flush()
+            .context_code(ExitCode::USR_ILLEGAL_STATE, "failed to flush allowance expirations")?;
+        Ok(())
+    }
+
+    fn load_allowance_expirations<'a, BS: Blockstore>(
+        &self,
+        store: &'a BS,
+    ) -> Result<MapMap<'a, BS, ChainEpoch, ActorID, ActorID>, ActorError> {
+        MapMap::from_root(store, &self.allowance_expirations, HAMT_BIT_WIDTH, HAMT_BIT_WIDTH)
+            .context_code(ExitCode::USR_ILLEGAL_STATE, "failed to load allowance expirations")
+    }
 }
@@ -2,7 +2,7 @@ use frc46_token::token::state::StateSummary;
 use fvm_ipld_blockstore::Blockstore;
 use fvm_shared::address::Protocol;
 
-use fil_actors_runtime::MessageAccumulator;
+use fil_actors_runtime::{ActorState, MessageAccumulator};
 
 use crate::{State, DATACAP_GRANULARITY};
 
@@ -12,7 +12,15 @@ pub fn check_state_invariants<BS: Blockstore>(
     store: &BS,
 ) -> (StateSummary, MessageAccumulator) {
     let acc = MessageAccumulator::default();
+    state.check_fields(store, &acc);
     acc.require(state.governor.protocol() == Protocol::ID, "governor must be ID address");
+    for rule in &state.transfer_rules {
+        let addrs = [rule.from, rule.to, rule.operator];
+        acc.require(
+            addrs.iter().flatten().all(|addr| addr.protocol() == Protocol::ID),
+            format!("transfer rule {:?} must name only ID addresses", rule),
+        );
+    }
     let (summary, msgs) = state.token.check_invariants(store, DATACAP_GRANULARITY);
     for e in msgs {
         acc.add(e.to_string());
@@ -1,5 +1,8 @@
+use fil_actors_runtime::BatchReturn;
+use frc46_token::token::types::{TransferParams, TransferReturn};
 use fvm_ipld_encoding::tuple::*;
 use fvm_shared::address::Address;
+use fvm_shared::clock::ChainEpoch;
 use fvm_shared::econ::TokenAmount;
 
 #[derive(Clone, Debug, PartialEq, Eq, Serialize_tuple, Deserialize_tuple)]
@@ -83,3 +86,81 @@ pub struct DestroyParams {
 pub struct GranularityReturn {
     pub granularity: u64,
 }
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize_tuple, Deserialize_tuple)]
+#[serde(transparent)]
+pub struct SetPausedParams {
+    pub paused: bool,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize_tuple, Deserialize_tuple)]
+#[serde(transparent)]
+pub struct IsPausedReturn {
+    pub paused: bool,
+}
+
+#[derive(Clone, Debug, Serialize_tuple, Deserialize_tuple)]
+#[serde(transparent)]
+pub struct TransferBatchParams {
+    pub transfers: Vec<TransferParams>,
+}
+
+#[derive(Clone, Debug, Serialize_tuple, Deserialize_tuple)]
+pub struct TransferBatchReturn {
+    // Result for each transfer.
+    pub batch_info: BatchReturn,
+    // Return value of each transfer, including its recipient's receiver hook data.
+    pub results: Vec<TransferReturn>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize_tuple, Deserialize_tuple)]
+pub struct SetAllowanceExpirationParams {
+    // Operator whose allowance for the caller is to expire.
+    pub operator: Address,
+    // Epoch from which the allowance is treated as zero, or None for no expiration.
+    pub expiration: Option<ChainEpoch>,
+}
+
+/// A pattern of transfer that is allowed, although data cap is not generally transferable.
+/// Each address matches any address when absent.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize_tuple, Deserialize_tuple)]
+pub struct TransferRule {
+    // Holder from which the tokens are transferred.
+    pub from: Option<Address>,
+    // Recipient of the tokens.
+    pub to: Option<Address>,
+    // Address making the transfer, which is the holder itself unless spending an allowance.
+    pub operator: Option<Address>,
+}
+
+impl TransferRule {
+    /// Checks whether a transfer matches the rule.
+    /// Addresses are compared exactly, so all should be ID addresses.
+    pub fn matches(&self, from: &Address, to: &Address, operator: &Address) -> bool {
+        fn matches(pattern: &Option<Address>, addr: &Address) -> bool {
+            pattern.as_ref().map_or(true, |p| p == addr)
+        }
+        matches(&self.from, from) && matches(&self.to, to) && matches(&self.operator, operator)
+    }
+}
+
+/// The transfers allowed on construction: any holder may transfer to the governor,
+/// and the governor may transfer its own tokens to anyone.
+pub fn default_transfer_rules(governor: Address) -> Vec<TransferRule> {
+    vec![
+        TransferRule { from: None, to: Some(governor), operator: None },
+        TransferRule { from: Some(governor), to: None, operator: Some(governor) },
+    ]
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize_tuple, Deserialize_tuple)]
+#[serde(transparent)]
+pub struct SetTransferRulesParams {
+    pub rules: Vec<TransferRule>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize_tuple, Deserialize_tuple)]
+#[serde(transparent)]
+pub struct GetTransferRulesReturn {
+    pub rules: Vec<TransferRule>,
+}
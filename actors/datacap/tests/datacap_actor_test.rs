@@ -159,9 +159,13 @@ mod transfer {
 
     use crate::{make_harness, ALICE, BOB, CARLA};
     use fil_actors_runtime::test_utils::expect_abort_contains_message;
+    use fil_actors_runtime::BatchReturn;
+    use frc46_token::token::types::TransferParams;
     use fvm_ipld_encoding::RawBytes;
+    use fvm_shared::address::Address;
     use fvm_shared::econ::TokenAmount;
     use fvm_shared::error::ExitCode;
+    use num_traits::Zero;
 
     #[test]
     fn only_governor_allowed() {
@@ -218,6 +222,335 @@ mod transfer {
         );
         rt.reset();
     }
+
+    #[test]
+    fn transfer_batch() {
+        let (rt, h) = make_harness();
+        let operator_data = RawBytes::new(vec![1, 2, 3, 4]);
+
+        let amt = TokenAmount::from_whole(1);
+        h.mint(&rt, &h.governor, &(amt.clone() * 3), vec![]).unwrap();
+
+        let transfers = vec![
+            transfer_params(&ALICE, &amt, &operator_data),
+            transfer_params(&BOB, &(amt.clone() * 2), &operator_data),
+        ];
+        let ret = h
+            .transfer_batch(&rt, &h.governor, transfers, vec![ExitCode::OK, ExitCode::OK])
+            .unwrap();
+        assert_eq!(BatchReturn::ok(2), ret.batch_info);
+        assert_eq!(2, ret.results.len());
+        assert_eq!(TokenAmount::zero(), ret.results[1].from_balance);
+        assert_eq!(amt.clone() * 2, ret.results[1].to_balance);
+
+        assert_eq!(TokenAmount::zero(), h.get_balance(&rt, &h.governor));
+        assert_eq!(amt, h.get_balance(&rt, &ALICE));
+        assert_eq!(amt.clone() * 2, h.get_balance(&rt, &BOB));
+        h.check_state(&rt);
+    }
+
+    #[test]
+    fn transfer_batch_is_atomic() {
+        let (rt, h) = make_harness();
+        let operator_data = RawBytes::new(vec![1, 2, 3, 4]);
+
+        let amt = TokenAmount::from_whole(1);
+        h.mint(&rt, &ALICE, &(amt.clone() * 2), vec![]).unwrap();
+
+        // A restricted transfer fails the whole batch.
+        let transfers = vec![
+            transfer_params(&h.governor, &amt, &operator_data),
+            transfer_params(&BOB, &amt, &operator_data),
+        ];
+        expect_abort_contains_message(
+            ExitCode::USR_FORBIDDEN,
+            "transfer not allowed",
+            h.transfer_batch(&rt, &ALICE, transfers, vec![ExitCode::OK]),
+        );
+        rt.reset();
+
+        // An overdrawn transfer fails the whole batch.
+        let transfers = vec![
+            transfer_params(&h.governor, &amt, &operator_data),
+            transfer_params(&h.governor, &(amt.clone() * 2), &operator_data),
+        ];
+        expect_abort_contains_message(
+            ExitCode::USR_INSUFFICIENT_FUNDS,
+            "transfer 1 failed",
+            h.transfer_batch(&rt, &ALICE, transfers, vec![ExitCode::OK]),
+        );
+        rt.reset();
+
+        assert_eq!(amt.clone() * 2, h.get_balance(&rt, &ALICE));
+        assert_eq!(TokenAmount::zero(), h.get_balance(&rt, &h.governor));
+        h.check_state(&rt);
+    }
+
+    fn transfer_params(to: &Address, amount: &TokenAmount, data: &RawBytes) -> TransferParams {
+        TransferParams { to: *to, amount: amount.clone(), operator_data: data.clone() }
+    }
+}
+
+mod allowance_expiration {
+    use crate::{make_harness, ALICE, BOB};
+    use fil_actor_datacap::{State, INFINITE_ALLOWANCE};
+    use fil_actors_runtime::runtime::Runtime;
+    use fil_actors_runtime::test_utils::{expect_abort, expect_abort_contains_message};
+    use fvm_ipld_encoding::RawBytes;
+    use fvm_shared::econ::TokenAmount;
+    use fvm_shared::error::ExitCode;
+    use num_traits::Zero;
+
+    #[test]
+    fn expired_allowance_is_zero_and_revoked_on_use() {
+        let (rt, h) = make_harness();
+        let amt = TokenAmount::from_whole(1);
+        h.mint(&rt, &ALICE, &amt, vec![*BOB]).unwrap();
+
+        rt.set_epoch(10);
+        expect_abort_contains_message(
+            ExitCode::USR_ILLEGAL_ARGUMENT,
+            "must be after current epoch",
+            h.set_allowance_expiration(&rt, &ALICE, &BOB, Some(10)),
+        );
+        rt.reset();
+        h.set_allowance_expiration(&rt, &ALICE, &BOB, Some(20)).unwrap();
+        assert_eq!(*INFINITE_ALLOWANCE, h.get_allowance(&rt, &ALICE, &BOB));
+
+        // From the expiration epoch, the allowance is zero but remains in state until used.
+        rt.set_epoch(20);
+        assert_eq!(TokenAmount::zero(), h.get_allowance(&rt, &ALICE, &BOB));
+        assert_eq!(*INFINITE_ALLOWANCE, h.get_allowance_between(&rt, &ALICE, &BOB));
+        expect_abort(
+            ExitCode::USR_INSUFFICIENT_FUNDS,
+            h.transfer_from(&rt, &BOB, &ALICE, &h.governor, &amt, RawBytes::default()),
+        );
+        rt.reset();
+
+        // A new grant starts from zero, without expiration.
+        assert_eq!(amt, h.increase_allowance(&rt, &ALICE, &BOB, &amt).unwrap());
+        assert_eq!(amt, h.get_allowance_between(&rt, &ALICE, &BOB));
+        let st: State = rt.get_state();
+        assert_eq!(None, st.allowance_expiration(rt.store(), 101, 102).unwrap());
+        rt.set_epoch(30);
+        assert_eq!(amt, h.get_allowance(&rt, &ALICE, &BOB));
+        h.check_state(&rt);
+    }
+
+    #[test]
+    fn clear_expiration() {
+        let (rt, h) = make_harness();
+        let amt = TokenAmount::from_whole(1);
+        h.mint(&rt, &ALICE, &amt, vec![*BOB]).unwrap();
+
+        rt.set_epoch(10);
+        h.set_allowance_expiration(&rt, &ALICE, &BOB, Some(20)).unwrap();
+        h.set_allowance_expiration(&rt, &ALICE, &BOB, None).unwrap();
+
+        rt.set_epoch(20);
+        assert_eq!(*INFINITE_ALLOWANCE, h.get_allowance(&rt, &ALICE, &BOB));
+        h.transfer_from(&rt, &BOB, &ALICE, &h.governor, &amt, RawBytes::default()).unwrap();
+        h.check_state(&rt);
+    }
+}
+
+mod pause {
+    use crate::{make_harness, ALICE, BOB};
+    use fil_actor_datacap::{Actor, IsPausedReturn, Method};
+    use fil_actors_runtime::test_utils::expect_abort_contains_message;
+    use fvm_ipld_encoding::RawBytes;
+    use fvm_shared::econ::TokenAmount;
+    use fvm_shared::error::ExitCode;
+    use fvm_shared::MethodNum;
+
+    #[test]
+    fn paused_blocks_mint_and_transfer() {
+        let (rt, h) = make_harness();
+        let amt = TokenAmount::from_whole(1);
+        h.mint(&rt, &ALICE, &amt, vec![*BOB]).unwrap();
+
+        h.set_paused(&rt, true).unwrap();
+        rt.expect_validate_caller_any();
+        let ret: IsPausedReturn = rt
+            .call::<Actor>(Method::IsPausedExported as MethodNum, None)
+            .unwrap()
+            .unwrap()
+            .deserialize()
+            .unwrap();
+        assert!(ret.paused);
+
+        expect_abort_contains_message(
+            ExitCode::USR_FORBIDDEN,
+            "datacap token is paused",
+            h.mint(&rt, &ALICE, &amt, vec![]),
+        );
+        rt.reset();
+        expect_abort_contains_message(
+            ExitCode::USR_FORBIDDEN,
+            "datacap token is paused",
+            h.transfer(&rt, &ALICE, &h.governor, &amt, RawBytes::default()),
+        );
+        rt.reset();
+
+        // Holdings can still be destroyed by the governor.
+        h.destroy(&rt, &ALICE, &amt).unwrap();
+
+        h.set_paused(&rt, false).unwrap();
+        h.mint(&rt, &ALICE, &amt, vec![]).unwrap();
+        h.transfer(&rt, &ALICE, &h.governor, &amt, RawBytes::default()).unwrap();
+        h.check_state(&rt);
+    }
+}
+
+mod transfer_rules {
+    use crate::{make_harness, ALICE, BOB, CARLA};
+    use fil_actor_datacap::{
+        default_transfer_rules, Actor, GetTransferRulesReturn, Method, SetTransferRulesParams,
+        TransferRule,
+    };
+    use fil_actors_runtime::test_utils::{expect_abort_contains_message, ACCOUNT_ACTOR_CODE_ID};
+    use fvm_ipld_encoding::ipld_block::IpldBlock;
+    use fvm_ipld_encoding::RawBytes;
+    use fvm_shared::econ::TokenAmount;
+    use fvm_shared::error::ExitCode;
+    use fvm_shared::MethodNum;
+
+    #[test]
+    fn rules_allow_transfers() {
+        let (rt, h) = make_harness();
+        let amt = TokenAmount::from_whole(1);
+        h.mint(&rt, &ALICE, &TokenAmount::from_whole(3), vec![*BOB]).unwrap();
+
+        // Allow Alice to move data cap to Bob, but only herself.
+        let mut rules = default_transfer_rules(h.governor);
+        rules.push(TransferRule { from: Some(*ALICE), to: Some(*BOB), operator: Some(*ALICE) });
+        h.set_transfer_rules(&rt, rules.clone()).unwrap();
+
+        rt.expect_validate_caller_any();
+        let ret: GetTransferRulesReturn = rt
+            .call::<Actor>(Method::GetTransferRulesExported as MethodNum, None)
+            .unwrap()
+            .unwrap()
+            .deserialize()
+            .unwrap();
+        assert_eq!(rules, ret.rules);
+
+        h.transfer(&rt, &ALICE, &BOB, &amt, RawBytes::default()).unwrap();
+        assert_eq!(amt, h.get_balance(&rt, &BOB));
+        expect_abort_contains_message(
+            ExitCode::USR_FORBIDDEN,
+            "transfer not allowed",
+            h.transfer(&rt, &ALICE, &CARLA, &amt, RawBytes::default()),
+        );
+        rt.reset();
+        expect_abort_contains_message(
+            ExitCode::USR_FORBIDDEN,
+            "transfer not allowed",
+            h.transfer_from(&rt, &BOB, &ALICE, &BOB, &amt, RawBytes::default()),
+        );
+        rt.reset();
+
+        // With no rules, not even transfers to the governor are allowed.
+        h.set_transfer_rules(&rt, vec![]).unwrap();
+        expect_abort_contains_message(
+            ExitCode::USR_FORBIDDEN,
+            "transfer not allowed",
+            h.transfer(&rt, &ALICE, &h.governor, &amt, RawBytes::default()),
+        );
+        rt.reset();
+        h.check_state(&rt);
+    }
+
+    #[test]
+    fn only_governor_sets_rules() {
+        let (rt, h) = make_harness();
+        rt.expect_validate_caller_addr(vec![h.governor]);
+        rt.set_caller(*ACCOUNT_ACTOR_CODE_ID, *ALICE);
+        expect_abort_contains_message(
+            ExitCode::USR_FORBIDDEN,
+            "caller address",
+            rt.call::<Actor>(
+                Method::SetTransferRulesExported as MethodNum,
+                IpldBlock::serialize_cbor(&SetTransferRulesParams { rules: vec![] }).unwrap(),
+            ),
+        );
+        rt.verify();
+        h.check_state(&rt);
+    }
+}
+
+mod upgrade {
+    use crate::{make_harness, ALICE, BOB};
+    use fil_actor_datacap::{
+        default_transfer_rules, Actor as DataCapActor, State, INFINITE_ALLOWANCE,
+    };
+    use fil_actors_runtime::runtime::Runtime;
+    use fil_actors_runtime::test_utils::expect_abort_contains_message;
+    use fvm_ipld_encoding::RawBytes;
+    use fvm_shared::econ::TokenAmount;
+    use fvm_shared::error::ExitCode;
+    use num_traits::Zero;
+
+    #[test]
+    fn upgrade_migrates_legacy_state() {
+        let (rt, h) = make_harness();
+        let amt = TokenAmount::from_whole(2);
+        h.mint(&rt, &ALICE, &amt, vec![*BOB]).unwrap();
+        let st: State = rt.get_state();
+        h.replace_with_legacy_state(&rt);
+
+        rt.upgrade::<DataCapActor>();
+        let migrated: State = rt.get_state();
+        assert_eq!(st.governor, migrated.governor);
+        assert_eq!(st.token, migrated.token);
+        assert!(!migrated.paused);
+
+        // The token remains usable.
+        h.mint(&rt, &ALICE, &amt, vec![]).unwrap();
+        h.transfer(&rt, &ALICE, &h.governor, &amt, RawBytes::default()).unwrap();
+        assert_eq!(amt, h.get_balance(&rt, &ALICE));
+        h.check_state(&rt);
+    }
+
+    #[test]
+    fn upgrade_leaves_allowances_unexpired() {
+        let (rt, h) = make_harness();
+        let amt = TokenAmount::from_whole(1);
+        h.mint(&rt, &ALICE, &amt, vec![*BOB]).unwrap();
+        h.replace_with_legacy_state(&rt);
+        rt.upgrade::<DataCapActor>();
+
+        let st: State = rt.get_state();
+        assert_eq!(None, st.allowance_expiration(rt.store(), 101, 102).unwrap());
+        rt.set_epoch(10);
+        assert_eq!(*INFINITE_ALLOWANCE, h.get_allowance(&rt, &ALICE, &BOB));
+
+        // Expirations can be set on allowances granted before the upgrade.
+        h.set_allowance_expiration(&rt, &ALICE, &BOB, Some(20)).unwrap();
+        rt.set_epoch(20);
+        assert_eq!(TokenAmount::zero(), h.get_allowance(&rt, &ALICE, &BOB));
+        h.check_state(&rt);
+    }
+
+    #[test]
+    fn upgrade_sets_default_transfer_rules() {
+        let (rt, h) = make_harness();
+        let amt = TokenAmount::from_whole(1);
+        h.mint(&rt, &ALICE, &TokenAmount::from_whole(2), vec![]).unwrap();
+        h.replace_with_legacy_state(&rt);
+        rt.upgrade::<DataCapActor>();
+
+        let st: State = rt.get_state();
+        assert_eq!(default_transfer_rules(h.governor), st.transfer_rules);
+        h.transfer(&rt, &ALICE, &h.governor, &amt, RawBytes::default()).unwrap();
+        expect_abort_contains_message(
+            ExitCode::USR_FORBIDDEN,
+            "transfer not allowed",
+            h.transfer(&rt, &ALICE, &BOB, &amt, RawBytes::default()),
+        );
+        rt.reset();
+        h.check_state(&rt);
+    }
 }
 
 mod destroy {
@@ -2,23 +2,31 @@ use std::cell::RefCell;
 
 use frc46_token::receiver::{FRC46TokenReceived, FRC46_TOKEN_TYPE};
 use frc46_token::token::types::{
-    BurnReturn, MintReturn, TransferFromParams, TransferFromReturn, TransferParams, TransferReturn,
+    BurnReturn, GetAllowanceParams, IncreaseAllowanceParams, MintReturn, TransferFromParams,
+    TransferFromReturn, TransferParams, TransferReturn,
 };
 use fvm_actor_utils::receiver::UniversalReceiverParams;
 use fvm_ipld_encoding::RawBytes;
 use fvm_shared::address::Address;
+use fvm_shared::clock::ChainEpoch;
 use fvm_shared::econ::TokenAmount;
 use fvm_shared::error::ExitCode;
 use fvm_shared::MethodNum;
 use num_traits::Zero;
 
+use fil_actor_datacap::migration::LegacyState;
 use fil_actor_datacap::testing::check_state_invariants;
-use fil_actor_datacap::{Actor as DataCapActor, DestroyParams, Method, MintParams, State};
+use fil_actor_datacap::{
+    Actor as DataCapActor, DestroyParams, GetAllowanceReturn, IncreaseAllowanceReturn, Method,
+    MintParams, SetAllowanceExpirationParams, SetPausedParams, SetTransferRulesParams, State,
+    TransferBatchParams, TransferBatchReturn, TransferRule,
+};
 use fil_actors_runtime::cbor::serialize;
 use fil_actors_runtime::runtime::Runtime;
 use fil_actors_runtime::test_utils::*;
 use fil_actors_runtime::{
-    ActorError, DATACAP_TOKEN_ACTOR_ADDR, SYSTEM_ACTOR_ADDR, VERIFIED_REGISTRY_ACTOR_ADDR,
+    ActorError, EventBuilder, DATACAP_TOKEN_ACTOR_ADDR, SYSTEM_ACTOR_ADDR,
+    VERIFIED_REGISTRY_ACTOR_ADDR,
 };
 use fvm_ipld_encoding::ipld_block::IpldBlock;
 
@@ -126,6 +134,48 @@ impl Harness {
         Ok(ret.unwrap().deserialize().unwrap())
     }
 
+    // Replaces the state with its layout before the upgrade migration.
+    pub fn replace_with_legacy_state(&self, rt: &MockRuntime) {
+        let st: State = rt.get_state();
+        rt.replace_state(&LegacyState { governor: st.governor, token: st.token });
+    }
+
+    pub fn set_paused(&self, rt: &MockRuntime, paused: bool) -> Result<(), ActorError> {
+        rt.expect_validate_caller_addr(vec![VERIFIED_REGISTRY_ACTOR_ADDR]);
+        rt.expect_emitted_event(
+            EventBuilder::new().typ("paused").field("paused", &paused).build()?,
+        );
+
+        rt.set_caller(*VERIFREG_ACTOR_CODE_ID, VERIFIED_REGISTRY_ACTOR_ADDR);
+        let ret = rt.call::<DataCapActor>(
+            Method::SetPausedExported as MethodNum,
+            IpldBlock::serialize_cbor(&SetPausedParams { paused }).unwrap(),
+        )?;
+
+        assert!(ret.is_none());
+        rt.verify();
+        Ok(())
+    }
+
+    pub fn set_transfer_rules(
+        &self,
+        rt: &MockRuntime,
+        rules: Vec<TransferRule>,
+    ) -> Result<(), ActorError> {
+        rt.expect_validate_caller_addr(vec![VERIFIED_REGISTRY_ACTOR_ADDR]);
+
+        rt.set_caller(*VERIFREG_ACTOR_CODE_ID, VERIFIED_REGISTRY_ACTOR_ADDR);
+        let ret = rt.call::<DataCapActor>(
+            Method::SetTransferRulesExported as MethodNum,
+            IpldBlock::serialize_cbor(&SetTransferRulesParams { rules: rules.clone() }).unwrap(),
+        )?;
+
+        assert!(ret.is_none());
+        rt.verify();
+        assert_eq!(rules, rt.get_state::<State>().transfer_rules);
+        Ok(())
+    }
+
     pub fn transfer(
         &self,
         rt: &MockRuntime,
@@ -136,31 +186,7 @@ impl Harness {
     ) -> Result<TransferReturn, ActorError> {
         rt.expect_validate_caller_any();
         rt.set_caller(*ACCOUNT_ACTOR_CODE_ID, *from);
-
-        // Expect the token receiver hook to be called.
-        let hook_params = UniversalReceiverParams {
-            type_: FRC46_TOKEN_TYPE,
-            payload: serialize(
-                &FRC46TokenReceived {
-                    from: from.id().unwrap(),
-                    to: to.id().unwrap(),
-                    operator: from.id().unwrap(),
-                    amount: amount.clone(),
-                    operator_data: operator_data.clone(),
-                    token_data: Default::default(),
-                },
-                "hook payload",
-            )?,
-        };
-        // UniversalReceiverParams
-        rt.expect_send_simple(
-            *to,
-            frc42_dispatch::method_hash!("Receive"),
-            IpldBlock::serialize_cbor(&hook_params).unwrap(),
-            TokenAmount::zero(),
-            None,
-            ExitCode::OK,
-        );
+        expect_receiver_hook(rt, from, to, from, amount, &operator_data, ExitCode::OK);
 
         let params = TransferParams { to: *to, amount: amount.clone(), operator_data };
         let ret = rt.call::<DataCapActor>(
@@ -183,31 +209,7 @@ impl Harness {
     ) -> Result<TransferFromReturn, ActorError> {
         rt.expect_validate_caller_any();
         rt.set_caller(*ACCOUNT_ACTOR_CODE_ID, *operator);
-
-        // Expect the token receiver hook to be called.
-        let hook_params = UniversalReceiverParams {
-            type_: FRC46_TOKEN_TYPE,
-            payload: serialize(
-                &FRC46TokenReceived {
-                    from: from.id().unwrap(),
-                    to: to.id().unwrap(),
-                    operator: operator.id().unwrap(),
-                    amount: amount.clone(),
-                    operator_data: operator_data.clone(),
-                    token_data: Default::default(),
-                },
-                "hook payload",
-            )?,
-        };
-        // UniversalReceiverParams
-        rt.expect_send_simple(
-            *to,
-            frc42_dispatch::method_hash!("Receive"),
-            IpldBlock::serialize_cbor(&hook_params).unwrap(),
-            TokenAmount::zero(),
-            None,
-            ExitCode::OK,
-        );
+        expect_receiver_hook(rt, from, to, operator, amount, &operator_data, ExitCode::OK);
 
         let params =
             TransferFromParams { to: *to, from: *from, amount: amount.clone(), operator_data };
@@ -220,6 +222,102 @@ impl Harness {
         Ok(ret.unwrap().deserialize().unwrap())
     }
 
+    // Makes a batch of transfers from an address, expecting the receiver hook of each transfer
+    // to be called in turn and respond with the corresponding exit code.
+    pub fn transfer_batch(
+        &self,
+        rt: &MockRuntime,
+        from: &Address,
+        transfers: Vec<TransferParams>,
+        hook_results: Vec<ExitCode>,
+    ) -> Result<TransferBatchReturn, ActorError> {
+        rt.expect_validate_caller_any();
+        rt.set_caller(*ACCOUNT_ACTOR_CODE_ID, *from);
+        for (transfer, exit_code) in transfers.iter().zip(hook_results) {
+            expect_receiver_hook(
+                rt,
+                from,
+                &transfer.to,
+                from,
+                &transfer.amount,
+                &transfer.operator_data,
+                exit_code,
+            );
+        }
+
+        let params = TransferBatchParams { transfers };
+        let ret = rt.call::<DataCapActor>(
+            Method::TransferBatchExported as MethodNum,
+            IpldBlock::serialize_cbor(&params).unwrap(),
+        )?;
+
+        rt.verify();
+        Ok(ret.unwrap().deserialize().unwrap())
+    }
+
+    pub fn increase_allowance(
+        &self,
+        rt: &MockRuntime,
+        owner: &Address,
+        operator: &Address,
+        increase: &TokenAmount,
+    ) -> Result<TokenAmount, ActorError> {
+        rt.expect_validate_caller_any();
+        rt.set_caller(*ACCOUNT_ACTOR_CODE_ID, *owner);
+        let params = IncreaseAllowanceParams { operator: *operator, increase: increase.clone() };
+        let ret: IncreaseAllowanceReturn = rt
+            .call::<DataCapActor>(
+                Method::IncreaseAllowanceExported as MethodNum,
+                IpldBlock::serialize_cbor(&params).unwrap(),
+            )?
+            .unwrap()
+            .deserialize()
+            .unwrap();
+        rt.verify();
+        Ok(ret.new_allowance)
+    }
+
+    pub fn set_allowance_expiration(
+        &self,
+        rt: &MockRuntime,
+        owner: &Address,
+        operator: &Address,
+        expiration: Option<ChainEpoch>,
+    ) -> Result<(), ActorError> {
+        rt.expect_validate_caller_any();
+        rt.set_caller(*ACCOUNT_ACTOR_CODE_ID, *owner);
+        let params = SetAllowanceExpirationParams { operator: *operator, expiration };
+        let ret = rt.call::<DataCapActor>(
+            Method::SetAllowanceExpirationExported as MethodNum,
+            IpldBlock::serialize_cbor(&params).unwrap(),
+        )?;
+        assert!(ret.is_none());
+        rt.verify();
+        Ok(())
+    }
+
+    // Reads an allowance through the actor, which accounts for expiration.
+    pub fn get_allowance(
+        &self,
+        rt: &MockRuntime,
+        owner: &Address,
+        operator: &Address,
+    ) -> TokenAmount {
+        rt.expect_validate_caller_any();
+        let params = GetAllowanceParams { owner: *owner, operator: *operator };
+        let ret: GetAllowanceReturn = rt
+            .call::<DataCapActor>(
+                Method::AllowanceExported as MethodNum,
+                IpldBlock::serialize_cbor(&params).unwrap(),
+            )
+            .unwrap()
+            .unwrap()
+            .deserialize()
+            .unwrap();
+        rt.verify();
+        ret.allowance
+    }
+
     // Reads the total supply from state directly.
     pub fn get_supply(&self, rt: &MockRuntime) -> TokenAmount {
         rt.get_state::<State>().token.supply
@@ -259,3 +357,38 @@ impl Harness {
         acc.assert_empty();
     }
 }
+
+// Expects the token receiver hook of a transfer's recipient to be called.
+fn expect_receiver_hook(
+    rt: &MockRuntime,
+    from: &Address,
+    to: &Address,
+    operator: &Address,
+    amount: &TokenAmount,
+    operator_data: &RawBytes,
+    exit_code: ExitCode,
+) {
+    let hook_params = UniversalReceiverParams {
+        type_: FRC46_TOKEN_TYPE,
+        payload: serialize(
+            &FRC46TokenReceived {
+                from: from.id().unwrap(),
+                to: to.id().unwrap(),
+                operator: operator.id().unwrap(),
+                amount: amount.clone(),
+                operator_data: operator_data.clone(),
+                token_data: Default::default(),
+            },
+            "hook payload",
+        )
+        .unwrap(),
+    };
+    rt.expect_send_simple(
+        *to,
+        frc42_dispatch::method_hash!("Receive"),
+        IpldBlock::serialize_cbor(&hook_params).unwrap(),
+        TokenAmount::zero(),
+        None,
+        exit_code,
+    );
+}
@@ -10,14 +10,18 @@ use ext::{
 };
 use fil_actors_runtime::{
     actor_dispatch_unrestricted, actor_error, deserialize_block, extract_send_result, ActorError,
-    AsActorError, EAM_ACTOR_ID, INIT_ACTOR_ADDR, SYSTEM_ACTOR_ADDR,
+    AsActorError, OnUpgradeParams, EAM_ACTOR_ID, INIT_ACTOR_ADDR, SYSTEM_ACTOR_ADDR,
 };
 
 use fvm_ipld_encoding::ipld_block::IpldBlock;
 use fvm_shared::{error::ExitCode, sys::SendFlags, ActorID, METHOD_CONSTRUCTOR};
 use serde::{Deserialize, Serialize};
 
+#[cfg(feature = "testing")]
 pub mod ext;
+#[cfg(not(feature = "testing"))]
+#[allow(dead_code)]
+mod ext;
 
 use fil_actors_runtime::runtime::builtins::Type;
 use fil_actors_runtime::runtime::{ActorCode, Runtime};
@@ -37,6 +41,7 @@ pub enum Method {
     Create = 2,
     Create2 = 3,
     CreateExternal = 4,
+    PredictCreate2AddressExported = frc42_dispatch::method_hash!("PredictCreate2Address"),
 }
 
 /// Compute the a new actor address using the EVM's CREATE rules.
@@ -54,7 +59,18 @@ pub fn compute_address_create2(
     initcode: &[u8],
 ) -> EthAddress {
     let inithash = rt.hash(SupportedHashes::Keccak256, initcode);
-    EthAddress(hash_20(rt, &[&[0xff], &from.0[..], salt, &inithash].concat()))
+    compute_address_create2_from_hash(rt, from, salt, &inithash)
+}
+
+/// Compute the a new actor address using the EVM's CREATE2 rules, given the Keccak256 hash
+/// of the initcode.
+pub fn compute_address_create2_from_hash(
+    rt: &impl Runtime,
+    from: &EthAddress,
+    salt: &[u8; 32],
+    inithash: &[u8],
+) -> EthAddress {
+    EthAddress(hash_20(rt, &[&[0xff], &from.0[..], salt, inithash].concat()))
 }
 
 pub fn compute_address_create_external(rt: &impl Runtime, from: &EthAddress) -> EthAddress {
@@ -76,6 +92,23 @@ pub struct Create2Params {
     pub salt: [u8; 32],
 }
 
+#[derive(Serialize_tuple, Deserialize_tuple)]
+pub struct PredictCreate2AddressParams {
+    pub creator: EthAddress,
+    #[serde(with = "strict_bytes")]
+    pub salt: [u8; 32],
+    /// The Keccak256 hash of the initcode.
+    #[serde(with = "strict_bytes")]
+    pub initcode_hash: [u8; 32],
+}
+
+#[derive(Serialize_tuple, Deserialize_tuple, Debug, PartialEq, Eq)]
+pub struct PredictCreate2AddressReturn {
+    pub eth_address: EthAddress,
+    /// Whether a contract may be created at the address, i.e. it isn't reserved.
+    pub assignable: bool,
+}
+
 #[derive(Serialize, Deserialize)]
 #[serde(transparent)]
 pub struct CreateExternalParams(#[serde(with = "strict_bytes")] pub Vec<u8>);
@@ -286,6 +319,27 @@ impl EamActor {
         let eth_addr = compute_address_create_external(rt, &stable_addr);
         create_actor(rt, owner_addr, eth_addr, params.0)
     }
+
+    /// Compute the address at which a creator would create a contract per the EVM's CREATE2
+    /// rules, without creating it.
+    ///
+    /// Permissions: May be called by any actor.
+    pub fn predict_create2_address(
+        rt: &impl Runtime,
+        params: PredictCreate2AddressParams,
+    ) -> Result<PredictCreate2AddressReturn, ActorError> {
+        rt.validate_immediate_caller_accept_any()?;
+        let eth_address = compute_address_create2_from_hash(
+            rt,
+            &params.creator,
+            &params.salt,
+            &params.initcode_hash,
+        );
+        Ok(PredictCreate2AddressReturn {
+            eth_address,
+            assignable: can_assign_address(&eth_address),
+        })
+    }
 }
 
 impl ActorCode for EamActor {
@@ -295,11 +349,21 @@ impl ActorCode for EamActor {
         "EVMAddressManager"
     }
 
+    /// This actor has no state, so there is nothing to migrate.
+    fn on_upgrade<RT>(_rt: &RT, _params: OnUpgradeParams) -> Result<(), ActorError>
+    where
+        RT: Runtime,
+        RT::Blockstore: Clone,
+    {
+        Ok(())
+    }
+
     actor_dispatch_unrestricted! {
         Constructor => constructor,
         Create => create,
         Create2 => create2,
         CreateExternal => create_external,
+        PredictCreate2AddressExported => predict_create2_address,
     }
 }
 
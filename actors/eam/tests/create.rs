@@ -1,6 +1,9 @@
 use eam::ext::evm::RESURRECT_METHOD;
 use eam::ext::init::{Exec4Params, Exec4Return, EXEC4_METHOD};
-use eam::{compute_address_create, Create2Params, CreateParams, Return};
+use eam::{
+    compute_address_create, Create2Params, CreateParams, PredictCreate2AddressParams,
+    PredictCreate2AddressReturn, Return,
+};
 use fil_actor_eam as eam;
 use fil_actor_eam::CreateExternalParams;
 use fil_actors_evm_shared::address::EthAddress;
@@ -266,6 +269,42 @@ fn call_create2() {
     rt.verify();
 }
 
+#[test]
+fn predict_create2_address() {
+    let rt = construct_and_verify();
+    rt.set_caller(*ETHACCOUNT_ACTOR_CODE_ID, Address::new_id(110));
+    rt.expect_validate_caller_any();
+
+    let initcode_hash: [u8; 32] = rt
+        .hash(fvm_shared::crypto::hash::SupportedHashes::Keccak256, b"foobar")
+        .try_into()
+        .unwrap();
+    let params = PredictCreate2AddressParams {
+        creator: EthAddress([0x99; 20]),
+        salt: [0x42; 32],
+        initcode_hash,
+    };
+    let result = rt
+        .call::<eam::EamActor>(
+            eam::Method::PredictCreate2AddressExported as u64,
+            IpldBlock::serialize_cbor(&params).unwrap(),
+        )
+        .unwrap()
+        .unwrap()
+        .deserialize::<PredictCreate2AddressReturn>()
+        .unwrap();
+    rt.verify();
+
+    // the address CREATE2 would assign to the same creator, salt and initcode
+    assert_eq!(
+        PredictCreate2AddressReturn {
+            eth_address: EthAddress(hex_literal::hex!("64425c93a90901271fa355c2bc462190803b97d4")),
+            assignable: true,
+        },
+        result
+    );
+}
+
 pub fn construct_and_verify() -> MockRuntime {
     let rt = MockRuntime { receiver: Address::new_id(10), ..Default::default() };
 
@@ -7,8 +7,8 @@ use num_derive::FromPrimitive;
 
 use fil_actors_runtime::runtime::{ActorCode, Runtime};
 use fil_actors_runtime::{
-    actor_dispatch, actor_error, ActorError, EAM_ACTOR_ID, FIRST_EXPORTED_METHOD_NUMBER,
-    SYSTEM_ACTOR_ADDR,
+    actor_dispatch, actor_error, ActorError, OnUpgradeParams, EAM_ACTOR_ID,
+    FIRST_EXPORTED_METHOD_NUMBER, SYSTEM_ACTOR_ADDR,
 };
 
 #[cfg(feature = "fil-actor")]
@@ -72,6 +72,15 @@ impl ActorCode for EthAccountActor {
         "EVMAccount"
     }
 
+    /// This actor has no state, so there is nothing to migrate.
+    fn on_upgrade<RT>(_rt: &RT, _params: OnUpgradeParams) -> Result<(), ActorError>
+    where
+        RT: Runtime,
+        RT::Blockstore: Clone,
+    {
+        Ok(())
+    }
+
     actor_dispatch! {
         Constructor => constructor,
         _ => fallback,
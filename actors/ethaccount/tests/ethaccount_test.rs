@@ -10,6 +10,7 @@ use fil_actor_ethaccount::{EthAccountActor, Method};
 use fvm_shared::error::ExitCode;
 use fvm_shared::MethodNum;
 
+use fil_actors_runtime::runtime::EMPTY_ARR_CID;
 use fil_actors_runtime::test_utils::{
     expect_abort_contains_message, ACCOUNT_ACTOR_CODE_ID, SYSTEM_ACTOR_CODE_ID,
 };
@@ -46,3 +47,9 @@ fn token_receiver() {
         .unwrap();
     assert!(ret.is_none());
 }
+
+#[test]
+fn upgrade_is_a_no_op() {
+    let rt = setup();
+    assert_eq!(EMPTY_ARR_CID, rt.upgrade::<EthAccountActor>());
+}
@@ -0,0 +1,49 @@
+//! Benchmarks of the interpreter's instruction dispatch, over loops of cheap instructions that
+//! don't call out to the runtime.
+
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use fil_actor_evm::interpreter::opcodes::*;
+use fil_actor_evm::interpreter::{execute, Bytecode, ExecutionState, System};
+use fil_actors_evm_shared::address::EthAddress;
+use fil_actors_runtime::test_utils::MockRuntime;
+use fvm_shared::econ::TokenAmount;
+
+const ITERATIONS: u32 = 10_000;
+
+/// Counts down from `ITERATIONS` to zero, running `body` on each iteration.
+/// The body must leave the stack as it found it, with the counter on top.
+fn counting_loop(body: &[u8]) -> Bytecode {
+    let [_, b0, b1, b2] = ITERATIONS.to_be_bytes();
+    let mut code = vec![PUSH3, b0, b1, b2, JUMPDEST];
+    code.extend_from_slice(body);
+    code.extend_from_slice(&[PUSH1, 0x01, SWAP1, SUB, DUP1, PUSH1, 0x04, JUMPI, POP, STOP]);
+    Bytecode::new(code)
+}
+
+fn run(rt: &MockRuntime, bytecode: &Bytecode) {
+    let mut system = System::create(rt).unwrap();
+    let mut state = ExecutionState::new(
+        EthAddress::from_id(1000),
+        EthAddress::from_id(1000),
+        TokenAmount::from_atto(0),
+        Vec::new(),
+    );
+    black_box(execute(bytecode, &mut state, &mut system).unwrap());
+}
+
+fn bench_dispatch(c: &mut Criterion) {
+    let rt = MockRuntime::default();
+    rt.in_call.replace(true);
+
+    let empty = counting_loop(&[]);
+    c.bench_function("loop", |b| b.iter(|| run(&rt, &empty)));
+
+    let arithmetic = counting_loop(&[DUP1, DUP1, MUL, DUP2, ADD, PUSH1, 0x07, SWAP1, MOD, POP]);
+    c.bench_function("loop_arithmetic", |b| b.iter(|| run(&rt, &arithmetic)));
+
+    let memory = counting_loop(&[DUP1, PUSH1, 0x00, MSTORE, PUSH1, 0x00, MLOAD, SWAP1, POP]);
+    c.bench_function("loop_memory", |b| b.iter(|| run(&rt, &memory)));
+}
+
+criterion_group!(benches, bench_dispatch);
+criterion_main!(benches);
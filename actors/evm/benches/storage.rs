@@ -0,0 +1,64 @@
+//! Benchmarks of contract storage over KAMTs of different bit widths, writing many slots and
+//! reading them back cold and through the per-invocation slot cache.
+
+use criterion::{black_box, criterion_group, criterion_main, BatchSize, BenchmarkId, Criterion};
+use fil_actor_evm::interpreter::System;
+use fil_actors_evm_shared::uints::U256;
+use fil_actors_runtime::test_utils::MockRuntime;
+
+const SLOTS: u64 = 1_000;
+const BIT_WIDTHS: [u32; 3] = [3, 5, 8];
+
+fn new_runtime(bit_width: u32) -> MockRuntime {
+    let mut rt = MockRuntime::default();
+    rt.policy.evm_storage_bit_width = bit_width;
+    rt.in_call.replace(true);
+    rt
+}
+
+/// Creates a contract with `SLOTS` storage slots set, flushed to the runtime's state.
+fn fill_storage(rt: &MockRuntime) {
+    let mut system = System::create(rt).unwrap();
+    for i in 0..SLOTS {
+        system.set_storage(U256::from(i), U256::from(i + 1)).unwrap();
+    }
+    system.flush().unwrap();
+}
+
+fn bench_write(c: &mut Criterion) {
+    let mut group = c.benchmark_group("storage_write");
+    for bit_width in BIT_WIDTHS {
+        group.bench_with_input(BenchmarkId::from_parameter(bit_width), &bit_width, |b, &w| {
+            b.iter_batched(|| new_runtime(w), |rt| fill_storage(&rt), BatchSize::SmallInput)
+        });
+    }
+    group.finish();
+}
+
+fn bench_read(c: &mut Criterion) {
+    let mut group = c.benchmark_group("storage_read");
+    for bit_width in BIT_WIDTHS {
+        let rt = new_runtime(bit_width);
+        fill_storage(&rt);
+        group.bench_with_input(BenchmarkId::new("cold", bit_width), &rt, |b, rt| {
+            b.iter(|| {
+                let mut system = System::load(rt).unwrap();
+                for i in 0..SLOTS {
+                    black_box(system.get_storage(U256::from(i)).unwrap());
+                }
+            })
+        });
+        group.bench_with_input(BenchmarkId::new("hot", bit_width), &rt, |b, rt| {
+            b.iter(|| {
+                let mut system = System::load(rt).unwrap();
+                for _ in 0..SLOTS {
+                    black_box(system.get_storage(U256::from(SLOTS / 2)).unwrap());
+                }
+            })
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_write, bench_read);
+criterion_main!(benches);
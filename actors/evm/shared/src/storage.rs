@@ -0,0 +1,64 @@
+use fil_actors_runtime::runtime::Primitives;
+use fvm_shared::crypto::hash::SupportedHashes;
+
+use crate::uints::U256;
+
+/// The key of a slot in an EVM contract's storage, as read by the EVM actor's GetStorageAt
+/// method, with helpers to locate Solidity storage variables.
+///
+/// Solidity assigns each state variable a slot in declaration order, starting from slot 0 and
+/// packing consecutive variables smaller than 32 bytes into one slot. The contents of mappings
+/// and dynamic arrays are stored at slots derived by hashing, computed here with Keccak256.
+/// See <https://docs.soliditylang.org/en/latest/internals/layout_in_storage.html>.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct StorageSlot(pub U256);
+
+impl StorageSlot {
+    pub const fn new(slot: u64) -> Self {
+        Self(U256::from_u64(slot))
+    }
+
+    /// The storage key of this slot.
+    pub fn key(&self) -> U256 {
+        self.0
+    }
+
+    /// The slot `n` slots after this one, such as that of a later member of a struct stored here.
+    pub fn offset(&self, n: u64) -> Self {
+        Self(self.0.overflowing_add(U256::from(n)).0)
+    }
+
+    /// The slot of the value for a key in a mapping stored at this slot, where the key is a value
+    /// type (such as `uint256`, `address` or `bytes32`) given as its 32-byte ABI encoding.
+    pub fn mapping_value(&self, p: &impl Primitives, key: &[u8; 32]) -> Self {
+        self.mapping_value_bytes(p, key)
+    }
+
+    /// The slot of the value for a key in a mapping stored at this slot, where the key is a
+    /// `string` or `bytes`, given unpadded.
+    pub fn mapping_value_bytes(&self, p: &impl Primitives, key: &[u8]) -> Self {
+        let mut preimage = Vec::with_capacity(key.len() + 32);
+        preimage.extend_from_slice(key);
+        preimage.extend_from_slice(&self.0.to_bytes());
+        Self(keccak256(p, &preimage))
+    }
+
+    /// The slot of an element of a dynamic array stored at this slot, where each element takes
+    /// `element_slots` whole slots. Elements smaller than a slot are packed several to a slot,
+    /// and must be located within the slot of element `index * elements_per_slot` instead.
+    pub fn array_element(&self, p: &impl Primitives, index: u64, element_slots: u64) -> Self {
+        let start = keccak256(p, &self.0.to_bytes());
+        let offset = U256::from(index).overflowing_mul(U256::from(element_slots)).0;
+        Self(start.overflowing_add(offset).0)
+    }
+}
+
+impl From<U256> for StorageSlot {
+    fn from(key: U256) -> Self {
+        Self(key)
+    }
+}
+
+fn keccak256(p: &impl Primitives, data: &[u8]) -> U256 {
+    U256::from_big_endian(&p.hash(SupportedHashes::Keccak256, data))
+}
@@ -28,3 +28,68 @@ pub mod eam {
         pub eth_address: EthAddress,
     }
 }
+
+pub mod system {
+    use fvm_ipld_encoding::tuple::*;
+    use fvm_shared::address::Address;
+
+    pub const GET_CALL_FILTER_METHOD: u64 = frc42_dispatch::method_hash!("GetCallFilter");
+
+    #[derive(Serialize_tuple, Deserialize_tuple, Debug, Clone, PartialEq, Eq)]
+    #[serde(transparent)]
+    pub struct GetCallFilterReturn {
+        pub call_filter: Option<Address>,
+    }
+}
+
+/// The interface of a call filter, an actor registered with the system actor that decides
+/// whether a call to an EVM contract may execute.
+pub mod call_filter {
+    use fvm_ipld_encoding::{strict_bytes, tuple::*};
+    use fvm_shared::address::Address;
+    use fvm_shared::econ::TokenAmount;
+
+    pub const CHECK_CALL_METHOD: u64 = frc42_dispatch::method_hash!("CheckCall");
+
+    #[derive(Serialize_tuple, Deserialize_tuple, Debug, Clone, PartialEq, Eq)]
+    pub struct CheckCallParams {
+        /// The contract being called.
+        pub contract: Address,
+        /// The immediate caller.
+        pub caller: Address,
+        pub value: TokenAmount,
+        #[serde(with = "strict_bytes")]
+        pub input_data: Vec<u8>,
+    }
+
+    /// Whether the call may execute.
+    #[derive(Serialize_tuple, Deserialize_tuple, Debug, Clone, Copy, PartialEq, Eq)]
+    #[serde(transparent)]
+    pub struct CheckCallReturn {
+        pub allowed: bool,
+    }
+}
+
+pub mod miner {
+    use fvm_ipld_encoding::tuple::*;
+    use fvm_shared::clock::ChainEpoch;
+    use fvm_shared::sector::SectorNumber;
+
+    pub const GET_SECTOR_STATUS_METHOD: u64 = frc42_dispatch::method_hash!("GetSectorStatus");
+
+    #[derive(Serialize_tuple, Deserialize_tuple, Debug, Clone, PartialEq, Eq)]
+    #[serde(transparent)]
+    pub struct GetSectorStatusParams {
+        pub sector_number: SectorNumber,
+    }
+
+    #[derive(Serialize_tuple, Deserialize_tuple, Debug, Clone, PartialEq, Eq)]
+    pub struct GetSectorStatusReturn {
+        pub live: bool,
+        pub faulty: bool,
+        pub activation: ChainEpoch,
+        pub expiration: ChainEpoch,
+        pub deadline: u64,
+        pub partition: u64,
+    }
+}
@@ -0,0 +1,273 @@
+//! Interpretation of the address operands of EVM instructions.
+//!
+//! Every address-consuming instruction (the CALL family, BALANCE, EXTCODESIZE, EXTCODEHASH,
+//! EXTCODECOPY and the SELFDESTRUCT beneficiary) names an actor by the same rule:
+//!
+//! - Only the low 20 bytes of the operand are significant; the high bytes are ignored.
+//! - A masked ID address (`0xff`, eleven zero bytes, then the big-endian actor ID) names the actor
+//!   with that ID directly, whether or not the actor also has a delegated address.
+//! - Any other address names the f410 address with that payload in the EAM's namespace.
+//!
+//! A masked ID address naming no actor behaves as an address with no code and no balance: calls
+//! to it succeed without effect. The exception is SELFDESTRUCT, which aborts rather than burn
+//! funds sent to a beneficiary that can never exist.
+
+use fil_actors_evm_shared::address::EthAddress;
+use fil_actors_evm_shared::uints::U256;
+use fil_actors_runtime::runtime::Runtime;
+use fvm_shared::address::Address;
+use fvm_shared::ActorID;
+
+/// Returns the EVM address named by an instruction's address operand.
+#[inline]
+pub fn operand_address(operand: U256) -> EthAddress {
+    operand.into()
+}
+
+/// Returns the Filecoin address named by an EVM address.
+#[inline]
+pub fn to_fil_address(addr: &EthAddress) -> Address {
+    addr.into()
+}
+
+/// Resolves an EVM address to the ID of the actor it names, if that actor exists.
+pub fn resolve_actor(rt: &impl Runtime, addr: &EthAddress) -> Option<ActorID> {
+    rt.resolve_address(&to_fil_address(addr))
+}
+
+#[cfg(test)]
+mod tests {
+    use cid::Cid;
+    use fil_actors_evm_shared::address::EthAddress;
+    use fil_actors_evm_shared::uints::U256;
+    use fil_actors_runtime::test_utils::EVM_ACTOR_CODE_ID;
+    use fil_actors_runtime::EAM_ACTOR_ID;
+    use fvm_ipld_blockstore::Blockstore;
+    use fvm_ipld_encoding::ipld_block::IpldBlock;
+    use fvm_shared::address::Address;
+    use fvm_shared::error::ExitCode;
+    use fvm_shared::sys::SendFlags;
+    use fvm_shared::METHOD_SEND;
+    use num_traits::Zero;
+
+    use super::{operand_address, to_fil_address};
+    use crate::evm_unit_test;
+    use crate::BytecodeHash;
+
+    const TARGET: u64 = 1001;
+
+    /// Address operands paired with the Filecoin address each must name.
+    fn address_operands() -> Vec<(U256, Address)> {
+        let masked = EthAddress::from_id(TARGET);
+
+        let mut high_bytes_set = masked.as_evm_word().to_bytes();
+        high_bytes_set[..12].copy_from_slice(&[0xab; 12]);
+
+        let mut bad_padding = masked;
+        bad_padding.0[5] = 0x01;
+
+        let eth = EthAddress([0xab; 20]);
+
+        vec![
+            (masked.as_evm_word(), Address::new_id(TARGET)),
+            (U256::from(high_bytes_set), Address::new_id(TARGET)),
+            (
+                bad_padding.as_evm_word(),
+                Address::new_delegated(EAM_ACTOR_ID, &bad_padding.0).unwrap(),
+            ),
+            (eth.as_evm_word(), Address::new_delegated(EAM_ACTOR_ID, &eth.0).unwrap()),
+        ]
+    }
+
+    #[test]
+    fn operands_name_addresses() {
+        for (operand, expected) in address_operands() {
+            assert_eq!(expected, to_fil_address(&operand_address(operand)));
+        }
+    }
+
+    #[test]
+    fn balance() {
+        for (operand, addr) in address_operands() {
+            evm_unit_test! {
+                (rt) {
+                    if addr.id().is_err() {
+                        rt.add_id_address(addr, Address::new_id(TARGET));
+                    }
+                    rt.actor_balances.insert(TARGET, TokenAmount::from_atto(1234));
+                }
+                (m) {
+                    BALANCE;
+                }
+                m.state.stack.push(operand).unwrap();
+                m.step().expect("execution step failed");
+                assert_eq!(m.state.stack.pop().unwrap(), U256::from(1234));
+            };
+        }
+    }
+
+    #[test]
+    fn extcode() {
+        let bytecode = vec![0x01, 0x02, 0x03, 0x04];
+        let bytecode_cid = Cid::try_from("baeaikaia").unwrap();
+        let bytecode_hash = BytecodeHash::try_from([0x11; 32].as_slice()).unwrap();
+        for (operand, addr) in address_operands() {
+            evm_unit_test! {
+                (rt) {
+                    if addr.id().is_err() {
+                        rt.add_id_address(addr, Address::new_id(TARGET));
+                    }
+                    rt.set_address_actor_type(Address::new_id(TARGET), *EVM_ACTOR_CODE_ID);
+                    rt.store.put_keyed(&bytecode_cid, &bytecode).unwrap();
+                    for (method, ret) in [
+                        (crate::Method::GetBytecode, IpldBlock::serialize_cbor(&bytecode_cid)),
+                        (crate::Method::GetBytecodeHash, IpldBlock::serialize_cbor(&bytecode_hash)),
+                        (crate::Method::GetBytecode, IpldBlock::serialize_cbor(&bytecode_cid)),
+                    ] {
+                        rt.expect_send(
+                            Address::new_id(TARGET),
+                            method as u64,
+                            None,
+                            TokenAmount::zero(),
+                            None,
+                            SendFlags::READ_ONLY,
+                            ret.unwrap(),
+                            ExitCode::OK,
+                            None,
+                        );
+                    }
+                }
+                (m) {
+                    EXTCODESIZE;
+                    EXTCODEHASH;
+                    EXTCODECOPY;
+                }
+                m.state.stack.push(operand).unwrap();
+                m.step().expect("execution step failed");
+                assert_eq!(m.state.stack.pop().unwrap(), U256::from(bytecode.len()));
+
+                m.state.stack.push(operand).unwrap();
+                m.step().expect("execution step failed");
+                assert_eq!(m.state.stack.pop().unwrap(), U256::from(bytecode_hash));
+
+                m.state.stack.push(U256::from(bytecode.len())).unwrap(); // length
+                m.state.stack.push(U256::from(0)).unwrap(); // offset
+                m.state.stack.push(U256::from(0)).unwrap(); // destOffset
+                m.state.stack.push(operand).unwrap();
+                m.step().expect("execution step failed");
+                assert_eq!(&m.state.memory[..bytecode.len()], &bytecode);
+            };
+        }
+    }
+
+    #[test]
+    fn call_and_staticcall() {
+        for (operand, addr) in address_operands() {
+            evm_unit_test! {
+                (rt) {
+                    for flags in [SendFlags::empty(), SendFlags::READ_ONLY] {
+                        rt.expect_send(
+                            addr,
+                            crate::Method::InvokeContract as u64,
+                            None,
+                            TokenAmount::zero(),
+                            Some(1_000_000_000),
+                            flags,
+                            None,
+                            ExitCode::OK,
+                            None,
+                        );
+                        rt.expect_gas_available(10_000_000_000);
+                    }
+                }
+                (m) {
+                    CALL;
+                    STATICCALL;
+                }
+                m.state.stack.push(U256::from(0)).unwrap(); // output size
+                m.state.stack.push(U256::from(0)).unwrap(); // output offset
+                m.state.stack.push(U256::from(0)).unwrap(); // input size
+                m.state.stack.push(U256::from(0)).unwrap(); // input offset
+                m.state.stack.push(U256::from(0)).unwrap(); // value
+                m.state.stack.push(operand).unwrap(); // dest
+                m.state.stack.push(U256::from(1_000_000_000)).unwrap(); // gas
+                m.step().expect("execution step failed");
+                assert_eq!(m.state.stack.pop().unwrap(), U256::from(1));
+
+                m.state.stack.push(U256::from(0)).unwrap(); // output size
+                m.state.stack.push(U256::from(0)).unwrap(); // output offset
+                m.state.stack.push(U256::from(0)).unwrap(); // input size
+                m.state.stack.push(U256::from(0)).unwrap(); // input offset
+                m.state.stack.push(operand).unwrap(); // dest
+                m.state.stack.push(U256::from(1_000_000_000)).unwrap(); // gas
+                m.step().expect("execution step failed");
+                assert_eq!(m.state.stack.pop().unwrap(), U256::from(1));
+            };
+        }
+    }
+
+    #[test]
+    fn delegatecall() {
+        for (operand, addr) in address_operands() {
+            evm_unit_test! {
+                (rt) {
+                    if addr.id().is_err() {
+                        rt.add_id_address(addr, Address::new_id(TARGET));
+                    }
+                    rt.set_address_actor_type(Address::new_id(TARGET), *EVM_ACTOR_CODE_ID);
+                    // A contract without bytecode short-circuits the delegate call.
+                    rt.expect_send(
+                        Address::new_id(TARGET),
+                        crate::Method::GetBytecode as u64,
+                        None,
+                        TokenAmount::zero(),
+                        None,
+                        SendFlags::READ_ONLY,
+                        IpldBlock::serialize_cbor(&Option::<Cid>::None).unwrap(),
+                        ExitCode::OK,
+                        None,
+                    );
+                }
+                (m) {
+                    DELEGATECALL;
+                }
+                m.state.stack.push(U256::from(0)).unwrap(); // output size
+                m.state.stack.push(U256::from(0)).unwrap(); // output offset
+                m.state.stack.push(U256::from(0)).unwrap(); // input size
+                m.state.stack.push(U256::from(0)).unwrap(); // input offset
+                m.state.stack.push(operand).unwrap(); // dest
+                m.state.stack.push(U256::from(1_000_000_000)).unwrap(); // gas
+                m.step().expect("execution step failed");
+                assert_eq!(m.state.stack.pop().unwrap(), U256::from(1));
+            };
+        }
+    }
+
+    #[test]
+    fn selfdestruct_beneficiary() {
+        for (operand, addr) in address_operands() {
+            evm_unit_test! {
+                (rt) {
+                    rt.set_balance(TokenAmount::from_atto(1_000_000));
+                    rt.expect_send(
+                        addr,
+                        METHOD_SEND,
+                        None,
+                        TokenAmount::from_atto(1_000_000),
+                        None,
+                        SendFlags::empty(),
+                        None,
+                        ExitCode::OK,
+                        None,
+                    );
+                }
+                (m) {
+                    SELFDESTRUCT;
+                }
+                m.state.stack.push(operand).unwrap();
+                let result = m.execute().expect("execution failed");
+                assert_eq!(result.outcome, crate::Outcome::Return);
+            };
+        }
+    }
+}
@@ -5,18 +5,31 @@ use super::opcodes;
 #[derive(Clone, Debug)]
 pub struct Bytecode {
     code: Vec<u8>,
-    jumpdest: Vec<bool>,
+    /// Bitmap of valid jump destinations, one bit per byte of code, least significant bit first.
+    jumpdest: Vec<u8>,
 }
 
 impl Bytecode {
     pub fn new(bytecode: Vec<u8>) -> Self {
+        let jumpdest = Self::analyze_jumpdests(&bytecode);
+        Self { code: bytecode, jumpdest }
+    }
+
+    /// Creates bytecode with jump destinations previously computed by `analyze_jumpdests`,
+    /// or returns None if the bitmap is the wrong length for the code.
+    pub fn with_jumpdests(bytecode: Vec<u8>, jumpdest: Vec<u8>) -> Option<Self> {
+        (jumpdest.len() == bytecode.len().div_ceil(8)).then_some(Self { code: bytecode, jumpdest })
+    }
+
+    /// Computes the bitmap of valid jump destinations in the bytecode.
+    pub fn analyze_jumpdests(bytecode: &[u8]) -> Vec<u8> {
         // only jumps to those addresses are valid. This is a security
         // feature by EVM to disallow jumps to arbitary code addresses.
-        let mut jumpdest = vec![false; bytecode.len()];
+        let mut jumpdest = vec![0u8; bytecode.len().div_ceil(8)];
         let mut i = 0;
         while i < bytecode.len() {
             if bytecode[i] == opcodes::JUMPDEST {
-                jumpdest[i] = true;
+                jumpdest[i / 8] |= 1 << (i % 8);
                 i += 1;
             } else if bytecode[i] >= opcodes::PUSH1 && bytecode[i] <= opcodes::PUSH32 {
                 i += (bytecode[i] - opcodes::PUSH1) as usize + 2;
@@ -24,8 +37,12 @@ impl Bytecode {
                 i += 1;
             }
         }
+        jumpdest
+    }
 
-        Self { code: bytecode, jumpdest }
+    /// The bitmap of valid jump destinations.
+    pub fn jumpdests(&self) -> &[u8] {
+        &self.jumpdest
     }
 
     /// Checks if the EVM is allowed to jump to this location.
@@ -33,7 +50,7 @@ impl Bytecode {
     /// This location must begin with a JUMPDEST opcode that
     /// marks a valid jump destination
     pub fn valid_jump_destination(&self, offset: usize) -> bool {
-        offset < self.jumpdest.len() && self.jumpdest[offset]
+        offset < self.code.len() && self.jumpdest[offset / 8] & (1 << (offset % 8)) != 0
     }
 }
 
@@ -50,3 +67,25 @@ impl AsRef<[u8]> for Bytecode {
         &self.code
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::opcodes::{JUMPDEST, PUSH1, PUSH2, STOP};
+    use super::Bytecode;
+
+    #[test]
+    fn jumpdests_skip_push_data() {
+        // JUMPDEST PUSH1 JUMPDEST STOP PUSH2 JUMPDEST JUMPDEST JUMPDEST, then JUMPDEST at 8
+        let code =
+            vec![JUMPDEST, PUSH1, JUMPDEST, STOP, PUSH2, JUMPDEST, JUMPDEST, JUMPDEST, JUMPDEST];
+        let bytecode = Bytecode::new(code.clone());
+        let valid: Vec<_> =
+            (0..code.len() + 1).filter(|i| bytecode.valid_jump_destination(*i)).collect();
+        assert_eq!(vec![0, 7, 8], valid);
+        assert_eq!(&[0b1000_0001, 0b1], bytecode.jumpdests());
+
+        let reloaded = Bytecode::with_jumpdests(code.clone(), bytecode.jumpdests().to_vec());
+        assert!(reloaded.unwrap().valid_jump_destination(8));
+        assert!(Bytecode::with_jumpdests(code, vec![0b1000_0001]).is_none());
+    }
+}
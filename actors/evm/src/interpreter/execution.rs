@@ -1,4 +1,6 @@
 use fil_actors_evm_shared::address::EthAddress;
+#[cfg(feature = "trace")]
+use fil_actors_evm_shared::uints::U256;
 use fil_actors_runtime::ActorError;
 use fvm_shared::econ::TokenAmount;
 
@@ -54,50 +56,32 @@ pub struct Machine<'r, 'a, RT: Runtime + 'a> {
 
 macro_rules! def_opcodes {
     ($($code:literal: $op:ident,)*) => {
-        pub(crate) const fn jumptable<'r, 'a, RT: Runtime>() -> [Instruction<'r, 'a, RT>; 256] {
-            def_ins_raw! {
-                UNDEFINED(_m) {
-                    Err(ActorError::unchecked(
-                        crate::EVM_CONTRACT_UNDEFINED_INSTRUCTION,
-                        "undefined instruction".into()
-                    ))
-                }
+        /// Executes the instruction for the given opcode.
+        /// The match compiles down to a jump table over the opcode byte.
+        /// Not forced inline: every instruction is inlined here, and `step` is inlined into
+        /// each of its callers.
+        pub(crate) fn dispatch<'r, 'a, RT: Runtime + 'a>(
+            m: &mut Machine<'r, 'a, RT>,
+            op: u8,
+        ) -> Result<(), ActorError> {
+            match op {
+                $($code => instructions::$op(m),)*
+                _ => Err(ActorError::unchecked(
+                    crate::EVM_CONTRACT_UNDEFINED_INSTRUCTION,
+                    "undefined instruction".into(),
+                )),
             }
-            $(def_ins_raw! {
-                $op (m) {
-                    instructions::$op(m)
-                }
-            })*
-
-            let mut table: [Instruction<'r, 'a, RT>; 256] = [UNDEFINED; 256];
-            $(table[$code] = $op;)*
-            table
         }
         $(pub const $op: u8 = $code;)*
     }
 }
 
-macro_rules! def_ins_raw {
-    ($ins:ident ($arg:ident) $body:block) => {
-        #[allow(non_snake_case)]
-        unsafe fn $ins<'r, 'a, RT: Runtime>(p: *mut Machine<'r, 'a, RT>) -> Result<(), ActorError> {
-            // SAFETY: macro ensures that mut pointer is taken directly from a mutable borrow, used
-            // once, then goes out of scope immediately after
-            let $arg: &mut Machine<'r, 'a, RT> = &mut *p;
-            $body
-        }
-    };
-}
-
 pub mod opcodes {
     use super::instructions;
     use super::Machine;
     use fil_actors_runtime::runtime::Runtime;
     use fil_actors_runtime::ActorError;
 
-    pub(crate) type Instruction<'r, 'a, RT> =
-        unsafe fn(*mut Machine<'r, 'a, RT>) -> Result<(), ActorError>;
-
     def_opcodes! {
         0x00: STOP,
         0x01: ADD,
@@ -274,10 +258,10 @@ impl<'r, 'a, RT: Runtime + 'r> Machine<'r, 'a, RT> {
     // Note: pub only for unit test steps.
     pub(crate) fn step(&mut self) -> Result<(), ActorError> {
         let op = self.bytecode[self.pc];
-        unsafe { Self::JMPTABLE[op as usize](self) }
+        #[cfg(feature = "trace")]
+        self.system.rt.trace_step(self.pc as u32, op, self.state.stack.top().map(U256::to_bytes));
+        opcodes::dispatch(self, op)
     }
-
-    const JMPTABLE: [opcodes::Instruction<'r, 'a, RT>; 256] = opcodes::jumptable::<'r, 'a, RT>();
 }
 
 pub fn execute(
@@ -516,4 +500,35 @@ mod tests {
             };
         }
     }
+
+    #[test]
+    #[cfg(feature = "trace")]
+    fn test_trace_steps() {
+        use super::opcodes::{ADD, PUSH1};
+
+        evm_unit_test! {
+            (rt) {
+                rt.start_trace();
+            }
+            (m) {
+                PUSH1;
+                0x02;
+                PUSH1;
+                0x03;
+                ADD;
+            }
+            for _ in 0..3 {
+                m.step().unwrap();
+            }
+            let trace = rt.take_trace();
+            let steps: Vec<_> = trace.iter().map(|s| (s.pc, s.opcode, s.stack_top)).collect();
+            let word = |v: u8| {
+                let mut w = [0u8; 32];
+                w[31] = v;
+                Some(w)
+            };
+            assert_eq!(vec![(0, PUSH1, None), (2, PUSH1, word(2)), (4, ADD, word(3))], steps);
+            assert!(trace.iter().all(|s| s.actor == 0 && s.gas_available == rt.gas_limit));
+        };
+    }
 }
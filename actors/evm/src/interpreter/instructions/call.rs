@@ -1,11 +1,12 @@
 #![allow(clippy::too_many_arguments)]
 
-use fil_actors_evm_shared::{address::EthAddress, uints::U256};
+use fil_actors_evm_shared::uints::U256;
 use fvm_ipld_encoding::ipld_block::IpldBlock;
 use fvm_ipld_encoding::BytesDe;
 use fvm_shared::{address::Address, sys::SendFlags, MethodNum, IPLD_RAW};
 
 use crate::interpreter::{
+    address::{operand_address, to_fil_address},
     precompiles::{is_reserved_precompile_address, PrecompileContext},
     CallKind,
 };
@@ -18,15 +19,16 @@ use {
     crate::interpreter::precompiles,
     crate::interpreter::ExecutionState,
     crate::interpreter::System,
-    crate::{DelegateCallParams, Method},
+    crate::{DelegateCallParams, Method, SponsorValueShortfallParams},
     fil_actors_runtime::runtime::Runtime,
     fil_actors_runtime::ActorError,
     fvm_shared::econ::TokenAmount,
     fvm_shared::error::ErrorNumber,
+    num_traits::Zero,
 };
 
-/// The gas granted on bare "transfers".
-const TRANSFER_GAS_LIMIT: U256 = U256::from_u64(10_000_000);
+/// The FVM gas granted on bare "transfers".
+const TRANSFER_GAS_LIMIT: u64 = 10_000_000;
 
 pub fn calldataload(
     state: &mut ExecutionState,
@@ -153,7 +155,7 @@ pub fn call_generic<RT: Runtime>(
 ) -> Result<U256, ActorError> {
     let ExecutionState { stack: _, memory, .. } = state;
 
-    let (mut gas, dst, value, input_offset, input_size, output_offset, output_size) = params;
+    let (gas, dst, value, input_offset, input_size, output_offset, output_size) = params;
 
     if system.readonly && value > U256::zero() {
         // non-zero sends are side-effects and hence a static mode violation
@@ -170,7 +172,7 @@ pub fn call_generic<RT: Runtime>(
             &[]
         };
 
-        let dst: EthAddress = dst.into();
+        let dst = operand_address(dst);
         if is_reserved_precompile_address(&dst) {
             let context = PrecompileContext {
                 call_type: kind,
@@ -199,18 +201,23 @@ pub fn call_generic<RT: Runtime>(
         } else {
             let call_result = match kind {
                 CallKind::Call | CallKind::StaticCall => {
-                    let dst_addr: Address = dst.into();
-                    if (gas == 0 && value > 0) || (gas == 2300 && value == 0) {
+                    let dst_addr = to_fil_address(&dst);
+                    let gas_limit = if (gas == 0 && value > 0) || (gas == 2300 && value == 0) {
                         // We provide enough gas for the transfer to succeed in all case.
-                        gas = TRANSFER_GAS_LIMIT;
-                    }
-                    let gas_limit = Some(effective_gas_limit(system, gas));
+                        system.subcall_gas_limit(TRANSFER_GAS_LIMIT)
+                    } else {
+                        effective_gas_limit(system, gas)
+                    };
+                    let gas_limit = Some(gas_limit);
                     let params = if input_data.is_empty() {
                         None
                     } else {
                         Some(IpldBlock { codec: IPLD_RAW, data: input_data.into() })
                     };
                     let value = TokenAmount::from(&value);
+                    if kind == CallKind::Call {
+                        request_sponsorship(system, &value)?;
+                    }
                     let send_flags = if kind == CallKind::StaticCall {
                         SendFlags::READ_ONLY
                     } else {
@@ -332,10 +339,40 @@ pub fn call_generic<RT: Runtime>(
     Ok(U256::from(call_result))
 }
 
+/// If the contract has designated a paymaster and its own balance can't cover `value`, asks the
+/// paymaster to sponsor the shortfall. A refusal is ignored: the call then proceeds and fails for
+/// lack of funds, exactly as it would have without a paymaster.
+fn request_sponsorship(
+    system: &mut System<impl Runtime>,
+    value: &TokenAmount,
+) -> Result<(), ActorError> {
+    let paymaster = match system.paymaster() {
+        Some(paymaster) => paymaster,
+        None => return Ok(()),
+    };
+    let balance = system.rt.current_balance();
+    if value <= &balance {
+        return Ok(());
+    }
+    let params =
+        IpldBlock::serialize_cbor(&SponsorValueShortfallParams { amount: value - &balance })?;
+    if let Ok(resp) = system.send_raw(
+        &Address::new_id(paymaster),
+        Method::SponsorValueShortfall as MethodNum,
+        params,
+        TokenAmount::zero(),
+        None,
+        SendFlags::default(),
+    )? {
+        if !resp.exit_code.is_success() {
+            log::debug!(target: "evm", "paymaster {paymaster} declined to sponsor: {}", resp.exit_code);
+        }
+    }
+    Ok(())
+}
+
 fn effective_gas_limit<RT: Runtime>(system: &System<RT>, gas: U256) -> u64 {
-    let gas_rsvp = (63 * system.rt.gas_available()) / 64;
-    let gas = gas.to_u64_saturating();
-    std::cmp::min(gas, gas_rsvp)
+    system.subcall_gas_limit(system.evm_to_fvm_gas(gas))
 }
 
 #[cfg(test)]
@@ -550,6 +587,42 @@ mod tests {
         };
     }
 
+    #[test]
+    fn test_codecopy_zero_fill() {
+        // bytes past the end of the code are zero, overwriting what was in memory
+        evm_unit_test! {
+            (m) {
+                CODECOPY;
+                JUMPDEST;
+            }
+            m.state.memory.grow(32);
+            m.state.memory.fill(0xff);
+            m.state.stack.push(U256::from(4)).unwrap();  // length
+            m.state.stack.push(U256::from(1)).unwrap();  // offset
+            m.state.stack.push(U256::from(0)).unwrap();  // dest-offset
+            let result = m.step();
+            assert!(result.is_ok(), "execution step failed");
+            assert_eq!(&m.state.memory[..5], &[m.bytecode[1], 0, 0, 0, 0xff]);
+        };
+    }
+
+    #[test]
+    fn test_calldatacopy_empty_large_offsets() {
+        // copying nothing never expands memory, however large the offsets
+        evm_unit_test! {
+            (m) {
+                CALLDATACOPY;
+            }
+            m.state.input_data = vec![0x00, 0x01, 0x02];
+            m.state.stack.push(U256::from(0)).unwrap();  // length
+            m.state.stack.push(U256::MAX).unwrap();      // offset
+            m.state.stack.push(U256::MAX).unwrap();      // dest-offset
+            let result = m.step();
+            assert!(result.is_ok(), "execution step failed");
+            assert!(m.state.memory.is_empty());
+        };
+    }
+
     #[test]
     fn test_call() {
         let dest = EthAddress::from_id(1001);
@@ -597,6 +670,86 @@ mod tests {
         };
     }
 
+    #[test]
+    fn test_call_gas_in_evm_units() {
+        let dest = EthAddress::from_id(1001);
+        let fil_dest = FilAddress::new_id(1001);
+        evm_unit_test! {
+            (rt) {
+                rt.in_call.replace(true);
+                rt.policy.evm_fvm_gas_per_evm_gas = 2;
+                rt.expect_send(
+                    fil_dest,
+                    crate::Method::InvokeContract as u64,
+                    None,
+                    TokenAmount::zero(),
+                    // The requested EVM gas in FVM units, capped at 63/64ths of that available.
+                    Some(9_843_750_000),
+                    SendFlags::empty(),
+                    None,
+                    ExitCode::OK,
+                    None,
+                );
+                rt.expect_gas_available(10_000_000_000);
+            }
+            (m) {
+                CALL;
+            }
+            m.state.stack.push(U256::from(0)).unwrap();  // output size
+            m.state.stack.push(U256::from(0)).unwrap();  // output offset
+            m.state.stack.push(U256::from(0)).unwrap();  // input size
+            m.state.stack.push(U256::from(0)).unwrap();  // input offset
+            m.state.stack.push(U256::from(0)).unwrap();  // value
+            m.state.stack.push(dest.as_evm_word()).unwrap();  // dest
+            m.state.stack.push(U256::from(6_000_000_000u64)).unwrap(); // gas
+            m.step().expect("execution step failed");
+            assert_eq!(m.state.stack.len(), 1);
+            assert_eq!(m.state.stack.pop().unwrap(), U256::from(1));
+        };
+    }
+
+    #[test]
+    fn test_call_forwards_remaining_gas_under_default_policy() {
+        let dest = EthAddress::from_id(1001);
+        let fil_dest = FilAddress::new_id(1001);
+        evm_unit_test! {
+            (rt) {
+                rt.in_call.replace(true);
+                assert_eq!(1, rt.policy.evm_fvm_gas_per_evm_gas);
+                // GAS reports the FVM gas remaining.
+                rt.expect_gas_available(10_000_000_000);
+                rt.expect_send(
+                    fil_dest,
+                    crate::Method::InvokeContract as u64,
+                    None,
+                    TokenAmount::zero(),
+                    // All but a 64th of the gas remaining at the call.
+                    Some(9_843_750_000),
+                    SendFlags::empty(),
+                    None,
+                    ExitCode::OK,
+                    None,
+                );
+                rt.expect_gas_available(10_000_000_000);
+            }
+            (m) {
+                GAS;
+                CALL;
+            }
+            m.state.stack.push(U256::from(0)).unwrap();  // output size
+            m.state.stack.push(U256::from(0)).unwrap();  // output offset
+            m.state.stack.push(U256::from(0)).unwrap();  // input size
+            m.state.stack.push(U256::from(0)).unwrap();  // input offset
+            m.state.stack.push(U256::from(0)).unwrap();  // value
+            m.state.stack.push(dest.as_evm_word()).unwrap();  // dest
+            m.step().expect("execution step failed");
+            assert_eq!(m.state.stack.top(), Some(&U256::from(10_000_000_000u64)));
+            m.step().expect("execution step failed");
+            assert_eq!(m.state.stack.len(), 1);
+            assert_eq!(m.state.stack.pop().unwrap(), U256::from(1));
+        };
+    }
+
     #[test]
     fn test_call_revert() {
         let dest = EthAddress::from_id(1001);
@@ -9,6 +9,8 @@ use {
     fil_actors_runtime::runtime::Runtime,
 };
 
+/// Returns the hash of the tipset at a recent epoch, i.e. the first 32 bytes of the digest of its
+/// CID, or zero if the epoch is out of range or was a null round.
 #[inline]
 pub fn blockhash(
     _state: &mut ExecutionState,
@@ -25,7 +27,7 @@ pub fn blockhash(
             let curr_epoch = system.rt.curr_epoch();
             height >= curr_epoch - 256 && height < curr_epoch
         })
-        .and_then(|height| system.rt.tipset_cid(height).ok())
+        .and_then(|height| system.tipset_cid(height))
         .map(|cid| {
             let mut hash = cid.hash().digest();
             if hash.len() > EVM_WORD_SIZE {
@@ -89,7 +91,7 @@ pub fn gas_price(
 
 #[inline]
 pub fn gas(_state: &mut ExecutionState, system: &System<impl Runtime>) -> Result<U256, ActorError> {
-    Ok(U256::from(system.rt.gas_available()))
+    Ok(U256::from(system.evm_gas_available()))
 }
 
 #[inline]
@@ -178,6 +180,9 @@ mod tests {
             ),
             (1234, 1230u16, Some(cbor_odd_hash), cbor_odd_expect, "normal-ish tipset"),
             (123, 222u16, None, nothing, "future tipset"),
+            (123, 123u16, None, nothing, "current tipset"),
+            (1234, 978u16, Some(cbor_odd_hash), cbor_odd_expect, "oldest available tipset"),
+            (1234, 977u16, Some(cbor_odd_hash), nothing, "requested older than 256 epochs"),
             (1234, 123u16, None, nothing, "requested older than finality (256)"),
         ] {
             let [a, b] = getting.to_be_bytes();
@@ -204,6 +209,36 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_blockhash_null_round() {
+        let tipset = Cid::new_v1(DAG_CBOR, Multihash::wrap(0xb220, &[0xab; 32]).unwrap());
+        for (getting, expect, test) in
+            [(1230u16, [0xabu8; 32], "tipset before null round"), (1231u16, [0; 32], "null round")]
+        {
+            let [a, b] = getting.to_be_bytes();
+            evm_unit_test! {
+                (rt) {
+                    rt.in_call.replace(true);
+                    rt.set_epoch(1234);
+                    rt.tipset_cids.resize(1234, Cid::default());
+                    // The null round at 1231 reports the CID of the tipset at 1230.
+                    rt.tipset_cids[1230] = tipset;
+                    rt.tipset_cids[1231] = tipset;
+                }
+                (m) {
+                    PUSH2;
+                    {a};
+                    {b};
+                    BLOCKHASH;
+                }
+                m.step().expect("execution step failed");
+                m.step().expect("execution step failed");
+                assert_eq!(m.state.stack.len(), 1);
+                assert_eq!(m.state.stack.pop().unwrap(), U256::from(expect), "{}", test);
+            };
+        }
+    }
+
     #[test]
     fn test_callvalue() {
         evm_unit_test! {
@@ -285,7 +320,7 @@ mod tests {
     fn test_timestamp() {
         evm_unit_test! {
             (rt) {
-                rt.tipset_timestamp = 12345;
+                rt.set_tipset_timestamp(12345);
             }
             (m) {
                 TIMESTAMP;
@@ -412,4 +447,20 @@ mod tests {
             assert_eq!(m.state.stack.pop().unwrap(), U256::from(1234000));
         };
     }
+
+    #[test]
+    fn test_gas_in_evm_units() {
+        evm_unit_test! {
+            (rt) {
+                rt.policy.evm_fvm_gas_per_evm_gas = 1000;
+                rt.expect_gas_available(1234567);
+            }
+            (m) {
+                GAS;
+            }
+            m.step().expect("execution step failed");
+            assert_eq!(m.state.stack.len(), 1);
+            assert_eq!(m.state.stack.pop().unwrap(), U256::from(1234));
+        };
+    }
 }
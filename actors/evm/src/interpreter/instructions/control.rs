@@ -8,7 +8,7 @@ use crate::{
 };
 
 use {
-    super::memory::get_memory_region,
+    super::memory::copy_to_memory,
     crate::interpreter::Bytecode,
     crate::interpreter::{ExecutionState, System},
     fil_actors_runtime::runtime::Runtime,
@@ -91,43 +91,28 @@ pub fn returndatacopy(
     input_index: U256,
     size: U256,
 ) -> Result<(), ActorError> {
-    let region = get_memory_region(&mut state.memory, mem_index, size)?;
-
-    let src: usize = input_index
+    // Unlike the other copy instructions, reading past the end of the return data is an error
+    // rather than zero-filled (EIP-211), even when nothing is copied.
+    let src: u32 = input_index.try_into().context_code(
+        EVM_CONTRACT_ILLEGAL_MEMORY_ACCESS,
+        "returndatacopy offset exceeds max u32",
+    )?;
+    let len: u32 = size
         .try_into()
-        .context_code(EVM_CONTRACT_ILLEGAL_MEMORY_ACCESS, "returndatacopy index exceeds max u32")?;
-    if src > state.return_data.len() {
-        return Err(ActorError::unchecked(
-            EVM_CONTRACT_ILLEGAL_MEMORY_ACCESS,
-            format!(
-                "returndatacopy start {} exceeds return-data length {}",
-                src,
-                state.return_data.len()
-            ),
-        ));
-    }
-
-    let end = src
-        .checked_add(region.as_ref().map(|r| r.size.get()).unwrap_or(0))
-        .context_code(EVM_CONTRACT_ILLEGAL_MEMORY_ACCESS, "returndatacopy end exceeds max u32")?;
-
-    if end > state.return_data.len() {
+        .context_code(EVM_CONTRACT_ILLEGAL_MEMORY_ACCESS, "returndatacopy size exceeds max u32")?;
+    let end = src as u64 + len as u64;
+    if end > state.return_data.len() as u64 {
         return Err(ActorError::unchecked(
             EVM_CONTRACT_ILLEGAL_MEMORY_ACCESS,
             format!(
                 "returndatacopy end {} exceeds return-data length {}",
-                src,
+                end,
                 state.return_data.len()
             ),
         ));
     }
 
-    if let Some(region) = region {
-        state.memory[region.offset..region.offset + region.size.get()]
-            .copy_from_slice(&state.return_data[src..src + region.size.get()]);
-    }
-
-    Ok(())
+    copy_to_memory(&mut state.memory, mem_index, size, input_index, &state.return_data, true)
 }
 
 #[inline]
@@ -402,6 +387,58 @@ mod tests {
         };
     }
 
+    #[test]
+    fn test_returndatacopy_empty_at_end() {
+        // copying nothing from the end of the return data is allowed, and doesn't touch memory
+        evm_unit_test! {
+            (m) {
+                RETURNDATACOPY;
+            }
+            m.state.return_data = vec![0x00, 0x01, 0x02];
+            m.state.stack.push(U256::from(0)).unwrap();         // length
+            m.state.stack.push(U256::from(3)).unwrap();         // offset
+            m.state.stack.push(U256::from(u64::MAX)).unwrap();  // dest-offset
+            let result = m.step();
+            assert!(result.is_ok(), "execution step failed");
+            assert!(m.state.memory.is_empty());
+        };
+    }
+
+    #[test]
+    fn test_returndatacopy_empty_past_end() {
+        // copying nothing from past the end of the return data is still out of bounds (EIP-211)
+        evm_unit_test! {
+            (m) {
+                RETURNDATACOPY;
+            }
+            m.state.return_data = vec![0x00, 0x01, 0x02];
+            m.state.stack.push(U256::from(0)).unwrap();  // length
+            m.state.stack.push(U256::from(4)).unwrap();  // offset
+            m.state.stack.push(U256::from(0)).unwrap();  // dest-offset
+            let result = m.step();
+            assert_eq!(result.err().unwrap().exit_code(), EVM_CONTRACT_ILLEGAL_MEMORY_ACCESS);
+            assert!(m.state.memory.is_empty());
+        };
+    }
+
+    #[test]
+    fn test_returndatacopy_overwrites_memory() {
+        evm_unit_test! {
+            (m) {
+                RETURNDATACOPY;
+            }
+            m.state.memory.grow(32);
+            m.state.memory.fill(0xff);
+            m.state.return_data = vec![0x00, 0x01, 0x02];
+            m.state.stack.push(U256::from(3)).unwrap();  // length
+            m.state.stack.push(U256::from(0)).unwrap();  // offset
+            m.state.stack.push(U256::from(1)).unwrap();  // dest-offset
+            let result = m.step();
+            assert!(result.is_ok(), "execution step failed");
+            assert_eq!(&m.state.memory[..5], &[0xff, 0x00, 0x01, 0x02, 0xff]);
+        };
+    }
+
     #[test]
     fn test_return() {
         evm_unit_test! {
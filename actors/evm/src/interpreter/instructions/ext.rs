@@ -1,3 +1,4 @@
+use crate::interpreter::address::{operand_address, resolve_actor};
 use crate::interpreter::instructions::memory::copy_to_memory;
 use crate::interpreter::precompiles::Precompiles;
 use crate::BytecodeHash;
@@ -25,7 +26,7 @@ pub fn extcodesize(
     // TODO (M2.2) we're fetching the entire block here just to get its size. We should instead use
     //  the ipld::block_stat syscall, but the Runtime nor the Blockstore expose it.
     //  Tracked in https://github.com/filecoin-project/ref-fvm/issues/867
-    let len = match get_contract_type(system.rt, &addr.into()) {
+    let len = match get_contract_type(system.rt, &operand_address(addr)) {
         ContractType::EVM(addr) => {
             get_evm_bytecode(system, &addr).map(|bytecode| bytecode.len())?
         }
@@ -42,7 +43,7 @@ pub fn extcodehash(
     system: &mut System<impl Runtime>,
     addr: U256,
 ) -> Result<U256, ActorError> {
-    let addr = match get_contract_type(system.rt, &addr.into()) {
+    let addr = match get_contract_type(system.rt, &operand_address(addr)) {
         ContractType::EVM(a) => a,
         // _Technically_ since we have native "bytecode" set as 0xfe this is valid, though we cant differentiate between different native actors.
         ContractType::Native(_) => return Ok(BytecodeHash::NATIVE_ACTOR.into()),
@@ -77,7 +78,7 @@ pub fn extcodecopy(
     data_offset: U256,
     size: U256,
 ) -> Result<(), ActorError> {
-    let bytecode = match get_contract_type(system.rt, &addr.into()) {
+    let bytecode = match get_contract_type(system.rt, &operand_address(addr)) {
         ContractType::EVM(addr) => get_evm_bytecode(system, &addr)?,
         ContractType::NotFound | ContractType::Account | ContractType::Precompile => Vec::new(),
         // calling EXTCODECOPY on native actors results with a single byte 0xFE which solidtiy uses for its `assert`/`throw` methods
@@ -107,8 +108,7 @@ pub fn get_contract_type<RT: Runtime>(rt: &RT, addr: &EthAddress) -> ContractTyp
         return ContractType::Precompile;
     }
 
-    let addr: Address = addr.into();
-    rt.resolve_address(&addr) // resolve actor id
+    resolve_actor(rt, addr) // resolve actor id
         .and_then(|id| rt.get_actor_code_cid(&id).map(|cid| (id, cid))) // resolve code cid
         .map(|(id, cid)| match rt.resolve_builtin_actor_type(&cid) {
             // TODO part of current account abstraction hack where placeholders are accounts
@@ -1,16 +1,17 @@
-use fil_actors_evm_shared::address::EthAddress;
 use fil_actors_evm_shared::uints::U256;
 use fil_actors_runtime::ActorError;
 use fil_actors_runtime::EAM_ACTOR_ADDR;
 use fil_actors_runtime::{deserialize_block, extract_send_result};
 use fvm_ipld_encoding::ipld_block::IpldBlock;
 use fvm_ipld_encoding::BytesDe;
+use fvm_shared::econ::TokenAmount;
 use fvm_shared::sys::SendFlags;
 use fvm_shared::MethodNum;
 use fvm_shared::METHOD_SEND;
-use fvm_shared::{address::Address, econ::TokenAmount};
 
 use crate::ext::eam;
+use crate::interpreter::address::{operand_address, to_fil_address};
+use crate::interpreter::system::check_initcode_size;
 use crate::interpreter::Output;
 use crate::EVM_CONTRACT_SELFDESTRUCT_FAILED;
 
@@ -40,6 +41,7 @@ pub fn create(
     } else {
         &[]
     };
+    check_initcode_size(input_data.len())?;
 
     // We increment the nonce earlier than in the EVM. See the comment in `create2` for details.
     let params = eam::CreateParams { code: input_data.to_vec(), nonce: system.nonce };
@@ -74,6 +76,7 @@ pub fn create2(
     } else {
         &[]
     };
+    check_initcode_size(input_data.len())?;
     let params = eam::Create2Params { code: input_data.to_vec(), salt };
 
     create_common(
@@ -118,7 +121,7 @@ fn create_common(
     system.increment_nonce();
 
     // Apply EIP-150
-    let gas_limit = (63 * system.rt.gas_available()) / 64;
+    let gas_limit = system.subcall_gas_limit(u64::MAX);
 
     // send bytecode & params to EAM to generate the address and contract
     let ret = system.send(
@@ -165,8 +168,7 @@ pub fn selfdestruct(
     // 1. The target address is an embedded ID address and said actor doesn't exist.
     // 2. We're at the maximum call depth.
     // 3. This call would cause us to exceed some system limit (e.g., a memory limit).
-    let beneficiary: EthAddress = beneficiary.into();
-    let beneficiary: Address = beneficiary.into();
+    let beneficiary = to_fil_address(&operand_address(beneficiary));
     let balance = system.rt.current_balance();
     extract_send_result(system.rt.send_simple(&beneficiary, METHOD_SEND, None, balance)).map_err(
         |e| {
@@ -179,8 +181,11 @@ pub fn selfdestruct(
         },
     )?;
 
-    // Now mark ourselves as deleted.
-    system.mark_selfdestructed();
+    // Now mark ourselves as deleted, but only if we were created in this transaction (EIP-6780).
+    // Otherwise, SELFDESTRUCT only sends away our funds.
+    if system.created_in_current_transaction() {
+        system.mark_selfdestructed();
+    }
 
     // And "return".
     //
@@ -618,6 +623,7 @@ mod tests {
             (m) {
                 SELFDESTRUCT;
             }
+            m.system.created_in = Some(crate::Tombstone { origin: 1001, nonce: 0 });
             m.state.stack.push(beneficiary.as_evm_word()).unwrap();
             m.step().expect("execution step failed");
             assert!(m.system.tombstone.is_some());
@@ -629,6 +635,39 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_selfdestruct_after_creation_transaction() {
+        // outside the transaction that created the contract, selfdestruct only sends funds away
+        let beneficiary = EthAddress::from_id(1001);
+        let fil_beneficiary = FilAddress::new_id(1001);
+
+        evm_unit_test! {
+            (rt) {
+                rt.set_balance(TokenAmount::from_atto(1_000_000));
+                rt.set_origin(fil_beneficiary);
+
+                rt.expect_send(
+                    fil_beneficiary,
+                    METHOD_SEND,
+                    None,
+                    TokenAmount::from_atto(1_000_000),
+                    None,
+                    SendFlags::empty(),
+                    None,
+                    ExitCode::OK,
+                    None,
+                );
+            }
+            (m) {
+                SELFDESTRUCT;
+            }
+            m.system.created_in = Some(crate::Tombstone { origin: 1001, nonce: 1 });
+            m.state.stack.push(beneficiary.as_evm_word()).unwrap();
+            m.step().expect("execution step failed");
+            assert!(m.system.tombstone.is_none());
+        }
+    }
+
     #[test]
     fn test_selfdestruct_fail() {
         // tests the outcome of selfdestruct
@@ -1,4 +1,5 @@
 use crate::interpreter::instructions::memory::get_memory_region;
+use crate::EVM_CONTRACT_LOG_LIMIT_EXCEEDED;
 use fil_actors_evm_shared::uints::U256;
 use fil_actors_runtime::ActorError;
 use fvm_ipld_encoding::IPLD_RAW;
@@ -8,6 +9,14 @@ use {
     fil_actors_runtime::runtime::Runtime,
 };
 
+// An Ethereum log is emitted as an FVM event with one entry per topic followed by one for the
+// data, all with the raw codec:
+//
+// - topic i (1 to 4) is keyed "t<i>" and holds the 32-byte big-endian topic. Topics are indexed
+//   by key and value, so indexers can filter logs by topic without decoding the event.
+// - the data is keyed "d" and holds the log data verbatim. It is not indexed, and is omitted if
+//   empty.
+
 /// The event key for the Ethereum log data.
 const EVENT_DATA_KEY: &str = "d";
 
@@ -17,7 +26,7 @@ const EVENT_TOPIC_KEYS: &[&str] = &["t1", "t2", "t3", "t4"];
 #[inline]
 pub fn log(
     state: &mut ExecutionState,
-    system: &System<impl Runtime>,
+    system: &mut System<impl Runtime>,
     num_topics: usize,
     mem_index: U256,
     size: U256,
@@ -27,6 +36,24 @@ pub fn log(
         return Err(ActorError::read_only("log called while read-only".into()));
     }
 
+    // Bound the events a single invocation may hand to the event index.
+    let policy = system.rt.policy();
+    if system.logs_emitted >= policy.evm_max_logs_per_invocation {
+        return Err(ActorError::unchecked(
+            EVM_CONTRACT_LOG_LIMIT_EXCEEDED,
+            format!("invocation exceeded the limit of {} logs", policy.evm_max_logs_per_invocation),
+        ));
+    }
+    if size > U256::from(policy.evm_max_log_data_size) {
+        return Err(ActorError::unchecked(
+            EVM_CONTRACT_LOG_LIMIT_EXCEEDED,
+            format!(
+                "log data size {} exceeds the limit of {} bytes",
+                size, policy.evm_max_log_data_size
+            ),
+        ));
+    }
+
     // Handle the data.
     // Passing in a zero-sized memory region omits the data key entirely.
     // LOG0 + a zero-sized memory region emits an event with no entries whatsoever. In this case,
@@ -48,11 +75,11 @@ pub fn log(
         entries.push(entry);
     }
 
-    // Skip adding the data if it's zero-sized.
+    // Skip adding the data if it's zero-sized. The data is opaque to indexers, so it isn't indexed.
     if let Some(r) = region {
         let data = state.memory[r.offset..r.offset + r.size.get()].to_vec();
         let entry = Entry {
-            flags: Flags::FLAG_INDEXED_ALL,
+            flags: Flags::empty(),
             key: EVENT_DATA_KEY.to_owned(),
             codec: IPLD_RAW,
             value: data,
@@ -61,6 +88,7 @@ pub fn log(
     }
 
     system.rt.emit_event(&entries.into())?;
+    system.logs_emitted += 1;
 
     Ok(())
 }
@@ -72,7 +100,7 @@ mod tests {
     use fvm_shared::event::{ActorEvent, Entry, Flags};
 
     use super::{EVENT_DATA_KEY, EVENT_TOPIC_KEYS};
-    use crate::evm_unit_test;
+    use crate::{evm_unit_test, EVM_CONTRACT_LOG_LIMIT_EXCEEDED};
 
     #[test]
     fn test_log0() {
@@ -85,7 +113,7 @@ mod tests {
                 data[31] = 0xBE;
                 rt.expect_emitted_event(
                     ActorEvent::from(vec![Entry{
-                        flags: Flags::FLAG_INDEXED_ALL,
+                        flags: Flags::empty(),
                         key: EVENT_DATA_KEY.to_owned(),
                         codec: IPLD_RAW,
                         value: data.into(),
@@ -125,7 +153,7 @@ mod tests {
                             value: t1.to_bytes().into(),
                         },
                         Entry{
-                            flags: Flags::FLAG_INDEXED_ALL,
+                            flags: Flags::empty(),
                             key: EVENT_DATA_KEY.to_owned(),
                             codec: IPLD_RAW,
                             value: data.into(),
@@ -174,7 +202,7 @@ mod tests {
                             value: t2.to_bytes().into(),
                         },
                         Entry{
-                            flags: Flags::FLAG_INDEXED_ALL,
+                            flags: Flags::empty(),
                             key: EVENT_DATA_KEY.to_owned(),
                             codec: IPLD_RAW,
                             value: data.into(),
@@ -231,7 +259,7 @@ mod tests {
                             value: t3.to_bytes().into(),
                         },
                         Entry{
-                            flags: Flags::FLAG_INDEXED_ALL,
+                            flags: Flags::empty(),
                             key: EVENT_DATA_KEY.to_owned(),
                             codec: IPLD_RAW,
                             value: data.into(),
@@ -296,7 +324,7 @@ mod tests {
                             value: t4.to_bytes().into(),
                         },
                         Entry{
-                            flags: Flags::FLAG_INDEXED_ALL,
+                            flags: Flags::empty(),
                             key: EVENT_DATA_KEY.to_owned(),
                             codec: IPLD_RAW,
                             value: data.into(),
@@ -322,4 +350,42 @@ mod tests {
             assert!(result.is_ok(), "execution step failed");
         };
     }
+
+    #[test]
+    fn test_log_data_size_limit() {
+        evm_unit_test! {
+            (rt) {
+                rt.policy.evm_max_log_data_size = 0x1f;
+            }
+            (m) {
+                PUSH1; 0x20;
+                PUSH0;
+                LOG0;
+            }
+
+            let result = m.execute();
+            assert_eq!(result.unwrap_err().exit_code(), EVM_CONTRACT_LOG_LIMIT_EXCEEDED);
+        };
+    }
+
+    #[test]
+    fn test_logs_per_invocation_limit() {
+        evm_unit_test! {
+            (rt) {
+                rt.policy.evm_max_logs_per_invocation = 1;
+                rt.expect_emitted_event(ActorEvent::from(vec![]));
+            }
+            (m) {
+                PUSH0;
+                PUSH0;
+                LOG0;
+                PUSH0;
+                PUSH0;
+                LOG0;
+            }
+
+            let result = m.execute();
+            assert_eq!(result.unwrap_err().exit_code(), EVM_CONTRACT_LOG_LIMIT_EXCEEDED);
+        };
+    }
 }
@@ -44,6 +44,12 @@ pub fn get_memory_region(
     let new_size: u32 = offset
         .checked_add(size)
         .context_code(EVM_CONTRACT_ILLEGAL_MEMORY_ACCESS, "new memory size exceeds max u32")?;
+    if new_size as usize > mem.limit() {
+        return Err(ActorError::unchecked(
+            EVM_CONTRACT_ILLEGAL_MEMORY_ACCESS,
+            format!("new memory size {} exceeds limit {}", new_size, mem.limit()),
+        ));
+    }
 
     mem.grow(new_size as usize);
 
@@ -95,6 +101,16 @@ pub fn copy_within_memory(
     Ok(())
 }
 
+/// Copies `data[data_offset..data_offset + dest_size]` into memory at `dest_offset`, expanding
+/// memory to cover the destination region. This is the copy used by all the copy instructions.
+///
+/// - An empty destination region is a no-op: memory isn't expanded and the offsets aren't
+///   checked, however large they are.
+/// - A destination region beyond the supported memory size is an illegal memory access.
+/// - Bytes past the end of `data`, including when `data_offset` is past the end, are zero when
+///   `zero_fill` is set, and otherwise leave the memory as it was (as for call output).
+///
+/// Callers that must reject reads past the end of `data` (RETURNDATACOPY) check the bounds first.
 pub fn copy_to_memory(
     memory: &mut Memory,
     dest_offset: U256,
@@ -203,6 +219,31 @@ mod tests {
         assert_eq!(result.unwrap_err().exit_code(), EVM_CONTRACT_ILLEGAL_MEMORY_ACCESS);
     }
 
+    #[test]
+    fn memory_region_limit() {
+        let mut mem = Memory::with_limit(1024);
+
+        // Memory may grow to exactly its limit, but not beyond.
+        let region = get_memory_region(&mut mem, 1000, 24).unwrap().unwrap();
+        assert_eq!((region.offset, region.size.get()), (1000, 24));
+        assert_eq!(mem.len(), 1024);
+        let err = get_memory_region(&mut mem, 1000, 25).unwrap_err();
+        assert_eq!(err.exit_code(), EVM_CONTRACT_ILLEGAL_MEMORY_ACCESS);
+        let err = get_memory_region(&mut mem, 1025, 1).unwrap_err();
+        assert_eq!(err.exit_code(), EVM_CONTRACT_ILLEGAL_MEMORY_ACCESS);
+        assert_eq!(mem.len(), 1024);
+
+        // Empty regions never grow memory, so aren't limited.
+        assert!(get_memory_region(&mut mem, 1 << 20, 0).unwrap().is_none());
+
+        // Memory grows by whole words, so may exceed a limit that isn't word-aligned.
+        let mut mem = Memory::with_limit(1000);
+        assert!(get_memory_region(&mut mem, 999, 1).unwrap().is_some());
+        assert_eq!(mem.len(), 1024);
+        let err = get_memory_region(&mut mem, 1000, 1).unwrap_err();
+        assert_eq!(err.exit_code(), EVM_CONTRACT_ILLEGAL_MEMORY_ACCESS);
+    }
+
     #[test]
     fn copy_to_memory_zero() {
         let mut mem: Memory = Default::default();
@@ -242,6 +283,73 @@ mod tests {
         assert_eq!(&mem[0..4], result_data);
     }
 
+    #[test]
+    fn copy_to_memory_empty_region_ignores_offsets() {
+        // An empty copy never expands memory, whatever the offsets (e.g. calldatacopy_DataIndexTooHigh).
+        let mut mem: Memory = Default::default();
+        let result = copy_to_memory(&mut mem, U256::MAX, U256::zero(), U256::MAX, &[1, 2, 3], true);
+        assert_eq!(result, Ok(()));
+        assert!(mem.is_empty());
+    }
+
+    #[test]
+    fn copy_to_memory_data_offset_past_end() {
+        let data = &[1u8, 2u8, 3u8];
+        for data_offset in [U256::from(3), U256::from(4), U256::from(u64::MAX), U256::MAX] {
+            let mut mem: Memory = Default::default();
+            mem.grow(32);
+            mem.fill(0xff);
+            let result =
+                copy_to_memory(&mut mem, U256::zero(), U256::from(32), data_offset, data, true);
+            assert_eq!(result, Ok(()));
+            assert_eq!(&*mem, &[0u8; 32]);
+        }
+    }
+
+    #[test]
+    fn copy_to_memory_zero_fills_tail() {
+        let data = &[1u8, 2u8];
+        let mut mem: Memory = Default::default();
+        mem.grow(32);
+        mem.fill(0xff);
+        let result =
+            copy_to_memory(&mut mem, U256::from(1), U256::from(4), U256::zero(), data, true);
+        assert_eq!(result, Ok(()));
+        assert_eq!(&mem[..6], &[0xff, 1, 2, 0, 0, 0xff]);
+    }
+
+    #[test]
+    fn copy_to_memory_without_zero_fill() {
+        let data = &[1u8, 2u8];
+        let mut mem: Memory = Default::default();
+        mem.grow(32);
+        mem.fill(0xff);
+        let result =
+            copy_to_memory(&mut mem, U256::from(1), U256::from(4), U256::zero(), data, false);
+        assert_eq!(result, Ok(()));
+        assert_eq!(&mem[..6], &[0xff, 1, 2, 0xff, 0xff, 0xff]);
+    }
+
+    #[test]
+    fn copy_to_memory_expands_memory() {
+        // Memory grows to the end of the destination region, rounded up to a word.
+        let mut mem: Memory = Default::default();
+        let result =
+            copy_to_memory(&mut mem, U256::from(31), U256::from(2), U256::zero(), &[], true);
+        assert_eq!(result, Ok(()));
+        assert_eq!(&*mem, &[0u8; 64]);
+    }
+
+    #[test]
+    fn copy_to_memory_dest_overflow() {
+        for (offset, size) in [(U256::from(u32::MAX), U256::one()), (U256::MAX, U256::one())] {
+            let mut mem: Memory = Default::default();
+            let result = copy_to_memory(&mut mem, offset, size, U256::zero(), &[1], true);
+            assert_eq!(result.unwrap_err().exit_code(), EVM_CONTRACT_ILLEGAL_MEMORY_ACCESS);
+            assert!(mem.is_empty());
+        }
+    }
+
     #[test]
     fn test_mcopy() {
         const LENGTH: usize = 2;
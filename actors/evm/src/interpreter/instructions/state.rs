@@ -1,8 +1,8 @@
-use fil_actors_evm_shared::{address::EthAddress, uints::U256};
+use fil_actors_evm_shared::uints::U256;
 use fil_actors_runtime::ActorError;
-use fvm_shared::address::Address;
 
 use {
+    crate::interpreter::address::{operand_address, resolve_actor},
     crate::interpreter::{ExecutionState, System},
     fil_actors_runtime::runtime::Runtime,
 };
@@ -13,12 +13,7 @@ pub fn balance(
     system: &System<impl Runtime>,
     actor: U256,
 ) -> Result<U256, ActorError> {
-    let addr: EthAddress = actor.into();
-    let addr: Address = addr.into();
-
-    let balance = system
-        .rt
-        .resolve_address(&addr)
+    let balance = resolve_actor(system.rt, &operand_address(actor))
         .and_then(|id| system.rt.actor_balance(id).as_ref().map(U256::from))
         .unwrap_or_default();
 
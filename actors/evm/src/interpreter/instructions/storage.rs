@@ -27,7 +27,12 @@ pub fn sstore(
         return Err(ActorError::read_only("store called while read-only".into()));
     }
 
-    system.set_storage(key, value)
+    let status = system.set_storage(key, value)?;
+    #[cfg(feature = "trace")]
+    system.rt.trace_storage_status(status);
+    #[cfg(not(feature = "trace"))]
+    let _ = status;
+    Ok(())
 }
 
 #[inline]
@@ -107,6 +112,59 @@ mod tests {
         };
     }
 
+    #[test]
+    fn test_sstore_status() {
+        use crate::interpreter::StorageStatus;
+
+        evm_unit_test! {
+            (m) {
+                STOP;
+            }
+            let (key, a, b, zero) = (U256::from(0), U256::from(1), U256::from(2), U256::zero());
+            assert_eq!(m.system.set_storage(key, zero).unwrap(), StorageStatus::Unchanged);
+            assert_eq!(m.system.set_storage(key, a).unwrap(), StorageStatus::Added);
+            assert_eq!(m.system.set_storage(key, a).unwrap(), StorageStatus::Unchanged);
+            assert_eq!(m.system.set_storage(key, b).unwrap(), StorageStatus::Assigned);
+            assert_eq!(m.system.set_storage(key, zero).unwrap(), StorageStatus::Restored);
+        };
+    }
+
+    #[test]
+    #[cfg(feature = "trace")]
+    fn test_sstore_status_traced() {
+        use crate::interpreter::execution::opcodes::SSTORE;
+        use crate::interpreter::StorageStatus;
+
+        evm_unit_test! {
+            (rt) {
+                rt.start_trace();
+            }
+            (m) {
+                PUSH1;
+                0x01;
+                PUSH1;
+                0x00;
+                SSTORE;
+                PUSH1;
+                0x00;
+                PUSH1;
+                0x00;
+                SSTORE;
+            }
+            for _ in 0..6 {
+                m.step().unwrap();
+            }
+            let trace = rt.take_trace();
+            let statuses: Vec<_> = trace
+                .iter()
+                .filter(|s| s.opcode == SSTORE)
+                .map(|s| s.storage_status)
+                .collect();
+            assert_eq!(vec![Some(StorageStatus::Added), Some(StorageStatus::Restored)], statuses);
+            assert!(trace.iter().filter(|s| s.opcode != SSTORE).all(|s| s.storage_status.is_none()));
+        };
+    }
+
     #[test]
     fn test_tload() {
         // happy path
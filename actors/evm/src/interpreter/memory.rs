@@ -4,33 +4,47 @@ use std::ops::{Deref, DerefMut};
 const PAGE_SIZE: usize = 4 * 1024;
 
 #[derive(Clone, Debug)]
-pub struct Memory(Vec<u8>);
+pub struct Memory {
+    buf: Vec<u8>,
+    /// Maximum size in bytes to which memory may grow.
+    limit: usize,
+}
 
 impl Deref for Memory {
     type Target = [u8];
 
     fn deref(&self) -> &Self::Target {
-        &self.0
+        &self.buf
     }
 }
 
 impl DerefMut for Memory {
     fn deref_mut(&mut self) -> &mut Self::Target {
-        &mut self.0
+        &mut self.buf
     }
 }
 
 impl Default for Memory {
     fn default() -> Self {
-        Self(Vec::with_capacity(PAGE_SIZE))
+        Self::with_limit(u32::MAX as usize)
     }
 }
 
 impl Memory {
+    /// Creates empty memory which may grow to at most `limit` bytes.
+    pub fn with_limit(limit: usize) -> Self {
+        Self { buf: Vec::with_capacity(PAGE_SIZE), limit }
+    }
+
+    /// The maximum size in bytes to which memory may grow.
+    pub fn limit(&self) -> usize {
+        self.limit
+    }
+
     #[inline]
     /// Reserve extra pages of memory
     fn reserve_pages(&mut self, pages: usize) {
-        self.0.reserve((PAGE_SIZE * pages) - self.0.len());
+        self.buf.reserve((PAGE_SIZE * pages) - self.buf.len());
     }
 
     #[inline]
@@ -51,7 +65,7 @@ impl Memory {
         }
 
         // Reserve any new pages.
-        let cap = self.0.capacity();
+        let cap = self.buf.capacity();
         if new_size > cap {
             let required_pages = (new_size + PAGE_SIZE - 1) / PAGE_SIZE;
             self.reserve_pages(required_pages);
@@ -59,7 +73,7 @@ impl Memory {
 
         debug_assert_eq!(new_size % 32, 0, "MSIZE depends that memory is aligned to 32 bytes");
         // Grow to new aligned size.
-        self.0.resize(new_size, 0);
+        self.buf.resize(new_size, 0);
     }
 }
 
@@ -72,6 +86,6 @@ mod tests {
         let mut mem = Memory::default();
         mem.grow(PAGE_SIZE * 2 + 1);
         assert_eq!(mem.len(), PAGE_SIZE * 2 + EVM_WORD_SIZE);
-        assert_eq!(mem.0.capacity(), PAGE_SIZE * 3);
+        assert_eq!(mem.buf.capacity(), PAGE_SIZE * 3);
     }
 }
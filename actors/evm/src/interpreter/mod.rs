@@ -1,3 +1,4 @@
+mod address;
 mod bytecode;
 mod execution;
 mod instructions;
@@ -13,8 +14,9 @@ pub mod test_util;
 pub use {
     bytecode::Bytecode,
     execution::{execute, opcodes, ExecutionState},
+    memory::Memory,
     output::{Outcome, Output},
-    system::System,
+    system::{StorageStatus, System, LEGACY_STORAGE_BIT_WIDTH},
 };
 
 /// The kind of call-like instruction.
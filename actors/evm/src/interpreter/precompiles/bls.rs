@@ -0,0 +1,425 @@
+//! BLS12-381 curve operations, as specified by https://eips.ethereum.org/EIPS/eip-2537
+//!
+//! Field elements are encoded as 64 byte big-endian integers, of which the top 16 bytes must be
+//! zero. Points are encoded as their affine coordinates (with the components of an Fp2 element
+//! ordered c0, c1), and the point at infinity as all zeros. Scalars are 32 byte big-endian
+//! integers, which need not be reduced.
+
+use bls12_381::{
+    multi_miller_loop, G1Affine, G1Projective, G2Affine, G2Prepared, G2Projective, Gt, Scalar,
+};
+use fil_actors_runtime::runtime::Runtime;
+
+use crate::interpreter::System;
+
+use super::{charge_evm_gas, PrecompileContext, PrecompileError, PrecompileResult};
+
+/// Length of an encoded field element.
+const FP_LEN: usize = 64;
+/// Length of a field element once its padding is stripped.
+const FP_UNPADDED_LEN: usize = 48;
+const G1_LEN: usize = 2 * FP_LEN;
+const G2_LEN: usize = 4 * FP_LEN;
+const SCALAR_LEN: usize = 32;
+const PAIR_LEN: usize = G1_LEN + G2_LEN;
+
+const G1_ADD_GAS: u64 = 375;
+const G2_ADD_GAS: u64 = 600;
+const G1_MUL_GAS: u64 = 12000;
+const G2_MUL_GAS: u64 = 22500;
+const PAIRING_BASE_GAS: u64 = 37700;
+const PAIRING_PER_PAIR_GAS: u64 = 32600;
+
+/// Discounts (per mille) of a multi-scalar multiplication of k points, indexed by k-1. The last
+/// entry applies to any larger k.
+const G1_MSM_DISCOUNT: [u64; 128] = [
+    1000, 949, 848, 797, 764, 750, 738, 728, 719, 712, 705, 698, 692, 687, 682, 677, 673, 669, 665,
+    661, 658, 654, 651, 648, 645, 642, 640, 637, 635, 632, 630, 627, 625, 623, 621, 619, 617, 615,
+    613, 611, 609, 608, 606, 604, 603, 601, 599, 598, 596, 595, 593, 592, 591, 589, 588, 586, 585,
+    584, 582, 581, 580, 579, 577, 576, 575, 574, 573, 572, 570, 569, 568, 567, 566, 565, 564, 563,
+    562, 561, 560, 559, 558, 557, 556, 555, 554, 553, 552, 551, 550, 549, 548, 547, 547, 546, 545,
+    544, 543, 542, 541, 540, 540, 539, 538, 537, 536, 536, 535, 534, 533, 532, 532, 531, 530, 529,
+    528, 528, 527, 526, 525, 525, 524, 523, 522, 522, 521, 520, 520, 519,
+];
+const G2_MSM_DISCOUNT: [u64; 128] = [
+    1000, 1000, 923, 884, 855, 832, 812, 796, 782, 770, 759, 749, 740, 732, 724, 717, 711, 704,
+    699, 693, 688, 683, 679, 674, 670, 666, 663, 659, 655, 652, 649, 646, 643, 640, 637, 634, 632,
+    629, 627, 624, 622, 620, 618, 615, 613, 611, 609, 607, 606, 604, 602, 600, 598, 597, 595, 593,
+    592, 590, 589, 587, 586, 584, 583, 582, 580, 579, 578, 576, 575, 574, 573, 571, 570, 569, 568,
+    567, 566, 565, 563, 562, 561, 560, 559, 558, 557, 556, 555, 554, 553, 552, 552, 551, 550, 549,
+    548, 547, 546, 545, 545, 544, 543, 542, 541, 541, 540, 539, 538, 537, 537, 536, 535, 535, 534,
+    533, 532, 532, 531, 530, 530, 529, 528, 528, 527, 526, 526, 525, 524, 524,
+];
+
+/// Computes the EVM gas cost of a multi-scalar multiplication of k points.
+fn msm_gas(k: usize, mul_gas: u64, discounts: &[u64; 128]) -> u64 {
+    if k == 0 {
+        return 0;
+    }
+    let discount = discounts[k.min(discounts.len()) - 1];
+    (k as u64).saturating_mul(mul_gas).saturating_mul(discount) / 1000
+}
+
+/// Strips the padding from an encoded field element.
+fn read_fp(input: &[u8]) -> Result<&[u8], PrecompileError> {
+    let (padding, fp) = input.split_at(FP_LEN - FP_UNPADDED_LEN);
+    // The top three bits of a field element are never set, and the bls12_381 crate would
+    // otherwise interpret them as flags.
+    if padding.iter().any(|b| *b != 0) || fp[0] & 0xe0 != 0 {
+        return Err(PrecompileError::InvalidInput);
+    }
+    Ok(fp)
+}
+
+/// Writes a field element serialized by the bls12_381 crate, with padding.
+fn write_fp(output: &mut [u8], fp: &[u8]) {
+    output[FP_LEN - FP_UNPADDED_LEN..FP_LEN].copy_from_slice(fp);
+}
+
+/// Reads a point on G1, which must be on the curve, and optionally in the correct subgroup.
+fn read_g1(input: &[u8], subgroup_check: bool) -> Result<G1Affine, PrecompileError> {
+    let mut bytes = [0u8; 2 * FP_UNPADDED_LEN];
+    bytes[..FP_UNPADDED_LEN].copy_from_slice(read_fp(&input[..FP_LEN])?);
+    bytes[FP_UNPADDED_LEN..].copy_from_slice(read_fp(&input[FP_LEN..G1_LEN])?);
+    if bytes.iter().all(|b| *b == 0) {
+        return Ok(G1Affine::identity());
+    }
+    let point: G1Affine = Option::from(G1Affine::from_uncompressed_unchecked(&bytes))
+        .ok_or(PrecompileError::InvalidInput)?;
+    if !bool::from(point.is_on_curve()) || (subgroup_check && !bool::from(point.is_torsion_free()))
+    {
+        return Err(PrecompileError::InvalidInput);
+    }
+    Ok(point)
+}
+
+/// Reads a point on G2, which must be on the curve, and optionally in the correct subgroup.
+fn read_g2(input: &[u8], subgroup_check: bool) -> Result<G2Affine, PrecompileError> {
+    // The bls12_381 crate orders the components of each coordinate c1, c0.
+    let mut bytes = [0u8; 4 * FP_UNPADDED_LEN];
+    for (i, fp) in input[..G2_LEN].chunks_exact(FP_LEN).enumerate() {
+        let offset = (i ^ 1) * FP_UNPADDED_LEN;
+        bytes[offset..offset + FP_UNPADDED_LEN].copy_from_slice(read_fp(fp)?);
+    }
+    if bytes.iter().all(|b| *b == 0) {
+        return Ok(G2Affine::identity());
+    }
+    let point: G2Affine = Option::from(G2Affine::from_uncompressed_unchecked(&bytes))
+        .ok_or(PrecompileError::InvalidInput)?;
+    if !bool::from(point.is_on_curve()) || (subgroup_check && !bool::from(point.is_torsion_free()))
+    {
+        return Err(PrecompileError::InvalidInput);
+    }
+    Ok(point)
+}
+
+/// Reads a scalar, reducing it modulo the group order.
+fn read_scalar(input: &[u8]) -> Scalar {
+    let mut wide = [0u8; 64];
+    for (dst, src) in wide.iter_mut().zip(input[..SCALAR_LEN].iter().rev()) {
+        *dst = *src;
+    }
+    Scalar::from_bytes_wide(&wide)
+}
+
+fn g1_to_vec(point: G1Affine) -> Vec<u8> {
+    let mut output = vec![0u8; G1_LEN];
+    if !bool::from(point.is_identity()) {
+        let bytes = point.to_uncompressed();
+        for (i, fp) in bytes.chunks_exact(FP_UNPADDED_LEN).enumerate() {
+            write_fp(&mut output[i * FP_LEN..], fp);
+        }
+    }
+    output
+}
+
+fn g2_to_vec(point: G2Affine) -> Vec<u8> {
+    let mut output = vec![0u8; G2_LEN];
+    if !bool::from(point.is_identity()) {
+        let bytes = point.to_uncompressed();
+        for (i, fp) in bytes.chunks_exact(FP_UNPADDED_LEN).enumerate() {
+            write_fp(&mut output[(i ^ 1) * FP_LEN..], fp);
+        }
+    }
+    output
+}
+
+/// add two points on G1
+pub(super) fn bls12_g1_add<RT: Runtime>(
+    system: &mut System<RT>,
+    input: &[u8],
+    context: PrecompileContext,
+) -> PrecompileResult {
+    charge_evm_gas(system, &context, "OnEvmBls12G1Add", G1_ADD_GAS)?;
+    if input.len() != 2 * G1_LEN {
+        return Err(PrecompileError::IncorrectInputSize);
+    }
+    let a = read_g1(&input[..G1_LEN], false)?;
+    let b = read_g1(&input[G1_LEN..], false)?;
+    Ok(g1_to_vec((G1Projective::from(a) + b).into()))
+}
+
+/// sum the products of points on G1 with scalars
+pub(super) fn bls12_g1_msm<RT: Runtime>(
+    system: &mut System<RT>,
+    input: &[u8],
+    context: PrecompileContext,
+) -> PrecompileResult {
+    const TERM_LEN: usize = G1_LEN + SCALAR_LEN;
+    if input.is_empty() || input.len() % TERM_LEN != 0 {
+        return Err(PrecompileError::IncorrectInputSize);
+    }
+    let k = input.len() / TERM_LEN;
+    charge_evm_gas(system, &context, "OnEvmBls12G1Msm", msm_gas(k, G1_MUL_GAS, &G1_MSM_DISCOUNT))?;
+
+    let mut sum = G1Projective::identity();
+    for term in input.chunks_exact(TERM_LEN) {
+        let point = read_g1(&term[..G1_LEN], true)?;
+        sum += point * read_scalar(&term[G1_LEN..]);
+    }
+    Ok(g1_to_vec(sum.into()))
+}
+
+/// add two points on G2
+pub(super) fn bls12_g2_add<RT: Runtime>(
+    system: &mut System<RT>,
+    input: &[u8],
+    context: PrecompileContext,
+) -> PrecompileResult {
+    charge_evm_gas(system, &context, "OnEvmBls12G2Add", G2_ADD_GAS)?;
+    if input.len() != 2 * G2_LEN {
+        return Err(PrecompileError::IncorrectInputSize);
+    }
+    let a = read_g2(&input[..G2_LEN], false)?;
+    let b = read_g2(&input[G2_LEN..], false)?;
+    Ok(g2_to_vec((G2Projective::from(a) + b).into()))
+}
+
+/// sum the products of points on G2 with scalars
+pub(super) fn bls12_g2_msm<RT: Runtime>(
+    system: &mut System<RT>,
+    input: &[u8],
+    context: PrecompileContext,
+) -> PrecompileResult {
+    const TERM_LEN: usize = G2_LEN + SCALAR_LEN;
+    if input.is_empty() || input.len() % TERM_LEN != 0 {
+        return Err(PrecompileError::IncorrectInputSize);
+    }
+    let k = input.len() / TERM_LEN;
+    charge_evm_gas(system, &context, "OnEvmBls12G2Msm", msm_gas(k, G2_MUL_GAS, &G2_MSM_DISCOUNT))?;
+
+    let mut sum = G2Projective::identity();
+    for term in input.chunks_exact(TERM_LEN) {
+        let point = read_g2(&term[..G2_LEN], true)?;
+        sum += point * read_scalar(&term[G2_LEN..]);
+    }
+    Ok(g2_to_vec(sum.into()))
+}
+
+/// check that the product of the pairings of pairs of points on G1 and G2 is one
+pub(super) fn bls12_pairing<RT: Runtime>(
+    system: &mut System<RT>,
+    input: &[u8],
+    context: PrecompileContext,
+) -> PrecompileResult {
+    if input.is_empty() || input.len() % PAIR_LEN != 0 {
+        return Err(PrecompileError::IncorrectInputSize);
+    }
+    let k = (input.len() / PAIR_LEN) as u64;
+    charge_evm_gas(
+        system,
+        &context,
+        "OnEvmBls12Pairing",
+        PAIRING_PER_PAIR_GAS * k + PAIRING_BASE_GAS,
+    )?;
+
+    let mut pairs = Vec::with_capacity(k as usize);
+    for pair in input.chunks_exact(PAIR_LEN) {
+        let p = read_g1(&pair[..G1_LEN], true)?;
+        let q = read_g2(&pair[G1_LEN..], true)?;
+        pairs.push((p, G2Prepared::from(q)));
+    }
+    let terms: Vec<_> = pairs.iter().map(|(p, q)| (p, q)).collect();
+    let result = multi_miller_loop(&terms).final_exponentiation();
+
+    let mut output = vec![0u8; 32];
+    output[31] = (result == Gt::identity()) as u8;
+    Ok(output)
+}
+
+#[cfg(test)]
+mod tests {
+    use fil_actors_runtime::test_utils::MockRuntime;
+    use hex_literal::hex;
+
+    use super::super::fvm_gas;
+    use super::*;
+
+    const G1_GENERATOR: [u8; G1_LEN] = hex!(
+        "0000000000000000000000000000000017f1d3a73197d7942695638c4fa9ac0fc3688c4f9774b905a14e3a3f171bac586c55e83ff97a1aeffb3af00adb22c6bb"
+        "0000000000000000000000000000000008b3f481e3aaa0f1a09e30ed741d8ae4fcf5e095d5d00af600db18cb2c04b3edd03cc744a2888ae40caa232946c5e7e1"
+    );
+    const G2_GENERATOR: [u8; G2_LEN] = hex!(
+        "00000000000000000000000000000000024aa2b2f08f0a91260805272dc51051c6e47ad4fa403b02b4510b647ae3d1770bac0326a805bbefd48056c8c121bdb8"
+        "0000000000000000000000000000000013e02b6052719f607dacd3a088274f65596bd0d09920b61ab5da61bbdc7f5049334cf11213945d57e5ac7d055d042b7e"
+        "000000000000000000000000000000000ce5d527727d6e118cc9cdc6da2e351aadfd9baa8cbdd3a76d429a695160d12c923ac9cc3baca289e193548608b82801"
+        "000000000000000000000000000000000606c4a02ea734cc32acd2b02bc28b99cb3e287e85a763af267492ab572e99ab3f370d275cec1da1aaa9075ff05f79be"
+    );
+
+    const ORDER: [u8; SCALAR_LEN] =
+        hex!("73eda753299d7d483339d80809a1d80553bda402fffe5bfeffffffff00000001");
+    const ORDER_MINUS_ONE: [u8; SCALAR_LEN] =
+        hex!("73eda753299d7d483339d80809a1d80553bda402fffe5bfeffffffff00000000");
+
+    fn scalar_bytes(n: u8) -> [u8; SCALAR_LEN] {
+        let mut bytes = [0u8; SCALAR_LEN];
+        bytes[SCALAR_LEN - 1] = n;
+        bytes
+    }
+
+    #[test]
+    fn encoding() {
+        assert_eq!(g1_to_vec(G1Affine::generator()), G1_GENERATOR);
+        assert_eq!(g2_to_vec(G2Affine::generator()), G2_GENERATOR);
+        assert_eq!(read_g1(&G1_GENERATOR, true).unwrap(), G1Affine::generator());
+        assert_eq!(read_g2(&G2_GENERATOR, true).unwrap(), G2Affine::generator());
+        assert_eq!(read_g1(&[0u8; G1_LEN], true).unwrap(), G1Affine::identity());
+        assert_eq!(g2_to_vec(G2Affine::identity()), vec![0u8; G2_LEN]);
+
+        // Non-zero padding.
+        let mut bad = G1_GENERATOR;
+        bad[0] = 1;
+        assert!(matches!(read_g1(&bad, false), Err(PrecompileError::InvalidInput)));
+        // Flag bits.
+        let mut bad = G1_GENERATOR;
+        bad[16] |= 0x80;
+        assert!(matches!(read_g1(&bad, false), Err(PrecompileError::InvalidInput)));
+        // Not on the curve.
+        let mut bad = G1_GENERATOR;
+        bad[G1_LEN - 1] ^= 1;
+        assert!(matches!(read_g1(&bad, false), Err(PrecompileError::InvalidInput)));
+        let mut bad = G2_GENERATOR;
+        bad[G2_LEN - 1] ^= 1;
+        assert!(matches!(read_g2(&bad, false), Err(PrecompileError::InvalidInput)));
+
+        // Scalars are reduced modulo the group order.
+        assert_eq!(read_scalar(&scalar_bytes(2)), Scalar::from(2u64));
+        assert_eq!(read_scalar(&ORDER), Scalar::zero());
+        assert_eq!(read_scalar(&ORDER_MINUS_ONE), -Scalar::one());
+    }
+
+    #[test]
+    fn g1_add_and_msm() {
+        let rt = MockRuntime::default();
+        rt.in_call.replace(true);
+        let mut system = System::create(&rt).unwrap();
+
+        let input = [G1_GENERATOR, G1_GENERATOR].concat();
+        rt.expect_gas_charge(fvm_gas(G1_ADD_GAS));
+        let doubled = bls12_g1_add(&mut system, &input, PrecompileContext::default()).unwrap();
+        assert_eq!(doubled, g1_to_vec((G1Affine::generator() * Scalar::from(2u64)).into()));
+
+        // Adding the point at infinity.
+        let input = [G1_GENERATOR, [0u8; G1_LEN]].concat();
+        rt.expect_gas_charge(fvm_gas(G1_ADD_GAS));
+        let res = bls12_g1_add(&mut system, &input, PrecompileContext::default()).unwrap();
+        assert_eq!(res, G1_GENERATOR);
+
+        let input = [&G1_GENERATOR[..], &scalar_bytes(2)].concat();
+        rt.expect_gas_charge(fvm_gas(G1_MUL_GAS));
+        let res = bls12_g1_msm(&mut system, &input, PrecompileContext::default()).unwrap();
+        assert_eq!(res, doubled);
+
+        // 2 * G + 3 * G - 2G
+        let input = [
+            &G1_GENERATOR[..],
+            &scalar_bytes(2),
+            &G1_GENERATOR,
+            &scalar_bytes(3),
+            &doubled[..],
+            &ORDER_MINUS_ONE,
+        ]
+        .concat();
+        let expected = G1Affine::generator() * Scalar::from(3u64);
+        rt.expect_gas_charge(fvm_gas(msm_gas(3, G1_MUL_GAS, &G1_MSM_DISCOUNT)));
+        let res = bls12_g1_msm(&mut system, &input, PrecompileContext::default()).unwrap();
+        assert_eq!(res, g1_to_vec(expected.into()));
+
+        // Addition is charged regardless of the input.
+        rt.expect_gas_charge(fvm_gas(G1_ADD_GAS));
+        let res = bls12_g1_add(&mut system, &G1_GENERATOR, PrecompileContext::default());
+        assert!(matches!(res, Err(PrecompileError::IncorrectInputSize)));
+        let res = bls12_g1_msm(&mut system, &[], PrecompileContext::default());
+        assert!(matches!(res, Err(PrecompileError::IncorrectInputSize)));
+        rt.verify();
+    }
+
+    #[test]
+    fn g2_add_and_msm() {
+        let rt = MockRuntime::default();
+        rt.in_call.replace(true);
+        let mut system = System::create(&rt).unwrap();
+
+        let input = [G2_GENERATOR, G2_GENERATOR].concat();
+        rt.expect_gas_charge(fvm_gas(G2_ADD_GAS));
+        let doubled = bls12_g2_add(&mut system, &input, PrecompileContext::default()).unwrap();
+        assert_eq!(doubled, g2_to_vec((G2Affine::generator() * Scalar::from(2u64)).into()));
+
+        let input = [&G2_GENERATOR[..], &scalar_bytes(2)].concat();
+        rt.expect_gas_charge(fvm_gas(G2_MUL_GAS));
+        let res = bls12_g2_msm(&mut system, &input, PrecompileContext::default()).unwrap();
+        assert_eq!(res, doubled);
+
+        let input = [&G2_GENERATOR[..], &scalar_bytes(0)].concat();
+        rt.expect_gas_charge(fvm_gas(G2_MUL_GAS));
+        let res = bls12_g2_msm(&mut system, &input, PrecompileContext::default()).unwrap();
+        assert_eq!(res, vec![0u8; G2_LEN]);
+        rt.verify();
+    }
+
+    #[test]
+    fn pairing() {
+        let rt = MockRuntime::default();
+        rt.in_call.replace(true);
+        let mut system = System::create(&rt).unwrap();
+
+        // e(G1, 2 * G2) * e(-2 * G1, G2) == 1
+        let g2_doubled = g2_to_vec((G2Affine::generator() * Scalar::from(2u64)).into());
+        let g1_neg_doubled = g1_to_vec((G1Affine::generator() * -Scalar::from(2u64)).into());
+        let input =
+            [&G1_GENERATOR[..], &g2_doubled[..], &g1_neg_doubled[..], &G2_GENERATOR].concat();
+        rt.expect_gas_charge(fvm_gas(2 * PAIRING_PER_PAIR_GAS + PAIRING_BASE_GAS));
+        let res = bls12_pairing(&mut system, &input, PrecompileContext::default()).unwrap();
+        assert_eq!(res, scalar_bytes(1));
+
+        let input = [G1_GENERATOR.to_vec(), G2_GENERATOR.to_vec()].concat();
+        rt.expect_gas_charge(fvm_gas(PAIRING_PER_PAIR_GAS + PAIRING_BASE_GAS));
+        let res = bls12_pairing(&mut system, &input, PrecompileContext::default()).unwrap();
+        assert_eq!(res, scalar_bytes(0));
+
+        // Pairing with the point at infinity.
+        let input = [vec![0u8; G1_LEN], G2_GENERATOR.to_vec()].concat();
+        rt.expect_gas_charge(fvm_gas(PAIRING_PER_PAIR_GAS + PAIRING_BASE_GAS));
+        let res = bls12_pairing(&mut system, &input, PrecompileContext::default()).unwrap();
+        assert_eq!(res, scalar_bytes(1));
+        rt.verify();
+    }
+
+    #[test]
+    fn gas() {
+        assert_eq!(msm_gas(1, G1_MUL_GAS, &G1_MSM_DISCOUNT), 12000);
+        assert_eq!(msm_gas(2, G1_MUL_GAS, &G1_MSM_DISCOUNT), 2 * 12000 * 949 / 1000);
+        assert_eq!(msm_gas(200, G1_MUL_GAS, &G1_MSM_DISCOUNT), 200 * 12000 * 519 / 1000);
+        assert_eq!(msm_gas(2, G2_MUL_GAS, &G2_MSM_DISCOUNT), 2 * 22500);
+        assert_eq!(msm_gas(200, G2_MUL_GAS, &G2_MSM_DISCOUNT), 200 * 22500 * 524 / 1000);
+
+        let rt = MockRuntime::default();
+        rt.in_call.replace(true);
+        let mut system = System::create(&rt).unwrap();
+        let input = [G1_GENERATOR, G1_GENERATOR].concat();
+        let context = PrecompileContext { gas_limit: G1_ADD_GAS - 1, ..Default::default() };
+        let res = bls12_g1_add(&mut system, &input, context);
+        assert!(matches!(res, Err(PrecompileError::OutOfGas)));
+        rt.verify();
+    }
+}
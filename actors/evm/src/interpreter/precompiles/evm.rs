@@ -14,7 +14,7 @@ use crate::{
     EVM_WORD_SIZE,
 };
 
-use super::{PrecompileContext, PrecompileResult};
+use super::{charge_evm_gas, PrecompileContext, PrecompileResult};
 use crate::reader::ValueReader;
 
 const SECP256K1_N: U256 =
@@ -29,7 +29,10 @@ fn test_secp_range() {
     assert!(!SECP256K1_RANGE.contains(&SECP256K1_N));
 }
 
-fn ec_recover_internal<RT: Runtime>(system: &mut System<RT>, input: &[u8]) -> PrecompileResult {
+pub(super) fn ec_recover_internal<RT: Runtime>(
+    system: &mut System<RT>,
+    input: &[u8],
+) -> PrecompileResult {
     let mut input_params = ValueReader::new(input);
     let hash: [u8; SECP_SIG_MESSAGE_HASH_SIZE] = input_params.read_fixed();
     let recovery_byte: u8 = input_params.read_value()?;
@@ -101,13 +104,40 @@ pub(super) fn identity<RT: Runtime>(
     Ok(Vec::from(input))
 }
 
+/// Minimum EVM gas charged for a call to MODEXP (EIP-2565).
+const MODEXP_MIN_GAS: u64 = 200;
+
+/// Computes the EVM gas cost of a call to MODEXP with the given input, following the pricing
+/// of EIP-2565. The cost depends only on the operand lengths and the leading 32 bytes of the
+/// exponent, so it can be charged before any of the operands are read.
+pub(super) fn modexp_gas(input: &[u8]) -> Result<u64, PrecompileError> {
+    let mut reader = ValueReader::new(input);
+    let base_len = reader.read_value::<u32>()? as u64;
+    let exponent_len = reader.read_value::<u32>()? as u64;
+    let mod_len = reader.read_value::<u32>()? as u64;
+
+    // The exponent's leading (up to) 32 bytes, padded with zeros like the operands themselves.
+    reader.seek((3 * EVM_WORD_SIZE).saturating_add(base_len as usize));
+    let exponent_head = reader.read_biguint(exponent_len.min(32) as usize);
+
+    let words = base_len.max(mod_len).div_ceil(8);
+    let complexity = words * words;
+    let head_bits = exponent_head.bits().saturating_sub(1);
+    let iterations =
+        if exponent_len <= 32 { head_bits } else { 8 * (exponent_len - 32) + head_bits };
+    Ok((complexity.saturating_mul(iterations.max(1)) / 3).max(MODEXP_MIN_GAS))
+}
+
 // https://eips.ethereum.org/EIPS/eip-198
 /// modulus exponent a number
 pub(super) fn modexp<RT: Runtime>(
-    _: &mut System<RT>,
+    system: &mut System<RT>,
     input: &[u8],
-    _: PrecompileContext,
+    context: PrecompileContext,
 ) -> PrecompileResult {
+    // Charge up front, as the cost of the exponentiation is unbounded by the input size.
+    charge_evm_gas(system, &context, "OnEvmModExp", modexp_gas(input)?)?;
+
     let mut reader = ValueReader::new(input);
 
     // This will error out if the user passes values greater than u32, but that's fine. The user
@@ -150,12 +180,19 @@ pub(super) fn curve_to_vec(curve: G1) -> Vec<u8> {
     output
 }
 
+// https://eips.ethereum.org/EIPS/eip-1108
+const EC_ADD_GAS: u64 = 150;
+const EC_MUL_GAS: u64 = 6000;
+const EC_PAIRING_BASE_GAS: u64 = 45000;
+const EC_PAIRING_PER_PAIR_GAS: u64 = 34000;
+
 /// add 2 points together on an elliptic curve
 pub(super) fn ec_add<RT: Runtime>(
-    _: &mut System<RT>,
+    system: &mut System<RT>,
     input: &[u8],
-    _: PrecompileContext,
+    context: PrecompileContext,
 ) -> PrecompileResult {
+    charge_evm_gas(system, &context, "OnEvmEcAdd", EC_ADD_GAS)?;
     let mut input_params = ValueReader::new(input);
     let point1: G1 = input_params.read_value()?;
     let point2: G1 = input_params.read_value()?;
@@ -165,10 +202,11 @@ pub(super) fn ec_add<RT: Runtime>(
 
 /// multiply a point on an elliptic curve by a scalar value
 pub(super) fn ec_mul<RT: Runtime>(
-    _: &mut System<RT>,
+    system: &mut System<RT>,
     input: &[u8],
-    _: PrecompileContext,
+    context: PrecompileContext,
 ) -> PrecompileResult {
+    charge_evm_gas(system, &context, "OnEvmEcMul", EC_MUL_GAS)?;
     let mut input_params = ValueReader::new(input);
     let point: G1 = input_params.read_value()?;
     let scalar: Fr = input_params.read_value()?;
@@ -178,9 +216,9 @@ pub(super) fn ec_mul<RT: Runtime>(
 
 /// pairs multple groups of twisted bn curves
 pub(super) fn ec_pairing<RT: Runtime>(
-    _: &mut System<RT>,
+    system: &mut System<RT>,
     input: &[u8],
-    _: PrecompileContext,
+    context: PrecompileContext,
 ) -> PrecompileResult {
     fn read_group(input: &[u8]) -> Result<(G1, G2), PrecompileError> {
         let mut reader = ValueReader::new(input);
@@ -225,6 +263,9 @@ pub(super) fn ec_pairing<RT: Runtime>(
     if input.len() % GROUP_BYTE_LEN != 0 {
         return Err(PrecompileError::IncorrectInputSize);
     }
+    let pairs = (input.len() / GROUP_BYTE_LEN) as u64;
+    let gas = EC_PAIRING_BASE_GAS + EC_PAIRING_PER_PAIR_GAS * pairs;
+    charge_evm_gas(system, &context, "OnEvmEcPairing", gas)?;
 
     let mut groups = Vec::new();
     for i in 0..input.len() / GROUP_BYTE_LEN {
@@ -240,11 +281,14 @@ pub(super) fn ec_pairing<RT: Runtime>(
     Ok(ret.to_vec())
 }
 
+// https://eips.ethereum.org/EIPS/eip-152#gas-costs-and-benchmarks
+const BLAKE2F_ROUND_GAS: u64 = 1;
+
 /// https://eips.ethereum.org/EIPS/eip-152
 pub(super) fn blake2f<RT: Runtime>(
-    _: &mut System<RT>,
+    system: &mut System<RT>,
     input: &[u8],
-    _: PrecompileContext,
+    context: PrecompileContext,
 ) -> PrecompileResult {
     if input.len() != 213 {
         return Err(PrecompileError::IncorrectInputSize);
@@ -274,6 +318,9 @@ pub(super) fn blake2f<RT: Runtime>(
     }?;
 
     let rounds = u32::from_be_bytes(rounds);
+    // There's no syscall for the compression function alone, so the rounds run in the actor
+    // and are charged per round, as on Ethereum.
+    charge_evm_gas(system, &context, "OnEvmBlake2f", rounds as u64 * BLAKE2F_ROUND_GAS)?;
     let mut h = {
         let mut ret = [0u64; 8];
         LE::read_u64_into(h, &mut ret);
@@ -299,6 +346,7 @@ pub(super) fn blake2f<RT: Runtime>(
 
 #[cfg(test)]
 mod tests {
+    use crate::interpreter::precompiles::fvm_gas;
     use crate::interpreter::CallKind;
 
     use super::*;
@@ -400,6 +448,7 @@ mod tests {
         let mut system = System::create(&rt).unwrap();
 
         let expected = hex!("08");
+        rt.expect_gas_charge(fvm_gas(200));
         let res = modexp(&mut system, input, PrecompileContext::default()).unwrap();
         assert_eq!(&res, &expected);
 
@@ -412,6 +461,7 @@ mod tests {
             "012345678910" // mod
         );
         let expected = hex!("00358eac8f30"); // left padding & 230026940208
+        rt.expect_gas_charge(fvm_gas(200));
         let res = modexp(&mut system, input, PrecompileContext::default()).unwrap();
         assert_eq!(&res, &expected);
 
@@ -425,6 +475,7 @@ mod tests {
             "03" // mod
         );
         // input smaller than expected
+        rt.expect_gas_charge(fvm_gas(200));
         let res = modexp(&mut system, input, PrecompileContext::default()).unwrap();
         assert_eq!(&res, &expected);
 
@@ -436,10 +487,104 @@ mod tests {
             "09" // exp
         );
         // no mod is invalid
+        rt.expect_gas_charge(fvm_gas(200));
         let res = modexp(&mut system, input, PrecompileContext::default()).unwrap();
         assert!(res.is_empty());
     }
 
+    #[test]
+    fn mod_exponent_gas() {
+        #[derive(serde::Deserialize)]
+        #[serde(rename_all = "PascalCase")]
+        struct TestCase {
+            name: String,
+            #[serde(with = "hex")]
+            input: Vec<u8>,
+            gas: u64,
+        }
+
+        let td = std::fs::read_to_string(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/precompile-testdata/modexp_eip2565.json"
+        ))
+        .unwrap();
+        let cases: Vec<TestCase> = serde_json::from_str(&td).unwrap();
+        for t in cases {
+            assert_eq!(modexp_gas(&t.input).unwrap(), t.gas, "{}", t.name);
+        }
+
+        // The exponent's leading bytes are counted beyond the first 32.
+        let input = &hex!(
+            "0000000000000000000000000000000000000000000000000000000000000001" // base len
+            "0000000000000000000000000000000000000000000000000000000000000021" // exp len
+            "0000000000000000000000000000000000000000000000000000000000000040" // mod len
+            "02" // base
+            "ff" // exp (leading byte)
+        );
+        // 64 squared multiplication complexity, (8 + 255) iterations.
+        assert_eq!(modexp_gas(input).unwrap(), 64 * 263 / 3);
+    }
+
+    #[test]
+    fn mod_exponent_out_of_gas() {
+        let input = &hex!(
+            "0000000000000000000000000000000000000000000000000000000000000001" // base len
+            "0000000000000000000000000000000000000000000000000000000000000001" // exp len
+            "0000000000000000000000000000000000000000000000000000000000000001" // mod len
+            "08" // base
+            "09" // exp
+            "0A" // mod
+        );
+
+        let mut rt = MockRuntime::default();
+        rt.in_call.replace(true);
+        rt.policy.evm_precompile_fvm_gas_per_evm_gas = 10;
+
+        let mut system = System::create(&rt).unwrap();
+
+        // The cost is charged in FVM gas.
+        let context = PrecompileContext { gas_limit: 1999, ..PrecompileContext::default() };
+        let res = modexp(&mut system, input, context);
+        assert!(matches!(res, Err(PrecompileError::OutOfGas)));
+
+        let context = PrecompileContext { gas_limit: 2000, ..PrecompileContext::default() };
+        rt.expect_gas_charge(2000);
+        let res = modexp(&mut system, input, context).unwrap();
+        assert_eq!(&res, &hex!("08"));
+        rt.verify();
+    }
+
+    #[test]
+    fn mod_exponent_large_exponent_charges_fvm_gas() {
+        let input = &hex!(
+            "0000000000000000000000000000000000000000000000000000000000000001" // base len
+            "0000000000000000000000000000000000000000000000000000000000000020" // exp len
+            "0000000000000000000000000000000000000000000000000000000000000001" // mod len
+            "03" // base
+            "ffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff" // exp
+            "05" // mod
+        );
+
+        let rt = MockRuntime::default();
+        rt.in_call.replace(true);
+        let mut system = System::create(&rt).unwrap();
+
+        // 1 squared multiplication complexity, 255 iterations: the EIP-2565 minimum of 200.
+        assert_eq!(modexp_gas(input).unwrap(), 200);
+        // Charged in FVM gas at the precompile scale rather than one for one.
+        let charged = fvm_gas(200);
+        assert!(charged >= 50_000, "charged only {charged} FVM gas");
+
+        let context = PrecompileContext { gas_limit: charged as u64 - 1, ..Default::default() };
+        assert!(matches!(modexp(&mut system, input, context), Err(PrecompileError::OutOfGas)));
+
+        rt.expect_gas_charge(charged);
+        let context = PrecompileContext { gas_limit: charged as u64, ..Default::default() };
+        // 3^(2^256 - 1) mod 5 = 2
+        assert_eq!(modexp(&mut system, input, context).unwrap(), hex!("02"));
+        rt.verify();
+    }
+
     // bn tests borrowed from https://github.com/bluealloy/revm/blob/26540bf5b29de6e7c8020c4c1880f8a97d1eadc9/crates/revm_precompiles/src/bn128.rs
     mod bn {
         use serde::{Deserialize, Serialize};
@@ -449,8 +594,8 @@ mod tests {
         use crate::interpreter::{
             precompiles::{
                 ec_add, ec_mul, ec_pairing,
-                evm::{blake2f, ec_recover, modexp},
-                PrecompileContext, PrecompileError, PrecompileFn,
+                evm::{blake2f, ec_recover, modexp, modexp_gas},
+                fvm_gas, PrecompileContext, PrecompileError, PrecompileFn,
             },
             System,
         };
@@ -476,15 +621,18 @@ mod tests {
                 301d1d33be6da8e509df21cc35964723180eed7532537db9ae5e7d48f195c915",
             )
             .unwrap();
+            rt.expect_gas_charge(fvm_gas(150));
             let res = ec_add(&mut system, &input, PrecompileContext::default()).unwrap();
             assert_eq!(res, expected);
             // zero sum test
             let input = vec![0; 32 * 4];
+            rt.expect_gas_charge(fvm_gas(150));
             let res = ec_add(&mut system, &input, PrecompileContext::default()).unwrap();
             assert_eq!(res, vec![0; 64]);
 
             // no input test (auto zero extend)
             let input = [];
+            rt.expect_gas_charge(fvm_gas(150));
             let res = ec_add(&mut system, &input, PrecompileContext::default()).unwrap();
             assert_eq!(res, vec![0; 64]);
 
@@ -497,6 +645,7 @@ mod tests {
                 1111111111111111111111111111111111111111111111111111111111111111",
             )
             .unwrap();
+            rt.expect_gas_charge(fvm_gas(150));
             let res = ec_add(&mut system, &input, PrecompileContext::default());
             assert!(matches!(
                 res,
@@ -516,6 +665,7 @@ mod tests {
                 input: Vec<u8>,
                 #[serde(with = "hex")]
                 expected: Vec<u8>,
+                gas: u64,
             }
 
             let tests: &[(PrecompileFn<MockRuntime>, &'static str)] = &[
@@ -537,6 +687,12 @@ mod tests {
                 let td = std::fs::read_to_string(format!("{TESTDATA_PATH}/{name}.json")).unwrap();
                 let cases: Vec<TestCase> = serde_json::from_str(&td).unwrap();
                 for t in cases {
+                    if name.starts_with("modexp") {
+                        rt.expect_gas_charge(fvm_gas(modexp_gas(&t.input).unwrap()));
+                    } else if name.starts_with("bn256") || *name == "blake2F" {
+                        // The BN254 and BLAKE2 precompiles are priced as on Ethereum.
+                        rt.expect_gas_charge(fvm_gas(t.gas));
+                    }
                     let res = f(&mut system, &t.input, PrecompileContext::default())
                         .expect("call failed");
                     assert_eq!(res, t.expected);
@@ -592,11 +748,13 @@ mod tests {
                 031b8ce914eba3a9ffb989f9cdd5b0f01943074bf4f0f315690ec3cec6981afc",
             )
             .unwrap();
+            rt.expect_gas_charge(fvm_gas(6000));
             let res = ec_mul(&mut system, &input, PrecompileContext::default()).unwrap();
             assert_eq!(res, expected);
 
             // no input test
             let input = [0u8; 0];
+            rt.expect_gas_charge(fvm_gas(6000));
             let res = ec_mul(&mut system, &input, PrecompileContext::default()).unwrap();
             assert_eq!(res, vec![0u8; 64]);
 
@@ -608,6 +766,7 @@ mod tests {
                 0f00000000000000000000000000000000000000000000000000000000000000",
             )
             .unwrap();
+            rt.expect_gas_charge(fvm_gas(6000));
             let res = ec_mul(&mut system, &input, PrecompileContext::default());
             assert!(matches!(
                 res,
@@ -623,6 +782,7 @@ mod tests {
                 ",
             )
             .unwrap();
+            rt.expect_gas_charge(fvm_gas(6000));
             let res = ec_mul(&mut system, &input, PrecompileContext::default());
             assert_eq!(vec![0u8; 64], res.unwrap());
         }
@@ -655,6 +815,7 @@ mod tests {
                 hex::decode("0000000000000000000000000000000000000000000000000000000000000001")
                     .unwrap();
 
+            rt.expect_gas_charge(fvm_gas(113000));
             let res = ec_pairing(&mut system, &input, PrecompileContext::default()).unwrap();
             assert_eq!(res, expected);
 
@@ -675,13 +836,15 @@ mod tests {
                 12c85ea5db8c6deb4aab71808dcb408fe3d1e7690c43d37b4ce6cc0166fa7daa",
             )
             .unwrap();
-            let res = ec_pairing(&mut system, &input, PrecompileContext::default()).unwrap();
-            assert_eq!(res, expected);
+            let context = PrecompileContext { gas_limit: 112999, ..Default::default() };
+            let res = ec_pairing(&mut system, &input, context);
+            assert!(matches!(res, Err(PrecompileError::OutOfGas)));
             // no input test
             let input = [0u8; 0];
             let expected =
                 hex::decode("0000000000000000000000000000000000000000000000000000000000000001")
                     .unwrap();
+            rt.expect_gas_charge(fvm_gas(45000));
             let res = ec_pairing(&mut system, &input, PrecompileContext::default()).unwrap();
             assert_eq!(res, expected);
             // point not on curve fail
@@ -695,6 +858,7 @@ mod tests {
                 1111111111111111111111111111111111111111111111111111111111111111",
             )
             .unwrap();
+            rt.expect_gas_charge(fvm_gas(79000));
             let res = ec_pairing(&mut system, &input, PrecompileContext::default());
             assert!(matches!(
                 res,
@@ -802,6 +966,7 @@ mod tests {
             "0000000000000000"
             "01"
         );
+        rt.expect_gas_charge(fvm_gas(0));
         assert!(
             matches!(blake2f(&mut system, input, PrecompileContext::default()), Ok(v) if v == expected)
         );
@@ -816,6 +981,7 @@ mod tests {
             "0000000000000000"
             "01"
         );
+        rt.expect_gas_charge(fvm_gas(12));
         assert!(
             matches!(blake2f(&mut system, input, PrecompileContext::default()), Ok(v) if v == expected)
         );
@@ -830,6 +996,7 @@ mod tests {
             "0000000000000000"
             "00"
         );
+        rt.expect_gas_charge(fvm_gas(12));
         assert!(
             matches!(blake2f(&mut system, input, PrecompileContext::default()), Ok(v) if v == expected)
         );
@@ -844,10 +1011,23 @@ mod tests {
             "0000000000000000"
             "01"
         );
+        rt.expect_gas_charge(fvm_gas(1));
         assert!(
             matches!(blake2f(&mut system, input, PrecompileContext::default()), Ok(v) if v == expected)
         );
 
+        // Not enough gas for the rounds requested.
+        let input = &hex!(
+            "0000000c"
+            "48c9bdf267e6096a3ba7ca8485ae67bb2bf894fe72f36e3cf1361d5f3af54fa5d182e6ad7f520e511f6c3e2b8c68059b6bbd41fbabd9831f79217e1319cde05b"
+            "6162630000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000"
+            "0300000000000000"
+            "0000000000000000"
+            "01"
+        );
+        let context = PrecompileContext { gas_limit: 11, ..PrecompileContext::default() };
+        assert!(matches!(blake2f(&mut system, input, context), Err(PrecompileError::OutOfGas)));
+
         // T8
         // NOTE:
         //  original test case ran ffffffff rounds of blake2b
@@ -863,6 +1043,7 @@ mod tests {
             "0000000000000000"
             "01"
         );
+        rt.expect_gas_charge(fvm_gas(65535));
         assert!(
             matches!(blake2f(&mut system, input, PrecompileContext::default()), Ok(v) if v == expected)
         );
@@ -1,13 +1,24 @@
-use crate::{EVM_MAX_RESERVED_METHOD, EVM_WORD_SIZE};
+use crate::{ext, EVM_CONTRACT_REVERTED, EVM_MAX_RESERVED_METHOD, EVM_WORD_SIZE};
 use fil_actors_evm_shared::uints::U256;
+use fil_actors_runtime::runtime::builtins::Type;
 use fil_actors_runtime::runtime::Runtime;
 use fvm_ipld_encoding::ipld_block::IpldBlock;
-use fvm_shared::{address::Address, econ::TokenAmount, sys::SendFlags, METHOD_SEND};
+use fvm_ipld_encoding::BytesDe;
+use fvm_shared::crypto::hash::SupportedHashes;
+use fvm_shared::crypto::signature::{Signature, SignatureType};
+use fvm_shared::{
+    address::{Address, Protocol},
+    econ::TokenAmount,
+    sys::SendFlags,
+    METHOD_SEND,
+};
 
 use crate::interpreter::{CallKind, System};
 
+use super::evm::ec_recover_internal;
 use super::{PrecompileContext, PrecompileError, PrecompileResult};
 use crate::reader::ValueReader;
+use num_traits::Zero;
 
 /// Read BE encoded low u64 ID address from a u256 word
 /// Looks up and returns the encoded f4 addresses of an ID address. Empty array if not found or `InvalidInput` input was larger 2^64.
@@ -61,7 +72,7 @@ pub(super) fn resolve_address<RT: Runtime>(
 /// u64   method
 /// u256  value
 /// u64   flags (1 for read-only, 0 otherwise)
-/// u64   codec (0x71 for "dag-cbor", or `0` for "nothing")
+/// u64   codec (0x51 for "cbor", 0x71 for "dag-cbor", 0x55 for "raw", or `0` for "nothing")
 /// bytes params (must be empty if the codec is 0x0)
 /// bytes address
 /// ```
@@ -70,14 +81,17 @@ pub(super) fn resolve_address<RT: Runtime>(
 ///
 /// ```text
 /// i256  exit_code
-/// u64   codec
+/// u64   codec (of the return value, as returned by the called actor)
 /// bytes return_value
 /// ```
 ///
 /// for exit_code:
-/// - negative values are system errors
-/// - positive are user errors (from the called actor)
+/// - negative values are system errors, with no return value
+/// - positive are user errors (from the called actor), with the actor's return value if any
 /// - 0 is success
+///
+/// If the called actor is an EVM contract that reverted (exit code 33), the return value is its
+/// revert data, as raw bytes (codec 0x55).
 pub(super) fn call_actor<RT: Runtime>(
     system: &mut System<RT>,
     input: &[u8],
@@ -94,7 +108,7 @@ pub(super) fn call_actor<RT: Runtime>(
 /// u64   method
 /// u256  value
 /// u64   flags (1 for read-only, 0 otherwise)
-/// u64   codec (0x71 for "dag-cbor", or `0` for "nothing")
+/// u64   codec (0x51 for "cbor", 0x71 for "dag-cbor", 0x55 for "raw", or `0` for "nothing")
 /// bytes params (must be empty if the codec is 0x0)
 /// u64   actor_id
 /// ```
@@ -103,14 +117,17 @@ pub(super) fn call_actor<RT: Runtime>(
 ///
 /// ```text
 /// i256  exit_code
-/// u64   codec
+/// u64   codec (of the return value, as returned by the called actor)
 /// bytes return_value
 /// ```
 ///
 /// for exit_code:
-/// - negative values are system errors
-/// - positive are user errors (from the called actor)
+/// - negative values are system errors, with no return value
+/// - positive are user errors (from the called actor), with the actor's return value if any
 /// - 0 is success
+///
+/// If the called actor is an EVM contract that reverted (exit code 33), the return value is its
+/// revert data, as raw bytes (codec 0x55).
 pub(super) fn call_actor_id<RT: Runtime>(
     system: &mut System<RT>,
     input: &[u8],
@@ -166,9 +183,10 @@ pub(super) fn call_actor_shared<RT: Runtime>(
     // ------ Begin Call -------
 
     let result = {
-        // TODO only CBOR or "nothing" for now. We should support RAW and DAG_CBOR in the future.
         let params = match codec {
-            fvm_ipld_encoding::CBOR => Some(IpldBlock { codec, data: params.into() }),
+            fvm_ipld_encoding::CBOR | fvm_ipld_encoding::DAG_CBOR | fvm_ipld_encoding::IPLD_RAW => {
+                Some(IpldBlock { codec, data: params.into() })
+            }
             0 if params.is_empty() => None,
             _ => return Err(PrecompileError::InvalidInput),
         };
@@ -199,7 +217,14 @@ pub(super) fn call_actor_shared<RT: Runtime>(
                 // no return only exit code
                 (exit_code, None)
             }
-            Ok(resp) => (U256::from(resp.exit_code.value()), resp.return_data),
+            Ok(resp) => {
+                let data = if resp.exit_code == EVM_CONTRACT_REVERTED {
+                    resp.return_data.map(unwrap_revert_data)
+                } else {
+                    resp.return_data
+                };
+                (U256::from(resp.exit_code.value()), data)
+            }
         };
 
         let ret_blk = data.unwrap_or(IpldBlock { codec: 0, data: vec![] });
@@ -221,6 +246,22 @@ pub(super) fn call_actor_shared<RT: Runtime>(
     Ok(output)
 }
 
+// An EVM contract that reverts exits with its revert data wrapped in a CBOR byte string.
+// Unwrapping it gives the calling contract the data exactly as the callee reverted with it,
+// ready to be decoded like the data of a reverted call between contracts. Data that isn't a
+// CBOR byte string, which a non-EVM actor might return with the same exit code, is left as is.
+fn unwrap_revert_data(data: IpldBlock) -> IpldBlock {
+    if data.codec != fvm_ipld_encoding::CBOR {
+        return data;
+    }
+    match data.deserialize::<BytesDe>() {
+        Ok(BytesDe(revert_data)) => {
+            IpldBlock { codec: fvm_ipld_encoding::IPLD_RAW, data: revert_data }
+        }
+        Err(_) => data,
+    }
+}
+
 /// Params:
 ///
 /// | Param            | Value                     |
@@ -238,3 +279,243 @@ pub(super) fn get_randomness<RT: Runtime>(
     let randomness = system.rt.get_beacon_randomness(randomness_epoch);
     randomness.map(|r| r.to_vec()).map_err(|_| PrecompileError::InvalidInput)
 }
+
+/// Recovers the signer of an EIP-712 typed data signature, such as a meta-transaction signed
+/// by a user and submitted by a gasless relayer.
+/// The signed digest is `keccak256(0x1901 ‖ domain_separator ‖ struct_hash)`.
+///
+/// | Param            | Value                     |
+/// |------------------|---------------------------|
+/// | domain_separator | bytes32                   |
+/// | struct_hash      | bytes32                   |
+/// | v                | U256 - 27 or 28           |
+/// | r                | U256                      |
+/// | s                | U256                      |
+///
+/// Returns the signer's address left-padded to 32 bytes, or an empty result if the signature
+/// is invalid (matching ecrecover).
+///
+/// An EIP-2771 trusted forwarder hashes a request such as
+/// `ForwardRequest(address from,address to,uint256 value,uint256 gas,uint256 nonce,bytes data)`
+/// into `struct_hash`, checks that the signer returned for its domain separator is `from` and
+/// that the nonce is unused, then calls `to` with `from` appended to the calldata. Recipient
+/// contracts that trust the forwarder take the last 20 bytes of calldata as the sender.
+pub(super) fn recover_typed_data_signer<RT: Runtime>(
+    system: &mut System<RT>,
+    input: &[u8],
+    _: PrecompileContext,
+) -> PrecompileResult {
+    let mut input_params = ValueReader::new(input);
+    let domain_separator: [u8; 32] = input_params.read_fixed();
+    let struct_hash: [u8; 32] = input_params.read_fixed();
+
+    let mut preimage = [0u8; 66];
+    preimage[..2].copy_from_slice(&[0x19, 0x01]);
+    preimage[2..34].copy_from_slice(&domain_separator);
+    preimage[34..].copy_from_slice(&struct_hash);
+    let digest = system.rt.hash(SupportedHashes::Keccak256, &preimage);
+
+    // The remaining input (v, r, s) follows the digest as for ecrecover.
+    let mut recover_input = digest;
+    recover_input.extend_from_slice(input.get(2 * EVM_WORD_SIZE..).unwrap_or_default());
+    Ok(ec_recover_internal(system, &recover_input).unwrap_or_default())
+}
+
+/// Returns the chain context of the current message, so that contracts can bind proofs to it.
+///
+/// | Output             | Value                                  |
+/// |--------------------|----------------------------------------|
+/// | epoch              | U256 - the current epoch               |
+/// | network_version    | U256 - the current network version     |
+/// | parent_tipset_hash | bytes32                                |
+///
+/// The current epoch's tipset is not yet known while its messages execute, so the last
+/// commitment available is the CID of the parent tipset. Its hash is truncated to 32 bytes as
+/// for BLOCKHASH, and is zero if the parent epoch was a null round.
+pub(super) fn get_chain_context<RT: Runtime>(
+    system: &mut System<RT>,
+    _: &[u8],
+    _: PrecompileContext,
+) -> PrecompileResult {
+    let epoch = system.rt.curr_epoch();
+    let network_version: u32 = system.rt.network_version().into();
+
+    let mut parent_tipset_hash = [0u8; EVM_WORD_SIZE];
+    if let Some(cid) = system.tipset_cid(epoch - 1) {
+        let digest = cid.hash().digest();
+        let len = digest.len().min(EVM_WORD_SIZE);
+        parent_tipset_hash[..len].copy_from_slice(&digest[..len]);
+    }
+
+    let mut output = Vec::with_capacity(3 * EVM_WORD_SIZE);
+    output.extend_from_slice(&U256::from(epoch).to_bytes());
+    output.extend_from_slice(&U256::from(network_version).to_bytes());
+    output.extend_from_slice(&parent_tipset_hash);
+    Ok(output)
+}
+
+/// Verifies a Filecoin signature by an f1 (secp256k1) or f3 (BLS) address over arbitrary bytes.
+///
+/// Parameters are encoded according to the solidity ABI, with no function selector:
+///
+/// ```text
+/// bytes address
+/// bytes plaintext
+/// bytes signature
+/// ```
+///
+/// The signature type is implied by the address protocol. Returns 1 as a U256 if the signature
+/// is valid and 0 otherwise. Errors with `InvalidInput` if the address is not an f1 or f3
+/// address.
+pub(super) fn verify_signature<RT: Runtime>(
+    system: &mut System<RT>,
+    input: &[u8],
+    _: PrecompileContext,
+) -> PrecompileResult {
+    let mut input_params = ValueReader::new(input);
+    let address_off: u32 = input_params.read_value()?;
+    let plaintext_off: u32 = input_params.read_value()?;
+    let signature_off: u32 = input_params.read_value()?;
+
+    let mut read_bytes = |offset: u32| -> Result<Vec<u8>, PrecompileError> {
+        input_params.seek(offset.try_into()?);
+        let len: u32 = input_params.read_value()?;
+        Ok(input_params.read_padded(len.try_into()?).into_owned())
+    };
+    let address_bytes = read_bytes(address_off)?;
+    let plaintext = read_bytes(plaintext_off)?;
+    let signature_bytes = read_bytes(signature_off)?;
+
+    let signer = Address::from_bytes(&address_bytes).map_err(|_| PrecompileError::InvalidInput)?;
+    let sig_type = match signer.protocol() {
+        Protocol::Secp256k1 => SignatureType::Secp256k1,
+        Protocol::BLS => SignatureType::BLS,
+        _ => return Err(PrecompileError::InvalidInput),
+    };
+    let signature = Signature { sig_type, bytes: signature_bytes };
+
+    let valid = system.rt.verify_signature(&signature, &signer, &plaintext).is_ok();
+    Ok(U256::from(valid as u8).to_bytes().to_vec())
+}
+
+/// Fetches the status of a sector from a storage miner actor, with a read-only call.
+///
+/// | Param            | Value                     |
+/// |------------------|---------------------------|
+/// | miner            | U256 - low u64 actor ID   |
+/// | sector_number    | U256 - low u64            |
+///
+/// Returns (solidity ABI encoded):
+///
+/// ```text
+/// bool  live
+/// bool  faulty
+/// i64   activation
+/// i64   expiration
+/// u64   deadline
+/// u64   partition
+/// ```
+///
+/// Errors with `InvalidInput` if the actor is not a storage miner, and reverts if the miner
+/// doesn't have the sector.
+pub(super) fn get_miner_sector_status<RT: Runtime>(
+    system: &mut System<RT>,
+    input: &[u8],
+    _: PrecompileContext,
+) -> PrecompileResult {
+    let mut input_params = ValueReader::new(input);
+    let miner: u64 = input_params.read_value()?;
+    let sector_number: u64 = input_params.read_value()?;
+
+    let is_miner = system
+        .rt
+        .get_actor_code_cid(&miner)
+        .and_then(|code| system.rt.resolve_builtin_actor_type(&code))
+        == Some(Type::Miner);
+    if !is_miner {
+        return Err(PrecompileError::InvalidInput);
+    }
+
+    let ret = system.rt.send(
+        &Address::new_id(miner),
+        ext::miner::GET_SECTOR_STATUS_METHOD,
+        IpldBlock::serialize_cbor(&ext::miner::GetSectorStatusParams { sector_number })
+            .map_err(|_| PrecompileError::InvalidInput)?,
+        TokenAmount::zero(),
+        None,
+        SendFlags::READ_ONLY,
+    );
+    let status: ext::miner::GetSectorStatusReturn = match ret {
+        Ok(resp) if resp.exit_code.is_success() => resp
+            .return_data
+            .ok_or(PrecompileError::InvalidInput)?
+            .deserialize()
+            .map_err(|_| PrecompileError::InvalidInput)?,
+        _ => return Err(PrecompileError::CallFailed),
+    };
+
+    let mut output = Vec::with_capacity(6 * EVM_WORD_SIZE);
+    for word in [
+        U256::from(status.live as u8),
+        U256::from(status.faulty as u8),
+        U256::from(status.activation),
+        U256::from(status.expiration),
+        U256::from(status.deadline),
+        U256::from(status.partition),
+    ] {
+        output.extend_from_slice(&word.to_bytes());
+    }
+    Ok(output)
+}
+
+/// Designates the paymaster charged for value shortfalls of this contract's outgoing calls.
+/// When the contract calls another actor with more value than its balance, the paymaster is
+/// asked to cover the difference, within whatever it has approved for this contract.
+///
+/// | Param            | Value                                   |
+/// |------------------|-----------------------------------------|
+/// | paymaster        | U256 - low u64 actor ID, 0 to clear     |
+///
+/// Must be called with delegatecall, and outside of a read-only context.
+pub(super) fn set_paymaster<RT: Runtime>(
+    system: &mut System<RT>,
+    input: &[u8],
+    ctx: PrecompileContext,
+) -> PrecompileResult {
+    if ctx.call_type != CallKind::DelegateCall || system.readonly {
+        return Err(PrecompileError::CallForbidden);
+    }
+
+    let mut input_params = ValueReader::new(input);
+    let paymaster: u64 = input_params.read_value()?;
+
+    system.set_paymaster(if paymaster == 0 { None } else { Some(paymaster) });
+    Ok(Vec::new())
+}
+
+/// Approves this contract to sponsor up to `amount` of value shortfalls for a beneficiary
+/// contract that has designated it as paymaster. Replaces any previous approval for the
+/// beneficiary; an amount of zero revokes it.
+///
+/// | Param            | Value                     |
+/// |------------------|---------------------------|
+/// | beneficiary      | U256 - low u64 actor ID   |
+/// | amount           | U256                      |
+///
+/// Must be called with delegatecall, and outside of a read-only context.
+pub(super) fn approve_sponsorship<RT: Runtime>(
+    system: &mut System<RT>,
+    input: &[u8],
+    ctx: PrecompileContext,
+) -> PrecompileResult {
+    if ctx.call_type != CallKind::DelegateCall || system.readonly {
+        return Err(PrecompileError::CallForbidden);
+    }
+
+    let mut input_params = ValueReader::new(input);
+    let beneficiary: u64 = input_params.read_value()?;
+    let amount: U256 = input_params.read_value()?;
+
+    system.set_sponsor_approval(beneficiary, TokenAmount::from(&amount))?;
+    Ok(Vec::new())
+}
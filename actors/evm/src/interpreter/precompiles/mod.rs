@@ -10,11 +10,17 @@ use crate::reader::OverflowError;
 
 use super::{CallKind, System};
 mod blake2f_impl;
+mod bls;
 mod evm;
 mod fvm;
 
+use bls::{bls12_g1_add, bls12_g1_msm, bls12_g2_add, bls12_g2_msm, bls12_pairing};
 use evm::{blake2f, ec_add, ec_mul, ec_pairing, ec_recover, identity, modexp, ripemd160, sha256};
-use fvm::{call_actor, call_actor_id, get_randomness, lookup_delegated_address, resolve_address};
+use fvm::{
+    approve_sponsorship, call_actor, call_actor_id, get_chain_context, get_miner_sector_status,
+    get_randomness, lookup_delegated_address, recover_typed_data_signer, resolve_address,
+    set_paymaster, verify_signature,
+};
 
 type PrecompileFn<RT> = fn(&mut System<RT>, &[u8], PrecompileContext) -> PrecompileResult;
 pub type PrecompileResult = Result<Vec<u8>, PrecompileError>;
@@ -42,26 +48,38 @@ pub struct Precompiles<RT>(PhantomData<RT>);
 
 impl<RT: Runtime> Precompiles<RT> {
     /// FEVM specific precompiles (0xfe prefix)
-    const NATIVE_PRECOMPILES: PrecompileTable<RT, 6> = PrecompileTable([
-        Some(resolve_address::<RT>),          // 0xfe00..01
-        Some(lookup_delegated_address::<RT>), // 0xfe00..02
-        Some(call_actor::<RT>),               // 0xfe00..03
-        None,                                 // 0xfe00..04 get_actor_type DISABLED
-        Some(call_actor_id::<RT>),            // 0xfe00..05
-        Some(get_randomness::<RT>),           // 0xfe00..06
+    const NATIVE_PRECOMPILES: PrecompileTable<RT, 12> = PrecompileTable([
+        Some(resolve_address::<RT>),           // 0xfe00..01
+        Some(lookup_delegated_address::<RT>),  // 0xfe00..02
+        Some(call_actor::<RT>),                // 0xfe00..03
+        None,                                  // 0xfe00..04 get_actor_type DISABLED
+        Some(call_actor_id::<RT>),             // 0xfe00..05
+        Some(get_randomness::<RT>),            // 0xfe00..06
+        Some(set_paymaster::<RT>),             // 0xfe00..07
+        Some(approve_sponsorship::<RT>),       // 0xfe00..08
+        Some(recover_typed_data_signer::<RT>), // 0xfe00..09
+        Some(get_chain_context::<RT>),         // 0xfe00..0a
+        Some(verify_signature::<RT>),          // 0xfe00..0b
+        Some(get_miner_sector_status::<RT>),   // 0xfe00..0c
     ]);
 
     /// EVM specific precompiles
-    const EVM_PRECOMPILES: PrecompileTable<RT, 9> = PrecompileTable([
-        Some(ec_recover::<RT>), // 0x01 ecrecover
-        Some(sha256::<RT>),     // 0x02 SHA2-256
-        Some(ripemd160::<RT>),  // 0x03 ripemd160
-        Some(identity::<RT>),   // 0x04 identity
-        Some(modexp::<RT>),     // 0x05 modexp
-        Some(ec_add::<RT>),     // 0x06 ecAdd
-        Some(ec_mul::<RT>),     // 0x07 ecMul
-        Some(ec_pairing::<RT>), // 0x08 ecPairing
-        Some(blake2f::<RT>),    // 0x09 blake2f
+    const EVM_PRECOMPILES: PrecompileTable<RT, 15> = PrecompileTable([
+        Some(ec_recover::<RT>),    // 0x01 ecrecover
+        Some(sha256::<RT>),        // 0x02 SHA2-256
+        Some(ripemd160::<RT>),     // 0x03 ripemd160
+        Some(identity::<RT>),      // 0x04 identity
+        Some(modexp::<RT>),        // 0x05 modexp
+        Some(ec_add::<RT>),        // 0x06 ecAdd
+        Some(ec_mul::<RT>),        // 0x07 ecMul
+        Some(ec_pairing::<RT>),    // 0x08 ecPairing
+        Some(blake2f::<RT>),       // 0x09 blake2f
+        None,                      // 0x0a point evaluation UNSUPPORTED
+        Some(bls12_g1_add::<RT>),  // 0x0b BLS12_G1ADD
+        Some(bls12_g1_msm::<RT>),  // 0x0c BLS12_G1MSM
+        Some(bls12_g2_add::<RT>),  // 0x0d BLS12_G2ADD
+        Some(bls12_g2_msm::<RT>),  // 0x0e BLS12_G2MSM
+        Some(bls12_pairing::<RT>), // 0x0f BLS12_PAIRING_CHECK
     ]);
 
     fn lookup_precompile(addr: &EthAddress) -> Option<PrecompileFn<RT>> {
@@ -94,8 +112,8 @@ impl<RT: Runtime> Precompiles<RT> {
         // Then transfer the value. We do this second because we don't want to transfer if the
         // precompile reverts.
         //
-        // This shouldn't be observable as the only precompile with side-effects is the call_actor
-        // precompile, and that precompile can only be called with delegatecall.
+        // This shouldn't be observable as the only precompiles with side-effects (call_actor and
+        // the paymaster precompiles) can only be called with delegatecall.
         if !context.value.is_zero() {
             let fil_addr: Address = precompile_addr.into();
             system
@@ -119,6 +137,8 @@ pub enum PrecompileError {
     EcErr(CurveError),
     #[error("incorrect input size to precompile")]
     IncorrectInputSize,
+    #[error("insufficient gas for precompile")]
+    OutOfGas,
     // FVM precompile errors
     #[error("invalid input to precompile")]
     InvalidInput,
@@ -126,6 +146,8 @@ pub enum PrecompileError {
     CallForbidden,
     #[error("transfering funds to precompile failed")]
     TransferFailed,
+    #[error("call to actor by precompile failed")]
+    CallFailed,
     #[error("internal evm error when calling precompile: {0}")]
     VMError(ActorError),
 }
@@ -165,6 +187,29 @@ impl From<GroupError> for PrecompileError {
     }
 }
 
+/// Charges the EVM gas cost of a precompile call in FVM gas, failing if it exceeds the gas
+/// available to the call.
+fn charge_evm_gas<RT: Runtime>(
+    system: &mut System<RT>,
+    context: &PrecompileContext,
+    name: &'static str,
+    evm_gas: u64,
+) -> Result<(), PrecompileError> {
+    let gas = system.precompile_evm_to_fvm_gas(evm_gas);
+    if gas > context.gas_limit {
+        return Err(PrecompileError::OutOfGas);
+    }
+    system.rt.charge_gas(name, gas.try_into().unwrap_or(i64::MAX));
+    Ok(())
+}
+
+/// The FVM gas charged under the default policy for a precompile priced at `evm_gas`.
+#[cfg(test)]
+fn fvm_gas(evm_gas: u64) -> i64 {
+    use fil_actors_runtime::runtime::policy_constants::EVM_PRECOMPILE_FVM_GAS_PER_EVM_GAS;
+    (evm_gas * EVM_PRECOMPILE_FVM_GAS_PER_EVM_GAS) as i64
+}
+
 #[derive(Debug, PartialEq, Eq, Clone, Copy)]
 pub struct PrecompileContext {
     pub call_type: CallKind,
@@ -212,6 +257,19 @@ mod test {
         assert!(!is_reserved_precompile_address(&native_addr));
     }
 
+    #[test]
+    fn bls_precompiles() {
+        let addr = EthAddress(hex_literal::hex!("000000000000000000000000000000000000000a"));
+        assert!(!Precompiles::<MockRuntime>::is_precompile(&addr));
+        for index in 0x0b..=0x0f {
+            let mut addr = EthAddress([0u8; 20]);
+            addr.0[19] = index;
+            assert!(Precompiles::<MockRuntime>::is_precompile(&addr));
+        }
+        let addr = EthAddress(hex_literal::hex!("0000000000000000000000000000000000000010"));
+        assert!(!Precompiles::<MockRuntime>::is_precompile(&addr));
+    }
+
     #[test]
     fn between_precompile() {
         let addr = EthAddress(hex_literal::hex!("a000000000000000000000000000000000000001"));
@@ -32,6 +32,12 @@ impl Stack {
         self.stack.is_empty()
     }
 
+    /// Returns the value at the top of the stack, if any.
+    #[inline]
+    pub fn top(&self) -> Option<&U256> {
+        self.stack.last()
+    }
+
     #[inline(always)]
     pub fn push_unchecked(&mut self, value: U256) {
         self.stack.push(value);
@@ -1,23 +1,31 @@
 use std::borrow::Cow;
+use std::collections::BTreeMap;
 
 use fil_actors_evm_shared::{address::EthAddress, uints::U256};
+pub use fil_actors_runtime::runtime::StorageStatus;
 use fil_actors_runtime::{
-    actor_error, extract_send_result, runtime::EMPTY_ARR_CID, AsActorError, EAM_ACTOR_ID,
+    actor_error, extract_send_result, runtime::EMPTY_ARR_CID, AsActorError, Map2,
+    DEFAULT_HAMT_CONFIG, EAM_ACTOR_ID,
 };
 use fvm_ipld_blockstore::Block;
 use fvm_ipld_encoding::ipld_block::IpldBlock;
 use fvm_ipld_encoding::CborStore;
 use fvm_ipld_kamt::HashedKey;
 use fvm_shared::address::{Address, Payload};
+use fvm_shared::clock::ChainEpoch;
 use fvm_shared::crypto::hash::SupportedHashes;
 use fvm_shared::econ::TokenAmount;
 use fvm_shared::error::{ErrorNumber, ExitCode};
 use fvm_shared::sys::SendFlags;
-use fvm_shared::{MethodNum, Response, IPLD_RAW, METHOD_SEND};
+use fvm_shared::{ActorID, MethodNum, Response, IPLD_RAW, METHOD_SEND};
 use multihash_codetable::Code;
+use num_traits::Zero;
 
 use crate::state::{State, Tombstone, TransientData, TransientDataLifespan};
-use crate::BytecodeHash;
+use crate::{
+    Bytecode, BytecodeHash, EVM_CONTRACT_CODE_SIZE_EXCEEDED, INITCODE_WORD_GAS, MAX_CODE_SIZE,
+    MAX_INITCODE_SIZE,
+};
 
 use cid::Cid;
 use fil_actors_runtime::{runtime::Runtime, ActorError};
@@ -46,6 +54,15 @@ use fvm_ipld_kamt::{AsHashedKey, Config as KamtConfig, Kamt};
 // with the test contract. They might not be the best for other contracts.
 const KAMT_CONFIG: KamtConfig = KamtConfig { min_data_depth: 0, bit_width: 5, max_array_width: 1 };
 
+/// The storage KAMT bit width of contracts created before the bit width was configurable,
+/// recorded in their state by the upgrade migration.
+pub const LEGACY_STORAGE_BIT_WIDTH: u32 = KAMT_CONFIG.bit_width;
+
+/// The configuration of a contract's storage KAMT with the given bit width.
+fn storage_kamt_config(bit_width: u32) -> KamtConfig {
+    KamtConfig { bit_width, ..KAMT_CONFIG }
+}
+
 pub struct StateHashAlgorithm;
 
 /// Wrapper around the base U256 type so we can control the byte order in the hash, because
@@ -63,9 +80,27 @@ impl AsHashedKey<U256, 32> for StateHashAlgorithm {
 /// being 256 bits long, which we store in a KAMT.
 pub type StateKamt<BS> = Kamt<BS, U256, U256, StateHashAlgorithm>;
 
-/// Maximum allowed EVM bytecode size.
-/// The contract code size limit is 24kB.
-const MAX_CODE_SIZE: usize = 24 << 10;
+/// Sponsorship approvals granted by a paymaster contract, keyed by beneficiary actor ID.
+type SponsorApprovals<BS> = Map2<BS, ActorID, TokenAmount>;
+
+/// Classifies a write of `new` to a slot with the given original and current values.
+fn classify_storage_write(original: U256, current: U256, new: U256) -> StorageStatus {
+    if current == new {
+        StorageStatus::Unchanged
+    } else if original == current {
+        if original.is_zero() {
+            StorageStatus::Added
+        } else if new.is_zero() {
+            StorageStatus::Deleted
+        } else {
+            StorageStatus::Modified
+        }
+    } else if original == new {
+        StorageStatus::Restored
+    } else {
+        StorageStatus::Assigned
+    }
+}
 
 #[derive(Clone, Copy)]
 pub struct EvmBytecode {
@@ -73,11 +108,13 @@ pub struct EvmBytecode {
     pub cid: Cid,
     /// Keccak256 hash of the contract
     pub evm_hash: BytecodeHash,
+    /// CID of the contract's jump destination bitmap, if analysed
+    pub jumpdests: Option<Cid>,
 }
 
 impl EvmBytecode {
-    fn new(cid: Cid, evm_hash: BytecodeHash) -> Self {
-        Self { cid, evm_hash }
+    fn new(cid: Cid, evm_hash: BytecodeHash, jumpdests: Option<Cid>) -> Self {
+        Self { cid, evm_hash, jumpdests }
     }
 }
 
@@ -89,8 +126,17 @@ pub struct System<'r, RT: Runtime> {
     /// The current bytecode. This is usually only "none" when the actor is first constructed.
     /// (blake2b256(ipld_raw(bytecode)), keccak256(bytecode))
     bytecode: Option<EvmBytecode>,
-    /// The contract's EVM storage slots.
+    /// The contract's EVM storage slots. Writes go directly to the KAMT, whose root doesn't
+    /// depend on the order in which slots were written.
     slots: StateKamt<RT::Blockstore>,
+    /// The bit width of the storage KAMT.
+    storage_bit_width: u32,
+    /// Values of storage slots read or written by this invocation, so that hot slots are
+    /// looked up in the KAMT only once.
+    slot_cache: BTreeMap<U256, U256>,
+    /// The original values of storage slots written by this invocation, ordered by key so that
+    /// nothing derived from them depends on write order.
+    original_slots: BTreeMap<U256, U256>,
 
     /// The contract's EVM transient storage slots.
     transient_slots: StateKamt<RT::Blockstore>,
@@ -108,6 +154,16 @@ pub struct System<'r, RT: Runtime> {
     /// This is "some" if the actor is currently a "zombie". I.e., it has selfdestructed, but the
     /// current message is still executing. `System` cannot load a contracts state with a
     pub(crate) tombstone: Option<Tombstone>,
+    /// The transaction in which the contract was created, if known. SELFDESTRUCT only deletes the
+    /// contract during this transaction.
+    pub(crate) created_in: Option<Tombstone>,
+
+    /// The paymaster charged for value shortfalls of outgoing calls, if any.
+    paymaster: Option<ActorID>,
+    /// The root of the sponsorship approvals granted by this contract, if any.
+    sponsor_approvals: Option<Cid>,
+    /// The number of logs emitted by this invocation.
+    pub(crate) logs_emitted: u64,
 }
 
 impl<'r, RT: Runtime> System<'r, RT> {
@@ -118,9 +174,13 @@ impl<'r, RT: Runtime> System<'r, RT> {
         let store = rt.store().clone();
         let transient_store = rt.store().clone();
         let current_transient_data_lifespan = get_current_transient_data_lifespan(rt);
+        let storage_bit_width = rt.policy().evm_storage_bit_width;
         Self {
             rt,
-            slots: StateKamt::new_with_config(store, KAMT_CONFIG.clone()),
+            slots: StateKamt::new_with_config(store, storage_kamt_config(storage_bit_width)),
+            storage_bit_width,
+            slot_cache: BTreeMap::new(),
+            original_slots: BTreeMap::new(),
             transient_slots: StateKamt::new_with_config(transient_store, KAMT_CONFIG.clone()),
             current_transient_data_lifespan,
             nonce: 1,
@@ -129,6 +189,58 @@ impl<'r, RT: Runtime> System<'r, RT> {
             readonly,
             randomness: None,
             tombstone: None,
+            created_in: None,
+            paymaster: None,
+            sponsor_approvals: None,
+            logs_emitted: 0,
+        }
+    }
+
+    /// The remaining execution gas, in EVM gas units.
+    pub fn evm_gas_available(&self) -> u64 {
+        self.rt.gas_available() / self.fvm_gas_per_evm_gas()
+    }
+
+    /// Converts an amount of EVM gas to FVM execution gas, saturating at `u64::MAX`.
+    pub fn evm_to_fvm_gas(&self, gas: U256) -> u64 {
+        gas.to_u64_saturating().saturating_mul(self.fvm_gas_per_evm_gas())
+    }
+
+    /// Converts the EVM gas price of a precompile to FVM execution gas, saturating at `u64::MAX`.
+    pub fn precompile_evm_to_fvm_gas(&self, gas: u64) -> u64 {
+        gas.saturating_mul(self.rt.policy().evm_precompile_fvm_gas_per_evm_gas.max(1))
+    }
+
+    /// The FVM gas limit for a subcall requesting `gas_limit`: as in EIP-150, at most all but one
+    /// 64th of the remaining gas is forwarded.
+    pub fn subcall_gas_limit(&self, gas_limit: u64) -> u64 {
+        let gas_rsvp = (63 * self.rt.gas_available()) / 64;
+        std::cmp::min(gas_limit, gas_rsvp)
+    }
+
+    /// Checks the size of initcode about to be executed and charges for it per word, as in
+    /// EIP-3860.
+    pub fn charge_initcode(&self, len: usize) -> Result<(), ActorError> {
+        check_initcode_size(len)?;
+        let words = len.div_ceil(32) as u64;
+        let gas = self.evm_to_fvm_gas(U256::from(words * INITCODE_WORD_GAS));
+        self.rt.charge_gas("OnEvmInitcode", gas.try_into().unwrap_or(i64::MAX));
+        Ok(())
+    }
+
+    fn fvm_gas_per_evm_gas(&self) -> u64 {
+        self.rt.policy().evm_fvm_gas_per_evm_gas.max(1)
+    }
+
+    /// The CID of the tipset at a past epoch, or None if it is out of the lookback range or the
+    /// epoch was a null round.
+    pub fn tipset_cid(&self, epoch: ChainEpoch) -> Option<Cid> {
+        let cid = self.rt.tipset_cid(epoch).ok()?;
+        // A null round has no tipset of its own. Depending on the node, looking one up either
+        // fails or gives the CID of the preceding tipset, which no real tipset shares.
+        match self.rt.tipset_cid(epoch - 1) {
+            Ok(prev) if prev == cid => None,
+            _ => Some(cid),
         }
     }
 
@@ -150,7 +262,9 @@ impl<'r, RT: Runtime> System<'r, RT> {
             return Err(actor_error!(forbidden, "can only resurrect a dead contract"));
         }
 
-        return Ok(Self::new(rt, read_only));
+        let mut system = Self::new(rt, read_only);
+        system.created_in = Some(crate::current_tombstone(rt));
+        Ok(system)
     }
 
     /// Create the contract. This will return a new empty contract if, and only if, the contract
@@ -164,7 +278,9 @@ impl<'r, RT: Runtime> System<'r, RT> {
         if state_root != EMPTY_ARR_CID {
             return Err(actor_error!(illegal_state, "can't create over an existing actor"));
         }
-        return Ok(Self::new(rt, read_only));
+        let mut system = Self::new(rt, read_only);
+        system.created_in = Some(crate::current_tombstone(rt));
+        Ok(system)
     }
 
     /// Load the actor from state.
@@ -210,16 +326,31 @@ impl<'r, RT: Runtime> System<'r, RT> {
 
         Ok(Self {
             rt,
-            slots: StateKamt::load_with_config(&state.contract_state, store, KAMT_CONFIG.clone())
-                .context_code(ExitCode::USR_ILLEGAL_STATE, "state not in blockstore")?,
+            slots: StateKamt::load_with_config(
+                &state.contract_state,
+                store,
+                storage_kamt_config(state.storage_bit_width),
+            )
+            .context_code(ExitCode::USR_ILLEGAL_STATE, "state not in blockstore")?,
+            storage_bit_width: state.storage_bit_width,
+            slot_cache: BTreeMap::new(),
+            original_slots: BTreeMap::new(),
             transient_slots,
             current_transient_data_lifespan,
             nonce: state.nonce,
             saved_state_root: Some(state_root),
-            bytecode: Some(EvmBytecode::new(state.bytecode, state.bytecode_hash)),
+            bytecode: Some(EvmBytecode::new(
+                state.bytecode,
+                state.bytecode_hash,
+                state.bytecode_jumpdests,
+            )),
             readonly: read_only,
             randomness: None,
             tombstone: state.tombstone,
+            created_in: state.created_in,
+            paymaster: state.paymaster,
+            sponsor_approvals: state.sponsor_approvals,
+            logs_emitted: 0,
         })
     }
 
@@ -297,7 +428,7 @@ impl<'r, RT: Runtime> System<'r, RT> {
             return Err(ActorError::forbidden("contract invocation is read only".to_string()));
         }
 
-        let EvmBytecode { cid, evm_hash } = match self.bytecode {
+        let EvmBytecode { cid, evm_hash, jumpdests } = match self.bytecode {
             Some(cid) => cid,
             // set empty bytecode hashes
             None => self.set_bytecode(&[])?,
@@ -322,13 +453,18 @@ impl<'r, RT: Runtime> System<'r, RT> {
                 &State {
                     bytecode: cid,
                     bytecode_hash: evm_hash,
+                    bytecode_jumpdests: jumpdests,
                     contract_state: self.slots.flush().context_code(
                         ExitCode::USR_ILLEGAL_STATE,
                         "failed to flush contract state",
                     )?,
+                    storage_bit_width: self.storage_bit_width,
                     transient_data,
                     nonce: self.nonce,
                     tombstone: self.tombstone,
+                    created_in: self.created_in,
+                    paymaster: self.paymaster,
+                    sponsor_approvals: self.sponsor_approvals,
                 },
                 Code::Blake2b256,
             )
@@ -369,13 +505,27 @@ impl<'r, RT: Runtime> System<'r, RT> {
             _ => self.transient_slots.clear(),
         }
 
+        if state.storage_bit_width != self.storage_bit_width {
+            return Err(actor_error!(
+                illegal_state,
+                "storage bit width changed from {} to {}",
+                self.storage_bit_width,
+                state.storage_bit_width
+            ));
+        }
         self.slots
             .set_root(&state.contract_state)
             .context_code(ExitCode::USR_ILLEGAL_STATE, "state not in blockstore")?;
+        // Slots may have been written by a re-entrant call.
+        self.slot_cache.clear();
         self.nonce = state.nonce;
         self.saved_state_root = Some(root);
-        self.bytecode = Some(EvmBytecode::new(state.bytecode, state.bytecode_hash));
+        self.bytecode =
+            Some(EvmBytecode::new(state.bytecode, state.bytecode_hash, state.bytecode_jumpdests));
         self.tombstone = state.tombstone;
+        self.created_in = state.created_in;
+        self.paymaster = state.paymaster;
+        self.sponsor_approvals = state.sponsor_approvals;
         Ok(())
     }
 
@@ -384,14 +534,22 @@ impl<'r, RT: Runtime> System<'r, RT> {
         self.bytecode.as_ref().map(|b| b.cid)
     }
 
+    /// Get the bitmap of the bytecode's jump destinations, if analysed.
+    pub fn get_bytecode_jumpdests(&self) -> Option<Cid> {
+        self.bytecode.as_ref().and_then(|b| b.jumpdests)
+    }
+
     /// Set the bytecode.
     pub fn set_bytecode(&mut self, bytecode: &[u8]) -> Result<EvmBytecode, ActorError> {
         self.saved_state_root = None;
         if bytecode.len() > MAX_CODE_SIZE {
-            return Err(ActorError::illegal_argument(format!(
-                "EVM byte code length ({}) is exceeding the maximum allowed of {MAX_CODE_SIZE}",
-                bytecode.len()
-            )));
+            return Err(ActorError::unchecked(
+                EVM_CONTRACT_CODE_SIZE_EXCEEDED,
+                format!(
+                    "EIP-170: EVM byte code length ({}) is exceeding the maximum allowed of {MAX_CODE_SIZE}",
+                    bytecode.len()
+                ),
+            ));
         } else if bytecode.first() == Some(&0xEF) {
             // Reject code starting with 0xEF, EIP-3541
             return Err(ActorError::illegal_argument(
@@ -408,39 +566,59 @@ impl<'r, RT: Runtime> System<'r, RT> {
             .store()
             .put(Code::Blake2b256, &Block::new(IPLD_RAW, bytecode))
             .context_code(ExitCode::USR_ILLEGAL_STATE, "failed to write bytecode")?;
-        let bytecode = EvmBytecode::new(cid, code_hash);
+        let jumpdests = if bytecode.is_empty() {
+            None
+        } else {
+            let jumpdests = Bytecode::analyze_jumpdests(bytecode);
+            Some(
+                self.rt
+                    .store()
+                    .put(Code::Blake2b256, &Block::new(IPLD_RAW, &jumpdests))
+                    .context_code(
+                        ExitCode::USR_ILLEGAL_STATE,
+                        "failed to write jump destinations",
+                    )?,
+            )
+        };
+        let bytecode = EvmBytecode::new(cid, code_hash, jumpdests);
         self.bytecode = Some(bytecode);
         Ok(bytecode)
     }
 
     /// Get value of a storage key.
     pub fn get_storage(&mut self, key: U256) -> Result<U256, ActorError> {
-        Ok(self
+        if let Some(value) = self.slot_cache.get(&key) {
+            return Ok(*value);
+        }
+        let value = self
             .slots
             .get(&key)
             .context_code(ExitCode::USR_ILLEGAL_STATE, "failed to clear storage slot")?
             .cloned()
-            .unwrap_or_default())
+            .unwrap_or_default();
+        self.slot_cache.insert(key, value);
+        Ok(value)
     }
 
-    /// Set value of a storage key.
-    pub fn set_storage(&mut self, key: U256, value: U256) -> Result<(), ActorError> {
-        let changed = if value.is_zero() {
+    /// Set value of a storage key, returning the classification of the write.
+    pub fn set_storage(&mut self, key: U256, value: U256) -> Result<StorageStatus, ActorError> {
+        let current = if value.is_zero() {
             self.slots
                 .delete(&key)
-                .map(|v| v.is_some())
                 .context_code(ExitCode::USR_ILLEGAL_STATE, "failed to clear storage slot")?
         } else {
             self.slots
                 .set(key, value)
-                .map(|v| v != Some(value))
                 .context_code(ExitCode::USR_ILLEGAL_STATE, "failed to update storage slot")?
-        };
+        }
+        .unwrap_or_default();
+        let original = *self.original_slots.entry(key).or_insert(current);
+        self.slot_cache.insert(key, value);
 
-        if changed {
+        if current != value {
             self.saved_state_root = None; // dirty.
         };
-        Ok(())
+        Ok(classify_storage_write(original, current, value))
     }
 
     /// Get value of a transient storage key.
@@ -531,6 +709,88 @@ impl<'r, RT: Runtime> System<'r, RT> {
         }
     }
 
+    /// Get the paymaster charged for value shortfalls of outgoing calls, if any.
+    pub fn paymaster(&self) -> Option<ActorID> {
+        self.paymaster
+    }
+
+    /// Set (or clear) the paymaster charged for value shortfalls of outgoing calls.
+    pub fn set_paymaster(&mut self, paymaster: Option<ActorID>) {
+        if self.paymaster != paymaster {
+            self.saved_state_root = None;
+            self.paymaster = paymaster;
+        }
+    }
+
+    /// Get the remaining amount this contract has approved to sponsor for the beneficiary.
+    pub fn get_sponsor_approval(&self, beneficiary: ActorID) -> Result<TokenAmount, ActorError> {
+        let approvals = match self.sponsor_approvals {
+            Some(root) => self.load_sponsor_approvals(&root)?,
+            None => return Ok(TokenAmount::zero()),
+        };
+        Ok(approvals.get(&beneficiary)?.cloned().unwrap_or_default())
+    }
+
+    /// Set the amount this contract approves to sponsor for the beneficiary, replacing any
+    /// previous approval. A zero amount revokes the approval.
+    pub fn set_sponsor_approval(
+        &mut self,
+        beneficiary: ActorID,
+        amount: TokenAmount,
+    ) -> Result<(), ActorError> {
+        let mut approvals = match self.sponsor_approvals {
+            Some(root) => self.load_sponsor_approvals(&root)?,
+            None if amount.is_zero() => return Ok(()),
+            None => {
+                SponsorApprovals::empty(self.rt.store(), DEFAULT_HAMT_CONFIG, "sponsor approvals")
+            }
+        };
+        if amount.is_zero() {
+            approvals.delete(&beneficiary)?;
+        } else {
+            approvals.set(&beneficiary, amount)?;
+        }
+        let root = approvals.flush()?;
+        if self.sponsor_approvals != Some(root) {
+            self.saved_state_root = None;
+            self.sponsor_approvals = Some(root);
+        }
+        Ok(())
+    }
+
+    /// Deduct the amount from the beneficiary's sponsorship approval, failing if the beneficiary
+    /// has not been approved for at least that amount.
+    pub fn charge_sponsor_approval(
+        &mut self,
+        beneficiary: ActorID,
+        amount: &TokenAmount,
+    ) -> Result<(), ActorError> {
+        let approved = self.get_sponsor_approval(beneficiary)?;
+        if approved.is_zero() {
+            return Err(
+                actor_error!(forbidden; "actor {} is not approved for sponsorship", beneficiary),
+            );
+        }
+        if &approved < amount {
+            return Err(actor_error!(insufficient_funds;
+                "sponsorship of {} exceeds remaining approval {} for actor {}", amount, approved, beneficiary));
+        }
+        self.set_sponsor_approval(beneficiary, approved - amount)
+    }
+
+    fn load_sponsor_approvals(
+        &self,
+        root: &Cid,
+    ) -> Result<SponsorApprovals<&'r RT::Blockstore>, ActorError> {
+        SponsorApprovals::load(self.rt.store(), root, DEFAULT_HAMT_CONFIG, "sponsor approvals")
+    }
+
+    /// Whether the contract was created in the currently executing transaction, and may therefore
+    /// be deleted by SELFDESTRUCT (EIP-6780).
+    pub fn created_in_current_transaction(&self) -> bool {
+        self.created_in == Some(crate::current_tombstone(self.rt))
+    }
+
     /// Mark ourselves as "selfdestructed".
     pub fn mark_selfdestructed(&mut self) {
         self.saved_state_root = None;
@@ -538,6 +798,19 @@ impl<'r, RT: Runtime> System<'r, RT> {
     }
 }
 
+/// Rejects initcode exceeding the maximum initcode size (EIP-3860).
+pub(crate) fn check_initcode_size(len: usize) -> Result<(), ActorError> {
+    if len > MAX_INITCODE_SIZE {
+        return Err(ActorError::unchecked(
+            EVM_CONTRACT_CODE_SIZE_EXCEEDED,
+            format!(
+                "EIP-3860: initcode length ({len}) is exceeding the maximum allowed of {MAX_INITCODE_SIZE}"
+            ),
+        ));
+    }
+    Ok(())
+}
+
 /// Returns the current transient data lifespan based on the execution environment.
 fn get_current_transient_data_lifespan<RT: Runtime>(rt: &RT) -> TransientDataLifespan {
     TransientDataLifespan {
@@ -545,3 +818,99 @@ fn get_current_transient_data_lifespan<RT: Runtime>(rt: &RT) -> TransientDataLif
         nonce: rt.message().nonce(),
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use fil_actors_evm_shared::uints::U256;
+    use fil_actors_runtime::runtime::Runtime;
+    use fil_actors_runtime::test_utils::MockRuntime;
+
+    use super::{classify_storage_write, StorageStatus, System};
+    use crate::State;
+
+    #[test]
+    fn classify_storage_status() {
+        let (zero, a, b, c) = (U256::zero(), U256::from(1), U256::from(2), U256::from(3));
+        let cases = [
+            // (original, current, new)
+            ((zero, zero, zero), StorageStatus::Unchanged),
+            ((a, b, b), StorageStatus::Unchanged),
+            ((zero, zero, a), StorageStatus::Added),
+            ((a, a, b), StorageStatus::Modified),
+            ((a, a, zero), StorageStatus::Deleted),
+            ((a, b, a), StorageStatus::Restored),
+            ((zero, a, zero), StorageStatus::Restored),
+            ((a, zero, a), StorageStatus::Restored),
+            ((a, b, c), StorageStatus::Assigned),
+            ((a, zero, b), StorageStatus::Assigned),
+            ((zero, a, b), StorageStatus::Assigned),
+        ];
+        for ((original, current, new), expected) in cases {
+            assert_eq!(expected, classify_storage_write(original, current, new));
+        }
+    }
+
+    #[test]
+    fn flushed_state_independent_of_write_order() {
+        let writes: Vec<(U256, U256)> =
+            (1..=8u64).map(|i| (U256::from(i * 0x1000 + i), U256::from(i))).collect();
+        let mut orders = vec![writes.clone(), writes.iter().rev().cloned().collect()];
+        let (evens, odds): (Vec<_>, Vec<_>) =
+            writes.iter().enumerate().partition(|(i, _)| i % 2 == 0);
+        orders.push(odds.into_iter().chain(evens).map(|(_, w)| *w).collect());
+
+        let roots: Vec<_> = orders
+            .into_iter()
+            .map(|order| {
+                let rt = MockRuntime::default();
+                rt.in_call.replace(true);
+                let mut system = System::new(&rt, false);
+                for (key, value) in order {
+                    system.set_storage(key, value).unwrap();
+                }
+                system.flush().unwrap();
+                rt.get_state_root().unwrap()
+            })
+            .collect();
+        assert!(roots.windows(2).all(|w| w[0] == w[1]), "state roots differ: {:?}", roots);
+    }
+
+    #[test]
+    fn repeated_reads_hit_slot_cache() {
+        let rt = MockRuntime::default();
+        rt.in_call.replace(true);
+        let key = U256::from(0x1234);
+        let mut system = System::new(&rt, false);
+        system.set_storage(key, U256::from(42)).unwrap();
+        system.flush().unwrap();
+
+        let mut system = System::load(&rt).unwrap();
+        assert_eq!(U256::from(42), system.get_storage(key).unwrap());
+        let reads = rt.store.stats.borrow().r;
+        for _ in 0..10 {
+            assert_eq!(U256::from(42), system.get_storage(key).unwrap());
+        }
+        assert_eq!(reads, rt.store.stats.borrow().r);
+    }
+
+    #[test]
+    fn storage_bit_width_fixed_at_creation() {
+        let mut rt = MockRuntime::default();
+        rt.in_call.replace(true);
+        rt.policy.evm_storage_bit_width = 8;
+        let mut system = System::new(&rt, false);
+        for i in 0..64u64 {
+            system.set_storage(U256::from(i), U256::from(i + 1)).unwrap();
+        }
+        system.flush().unwrap();
+        let state: State = rt.get_state();
+        assert_eq!(8, state.storage_bit_width);
+
+        // Changing the policy doesn't affect existing contracts.
+        rt.policy.evm_storage_bit_width = 5;
+        let mut system = System::load(&rt).unwrap();
+        for i in 0..64u64 {
+            assert_eq!(U256::from(i + 1), system.get_storage(U256::from(i)).unwrap());
+        }
+    }
+}
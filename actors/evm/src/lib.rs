@@ -1,7 +1,8 @@
 use fil_actors_evm_shared::address::EthAddress;
 use fil_actors_runtime::{
-    actor_dispatch_unrestricted, actor_error, ActorError, AsActorError, WithCodec, EAM_ACTOR_ADDR,
-    INIT_ACTOR_ADDR,
+    actor_dispatch_unrestricted, actor_error, deserialize_block, extract_send_result,
+    migrate_state, ActorContext, ActorError, AsActorError, OnUpgradeParams, WithCodec,
+    EAM_ACTOR_ADDR, INIT_ACTOR_ADDR, SYSTEM_ACTOR_ADDR,
 };
 use fvm_ipld_blockstore::Blockstore;
 use fvm_ipld_encoding::ipld_block::IpldBlock;
@@ -9,20 +10,27 @@ use fvm_ipld_encoding::{BytesSer, DAG_CBOR};
 use fvm_shared::address::Address;
 use fvm_shared::econ::TokenAmount;
 use fvm_shared::error::ExitCode;
+use fvm_shared::sys::SendFlags;
 
 use crate::interpreter::Outcome;
-use crate::interpreter::{execute, Bytecode, ExecutionState, System};
+use crate::interpreter::{execute, Bytecode, ExecutionState, Memory, System};
+use crate::migration::LegacyState;
 use crate::reader::ValueReader;
 use cid::Cid;
 use fil_actors_runtime::runtime::{ActorCode, Runtime};
 use fvm_shared::METHOD_CONSTRUCTOR;
 use num_derive::FromPrimitive;
+use num_traits::Zero;
 
 pub use types::*;
 
-#[doc(hidden)]
+#[cfg(feature = "testing")]
 pub mod ext;
+#[cfg(not(feature = "testing"))]
+#[allow(dead_code)]
+mod ext;
 pub mod interpreter;
+pub mod migration;
 pub(crate) mod reader;
 mod state;
 mod types;
@@ -40,6 +48,15 @@ pub const EVM_CONTRACT_STACK_OVERFLOW: ExitCode = ExitCode::new(37);
 pub const EVM_CONTRACT_ILLEGAL_MEMORY_ACCESS: ExitCode = ExitCode::new(38);
 pub const EVM_CONTRACT_BAD_JUMPDEST: ExitCode = ExitCode::new(39);
 pub const EVM_CONTRACT_SELFDESTRUCT_FAILED: ExitCode = ExitCode::new(40);
+pub const EVM_CONTRACT_LOG_LIMIT_EXCEEDED: ExitCode = ExitCode::new(41);
+pub const EVM_CONTRACT_CODE_SIZE_EXCEEDED: ExitCode = ExitCode::new(42);
+
+/// Maximum size of deployed contract code (EIP-170).
+pub const MAX_CODE_SIZE: usize = 24 << 10;
+/// Maximum size of contract initcode (EIP-3860).
+pub const MAX_INITCODE_SIZE: usize = 2 * MAX_CODE_SIZE;
+/// EVM gas charged per 32 byte word of initcode (EIP-3860).
+pub const INITCODE_WORD_GAS: u64 = 2;
 
 const EVM_MAX_RESERVED_METHOD: u64 = 1023;
 pub const NATIVE_METHOD_SIGNATURE: &str = "handle_filecoin_method(uint64,uint64,bytes)";
@@ -66,6 +83,9 @@ pub enum Method {
     GetStorageAt = 5,
     InvokeContractDelegate = 6,
     InvokeContract = frc42_dispatch::method_hash!("InvokeEVM"),
+    SponsorValueShortfall = frc42_dispatch::method_hash!("SponsorValueShortfall"),
+    GetBytecodeHashExported = frc42_dispatch::method_hash!("GetBytecodeHash"),
+    GetStorageAtExported = frc42_dispatch::method_hash!("GetStorageAt"),
 }
 
 pub struct EvmContractActor;
@@ -87,16 +107,33 @@ pub(crate) fn is_dead(rt: &impl Runtime, state: &State) -> bool {
     state.tombstone.map_or(false, |t| t != current_tombstone(rt))
 }
 
-fn load_bytecode(bs: &impl Blockstore, cid: &Cid) -> Result<Option<Bytecode>, ActorError> {
+/// Loads bytecode along with its jump destinations, which are analysed if not given.
+fn load_bytecode(
+    bs: &impl Blockstore,
+    cid: &Cid,
+    jumpdests: Option<&Cid>,
+) -> Result<Option<Bytecode>, ActorError> {
     let bytecode = bs
         .get(cid)
         .context_code(ExitCode::USR_NOT_FOUND, "failed to read bytecode")?
         .expect("bytecode not in state tree");
     if bytecode.is_empty() {
-        Ok(None)
-    } else {
-        Ok(Some(Bytecode::new(bytecode)))
+        return Ok(None);
     }
+    let bytecode = match jumpdests {
+        Some(jumpdests) => {
+            let jumpdests = bs
+                .get(jumpdests)
+                .context_code(ExitCode::USR_NOT_FOUND, "failed to read jump destinations")?
+                .context_code(ExitCode::USR_ILLEGAL_STATE, "jump destinations not in state tree")?;
+            Bytecode::with_jumpdests(bytecode, jumpdests).context_code(
+                ExitCode::USR_ILLEGAL_STATE,
+                "jump destinations don't match bytecode",
+            )?
+        }
+        None => Bytecode::new(bytecode),
+    };
+    Ok(Some(bytecode))
 }
 
 fn initialize_evm_contract(
@@ -125,9 +162,13 @@ fn initialize_evm_contract(
         return system.flush();
     }
 
+    // Meter the initcode before executing it, as its size is not otherwise bounded by execution.
+    system.charge_initcode(initcode.len())?;
+
     // create a new execution context
     let value_received = system.rt.message().value_received();
     let mut exec_state = ExecutionState::new(caller, receiver_eth_addr, value_received, Vec::new());
+    exec_state.memory = Memory::with_limit(system.rt.policy().evm_max_memory_size);
 
     // identify bytecode valid jump destinations
     let initcode = Bytecode::new(initcode);
@@ -152,6 +193,7 @@ fn invoke_contract_inner<RT>(
     system: &mut System<RT>,
     input_data: Vec<u8>,
     bytecode_cid: &Cid,
+    jumpdests: Option<&Cid>,
     caller: &EthAddress,
     value_received: TokenAmount,
 ) -> Result<Vec<u8>, ActorError>
@@ -159,7 +201,7 @@ where
     RT: Runtime,
     RT::Blockstore: Clone,
 {
-    let bytecode = match load_bytecode(system.rt.store(), bytecode_cid)? {
+    let bytecode = match load_bytecode(system.rt.store(), bytecode_cid, jumpdests)? {
         Some(bytecode) => bytecode,
         // an EVM contract with no code returns immediately
         None => return Ok(Vec::new()),
@@ -171,6 +213,7 @@ where
 
     let mut exec_state =
         ExecutionState::new(*caller, receiver_eth_addr, value_received, input_data);
+    exec_state.memory = Memory::with_limit(system.rt.policy().evm_max_memory_size);
 
     let output = execute(&bytecode, &mut exec_state, system)?;
 
@@ -229,6 +272,8 @@ impl EvmContractActor {
             &mut system,
             params.input,
             &params.code,
+            // The delegate's jump destinations aren't known, so its bytecode is analysed.
+            None,
             &params.caller,
             params.value,
         )?;
@@ -244,6 +289,7 @@ impl EvmContractActor {
         RT::Blockstore: Clone,
     {
         rt.validate_immediate_caller_accept_any()?;
+        check_call_filter(rt, &params.input_data)?;
 
         let mut system = System::load(rt).map_err(|e| {
             ActorError::unspecified(format!("failed to create execution abstraction layer: {e:?}"))
@@ -255,12 +301,14 @@ impl EvmContractActor {
             None => return Ok(InvokeContractReturn { output_data: Vec::new() }),
         };
 
+        let jumpdests = system.get_bytecode_jumpdests();
         let received_value = system.rt.message().value_received();
         let caller = system.resolve_ethereum_address(&system.rt.message().caller()).unwrap();
         let data = invoke_contract_inner(
             &mut system,
             params.input_data,
             &bytecode_cid,
+            jumpdests.as_ref(),
             &caller,
             received_value,
         )?;
@@ -301,6 +349,8 @@ impl EvmContractActor {
         }
     }
 
+    /// Returns the keccak hash of the contract's EVM bytecode, or the hash of empty bytecode if
+    /// the contract has been deleted.
     pub fn bytecode_hash(rt: &impl Runtime) -> Result<BytecodeHash, ActorError> {
         // Any caller can fetch the bytecode hash of a contract; this is where EXTCODEHASH gets it's value for EVM contracts.
         rt.validate_immediate_caller_accept_any()?;
@@ -314,6 +364,8 @@ impl EvmContractActor {
         }
     }
 
+    /// Returns the value of a storage slot, or zero if the slot is unset or the contract has been
+    /// deleted. Intended for off-chain queries such as eth_getStorageAt.
     pub fn storage_at<RT>(
         rt: &RT,
         params: GetStorageAtParams,
@@ -333,6 +385,83 @@ impl EvmContractActor {
 
         Ok(GetStorageAtReturn { storage: val })
     }
+
+    /// Covers a value shortfall for a contract that has designated this contract as its
+    /// paymaster, within the amount this contract has approved for it via the
+    /// `approve_sponsorship` precompile. The amount is transferred to the calling contract.
+    pub fn sponsor_value_shortfall<RT>(
+        rt: &RT,
+        params: SponsorValueShortfallParams,
+    ) -> Result<(), ActorError>
+    where
+        RT: Runtime,
+        RT::Blockstore: Clone,
+    {
+        rt.validate_immediate_caller_accept_any()?;
+
+        if !params.amount.is_positive() {
+            return Err(
+                actor_error!(illegal_argument; "sponsored amount {} must be positive", params.amount),
+            );
+        }
+        let caller = rt.message().caller();
+        let beneficiary = caller
+            .id()
+            .context_code(ExitCode::USR_ASSERTION_FAILED, "caller must be an ID address")?;
+
+        // If the contract is dead, it has no approvals and this will fail.
+        let mut system = System::load(rt)?;
+        system.charge_sponsor_approval(beneficiary, &params.amount)?;
+        system.flush()?;
+        system.transfer(&caller, params.amount)
+    }
+}
+
+/// Checks that the call filter registered with the system actor, if any, allows the current
+/// call to this contract to execute. The filter is consulted only if enabled by policy.
+fn check_call_filter(rt: &impl Runtime, input_data: &[u8]) -> Result<(), ActorError> {
+    if !rt.policy().evm_call_filter_enabled {
+        return Ok(());
+    }
+
+    let ext::system::GetCallFilterReturn { call_filter } = deserialize_block(
+        extract_send_result(rt.send(
+            &SYSTEM_ACTOR_ADDR,
+            ext::system::GET_CALL_FILTER_METHOD,
+            None,
+            TokenAmount::zero(),
+            None,
+            SendFlags::READ_ONLY,
+        ))
+        .context("failed to get call filter")?,
+    )?;
+    let Some(call_filter) = call_filter else {
+        return Ok(());
+    };
+
+    let contract = rt.message().receiver();
+    let caller = rt.message().caller();
+    let ext::call_filter::CheckCallReturn { allowed } = deserialize_block(
+        extract_send_result(rt.send(
+            &call_filter,
+            ext::call_filter::CHECK_CALL_METHOD,
+            IpldBlock::serialize_cbor(&ext::call_filter::CheckCallParams {
+                contract,
+                caller,
+                value: rt.message().value_received(),
+                input_data: input_data.to_vec(),
+            })?,
+            TokenAmount::zero(),
+            None,
+            SendFlags::READ_ONLY,
+        ))
+        .with_context(|| format!("call filter {} failed", call_filter))?,
+    )?;
+    if !allowed {
+        return Err(actor_error!(forbidden;
+            "call to {} from {} rejected by call filter {}", contract, caller, call_filter));
+    }
+    Ok(())
 }
 
 /// Format "filecoin_native_method" input parameters.
@@ -417,14 +546,23 @@ impl ActorCode for EvmContractActor {
         "EVMContract"
     }
 
+    fn on_upgrade<RT>(rt: &RT, params: OnUpgradeParams) -> Result<(), ActorError>
+    where
+        RT: Runtime,
+        RT::Blockstore: Clone,
+    {
+        migrate_state(rt, &params.old_state, |rt, legacy: LegacyState| legacy.migrate(rt.store()))
+    }
+
     actor_dispatch_unrestricted! {
         Constructor => constructor,
         InvokeContract => invoke_contract [default_params],
         GetBytecode => bytecode,
-        GetBytecodeHash => bytecode_hash,
-        GetStorageAt => storage_at,
+        GetBytecodeHash|GetBytecodeHashExported => bytecode_hash,
+        GetStorageAt|GetStorageAtExported => storage_at,
         InvokeContractDelegate => invoke_contract_delegate,
         Resurrect => resurrect,
+        SponsorValueShortfall => sponsor_value_shortfall,
         _ => handle_filecoin_method,
     }
 }
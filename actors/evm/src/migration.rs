@@ -0,0 +1,57 @@
+use cid::Cid;
+use fil_actors_runtime::{ActorError, AsActorError};
+use fvm_ipld_blockstore::{Block, Blockstore};
+use fvm_ipld_encoding::tuple::*;
+use fvm_shared::error::ExitCode;
+use fvm_shared::IPLD_RAW;
+use multihash_codetable::Code;
+
+use crate::interpreter::{Bytecode, LEGACY_STORAGE_BIT_WIDTH};
+use crate::state::{BytecodeHash, State, Tombstone, TransientData};
+
+/// EVM contract state as stored before paymasters, configurable storage bit widths, creation
+/// transactions and jump destination bitmaps were recorded.
+#[derive(Debug, Serialize_tuple, Deserialize_tuple)]
+pub struct LegacyState {
+    pub bytecode: Cid,
+    pub bytecode_hash: BytecodeHash,
+    pub contract_state: Cid,
+    pub transient_data: Option<TransientData>,
+    pub nonce: u64,
+    pub tombstone: Option<Tombstone>,
+}
+
+impl LegacyState {
+    /// Migrates to the current state schema, with no paymaster or sponsorship approvals.
+    /// The contract's storage keeps the bit width it was created with, and its creation
+    /// transaction is unknown. The bytecode is analysed to record its jump destinations.
+    pub fn migrate(self, store: &impl Blockstore) -> Result<State, ActorError> {
+        let bytecode = store
+            .get(&self.bytecode)
+            .context_code(ExitCode::USR_ILLEGAL_STATE, "failed to read bytecode to migrate")?
+            .context_code(ExitCode::USR_ILLEGAL_STATE, "bytecode not in state tree")?;
+        let bytecode_jumpdests =
+            if bytecode.is_empty() {
+                None
+            } else {
+                let jumpdests = Bytecode::analyze_jumpdests(&bytecode);
+                Some(store.put(Code::Blake2b256, &Block::new(IPLD_RAW, &jumpdests)).context_code(
+                    ExitCode::USR_ILLEGAL_STATE,
+                    "failed to write jump destinations",
+                )?)
+            };
+        Ok(State {
+            bytecode: self.bytecode,
+            bytecode_hash: self.bytecode_hash,
+            bytecode_jumpdests,
+            contract_state: self.contract_state,
+            storage_bit_width: LEGACY_STORAGE_BIT_WIDTH,
+            transient_data: self.transient_data,
+            nonce: self.nonce,
+            tombstone: self.tombstone,
+            created_in: None,
+            paymaster: None,
+            sponsor_approvals: None,
+        })
+    }
+}
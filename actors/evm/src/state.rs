@@ -120,12 +120,23 @@ pub struct State {
     /// The EVM contract bytecode hash keccak256(bytecode)
     pub bytecode_hash: BytecodeHash,
 
+    /// The bitmap of valid jump destinations in the bytecode, stored as a raw block so that
+    /// invocations needn't analyse the bytecode again. None for empty bytecode.
+    /// The upgrade migration records the bitmap for contracts deployed before it was stored.
+    pub bytecode_jumpdests: Option<Cid>,
+
     /// The EVM contract state dictionary.
     /// All eth contract state is a map of U256 -> U256 values.
     ///
     /// KAMT<U256, U256>
     pub contract_state: Cid,
 
+    /// The bit width of the `contract_state` KAMT, fixed when the contract is created.
+    /// Contracts migrated from the legacy layout use [`LEGACY_STORAGE_BIT_WIDTH`].
+    ///
+    /// [`LEGACY_STORAGE_BIT_WIDTH`]: crate::interpreter::LEGACY_STORAGE_BIT_WIDTH
+    pub storage_bit_width: u32,
+
     /// The data that containers tranisent state. The struct includes the CID of the KAMT that
     /// contains the state data and the tuple of nonce and actor id that represents
     /// the lifespan of the transient storage data
@@ -141,8 +152,9 @@ pub struct State {
     ///
     /// Specifically:
     ///
-    /// 1. On SELFDESTRUCT, they mark themselves as "deleted" (by setting a tombstone with the
-    ///    current origin/nonce), send away all funds, and return immediately.
+    /// 1. On SELFDESTRUCT in the transaction that created them (see `created_in`), they mark
+    ///    themselves as "deleted" (by setting a tombstone with the current origin/nonce), send
+    ///    away all funds, and return immediately.
     /// 2. For the rest of the current transaction (as long as the tombstone's origin/nonce matches
     ///    the currently executing top-level transaction) , the contract continues to behave
     ///    normally.
@@ -152,6 +164,23 @@ pub struct State {
     ///
     /// See https://github.com/filecoin-project/ref-fvm/issues/1174 for some context.
     pub tombstone: Option<Tombstone>,
+
+    /// The transaction (origin and nonce) in which this contract was created or last resurrected.
+    ///
+    /// As in EIP-6780, SELFDESTRUCT only deletes the contract within this transaction; at any
+    /// later point it just sends away the contract's funds. None for contracts created before this
+    /// was recorded, which can therefore never be deleted.
+    pub created_in: Option<Tombstone>,
+
+    /// The paymaster charged for any value shortfall when this contract calls another actor
+    /// with more value than its balance. Set via the `set_paymaster` precompile.
+    pub paymaster: Option<ActorID>,
+
+    /// The amounts this contract has approved to sponsor for other contracts' value shortfalls,
+    /// keyed by the beneficiary's actor ID. Set via the `approve_sponsorship` precompile.
+    ///
+    /// HAMT<ActorID, TokenAmount>, or None if no sponsorship has ever been approved.
+    pub sponsor_approvals: Option<Cid>,
 }
 
 #[cfg(test)]
@@ -65,3 +65,9 @@ pub struct DelegateCallReturn {
 pub struct GetStorageAtParams {
     pub storage_key: U256,
 }
+
+#[derive(Serialize_tuple, Deserialize_tuple)]
+#[serde(transparent)]
+pub struct SponsorValueShortfallParams {
+    pub amount: TokenAmount,
+}
@@ -2,11 +2,15 @@ mod asm;
 
 use cid::Cid;
 use fil_actor_evm as evm;
+use fil_actor_evm::interpreter::LEGACY_STORAGE_BIT_WIDTH;
+use fil_actor_evm::migration::LegacyState;
 use fil_actors_evm_shared::uints::U256;
 use fil_actors_runtime::test_utils::*;
 use fvm_ipld_blockstore::Blockstore;
 use fvm_ipld_encoding::ipld_block::IpldBlock;
+use fvm_ipld_encoding::BytesSer;
 use fvm_shared::address::Address;
+use fvm_shared::error::ExitCode;
 
 mod util;
 
@@ -160,6 +164,50 @@ sstore";
     rt.verify();
 }
 
+#[test]
+fn get_storage_at_exported() {
+    let init_code = {
+        let init = r"
+push2 0xfffa
+push2 0x8965
+sstore";
+        let body = r#"return"#;
+
+        asm::new_contract("get_storage_at_exported", init, body).unwrap()
+    };
+
+    let rt = util::construct_and_verify(init_code);
+    rt.reset();
+    let params = evm::GetStorageAtParams { storage_key: 0x8965.into() };
+
+    let sender = Address::new_id(0);
+    rt.expect_validate_caller_addr(vec![sender]);
+    rt.caller.replace(sender);
+    let value: U256 = rt
+        .call::<evm::EvmContractActor>(
+            evm::Method::GetStorageAtExported as u64,
+            IpldBlock::serialize_cbor(&params).unwrap(),
+        )
+        .unwrap()
+        .unwrap()
+        .deserialize()
+        .unwrap();
+    rt.verify();
+    assert_eq!(U256::from(0xfffa), value);
+
+    // Still not invokable on-chain.
+    rt.caller.replace(Address::new_id(1000));
+    rt.expect_validate_caller_addr(vec![sender]);
+    expect_abort(
+        ExitCode::USR_FORBIDDEN,
+        rt.call::<evm::EvmContractActor>(
+            evm::Method::GetStorageAtExported as u64,
+            IpldBlock::serialize_cbor(&params).unwrap(),
+        ),
+    );
+    rt.verify();
+}
+
 #[test]
 fn test_push_last_byte() {
     // 60 01 # len
@@ -262,3 +310,115 @@ fn mcopy_test(bytecode: Vec<u8>) {
     let result = util::invoke_contract(&rt, &solidity_params);
     assert_eq!(&*result, &*encoded_testdata);
 }
+
+#[test]
+fn jumpdests_persisted_with_bytecode() {
+    let contract = Address::new_id(100);
+    let rt = util::init_construct_and_verify(
+        hex::decode(include_str!("contracts/simplecoin.hex")).unwrap(),
+        |rt| {
+            rt.actor_code_cids.borrow_mut().insert(contract, *EVM_ACTOR_CODE_ID);
+            rt.set_origin(contract);
+        },
+    );
+
+    // The jump destinations are analysed once, when the bytecode is deployed.
+    let mut state: evm::State = rt.get_state();
+    let bytecode = rt.store.get(&state.bytecode).unwrap().unwrap();
+    let jumpdests_cid = state.bytecode_jumpdests.expect("jump destinations not stored");
+    let jumpdests = rt.store.get(&jumpdests_cid).unwrap().unwrap();
+    assert_eq!(evm::interpreter::Bytecode::analyze_jumpdests(&bytecode), jumpdests);
+
+    // getBalance of the owner, which jumps through the dispatcher.
+    let mut get_balance = hex::decode("f8b2cb4f").unwrap();
+    let mut owner = [0u8; 32];
+    owner[12] = 0xff;
+    owner[31] = 100;
+    get_balance.extend_from_slice(&owner);
+    let result = util::invoke_contract(&rt, &get_balance);
+    assert_eq!(U256::from_big_endian(&result), U256::from(10000));
+
+    // Bytecode deployed without stored jump destinations is analysed on invocation.
+    state.bytecode_jumpdests = None;
+    rt.replace_state(&state);
+    let result = util::invoke_contract(&rt, &get_balance);
+    assert_eq!(U256::from_big_endian(&result), U256::from(10000));
+
+    // Jump destinations that don't match the bytecode are rejected.
+    state.bytecode_jumpdests = Some(state.bytecode);
+    rt.replace_state(&state);
+    rt.expect_validate_caller_any();
+    expect_abort(
+        ExitCode::USR_ILLEGAL_STATE,
+        rt.call::<evm::EvmContractActor>(
+            evm::Method::InvokeContract as u64,
+            IpldBlock::serialize_cbor(&BytesSer(&get_balance)).unwrap(),
+        ),
+    );
+}
+
+fn construct_simplecoin() -> MockRuntime {
+    let contract = Address::new_id(100);
+    util::init_construct_and_verify(
+        hex::decode(include_str!("contracts/simplecoin.hex")).unwrap(),
+        |rt| {
+            rt.actor_code_cids.borrow_mut().insert(contract, *EVM_ACTOR_CODE_ID);
+            rt.set_origin(contract);
+        },
+    )
+}
+
+// Returns the simplecoin balance of the contract's creator.
+fn simplecoin_balance(rt: &MockRuntime) -> U256 {
+    let mut get_balance = hex::decode("f8b2cb4f").unwrap();
+    let mut owner = [0u8; 32];
+    owner[12] = 0xff;
+    owner[31] = 100;
+    get_balance.extend_from_slice(&owner);
+    U256::from_big_endian(&util::invoke_contract(rt, &get_balance))
+}
+
+/// Rewrites the contract's state in the layout stored before the current schema.
+fn replace_with_legacy_state(rt: &MockRuntime) -> evm::State {
+    let st: evm::State = rt.get_state();
+    rt.replace_state(&LegacyState {
+        bytecode: st.bytecode,
+        bytecode_hash: st.bytecode_hash,
+        contract_state: st.contract_state,
+        transient_data: st.transient_data,
+        nonce: st.nonce,
+        tombstone: st.tombstone,
+    });
+    st
+}
+
+#[test]
+fn upgrade_migrates_legacy_state() {
+    let rt = construct_simplecoin();
+    let st = replace_with_legacy_state(&rt);
+
+    rt.upgrade::<evm::EvmContractActor>();
+    let migrated: evm::State = rt.get_state();
+    assert_eq!(st.contract_state, migrated.contract_state);
+    // The migration records the same jump destinations as deploying the contract did.
+    assert!(migrated.bytecode_jumpdests.is_some());
+    assert_eq!(st.bytecode_jumpdests, migrated.bytecode_jumpdests);
+    assert_eq!((None, None), (migrated.paymaster, migrated.sponsor_approvals));
+
+    // The migrated contract keeps its storage.
+    assert_eq!(U256::from(10000), simplecoin_balance(&rt));
+}
+
+#[test]
+fn upgrade_keeps_legacy_storage_bit_width() {
+    let mut rt = construct_simplecoin();
+    replace_with_legacy_state(&rt);
+
+    // Contracts created after the upgrade use the policy's bit width, but legacy
+    // contracts keep the one their storage was built with.
+    rt.policy.evm_storage_bit_width = LEGACY_STORAGE_BIT_WIDTH + 3;
+    rt.upgrade::<evm::EvmContractActor>();
+    let migrated: evm::State = rt.get_state();
+    assert_eq!(LEGACY_STORAGE_BIT_WIDTH, migrated.storage_bit_width);
+    assert_eq!(U256::from(10000), simplecoin_balance(&rt));
+}
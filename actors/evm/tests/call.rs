@@ -11,7 +11,7 @@ use fil_actors_evm_shared::address::EthAddress;
 use fil_actors_evm_shared::uints::U256;
 use fil_actors_runtime::{test_utils::*, EAM_ACTOR_ID, INIT_ACTOR_ADDR};
 use fvm_ipld_encoding::ipld_block::IpldBlock;
-use fvm_ipld_encoding::{BytesDe, BytesSer, CBOR, IPLD_RAW};
+use fvm_ipld_encoding::{BytesDe, BytesSer, CBOR, DAG_CBOR, IPLD_RAW};
 use fvm_shared::address::Address as FILAddress;
 use fvm_shared::address::Address;
 use fvm_shared::bigint::Zero;
@@ -753,6 +753,110 @@ fn call_actor_syscall_error() {
     test.run_test_expecting(&rt, expect, util::PrecompileExit::Success);
 }
 
+#[test]
+fn call_actor_param_codecs() {
+    let addr = Address::new_delegated(1234, b"foobarboxy").unwrap();
+    let send_params = vec![0xab; 32];
+    let ret_data = vec![0xde, 0xad, 0xbe, 0xef];
+
+    for codec in [CBOR, DAG_CBOR, IPLD_RAW] {
+        let contract = {
+            let (init, body) = util::PrecompileTest::test_runner_assembly();
+            asm::new_contract("call_actor-precompile-test", &init, &body).unwrap()
+        };
+        let rt = util::construct_and_verify(contract);
+
+        let mut call_params = CallActorParams::default();
+        call_params
+            .codec(U256::from(codec))
+            .set_params(CallActorParams::FIRST_DYNAMIC_OFFSET, Some(send_params.clone()))
+            .set_addr(CallActorParams::EMPTY_PARAM_ADDR_OFFSET + 32, addr.to_bytes());
+
+        // The params are passed with their codec, and the return value's codec is reported.
+        rt.expect_send(
+            addr,
+            0,
+            Some(IpldBlock { codec, data: send_params.clone() }),
+            TokenAmount::zero(),
+            Some(0),
+            SendFlags::empty(),
+            Some(IpldBlock { codec: IPLD_RAW, data: ret_data.clone() }),
+            ExitCode::OK,
+            None,
+        );
+
+        let mut data = vec![0; 32];
+        data[..ret_data.len()].copy_from_slice(&ret_data);
+        let expect = CallActorReturn {
+            codec: IPLD_RAW,
+            data_offset: 96,
+            data_size: ret_data.len() as u32,
+            data,
+            ..Default::default()
+        };
+
+        let mut test = util::PrecompileTest {
+            precompile_address: util::NativePrecompile::CallActor.eth_address(),
+            output_size: 32,
+            gas_avaliable: 10_000_000_000u64,
+            call_op: util::PrecompileCallOpcode::DelegateCall,
+            expected_return: vec![],
+            expected_exit_code: util::PrecompileExit::Success,
+            input: call_params.into(),
+        };
+        test.run_test_expecting(&rt, expect, util::PrecompileExit::Success);
+    }
+}
+
+#[test]
+fn call_actor_revert_data() {
+    let contract = {
+        let (init, body) = util::PrecompileTest::test_runner_assembly();
+        asm::new_contract("call_actor-precompile-test", &init, &body).unwrap()
+    };
+    let rt = util::construct_and_verify(contract);
+    let addr = Address::new_delegated(1234, b"foobarboxy").unwrap();
+
+    let mut call_params = CallActorParams::default();
+    call_params.set_addr(CallActorParams::EMPTY_PARAM_ADDR_OFFSET, addr.to_bytes());
+
+    // The callee reverts with an Error(string) selector, wrapped as the EVM actor does.
+    let revert_data = vec![0x08, 0xc3, 0x79, 0xa0];
+    rt.expect_send(
+        addr,
+        0,
+        None,
+        TokenAmount::zero(),
+        Some(0),
+        SendFlags::empty(),
+        IpldBlock::serialize_cbor(&BytesSer(&revert_data)).unwrap(),
+        EVM_CONTRACT_REVERTED,
+        None,
+    );
+
+    // The revert data is passed on unwrapped.
+    let mut data = vec![0; 32];
+    data[..revert_data.len()].copy_from_slice(&revert_data);
+    let expect = CallActorReturn {
+        send_exit_code: U256::from(EVM_CONTRACT_REVERTED.value()),
+        codec: IPLD_RAW,
+        data_offset: 96,
+        data_size: revert_data.len() as u32,
+        data,
+    };
+
+    let mut test = util::PrecompileTest {
+        precompile_address: util::NativePrecompile::CallActor.eth_address(),
+        output_size: 32,
+        gas_avaliable: 10_000_000_000u64,
+        call_op: util::PrecompileCallOpcode::DelegateCall,
+        expected_return: vec![],
+        expected_exit_code: util::PrecompileExit::Success,
+        input: call_params.into(),
+    };
+    test.run_test_expecting(&rt, expect, util::PrecompileExit::Success);
+}
+
 #[cfg(test)]
 mod call_actor_invalid {
     use super::*;
@@ -1154,6 +1258,7 @@ impl ContractTester {
         // invoke constructor
         rt.expect_validate_caller_addr(vec![INIT_ACTOR_ADDR]);
         rt.set_caller(*INIT_ACTOR_CODE_ID, INIT_ACTOR_ADDR);
+        util::expect_initcode_charge(&rt, params.initcode.len());
 
         rt.set_origin(FILAddress::new_id(0));
         // first actor created is 0
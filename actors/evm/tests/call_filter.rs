@@ -0,0 +1,116 @@
+mod asm;
+mod util;
+
+use fil_actor_evm as evm;
+use fil_actor_evm::ext::call_filter::{CheckCallParams, CheckCallReturn, CHECK_CALL_METHOD};
+use fil_actor_evm::ext::system::{GetCallFilterReturn, GET_CALL_FILTER_METHOD};
+use fil_actors_evm_shared::uints::U256;
+use fil_actors_runtime::test_utils::*;
+use fil_actors_runtime::SYSTEM_ACTOR_ADDR;
+use fvm_ipld_encoding::ipld_block::IpldBlock;
+use fvm_ipld_encoding::BytesSer;
+use fvm_shared::address::Address;
+use fvm_shared::econ::TokenAmount;
+use fvm_shared::error::ExitCode;
+use fvm_shared::sys::SendFlags;
+use num_traits::Zero;
+
+const CALLER: Address = Address::new_id(1000);
+const CALL_FILTER: Address = Address::new_id(1001);
+
+fn construct() -> MockRuntime {
+    let contract = asm::new_contract(
+        "call-filter",
+        "",
+        r#"
+push1 0x2a
+%return_stack_word()
+"#,
+    )
+    .unwrap();
+    let mut rt = util::construct_and_verify(contract);
+    rt.policy.evm_call_filter_enabled = true;
+    rt.set_caller(*ACCOUNT_ACTOR_CODE_ID, CALLER);
+    rt
+}
+
+fn expect_get_call_filter(rt: &MockRuntime, call_filter: Option<Address>) {
+    rt.expect_send(
+        SYSTEM_ACTOR_ADDR,
+        GET_CALL_FILTER_METHOD,
+        None,
+        TokenAmount::zero(),
+        None,
+        SendFlags::READ_ONLY,
+        IpldBlock::serialize_cbor(&GetCallFilterReturn { call_filter }).unwrap(),
+        ExitCode::OK,
+        None,
+    );
+}
+
+fn expect_check_call(rt: &MockRuntime, input_data: &[u8], allowed: bool) {
+    rt.expect_send(
+        CALL_FILTER,
+        CHECK_CALL_METHOD,
+        IpldBlock::serialize_cbor(&CheckCallParams {
+            contract: util::CONTRACT_ID,
+            caller: CALLER,
+            value: TokenAmount::zero(),
+            input_data: input_data.to_vec(),
+        })
+        .unwrap(),
+        TokenAmount::zero(),
+        None,
+        SendFlags::READ_ONLY,
+        IpldBlock::serialize_cbor(&CheckCallReturn { allowed }).unwrap(),
+        ExitCode::OK,
+        None,
+    );
+}
+
+#[test]
+fn call_without_registered_filter() {
+    let rt = construct();
+    expect_get_call_filter(&rt, None);
+    let result = util::invoke_contract(&rt, &[]);
+    rt.verify();
+    assert_eq!(U256::from(0x2a), U256::from_big_endian(&result));
+}
+
+#[test]
+fn call_allowed_by_filter() {
+    let rt = construct();
+    let input_data = [1, 2, 3, 4];
+    expect_get_call_filter(&rt, Some(CALL_FILTER));
+    expect_check_call(&rt, &input_data, true);
+    let result = util::invoke_contract(&rt, &input_data);
+    rt.verify();
+    assert_eq!(U256::from(0x2a), U256::from_big_endian(&result));
+}
+
+#[test]
+fn call_rejected_by_filter() {
+    let rt = construct();
+    let input_data = [1, 2, 3, 4];
+    expect_get_call_filter(&rt, Some(CALL_FILTER));
+    expect_check_call(&rt, &input_data, false);
+    rt.expect_validate_caller_any();
+    expect_abort_contains_message(
+        ExitCode::USR_FORBIDDEN,
+        "rejected by call filter",
+        rt.call::<evm::EvmContractActor>(
+            evm::Method::InvokeContract as u64,
+            IpldBlock::serialize_cbor(&BytesSer(&input_data)).unwrap(),
+        ),
+    );
+    rt.verify();
+}
+
+#[test]
+fn filter_not_consulted_unless_enabled() {
+    let mut rt = construct();
+    rt.policy.evm_call_filter_enabled = false;
+    let result = util::invoke_contract(&rt, &[]);
+    rt.verify();
+    assert_eq!(U256::from(0x2a), U256::from_big_endian(&result));
+}
@@ -3,8 +3,12 @@ mod asm;
 use evm::ext::eam;
 use fil_actor_evm as evm;
 use fil_actors_evm_shared::address::EthAddress;
-use fil_actors_runtime::EAM_ACTOR_ADDR;
+use fil_actors_runtime::test_utils::{MockRuntime, EVM_ACTOR_CODE_ID, INIT_ACTOR_CODE_ID};
+use fil_actors_runtime::{ActorError, EAM_ACTOR_ADDR, EAM_ACTOR_ID, INIT_ACTOR_ADDR};
+use fvm_ipld_blockstore::Blockstore;
 use fvm_ipld_encoding::ipld_block::IpldBlock;
+use fvm_ipld_encoding::BytesSer;
+use fvm_shared::address::Address;
 use fvm_shared::econ::TokenAmount;
 use fvm_shared::error::ExitCode;
 use fvm_shared::sys::SendFlags;
@@ -171,3 +175,99 @@ fn test_create() {
         assert_eq!(&result[..], &[0; 32]);
     }
 }
+
+#[test]
+fn test_deployed_code_size_limit() {
+    // Returns the given number of zero bytes as the deployed code.
+    let initcode = |len: u16| {
+        let [hi, lo] = len.to_be_bytes();
+        vec![0x61, hi, lo, 0x60, 0x00, 0xf3] // PUSH2 len PUSH1 0x00 RETURN
+    };
+
+    let rt = util::construct_and_verify(initcode(evm::MAX_CODE_SIZE as u16));
+    let state: evm::State = rt.get_state();
+    let bytecode = rt.store.get(&state.bytecode).unwrap().unwrap();
+    assert_eq!(bytecode.len(), evm::MAX_CODE_SIZE);
+
+    let (rt, result) = construct(initcode(evm::MAX_CODE_SIZE as u16 + 1));
+    assert_eq!(result.unwrap_err().exit_code(), evm::EVM_CONTRACT_CODE_SIZE_EXCEEDED);
+    rt.verify();
+}
+
+#[test]
+fn test_initcode_size_limit() {
+    // Oversized initcode is rejected before it is charged for or executed.
+    let (rt, result) = construct(vec![0; evm::MAX_INITCODE_SIZE + 1]);
+    assert_eq!(result.unwrap_err().exit_code(), evm::EVM_CONTRACT_CODE_SIZE_EXCEEDED);
+    rt.verify();
+
+    // Initcode at the limit is charged per word.
+    util::construct_and_verify(vec![0; evm::MAX_INITCODE_SIZE]);
+}
+
+#[test]
+fn test_create_initcode_size_limit() {
+    let body = r#"
+%dispatch_begin()
+%dispatch(0x00, test_create)
+%dispatch(0x01, test_create2)
+%dispatch_end()
+
+test_create:
+    jumpdest
+    push3 0x00c001 # in size (one byte over the limit)
+    push1 0x00     # in offset
+    push1 0x00     # value (attoFil)
+    create
+    %return_stack_word()
+
+test_create2:
+    jumpdest
+    push1 0x00     # salt
+    push3 0x00c001 # in size (one byte over the limit)
+    push1 0x00     # in offset
+    push1 0x00     # endowment (attoFil)
+    create2
+    %return_stack_word()
+"#;
+    let contract = asm::new_contract("create-oversized", "", body).unwrap();
+    let rt = util::construct_and_verify(contract);
+
+    // The creating contract aborts without calling the EAM.
+    for method in [0x00, 0x01] {
+        let mut contract_params = [0u8; 32];
+        contract_params[3] = method;
+        rt.expect_validate_caller_any();
+        let result = rt.call::<evm::EvmContractActor>(
+            evm::Method::InvokeContract as u64,
+            IpldBlock::serialize_cbor(&BytesSer(&contract_params)).unwrap(),
+        );
+        assert_eq!(result.unwrap_err().exit_code(), evm::EVM_CONTRACT_CODE_SIZE_EXCEEDED);
+        rt.verify();
+    }
+}
+
+/// Invokes the constructor, expecting any charge for the initcode that is within the limit.
+fn construct(initcode: Vec<u8>) -> (MockRuntime, Result<Option<IpldBlock>, ActorError>) {
+    let rt = MockRuntime::default();
+    rt.set_caller(*INIT_ACTOR_CODE_ID, INIT_ACTOR_ADDR);
+    rt.expect_validate_caller_addr(vec![INIT_ACTOR_ADDR]);
+    rt.set_delegated_address(
+        0,
+        Address::new_delegated(EAM_ACTOR_ID, &util::CONTRACT_ADDRESS).unwrap(),
+    );
+    rt.set_address_actor_type(Address::new_id(0), *EVM_ACTOR_CODE_ID);
+    if initcode.len() <= evm::MAX_INITCODE_SIZE {
+        util::expect_initcode_charge(&rt, initcode.len());
+    }
+
+    let params = evm::ConstructorParams {
+        creator: EthAddress::from_id(EAM_ACTOR_ID),
+        initcode: initcode.into(),
+    };
+    let result = rt.call::<evm::EvmContractActor>(
+        evm::Method::Constructor as u64,
+        IpldBlock::serialize_cbor(&params).unwrap(),
+    );
+    (rt, result)
+}
@@ -43,6 +43,10 @@ impl TestEnv {
         };
         // invoke constructor
         self.runtime.expect_validate_caller_addr(vec![INIT_ACTOR_ADDR]);
+        let initcode_words = params.initcode.len().div_ceil(32) as u64;
+        if initcode_words > 0 {
+            self.runtime.expect_gas_charge((initcode_words * evm::INITCODE_WORD_GAS) as i64);
+        }
         self.runtime.set_caller(*INIT_ACTOR_CODE_ID, INIT_ACTOR_ADDR);
 
         self.runtime.set_origin(self.evm_address);
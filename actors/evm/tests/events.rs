@@ -77,7 +77,7 @@ fn test_events() {
     let mut contract_params = vec![0u8; 32];
     rt.expect_emitted_event(ActorEvent {
         entries: vec![Entry {
-            flags: Flags::FLAG_INDEXED_ALL,
+            flags: Flags::empty(),
             key: "d".to_string(),
             codec: IPLD_RAW,
             value: vec![0x11, 0x22, 0x33, 0x44, 0x55, 0x66, 0x77, 0x88],
@@ -119,7 +119,7 @@ fn test_events() {
                 value: U256::from(0x4444).to_bytes().into(),
             },
             Entry {
-                flags: Flags::FLAG_INDEXED_ALL,
+                flags: Flags::empty(),
                 key: "d".to_string(),
                 codec: IPLD_RAW,
                 value: vec![0x11, 0x22, 0x33, 0x44, 0x55, 0x66, 0x77, 0x88],
@@ -308,6 +308,20 @@ fn test_getbytecodehash_method() {
     assert_eq!(<[u8; 32]>::from(res), empty_bytecode_hash(&rt))
 }
 
+#[test]
+fn test_getbytecodehash_exported_method() {
+    let rt = util::construct_and_verify(Vec::new());
+    rt.expect_validate_caller_any();
+
+    let res: BytecodeHash = rt
+        .call::<evm::EvmContractActor>(evm::Method::GetBytecodeHashExported as u64, None)
+        .unwrap()
+        .unwrap()
+        .deserialize()
+        .unwrap();
+    assert_eq!(<[u8; 32]>::from(res), empty_bytecode_hash(&rt))
+}
+
 /// Keccak256 hash of &[]
 fn empty_bytecode_hash(rt: &impl Runtime) -> [u8; 32] {
     rt.hash(SupportedHashes::Keccak256, &[]).try_into().unwrap()
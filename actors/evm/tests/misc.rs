@@ -3,9 +3,11 @@ mod util;
 
 use cid::multihash::Multihash;
 use cid::Cid;
+use fil_actor_evm as evm;
 use fil_actors_evm_shared::address::EthAddress;
 use fil_actors_evm_shared::uints::U256;
-use fvm_ipld_encoding::DAG_CBOR;
+use fvm_ipld_encoding::ipld_block::IpldBlock;
+use fvm_ipld_encoding::{BytesSer, DAG_CBOR};
 use fvm_shared::chainid::ChainID;
 use fvm_shared::{address::Address, econ::TokenAmount};
 
@@ -26,7 +28,7 @@ return
     .unwrap();
 
     let mut rt = util::construct_and_verify(contract);
-    rt.tipset_timestamp = 123;
+    rt.set_tipset_timestamp(123);
     let result = util::invoke_contract(&rt, &[]);
     assert_eq!(U256::from_big_endian(&result), U256::from(123));
 }
@@ -375,3 +377,32 @@ return
     // Make sure we prefer the eth address, if we have one.
     assert_eq!(eth_address, util::CONTRACT_ADDRESS);
 }
+
+#[test]
+fn test_memory_limit() {
+    // Writes a byte at the offset given in the call data.
+    let contract = asm::new_contract(
+        "memory-limit",
+        "",
+        r#"
+push1 0x01
+push1 0x00
+calldataload
+mstore8
+"#,
+    )
+    .unwrap();
+
+    let mut rt = util::construct_and_verify(contract);
+    rt.policy.evm_max_memory_size = 1024;
+    let offset = |offset: u64| U256::from(offset).to_bytes();
+
+    util::invoke_contract(&rt, &offset(1023));
+
+    rt.expect_validate_caller_any();
+    let result = rt.call::<evm::EvmContractActor>(
+        evm::Method::InvokeContract as u64,
+        IpldBlock::serialize_cbor(&BytesSer(&offset(1024))).unwrap(),
+    );
+    assert_eq!(result.unwrap_err().exit_code(), evm::EVM_CONTRACT_ILLEGAL_MEMORY_ACCESS);
+}
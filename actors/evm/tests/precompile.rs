@@ -1,11 +1,26 @@
 mod asm;
 
+use alloy_core::primitives::Bytes;
+use alloy_core::sol_types::SolValue;
+use cid::Cid;
+use fil_actor_evm as evm;
+use fil_actor_evm::ext::miner::{
+    GetSectorStatusParams, GetSectorStatusReturn, GET_SECTOR_STATUS_METHOD,
+};
 use fil_actors_evm_shared::{address::EthAddress, uints::U256};
 use fil_actors_runtime::{
-    test_utils::{new_bls_addr, MockRuntime},
+    test_utils::{
+        new_bls_addr, ExpectedVerifySig, MockRuntime, EVM_ACTOR_CODE_ID, MINER_ACTOR_CODE_ID,
+    },
     EAM_ACTOR_ID,
 };
+use fvm_ipld_encoding::{ipld_block::IpldBlock, DAG_CBOR};
+use fvm_shared::crypto::signature::{Signature, SignatureType};
+use fvm_shared::sys::SendFlags;
+use fvm_shared::version::NetworkVersion;
 use fvm_shared::{address::Address as FILAddress, econ::TokenAmount, error::ExitCode, METHOD_SEND};
+use multihash_codetable::{Code, MultihashDigest};
+use num_traits::Zero;
 
 mod util;
 
@@ -338,3 +353,255 @@ fn test_precompile_failure() {
     assert_eq!(&[1u8], result.as_slice());
     rt.reset();
 }
+
+#[test]
+fn test_precompile_paymaster() {
+    let (init, body) = PrecompileTest::test_runner_assembly();
+    let rt =
+        util::construct_and_verify(asm::new_contract("precompile-tester", &init, &body).unwrap());
+    let paymaster = 1234u64;
+    let beneficiary = FILAddress::new_id(1001);
+
+    let mut test = PrecompileTest {
+        precompile_address: NativePrecompile::SetPaymaster.eth_address(),
+        output_size: 32,
+        expected_exit_code: PrecompileExit::Reverted,
+        gas_avaliable: 10_000_000_000,
+        call_op: util::PrecompileCallOpcode::Call(0),
+        input: U256::from(paymaster).to_bytes().to_vec(),
+        expected_return: vec![],
+    };
+    // only callable with delegatecall
+    test.run_test(&rt);
+    assert_eq!(None, rt.get_state::<evm::State>().paymaster);
+
+    test.call_op = util::PrecompileCallOpcode::DelegateCall;
+    test.run_test_expecting(&rt, vec![], PrecompileExit::Success);
+    assert_eq!(Some(paymaster), rt.get_state::<evm::State>().paymaster);
+
+    // approve the beneficiary to be sponsored up to 50
+    let approve = PrecompileTest {
+        precompile_address: NativePrecompile::ApproveSponsorship.eth_address(),
+        expected_exit_code: PrecompileExit::Success,
+        call_op: util::PrecompileCallOpcode::DelegateCall,
+        input: [id_to_vec(&beneficiary), U256::from(50).to_bytes().to_vec()].concat(),
+        ..test
+    };
+    approve.run_test(&rt);
+
+    let sponsor = |amount: u64| {
+        rt.call::<evm::EvmContractActor>(
+            evm::Method::SponsorValueShortfall as u64,
+            IpldBlock::serialize_cbor(&evm::SponsorValueShortfallParams {
+                amount: TokenAmount::from_atto(amount),
+            })
+            .unwrap(),
+        )
+    };
+    rt.set_balance(TokenAmount::from_atto(100));
+    rt.set_caller(*EVM_ACTOR_CODE_ID, beneficiary);
+    rt.expect_validate_caller_any();
+    rt.expect_send_simple(
+        beneficiary,
+        METHOD_SEND,
+        None,
+        TokenAmount::from_atto(30),
+        None,
+        ExitCode::OK,
+    );
+    sponsor(30).unwrap();
+    rt.verify();
+
+    // the remaining approval doesn't cover a second shortfall
+    rt.expect_validate_caller_any();
+    let err = sponsor(30).unwrap_err();
+    assert_eq!(ExitCode::USR_INSUFFICIENT_FUNDS, err.exit_code());
+    rt.reset();
+
+    // actors without an approval can't be sponsored
+    rt.set_caller(*EVM_ACTOR_CODE_ID, FILAddress::new_id(1002));
+    rt.expect_validate_caller_any();
+    let err = sponsor(10).unwrap_err();
+    assert_eq!(ExitCode::USR_FORBIDDEN, err.exit_code());
+    rt.reset();
+}
+
+#[test]
+fn test_precompile_recover_typed_data_signer() {
+    let (init, body) = PrecompileTest::test_runner_assembly();
+    let rt =
+        util::construct_and_verify(asm::new_contract("precompile-tester", &init, &body).unwrap());
+
+    // keccak256("domain") and keccak256("request"), signed over the EIP-712 digest.
+    let domain_separator =
+        hex_literal::hex!("c5d3ba30d3ac69f3f095a61e99369d9450502ca0c2f4768b2c39ee277faa631d");
+    let struct_hash =
+        hex_literal::hex!("72859a6ae50aa97f593f23df1c78bb1fd78cfc493fcef64159d6486223196833");
+    let signature = hex_literal::hex!(
+        "000000000000000000000000000000000000000000000000000000000000001c" // v
+        "544e77a4dbfad982648d8e4c293bc8bb5185e04417b4b073d7155c4ee55a1350" // r
+        "0cc6ef757fa0faaf79f0681eea08632677eb2da70610562c5f109b3855b6b652" // s
+    );
+    let signer =
+        hex_literal::hex!("0000000000000000000000006e12d8c87503d4287c294f2fdef96acd9dff6bd2");
+
+    let mut test = PrecompileTest {
+        precompile_address: NativePrecompile::RecoverTypedDataSigner.eth_address(),
+        output_size: 32,
+        expected_exit_code: PrecompileExit::Success,
+        gas_avaliable: 10_000_000_000,
+        call_op: util::PrecompileCallOpcode::StaticCall,
+        input: [&domain_separator[..], &struct_hash[..], &signature[..]].concat(),
+        expected_return: signer.to_vec(),
+    };
+    test.run_test(&rt);
+
+    // An invalid recovery byte yields an empty result.
+    let mut bad_signature = signature;
+    bad_signature[31] = 0x1d;
+    test.input = [&domain_separator[..], &struct_hash[..], &bad_signature[..]].concat();
+    test.run_test_expecting(&rt, vec![], PrecompileExit::Success);
+}
+
+#[test]
+fn test_precompile_get_chain_context() {
+    let (init, body) = PrecompileTest::test_runner_assembly();
+    let mut rt =
+        util::construct_and_verify(asm::new_contract("precompile-tester", &init, &body).unwrap());
+    rt.network_version = NetworkVersion::V21;
+
+    let epoch = 1234;
+    let parent = Cid::new_v1(DAG_CBOR, Code::Blake2b256.digest(b"parent tipset"));
+    rt.tipset_cids.resize(epoch as usize, Cid::default());
+    rt.tipset_cids[epoch as usize - 1] = parent;
+    rt.set_epoch(epoch);
+
+    let mut test = PrecompileTest {
+        precompile_address: NativePrecompile::GetChainContext.eth_address(),
+        output_size: 96,
+        expected_exit_code: PrecompileExit::Success,
+        gas_avaliable: 10_000_000_000,
+        call_op: util::PrecompileCallOpcode::StaticCall,
+        input: vec![],
+        expected_return: [
+            &U256::from(epoch).to_bytes()[..],
+            &U256::from(21).to_bytes()[..],
+            parent.hash().digest(),
+        ]
+        .concat(),
+    };
+    test.run_test(&rt);
+
+    // No parent tipset is available at genesis.
+    rt.set_epoch(0);
+    test.run_test_expecting(
+        &rt,
+        [&[0u8; 32][..], &U256::from(21).to_bytes()[..], &[0u8; 32][..]].concat(),
+        PrecompileExit::Success,
+    );
+}
+
+#[test]
+fn test_precompile_verify_signature() {
+    let (init, body) = PrecompileTest::test_runner_assembly();
+    let rt =
+        util::construct_and_verify(asm::new_contract("precompile-tester", &init, &body).unwrap());
+
+    let signer = new_bls_addr(1);
+    let plaintext = b"signed message".to_vec();
+    let signature = Signature { sig_type: SignatureType::BLS, bytes: vec![7; 96] };
+    let input = |signer: &FILAddress| {
+        (
+            Bytes::from(signer.to_bytes()),
+            Bytes::from(plaintext.clone()),
+            Bytes::from(signature.bytes.clone()),
+        )
+            .abi_encode_params()
+    };
+
+    let mut test = PrecompileTest {
+        precompile_address: NativePrecompile::VerifySignature.eth_address(),
+        output_size: 32,
+        expected_exit_code: PrecompileExit::Success,
+        gas_avaliable: 10_000_000_000,
+        call_op: util::PrecompileCallOpcode::StaticCall,
+        input: input(&signer),
+        expected_return: U256::from(1).to_bytes().to_vec(),
+    };
+    rt.expect_verify_signature(ExpectedVerifySig {
+        sig: signature.clone(),
+        signer,
+        plaintext: plaintext.clone(),
+        result: Ok(()),
+    });
+    test.run_test(&rt);
+
+    rt.expect_verify_signature(ExpectedVerifySig {
+        sig: signature.clone(),
+        signer,
+        plaintext: plaintext.clone(),
+        result: Err(anyhow::anyhow!("invalid signature")),
+    });
+    test.run_test_expecting(&rt, U256::zero().to_bytes(), PrecompileExit::Success);
+
+    // Only f1 and f3 addresses sign.
+    test.input = input(&FILAddress::new_id(1000));
+    test.run_test_expecting(&rt, vec![], PrecompileExit::Reverted);
+}
+
+#[test]
+fn test_precompile_get_miner_sector_status() {
+    let (init, body) = PrecompileTest::test_runner_assembly();
+    let rt =
+        util::construct_and_verify(asm::new_contract("precompile-tester", &init, &body).unwrap());
+
+    let miner = FILAddress::new_id(1001);
+    rt.set_address_actor_type(miner, *MINER_ACTOR_CODE_ID);
+    let input = |id: u64, sector_number: u64| {
+        [U256::from(id).to_bytes(), U256::from(sector_number).to_bytes()].concat()
+    };
+    let expect_get_sector_status = |ret: Option<IpldBlock>, exit_code: ExitCode| {
+        rt.expect_send(
+            miner,
+            GET_SECTOR_STATUS_METHOD,
+            IpldBlock::serialize_cbor(&GetSectorStatusParams { sector_number: 7 }).unwrap(),
+            TokenAmount::zero(),
+            None,
+            SendFlags::READ_ONLY,
+            ret,
+            exit_code,
+            None,
+        );
+    };
+
+    let status = GetSectorStatusReturn {
+        live: true,
+        faulty: false,
+        activation: 100,
+        expiration: 5000,
+        deadline: 3,
+        partition: 1,
+    };
+    let mut test = PrecompileTest {
+        precompile_address: NativePrecompile::GetMinerSectorStatus.eth_address(),
+        output_size: 192,
+        expected_exit_code: PrecompileExit::Success,
+        gas_avaliable: 10_000_000_000,
+        call_op: util::PrecompileCallOpcode::StaticCall,
+        input: input(1001, 7),
+        expected_return: [1u64, 0, 100, 5000, 3, 1]
+            .iter()
+            .flat_map(|w| U256::from(*w).to_bytes())
+            .collect(),
+    };
+    expect_get_sector_status(IpldBlock::serialize_cbor(&status).unwrap(), ExitCode::OK);
+    test.run_test(&rt);
+
+    // The miner doesn't have the sector.
+    expect_get_sector_status(None, ExitCode::USR_NOT_FOUND);
+    test.run_test_expecting(&rt, vec![], PrecompileExit::Reverted);
+
+    // Only storage miners are queried.
+    test.input = input(1002, 7);
+    test.run_test_expecting(&rt, vec![], PrecompileExit::Reverted);
+}
@@ -1,3 +1,4 @@
+use fil_actor_evm::migration::LegacyState;
 use fil_actor_evm::{
     EvmContractActor, Method, ResurrectParams, State, Tombstone, EVM_CONTRACT_SELFDESTRUCT_FAILED,
 };
@@ -33,6 +34,7 @@ fn test_selfdestruct() {
 
     let returnone_params = hex::decode("901717d1").unwrap();
     let selfdestruct_params = hex::decode("35f46994").unwrap();
+    let bytecode_len = bytecode.len();
     let resurrect_params = IpldBlock::serialize_cbor(&ResurrectParams {
         creator: EthAddress([0; 20]),
         initcode: RawBytes::new(bytecode),
@@ -80,6 +82,7 @@ fn test_selfdestruct() {
     // We should now be able to resurrect.
     rt.set_caller(*EAM_ACTOR_CODE_ID, EAM_ACTOR_ADDR);
     rt.expect_validate_caller_addr(vec![EAM_ACTOR_ADDR]);
+    util::expect_initcode_charge(&rt, bytecode_len);
     rt.call::<EvmContractActor>(Method::Resurrect as MethodNum, resurrect_params).unwrap();
     rt.verify();
 
@@ -94,6 +97,82 @@ fn test_selfdestruct() {
     rt.verify();
 }
 
+#[test]
+fn test_selfdestruct_after_creation_transaction() {
+    let bytecode = hex::decode(include_str!("contracts/selfdestruct.hex")).unwrap();
+
+    let contract = Address::new_id(100);
+    let beneficiary_id = 1001;
+    let beneficiary = Address::new_id(beneficiary_id);
+
+    let token_amount = TokenAmount::from_whole(2);
+
+    let rt = util::init_construct_and_verify(bytecode, |rt| {
+        rt.actor_code_cids.borrow_mut().insert(contract, *EVM_ACTOR_CODE_ID);
+        rt.set_origin(contract);
+        rt.set_balance(token_amount.clone());
+    });
+    let state: State = rt.get_state();
+    assert_eq!(state.created_in, Some(Tombstone { origin: 100, nonce: 0 }));
+
+    let returnone_params = hex::decode("901717d1").unwrap();
+    let selfdestruct_params = hex::decode("35f46994").unwrap();
+
+    // In a later transaction, SELFDESTRUCT only sends away the funds (EIP-6780).
+    rt.set_origin(beneficiary);
+    rt.expect_send_simple(beneficiary, METHOD_SEND, None, token_amount, None, ExitCode::OK);
+    assert!(util::invoke_contract(&rt, &selfdestruct_params).is_empty());
+    rt.verify();
+
+    let state: State = rt.get_state();
+    assert_eq!(state.tombstone, None);
+
+    // The contract lives on, even in yet another transaction.
+    rt.set_origin(Address::new_id(1002));
+    assert_eq!(U256::from_big_endian(&util::invoke_contract(&rt, &returnone_params)), U256::ONE);
+    rt.verify();
+}
+
+#[test]
+fn test_selfdestruct_after_upgrade() {
+    let bytecode = hex::decode(include_str!("contracts/selfdestruct.hex")).unwrap();
+
+    let contract = Address::new_id(100);
+    let beneficiary = Address::new_id(1001);
+
+    let token_amount = TokenAmount::from_whole(2);
+
+    let rt = util::init_construct_and_verify(bytecode, |rt| {
+        rt.actor_code_cids.borrow_mut().insert(contract, *EVM_ACTOR_CODE_ID);
+        rt.set_origin(contract);
+        rt.set_balance(token_amount.clone());
+    });
+
+    // Migrate the contract from the legacy layout, which didn't record its creation.
+    let state: State = rt.get_state();
+    rt.replace_state(&LegacyState {
+        bytecode: state.bytecode,
+        bytecode_hash: state.bytecode_hash,
+        contract_state: state.contract_state,
+        transient_data: state.transient_data,
+        nonce: state.nonce,
+        tombstone: state.tombstone,
+    });
+    rt.upgrade::<EvmContractActor>();
+    let state: State = rt.get_state();
+    assert_eq!(state.created_in, None);
+
+    // Even in the transaction that created it, a migrated contract is never deleted.
+    let selfdestruct_params = hex::decode("35f46994").unwrap();
+    rt.set_caller(*INIT_ACTOR_CODE_ID, INIT_ACTOR_ADDR);
+    rt.expect_send_simple(beneficiary, METHOD_SEND, None, token_amount, None, ExitCode::OK);
+    assert!(util::invoke_contract(&rt, &selfdestruct_params).is_empty());
+    rt.verify();
+
+    let state: State = rt.get_state();
+    assert_eq!(state.tombstone, None);
+}
+
 #[test]
 fn test_selfdestruct_missing_beneficiary() {
     let bytecode = hex::decode(include_str!("contracts/selfdestruct.hex")).unwrap();
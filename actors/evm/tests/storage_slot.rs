@@ -0,0 +1,78 @@
+mod asm;
+
+use fil_actor_evm as evm;
+use fil_actors_evm_shared::storage::StorageSlot;
+use fil_actors_evm_shared::uints::U256;
+use fil_actors_runtime::test_utils::*;
+use fvm_ipld_encoding::ipld_block::IpldBlock;
+use fvm_shared::address::Address;
+use hex_literal::hex;
+
+mod util;
+
+#[test]
+fn derived_slots() {
+    let rt = MockRuntime::default();
+
+    // keccak256(bytes32(0) ++ bytes32(0))
+    assert_eq!(
+        StorageSlot(U256::from_big_endian(&hex!(
+            "ad3228b676f7d3cd4284a5443f17f1962b36e491b30a40b2405849e597ba5fb5"
+        ))),
+        StorageSlot::new(0).mapping_value(&rt, &[0; 32])
+    );
+
+    // keccak256(bytes32(0)) is the slot of the first element of an array at slot 0.
+    let first = StorageSlot(U256::from_big_endian(&hex!(
+        "290decd9548b62a8d60345a988386fc84ba6bc95484008f6362f93160ef3e563"
+    )));
+    assert_eq!(first, StorageSlot::new(0).array_element(&rt, 0, 1));
+    assert_eq!(first.offset(6), StorageSlot::new(0).array_element(&rt, 3, 2));
+
+    // keccak256(bytes32(1))
+    assert_eq!(
+        StorageSlot(U256::from_big_endian(&hex!(
+            "b10e2d527612073b26eecdfd717e6a320cf44b4afac2b0732d9fcbe2b7fa0cf6"
+        ))),
+        StorageSlot::new(1).array_element(&rt, 0, 1)
+    );
+}
+
+#[test]
+fn read_mapping_value() {
+    // Store 0xfffa for key 0x42 of a mapping at slot 3, as Solidity would.
+    let init = r"
+push1 0x42
+push1 0x00
+mstore
+push1 0x03
+push1 0x20
+mstore
+push1 0x40
+push1 0x00
+keccak256
+push2 0xfffa
+swap1
+sstore";
+    let rt = util::construct_and_verify(asm::new_contract("storage_slot", init, "return").unwrap());
+
+    let key = U256::from(0x42).to_bytes();
+    let slot = StorageSlot::new(3).mapping_value(&rt, &key);
+
+    rt.reset();
+    let sender = Address::new_id(0);
+    rt.expect_validate_caller_addr(vec![sender]);
+    rt.caller.replace(sender);
+    let value: U256 = rt
+        .call::<evm::EvmContractActor>(
+            evm::Method::GetStorageAt as u64,
+            IpldBlock::serialize_cbor(&evm::GetStorageAtParams { storage_key: slot.key() })
+                .unwrap(),
+        )
+        .unwrap()
+        .unwrap()
+        .deserialize()
+        .unwrap();
+    rt.verify();
+    assert_eq!(U256::from(0xfffa), value);
+}
@@ -45,6 +45,7 @@ pub fn init_construct_and_verify<F: FnOnce(&MockRuntime)>(
     rt.set_delegated_address(0, Address::new_delegated(EAM_ACTOR_ID, &CONTRACT_ADDRESS).unwrap());
     rt.set_address_actor_type(Address::new_id(0), *EVM_ACTOR_CODE_ID);
 
+    expect_initcode_charge(&rt, initcode.len());
     let params = evm::ConstructorParams {
         creator: EthAddress::from_id(fil_actors_runtime::EAM_ACTOR_ADDR.id().unwrap()),
         initcode: initcode.into(),
@@ -65,6 +66,13 @@ pub fn init_construct_and_verify<F: FnOnce(&MockRuntime)>(
     rt
 }
 
+/// Expects the gas charge for executing initcode of the given length in a constructor.
+pub fn expect_initcode_charge(rt: &MockRuntime, len: usize) {
+    if len > 0 {
+        rt.expect_gas_charge((len.div_ceil(32) as u64 * evm::INITCODE_WORD_GAS) as i64);
+    }
+}
+
 #[allow(dead_code)]
 pub fn invoke_contract(rt: &MockRuntime, input_data: &[u8]) -> Vec<u8> {
     rt.expect_validate_caller_any();
@@ -139,6 +147,12 @@ pub enum NativePrecompile {
     GetActorTypeDISABLED = 4,
     CallActorId = 5,
     GetRandomness = 6,
+    SetPaymaster = 7,
+    ApproveSponsorship = 8,
+    RecoverTypedDataSigner = 9,
+    GetChainContext = 10,
+    VerifySignature = 11,
+    GetMinerSectorStatus = 12,
 }
 
 #[allow(dead_code)]
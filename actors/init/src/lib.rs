@@ -6,9 +6,10 @@ use fil_actors_runtime::runtime::builtins::Type;
 use fil_actors_runtime::runtime::{ActorCode, Runtime};
 
 use fil_actors_runtime::{
-    actor_dispatch, actor_error, extract_send_result, ActorContext, ActorError, AsActorError,
-    EAM_ACTOR_ADDR, SYSTEM_ACTOR_ADDR,
+    actor_dispatch, actor_error, extract_send_result, migrate_state, ActorContext, ActorError,
+    AsActorError, OnUpgradeParams, EAM_ACTOR_ADDR, SYSTEM_ACTOR_ADDR,
 };
+use fvm_ipld_encoding::RawBytes;
 use fvm_shared::address::Address;
 use fvm_shared::error::ExitCode;
 use fvm_shared::{ActorID, METHOD_CONSTRUCTOR};
@@ -16,8 +17,11 @@ use num_derive::FromPrimitive;
 
 pub use self::state::State;
 pub use self::types::*;
+use crate::migration::LegacyState;
 
+pub mod migration;
 mod state;
+#[cfg(feature = "testing")]
 pub mod testing;
 mod types;
 
@@ -31,6 +35,9 @@ pub enum Method {
     Constructor = METHOD_CONSTRUCTOR,
     Exec = 2,
     Exec4 = 3,
+    // Method numbers derived from FRC-0042 standards
+    LookupRobustAddressExported = frc42_dispatch::method_hash!("LookupRobustAddress"),
+    ExecDeterministicExported = frc42_dispatch::method_hash!("ExecDeterministic"),
 }
 
 /// Init actor
@@ -53,19 +60,7 @@ impl Actor {
 
         log::trace!("called exec; params.code_cid: {:?}", &params.code_cid);
 
-        let caller_code =
-            rt.get_actor_code_cid(&rt.message().caller().id().unwrap()).ok_or_else(|| {
-                actor_error!(illegal_state, "no code for caller as {}", rt.message().caller())
-            })?;
-
-        log::trace!("caller code CID: {:?}", &caller_code);
-
-        if !can_exec(rt, &caller_code, &params.code_cid) {
-            return Err(actor_error!(forbidden;
-                    "called type {} cannot exec actor type {}",
-                    &caller_code, &params.code_cid
-            ));
-        }
+        check_can_exec(rt, &params.code_cid)?;
 
         // Compute a re-org-stable address.
         // This address exists for use by messages coming from outside the system, in order to
@@ -75,36 +70,28 @@ impl Actor {
 
         log::trace!("robust address: {:?}", &robust_address);
 
-        // Allocate an ID for this actor.
-        // Store mapping of actor addresses to the actor ID.
-        let (id_address, existing): (ActorID, bool) = rt.transaction(|s: &mut State, rt| {
-            s.map_addresses_to_id(rt.store(), &robust_address, None)
-                .context("failed to allocate ID address")
-        })?;
+        create_and_construct(rt, params.code_cid, params.constructor_params, robust_address)
+    }
 
-        if existing {
-            // NOTE: this case should be impossible, but we check it anyways just in case something
-            // changes.
-            return Err(actor_error!(
-                forbidden,
-                "cannot exec over an existing actor {}",
-                id_address
-            ));
-        }
+    /// Creates an actor at a robust address derived from the caller, a nonce and a salt,
+    /// rather than from the message, so that the address can be known before deployment.
+    /// See [`deterministic_address`] for the derivation.
+    pub fn exec_deterministic(
+        rt: &impl Runtime,
+        params: ExecDeterministicParams,
+    ) -> Result<ExecReturn, ActorError> {
+        rt.validate_immediate_caller_accept_any()?;
 
-        // Create an empty actor
-        rt.create_actor(params.code_cid, id_address, None)?;
+        log::trace!("called exec_deterministic; params.code_cid: {:?}", &params.code_cid);
 
-        // Invoke constructor
-        extract_send_result(rt.send_simple(
-            &Address::new_id(id_address),
-            METHOD_CONSTRUCTOR,
-            params.constructor_params.into(),
-            rt.message().value_received(),
-        ))
-        .context("constructor failed")?;
+        check_can_exec(rt, &params.code_cid)?;
+
+        let caller_id = rt.message().caller().id().unwrap();
+        let robust_address = deterministic_address(caller_id, params.nonce, &params.salt);
+
+        log::trace!("robust address: {:?}", &robust_address);
 
-        Ok(ExecReturn { id_address: Address::new_id(id_address), robust_address })
+        create_and_construct(rt, params.code_cid, params.constructor_params, robust_address)
     }
 
     /// Exec4 init actor
@@ -162,6 +149,19 @@ impl Actor {
 
         Ok(Exec4Return { id_address: Address::new_id(id_address), robust_address })
     }
+
+    /// Returns the robust address an actor ID was allocated for, if any.
+    pub fn lookup_robust_address(
+        rt: &impl Runtime,
+        params: LookupRobustAddressParams,
+    ) -> Result<LookupRobustAddressReturn, ActorError> {
+        rt.validate_immediate_caller_accept_any()?;
+        let st: State = rt.state()?;
+        let robust_address = st
+            .lookup_robust_address(rt.store(), params.actor_id)
+            .context("failed to look up robust address")?;
+        Ok(LookupRobustAddressReturn { robust_address })
+    }
 }
 
 impl ActorCode for Actor {
@@ -171,10 +171,20 @@ impl ActorCode for Actor {
         "Init"
     }
 
+    fn on_upgrade<RT>(rt: &RT, params: OnUpgradeParams) -> Result<(), ActorError>
+    where
+        RT: Runtime,
+        RT::Blockstore: Clone,
+    {
+        migrate_state(rt, &params.old_state, |rt, legacy: LegacyState| legacy.migrate(rt.store()))
+    }
+
     actor_dispatch! {
         Constructor => constructor,
         Exec => exec,
         Exec4 => exec4,
+        LookupRobustAddressExported => lookup_robust_address,
+        ExecDeterministicExported => exec_deterministic,
     }
 }
 
@@ -187,3 +197,74 @@ fn can_exec(rt: &impl Runtime, caller: &Cid, exec: &Cid) -> bool {
         })
         .unwrap_or(false)
 }
+
+fn check_can_exec(rt: &impl Runtime, exec: &Cid) -> Result<(), ActorError> {
+    let caller_code =
+        rt.get_actor_code_cid(&rt.message().caller().id().unwrap()).ok_or_else(|| {
+            actor_error!(illegal_state, "no code for caller as {}", rt.message().caller())
+        })?;
+
+    log::trace!("caller code CID: {:?}", &caller_code);
+
+    if !can_exec(rt, &caller_code, exec) {
+        return Err(actor_error!(forbidden;
+                "called type {} cannot exec actor type {}",
+                &caller_code, exec
+        ));
+    }
+    Ok(())
+}
+
+// Allocates an ID for a new actor at a robust address, then creates and constructs it.
+fn create_and_construct(
+    rt: &impl Runtime,
+    code_cid: Cid,
+    constructor_params: RawBytes,
+    robust_address: Address,
+) -> Result<ExecReturn, ActorError> {
+    // Allocate an ID for this actor.
+    // Store mapping of actor addresses to the actor ID.
+    let (id_address, existing): (ActorID, bool) = rt.transaction(|s: &mut State, rt| {
+        s.map_addresses_to_id(rt.store(), &robust_address, None)
+            .context("failed to allocate ID address")
+    })?;
+
+    if existing {
+        // NOTE: this case should be impossible, but we check it anyways just in case something
+        // changes.
+        return Err(actor_error!(forbidden, "cannot exec over an existing actor {}", id_address));
+    }
+
+    // Create an empty actor
+    rt.create_actor(code_cid, id_address, None)?;
+
+    // Invoke constructor
+    extract_send_result(rt.send_simple(
+        &Address::new_id(id_address),
+        METHOD_CONSTRUCTOR,
+        constructor_params.into(),
+        rt.message().value_received(),
+    ))
+    .context("constructor failed")?;
+
+    Ok(ExecReturn { id_address: Address::new_id(id_address), robust_address })
+}
+
+/// Domain separation prefix for deterministic actor addresses, distinguishing them from
+/// addresses derived from a message by the system.
+pub const DETERMINISTIC_ADDRESS_PREFIX: &[u8] = b"fil/init/exec-deterministic";
+
+/// Computes the robust address of an actor created by `creator` with `ExecDeterministic`.
+/// The address is the actor (f2) address of the payload
+/// `DETERMINISTIC_ADDRESS_PREFIX || creator || nonce || salt`,
+/// with the creator's actor ID and the nonce encoded as 8-byte big-endian integers.
+pub fn deterministic_address(creator: ActorID, nonce: u64, salt: &[u8; 32]) -> Address {
+    let payload = [
+        DETERMINISTIC_ADDRESS_PREFIX,
+        &creator.to_be_bytes()[..],
+        &nonce.to_be_bytes()[..],
+        &salt[..],
+    ]
+    .concat();
+    Address::new_actor(&payload)
+}
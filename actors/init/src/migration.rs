@@ -0,0 +1,45 @@
+// Copyright 2019-2022 ChainSafe Systems
+// SPDX-License-Identifier: Apache-2.0, MIT
+
+use cid::Cid;
+use fvm_ipld_blockstore::Blockstore;
+use fvm_ipld_encoding::tuple::*;
+use fvm_shared::address::Protocol;
+use fvm_shared::ActorID;
+
+use fil_actors_runtime::{ActorError, DEFAULT_HAMT_CONFIG};
+
+use crate::state::{AddressMap, RobustAddressMap};
+use crate::State;
+
+/// Init actor state as stored before robust addresses were indexed by actor ID.
+#[derive(Serialize_tuple, Deserialize_tuple, Clone, Debug)]
+pub struct LegacyState {
+    /// HAMT[Address]ActorID
+    pub address_map: Cid,
+    pub next_id: ActorID,
+    pub network_name: String,
+}
+
+impl LegacyState {
+    /// Migrates to the current state schema, recording the robust (non-delegated) address
+    /// of every actor in the address map.
+    pub fn migrate<BS: Blockstore>(self, store: &BS) -> Result<State, ActorError> {
+        let address_map =
+            AddressMap::load(store, &self.address_map, DEFAULT_HAMT_CONFIG, "addresses")?;
+        let mut robust_map =
+            RobustAddressMap::empty(store, DEFAULT_HAMT_CONFIG, "robust addresses");
+        address_map.for_each(|addr, id| {
+            if addr.protocol() != Protocol::Delegated {
+                robust_map.set(id, addr)?;
+            }
+            Ok(())
+        })?;
+        Ok(State {
+            address_map: self.address_map,
+            next_id: self.next_id,
+            network_name: self.network_name,
+            robust_address_map: robust_map.flush()?,
+        })
+    }
+}
@@ -8,23 +8,33 @@ use fvm_shared::address::{Address, Protocol};
 use fvm_shared::ActorID;
 
 use fil_actors_runtime::{
-    actor_error, ActorError, Map2, DEFAULT_HAMT_CONFIG, FIRST_NON_SINGLETON_ADDR,
+    actor_error, ActorError, ActorState, Map2, DEFAULT_HAMT_CONFIG, FIRST_NON_SINGLETON_ADDR,
 };
 
-#[derive(Serialize_tuple, Deserialize_tuple, Clone, Debug)]
+#[derive(Serialize_tuple, Deserialize_tuple, Clone, Debug, ActorState)]
 pub struct State {
     /// HAMT[Address]ActorID
     pub address_map: Cid,
     pub next_id: ActorID,
     pub network_name: String,
+    /// HAMT[ActorID]Address
+    /// The robust (non-delegated) address each actor ID was allocated for.
+    pub robust_address_map: Cid,
 }
 
 pub type AddressMap<BS> = Map2<BS, Address, ActorID>;
+pub type RobustAddressMap<BS> = Map2<BS, ActorID, Address>;
 
 impl State {
     pub fn new<BS: Blockstore>(store: &BS, network_name: String) -> Result<Self, ActorError> {
         let empty = AddressMap::flush_empty(store, DEFAULT_HAMT_CONFIG)?;
-        Ok(Self { address_map: empty, next_id: FIRST_NON_SINGLETON_ADDR, network_name })
+        let empty_robust = RobustAddressMap::flush_empty(store, DEFAULT_HAMT_CONFIG)?;
+        Ok(Self {
+            address_map: empty,
+            next_id: FIRST_NON_SINGLETON_ADDR,
+            network_name,
+            robust_address_map: empty_robust,
+        })
     }
 
     /// Maps argument addresses to to a new or existing actor ID.
@@ -32,6 +42,8 @@ impl State {
     /// allocates a new ID address and maps both to it.
     /// If the delegated address is already present, maps the robust address to that actor ID.
     /// Fails if the robust address is already mapped. The assignment of an ID to an address is one-time-only, even if the actor at that ID is deleted.
+    /// Unless the robust address is itself a delegated address, also records it as the
+    /// robust address of the actor ID.
     /// Returns the actor ID and a boolean indicating whether or not the actor already exists.
     pub fn map_addresses_to_id<BS: Blockstore>(
         &mut self,
@@ -68,6 +80,17 @@ impl State {
             ));
         }
         self.address_map = map.flush()?;
+
+        if robust_addr.protocol() != Protocol::Delegated {
+            let mut robust_map = RobustAddressMap::load(
+                store,
+                &self.robust_address_map,
+                DEFAULT_HAMT_CONFIG,
+                "robust addresses",
+            )?;
+            robust_map.set(&id, *robust_addr)?;
+            self.robust_address_map = robust_map.flush()?;
+        }
         Ok((id, existing))
     }
 
@@ -93,4 +116,21 @@ impl State {
         let found = map.get(addr)?;
         Ok(found.copied().map(Address::new_id))
     }
+
+    /// Looks up the robust address recorded for an actor ID.
+    /// Returns `None` for actors with no robust address in the map, such as singletons and
+    /// actors with only a delegated address.
+    pub fn lookup_robust_address<BS: Blockstore>(
+        &self,
+        store: &BS,
+        id: ActorID,
+    ) -> Result<Option<Address>, ActorError> {
+        let map = RobustAddressMap::load(
+            store,
+            &self.robust_address_map,
+            DEFAULT_HAMT_CONFIG,
+            "robust addresses",
+        )?;
+        Ok(map.get(&id)?.copied())
+    }
 }
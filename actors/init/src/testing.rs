@@ -6,9 +6,11 @@ use fvm_shared::{
     ActorID,
 };
 
-use fil_actors_runtime::{MessageAccumulator, DEFAULT_HAMT_CONFIG, FIRST_NON_SINGLETON_ADDR};
+use fil_actors_runtime::{
+    ActorState, MessageAccumulator, DEFAULT_HAMT_CONFIG, FIRST_NON_SINGLETON_ADDR,
+};
 
-use crate::state::AddressMap;
+use crate::state::{AddressMap, RobustAddressMap};
 use crate::State;
 
 pub struct StateSummary {
@@ -22,6 +24,7 @@ pub fn check_state_invariants<BS: Blockstore>(
     store: &BS,
 ) -> (StateSummary, MessageAccumulator) {
     let acc = MessageAccumulator::default();
+    state.check_fields(store, &acc);
 
     acc.require(!state.network_name.is_empty(), "network name is empty");
     acc.require(
@@ -73,5 +76,27 @@ pub fn check_state_invariants<BS: Blockstore>(
         Err(e) => acc.add(format!("error loading address map: {e}")),
     }
 
+    match RobustAddressMap::load(
+        store,
+        &state.robust_address_map,
+        DEFAULT_HAMT_CONFIG,
+        "robust addresses",
+    ) {
+        Ok(robust_address_map) => {
+            // Not every mapped actor has a reverse entry, but every reverse entry must agree
+            // with the address map.
+            let ret = robust_address_map.for_each(|actor_id, addr| {
+                acc.require(
+                    stable_address_by_id.get(&actor_id) == Some(addr),
+                    format!("robust address {addr} for ID {actor_id} not in address map"),
+                );
+                Ok(())
+            });
+
+            acc.require_no_error(ret, "error iterating robust address map");
+        }
+        Err(e) => acc.add(format!("error loading robust address map: {e}")),
+    }
+
     (init_summary, acc)
 }
@@ -3,8 +3,9 @@
 
 use cid::Cid;
 use fvm_ipld_encoding::tuple::*;
-use fvm_ipld_encoding::RawBytes;
+use fvm_ipld_encoding::{strict_bytes, RawBytes};
 use fvm_shared::address::Address;
+use fvm_shared::ActorID;
 
 /// Init actor Constructor parameters
 #[derive(Serialize_tuple, Deserialize_tuple)]
@@ -38,3 +39,29 @@ pub struct Exec4Params {
 
 /// Init actor Exec4 Return value
 pub type Exec4Return = ExecReturn;
+
+/// Init actor ExecDeterministic Params
+#[derive(Serialize_tuple, Deserialize_tuple)]
+pub struct ExecDeterministicParams {
+    pub code_cid: Cid,
+    pub constructor_params: RawBytes,
+    /// Caller-chosen nonce, allowing a series of actors with a common salt
+    pub nonce: u64,
+    #[serde(with = "strict_bytes")]
+    pub salt: [u8; 32],
+}
+
+/// Init actor LookupRobustAddress Params
+#[derive(Serialize_tuple, Deserialize_tuple)]
+#[serde(transparent)]
+pub struct LookupRobustAddressParams {
+    pub actor_id: ActorID,
+}
+
+/// Init actor LookupRobustAddress Return value
+#[derive(Debug, Serialize_tuple, Deserialize_tuple)]
+#[serde(transparent)]
+pub struct LookupRobustAddressReturn {
+    /// Robust address the actor was created with, if known
+    pub robust_address: Option<Address>,
+}
@@ -4,10 +4,12 @@
 use std::cell::RefCell;
 
 use cid::Cid;
+use fil_actor_init::migration::LegacyState;
 use fil_actor_init::testing::check_state_invariants;
 use fil_actor_init::{
-    Actor as InitActor, ConstructorParams, Exec4Params, Exec4Return, ExecParams, ExecReturn,
-    Method, State,
+    deterministic_address, Actor as InitActor, ConstructorParams, Exec4Params, Exec4Return,
+    ExecDeterministicParams, ExecParams, ExecReturn, LookupRobustAddressParams,
+    LookupRobustAddressReturn, Method, State,
 };
 use fil_actors_runtime::runtime::Runtime;
 use fil_actors_runtime::{test_utils::*, EAM_ACTOR_ADDR, EAM_ACTOR_ID};
@@ -409,6 +411,121 @@ fn call_exec4_placeholder() {
         .flatten()
         .expect("failed to lookup f4 address");
     assert_eq!(expected_id_addr, resolved_id, "f4 address not assigned to the right actor");
+
+    // The placeholder's f4 address isn't recorded as its robust address, but the new one is.
+    assert_eq!(Some(unique_address), lookup_robust_address(&rt, expected_id));
+}
+
+#[test]
+fn lookup_robust_address_for_id() {
+    let rt = construct_runtime();
+    construct_and_verify(&rt);
+    rt.set_caller(*ACCOUNT_ACTOR_CODE_ID, Address::new_id(1001));
+
+    let unique_address = Address::new_actor(b"paych");
+    rt.new_actor_addr.replace(Some(unique_address));
+
+    let expected_id = 100;
+    rt.expect_create_actor(*PAYCH_ACTOR_CODE_ID, expected_id, None);
+    let fake_params = ConstructorParams { network_name: String::from("fake_param") };
+    rt.expect_send_simple(
+        Address::new_id(expected_id),
+        METHOD_CONSTRUCTOR,
+        IpldBlock::serialize_cbor(&fake_params).unwrap(),
+        TokenAmount::zero(),
+        None,
+        ExitCode::OK,
+    );
+    exec_and_verify(&rt, *PAYCH_ACTOR_CODE_ID, &fake_params).unwrap();
+
+    assert_eq!(Some(unique_address), lookup_robust_address(&rt, expected_id));
+    // Unallocated and singleton IDs have no robust address.
+    assert_eq!(None, lookup_robust_address(&rt, expected_id + 1));
+    assert_eq!(None, lookup_robust_address(&rt, 0));
+    check_state(&rt);
+}
+
+#[test]
+fn exec_deterministic_at_predicted_address() {
+    let rt = construct_runtime();
+    construct_and_verify(&rt);
+    let creator: ActorID = 1001;
+    rt.set_caller(*ACCOUNT_ACTOR_CODE_ID, Address::new_id(creator));
+
+    let salt = [7; 32];
+    let fake_params = ConstructorParams { network_name: String::from("fake_param") };
+    for nonce in 0..2 {
+        let predicted = deterministic_address(creator, nonce, &salt);
+        let expected_id_addr = Address::new_id(100 + nonce);
+        rt.expect_create_actor(*PAYCH_ACTOR_CODE_ID, 100 + nonce, None);
+        rt.expect_send_simple(
+            expected_id_addr,
+            METHOD_CONSTRUCTOR,
+            IpldBlock::serialize_cbor(&fake_params).unwrap(),
+            TokenAmount::zero(),
+            None,
+            ExitCode::OK,
+        );
+
+        let exec_ret =
+            exec_deterministic_and_verify(&rt, *PAYCH_ACTOR_CODE_ID, nonce, salt, &fake_params)
+                .unwrap();
+        assert_eq!(predicted, exec_ret.robust_address);
+        assert_eq!(expected_id_addr, exec_ret.id_address);
+
+        let state: State = rt.get_state();
+        assert_eq!(Some(expected_id_addr), state.resolve_address(&rt.store, &predicted).unwrap());
+    }
+
+    // The address depends on the creator.
+    assert_ne!(deterministic_address(creator, 0, &salt), deterministic_address(1002, 0, &salt));
+
+    // Reusing a nonce and salt collides with the existing actor.
+    expect_abort_contains_message(
+        ExitCode::USR_FORBIDDEN,
+        "is already allocated",
+        exec_deterministic_and_verify(&rt, *PAYCH_ACTOR_CODE_ID, 0, salt, &fake_params),
+    );
+
+    // The same restrictions on actor type apply as for Exec.
+    expect_abort_contains_message(
+        ExitCode::USR_FORBIDDEN,
+        "cannot exec actor type",
+        exec_deterministic_and_verify(&rt, *POWER_ACTOR_CODE_ID, 2, salt, &fake_params),
+    );
+    check_state(&rt);
+}
+
+#[test]
+fn upgrade_migrates_legacy_state() {
+    let rt = construct_runtime();
+    construct_and_verify(&rt);
+
+    let robust = Address::new_actor(b"robust");
+    let robust_with_f4 = Address::new_actor(b"robust with f4");
+    let f4_addr = Address::new_delegated(EAM_ACTOR_ID, b"foobar").unwrap();
+    let placeholder_addr = Address::new_delegated(EAM_ACTOR_ID, b"placeholder").unwrap();
+    let mut st: State = rt.get_state();
+    let (robust_id, _) = st.map_addresses_to_id(rt.store(), &robust, None).unwrap();
+    let (f4_id, _) = st.map_addresses_to_id(rt.store(), &robust_with_f4, Some(&f4_addr)).unwrap();
+    let (placeholder_id, _) = st.map_addresses_to_id(rt.store(), &placeholder_addr, None).unwrap();
+    rt.replace_state(&LegacyState {
+        address_map: st.address_map,
+        next_id: st.next_id,
+        network_name: st.network_name.clone(),
+    });
+    rt.upgrade::<InitActor>();
+    let migrated: State = rt.get_state();
+    assert_eq!(st.address_map, migrated.address_map);
+    assert_eq!(st.next_id, migrated.next_id);
+    assert_eq!(st.network_name, migrated.network_name);
+
+    rt.set_caller(*ACCOUNT_ACTOR_CODE_ID, Address::new_id(1001));
+    assert_eq!(Some(robust), lookup_robust_address(&rt, robust_id));
+    assert_eq!(Some(robust_with_f4), lookup_robust_address(&rt, f4_id));
+    // Delegated addresses aren't recorded as robust addresses.
+    assert_eq!(None, lookup_robust_address(&rt, placeholder_id));
+    check_state(&rt);
 }
 
 fn construct_and_verify(rt: &MockRuntime) {
@@ -451,6 +568,34 @@ where
     ret.and_then(|v| v.unwrap().deserialize().map_err(|e| e.into()))
 }
 
+fn exec_deterministic_and_verify<S>(
+    rt: &MockRuntime,
+    code_id: Cid,
+    nonce: u64,
+    salt: [u8; 32],
+    params: &S,
+) -> Result<ExecReturn, ActorError>
+where
+    S: Serialize,
+{
+    rt.expect_validate_caller_any();
+    let exec_params = ExecDeterministicParams {
+        code_cid: code_id,
+        constructor_params: RawBytes::serialize(params).unwrap(),
+        nonce,
+        salt,
+    };
+
+    let ret = rt.call::<InitActor>(
+        Method::ExecDeterministicExported as u64,
+        IpldBlock::serialize_cbor(&exec_params).unwrap(),
+    );
+
+    rt.verify();
+    check_state(rt);
+    ret.and_then(|v| v.unwrap().deserialize().map_err(|e| e.into()))
+}
+
 fn exec4_and_verify<S>(
     rt: &MockRuntime,
     namespace: ActorID,
@@ -476,3 +621,18 @@ where
     check_state(rt);
     ret.and_then(|v| v.unwrap().deserialize().map_err(|e| e.into()))
 }
+
+fn lookup_robust_address(rt: &MockRuntime, actor_id: ActorID) -> Option<Address> {
+    rt.expect_validate_caller_any();
+    let ret: LookupRobustAddressReturn = rt
+        .call::<InitActor>(
+            Method::LookupRobustAddressExported as u64,
+            IpldBlock::serialize_cbor(&LookupRobustAddressParams { actor_id }).unwrap(),
+        )
+        .unwrap()
+        .unwrap()
+        .deserialize()
+        .unwrap();
+    rt.verify();
+    ret.robust_address
+}
@@ -5,10 +5,12 @@ use cid::Cid;
 use fvm_ipld_blockstore::Blockstore;
 use fvm_shared::address::Address;
 use fvm_shared::econ::TokenAmount;
+use fvm_shared::error::ExitCode;
+use fvm_shared::{ActorID, HAMT_BIT_WIDTH};
 use num_traits::Zero;
 
 use fil_actors_runtime::{
-    actor_error, ActorContext, ActorError, Config, Map2, DEFAULT_HAMT_CONFIG,
+    actor_error, ActorContext, ActorError, AsActorError, Config, Map2, MapMap, DEFAULT_HAMT_CONFIG,
 };
 
 /// Balance table which handles getting and updating token balances specifically
@@ -115,13 +117,86 @@ where
     }
 }
 
+/// Allowance table which tracks funds deposited by one account into the escrow of another,
+/// indexed by funder and then beneficiary.
+/// A funder may reclaim up to its allowance from the beneficiary's unlocked escrow balance.
+pub struct AllowanceTable<'a, BS: Blockstore>(pub MapMap<'a, BS, TokenAmount, ActorID, ActorID>);
+
+impl<'a, BS> AllowanceTable<'a, BS>
+where
+    BS: Blockstore,
+{
+    /// Initializes a new empty allowance table
+    pub fn new(bs: &'a BS) -> Self {
+        Self(MapMap::new(bs, HAMT_BIT_WIDTH, HAMT_BIT_WIDTH))
+    }
+
+    /// Initializes an allowance table from a root Cid
+    pub fn from_root(bs: &'a BS, cid: &Cid) -> Result<Self, ActorError> {
+        Ok(Self(
+            MapMap::from_root(bs, cid, HAMT_BIT_WIDTH, HAMT_BIT_WIDTH)
+                .context_code(ExitCode::USR_ILLEGAL_STATE, "failed to load allowance table")?,
+        ))
+    }
+
+    /// Retrieve root from allowance table
+    pub fn root(&mut self) -> Result<Cid, ActorError> {
+        self.0.flush().context_code(ExitCode::USR_ILLEGAL_STATE, "failed to flush allowance table")
+    }
+
+    /// Gets the allowance of a funder for a beneficiary
+    pub fn get(
+        &mut self,
+        funder: ActorID,
+        beneficiary: ActorID,
+    ) -> Result<TokenAmount, ActorError> {
+        Ok(self
+            .0
+            .get(funder, beneficiary)
+            .context_code(ExitCode::USR_ILLEGAL_STATE, "failed to get allowance")?
+            .cloned()
+            .unwrap_or_default())
+    }
+
+    /// Adds token amount to the allowance of a funder for a beneficiary.
+    pub fn add(
+        &mut self,
+        funder: ActorID,
+        beneficiary: ActorID,
+        value: &TokenAmount,
+    ) -> Result<(), ActorError> {
+        let prev = self.get(funder, beneficiary)?;
+        let sum = &prev + value;
+        if sum.is_negative() {
+            Err(actor_error!(
+                illegal_argument,
+                "negative allowance for {} to {} adding {} to {}",
+                funder,
+                beneficiary,
+                value,
+                prev
+            ))
+        } else if sum.is_zero() {
+            self.0
+                .remove(funder, beneficiary)
+                .context_code(ExitCode::USR_ILLEGAL_STATE, "failed to remove allowance")?;
+            Ok(())
+        } else {
+            self.0
+                .put(funder, beneficiary, sum)
+                .context_code(ExitCode::USR_ILLEGAL_STATE, "failed to set allowance")?;
+            Ok(())
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use fil_actors_runtime::test_blockstores::MemoryBlockstore;
     use fvm_shared::address::Address;
     use fvm_shared::econ::TokenAmount;
 
-    use crate::balance_table::BalanceTable;
+    use crate::balance_table::{AllowanceTable, BalanceTable};
 
     #[test]
     fn total() {
@@ -190,4 +265,32 @@ mod tests {
         // Test subtracting more than available
         assert!(bt.must_subtract(&addr, &TokenAmount::from_atto(100u8)).is_err());
     }
+
+    #[test]
+    fn allowance_add_and_remove() {
+        let store = MemoryBlockstore::default();
+        let mut at = AllowanceTable::new(&store);
+
+        at.add(100, 101, &TokenAmount::from_atto(50u8)).unwrap();
+        at.add(100, 101, &TokenAmount::from_atto(20u8)).unwrap();
+        at.add(102, 101, &TokenAmount::from_atto(10u8)).unwrap();
+        assert_eq!(at.get(100, 101).unwrap(), TokenAmount::from_atto(70u8));
+        assert_eq!(at.get(102, 101).unwrap(), TokenAmount::from_atto(10u8));
+        assert!(at.get(101, 100).unwrap().is_zero());
+
+        // Allowances survive a flush
+        let root = at.root().unwrap();
+        let mut at = AllowanceTable::from_root(&store, &root).unwrap();
+        assert_eq!(at.get(100, 101).unwrap(), TokenAmount::from_atto(70u8));
+
+        // Subtracting more than the allowance fails
+        assert!(at.add(100, 101, &TokenAmount::from_atto(-80)).is_err());
+
+        // Reducing an allowance to zero removes it
+        at.add(102, 101, &TokenAmount::from_atto(-10)).unwrap();
+        assert!(at.get(102, 101).unwrap().is_zero());
+        let empty = AllowanceTable::new(&store).root().unwrap();
+        at.add(100, 101, &TokenAmount::from_atto(-70)).unwrap();
+        assert_eq!(at.root().unwrap(), empty);
+    }
 }
@@ -134,6 +134,28 @@ pub struct ClientDealProposal {
     pub client_signature: Signature,
 }
 
+/// Optional terms of a deal that are not part of its proposal.
+/// They are stored apart from the proposal, so do not change the proposal's encoding or CID.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize_tuple, Deserialize_tuple)]
+pub struct DealExtensions {
+    /// Actor to be notified when the deal is activated, terminated or completes,
+    /// through its MarketNotifyDealOutcome method.
+    pub notify: Option<Address>,
+    /// CID of arbitrary metadata for the deal, such as retrieval terms or an SLA document.
+    /// The CID's encoding can be at most `DEAL_MAX_METADATA_CID_SIZE` bytes.
+    pub metadata: Option<Cid>,
+}
+
+/// ClientDealProposalWithExtensions is a DealProposal with extensions, signed by a client.
+/// The signature is over the domain separation tag `SIGNATURE_DOMAIN_SEPARATION_DEAL_EXTENSIONS`
+/// followed by the CBOR encoding of the two-element array `[proposal, extensions]`.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize_tuple, Deserialize_tuple)]
+pub struct ClientDealProposalWithExtensions {
+    pub proposal: DealProposal,
+    pub extensions: DealExtensions,
+    pub client_signature: Signature,
+}
+
 #[derive(Clone, Debug, PartialEq, Eq, Copy, Serialize_tuple, Deserialize_tuple)]
 pub struct DealState {
     // 0 if not yet included in proven sector (0 is also a valid sector number)
@@ -1,6 +1,9 @@
 use fil_actors_runtime::runtime::Runtime;
 use fil_actors_runtime::{ActorError, EventBuilder};
+use fvm_shared::address::Address;
+use fvm_shared::clock::ChainEpoch;
 use fvm_shared::deal::DealID;
+use fvm_shared::econ::TokenAmount;
 use fvm_shared::ActorID;
 
 /// Indicates a deal has been published.
@@ -63,6 +66,57 @@ pub fn deal_completed(
     )
 }
 
+/// Indicates the terms of an active deal have been amended.
+pub fn deal_amended(
+    rt: &impl Runtime,
+    deal_id: DealID,
+    client: ActorID,
+    provider: ActorID,
+    storage_price_per_epoch: &TokenAmount,
+    end_epoch: ChainEpoch,
+) -> Result<(), ActorError> {
+    rt.emit_event(
+        &EventBuilder::new()
+            .typ("deal-amended")
+            .with_parties(deal_id, client, provider)
+            .field("price-per-epoch", storage_price_per_epoch)
+            .field("end-epoch", &end_epoch)
+            .build()?,
+    )
+}
+
+/// Indicates funds have been withdrawn from an escrow balance.
+pub fn balance_withdrawn(
+    rt: &impl Runtime,
+    account: ActorID,
+    payee: &Address,
+    amount: &TokenAmount,
+) -> Result<(), ActorError> {
+    rt.emit_event(
+        &EventBuilder::new()
+            .typ("balance-withdrawn")
+            .field_indexed("account", &account)
+            .field_indexed("payee", payee)
+            .field("amount", amount)
+            .build()?,
+    )
+}
+
+/// Indicates cron processed more scheduled deal operations in an epoch than its budget.
+pub fn cron_budget_exceeded(
+    rt: &impl Runtime,
+    entries: u64,
+    budget: u64,
+) -> Result<(), ActorError> {
+    rt.emit_event(
+        &EventBuilder::new()
+            .typ("cron-budget-exceeded")
+            .field("entries", &entries)
+            .field("budget", &budget)
+            .build()?,
+    )
+}
+
 trait WithParties {
     fn with_parties(self, id: DealID, client: ActorID, provider: ActorID) -> EventBuilder;
 }
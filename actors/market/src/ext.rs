@@ -3,7 +3,7 @@ use fvm_ipld_encoding::tuple::*;
 use fvm_shared::address::Address;
 use fvm_shared::bigint::bigint_ser;
 use fvm_shared::econ::TokenAmount;
-use fvm_shared::sector::StoragePower;
+use fvm_shared::sector::{RegisteredPoStProof, RegisteredSealProof, StoragePower};
 
 use fil_actors_runtime::reward::FilterEstimate;
 
@@ -24,11 +24,6 @@ pub mod account {
 
 pub mod miner {
     use super::*;
-    use cid::Cid;
-    use fvm_ipld_encoding::RawBytes;
-    use fvm_shared::clock::ChainEpoch;
-    use fvm_shared::piece::PaddedPieceSize;
-    use fvm_shared::sector::SectorNumber;
     use fvm_shared::MethodNum;
 
     pub const CONTROL_ADDRESSES_METHOD: u64 = 2;
@@ -37,6 +32,13 @@ pub mod miner {
     pub const SECTOR_CONTENT_CHANGED: MethodNum =
         frc42_dispatch::method_hash!("SectorContentChanged");
 
+    // Tests use the full set of notification types, of which the actor uses only some.
+    #[allow(unused_imports)]
+    pub use fil_actors_runtime::sector_content::{
+        PieceChange, PieceReturn, SectorChanges, SectorContentChangedParams,
+        SectorContentChangedReturn, SectorReturn,
+    };
+
     #[derive(Serialize_tuple, Deserialize_tuple)]
     pub struct GetControlAddressesReturnParams {
         pub owner: Address,
@@ -55,44 +57,6 @@ pub mod miner {
     pub struct IsControllingAddressParam {
         pub address: Address,
     }
-
-    #[derive(Clone, Debug, PartialEq, Eq, Serialize_tuple, Deserialize_tuple)]
-    #[serde(transparent)]
-    pub struct SectorContentChangedParams {
-        pub sectors: Vec<SectorChanges>,
-    }
-
-    #[derive(Clone, Debug, PartialEq, Eq, Serialize_tuple, Deserialize_tuple)]
-    pub struct SectorChanges {
-        pub sector: SectorNumber,
-        pub minimum_commitment_epoch: ChainEpoch,
-        pub added: Vec<PieceChange>,
-    }
-
-    #[derive(Clone, Debug, PartialEq, Eq, Serialize_tuple, Deserialize_tuple)]
-    pub struct PieceChange {
-        pub data: Cid,
-        pub size: PaddedPieceSize,
-        pub payload: RawBytes,
-    }
-
-    #[derive(Clone, Debug, PartialEq, Eq, Serialize_tuple, Deserialize_tuple)]
-    #[serde(transparent)]
-    pub struct SectorContentChangedReturn {
-        pub sectors: Vec<SectorReturn>,
-    }
-
-    #[derive(Clone, Debug, PartialEq, Eq, Serialize_tuple, Deserialize_tuple)]
-    #[serde(transparent)]
-    pub struct SectorReturn {
-        pub added: Vec<PieceReturn>,
-    }
-
-    #[derive(Clone, Debug, PartialEq, Eq, Serialize_tuple, Deserialize_tuple)]
-    #[serde(transparent)]
-    pub struct PieceReturn {
-        pub accepted: bool,
-    }
 }
 
 pub mod verifreg {
@@ -164,5 +128,12 @@ pub mod power {
         pub quality_adj_power_smoothed: FilterEstimate,
         pub ramp_start_epoch: i64,
         pub ramp_duration_epochs: u64,
+        pub proof_type_allowlist: Option<ProofTypeAllowlist>,
+    }
+
+    #[derive(Serialize_tuple, Deserialize_tuple)]
+    pub struct ProofTypeAllowlist {
+        pub seal_proof_types: Vec<RegisteredSealProof>,
+        pub post_proof_types: Vec<RegisteredPoStProof>,
     }
 }
@@ -17,6 +17,7 @@ use fvm_shared::address::Address;
 use fvm_shared::bigint::BigInt;
 use fvm_shared::clock::{ChainEpoch, EPOCH_UNDEFINED};
 use fvm_shared::crypto::hash::SupportedHashes;
+use fvm_shared::crypto::signature::Signature;
 use fvm_shared::deal::DealID;
 use fvm_shared::econ::TokenAmount;
 use fvm_shared::error::ExitCode;
@@ -37,10 +38,14 @@ use fil_actors_runtime::{
     AsActorError, BURNT_FUNDS_ACTOR_ADDR, CRON_ACTOR_ADDR, DATACAP_TOKEN_ACTOR_ADDR,
     REWARD_ACTOR_ADDR, STORAGE_POWER_ACTOR_ADDR, SYSTEM_ACTOR_ADDR, VERIFIED_REGISTRY_ACTOR_ADDR,
 };
-use fil_actors_runtime::{extract_send_result, BatchReturnGen, FIRST_ACTOR_SPECIFIC_EXIT_CODE};
+use fil_actors_runtime::{
+    extract_send_result, migrate_state, BatchReturnGen, OnUpgradeParams,
+    FIRST_ACTOR_SPECIFIC_EXIT_CODE,
+};
 
 use crate::balance_table::BalanceTable;
 use crate::ext::verifreg::{AllocationID, AllocationRequest};
+use crate::migration::LegacyState;
 
 pub use self::deal::*;
 use self::policy::*;
@@ -49,9 +54,14 @@ pub use self::types::*;
 
 // exports for testing
 pub mod balance_table;
-#[doc(hidden)]
+#[cfg(feature = "testing")]
 pub mod ext;
+#[cfg(not(feature = "testing"))]
+#[allow(dead_code)]
+mod ext;
+pub mod migration;
 pub mod policy;
+#[cfg(feature = "testing")]
 pub mod testing;
 
 mod deal;
@@ -98,8 +108,19 @@ pub enum Method {
     GetDealVerifiedExported = frc42_dispatch::method_hash!("GetDealVerified"),
     GetDealActivationExported = frc42_dispatch::method_hash!("GetDealActivation"),
     GetDealSectorExported = frc42_dispatch::method_hash!("GetDealSector"),
+    GetDealsForProviderExported = frc42_dispatch::method_hash!("GetDealsForProvider"),
     SettleDealPaymentsExported = frc42_dispatch::method_hash!("SettleDealPayments"),
+    AmendDealTermsExported = frc42_dispatch::method_hash!("AmendDealTerms"),
+    AddBalanceForExported = frc42_dispatch::method_hash!("AddBalanceFor"),
+    ReclaimAllowanceExported = frc42_dispatch::method_hash!("ReclaimAllowance"),
+    VerifyDealsForActivationDirectExported =
+        frc42_dispatch::method_hash!("VerifyDealsForActivationDirect"),
+    WithdrawBalanceMultiExported = frc42_dispatch::method_hash!("WithdrawBalanceMulti"),
     SectorContentChangedExported = ext::miner::SECTOR_CONTENT_CHANGED,
+    GetDealMetadataExported = frc42_dispatch::method_hash!("GetDealMetadata"),
+    CronWorkStatsExported = frc42_dispatch::method_hash!("CronWorkStats"),
+    PublishStorageDealsWithExtensionsExported =
+        frc42_dispatch::method_hash!("PublishStorageDealsWithExtensions"),
 }
 
 /// Market Actor
@@ -138,8 +159,71 @@ impl Actor {
         Ok(())
     }
 
+    /// Deposits the received value into the balance held in escrow for a beneficiary,
+    /// on behalf of the caller.
+    /// The caller does not control the beneficiary's escrow, but is granted an allowance
+    /// with which it may later reclaim funds that remain unlocked.
+    fn add_balance_for(rt: &impl Runtime, params: AddBalanceForParams) -> Result<(), ActorError> {
+        let msg_value = rt.message().value_received();
+
+        if msg_value <= TokenAmount::zero() {
+            return Err(actor_error!(
+                illegal_argument,
+                "balance to add must be greater than zero was: {}",
+                msg_value
+            ));
+        }
+
+        rt.validate_immediate_caller_accept_any()?;
+        let funder = rt.message().caller().id().unwrap();
+
+        let (nominal, _, _) = escrow_address(rt, &params.beneficiary)?;
+
+        rt.transaction(|st: &mut State, rt| {
+            st.add_balance_for(rt.store(), funder, &nominal, &msg_value)?;
+            Ok(())
+        })?;
+
+        Ok(())
+    }
+
+    /// Attempt to reclaim the specified amount from the caller's allowance for a beneficiary.
+    /// If less than the specified amount is available, either because the allowance is smaller
+    /// or the beneficiary's escrow is locked, yields the entire available amount.
+    fn reclaim_allowance(
+        rt: &impl Runtime,
+        params: ReclaimAllowanceParams,
+    ) -> Result<ReclaimAllowanceReturn, ActorError> {
+        rt.validate_immediate_caller_accept_any()?;
+        if params.amount < TokenAmount::zero() {
+            return Err(actor_error!(illegal_argument, "negative amount: {}", params.amount));
+        }
+
+        let funder = rt.message().caller();
+        let beneficiary = rt.resolve_address(&params.beneficiary).ok_or_else(|| {
+            actor_error!(illegal_argument, "failed to resolve address {}", params.beneficiary)
+        })?;
+        let beneficiary = Address::new_id(beneficiary);
+
+        let amount_reclaimed = rt.transaction(|st: &mut State, rt| {
+            st.reclaim_allowance(rt.store(), funder.id().unwrap(), &beneficiary, &params.amount)
+        })?;
+
+        if amount_reclaimed.is_positive() {
+            extract_send_result(rt.send_simple(
+                &funder,
+                METHOD_SEND,
+                None,
+                amount_reclaimed.clone(),
+            ))?;
+        }
+
+        Ok(ReclaimAllowanceReturn { amount_reclaimed })
+    }
+
     /// Attempt to withdraw the specified amount from the balance held in escrow.
     /// If less than the specified amount is available, yields the entire available balance.
+    /// Funds are sent to the escrow owner, or to a payee designated by the owner.
     fn withdraw_balance(
         rt: &impl Runtime,
         params: WithdrawBalanceParams,
@@ -152,6 +236,20 @@ impl Actor {
         // for providers -> only corresponding owner or worker can withdraw
         // for clients -> only the client i.e the recipient can withdraw
         rt.validate_immediate_caller_is(&approved)?;
+        // Only the owner of the escrow, to whom funds are otherwise sent, may redirect them.
+        let payee = match params.payee {
+            Some(payee) => {
+                if rt.message().caller() != recipient {
+                    return Err(actor_error!(
+                        forbidden,
+                        "only the escrow owner {} may designate a payee",
+                        recipient
+                    ));
+                }
+                payee
+            }
+            None => recipient,
+        };
 
         let amount_extracted = rt.transaction(|st: &mut State, rt| {
             let ex = st.withdraw_balance_from_escrow_table(rt.store(), &nominal, &params.amount)?;
@@ -159,16 +257,64 @@ impl Actor {
             Ok(ex)
         })?;
 
-        extract_send_result(rt.send_simple(
-            &recipient,
-            METHOD_SEND,
-            None,
-            amount_extracted.clone(),
-        ))?;
+        extract_send_result(rt.send_simple(&payee, METHOD_SEND, None, amount_extracted.clone()))?;
+        emit::balance_withdrawn(rt, nominal.id().unwrap(), &payee, &amount_extracted)?;
 
         Ok(WithdrawBalanceReturn { amount_withdrawn: amount_extracted })
     }
 
+    /// Withdraws amounts from a provider's escrow balance to multiple recipients.
+    /// Only the provider's owner may withdraw to recipients of its choosing.
+    /// The total is debited at once, and the withdrawal fails entirely if the total
+    /// exceeds the balance not locked as collateral.
+    fn withdraw_balance_multi(
+        rt: &impl Runtime,
+        params: WithdrawBalanceMultiParams,
+    ) -> Result<WithdrawBalanceMultiReturn, ActorError> {
+        if params.withdrawals.is_empty() {
+            return Err(actor_error!(illegal_argument, "no withdrawals specified"));
+        }
+        let mut total = TokenAmount::zero();
+        for target in &params.withdrawals {
+            if !target.amount.is_positive() {
+                return Err(actor_error!(
+                    illegal_argument,
+                    "withdrawal amount to {} must be positive: {}",
+                    target.recipient,
+                    target.amount
+                ));
+            }
+            total += &target.amount;
+        }
+
+        let provider = rt.resolve_address(&params.provider).ok_or_else(|| {
+            actor_error!(illegal_argument, "failed to resolve address {}", params.provider)
+        })?;
+        let code_id = rt
+            .get_actor_code_cid(&provider)
+            .ok_or_else(|| actor_error!(illegal_argument, "no code for address {}", provider))?;
+        if rt.resolve_builtin_actor_type(&code_id) != Some(Type::Miner) {
+            return Err(actor_error!(illegal_argument, "{} is not a storage provider", provider));
+        }
+        let (owner, _, _) = request_miner_control_addrs(rt, provider)?;
+        rt.validate_immediate_caller_is(std::iter::once(&owner))?;
+
+        rt.transaction(|st: &mut State, rt| {
+            st.withdraw_exact_from_escrow_table(rt.store(), &Address::new_id(provider), &total)
+        })?;
+
+        for target in params.withdrawals {
+            extract_send_result(rt.send_simple(
+                &target.recipient,
+                METHOD_SEND,
+                None,
+                target.amount,
+            ))?;
+        }
+
+        Ok(WithdrawBalanceMultiReturn { amount_withdrawn: total })
+    }
+
     /// Returns the escrow balance and locked amount for an address.
     fn get_balance(
         rt: &impl Runtime,
@@ -197,12 +343,38 @@ impl Actor {
         params: PublishStorageDealsParams,
     ) -> Result<PublishStorageDealsReturn, ActorError> {
         rt.validate_immediate_caller_accept_any()?;
-        if params.deals.is_empty() {
+        Self::publish_deals(rt, params.deals.into_iter().map(|deal| (deal, None)).collect())
+    }
+
+    /// Publish a new set of storage deals with extensions, each signed by its client together
+    /// with the deal proposal.
+    fn publish_storage_deals_with_extensions(
+        rt: &impl Runtime,
+        params: PublishStorageDealsWithExtensionsParams,
+    ) -> Result<PublishStorageDealsReturn, ActorError> {
+        rt.validate_immediate_caller_accept_any()?;
+        let deals = params
+            .deals
+            .into_iter()
+            .map(|deal| {
+                let ClientDealProposalWithExtensions { proposal, extensions, client_signature } =
+                    deal;
+                (ClientDealProposal { proposal, client_signature }, Some(extensions))
+            })
+            .collect();
+        Self::publish_deals(rt, deals)
+    }
+
+    fn publish_deals(
+        rt: &impl Runtime,
+        deals: Vec<(ClientDealProposal, Option<DealExtensions>)>,
+    ) -> Result<PublishStorageDealsReturn, ActorError> {
+        if deals.is_empty() {
             return Err(actor_error!(illegal_argument, "Empty deals parameter"));
         }
 
         // All deals should have the same provider so get worker once
-        let provider_raw = params.deals[0].proposal.provider;
+        let provider_raw = deals[0].0.proposal.provider;
         let provider_id = rt.resolve_address(&provider_raw).ok_or_else(|| {
             actor_error!(not_found, "failed to resolve provider address {}", provider_raw)
         })?;
@@ -218,33 +390,17 @@ impl Actor {
             ));
         }
 
-        let caller = rt.message().caller();
-        let caller_status: ext::miner::IsControllingAddressReturn =
-            deserialize_block(extract_send_result(rt.send_simple(
-                &Address::new_id(provider_id),
-                ext::miner::IS_CONTROLLING_ADDRESS_EXPORTED,
-                IpldBlock::serialize_cbor(&ext::miner::IsControllingAddressParam {
-                    address: caller,
-                })?,
-                TokenAmount::zero(),
-            ))?)?;
-        if !caller_status.is_controlling {
-            return Err(actor_error!(
-                forbidden,
-                "caller {} is not worker or control address of provider {}",
-                caller,
-                provider_id
-            ));
-        }
+        validate_caller_controls_provider(rt, provider_id)?;
         // Deals that passed `AuthenticateMessage` and other state-less checks.
-        let mut validity_index: Vec<bool> = Vec::with_capacity(params.deals.len());
+        let mut validity_index: Vec<bool> = Vec::with_capacity(deals.len());
 
         let baseline_power = request_current_baseline_power(rt)?;
         let (network_raw_power, _) = request_current_network_power(rt)?;
 
         // We perform these checks before loading state since the call to `AuthenticateMessage` could recurse
-        for (di, deal) in params.deals.iter().enumerate() {
-            let valid = if let Err(e) = validate_deal(rt, deal, &network_raw_power, &baseline_power)
+        for (di, (deal, extensions)) in deals.iter().enumerate() {
+            let valid = if let Err(e) =
+                validate_deal(rt, deal, extensions.as_ref(), &network_raw_power, &baseline_power)
             {
                 info!("invalid deal {}: {}", di, e);
                 false
@@ -257,12 +413,13 @@ impl Actor {
 
         struct ValidDeal {
             proposal: DealProposal,
+            extensions: Option<DealExtensions>,
             serialized_proposal: RawBytes,
             cid: Cid,
         }
 
         // Deals that passed validation.
-        let mut valid_deals: Vec<ValidDeal> = Vec::with_capacity(params.deals.len());
+        let mut valid_deals: Vec<ValidDeal> = Vec::with_capacity(deals.len());
         // CIDs of valid proposals.
         let mut proposal_cid_lookup = BTreeSet::new();
         let mut total_client_lockup: BTreeMap<ActorID, TokenAmount> = BTreeMap::new();
@@ -278,7 +435,7 @@ impl Actor {
 
         let state: State = rt.state()?;
 
-        for (di, mut deal) in params.deals.into_iter().enumerate() {
+        for (di, (mut deal, mut extensions)) in deals.into_iter().enumerate() {
             if !*validity_index.get(di).context_code(
                 ExitCode::USR_ASSERTION_FAILED,
                 "validity index has incorrect length",
@@ -306,6 +463,20 @@ impl Actor {
                 }
             };
 
+            // Normalise the notification actor to an ID address, which must exist.
+            if let Some(notify) = extensions.as_mut().and_then(|e| e.notify.as_mut()) {
+                match rt.resolve_address(notify) {
+                    Some(id) => *notify = Address::new_id(id),
+                    None => {
+                        info!(
+                            "invalid deal {}: failed to resolve notification actor address {}",
+                            di, notify
+                        );
+                        continue;
+                    }
+                }
+            }
+
             // drop deals with insufficient lock up to cover costs
             let mut client_lockup =
                 total_client_lockup.get(&client_id).cloned().unwrap_or_default();
@@ -382,7 +553,12 @@ impl Actor {
             total_provider_lockup = provider_lockup;
             total_client_lockup.insert(client_id, client_lockup);
             proposal_cid_lookup.insert(pcid);
-            valid_deals.push(ValidDeal { proposal: deal.proposal, serialized_proposal, cid: pcid });
+            valid_deals.push(ValidDeal {
+                proposal: deal.proposal,
+                extensions,
+                serialized_proposal,
+                cid: pcid,
+            });
             valid_input_bf.set(di as u64)
         }
 
@@ -429,6 +605,7 @@ impl Actor {
             let mut deal_proposals: Vec<(DealID, DealProposal)> = vec![];
             let mut deals_by_epoch: Vec<(ChainEpoch, DealID)> = vec![];
             let mut pending_deal_allocation_ids: Vec<(DealID, AllocationID)> = vec![];
+            let mut deal_extensions: Vec<(DealID, DealExtensions)> = vec![];
 
             // All storage dealProposals will be added in an atomic transaction; this operation will be unrolled if any of them fails.
             // This should only fail on programmer error because all expected invalid conditions should be filtered in the first set of checks.
@@ -448,6 +625,13 @@ impl Actor {
                     pending_deal_allocation_ids.push((deal_id, *alloc_id));
                 }
 
+                // Store any extensions, which are removed along with the proposal.
+                if let Some(extensions) = &valid_deal.extensions {
+                    if extensions != &DealExtensions::default() {
+                        deal_extensions.push((deal_id, extensions.clone()));
+                    }
+                }
+
                 // Randomize the first epoch for when the deal will be processed so an attacker isn't able to
                 // schedule too many deals for the same tick.
                 deals_by_epoch.push((
@@ -465,6 +649,7 @@ impl Actor {
             st.put_pending_deals(rt.store(), &pending_deals)?;
             st.put_deal_proposals(rt.store(), &deal_proposals)?;
             st.put_pending_deal_allocation_ids(rt.store(), &pending_deal_allocation_ids)?;
+            st.put_deal_extensions(rt.store(), &deal_extensions)?;
             st.put_deals_by_epoch(rt.store(), &deals_by_epoch)?;
             Ok(())
         })?;
@@ -539,6 +724,60 @@ impl Actor {
         Ok(VerifyDealsForActivationReturn { unsealed_cids })
     }
 
+    /// Verifies client-signed piece manifests for sectors being committed by the calling miner,
+    /// returning the unsealed CID computed from each sector's pieces.
+    /// This allows data to be onboarded at zero price without publishing a deal: nothing is
+    /// recorded in market state and no funds are held in escrow.
+    fn verify_deals_for_activation_direct(
+        rt: &impl Runtime,
+        params: VerifyDealsForActivationDirectParams,
+    ) -> Result<VerifyDealsForActivationReturn, ActorError> {
+        rt.validate_immediate_caller_type(std::iter::once(&Type::Miner))?;
+        let miner_addr = rt.message().caller();
+        let curr_epoch = rt.curr_epoch();
+
+        let mut unsealed_cids = Vec::with_capacity(params.sectors.len());
+        for sector in params.sectors {
+            let sector_size = sector
+                .sector_type
+                .sector_size()
+                .map_err(|e| actor_error!(illegal_argument, "sector size unknown: {}", e))?;
+
+            let mut pieces = vec![];
+            for signed in &sector.manifests {
+                validate_piece_manifest(rt, signed, &miner_addr, curr_epoch).with_context(
+                    || format!("invalid piece manifest for sector {}", sector.sector_number),
+                )?;
+                pieces.extend_from_slice(&signed.manifest.pieces);
+            }
+
+            let data_size: u64 = pieces.iter().map(|p| p.size.0).sum();
+            if data_size > sector_size as u64 {
+                return Err(actor_error!(
+                    illegal_argument,
+                    "pieces too large to fit in sector {} {} > {}",
+                    sector.sector_number,
+                    data_size,
+                    sector_size as u64
+                ));
+            }
+
+            let commd = if pieces.is_empty() {
+                None
+            } else {
+                Some(rt.compute_unsealed_sector_cid(sector.sector_type, &pieces).map_err(|e| {
+                    e.downcast_default(
+                        ExitCode::USR_ILLEGAL_ARGUMENT,
+                        "failed to compute unsealed sector CID",
+                    )
+                })?)
+            };
+            unsealed_cids.push(commd);
+        }
+
+        Ok(VerifyDealsForActivationReturn { unsealed_cids })
+    }
+
     /// Activate a set of deals grouped by sector, returning the size and
     /// extra info about verified deals.
     /// Sectors' deals are activated in parameter-defined order.
@@ -553,11 +792,13 @@ impl Actor {
         rt.validate_immediate_caller_type(std::iter::once(&Type::Miner))?;
         let miner_addr = rt.message().caller();
         let curr_epoch = rt.curr_epoch();
+        let mut notifications: Vec<(Address, DealID, DealOutcome)> = vec![];
 
         let (activations, batch_ret) = rt.transaction(|st: &mut State, rt| {
             let proposals = st.load_proposals(rt.store())?;
             let states = st.load_deal_states(rt.store())?;
             let pending_deals = st.load_pending_deals(rt.store())?;
+            let deal_extensions = st.load_deal_extensions(rt.store())?;
             let mut pending_deal_allocation_ids =
                 st.load_pending_deal_allocation_ids(rt.store())?;
 
@@ -652,6 +893,9 @@ impl Actor {
                         proposal.client.id().unwrap(),
                         proposal.provider.id().unwrap(),
                     )?;
+                    if let Some(notify) = find_deal_notify(&deal_extensions, *deal_id)? {
+                        notifications.push((notify, *deal_id, DealOutcome::Activated));
+                    }
                 }
 
                 batch_gen.add_success();
@@ -663,6 +907,7 @@ impl Actor {
             Ok((activations, batch_gen.gen()))
         })?;
 
+        notify_deal_outcomes(rt, notifications)?;
         Ok(BatchActivateDealsResult { activations, activation_results: batch_ret })
     }
 
@@ -676,11 +921,13 @@ impl Actor {
         rt.validate_immediate_caller_type(std::iter::once(&Type::Miner))?;
         let miner_addr = rt.message().caller();
         let curr_epoch = rt.curr_epoch();
+        let mut notifications: Vec<(Address, DealID, DealOutcome)> = vec![];
 
         let sectors_ret = rt.transaction(|st: &mut State, rt| {
             let proposals = st.load_proposals(rt.store())?;
             let states = st.load_deal_states(rt.store())?;
             let pending_deals = st.load_pending_deals(rt.store())?;
+            let deal_extensions = st.load_deal_extensions(rt.store())?;
             let mut pending_deal_allocation_ids =
                 st.load_pending_deal_allocation_ids(rt.store())?;
 
@@ -752,6 +999,9 @@ impl Actor {
                         proposal.client.id().unwrap(),
                         proposal.provider.id().unwrap(),
                     )?;
+                    if let Some(notify) = find_deal_notify(&deal_extensions, deal_id)? {
+                        notifications.push((notify, deal_id, DealOutcome::Activated));
+                    }
 
                     // Remove any verified allocation ID for the pending deal.
                     pending_deal_allocation_ids.delete(&deal_id)?;
@@ -781,6 +1031,7 @@ impl Actor {
             Ok(sectors_ret)
         })?;
 
+        notify_deal_outcomes(rt, notifications)?;
         Ok(ext::miner::SectorContentChangedReturn { sectors: sectors_ret })
     }
 
@@ -798,16 +1049,28 @@ impl Actor {
             // Load the deal proposals and deal states once
             let proposals = st.load_proposals(rt.store())?;
             let states = st.load_deal_states(rt.store())?;
+            let deal_extensions = st.load_deal_extensions(rt.store())?;
+            let mut notifications: Vec<(Address, DealID, DealOutcome)> = vec![];
+
+            // Deals are settled here only up to a budget, bounding the cost of termination
+            // regardless of the number of deals. The deals of any further sectors are left in
+            // place, with the sectors recorded as terminated, and are settled as terminated
+            // on their next settlement.
+            let provider = miner_addr.id().unwrap();
+            let (settle_now, deferred) = st.split_terminated_sectors(
+                rt.store(),
+                provider,
+                params.sectors.iter(),
+                rt.policy().market_max_synchronous_deal_terminations,
+            )?;
+            st.put_terminated_sectors(rt.store(), provider, &deferred, params.epoch)?;
 
             // The sector deals mapping is removed all at once.
             // Note there may be some deal states that are not removed here,
             // despite deletion of this mapping, e.g. for expired but not-yet-settled deals.
             // The sector->deal mapping is no longer needed (the deal state has sector number too).
-            let all_deal_ids = st.pop_sector_deal_ids(
-                rt.store(),
-                miner_addr.id().unwrap(),
-                params.sectors.iter(),
-            )?;
+            let all_deal_ids =
+                st.pop_sector_deal_ids(rt.store(), provider, settle_now.into_iter())?;
 
             let mut total_slashed = TokenAmount::zero();
             for id in all_deal_ids {
@@ -871,8 +1134,14 @@ impl Actor {
                     deal.client.id().unwrap(),
                     deal.provider.id().unwrap(),
                 )?;
+                if let Some(notify) = find_deal_notify(&deal_extensions, id)? {
+                    notifications.push((notify, id, DealOutcome::Terminated));
+                }
             }
 
+            // Terminations may be processed in cron, so their notifications are delivered by
+            // the market's own cron, which bounds the number delivered each epoch.
+            st.queue_deal_notifications(rt.store(), &notifications)?;
             Ok(total_slashed)
         })?;
 
@@ -893,15 +1162,19 @@ impl Actor {
         let mut amount_slashed = TokenAmount::zero();
         let curr_epoch = rt.curr_epoch();
 
-        rt.transaction(|st: &mut State, rt| {
+        let (budget_overrun, notifications) = rt.transaction(|st: &mut State, rt| {
             let last_cron = st.last_cron;
+            let deal_extensions = st.load_deal_extensions(rt.store())?;
+            let mut notifications: Vec<(Address, DealID, DealOutcome)> = vec![];
             let mut provider_deals_to_remove =
                 BTreeMap::<ActorID, BTreeMap<SectorNumber, Vec<DealID>>>::new();
             let mut new_updates_scheduled: BTreeMap<ChainEpoch, Vec<DealID>> = BTreeMap::new();
             let mut epochs_completed: Vec<ChainEpoch> = vec![];
 
+            let mut processed: u64 = 0;
             for i in (last_cron + 1)..=rt.curr_epoch() {
                 let deal_ids = st.get_deals_for_epoch(rt.store(), i)?;
+                processed += deal_ids.len() as u64;
 
                 for deal_id in deal_ids {
                     let deal_proposal = match st.find_proposal(rt.store(), deal_id)? {
@@ -952,6 +1225,7 @@ impl Actor {
                     // https://github.com/filecoin-project/builtin-actors/issues/1389
                     // handling of legacy deals is still done in cron. we handle such deals here and continue to
                     // reschedule them. eventually, all legacy deals will expire and the below code can be removed.
+                    st.apply_deferred_termination(rt.store(), &deal_proposal, &mut state)?;
                     let (slash_amount, _payment_amount, completed, remove_deal) = st
                         .process_deal_update(
                             rt.store(),
@@ -984,6 +1258,9 @@ impl Actor {
                                 deal_proposal.client.id().unwrap(),
                                 deal_proposal.provider.id().unwrap(),
                             )?;
+                            if let Some(notify) = find_deal_notify(&deal_extensions, deal_id)? {
+                                notifications.push((notify, deal_id, DealOutcome::Terminated));
+                            }
                         }
                     } else {
                         if !slash_amount.is_zero() {
@@ -1016,6 +1293,9 @@ impl Actor {
                             deal_proposal.client.id().unwrap(),
                             deal_proposal.provider.id().unwrap(),
                         )?;
+                        if let Some(notify) = find_deal_notify(&deal_extensions, deal_id)? {
+                            notifications.push((notify, deal_id, DealOutcome::Completed));
+                        }
                     }
                 }
                 epochs_completed.push(i);
@@ -1026,9 +1306,26 @@ impl Actor {
             st.remove_deals_by_epoch(rt.store(), &epochs_completed)?;
             st.put_batch_deals_by_epoch(rt.store(), &new_updates_scheduled)?;
             st.last_cron = rt.curr_epoch();
-            Ok(())
+
+            // Notifications are queued behind any left from earlier epochs, and only a bounded
+            // number delivered.
+            st.queue_deal_notifications(rt.store(), &notifications)?;
+            let notifications =
+                st.take_deal_notifications(rt.store(), DEAL_NOTIFICATIONS_MAX_PER_CRON_TICK)?;
+
+            let budget = rt.policy().market_cron_entries_budget;
+            let overrun =
+                st.cron_stats.record(curr_epoch, processed, budget).then_some((processed, budget));
+            Ok((overrun, notifications))
         })?;
 
+        if let Some((processed, budget)) = budget_overrun {
+            // The report is best-effort, and must not abort cron.
+            if let Err(e) = emit::cron_budget_exceeded(rt, processed, budget) {
+                warn!("failed to report cron budget overrun: {}", e);
+            }
+        }
+
         if !amount_slashed.is_zero() {
             extract_send_result(rt.send_simple(
                 &BURNT_FUNDS_ACTOR_ADDR,
@@ -1037,6 +1334,7 @@ impl Actor {
                 amount_slashed,
             ))?;
         }
+        notify_deal_outcomes(rt, notifications)?;
         Ok(())
     }
 
@@ -1082,6 +1380,27 @@ impl Actor {
         Ok(GetDealLabelReturn { label: found.label })
     }
 
+    /// Returns the metadata CID of a deal, if it was published with one.
+    fn get_deal_metadata(
+        rt: &impl Runtime,
+        params: GetDealMetadataParams,
+    ) -> Result<GetDealMetadataReturn, ActorError> {
+        rt.validate_immediate_caller_accept_any()?;
+        let st: State = rt.state()?;
+        // Checks the deal exists.
+        st.get_proposal(rt.store(), params.id)?;
+        let extensions = st.get_deal_extensions(rt.store(), params.id)?;
+        Ok(GetDealMetadataReturn { metadata: extensions.and_then(|e| e.metadata) })
+    }
+
+    /// Returns counts of the scheduled deal operations processed by cron,
+    /// and of the epochs in which they exceeded the policy's budget.
+    fn cron_work_stats(rt: &impl Runtime) -> Result<CronWorkStatsReturn, ActorError> {
+        rt.validate_immediate_caller_accept_any()?;
+        let st: State = rt.state()?;
+        Ok(CronWorkStatsReturn { stats: st.cron_stats })
+    }
+
     /// Returns the start epoch and duration (in epochs) of a deal proposal.
     fn get_deal_term(
         rt: &impl Runtime,
@@ -1145,7 +1464,7 @@ impl Actor {
     ) -> Result<GetDealActivationReturn, ActorError> {
         rt.validate_immediate_caller_accept_any()?;
         let st = rt.state::<State>()?;
-        let found = st.find_deal_state(rt.store(), params.id)?;
+        let found = find_deal_state_with_terminations(rt, &st, params.id)?;
         match found {
             Some(state) => {
                 if state.slash_epoch != EPOCH_UNDEFINED {
@@ -1187,7 +1506,7 @@ impl Actor {
     ) -> Result<GetDealSectorReturn, ActorError> {
         rt.validate_immediate_caller_accept_any()?;
         let st = rt.state::<State>()?;
-        let found = st.find_deal_state(rt.store(), params.id)?;
+        let found = find_deal_state_with_terminations(rt, &st, params.id)?;
         match found {
             Some(state) => {
                 // The deal has been activated and not yet finally settled.
@@ -1217,6 +1536,43 @@ impl Actor {
         }
     }
 
+    /// Lists the deals activated in a provider's sectors, a page at a time.
+    /// At most `limit` sectors, and no more than `MAX_PROVIDER_SECTORS_PER_PAGE`, are visited
+    /// per call, and all deals in each visited sector are returned.
+    /// The returned cursor should be passed to a subsequent call to resume the traversal,
+    /// and is absent once all sectors have been visited. The traversal resumes correctly
+    /// even if the sector at the cursor has since had all its deals removed.
+    /// Deals are grouped by sector, but sectors are not returned in numeric order.
+    fn get_deals_for_provider(
+        rt: &impl Runtime,
+        params: GetDealsForProviderParams,
+    ) -> Result<GetDealsForProviderReturn, ActorError> {
+        rt.validate_immediate_caller_accept_any()?;
+        if params.limit == 0 {
+            return Err(actor_error!(illegal_argument, "limit must be positive"));
+        }
+        let provider = rt.resolve_address(&params.provider).ok_or_else(|| {
+            actor_error!(illegal_argument, "failed to resolve address {}", params.provider)
+        })?;
+
+        let limit = usize::try_from(params.limit.min(MAX_PROVIDER_SECTORS_PER_PAGE))
+            .context_code(ExitCode::USR_ILLEGAL_ARGUMENT, "invalid limit")?;
+
+        let st: State = rt.state()?;
+        let provider_sectors = st.load_provider_sectors(rt.store())?;
+        let mut sector_deals = load_provider_sector_deals(rt.store(), &provider_sectors, provider)?;
+        let mut deals = Vec::new();
+        let (_, next_cursor) = sector_deals.for_each_ranged_resuming(
+            params.cursor.as_ref(),
+            Some(limit),
+            |_, sector_deal_ids| {
+                deals.extend_from_slice(sector_deal_ids);
+                Ok(())
+            },
+        )?;
+        Ok(GetDealsForProviderReturn { deals, next_cursor })
+    }
+
     fn settle_deal_payments(
         rt: &impl Runtime,
         params: SettleDealPaymentsParams,
@@ -1228,8 +1584,10 @@ impl Actor {
         let mut settlements: Vec<DealSettlementSummary> = Vec::new();
         // accumulates slashed amounts from timed out deal proposals that weren't activated in time
         let mut total_slashed = TokenAmount::zero();
+        let mut notifications: Vec<(Address, DealID, DealOutcome)> = vec![];
 
         rt.transaction(|st: &mut State, rt| {
+            let deal_extensions = st.load_deal_extensions(rt.store())?;
             let mut new_deal_states: Vec<(DealID, DealState)> = Vec::new();
             let mut provider_deals_to_remove =
                 BTreeMap::<ActorID, BTreeMap<SectorNumber, Vec<DealID>>>::new();
@@ -1292,19 +1650,21 @@ impl Actor {
                     ));
                 }
 
-                let (_, payment_amount, completed, remove_deal) = match st.process_deal_update(
-                    rt.store(),
-                    &deal_state,
-                    &deal_proposal,
-                    &dcid,
-                    curr_epoch,
-                ) {
+                // A deal in a sector terminated without settling its deals is settled as
+                // terminated now.
+                let stored_slash_epoch = deal_state.slash_epoch;
+                st.apply_deferred_termination(rt.store(), &deal_proposal, &mut deal_state)?;
+
+                let (slash_amount, payment_amount, completed, remove_deal) = match st
+                    .process_deal_update(rt.store(), &deal_state, &deal_proposal, &dcid, curr_epoch)
+                {
                     Ok(res) => res,
                     Err(e) => {
                         batch_gen.add_fail(e.exit_code());
                         continue;
                     }
                 };
+                total_slashed += slash_amount;
 
                 if remove_deal {
                     st.remove_completed_deal(rt.store(), deal_id)?;
@@ -1322,8 +1682,14 @@ impl Actor {
                             deal_proposal.client.id().unwrap(),
                             deal_proposal.provider.id().unwrap(),
                         )?;
+                        if let Some(notify) = find_deal_notify(&deal_extensions, deal_id)? {
+                            notifications.push((notify, deal_id, DealOutcome::Terminated));
+                        }
                     }
                 } else {
+                    // A deal yet to start is not settled, and its termination stays recorded
+                    // against the sector.
+                    deal_state.slash_epoch = stored_slash_epoch;
                     deal_state.last_updated_epoch = curr_epoch;
                     new_deal_states.push((deal_id, deal_state));
                 }
@@ -1341,6 +1707,9 @@ impl Actor {
                         deal_proposal.client.id().unwrap(),
                         deal_proposal.provider.id().unwrap(),
                     )?;
+                    if let Some(notify) = find_deal_notify(&deal_extensions, deal_id)? {
+                        notifications.push((notify, deal_id, DealOutcome::Completed));
+                    }
                 }
             }
 
@@ -1358,8 +1727,145 @@ impl Actor {
             ))?;
         }
 
+        notify_deal_outcomes(rt, notifications)?;
         Ok(SettleDealPaymentsReturn { results: batch_gen.gen(), settlements })
     }
+
+    /// Amends the price and/or brings forward the end epoch of an active deal.
+    /// The amendment must be submitted by a worker or control address of the deal's provider,
+    /// and carry the client's signature over the serialized amendment, including the deal's
+    /// amendment nonce.
+    /// The end epoch cannot be extended, since the deal's sector is only committed to
+    /// expire no earlier than the deal's current end epoch.
+    /// The deal is first settled up to the current epoch under its previous terms, so that
+    /// payments from the current epoch onwards accrue at the new price.
+    /// The end epoch of a verified deal cannot be changed, as it is bound to the terms
+    /// of the deal's verified claim.
+    fn amend_deal_terms(
+        rt: &impl Runtime,
+        params: AmendDealTermsParams,
+    ) -> Result<AmendDealTermsReturn, ActorError> {
+        rt.validate_immediate_caller_accept_any()?;
+        let curr_epoch = rt.curr_epoch();
+        let amendment = params.amendment;
+        let deal_id = amendment.deal_id;
+
+        let proposal = rt.state::<State>()?.get_proposal(rt.store(), deal_id)?;
+        let dcid = deal_cid(rt, &proposal)?;
+        if dcid != amendment.proposal_cid {
+            return Err(actor_error!(
+                illegal_argument,
+                "amendment proposal {} does not match current proposal {} for deal {}",
+                amendment.proposal_cid,
+                dcid,
+                deal_id
+            ));
+        }
+
+        // All proposals are stored with normalised addresses.
+        validate_caller_controls_provider(rt, proposal.provider.id().unwrap())?;
+
+        // Authenticate the client before loading state for update,
+        // since the call to `AuthenticateMessage` could recurse.
+        let amendment_bytes = serialize(&amendment, "deal amendment")?;
+        if !authenticate_message(
+            rt,
+            &proposal.client,
+            &params.client_signature,
+            amendment_bytes.to_vec(),
+        )
+        .context("amendment authentication failed")?
+        {
+            return Err(actor_error!(illegal_argument, "amendment authentication failed"));
+        }
+
+        if proposal.verified_deal && amendment.end_epoch != proposal.end_epoch {
+            return Err(actor_error!(
+                illegal_argument,
+                "cannot change end epoch of verified deal {}",
+                deal_id
+            ));
+        }
+        if amendment.end_epoch > proposal.end_epoch {
+            return Err(actor_error!(
+                illegal_argument,
+                "amended end epoch {} cannot be after current end epoch {} of deal {}",
+                amendment.end_epoch,
+                proposal.end_epoch,
+                deal_id
+            ));
+        }
+        if amendment.end_epoch <= curr_epoch || amendment.end_epoch <= proposal.start_epoch {
+            return Err(actor_error!(
+                illegal_argument,
+                "amended end epoch {} must be after current epoch {} and deal start {}",
+                amendment.end_epoch,
+                curr_epoch,
+                proposal.start_epoch
+            ));
+        }
+        let amended = DealProposal {
+            storage_price_per_epoch: amendment.storage_price_per_epoch,
+            end_epoch: amendment.end_epoch,
+            ..proposal.clone()
+        };
+        let (min_dur, max_dur) = deal_duration_bounds(rt.policy(), amended.piece_size);
+        if amended.duration() < min_dur || amended.duration() > max_dur {
+            return Err(actor_error!(illegal_argument, "amended deal duration out of bounds"));
+        }
+        let (min_price, max_price) =
+            deal_price_per_epoch_bounds(rt.policy(), amended.piece_size, amended.duration());
+        if amended.storage_price_per_epoch < min_price
+            || &amended.storage_price_per_epoch > max_price
+        {
+            return Err(actor_error!(illegal_argument, "amended storage price out of bounds"));
+        }
+
+        let payment = rt.transaction(|st: &mut State, rt| {
+            let mut deal_state = st.find_deal_state(rt.store(), deal_id)?.ok_or_else(|| {
+                ActorError::unchecked(
+                    EX_DEAL_NOT_ACTIVATED,
+                    format!("deal {} not yet activated", deal_id),
+                )
+            })?;
+            st.apply_deferred_termination(rt.store(), &proposal, &mut deal_state)?;
+            if deal_state.slash_epoch != EPOCH_UNDEFINED {
+                return Err(actor_error!(
+                    illegal_argument,
+                    "deal {} is marked for termination and cannot be amended",
+                    deal_id
+                ));
+            }
+            if curr_epoch >= proposal.end_epoch {
+                return Err(ActorError::unchecked(
+                    EX_DEAL_EXPIRED,
+                    format!("deal {} has ended and cannot be amended", deal_id),
+                ));
+            }
+
+            st.use_deal_amendment_nonce(rt.store(), deal_id, amendment.nonce)?;
+
+            // Settle payments accrued under the previous terms.
+            let (_, payment, _, _) =
+                st.process_deal_update(rt.store(), &deal_state, &proposal, &dcid, curr_epoch)?;
+            deal_state.last_updated_epoch = curr_epoch;
+            st.put_deal_states(rt.store(), &[(deal_id, deal_state)])?;
+
+            st.amend_deal_proposal(rt.store(), deal_id, &proposal, &amended, curr_epoch)?;
+            Ok(payment)
+        })?;
+
+        emit::deal_amended(
+            rt,
+            deal_id,
+            amended.client.id().unwrap(),
+            amended.provider.id().unwrap(),
+            &amended.storage_price_per_epoch,
+            amended.end_epoch,
+        )?;
+
+        Ok(AmendDealTermsReturn { payment })
+    }
 }
 
 fn get_proposals<BS: Blockstore>(
@@ -1616,10 +2122,11 @@ fn validate_deal_can_activate(
 fn validate_deal(
     rt: &impl Runtime,
     deal: &ClientDealProposal,
+    extensions: Option<&DealExtensions>,
     network_raw_power: &StoragePower,
     baseline_power: &StoragePower,
 ) -> Result<(), ActorError> {
-    deal_proposal_is_internally_valid(rt, deal)?;
+    deal_proposal_is_internally_valid(rt, deal, extensions)?;
 
     let proposal = &deal.proposal;
 
@@ -1632,6 +2139,18 @@ fn validate_deal(
         ));
     }
 
+    if let Some(metadata) = extensions.and_then(|e| e.metadata.as_ref()) {
+        let size = metadata.encoded_len();
+        if size > detail::DEAL_MAX_METADATA_CID_SIZE {
+            return Err(actor_error!(
+                illegal_argument,
+                "deal metadata CID can be at most {} bytes, is {}",
+                detail::DEAL_MAX_METADATA_CID_SIZE,
+                size
+            ));
+        }
+    }
+
     proposal
         .piece_size
         .validate()
@@ -1651,13 +2170,13 @@ fn validate_deal(
         return Err(actor_error!(illegal_argument, "Deal start epoch has already elapsed."));
     };
 
-    let (min_dur, max_dur) = deal_duration_bounds(proposal.piece_size);
+    let (min_dur, max_dur) = deal_duration_bounds(rt.policy(), proposal.piece_size);
     if proposal.duration() < min_dur || proposal.duration() > max_dur {
         return Err(actor_error!(illegal_argument, "Deal duration out of bounds."));
     };
 
     let (min_price, max_price) =
-        deal_price_per_epoch_bounds(proposal.piece_size, proposal.duration());
+        deal_price_per_epoch_bounds(rt.policy(), proposal.piece_size, proposal.duration());
     if proposal.storage_price_per_epoch < min_price || &proposal.storage_price_per_epoch > max_price
     {
         return Err(actor_error!(illegal_argument, "Storage price out of bounds."));
@@ -1677,7 +2196,7 @@ fn validate_deal(
     };
 
     let (min_client_collateral, max_client_collateral) =
-        deal_client_collateral_bounds(proposal.piece_size, proposal.duration());
+        deal_client_collateral_bounds(rt.policy(), proposal.piece_size, proposal.duration());
     if proposal.client_collateral < min_client_collateral
         || proposal.client_collateral > max_client_collateral
     {
@@ -1690,29 +2209,168 @@ fn validate_deal(
 fn deal_proposal_is_internally_valid(
     rt: &impl Runtime,
     proposal: &ClientDealProposal,
+    extensions: Option<&DealExtensions>,
 ) -> Result<(), ActorError> {
-    let signature_bytes = proposal.client_signature.bytes.clone();
-    // Generate unsigned bytes
-    let proposal_bytes = serialize(&proposal.proposal, "deal proposal")?;
+    // Generate unsigned bytes, of the proposal alone or together with its extensions
+    let signed_bytes = match extensions {
+        None => serialize(&proposal.proposal, "deal proposal")?.to_vec(),
+        Some(extensions) => [
+            SIGNATURE_DOMAIN_SEPARATION_DEAL_EXTENSIONS,
+            serialize(&(&proposal.proposal, extensions), "deal proposal with extensions")?.bytes(),
+        ]
+        .concat(),
+    };
 
-    if !extract_send_result(rt.send(
+    if !authenticate_message(
+        rt,
         &proposal.proposal.client,
+        &proposal.client_signature,
+        signed_bytes,
+    )
+    .context("proposal authentication failed")?
+    {
+        Err(actor_error!(illegal_argument, "proposal authentication failed"))
+    } else {
+        Ok(())
+    }
+}
+
+/// Validates that a client-signed piece manifest authorizes the provider to commit its pieces now.
+fn validate_piece_manifest(
+    rt: &impl Runtime,
+    signed: &ClientPieceManifest,
+    miner_addr: &Address,
+    curr_epoch: ChainEpoch,
+) -> Result<(), ActorError> {
+    let manifest = &signed.manifest;
+    if manifest.pieces.is_empty() {
+        return Err(actor_error!(illegal_argument, "manifest has no pieces"));
+    }
+    for piece in &manifest.pieces {
+        piece.size.validate().map_err(|e| {
+            actor_error!(illegal_argument, "invalid piece size {}: {}", piece.size.0, e)
+        })?;
+        if !is_piece_cid(&piece.cid) {
+            return Err(actor_error!(illegal_argument, "invalid piece CID {}", piece.cid));
+        }
+    }
+    if curr_epoch > manifest.expiration {
+        return Err(actor_error!(
+            illegal_argument,
+            "manifest expired at {}, current epoch {}",
+            manifest.expiration,
+            curr_epoch
+        ));
+    }
+
+    let provider = rt.resolve_address(&manifest.provider).ok_or_else(|| {
+        actor_error!(illegal_argument, "failed to resolve provider address {}", manifest.provider)
+    })?;
+    if Address::new_id(provider) != *miner_addr {
+        return Err(actor_error!(
+            forbidden,
+            "manifest is for provider {}, not caller {}",
+            manifest.provider,
+            miner_addr
+        ));
+    }
+
+    let manifest_bytes = serialize(manifest, "piece manifest")?;
+    if !authenticate_message(
+        rt,
+        &manifest.client,
+        &signed.client_signature,
+        manifest_bytes.to_vec(),
+    )
+    .context("manifest authentication failed")?
+    {
+        return Err(actor_error!(illegal_argument, "manifest authentication failed"));
+    }
+    Ok(())
+}
+
+/// Loads the state of an activated deal, marked as terminated if its sector was terminated
+/// without settling its deals.
+fn find_deal_state_with_terminations(
+    rt: &impl Runtime,
+    st: &State,
+    deal_id: DealID,
+) -> Result<Option<DealState>, ActorError> {
+    let mut state = match st.find_deal_state(rt.store(), deal_id)? {
+        Some(state) => state,
+        None => return Ok(None),
+    };
+    let proposal = st.get_proposal(rt.store(), deal_id)?;
+    st.apply_deferred_termination(rt.store(), &proposal, &mut state)?;
+    Ok(Some(state))
+}
+
+/// Notifies deals' notification actors of lifecycle outcomes.
+/// Unlike the notification of a published deal, these are sent after the outcome has taken
+/// effect, so a notification failure is logged and ignored rather than reverting the outcome.
+fn notify_deal_outcomes(
+    rt: &impl Runtime,
+    notifications: Vec<(Address, DealID, DealOutcome)>,
+) -> Result<(), ActorError> {
+    for (notify, deal_id, outcome) in notifications {
+        let res = extract_send_result(rt.send(
+            &notify,
+            MARKET_NOTIFY_DEAL_OUTCOME_METHOD,
+            IpldBlock::serialize_cbor(&MarketNotifyDealOutcomeParams { deal_id, outcome })?,
+            TokenAmount::zero(),
+            Some(DEAL_NOTIFICATION_GAS_LIMIT),
+            SendFlags::default(),
+        ));
+        if let Err(e) = res {
+            warn!("failed to notify {} of deal {} outcome {:?}: {}", notify, deal_id, outcome, e);
+        }
+    }
+    Ok(())
+}
+
+/// Asks the signer's actor to authenticate a signature over a message.
+fn authenticate_message(
+    rt: &impl Runtime,
+    signer: &Address,
+    signature: &Signature,
+    message: Vec<u8>,
+) -> Result<bool, ActorError> {
+    extract_send_result(rt.send(
+        signer,
         ext::account::AUTHENTICATE_MESSAGE_METHOD,
         IpldBlock::serialize_cbor(&ext::account::AuthenticateMessageParams {
-            signature: signature_bytes,
-            message: proposal_bytes.to_vec(),
+            signature: signature.bytes.clone(),
+            message,
         })?,
         TokenAmount::zero(),
         None,
         SendFlags::READ_ONLY,
     ))
     .and_then(deserialize_block)
-    .context("proposal authentication failed")?
-    {
-        Err(actor_error!(illegal_argument, "proposal authentication failed"))
-    } else {
-        Ok(())
+}
+
+/// Checks that the immediate caller is the worker or a control address of a provider.
+fn validate_caller_controls_provider(
+    rt: &impl Runtime,
+    provider_id: ActorID,
+) -> Result<(), ActorError> {
+    let caller = rt.message().caller();
+    let caller_status: ext::miner::IsControllingAddressReturn =
+        deserialize_block(extract_send_result(rt.send_simple(
+            &Address::new_id(provider_id),
+            ext::miner::IS_CONTROLLING_ADDRESS_EXPORTED,
+            IpldBlock::serialize_cbor(&ext::miner::IsControllingAddressParam { address: caller })?,
+            TokenAmount::zero(),
+        ))?)?;
+    if !caller_status.is_controlling {
+        return Err(actor_error!(
+            forbidden,
+            "caller {} is not worker or control address of provider {}",
+            caller,
+            provider_id
+        ));
     }
+    Ok(())
 }
 
 /// Compute a deal CID using the runtime.
@@ -1814,11 +2472,20 @@ impl ActorCode for Actor {
         "StorageMarket"
     }
 
+    fn on_upgrade<RT>(rt: &RT, params: OnUpgradeParams) -> Result<(), ActorError>
+    where
+        RT: Runtime,
+        RT::Blockstore: Clone,
+    {
+        migrate_state(rt, &params.old_state, |rt, legacy: LegacyState| legacy.migrate(rt.store()))
+    }
+
     actor_dispatch! {
         Constructor => constructor,
         AddBalance|AddBalanceExported => add_balance,
         WithdrawBalance|WithdrawBalanceExported => withdraw_balance,
         PublishStorageDeals|PublishStorageDealsExported => publish_storage_deals,
+        PublishStorageDealsWithExtensionsExported => publish_storage_deals_with_extensions,
         VerifyDealsForActivation => verify_deals_for_activation,
         BatchActivateDeals => batch_activate_deals,
         OnMinerSectorsTerminate => on_miner_sectors_terminate,
@@ -1835,7 +2502,15 @@ impl ActorCode for Actor {
         GetDealVerifiedExported => get_deal_verified,
         GetDealActivationExported => get_deal_activation,
         GetDealSectorExported => get_deal_sector,
+        GetDealsForProviderExported => get_deals_for_provider,
         SettleDealPaymentsExported => settle_deal_payments,
+        AmendDealTermsExported => amend_deal_terms,
+        AddBalanceForExported => add_balance_for,
+        ReclaimAllowanceExported => reclaim_allowance,
+        VerifyDealsForActivationDirectExported => verify_deals_for_activation_direct,
+        WithdrawBalanceMultiExported => withdraw_balance_multi,
         SectorContentChangedExported => sector_content_changed,
+        GetDealMetadataExported => get_deal_metadata,
+        CronWorkStatsExported => cron_work_stats,
     }
 }
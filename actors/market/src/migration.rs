@@ -0,0 +1,89 @@
+// Copyright 2019-2022 ChainSafe Systems
+// SPDX-License-Identifier: Apache-2.0, MIT
+
+use cid::Cid;
+use fvm_ipld_blockstore::Blockstore;
+use fvm_ipld_encoding::tuple::*;
+use fvm_shared::clock::ChainEpoch;
+use fvm_shared::deal::DealID;
+use fvm_shared::econ::TokenAmount;
+
+use fil_actors_runtime::{ActorError, CronWorkStats};
+
+use crate::balance_table::{AllowanceTable, BalanceTable};
+use crate::{
+    DealAmendmentNoncesMap, DealExtensionsMap, PendingDealNotificationsMap, ProviderSectorsMap,
+    State, DEAL_AMENDMENT_NONCES_CONFIG, DEAL_EXTENSIONS_CONFIG, PENDING_DEAL_NOTIFICATIONS_CONFIG,
+    PROVIDER_SECTORS_CONFIG,
+};
+
+/// Market state as stored before escrow deposits on behalf of other accounts, deferred settlement
+/// of terminated sectors, cron work statistics, deal extensions and deal amendments were
+/// introduced.
+#[derive(Serialize_tuple, Deserialize_tuple, Debug, Clone)]
+pub struct LegacyState {
+    pub proposals: Cid,
+    pub states: Cid,
+    pub pending_proposals: Cid,
+    pub escrow_table: Cid,
+    pub locked_table: Cid,
+    pub next_id: DealID,
+    pub deal_ops_by_epoch: Cid,
+    pub last_cron: ChainEpoch,
+    pub total_client_locked_collateral: TokenAmount,
+    pub total_provider_locked_collateral: TokenAmount,
+    pub total_client_storage_fee: TokenAmount,
+    pub pending_deal_allocation_ids: Cid,
+    pub provider_sectors: Cid,
+}
+
+impl LegacyState {
+    /// Migrates to the current state schema, with no allowances, funded balances, deferred
+    /// sector terminations, deal extensions, pending notifications or deal amendments, and zero
+    /// cron work statistics.
+    /// The deal ops queue is stored with the same layout, so is carried over as is.
+    pub fn migrate<BS: Blockstore>(self, store: &BS) -> Result<State, ActorError> {
+        Ok(State {
+            proposals: self.proposals,
+            states: self.states,
+            pending_proposals: self.pending_proposals,
+            escrow_table: self.escrow_table,
+            locked_table: self.locked_table,
+            next_id: self.next_id,
+            deal_ops_by_epoch: self.deal_ops_by_epoch,
+            last_cron: self.last_cron,
+            total_client_locked_collateral: self.total_client_locked_collateral,
+            total_provider_locked_collateral: self.total_provider_locked_collateral,
+            total_client_storage_fee: self.total_client_storage_fee,
+            pending_deal_allocation_ids: self.pending_deal_allocation_ids,
+            provider_sectors: self.provider_sectors,
+            allowance_table: AllowanceTable::new(store).root()?,
+            funded_table: BalanceTable::new(store, "funded table").root()?,
+            terminated_sectors: ProviderSectorsMap::empty(
+                store,
+                PROVIDER_SECTORS_CONFIG,
+                "terminated sectors",
+            )
+            .flush()?,
+            cron_stats: CronWorkStats::default(),
+            deal_extensions: DealExtensionsMap::empty(
+                store,
+                DEAL_EXTENSIONS_CONFIG,
+                "deal extensions",
+            )
+            .flush()?,
+            pending_deal_notifications: PendingDealNotificationsMap::empty(
+                store,
+                PENDING_DEAL_NOTIFICATIONS_CONFIG,
+                "pending deal notifications",
+            )
+            .flush()?,
+            deal_amendment_nonces: DealAmendmentNoncesMap::empty(
+                store,
+                DEAL_AMENDMENT_NONCES_CONFIG,
+                "deal amendment nonces",
+            )
+            .flush()?,
+        })
+    }
+}
@@ -3,37 +3,46 @@
 
 use std::cmp::max;
 
-use fil_actors_runtime::network::EPOCHS_IN_DAY;
 use fil_actors_runtime::runtime::Policy;
 use fvm_shared::bigint::{BigInt, Integer};
 use fvm_shared::clock::ChainEpoch;
 use fvm_shared::econ::TokenAmount;
 use fvm_shared::piece::PaddedPieceSize;
 use fvm_shared::sector::StoragePower;
-use lazy_static::lazy_static;
 use num_traits::Zero;
 
 pub mod detail {
     /// Maximum length of a deal label.
     pub const DEAL_MAX_LABEL_SIZE: usize = 256;
-}
 
-lazy_static! {
-    /// Total (assumed) Filecoin available to the network. This is only used to bound the maximum
-    /// deal collateral and price.
-    pub static ref TOTAL_FILECOIN: TokenAmount = TokenAmount::from_whole(2_000_000_000);
+    /// Maximum length of the encoding of a deal's metadata CID.
+    pub const DEAL_MAX_METADATA_CID_SIZE: usize = 64;
 }
 
+/// Gas limit for each notification sent to a deal's notification actor, bounding the cost
+/// that a notified actor can impose on deal activation, termination and settlement.
+pub const DEAL_NOTIFICATION_GAS_LIMIT: u64 = 10_000_000;
+
+/// Maximum number of deals whose queued outcome notifications are delivered in one cron tick.
+/// A deal terminates or completes only once, so this bounds the gas spent on notifications in
+/// cron to this number times `DEAL_NOTIFICATION_GAS_LIMIT`. Further notifications wait for
+/// later ticks.
+pub const DEAL_NOTIFICATIONS_MAX_PER_CRON_TICK: usize = 20;
+
 /// Bounds (inclusive) on deal duration.
-pub(super) fn deal_duration_bounds(_size: PaddedPieceSize) -> (ChainEpoch, ChainEpoch) {
-    (180 * EPOCHS_IN_DAY, 1278 * EPOCHS_IN_DAY)
+pub(super) fn deal_duration_bounds(
+    policy: &Policy,
+    _size: PaddedPieceSize,
+) -> (ChainEpoch, ChainEpoch) {
+    (policy.min_deal_duration, policy.max_deal_duration)
 }
 
 pub(super) fn deal_price_per_epoch_bounds(
+    policy: &Policy,
     _size: PaddedPieceSize,
     _duration: ChainEpoch,
-) -> (TokenAmount, &'static TokenAmount) {
-    (TokenAmount::zero(), &TOTAL_FILECOIN)
+) -> (TokenAmount, &TokenAmount) {
+    (TokenAmount::zero(), &policy.max_deal_token_amount)
 }
 
 pub fn deal_provider_collateral_bounds(
@@ -53,14 +62,15 @@ pub fn deal_provider_collateral_bounds(
 
     let num: BigInt = power_share_num * lock_target_num.atto();
     let denom: BigInt = power_share_denom * policy.prov_collateral_percent_supply_denom;
-    (TokenAmount::from_atto(num.div_floor(&denom)), TOTAL_FILECOIN.clone())
+    (TokenAmount::from_atto(num.div_floor(&denom)), policy.max_deal_token_amount.clone())
 }
 
 pub(super) fn deal_client_collateral_bounds(
+    policy: &Policy,
     _: PaddedPieceSize,
     _: ChainEpoch,
 ) -> (TokenAmount, TokenAmount) {
-    (TokenAmount::zero(), TOTAL_FILECOIN.clone()) // PARAM_FINISH
+    (TokenAmount::zero(), policy.max_deal_token_amount.clone()) // PARAM_FINISH
 }
 
 /// Penalty to provider deal collateral if the deadline expires before sector commitment.
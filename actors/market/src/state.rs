@@ -17,16 +17,16 @@ use fvm_shared::{ActorID, HAMT_BIT_WIDTH};
 use num_traits::Zero;
 
 use fil_actors_runtime::{
-    actor_error, ActorContext, ActorError, Array, AsActorError, Config, Map2, Set, SetMultimap,
-    SetMultimapConfig, DEFAULT_HAMT_CONFIG,
+    actor_error, ActorContext, ActorError, ActorState, Array, AsActorError, Config, CronWorkStats,
+    EpochQueue, Map2, Set, SetMultimapConfig, DEFAULT_HAMT_CONFIG,
 };
 
-use crate::balance_table::BalanceTable;
+use crate::balance_table::{AllowanceTable, BalanceTable};
 use crate::ext::verifreg::AllocationID;
 
 use super::policy::*;
 use super::types::*;
-use super::{DealProposal, DealState, EX_DEAL_EXPIRED};
+use super::{DealExtensions, DealProposal, DealState, EX_DEAL_EXPIRED};
 
 pub enum Reason {
     ClientCollateral,
@@ -35,7 +35,7 @@ pub enum Reason {
 }
 
 /// Market actor state
-#[derive(Clone, Default, Serialize_tuple, Deserialize_tuple, Debug)]
+#[derive(Clone, Default, Serialize_tuple, Deserialize_tuple, Debug, ActorState)]
 pub struct State {
     /// Proposals are deals that have been proposed and not yet cleaned up after expiry or termination.
     /// Array<DealID, DealProposal>
@@ -64,7 +64,7 @@ pub struct State {
     pub next_id: DealID,
 
     /// Metadata cached for efficient iteration over deals.
-    /// SetMultimap<Address>
+    /// EpochQueue<DealID>
     pub deal_ops_by_epoch: Cid,
     pub last_cron: ChainEpoch,
 
@@ -86,12 +86,48 @@ pub struct State {
     /// of multiple sectors all belonging to the same provider.
     /// HAMT[ActorID]HAMT[SectorNumber][]DealID
     pub provider_sectors: Cid,
+
+    /// Amounts deposited into escrow on behalf of another account, which the funder
+    /// may reclaim while they remain unlocked in the beneficiary's escrow.
+    /// HAMT[ActorID]HAMT[ActorID]TokenAmount
+    pub allowance_table: Cid,
+
+    /// Funds deposited into each account's escrow on its behalf that remain unspent,
+    /// bounding the total that funders may reclaim from the account.
+    /// An account's own deposits are spent before funds deposited on its behalf.
+    pub funded_table: Cid,
+
+    /// Maps providers to sectors terminated with more deals than could be settled at the time,
+    /// and the epoch of their termination. The deals remain in `provider_sectors`, and are
+    /// treated as terminated at that epoch when next settled.
+    /// The entry for a sector is removed once all its deals have been settled.
+    /// HAMT[ActorID]HAMT[SectorNumber]ChainEpoch
+    pub terminated_sectors: Cid,
+
+    /// Counts of the scheduled deal operations processed by cron.
+    pub cron_stats: CronWorkStats,
+
+    /// Extensions of deals published with them, such as the deal's notification actor.
+    /// An entry is removed along with its deal's proposal.
+    /// HAMT[DealID]DealExtensions
+    pub deal_extensions: Cid,
+
+    /// Deal outcome notifications awaiting delivery by cron, which delivers a bounded number
+    /// each epoch.
+    /// HAMT[DealID][]PendingDealNotification
+    pub pending_deal_notifications: Cid,
+
+    /// Number of amendments applied to each deal. A deal's next amendment must carry this
+    /// count as its nonce, so a signed amendment can be applied at most once.
+    /// An entry is removed along with its deal's proposal.
+    /// HAMT[DealID]u64
+    pub deal_amendment_nonces: Cid,
 }
 
 pub type PendingProposalsSet<BS> = Set<BS, Cid>;
 pub const PENDING_PROPOSALS_CONFIG: Config = DEFAULT_HAMT_CONFIG;
 
-pub type DealOpsByEpoch<BS> = SetMultimap<BS, ChainEpoch, DealID>;
+pub type DealOpsByEpoch<BS> = EpochQueue<BS, DealID>;
 pub const DEAL_OPS_BY_EPOCH_CONFIG: SetMultimapConfig =
     SetMultimapConfig { outer: DEFAULT_HAMT_CONFIG, inner: DEFAULT_HAMT_CONFIG };
 
@@ -106,6 +142,22 @@ pub const PROVIDER_SECTORS_CONFIG: Config =
 pub type SectorDealsMap<BS> = Map2<BS, SectorNumber, Vec<DealID>>;
 pub const SECTOR_DEALS_CONFIG: Config = Config { bit_width: HAMT_BIT_WIDTH, ..DEFAULT_HAMT_CONFIG };
 
+pub type TerminatedSectorsMap<BS> = Map2<BS, SectorNumber, ChainEpoch>;
+pub const TERMINATED_SECTORS_CONFIG: Config =
+    Config { bit_width: HAMT_BIT_WIDTH, ..DEFAULT_HAMT_CONFIG };
+
+pub type DealExtensionsMap<BS> = Map2<BS, DealID, DealExtensions>;
+pub const DEAL_EXTENSIONS_CONFIG: Config =
+    Config { bit_width: HAMT_BIT_WIDTH, ..DEFAULT_HAMT_CONFIG };
+
+pub type PendingDealNotificationsMap<BS> = Map2<BS, DealID, Vec<PendingDealNotification>>;
+pub const PENDING_DEAL_NOTIFICATIONS_CONFIG: Config =
+    Config { bit_width: HAMT_BIT_WIDTH, ..DEFAULT_HAMT_CONFIG };
+
+pub type DealAmendmentNoncesMap<BS> = Map2<BS, DealID, u64>;
+pub const DEAL_AMENDMENT_NONCES_CONFIG: Config =
+    Config { bit_width: HAMT_BIT_WIDTH, ..DEFAULT_HAMT_CONFIG };
+
 impl State {
     pub fn new<BS: Blockstore>(store: &BS) -> Result<Self, ActorError> {
         let empty_proposals_array =
@@ -136,6 +188,24 @@ impl State {
 
         let empty_sector_deals_hamt =
             ProviderSectorsMap::empty(store, PROVIDER_SECTORS_CONFIG, "sector deals").flush()?;
+        let empty_allowance_table = AllowanceTable::new(store).root()?;
+        let empty_terminated_sectors =
+            ProviderSectorsMap::empty(store, PROVIDER_SECTORS_CONFIG, "terminated sectors")
+                .flush()?;
+        let empty_deal_extensions =
+            DealExtensionsMap::empty(store, DEAL_EXTENSIONS_CONFIG, "deal extensions").flush()?;
+        let empty_pending_deal_notifications = PendingDealNotificationsMap::empty(
+            store,
+            PENDING_DEAL_NOTIFICATIONS_CONFIG,
+            "pending deal notifications",
+        )
+        .flush()?;
+        let empty_deal_amendment_nonces = DealAmendmentNoncesMap::empty(
+            store,
+            DEAL_AMENDMENT_NONCES_CONFIG,
+            "deal amendment nonces",
+        )
+        .flush()?;
 
         Ok(Self {
             proposals: empty_proposals_array,
@@ -152,6 +222,13 @@ impl State {
             total_client_storage_fee: TokenAmount::default(),
             pending_deal_allocation_ids: empty_pending_deal_allocation_map,
             provider_sectors: empty_sector_deals_hamt,
+            allowance_table: empty_allowance_table,
+            funded_table: empty_balance_table,
+            terminated_sectors: empty_terminated_sectors,
+            cron_stats: CronWorkStats::default(),
+            deal_extensions: empty_deal_extensions,
+            pending_deal_notifications: empty_pending_deal_notifications,
+            deal_amendment_nonces: empty_deal_amendment_nonces,
         })
     }
 
@@ -276,6 +353,16 @@ impl State {
             .flush()
             .context_code(ExitCode::USR_ILLEGAL_STATE, "failed to flush deal proposals")?;
 
+        let mut extensions = self.load_deal_extensions(store)?;
+        if extensions.delete(&deal_id)?.is_some() {
+            self.deal_extensions = extensions.flush()?;
+        }
+
+        let mut nonces = self.load_deal_amendment_nonces(store)?;
+        if nonces.delete(&deal_id)?.is_some() {
+            self.deal_amendment_nonces = nonces.flush()?;
+        }
+
         Ok(proposal)
     }
 
@@ -304,6 +391,149 @@ impl State {
         Ok(())
     }
 
+    pub fn load_deal_extensions<BS>(&self, store: BS) -> Result<DealExtensionsMap<BS>, ActorError>
+    where
+        BS: Blockstore,
+    {
+        DealExtensionsMap::load(
+            store,
+            &self.deal_extensions,
+            DEAL_EXTENSIONS_CONFIG,
+            "deal extensions",
+        )
+    }
+
+    pub fn put_deal_extensions<BS>(
+        &mut self,
+        store: &BS,
+        new_deal_extensions: &[(DealID, DealExtensions)],
+    ) -> Result<(), ActorError>
+    where
+        BS: Blockstore,
+    {
+        if new_deal_extensions.is_empty() {
+            return Ok(());
+        }
+        let mut extensions = self.load_deal_extensions(store)?;
+        for (deal_id, deal_extensions) in new_deal_extensions {
+            extensions.set(deal_id, deal_extensions.clone())?;
+        }
+        self.deal_extensions = extensions.flush()?;
+        Ok(())
+    }
+
+    pub fn get_deal_extensions<BS>(
+        &self,
+        store: &BS,
+        deal_id: DealID,
+    ) -> Result<Option<DealExtensions>, ActorError>
+    where
+        BS: Blockstore,
+    {
+        Ok(self.load_deal_extensions(store)?.get(&deal_id)?.cloned())
+    }
+
+    pub fn load_deal_amendment_nonces<BS>(
+        &self,
+        store: BS,
+    ) -> Result<DealAmendmentNoncesMap<BS>, ActorError>
+    where
+        BS: Blockstore,
+    {
+        DealAmendmentNoncesMap::load(
+            store,
+            &self.deal_amendment_nonces,
+            DEAL_AMENDMENT_NONCES_CONFIG,
+            "deal amendment nonces",
+        )
+    }
+
+    /// Checks that an amendment's nonce matches the number of amendments already applied
+    /// to the deal, and counts the amendment.
+    pub fn use_deal_amendment_nonce<BS>(
+        &mut self,
+        store: &BS,
+        deal_id: DealID,
+        nonce: u64,
+    ) -> Result<(), ActorError>
+    where
+        BS: Blockstore,
+    {
+        let mut nonces = self.load_deal_amendment_nonces(store)?;
+        let expected = nonces.get(&deal_id)?.copied().unwrap_or_default();
+        if nonce != expected {
+            return Err(actor_error!(
+                illegal_argument,
+                "amendment nonce {} does not match expected nonce {} for deal {}",
+                nonce,
+                expected,
+                deal_id
+            ));
+        }
+        nonces.set(&deal_id, expected + 1)?;
+        self.deal_amendment_nonces = nonces.flush()?;
+        Ok(())
+    }
+
+    /// Queues deal outcome notifications for later delivery by cron.
+    pub fn queue_deal_notifications<BS>(
+        &mut self,
+        store: &BS,
+        notifications: &[(Address, DealID, DealOutcome)],
+    ) -> Result<(), ActorError>
+    where
+        BS: Blockstore,
+    {
+        if notifications.is_empty() {
+            return Ok(());
+        }
+        let mut pending = PendingDealNotificationsMap::load(
+            store,
+            &self.pending_deal_notifications,
+            PENDING_DEAL_NOTIFICATIONS_CONFIG,
+            "pending deal notifications",
+        )?;
+        for (notify, deal_id, outcome) in notifications {
+            let mut deal_pending = pending.get(deal_id)?.cloned().unwrap_or_default();
+            deal_pending.push(PendingDealNotification { notify: *notify, outcome: *outcome });
+            pending.set(deal_id, deal_pending)?;
+        }
+        self.pending_deal_notifications = pending.flush()?;
+        Ok(())
+    }
+
+    /// Removes and returns the queued notifications for up to `max_deals` deals.
+    pub fn take_deal_notifications<BS>(
+        &mut self,
+        store: &BS,
+        max_deals: usize,
+    ) -> Result<Vec<(Address, DealID, DealOutcome)>, ActorError>
+    where
+        BS: Blockstore,
+    {
+        let mut pending = PendingDealNotificationsMap::load(
+            store,
+            &self.pending_deal_notifications,
+            PENDING_DEAL_NOTIFICATIONS_CONFIG,
+            "pending deal notifications",
+        )?;
+        if pending.is_empty() {
+            return Ok(vec![]);
+        }
+        let mut taken = Vec::new();
+        let mut deal_ids = Vec::new();
+        pending.for_each_ranged(None, Some(max_deals), |deal_id, deal_pending| {
+            taken.extend(deal_pending.iter().map(|n| (n.notify, deal_id, n.outcome)));
+            deal_ids.push(deal_id);
+            Ok(())
+        })?;
+        for deal_id in deal_ids {
+            pending.delete(&deal_id)?;
+        }
+        self.pending_deal_notifications = pending.flush()?;
+        Ok(taken)
+    }
+
     pub fn load_pending_deal_allocation_ids<BS>(
         &mut self,
         store: BS,
@@ -385,10 +615,7 @@ impl State {
         Ok(maybe_alloc_id)
     }
 
-    pub fn load_deal_ops<BS>(
-        &self,
-        store: BS,
-    ) -> Result<SetMultimap<BS, ChainEpoch, DealID>, ActorError>
+    pub fn load_deal_ops<BS>(&self, store: BS) -> Result<DealOpsByEpoch<BS>, ActorError>
     where
         BS: Blockstore,
     {
@@ -405,7 +632,7 @@ impl State {
     {
         let mut deals_by_epoch = self.load_deal_ops(store)?;
         new_deals_by_epoch.iter().try_for_each(|(epoch, id)| -> Result<(), ActorError> {
-            deals_by_epoch.put(epoch, *id)?;
+            deals_by_epoch.enqueue(*epoch, *id)?;
             Ok(())
         })?;
 
@@ -423,7 +650,7 @@ impl State {
     {
         let mut deals_by_epoch = self.load_deal_ops(store)?;
         new_deals_by_epoch.iter().try_for_each(|(epoch, deals)| -> Result<(), ActorError> {
-            deals_by_epoch.put_many(epoch, deals)?;
+            deals_by_epoch.enqueue_many(*epoch, deals)?;
             Ok(())
         })?;
 
@@ -439,14 +666,7 @@ impl State {
     where
         BS: Blockstore,
     {
-        let mut deal_ids = Vec::new();
-        let deals_by_epoch = self.load_deal_ops(store)?;
-        deals_by_epoch.for_each_in(&key, |deal_id| {
-            deal_ids.push(deal_id);
-            Ok(())
-        })?;
-
-        Ok(deal_ids)
+        self.load_deal_ops(store)?.get(key)
     }
 
     pub fn remove_deals_by_epoch<BS>(
@@ -459,7 +679,7 @@ impl State {
     {
         let mut deals_by_epoch = self.load_deal_ops(store)?;
         epochs_to_remove.iter().try_for_each(|epoch| -> Result<(), ActorError> {
-            deals_by_epoch.remove_all(epoch)?;
+            deals_by_epoch.remove_epoch(*epoch)?;
             Ok(())
         })?;
 
@@ -496,11 +716,123 @@ impl State {
 
         let min_balance = locked_table.get(addr)?;
         let ex = escrow_table.subtract_with_minimum(addr, amount, &min_balance)?;
+        if ex.is_positive() {
+            self.spend_funded_balance(store, addr, &escrow_table.get(addr)?)?;
+        }
+
+        self.escrow_table = escrow_table.root()?;
+        Ok(ex)
+    }
+
+    /// Withdraws exactly an amount from an escrow balance, failing if the balance
+    /// not locked is insufficient.
+    pub fn withdraw_exact_from_escrow_table<BS>(
+        &mut self,
+        store: &BS,
+        addr: &Address,
+        amount: &TokenAmount,
+    ) -> Result<(), ActorError>
+    where
+        BS: Blockstore,
+    {
+        let mut escrow_table = BalanceTable::from_root(store, &self.escrow_table, "escrow table")?;
+        let locked_table = BalanceTable::from_root(store, &self.locked_table, "locked table")?;
+
+        let available = escrow_table.get(addr)? - locked_table.get(addr)?;
+        if &available < amount {
+            return Err(actor_error!(
+                insufficient_funds,
+                "cannot withdraw {} from {} with {} available",
+                amount,
+                addr,
+                available
+            ));
+        }
+        escrow_table.must_subtract(addr, amount)?;
+        self.spend_funded_balance(store, addr, &escrow_table.get(addr)?)?;
 
         self.escrow_table = escrow_table.root()?;
+        Ok(())
+    }
+
+    /// Deposits an amount into the escrow of a beneficiary on behalf of a funder,
+    /// adding it to the funder's allowance for that beneficiary.
+    pub fn add_balance_for<BS>(
+        &mut self,
+        store: &BS,
+        funder: ActorID,
+        beneficiary: &Address,
+        amount: &TokenAmount,
+    ) -> Result<(), ActorError>
+    where
+        BS: Blockstore,
+    {
+        let beneficiary_id = beneficiary.id().map_err(|_| {
+            actor_error!(illegal_argument, "beneficiary {} is not an ID address", beneficiary)
+        })?;
+        self.add_balance_to_escrow_table(store, beneficiary, amount)?;
+
+        let mut funded = BalanceTable::from_root(store, &self.funded_table, "funded table")?;
+        funded.add(beneficiary, amount)?;
+        self.funded_table = funded.root()?;
+
+        let mut allowances = AllowanceTable::from_root(store, &self.allowance_table)?;
+        allowances.add(funder, beneficiary_id, amount)?;
+        self.allowance_table = allowances.root()?;
+        Ok(())
+    }
+
+    /// Withdraws up to the specified amount of a funder's allowance from the unlocked
+    /// escrow balance of the beneficiary, limited to the funds deposited on the beneficiary's
+    /// behalf that remain unspent.
+    /// Returns the amount withdrawn, which is deducted from the allowance.
+    pub fn reclaim_allowance<BS>(
+        &mut self,
+        store: &BS,
+        funder: ActorID,
+        beneficiary: &Address,
+        amount: &TokenAmount,
+    ) -> Result<TokenAmount, ActorError>
+    where
+        BS: Blockstore,
+    {
+        let beneficiary_id = beneficiary.id().map_err(|_| {
+            actor_error!(illegal_argument, "beneficiary {} is not an ID address", beneficiary)
+        })?;
+        let mut allowances = AllowanceTable::from_root(store, &self.allowance_table)?;
+        let allowance = allowances.get(funder, beneficiary_id)?;
+        let funded =
+            BalanceTable::from_root(store, &self.funded_table, "funded table")?.get(beneficiary)?;
+        let requested = min(min(&allowance, &funded), amount).clone();
+
+        let ex = self.withdraw_balance_from_escrow_table(store, beneficiary, &requested)?;
+        if ex.is_positive() {
+            allowances.add(funder, beneficiary_id, &-ex.clone())?;
+            self.allowance_table = allowances.root()?;
+        }
         Ok(ex)
     }
 
+    // Caps an account's unspent funded balance at its escrow balance after funds leave the
+    // escrow, so that its own deposits are spent first.
+    fn spend_funded_balance<BS>(
+        &mut self,
+        store: &BS,
+        addr: &Address,
+        escrow_balance: &TokenAmount,
+    ) -> Result<(), ActorError>
+    where
+        BS: Blockstore,
+    {
+        let mut funded = BalanceTable::from_root(store, &self.funded_table, "funded table")?;
+        let prev = funded.get(addr)?;
+        if &prev > escrow_balance {
+            funded.must_subtract(addr, &(&prev - escrow_balance))?;
+            self.funded_table = funded.root()?;
+        }
+        Ok(())
+    }
+
     pub fn load_pending_deals<BS>(&self, store: BS) -> Result<PendingProposalsSet<BS>, ActorError>
     where
         BS: Blockstore,
@@ -641,6 +973,7 @@ impl State {
         provider_sector_deal_ids: &BTreeMap<ActorID, BTreeMap<SectorNumber, Vec<DealID>>>,
     ) -> Result<(), ActorError> {
         let mut provider_sectors = self.load_provider_sectors(store)?;
+        let mut emptied_sectors = BTreeMap::<ActorID, Vec<SectorNumber>>::new();
         for (provider, sector_deal_ids) in provider_sector_deal_ids {
             let mut flush = false;
             let mut sector_deals = load_provider_sector_deals(store, &provider_sectors, *provider)?;
@@ -662,6 +995,7 @@ impl State {
                     flush = true;
 
                     if new_deals.is_empty() {
+                        emptied_sectors.entry(*provider).or_default().push(*sector_number);
                         sector_deals.delete(sector_number).with_context_code(
                             ExitCode::USR_ILLEGAL_STATE,
                             || {
@@ -689,9 +1023,168 @@ impl State {
             }
         }
         self.save_provider_sectors(&mut provider_sectors)?;
+        // A terminated sector is forgotten once all its deals have been settled.
+        self.remove_terminated_sectors(store, &emptied_sectors)?;
+        Ok(())
+    }
+
+    // Splits a provider's terminated sectors into those whose deals are to be settled now,
+    // up to a total of `max_deals` deals, and those whose deals are left for later settlement.
+    // Sectors without deals are omitted, and a sector already left for later settlement stays so.
+    pub fn split_terminated_sectors(
+        &self,
+        store: &impl Blockstore,
+        provider: ActorID,
+        sector_numbers: impl Iterator<Item = SectorNumber>,
+        max_deals: u64,
+    ) -> Result<(Vec<SectorNumber>, Vec<SectorNumber>), ActorError> {
+        let provider_sectors = self.load_provider_sectors(store)?;
+        let sector_deals = load_provider_sector_deals(store, &provider_sectors, provider)?;
+        let provider_terminated = self.load_terminated_sectors(store)?;
+        let terminated = load_provider_terminated_sectors(store, &provider_terminated, provider)?;
+
+        let mut settle_now = Vec::new();
+        let mut deferred = Vec::new();
+        let mut deal_count = 0u64;
+        for sector_number in sector_numbers {
+            let deals = sector_deals
+                .get(&sector_number)
+                .context_code(ExitCode::USR_ILLEGAL_STATE, "failed to read sector deals")?;
+            if let Some(deals) = deals {
+                let count = deals.len() as u64;
+                let already_terminated = terminated.contains_key(&sector_number)?;
+                if !already_terminated && deferred.is_empty() && deal_count + count <= max_deals {
+                    deal_count += count;
+                    settle_now.push(sector_number);
+                } else {
+                    deferred.push(sector_number);
+                }
+            }
+        }
+        Ok((settle_now, deferred))
+    }
+
+    // Records a provider's sectors as terminated at an epoch, leaving their deals to be settled
+    // later. A sector that is already recorded keeps its earlier termination epoch.
+    pub fn put_terminated_sectors(
+        &mut self,
+        store: &impl Blockstore,
+        provider: ActorID,
+        sector_numbers: &[SectorNumber],
+        epoch: ChainEpoch,
+    ) -> Result<(), ActorError> {
+        if sector_numbers.is_empty() {
+            return Ok(());
+        }
+        let mut provider_terminated = self.load_terminated_sectors(store)?;
+        let mut terminated =
+            load_provider_terminated_sectors(store, &provider_terminated, provider)?;
+        for sector_number in sector_numbers {
+            terminated
+                .set_if_absent(sector_number, epoch)
+                .with_context_code(ExitCode::USR_ILLEGAL_STATE, || {
+                    format!("failed to set terminated sector {} {}", provider, sector_number)
+                })?;
+        }
+        provider_terminated.set(&provider, terminated.flush()?)?;
+        self.terminated_sectors = provider_terminated.flush()?;
+        Ok(())
+    }
+
+    // Returns the epoch at which a provider's sector was terminated, if its deals have been left
+    // for later settlement.
+    pub fn find_sector_termination(
+        &self,
+        store: &impl Blockstore,
+        provider: ActorID,
+        sector_number: SectorNumber,
+    ) -> Result<Option<ChainEpoch>, ActorError> {
+        let provider_terminated = self.load_terminated_sectors(store)?;
+        let terminated = load_provider_terminated_sectors(store, &provider_terminated, provider)?;
+        let epoch = terminated
+            .get(&sector_number)
+            .with_context_code(ExitCode::USR_ILLEGAL_STATE, || {
+                format!("failed to read terminated sector {} {}", provider, sector_number)
+            })?;
+        Ok(epoch.copied())
+    }
+
+    // Marks a deal as terminated at the termination epoch of its sector, if settlement of that
+    // termination was deferred and the deal had not ended by then.
+    pub fn apply_deferred_termination(
+        &self,
+        store: &impl Blockstore,
+        proposal: &DealProposal,
+        state: &mut DealState,
+    ) -> Result<(), ActorError> {
+        if state.slash_epoch != EPOCH_UNDEFINED {
+            return Ok(());
+        }
+        let provider = proposal.provider.id().unwrap();
+        if let Some(epoch) = self.find_sector_termination(store, provider, state.sector_number)? {
+            if epoch < proposal.end_epoch {
+                state.slash_epoch = epoch;
+            }
+        }
+        Ok(())
+    }
+
+    fn remove_terminated_sectors(
+        &mut self,
+        store: &impl Blockstore,
+        provider_sector_numbers: &BTreeMap<ActorID, Vec<SectorNumber>>,
+    ) -> Result<(), ActorError> {
+        if provider_sector_numbers.is_empty() {
+            return Ok(());
+        }
+        let mut provider_terminated = self.load_terminated_sectors(store)?;
+        let mut flush = false;
+        for (provider, sector_numbers) in provider_sector_numbers {
+            if !provider_terminated.contains_key(provider)? {
+                continue;
+            }
+            let mut terminated =
+                load_provider_terminated_sectors(store, &provider_terminated, *provider)?;
+            let mut removed = false;
+            for sector_number in sector_numbers {
+                removed |= terminated
+                    .delete(sector_number)
+                    .with_context_code(ExitCode::USR_ILLEGAL_STATE, || {
+                        format!("failed to delete terminated sector {} {}", provider, sector_number)
+                    })?
+                    .is_some();
+            }
+            if !removed {
+                continue;
+            }
+            if terminated.is_empty() {
+                provider_terminated.delete(provider)?;
+            } else {
+                provider_terminated.set(provider, terminated.flush()?)?;
+            }
+            flush = true;
+        }
+        if flush {
+            self.terminated_sectors = provider_terminated.flush()?;
+        }
         Ok(())
     }
 
+    pub fn load_terminated_sectors<BS>(
+        &self,
+        store: BS,
+    ) -> Result<ProviderSectorsMap<BS>, ActorError>
+    where
+        BS: Blockstore,
+    {
+        ProviderSectorsMap::load(
+            store,
+            &self.terminated_sectors,
+            PROVIDER_SECTORS_CONFIG,
+            "terminated sectors",
+        )
+    }
+
     pub fn load_provider_sectors<BS>(&self, store: BS) -> Result<ProviderSectorsMap<BS>, ActorError>
     where
         BS: Blockstore,
@@ -919,6 +1412,31 @@ impl State {
         Ok((TokenAmount::zero(), elapsed_payment, false, false))
     }
 
+    /// Replaces the terms of an active deal which has been settled up to the current epoch,
+    /// re-locking the client's remaining storage fee according to the new terms.
+    pub fn amend_deal_proposal<BS>(
+        &mut self,
+        store: &BS,
+        deal_id: DealID,
+        old: &DealProposal,
+        new: &DealProposal,
+        curr_epoch: ChainEpoch,
+    ) -> Result<(), ActorError>
+    where
+        BS: Blockstore,
+    {
+        let old_remaining = deal_get_payment_remaining(old, curr_epoch)?;
+        self.unlock_balance(store, &old.client, &old_remaining, Reason::ClientStorageFee)
+            .context("unlocking client storage fee")?;
+
+        let new_remaining = deal_get_payment_remaining(new, curr_epoch)?;
+        self.maybe_lock_balance(store, &new.client, &new_remaining)
+            .context("locking client storage fee")?;
+        self.total_client_storage_fee += &new_remaining;
+
+        self.put_deal_proposals(store, &[(deal_id, new.clone())])
+    }
+
     pub fn process_slashed_deal<BS>(
         &mut self,
         store: &BS,
@@ -1149,6 +1667,7 @@ impl State {
 
         // Subtract from locked and escrow tables
         escrow_table.must_subtract(from_addr, amount)?;
+        self.spend_funded_balance(store, from_addr, &escrow_table.get(from_addr)?)?;
         self.unlock_balance(store, from_addr, amount, Reason::ClientStorageFee)
             .context("unlocking client balance")?;
 
@@ -1176,6 +1695,7 @@ impl State {
 
         // Subtract from locked and escrow tables
         escrow_table.must_subtract(addr, amount)?;
+        self.spend_funded_balance(store, addr, &escrow_table.get(addr)?)?;
         self.escrow_table = escrow_table.root()?;
         self.unlock_balance(store, addr, amount, lock_reason)
     }
@@ -1258,6 +1778,17 @@ where
     Ok(state.cloned())
 }
 
+/// Returns a deal's notification actor, if it was published with one.
+pub fn find_deal_notify<BS>(
+    extensions: &DealExtensionsMap<BS>,
+    deal_id: DealID,
+) -> Result<Option<Address>, ActorError>
+where
+    BS: Blockstore,
+{
+    Ok(extensions.get(&deal_id)?.and_then(|e| e.notify))
+}
+
 pub fn load_provider_sector_deals<BS>(
     store: BS,
     provider_sectors: &ProviderSectorsMap<BS>,
@@ -1276,6 +1807,23 @@ where
     Ok(sector_deals)
 }
 
+pub fn load_provider_terminated_sectors<BS>(
+    store: BS,
+    provider_terminated: &ProviderSectorsMap<BS>,
+    provider: ActorID,
+) -> Result<TerminatedSectorsMap<BS>, ActorError>
+where
+    BS: Blockstore,
+{
+    match provider_terminated.get(&provider)? {
+        Some(root) => {
+            TerminatedSectorsMap::load(store, root, TERMINATED_SECTORS_CONFIG, "terminated sectors")
+                .with_context(|| format!("provider {}", provider))
+        }
+        None => Ok(TerminatedSectorsMap::empty(store, TERMINATED_SECTORS_CONFIG, "empty")),
+    }
+}
+
 fn save_provider_sector_deals<BS>(
     provider_sectors: &mut ProviderSectorsMap<BS>,
     provider: ActorID,
@@ -23,14 +23,15 @@ use num_traits::Zero;
 use fil_actors_runtime::builtin::HAMT_BIT_WIDTH;
 use fil_actors_runtime::cbor::serialize;
 use fil_actors_runtime::{
-    make_map_with_root_and_bitwidth, ActorError, AsActorError, MessageAccumulator,
+    make_map_with_root_and_bitwidth, ActorError, ActorState, AsActorError, MessageAccumulator,
 };
 
 use crate::ext::verifreg::AllocationID;
 use crate::{
     balance_table::BalanceTable, DealArray, DealMetaArray, DealOpsByEpoch, DealProposal,
-    PendingProposalsSet, ProviderSectorsMap, SectorDealsMap, State, DEAL_OPS_BY_EPOCH_CONFIG,
-    PENDING_PROPOSALS_CONFIG, PROVIDER_SECTORS_CONFIG, SECTOR_DEALS_CONFIG,
+    PendingProposalsSet, ProviderSectorsMap, SectorDealsMap, State, TerminatedSectorsMap,
+    DEAL_OPS_BY_EPOCH_CONFIG, PENDING_PROPOSALS_CONFIG, PROVIDER_SECTORS_CONFIG,
+    SECTOR_DEALS_CONFIG, TERMINATED_SECTORS_CONFIG,
 };
 
 #[derive(Clone)]
@@ -80,6 +81,7 @@ pub fn check_state_invariants<BS: Blockstore>(
     current_epoch: ChainEpoch,
 ) -> (StateSummary, MessageAccumulator) {
     let acc = MessageAccumulator::default();
+    state.check_fields(store, &acc);
 
     acc.require(
         !state.total_client_locked_collateral.is_negative(),
@@ -180,6 +182,45 @@ pub fn check_state_invariants<BS: Blockstore>(
         Err(e) => acc.add(format!("error loading pending allocations: {e}")),
     };
 
+    // deal extensions
+    match state.load_deal_extensions(store) {
+        Ok(deal_extensions) => {
+            let ret = deal_extensions.for_each(|deal_id, extensions| {
+                acc.require(
+                    proposal_stats.contains_key(&deal_id),
+                    format!("deal extensions {} not found in proposals", deal_id),
+                );
+                if let Some(notify) = extensions.notify {
+                    acc.require(
+                        notify.protocol() == Protocol::ID,
+                        format!(
+                            "deal {} notification actor {} is not an ID address",
+                            deal_id, notify
+                        ),
+                    );
+                }
+                Ok(())
+            });
+            acc.require_no_error(ret, "error iterating deal extensions");
+        }
+        Err(e) => acc.add(format!("error loading deal extensions: {e}")),
+    };
+
+    // deal amendment nonces
+    match state.load_deal_amendment_nonces(store) {
+        Ok(nonces) => {
+            let ret = nonces.for_each(|deal_id, _| {
+                acc.require(
+                    proposal_stats.contains_key(&deal_id),
+                    format!("deal amendment nonce {} not found in proposals", deal_id),
+                );
+                Ok(())
+            });
+            acc.require_no_error(ret, "error iterating deal amendment nonces");
+        }
+        Err(e) => acc.add(format!("error loading deal amendment nonces: {e}")),
+    };
+
     // deal states
     let mut deal_state_count = 0;
     match DealMetaArray::load(&state.states, store) {
@@ -292,6 +333,48 @@ pub fn check_state_invariants<BS: Blockstore>(
         }
     }
 
+    // Terminated sectors whose deals are yet to be settled.
+    // Each entry corresponds to a sector that still has deals in the provider sector deals.
+    match ProviderSectorsMap::load(
+        store,
+        &state.terminated_sectors,
+        PROVIDER_SECTORS_CONFIG,
+        "terminated sectors",
+    ) {
+        Ok(terminated_sectors) => {
+            let ret = terminated_sectors.for_each(|provider, sectors_root| {
+                match TerminatedSectorsMap::load(
+                    store,
+                    sectors_root,
+                    TERMINATED_SECTORS_CONFIG,
+                    "terminated sectors",
+                ) {
+                    Ok(sectors) => {
+                        let ret = sectors.for_each(|sector, epoch| {
+                            acc.require(
+                                provider_sector_deals
+                                    .get(&provider)
+                                    .map(|p| p.contains_key(&sector))
+                                    .unwrap_or(false),
+                                format!("terminated sector {sector} of provider {provider} has no deals"),
+                            );
+                            acc.require(
+                                *epoch <= current_epoch,
+                                format!("sector {sector} of provider {provider} terminated at future epoch {epoch}"),
+                            );
+                            Ok(())
+                        });
+                        acc.require_no_error(ret, "error iterating terminated sectors");
+                    }
+                    Err(e) => acc.add(format!("error loading terminated sectors: {e}")),
+                }
+                Ok(())
+            });
+            acc.require_no_error(ret, "error iterating terminated sectors");
+        }
+        Err(e) => acc.add(format!("error loading terminated sectors: {e}")),
+    };
+
     // pending proposals
     let mut pending_proposal_count = 0;
     match PendingProposalsSet::load(
@@ -358,6 +441,19 @@ pub fn check_state_invariants<BS: Blockstore>(
                 }
                 Err(e) => acc.add(format!("error calculating escrow total: {e}")),
             }
+
+            // unspent funded balances are held in escrow
+            match BalanceTable::from_root(store, &state.funded_table, "funded table") {
+                Ok(funded_table) => {
+                    let ret = funded_table.0.for_each(|address, funded_amount| {
+                        let escrow_amount = &escrow_table.get(&address)?;
+                        acc.require(escrow_amount >= funded_amount, format!("funded balance for {address}, {funded_amount}, greater than escrow amount, {escrow_amount}"));
+                        Ok(())
+                    });
+                    acc.require_no_error(ret, "error iterating funded table");
+                }
+                Err(e) => acc.add(format!("error loading funded table: {e}")),
+            }
         }
         (escrow_table, lock_table) => {
             acc.require_no_error(escrow_table, "error loading escrow table");
@@ -374,9 +470,9 @@ pub fn check_state_invariants<BS: Blockstore>(
         "deal ops",
     ) {
         Ok(deal_ops) => {
-            let ret = deal_ops.for_each(|epoch: ChainEpoch, _| {
-                deal_op_epoch_count += 1;
-                deal_ops.for_each_in(&epoch, |deal_id: DealID| {
+            let ret = deal_ops.epochs().and_then(|epochs| {
+                deal_op_epoch_count = epochs.len() as u64;
+                deal_ops.for_each(|_, deal_id: DealID| {
                     expected_deal_ops.remove(&deal_id);
                     deal_op_count += 1;
                     Ok(())
@@ -5,25 +5,32 @@ use super::ext::verifreg::AllocationID;
 use cid::Cid;
 use fil_actors_runtime::Array;
 use fil_actors_runtime::BatchReturn;
+use fil_actors_runtime::CronWorkStats;
 use fvm_ipld_bitfield::BitField;
 use fvm_ipld_encoding::strict_bytes;
 use fvm_ipld_encoding::tuple::*;
 use fvm_shared::address::Address;
 use fvm_shared::bigint::{bigint_ser, BigInt};
 use fvm_shared::clock::ChainEpoch;
+use fvm_shared::crypto::signature::Signature;
 use fvm_shared::deal::DealID;
 use fvm_shared::econ::TokenAmount;
-use fvm_shared::piece::PaddedPieceSize;
+use fvm_shared::piece::{PaddedPieceSize, PieceInfo};
 use fvm_shared::ActorID;
+use serde_repr::{Deserialize_repr, Serialize_repr};
 
 use crate::Label;
 use fvm_shared::sector::{RegisteredSealProof, SectorNumber};
 
-use super::deal::{ClientDealProposal, DealProposal, DealState};
+use super::deal::{ClientDealProposal, ClientDealProposalWithExtensions, DealProposal, DealState};
 
 pub const PROPOSALS_AMT_BITWIDTH: u32 = 5;
 pub const STATES_AMT_BITWIDTH: u32 = 6;
 
+/// Domain separation tag prefixed to a deal proposal and its extensions when signed together
+/// by the client, so the signature can't be taken for one over a proposal alone.
+pub const SIGNATURE_DOMAIN_SEPARATION_DEAL_EXTENSIONS: &[u8] = b"fil_dealextensions:";
+
 #[derive(Serialize_tuple, Deserialize_tuple, Debug, Clone, Eq, PartialEq)]
 #[serde(transparent)]
 pub struct AddBalanceParams {
@@ -34,6 +41,9 @@ pub struct AddBalanceParams {
 pub struct WithdrawBalanceParams {
     pub provider_or_client: Address,
     pub amount: TokenAmount,
+    /// Address to receive the withdrawal, if not the escrow owner.
+    /// Only the escrow owner (the client, or a provider's owner) may designate a payee.
+    pub payee: Option<Address>,
 }
 
 #[derive(Serialize_tuple, Deserialize_tuple, Debug, Clone, Eq, PartialEq)]
@@ -42,6 +52,47 @@ pub struct WithdrawBalanceReturn {
     pub amount_withdrawn: TokenAmount,
 }
 
+#[derive(Serialize_tuple, Deserialize_tuple, Debug, Clone, Eq, PartialEq)]
+pub struct WithdrawalTarget {
+    pub recipient: Address,
+    pub amount: TokenAmount,
+}
+
+#[derive(Serialize_tuple, Deserialize_tuple, Debug, Clone, Eq, PartialEq)]
+pub struct WithdrawBalanceMultiParams {
+    pub provider: Address,
+    /// Recipients of the withdrawal and the amount sent to each, all debited from the
+    /// provider's escrow.
+    pub withdrawals: Vec<WithdrawalTarget>,
+}
+
+#[derive(Serialize_tuple, Deserialize_tuple, Debug, Clone, Eq, PartialEq)]
+#[serde(transparent)]
+pub struct WithdrawBalanceMultiReturn {
+    pub amount_withdrawn: TokenAmount,
+}
+
+#[derive(Serialize_tuple, Deserialize_tuple, Debug, Clone, Eq, PartialEq)]
+#[serde(transparent)]
+pub struct AddBalanceForParams {
+    /// The account whose escrow balance is topped up.
+    pub beneficiary: Address,
+}
+
+#[derive(Serialize_tuple, Deserialize_tuple, Debug, Clone, Eq, PartialEq)]
+pub struct ReclaimAllowanceParams {
+    /// The account whose escrow balance was previously topped up by the caller.
+    pub beneficiary: Address,
+    /// The maximum amount to reclaim.
+    pub amount: TokenAmount,
+}
+
+#[derive(Serialize_tuple, Deserialize_tuple, Debug, Clone, Eq, PartialEq)]
+#[serde(transparent)]
+pub struct ReclaimAllowanceReturn {
+    pub amount_reclaimed: TokenAmount,
+}
+
 #[derive(Serialize_tuple, Deserialize_tuple, Debug, Clone, Eq, PartialEq)]
 #[serde(transparent)]
 pub struct GetBalanceParams {
@@ -65,6 +116,11 @@ pub struct PublishStorageDealsParams {
     pub deals: Vec<ClientDealProposal>,
 }
 
+#[derive(Serialize_tuple, Deserialize_tuple, Debug, Clone, Eq, PartialEq)]
+pub struct PublishStorageDealsWithExtensionsParams {
+    pub deals: Vec<ClientDealProposalWithExtensions>,
+}
+
 #[derive(Serialize_tuple, Deserialize_tuple, Debug, Clone, PartialEq)] // Add Eq when BitField does
 pub struct PublishStorageDealsReturn {
     pub ids: Vec<DealID>,
@@ -92,6 +148,39 @@ pub struct VerifyDealsForActivationReturn {
     pub unsealed_cids: Vec<Option<Cid>>,
 }
 
+/// A client's authorization for a provider to commit pieces of data without a published deal.
+/// Such data carries no payment or collateral, so is never held in escrow.
+#[derive(Serialize_tuple, Deserialize_tuple, Debug, Clone, Eq, PartialEq)]
+pub struct PieceManifest {
+    pub client: Address,
+    pub provider: Address,
+    /// The pieces, in the order they are to be committed to a sector.
+    pub pieces: Vec<PieceInfo>,
+    /// The last epoch at which the manifest may be used to activate data.
+    pub expiration: ChainEpoch,
+}
+
+#[derive(Serialize_tuple, Deserialize_tuple, Debug, Clone, Eq, PartialEq)]
+pub struct ClientPieceManifest {
+    pub manifest: PieceManifest,
+    /// The client's signature over the CBOR-serialized manifest.
+    pub client_signature: Signature,
+}
+
+#[derive(Serialize_tuple, Deserialize_tuple, Debug, Clone, Eq, PartialEq)]
+pub struct SectorPieceManifests {
+    pub sector_number: SectorNumber,
+    pub sector_type: RegisteredSealProof,
+    /// Manifests whose pieces make up the sector's data, in order.
+    pub manifests: Vec<ClientPieceManifest>,
+}
+
+#[derive(Serialize_tuple, Deserialize_tuple, Debug, Clone, Eq, PartialEq)]
+pub struct VerifyDealsForActivationDirectParams {
+    /// Client-signed piece manifests to verify, grouped by sector.
+    pub sectors: Vec<SectorPieceManifests>,
+}
+
 #[derive(Serialize_tuple, Deserialize_tuple, Debug, Clone, Eq, PartialEq)]
 pub struct BatchActivateDealsParams {
     /// Deals to activate, grouped by sector.
@@ -187,6 +276,14 @@ pub struct GetDealLabelReturn {
     pub label: Label,
 }
 
+pub type GetDealMetadataParams = DealQueryParams;
+
+#[derive(Serialize_tuple, Deserialize_tuple, Debug, Clone, Eq, PartialEq)]
+#[serde(transparent)]
+pub struct GetDealMetadataReturn {
+    pub metadata: Option<Cid>,
+}
+
 pub type GetDealTermParams = DealQueryParams;
 
 #[derive(Serialize_tuple, Deserialize_tuple, Debug, Clone, Eq, PartialEq)]
@@ -248,6 +345,56 @@ pub struct GetDealSectorReturn {
     pub sector: SectorNumber,
 }
 
+/// Maximum number of sectors visited by one call to GetDealsForProvider.
+pub const MAX_PROVIDER_SECTORS_PER_PAGE: u64 = 1000;
+
+#[derive(Serialize_tuple, Deserialize_tuple, Debug, Clone, Eq, PartialEq)]
+pub struct GetDealsForProviderParams {
+    pub provider: Address,
+    /// Sector from which to resume a previous traversal, as returned in `next_cursor`.
+    pub cursor: Option<SectorNumber>,
+    /// Maximum number of sectors to visit, capped at `MAX_PROVIDER_SECTORS_PER_PAGE`.
+    pub limit: u64,
+}
+
+#[derive(Serialize_tuple, Deserialize_tuple, Debug, Clone, Eq, PartialEq)]
+pub struct GetDealsForProviderReturn {
+    /// IDs of deals in the visited sectors.
+    pub deals: Vec<DealID>,
+    /// Sector from which to resume the traversal, or None if it is complete.
+    pub next_cursor: Option<SectorNumber>,
+}
+
+/// New terms for an active deal, agreed by both its client and provider.
+#[derive(Serialize_tuple, Deserialize_tuple, Debug, Clone, Eq, PartialEq)]
+pub struct DealAmendment {
+    pub deal_id: DealID,
+    /// CID of the deal proposal being amended.
+    /// This binds the amendment to the deal's current terms, so it cannot be replayed
+    /// after the terms have changed.
+    pub proposal_cid: Cid,
+    /// Number of amendments previously applied to the deal.
+    /// This prevents an amendment being replayed after the deal's terms are restored.
+    pub nonce: u64,
+    pub storage_price_per_epoch: TokenAmount,
+    /// New end epoch, which may not be later than the deal's current end epoch.
+    pub end_epoch: ChainEpoch,
+}
+
+#[derive(Serialize_tuple, Deserialize_tuple, Debug, Clone, Eq, PartialEq)]
+pub struct AmendDealTermsParams {
+    pub amendment: DealAmendment,
+    /// The client's signature over the serialized amendment.
+    pub client_signature: Signature,
+}
+
+#[derive(Serialize_tuple, Deserialize_tuple, Debug, Clone, Eq, PartialEq)]
+#[serde(transparent)]
+pub struct AmendDealTermsReturn {
+    /// Payment settled under the previous terms, up to the current epoch.
+    pub payment: TokenAmount,
+}
+
 // Interface market clients can implement to receive notifications from builtin market
 pub const MARKET_NOTIFY_DEAL_METHOD: u64 = frc42_dispatch::method_hash!("MarketNotifyDeal");
 
@@ -258,6 +405,35 @@ pub struct MarketNotifyDealParams {
     pub deal_id: u64,
 }
 
+// Interface a deal's notification actor can implement to receive deal lifecycle outcomes
+pub const MARKET_NOTIFY_DEAL_OUTCOME_METHOD: u64 =
+    frc42_dispatch::method_hash!("MarketNotifyDealOutcome");
+
+/// Lifecycle outcome of a deal reported to the deal's notification actor.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize_repr, Deserialize_repr)]
+#[repr(u8)]
+pub enum DealOutcome {
+    /// The deal was activated in a sector.
+    Activated = 1,
+    /// The deal was terminated before its end epoch, and the provider slashed.
+    Terminated = 2,
+    /// The deal reached its end epoch and received its final settlement.
+    Completed = 3,
+}
+
+#[derive(Serialize_tuple, Deserialize_tuple, Debug, Clone, Eq, PartialEq)]
+pub struct MarketNotifyDealOutcomeParams {
+    pub deal_id: DealID,
+    pub outcome: DealOutcome,
+}
+
+/// A deal outcome notification awaiting delivery to a deal's notification actor.
+#[derive(Serialize_tuple, Deserialize_tuple, Debug, Clone, Eq, PartialEq)]
+pub struct PendingDealNotification {
+    pub notify: Address,
+    pub outcome: DealOutcome,
+}
+
 #[derive(Serialize_tuple, Deserialize_tuple, Debug, Clone)]
 #[serde(transparent)]
 pub struct SettleDealPaymentsParams {
@@ -279,3 +455,9 @@ pub struct DealSettlementSummary {
     /// Whether the deal has settled for the final time
     pub completed: bool,
 }
+
+#[derive(Serialize_tuple, Deserialize_tuple, Debug, Clone, Eq, PartialEq)]
+#[serde(transparent)]
+pub struct CronWorkStatsReturn {
+    pub stats: CronWorkStats,
+}
@@ -0,0 +1,199 @@
+use fvm_ipld_encoding::ipld_block::IpldBlock;
+use fvm_shared::clock::ChainEpoch;
+use fvm_shared::crypto::signature::Signature;
+use fvm_shared::econ::TokenAmount;
+use fvm_shared::error::ExitCode;
+
+use fil_actor_market::{
+    deal_cid, Actor as MarketActor, AmendDealTermsParams, AmendDealTermsReturn, DealAmendment,
+    Method,
+};
+use fil_actors_runtime::network::EPOCHS_IN_DAY;
+use fil_actors_runtime::test_utils::{expect_abort_contains_message, ACCOUNT_ACTOR_CODE_ID};
+use harness::*;
+
+mod harness;
+
+const START_EPOCH: ChainEpoch = 0;
+const END_EPOCH: ChainEpoch = START_EPOCH + 200 * EPOCHS_IN_DAY;
+
+#[test]
+fn amendment_settles_previous_terms_and_applies_new_price() {
+    let rt = setup();
+    let addrs = MinerAddresses::default();
+    let sector_number = 7;
+    let (deal_id, proposal) = publish_and_activate_deal(
+        &rt,
+        CLIENT_ADDR,
+        &addrs,
+        sector_number,
+        START_EPOCH,
+        END_EPOCH,
+        0,
+        END_EPOCH,
+    );
+
+    // Halve the price and shorten the deal after 100 epochs.
+    let amend_epoch = START_EPOCH + 100;
+    rt.set_epoch(amend_epoch);
+    let new_price = TokenAmount::from_atto(5);
+    let new_end = END_EPOCH - EPOCHS_IN_DAY;
+    let amendment = DealAmendment {
+        deal_id,
+        proposal_cid: deal_cid(&rt, &proposal).unwrap(),
+        nonce: 0,
+        storage_price_per_epoch: new_price.clone(),
+        end_epoch: new_end,
+    };
+    let ret = amend_deal_terms(&rt, &addrs, amendment.clone()).unwrap();
+    assert_eq!(AmendDealTermsReturn { payment: &proposal.storage_price_per_epoch * 100i64 }, ret);
+
+    let amended = get_deal_proposal(&rt, deal_id);
+    assert_eq!(new_price, amended.storage_price_per_epoch);
+    assert_eq!(new_end, amended.end_epoch);
+    // The client's remaining storage fee is locked at the new price.
+    let client = get_balance(&rt, &CLIENT_ADDR);
+    assert_eq!(&proposal.client_collateral + &new_price * (new_end - amend_epoch), client.locked);
+    check_state(&rt);
+
+    // Subsequent settlement accrues at the new price.
+    rt.set_epoch(amend_epoch + 100);
+    let ret = settle_deal_payments(&rt, addrs.provider, &[deal_id], &[], &[]);
+    assert_eq!(&new_price * 100i64, ret.settlements[0].payment);
+
+    // The amendment can't be replayed once the terms have changed.
+    rt.set_caller(*ACCOUNT_ACTOR_CODE_ID, addrs.worker);
+    rt.expect_validate_caller_any();
+    let params = AmendDealTermsParams {
+        amendment,
+        client_signature: Signature::new_bls("does not matter".as_bytes().to_vec()),
+    };
+    expect_abort_contains_message(
+        ExitCode::USR_ILLEGAL_ARGUMENT,
+        "does not match current proposal",
+        rt.call::<MarketActor>(
+            Method::AmendDealTermsExported as u64,
+            IpldBlock::serialize_cbor(&params).unwrap(),
+        ),
+    );
+    rt.verify();
+    check_state(&rt);
+}
+
+#[test]
+fn amendment_requires_client_escrow_for_price_increase() {
+    let rt = setup();
+    let addrs = MinerAddresses::default();
+    let (deal_id, proposal) = publish_and_activate_deal(
+        &rt,
+        CLIENT_ADDR,
+        &addrs,
+        7,
+        START_EPOCH,
+        END_EPOCH,
+        0,
+        END_EPOCH,
+    );
+
+    rt.set_epoch(START_EPOCH + 100);
+    let amendment = DealAmendment {
+        deal_id,
+        proposal_cid: deal_cid(&rt, &proposal).unwrap(),
+        nonce: 0,
+        storage_price_per_epoch: &proposal.storage_price_per_epoch * 2i64,
+        end_epoch: proposal.end_epoch,
+    };
+    expect_abort_contains_message(
+        ExitCode::USR_INSUFFICIENT_FUNDS,
+        "not enough balance to lock",
+        amend_deal_terms(&rt, &addrs, amendment.clone()),
+    );
+    rt.reset();
+
+    // With more funds in escrow, the amendment succeeds.
+    add_participant_funds(&rt, CLIENT_ADDR, &proposal.storage_price_per_epoch * END_EPOCH);
+    amend_deal_terms(&rt, &addrs, amendment).unwrap();
+    check_state(&rt);
+}
+
+#[test]
+fn amendment_cannot_be_replayed_after_terms_are_restored() {
+    let rt = setup();
+    let addrs = MinerAddresses::default();
+    let (deal_id, proposal) = publish_and_activate_deal(
+        &rt,
+        CLIENT_ADDR,
+        &addrs,
+        7,
+        START_EPOCH,
+        END_EPOCH,
+        0,
+        END_EPOCH,
+    );
+    let original_cid = deal_cid(&rt, &proposal).unwrap();
+
+    rt.set_epoch(START_EPOCH + 100);
+    let lower = DealAmendment {
+        deal_id,
+        proposal_cid: original_cid,
+        nonce: 0,
+        storage_price_per_epoch: TokenAmount::from_atto(5),
+        end_epoch: proposal.end_epoch,
+    };
+    amend_deal_terms(&rt, &addrs, lower.clone()).unwrap();
+
+    // Restore the original price, so the deal's proposal CID is the same as before.
+    let amended = get_deal_proposal(&rt, deal_id);
+    let restore = DealAmendment {
+        deal_id,
+        proposal_cid: deal_cid(&rt, &amended).unwrap(),
+        nonce: 1,
+        storage_price_per_epoch: proposal.storage_price_per_epoch.clone(),
+        end_epoch: proposal.end_epoch,
+    };
+    amend_deal_terms(&rt, &addrs, restore).unwrap();
+    assert_eq!(original_cid, deal_cid(&rt, &get_deal_proposal(&rt, deal_id)).unwrap());
+
+    // The first amendment matches the proposal again, but its nonce has been used.
+    expect_abort_contains_message(
+        ExitCode::USR_ILLEGAL_ARGUMENT,
+        "amendment nonce 0 does not match expected nonce 2",
+        amend_deal_terms(&rt, &addrs, lower),
+    );
+    rt.reset();
+    assert_eq!(proposal, get_deal_proposal(&rt, deal_id));
+    check_state(&rt);
+}
+
+#[test]
+fn amendment_cannot_extend_deal() {
+    let rt = setup();
+    let addrs = MinerAddresses::default();
+    let (deal_id, proposal) = publish_and_activate_deal(
+        &rt,
+        CLIENT_ADDR,
+        &addrs,
+        7,
+        START_EPOCH,
+        END_EPOCH,
+        0,
+        END_EPOCH + EPOCHS_IN_DAY,
+    );
+
+    rt.set_epoch(START_EPOCH + 100);
+    let amendment = DealAmendment {
+        deal_id,
+        proposal_cid: deal_cid(&rt, &proposal).unwrap(),
+        nonce: 0,
+        storage_price_per_epoch: proposal.storage_price_per_epoch.clone(),
+        end_epoch: END_EPOCH + 1,
+    };
+    expect_abort_contains_message(
+        ExitCode::USR_ILLEGAL_ARGUMENT,
+        "cannot be after current end epoch",
+        amend_deal_terms(&rt, &addrs, amendment),
+    );
+    rt.reset();
+    assert_eq!(proposal, get_deal_proposal(&rt, deal_id));
+    check_state(&rt);
+}
@@ -2,11 +2,12 @@
 // SPDX-License-Identifier: Apache-2.0, MIT
 
 use fil_actor_market::{
-    Actor as MarketActor, ClientDealProposal, Method, PublishStorageDealsParams,
+    Actor as MarketActor, ClientDealProposal, CronWorkStatsReturn, Method,
+    PublishStorageDealsParams,
 };
 use fil_actors_runtime::network::EPOCHS_IN_DAY;
 use fil_actors_runtime::test_utils::*;
-use fil_actors_runtime::BURNT_FUNDS_ACTOR_ADDR;
+use fil_actors_runtime::{CronWorkStats, EventBuilder, BURNT_FUNDS_ACTOR_ADDR};
 use fvm_ipld_encoding::RawBytes;
 use fvm_shared::clock::ChainEpoch;
 use fvm_shared::crypto::signature::Signature;
@@ -59,6 +60,59 @@ fn timed_out_deal_is_slashed_and_deleted() {
     check_state(&rt);
 }
 
+#[test]
+fn cron_work_counted_and_budget_overrun_reported() {
+    let mut rt = setup();
+    rt.policy.market_cron_entries_budget = 0;
+    let (deal_id, deal_proposal) = generate_and_publish_deal(
+        &rt,
+        CLIENT_ADDR,
+        &MinerAddresses::default(),
+        START_EPOCH,
+        END_EPOCH,
+    );
+
+    let epoch = process_epoch(START_EPOCH, deal_id);
+    rt.set_epoch(epoch);
+    rt.expect_send_simple(
+        BURNT_FUNDS_ACTOR_ADDR,
+        METHOD_SEND,
+        None,
+        deal_proposal.provider_collateral.clone(),
+        None,
+        ExitCode::OK,
+    );
+    rt.expect_emitted_event(
+        EventBuilder::new()
+            .typ("cron-budget-exceeded")
+            .field("entries", &1u64)
+            .field("budget", &0u64)
+            .build()
+            .unwrap(),
+    );
+    cron_tick(&rt);
+
+    rt.expect_validate_caller_any();
+    let ret: CronWorkStatsReturn = rt
+        .call::<MarketActor>(Method::CronWorkStatsExported as u64, None)
+        .unwrap()
+        .unwrap()
+        .deserialize()
+        .unwrap();
+    rt.verify();
+    assert_eq!(
+        CronWorkStats {
+            last_epoch: epoch,
+            last_entries: 1,
+            max_entries: 1,
+            total_entries: 1,
+            overruns: 1
+        },
+        ret.stats
+    );
+    check_state(&rt);
+}
+
 #[test]
 fn publishing_timed_out_deal_again_should_work_after_cron_tick_as_it_should_no_longer_be_pending() {
     const START_EPOCH: ChainEpoch = 0;
@@ -6,14 +6,16 @@ use num_traits::Zero;
 use serde::de::DeserializeOwned;
 
 use fil_actor_market::{
-    Actor as MarketActor, DealQueryParams, GetDealActivationReturn, GetDealClientCollateralReturn,
-    GetDealClientReturn, GetDealDataCommitmentReturn, GetDealLabelReturn,
-    GetDealProviderCollateralReturn, GetDealProviderReturn, GetDealSectorReturn, GetDealTermReturn,
-    GetDealTotalPriceReturn, GetDealVerifiedReturn, Method, EX_DEAL_EXPIRED, EX_DEAL_NOT_ACTIVATED,
+    Actor as MarketActor, DealExtensions, DealQueryParams, GetDealActivationReturn,
+    GetDealClientCollateralReturn, GetDealClientReturn, GetDealDataCommitmentReturn,
+    GetDealLabelReturn, GetDealMetadataReturn, GetDealProviderCollateralReturn,
+    GetDealProviderReturn, GetDealSectorReturn, GetDealTermReturn, GetDealTotalPriceReturn,
+    GetDealVerifiedReturn, GetDealsForProviderParams, GetDealsForProviderReturn, Method,
+    EX_DEAL_EXPIRED, EX_DEAL_NOT_ACTIVATED,
 };
 use fil_actors_runtime::network::EPOCHS_IN_DAY;
 use fil_actors_runtime::test_utils::{
-    expect_abort, expect_abort_contains_message, MockRuntime, ACCOUNT_ACTOR_CODE_ID,
+    expect_abort, expect_abort_contains_message, make_piece_cid, MockRuntime, ACCOUNT_ACTOR_CODE_ID,
 };
 use fil_actors_runtime::ActorError;
 use harness::*;
@@ -37,11 +39,14 @@ fn proposal_data() {
         start_epoch,
         end_epoch,
     );
+    let extensions =
+        DealExtensions { notify: None, metadata: Some(make_piece_cid(b"deal metadata")) };
     rt.set_caller(*ACCOUNT_ACTOR_CODE_ID, WORKER_ADDR);
-    let id = publish_deals(
+    let id = publish_deals_with_extensions(
         &rt,
         &MinerAddresses::default(),
         &[proposal.clone()],
+        &[extensions.clone()],
         TokenAmount::zero(),
         next_allocation_id,
     )[0];
@@ -60,6 +65,9 @@ fn proposal_data() {
     let label: GetDealLabelReturn = query_deal(&rt, Method::GetDealLabelExported, id);
     assert_eq!(proposal.label, label.label);
 
+    let metadata: GetDealMetadataReturn = query_deal(&rt, Method::GetDealMetadataExported, id);
+    assert_eq!(extensions.metadata, metadata.metadata);
+
     let term: GetDealTermReturn = query_deal(&rt, Method::GetDealTermExported, id);
     assert_eq!(proposal.start_epoch, term.start);
     assert_eq!(proposal.duration(), term.duration);
@@ -144,6 +152,124 @@ fn activation() {
     query_deal_fails(&rt, Method::GetDealSectorExported, id + 1, ExitCode::USR_NOT_FOUND);
 }
 
+#[test]
+fn deals_for_provider() {
+    let start_epoch = 1000;
+    let end_epoch = start_epoch + 200 * EPOCHS_IN_DAY;
+    let publish_epoch = ChainEpoch::from(1);
+
+    let rt = setup();
+    rt.set_epoch(publish_epoch);
+    let next_allocation_id = 1;
+
+    let proposals: Vec<_> = (0..3)
+        .map(|i| {
+            generate_deal_and_add_funds(
+                &rt,
+                CLIENT_ADDR,
+                &MinerAddresses::default(),
+                start_epoch,
+                end_epoch + i,
+            )
+        })
+        .collect();
+    rt.set_caller(*ACCOUNT_ACTOR_CODE_ID, WORKER_ADDR);
+    let ids = publish_deals(
+        &rt,
+        &MinerAddresses::default(),
+        &proposals,
+        TokenAmount::zero(),
+        next_allocation_id,
+    );
+
+    // No deals are listed before activation.
+    let ret = query_deals_for_provider(&rt, None, 10).unwrap();
+    assert_eq!(GetDealsForProviderReturn { deals: vec![], next_cursor: None }, ret);
+
+    let activate_epoch = start_epoch - 2;
+    rt.set_epoch(activate_epoch);
+    for (i, id) in ids.iter().enumerate() {
+        activate_deals(&rt, end_epoch + 3, PROVIDER_ADDR, activate_epoch, 7 + i as u64, &[*id]);
+    }
+
+    // Page through the sectors two at a time.
+    let first = query_deals_for_provider(&rt, None, 2).unwrap();
+    assert_eq!(2, first.deals.len());
+    assert!(first.next_cursor.is_some());
+    let second = query_deals_for_provider(&rt, first.next_cursor, 2).unwrap();
+    assert_eq!(1, second.deals.len());
+    assert_eq!(None, second.next_cursor);
+
+    let mut listed = [first.deals, second.deals].concat();
+    listed.sort();
+    assert_eq!(ids, listed);
+
+    // An oversized limit is capped rather than rejected.
+    let all = query_deals_for_provider(&rt, None, u64::MAX).unwrap();
+    assert_eq!(3, all.deals.len());
+    assert_eq!(None, all.next_cursor);
+
+    expect_abort(ExitCode::USR_ILLEGAL_ARGUMENT, query_deals_for_provider(&rt, None, 0));
+}
+
+#[test]
+fn deals_for_provider_resumes_after_cursor_sector_terminated() {
+    let start_epoch = 1000;
+    let end_epoch = start_epoch + 200 * EPOCHS_IN_DAY;
+    let rt = setup();
+    rt.set_epoch(ChainEpoch::from(1));
+
+    let proposals: Vec<_> = (0..3)
+        .map(|i| {
+            generate_deal_and_add_funds(
+                &rt,
+                CLIENT_ADDR,
+                &MinerAddresses::default(),
+                start_epoch,
+                end_epoch + i,
+            )
+        })
+        .collect();
+    rt.set_caller(*ACCOUNT_ACTOR_CODE_ID, WORKER_ADDR);
+    let mut ids =
+        publish_deals(&rt, &MinerAddresses::default(), &proposals, TokenAmount::zero(), 1);
+    let activate_epoch = start_epoch - 2;
+    rt.set_epoch(activate_epoch);
+    for (i, id) in ids.iter().enumerate() {
+        activate_deals(&rt, end_epoch + 3, PROVIDER_ADDR, activate_epoch, 7 + i as u64, &[*id]);
+    }
+
+    let first = query_deals_for_provider(&rt, None, 1).unwrap();
+    let cursor = first.next_cursor.unwrap();
+
+    // The sector at the cursor is terminated between pages.
+    let terminated = ids[(cursor - 7) as usize];
+    rt.set_epoch(activate_epoch + 100);
+    terminate_deals(&rt, PROVIDER_ADDR, &[cursor], &[terminated]);
+
+    let second = query_deals_for_provider(&rt, Some(cursor), 10).unwrap();
+    assert_eq!(None, second.next_cursor);
+    let mut listed = [first.deals, second.deals].concat();
+    listed.sort();
+    ids.retain(|id| *id != terminated);
+    assert_eq!(ids, listed);
+}
+
+fn query_deals_for_provider(
+    rt: &MockRuntime,
+    cursor: Option<u64>,
+    limit: u64,
+) -> Result<GetDealsForProviderReturn, ActorError> {
+    let params = GetDealsForProviderParams { provider: PROVIDER_ADDR, cursor, limit };
+    rt.expect_validate_caller_any();
+    let ret = rt.call::<MarketActor>(
+        Method::GetDealsForProviderExported as u64,
+        IpldBlock::serialize_cbor(&params).unwrap(),
+    );
+    rt.verify();
+    Ok(ret?.unwrap().deserialize().unwrap())
+}
+
 fn query_deal<T: DeserializeOwned>(rt: &MockRuntime, method: Method, id: u64) -> T {
     query_deal_raw(rt, method, id).unwrap().unwrap().deserialize().unwrap()
 }
@@ -0,0 +1,169 @@
+use fil_actor_market::policy::DEAL_NOTIFICATIONS_MAX_PER_CRON_TICK;
+use fil_actor_market::{DealExtensions, DealOutcome, State, NO_ALLOCATION_ID};
+use fil_actors_runtime::network::EPOCHS_IN_DAY;
+use fil_actors_runtime::runtime::Runtime;
+use fil_actors_runtime::test_utils::{MockRuntime, ACCOUNT_ACTOR_CODE_ID};
+use fil_actors_runtime::BURNT_FUNDS_ACTOR_ADDR;
+use fvm_shared::address::Address;
+use fvm_shared::clock::ChainEpoch;
+use fvm_shared::deal::DealID;
+use fvm_shared::econ::TokenAmount;
+use fvm_shared::error::ExitCode;
+use fvm_shared::sector::SectorNumber;
+use fvm_shared::METHOD_SEND;
+use num_traits::Zero;
+
+use harness::*;
+
+mod harness;
+
+const START_EPOCH: ChainEpoch = 10;
+const END_EPOCH: ChainEpoch = START_EPOCH + 200 * EPOCHS_IN_DAY;
+const SECTOR_EXPIRY: ChainEpoch = END_EPOCH + EPOCHS_IN_DAY;
+const SECTOR_NUMBER: SectorNumber = 7;
+const NOTIFY_ADDR: Address = Address::new_id(1001);
+
+fn publish_deal_with_notify(rt: &MockRuntime) -> DealID {
+    let addrs = MinerAddresses::default();
+    let deal = generate_deal_and_add_funds(rt, CLIENT_ADDR, &addrs, START_EPOCH, END_EPOCH);
+    let extensions = DealExtensions { notify: Some(NOTIFY_ADDR), metadata: None };
+    rt.set_caller(*ACCOUNT_ACTOR_CODE_ID, addrs.worker);
+    let ids = publish_deals_with_extensions(
+        rt,
+        &addrs,
+        &[deal],
+        &[extensions.clone()],
+        TokenAmount::zero(),
+        NO_ALLOCATION_ID,
+    );
+    let st: State = rt.get_state();
+    assert_eq!(Some(extensions), st.get_deal_extensions(rt.store(), ids[0]).unwrap());
+    ids[0]
+}
+
+#[test]
+fn notifies_activation_and_completion() {
+    let rt = setup();
+    let deal_id = publish_deal_with_notify(&rt);
+
+    expect_deal_outcome_notification(
+        &rt,
+        NOTIFY_ADDR,
+        deal_id,
+        DealOutcome::Activated,
+        ExitCode::OK,
+    );
+    activate_deals(&rt, SECTOR_EXPIRY, PROVIDER_ADDR, 5, SECTOR_NUMBER, &[deal_id]);
+
+    // A failed notification does not prevent the deal from completing.
+    rt.set_epoch(END_EPOCH);
+    expect_deal_outcome_notification(
+        &rt,
+        NOTIFY_ADDR,
+        deal_id,
+        DealOutcome::Completed,
+        ExitCode::USR_UNHANDLED_MESSAGE,
+    );
+    let ret = settle_deal_payments(&rt, PROVIDER_ADDR, &[deal_id], &[deal_id], &[]);
+    assert!(ret.results.all_ok());
+    assert!(ret.settlements[0].completed);
+    assert!(find_deal_proposal(&rt, deal_id).is_none());
+    check_state(&rt);
+}
+
+#[test]
+fn notifies_termination() {
+    let rt = setup();
+    let deal_id = publish_deal_with_notify(&rt);
+    let deal = get_deal_proposal(&rt, deal_id);
+
+    expect_deal_outcome_notification(
+        &rt,
+        NOTIFY_ADDR,
+        deal_id,
+        DealOutcome::Activated,
+        ExitCode::OK,
+    );
+    activate_deals(&rt, SECTOR_EXPIRY, PROVIDER_ADDR, 5, SECTOR_NUMBER, &[deal_id]);
+
+    rt.set_epoch(START_EPOCH + 5);
+    rt.expect_send_simple(
+        BURNT_FUNDS_ACTOR_ADDR,
+        METHOD_SEND,
+        None,
+        deal.provider_collateral,
+        None,
+        ExitCode::OK,
+    );
+    terminate_deals_raw(&rt, PROVIDER_ADDR, &[SECTOR_NUMBER], &[deal_id]).unwrap();
+    rt.verify();
+    assert!(find_deal_proposal(&rt, deal_id).is_none());
+    let st: State = rt.get_state();
+    assert_eq!(None, st.get_deal_extensions(rt.store(), deal_id).unwrap());
+
+    // The termination notification is delivered by cron.
+    expect_deal_outcome_notification(
+        &rt,
+        NOTIFY_ADDR,
+        deal_id,
+        DealOutcome::Terminated,
+        ExitCode::OK,
+    );
+    cron_tick(&rt);
+    check_state(&rt);
+}
+
+#[test]
+fn cron_delivers_bounded_notifications() {
+    let rt = setup();
+    let queued = DEAL_NOTIFICATIONS_MAX_PER_CRON_TICK as DealID + 5;
+    let mut st: State = rt.get_state();
+    let notifications: Vec<_> =
+        (0..queued).map(|deal_id| (NOTIFY_ADDR, deal_id, DealOutcome::Completed)).collect();
+    st.queue_deal_notifications(rt.store(), &notifications).unwrap();
+    rt.replace_state(&st);
+
+    let mut delivered = 0;
+    for expected in [DEAL_NOTIFICATIONS_MAX_PER_CRON_TICK as DealID, 5, 0] {
+        let mut pending: State = rt.get_state();
+        let deal_ids: Vec<_> = pending
+            .take_deal_notifications(rt.store(), DEAL_NOTIFICATIONS_MAX_PER_CRON_TICK)
+            .unwrap()
+            .into_iter()
+            .map(|(_, deal_id, _)| deal_id)
+            .collect();
+        assert_eq!(expected, deal_ids.len() as DealID);
+        for deal_id in deal_ids {
+            expect_deal_outcome_notification(
+                &rt,
+                NOTIFY_ADDR,
+                deal_id,
+                DealOutcome::Completed,
+                ExitCode::OK,
+            );
+        }
+        let next_epoch = *rt.epoch.borrow() + 1;
+        rt.set_epoch(next_epoch);
+        cron_tick(&rt);
+        delivered += expected;
+    }
+    assert_eq!(queued, delivered);
+}
+
+#[test]
+fn fails_to_publish_with_unresolvable_notification_actor() {
+    let rt = setup();
+    let addrs = MinerAddresses::default();
+    let deal = generate_deal_and_add_funds(&rt, CLIENT_ADDR, &addrs, START_EPOCH, END_EPOCH);
+    let notify = Address::new_secp256k1(&[3; fvm_shared::address::SECP_PUB_LEN]).unwrap();
+    let extensions = DealExtensions { notify: Some(notify), metadata: None };
+
+    publish_deals_with_extensions_expect_abort(
+        &rt,
+        &addrs,
+        deal,
+        extensions,
+        ExitCode::USR_ILLEGAL_ARGUMENT,
+    );
+    check_state(&rt);
+}
@@ -29,18 +29,24 @@ use fil_actor_market::ext::miner::{
 };
 use fil_actor_market::ext::verifreg::{AllocationID, AllocationRequest, AllocationsResponse};
 use fil_actor_market::{
-    deal_cid, deal_get_payment_remaining, BatchActivateDealsParams, BatchActivateDealsResult,
-    DealOpsByEpoch, PendingDealAllocationsMap, PendingProposalsSet, ProviderSectorsMap,
-    SectorDealsMap, SettleDealPaymentsParams, SettleDealPaymentsReturn, PENDING_ALLOCATIONS_CONFIG,
-    PENDING_PROPOSALS_CONFIG, PROVIDER_SECTORS_CONFIG, SECTOR_DEALS_CONFIG,
+    deal_cid, deal_get_payment_remaining, AddBalanceForParams, AmendDealTermsParams,
+    AmendDealTermsReturn, BatchActivateDealsParams, BatchActivateDealsResult,
+    ClientDealProposalWithExtensions, DealAmendment, DealExtensions, DealOpsByEpoch, DealOutcome,
+    MarketNotifyDealOutcomeParams, PendingDealAllocationsMap, PendingProposalsSet,
+    ProviderSectorsMap, PublishStorageDealsWithExtensionsParams, ReclaimAllowanceParams,
+    ReclaimAllowanceReturn, SectorDealsMap, SettleDealPaymentsParams, SettleDealPaymentsReturn,
+    MARKET_NOTIFY_DEAL_OUTCOME_METHOD, PENDING_ALLOCATIONS_CONFIG, PENDING_PROPOSALS_CONFIG,
+    PROVIDER_SECTORS_CONFIG, SECTOR_DEALS_CONFIG, SIGNATURE_DOMAIN_SEPARATION_DEAL_EXTENSIONS,
 };
 use fil_actor_market::{
     ext, ext::miner::GetControlAddressesReturnParams, next_update_epoch,
-    testing::check_state_invariants, Actor as MarketActor, ClientDealProposal, DealArray,
-    DealMetaArray, DealProposal, DealState, GetBalanceReturn, Label, MarketNotifyDealParams,
-    Method, OnMinerSectorsTerminateParams, PublishStorageDealsParams, PublishStorageDealsReturn,
-    SectorDeals, State, VerifyDealsForActivationParams, VerifyDealsForActivationReturn,
-    WithdrawBalanceParams, WithdrawBalanceReturn, MARKET_NOTIFY_DEAL_METHOD, NO_ALLOCATION_ID,
+    policy::DEAL_NOTIFICATION_GAS_LIMIT, testing::check_state_invariants, Actor as MarketActor,
+    ClientDealProposal, ClientPieceManifest, DealArray, DealMetaArray, DealProposal, DealState,
+    GetBalanceReturn, Label, MarketNotifyDealParams, Method, OnMinerSectorsTerminateParams,
+    PieceManifest, PublishStorageDealsParams, PublishStorageDealsReturn, SectorDeals,
+    SectorPieceManifests, State, VerifyDealsForActivationDirectParams,
+    VerifyDealsForActivationParams, VerifyDealsForActivationReturn, WithdrawBalanceParams,
+    WithdrawBalanceReturn, MARKET_NOTIFY_DEAL_METHOD, NO_ALLOCATION_ID,
 };
 use fil_actor_power::{CurrentTotalPowerReturn, Method as PowerMethod};
 use fil_actor_reward::Method as RewardMethod;
@@ -133,15 +139,15 @@ pub fn assert_deal_ops_clean(rt: &MockRuntime) {
         .unwrap();
 
     let deal_ops = st.load_deal_ops(rt.store()).unwrap();
-    deal_ops.for_each(|epoch, _| {
-        deal_ops
-            .for_each_in(&epoch, |deal_id| {
-                assert!(proposal_set.contains(&deal_id), "deal op found for deal id {deal_id} with missing proposal at epoch {epoch}");
-                Ok(())
-            })
-            .unwrap();
-        Ok(())
-    }).unwrap();
+    deal_ops
+        .for_each(|epoch, deal_id| {
+            assert!(
+                proposal_set.contains(&deal_id),
+                "deal op found for deal id {deal_id} with missing proposal at epoch {epoch}"
+            );
+            Ok(())
+        })
+        .unwrap();
 }
 
 /// Checks internal invariants of market state asserting none of them are broken.
@@ -283,9 +289,14 @@ pub fn withdraw_provider_balance(
     rt.expect_validate_caller_addr(vec![owner, worker]);
     expect_provider_control_address(rt, provider, owner, worker);
 
-    let params = WithdrawBalanceParams { provider_or_client: provider, amount: withdraw_amount };
+    let params = WithdrawBalanceParams {
+        provider_or_client: provider,
+        amount: withdraw_amount,
+        payee: None,
+    };
 
     rt.expect_send_simple(owner, METHOD_SEND, None, expected_send.clone(), None, ExitCode::OK);
+    expect_balance_withdrawn(rt, provider, owner, &expected_send);
     let ret: WithdrawBalanceReturn = rt
         .call::<MarketActor>(
             Method::WithdrawBalance as u64,
@@ -313,8 +324,10 @@ pub fn withdraw_client_balance(
     rt.set_caller(*ACCOUNT_ACTOR_CODE_ID, client);
     rt.expect_send_simple(client, METHOD_SEND, None, expected_send.clone(), None, ExitCode::OK);
     rt.expect_validate_caller_addr(vec![client]);
+    expect_balance_withdrawn(rt, client, client, &expected_send);
 
-    let params = WithdrawBalanceParams { provider_or_client: client, amount: withdraw_amount };
+    let params =
+        WithdrawBalanceParams { provider_or_client: client, amount: withdraw_amount, payee: None };
 
     let ret: WithdrawBalanceReturn = rt
         .call::<MarketActor>(
@@ -334,6 +347,73 @@ pub fn withdraw_client_balance(
     );
 }
 
+pub fn expect_balance_withdrawn(
+    rt: &MockRuntime,
+    account: Address,
+    payee: Address,
+    amount: &TokenAmount,
+) {
+    rt.expect_emitted_event(
+        EventBuilder::new()
+            .typ("balance-withdrawn")
+            .field_indexed("account", &account.id().unwrap())
+            .field_indexed("payee", &payee)
+            .field("amount", amount)
+            .build()
+            .unwrap(),
+    );
+}
+
+pub fn add_balance_for(
+    rt: &MockRuntime,
+    funder: Address,
+    beneficiary: Address,
+    amount: TokenAmount,
+) {
+    rt.set_received(amount.clone());
+    rt.set_caller(*EVM_ACTOR_CODE_ID, funder);
+    rt.expect_validate_caller_any();
+
+    let params = AddBalanceForParams { beneficiary };
+    assert!(rt
+        .call::<MarketActor>(
+            Method::AddBalanceForExported as u64,
+            IpldBlock::serialize_cbor(&params).unwrap(),
+        )
+        .unwrap()
+        .is_none());
+    rt.verify();
+    rt.add_balance(amount);
+}
+
+pub fn reclaim_allowance(
+    rt: &MockRuntime,
+    funder: Address,
+    beneficiary: Address,
+    amount: TokenAmount,
+    expected_send: TokenAmount,
+) {
+    rt.set_caller(*EVM_ACTOR_CODE_ID, funder);
+    rt.expect_validate_caller_any();
+    if expected_send.is_positive() {
+        rt.expect_send_simple(funder, METHOD_SEND, None, expected_send.clone(), None, ExitCode::OK);
+    }
+
+    let params = ReclaimAllowanceParams { beneficiary, amount };
+    let ret: ReclaimAllowanceReturn = rt
+        .call::<MarketActor>(
+            Method::ReclaimAllowanceExported as u64,
+            IpldBlock::serialize_cbor(&params).unwrap(),
+        )
+        .unwrap()
+        .unwrap()
+        .deserialize()
+        .unwrap();
+    rt.verify();
+
+    assert_eq!(expected_send, ret.amount_reclaimed);
+}
+
 pub fn create_deal(
     rt: &MockRuntime,
     client_addr: Address,
@@ -706,6 +786,37 @@ pub fn publish_deals(
     publish_deals: &[DealProposal],
     clients_datacap_balance: TokenAmount,
     next_allocation_id: AllocationID,
+) -> Vec<DealID> {
+    publish_deals_inner(rt, addrs, publish_deals, None, clients_datacap_balance, next_allocation_id)
+}
+
+/// Publishes deals through PublishStorageDealsWithExtensions, with the extensions at the same
+/// index as each deal.
+pub fn publish_deals_with_extensions(
+    rt: &MockRuntime,
+    addrs: &MinerAddresses,
+    publish_deals: &[DealProposal],
+    extensions: &[DealExtensions],
+    clients_datacap_balance: TokenAmount,
+    next_allocation_id: AllocationID,
+) -> Vec<DealID> {
+    publish_deals_inner(
+        rt,
+        addrs,
+        publish_deals,
+        Some(extensions),
+        clients_datacap_balance,
+        next_allocation_id,
+    )
+}
+
+fn publish_deals_inner(
+    rt: &MockRuntime,
+    addrs: &MinerAddresses,
+    publish_deals: &[DealProposal],
+    extensions: Option<&[DealExtensions]>,
+    clients_datacap_balance: TokenAmount,
+    next_allocation_id: AllocationID,
 ) -> Vec<DealID> {
     let st: State = rt.get_state();
     let next_deal_id = st.next_id;
@@ -726,6 +837,7 @@ pub fn publish_deals(
     expect_query_network_info(rt);
 
     let mut params: PublishStorageDealsParams = PublishStorageDealsParams { deals: vec![] };
+    let mut params_with_extensions = PublishStorageDealsWithExtensionsParams { deals: vec![] };
 
     // Accumulate proposals by client, so we can set expectations for the per-client calls
     //  per-deal calls. This matches flow in the market actor.
@@ -737,13 +849,27 @@ pub fn publish_deals(
     let mut client_verified_deals: BTreeMap<ActorID, ClientVerifiedDeals> = BTreeMap::new();
     let mut alloc_id = next_allocation_id;
     let mut valid_deals = vec![];
-    for deal in publish_deals {
+    for (i, deal) in publish_deals.iter().enumerate() {
         // create a client proposal with a valid signature
-        let buf = RawBytes::serialize(deal.clone()).expect("failed to marshal deal proposal");
         let sig = Signature::new_bls("does not matter".as_bytes().to_vec());
-        let client_proposal =
-            ClientDealProposal { proposal: deal.clone(), client_signature: sig.clone() };
-        params.deals.push(client_proposal);
+        let signed_bytes = match extensions {
+            None => {
+                let client_proposal =
+                    ClientDealProposal { proposal: deal.clone(), client_signature: sig.clone() };
+                params.deals.push(client_proposal);
+                RawBytes::serialize(deal.clone()).expect("failed to marshal deal proposal").to_vec()
+            }
+            Some(extensions) => {
+                let client_proposal = ClientDealProposalWithExtensions {
+                    proposal: deal.clone(),
+                    extensions: extensions[i].clone(),
+                    client_signature: sig.clone(),
+                };
+                params_with_extensions.deals.push(client_proposal);
+                let bz = to_vec(&(deal, &extensions[i])).expect("failed to marshal deal proposal");
+                [SIGNATURE_DOMAIN_SEPARATION_DEAL_EXTENSIONS, &bz].concat()
+            }
+        };
 
         // Expect an invocation of authenticate_message to verify the signature.
         rt.expect_send(
@@ -751,7 +877,7 @@ pub fn publish_deals(
             ext::account::AUTHENTICATE_MESSAGE_METHOD,
             IpldBlock::serialize_cbor(&AuthenticateMessageParams {
                 signature: "does not matter".as_bytes().to_vec(),
-                message: buf.to_vec(),
+                message: signed_bytes,
             })
             .unwrap(),
             TokenAmount::zero(),
@@ -871,15 +997,15 @@ pub fn publish_deals(
         deal_id += 1;
     }
 
-    let ret: PublishStorageDealsReturn = rt
-        .call::<MarketActor>(
-            Method::PublishStorageDeals as u64,
-            IpldBlock::serialize_cbor(&params).unwrap(),
-        )
-        .unwrap()
-        .unwrap()
-        .deserialize()
-        .unwrap();
+    let (method, params) = match extensions {
+        None => (Method::PublishStorageDeals as u64, IpldBlock::serialize_cbor(&params).unwrap()),
+        Some(_) => (
+            Method::PublishStorageDealsWithExtensionsExported as u64,
+            IpldBlock::serialize_cbor(&params_with_extensions).unwrap(),
+        ),
+    };
+    let ret: PublishStorageDealsReturn =
+        rt.call::<MarketActor>(method, params).unwrap().unwrap().deserialize().unwrap();
     rt.verify();
 
     // assert state after publishing the deals
@@ -944,6 +1070,57 @@ pub fn publish_deals_expect_abort(
     rt.verify();
 }
 
+pub fn publish_deals_with_extensions_expect_abort(
+    rt: &MockRuntime,
+    miner_addresses: &MinerAddresses,
+    proposal: DealProposal,
+    extensions: DealExtensions,
+    expected_exit_code: ExitCode,
+) {
+    rt.expect_validate_caller_any();
+    expect_provider_is_control_address(rt, miner_addresses.provider, WORKER_ADDR, true);
+    expect_query_network_info(rt);
+
+    let signed_bytes = [
+        SIGNATURE_DOMAIN_SEPARATION_DEAL_EXTENSIONS,
+        &to_vec(&(&proposal, &extensions)).expect("failed to marshal deal proposal"),
+    ]
+    .concat();
+    rt.expect_send(
+        proposal.client,
+        AUTHENTICATE_MESSAGE_METHOD,
+        IpldBlock::serialize_cbor(&AuthenticateMessageParams {
+            signature: "does not matter".as_bytes().to_vec(),
+            message: signed_bytes,
+        })
+        .unwrap(),
+        TokenAmount::zero(),
+        None,
+        SendFlags::READ_ONLY,
+        AUTHENTICATE_MESSAGE_RESPONSE.clone(),
+        ExitCode::OK,
+        None,
+    );
+
+    rt.set_caller(*ACCOUNT_ACTOR_CODE_ID, WORKER_ADDR);
+    let params = PublishStorageDealsWithExtensionsParams {
+        deals: vec![ClientDealProposalWithExtensions {
+            proposal,
+            extensions,
+            client_signature: Signature::new_bls("does not matter".as_bytes().to_vec()),
+        }],
+    };
+    expect_abort(
+        expected_exit_code,
+        rt.call::<MarketActor>(
+            Method::PublishStorageDealsWithExtensionsExported as u64,
+            IpldBlock::serialize_cbor(&params).unwrap(),
+        ),
+    );
+
+    rt.verify();
+}
+
 pub fn settle_deal_payments(
     rt: &MockRuntime,
     caller: Address,
@@ -991,6 +1168,56 @@ pub fn settle_deal_payments(
     res
 }
 
+/// Submits a deal amendment from the provider's worker, with a client signature
+/// that is expected to be accepted.
+pub fn amend_deal_terms(
+    rt: &MockRuntime,
+    addrs: &MinerAddresses,
+    amendment: DealAmendment,
+) -> Result<AmendDealTermsReturn, ActorError> {
+    let proposal = get_deal_proposal(rt, amendment.deal_id);
+    rt.set_caller(*ACCOUNT_ACTOR_CODE_ID, addrs.worker);
+    rt.expect_validate_caller_any();
+    expect_provider_is_control_address(rt, addrs.provider, addrs.worker, true);
+    rt.expect_send(
+        proposal.client,
+        AUTHENTICATE_MESSAGE_METHOD,
+        IpldBlock::serialize_cbor(&AuthenticateMessageParams {
+            signature: "does not matter".as_bytes().to_vec(),
+            message: serialize(&amendment, "deal amendment").unwrap().to_vec(),
+        })
+        .unwrap(),
+        TokenAmount::zero(),
+        None,
+        SendFlags::READ_ONLY,
+        AUTHENTICATE_MESSAGE_RESPONSE.clone(),
+        ExitCode::OK,
+        None,
+    );
+    rt.expect_emitted_event(
+        EventBuilder::new()
+            .typ("deal-amended")
+            .field_indexed("id", &amendment.deal_id)
+            .field_indexed("client", &proposal.client.id().unwrap())
+            .field_indexed("provider", &proposal.provider.id().unwrap())
+            .field("price-per-epoch", &amendment.storage_price_per_epoch)
+            .field("end-epoch", &amendment.end_epoch)
+            .build()
+            .unwrap(),
+    );
+
+    let params = AmendDealTermsParams {
+        amendment,
+        client_signature: Signature::new_bls("does not matter".as_bytes().to_vec()),
+    };
+    let ret = rt.call::<MarketActor>(
+        Method::AmendDealTermsExported as u64,
+        IpldBlock::serialize_cbor(&params).unwrap(),
+    )?;
+    rt.verify();
+    Ok(ret.unwrap().deserialize().unwrap())
+}
+
 pub fn settle_deal_payments_no_change(
     rt: &MockRuntime,
     caller: Address,
@@ -1152,6 +1379,7 @@ pub fn expect_query_network_info(rt: &MockRuntime) {
         quality_adj_power_smoothed: FilterEstimate::new(reward.atto().clone(), BigInt::zero()),
         ramp_start_epoch: 0,
         ramp_duration_epochs: 0,
+        proof_type_allowlist: None,
     };
     let current_reward = ThisEpochRewardReturn {
         this_epoch_baseline_power: power,
@@ -1184,7 +1412,7 @@ pub fn assert_n_good_deals<BS>(
     BS: fvm_ipld_blockstore::Blockstore,
 {
     let mut count = 0;
-    dobe.for_each_in(&epoch, |id| {
+    dobe.for_each_in(epoch, |id| {
         assert_eq!(epoch % updates_interval, (id as i64) % updates_interval);
         count += 1;
         Ok(())
@@ -1697,6 +1925,28 @@ pub fn terminate_deals_raw(
     )
 }
 
+/// Expects a deal lifecycle notification to the deal's notification actor,
+/// which responds with `exit_code`.
+pub fn expect_deal_outcome_notification(
+    rt: &MockRuntime,
+    notify: Address,
+    deal_id: DealID,
+    outcome: DealOutcome,
+    exit_code: ExitCode,
+) {
+    rt.expect_send(
+        notify,
+        MARKET_NOTIFY_DEAL_OUTCOME_METHOD,
+        IpldBlock::serialize_cbor(&MarketNotifyDealOutcomeParams { deal_id, outcome }).unwrap(),
+        TokenAmount::zero(),
+        Some(DEAL_NOTIFICATION_GAS_LIMIT),
+        SendFlags::default(),
+        None,
+        exit_code,
+        None,
+    );
+}
+
 pub fn assert_account_zero(rt: &MockRuntime, addr: Address) {
     let account = get_balance(rt, &addr);
     assert!(account.balance.is_zero());
@@ -1744,6 +1994,54 @@ where
     ret
 }
 
+/// Builds a piece manifest from the client to the provider with a placeholder signature,
+/// which the client's account is expected to accept.
+pub fn signed_piece_manifest(
+    client: Address,
+    provider: Address,
+    pieces: Vec<fvm_shared::piece::PieceInfo>,
+    expiration: ChainEpoch,
+) -> ClientPieceManifest {
+    ClientPieceManifest {
+        manifest: PieceManifest { client, provider, pieces, expiration },
+        client_signature: Signature::new_bls("does not matter".as_bytes().to_vec()),
+    }
+}
+
+pub fn expect_manifest_authentication(rt: &MockRuntime, signed: &ClientPieceManifest) {
+    rt.expect_send(
+        signed.manifest.client,
+        AUTHENTICATE_MESSAGE_METHOD,
+        IpldBlock::serialize_cbor(&AuthenticateMessageParams {
+            signature: signed.client_signature.bytes.clone(),
+            message: serialize(&signed.manifest, "piece manifest").unwrap().to_vec(),
+        })
+        .unwrap(),
+        TokenAmount::zero(),
+        None,
+        SendFlags::READ_ONLY,
+        AUTHENTICATE_MESSAGE_RESPONSE.clone(),
+        ExitCode::OK,
+        None,
+    );
+}
+
+pub fn verify_deals_for_activation_direct(
+    rt: &MockRuntime,
+    provider: Address,
+    sectors: Vec<SectorPieceManifests>,
+) -> Result<VerifyDealsForActivationReturn, ActorError> {
+    rt.set_caller(*MINER_ACTOR_CODE_ID, provider);
+    rt.expect_validate_caller_type(vec![Type::Miner]);
+    let params = VerifyDealsForActivationDirectParams { sectors };
+    let ret = rt.call::<MarketActor>(
+        Method::VerifyDealsForActivationDirectExported as u64,
+        IpldBlock::serialize_cbor(&params).unwrap(),
+    )?;
+    rt.verify();
+    Ok(ret.unwrap().deserialize().unwrap())
+}
+
 // market cron tick uses last_updated_epoch == EPOCH_UNDEFINED to determine if a deal is new
 // it will not process such deals
 // however, for testing we need to simulate deals that are already in the system that should be
@@ -4,6 +4,8 @@
 use std::cell::RefCell;
 use std::ops::Add;
 
+use cid::multihash::Multihash;
+use cid::Cid;
 use frc46_token::token::types::{TransferFromParams, TransferFromReturn};
 use fvm_ipld_amt::Amt;
 use fvm_ipld_encoding::ipld_block::IpldBlock;
@@ -17,19 +19,21 @@ use fvm_shared::error::ExitCode;
 use fvm_shared::piece::PaddedPieceSize;
 use fvm_shared::sector::{RegisteredSealProof, StoragePower};
 use fvm_shared::sys::SendFlags;
-use fvm_shared::{MethodNum, METHOD_CONSTRUCTOR, METHOD_SEND};
+use fvm_shared::{MethodNum, IDENTITY_HASH, IPLD_RAW, METHOD_CONSTRUCTOR, METHOD_SEND};
 use num_traits::{FromPrimitive, Zero};
 use regex::Regex;
 
 use fil_actor_market::balance_table::BalanceTable;
 use fil_actor_market::ext::account::{AuthenticateMessageParams, AUTHENTICATE_MESSAGE_METHOD};
 use fil_actor_market::ext::verifreg::{AllocationRequest, AllocationsResponse};
-use fil_actor_market::policy::detail::DEAL_MAX_LABEL_SIZE;
+use fil_actor_market::migration::LegacyState;
+use fil_actor_market::policy::detail::{DEAL_MAX_LABEL_SIZE, DEAL_MAX_METADATA_CID_SIZE};
 use fil_actor_market::{
     ext, Actor as MarketActor, BatchActivateDealsResult, ClientDealProposal, DealArray,
-    DealMetaArray, DealOpsByEpoch, Label, MarketNotifyDealParams, Method,
-    PendingDealAllocationsMap, PendingProposalsSet, PublishStorageDealsParams,
-    PublishStorageDealsReturn, SectorDeals, State, WithdrawBalanceParams, DEAL_OPS_BY_EPOCH_CONFIG,
+    DealExtensions, DealMetaArray, DealOpsByEpoch, DealProposal, Label, MarketNotifyDealParams,
+    Method, PendingDealAllocationsMap, PendingProposalsSet, PublishStorageDealsParams,
+    PublishStorageDealsReturn, SectorDeals, State, WithdrawBalanceMultiParams,
+    WithdrawBalanceMultiReturn, WithdrawBalanceParams, WithdrawalTarget, DEAL_OPS_BY_EPOCH_CONFIG,
     EX_DEAL_EXPIRED, MARKET_NOTIFY_DEAL_METHOD, PENDING_ALLOCATIONS_CONFIG,
     PENDING_PROPOSALS_CONFIG, PROPOSALS_AMT_BITWIDTH, STATES_AMT_BITWIDTH,
 };
@@ -181,6 +185,59 @@ fn label_non_utf8() {
     out.expect_err("invalid cbor string shouldn't deser");
 }
 
+#[test]
+fn deal_proposal_and_extensions_cbor() {
+    let proposal = DealProposal {
+        piece_cid: Cid::new_v1(IPLD_RAW, Multihash::wrap(IDENTITY_HASH, b"piece").unwrap()),
+        piece_size: PaddedPieceSize(2048),
+        verified_deal: false,
+        client: Address::new_id(101),
+        provider: Address::new_id(102),
+        label: Label::String("label".to_string()),
+        start_epoch: 100,
+        end_epoch: 200,
+        storage_price_per_epoch: TokenAmount::from_atto(10),
+        provider_collateral: TokenAmount::zero(),
+        client_collateral: TokenAmount::from_atto(256),
+    };
+    // The proposal encoding is unchanged by deal extensions.
+    let expected = [
+        &[0x8b][..],
+        // piece_cid
+        &[0xd8, 0x2a, 0x4a, 0x00, 0x01, 0x55, 0x00, 0x05],
+        b"piece",
+        // piece_size, verified_deal, client, provider
+        &[0x19, 0x08, 0x00, 0xf4, 0x42, 0x00, 0x65, 0x42, 0x00, 0x66],
+        // label
+        &[0x65],
+        b"label",
+        // start_epoch, end_epoch
+        &[0x18, 0x64, 0x18, 0xc8],
+        // storage_price_per_epoch, provider_collateral, client_collateral
+        &[0x42, 0x00, 0x0a, 0x40, 0x43, 0x00, 0x01, 0x00],
+    ]
+    .concat();
+    assert_eq!(expected, to_vec(&proposal).unwrap());
+    assert_eq!(
+        proposal,
+        deserialize::<DealProposal>(&RawBytes::from(expected), "proposal").unwrap()
+    );
+
+    assert_eq!(vec![0x82, 0xf6, 0xf6], to_vec(&DealExtensions::default()).unwrap());
+    let extensions = DealExtensions {
+        notify: Some(Address::new_id(103)),
+        metadata: Some(Cid::new_v1(IPLD_RAW, Multihash::wrap(IDENTITY_HASH, b"sla").unwrap())),
+    };
+    let expected =
+        [&[0x82, 0x42, 0x00, 0x67, 0xd8, 0x2a, 0x48, 0x00, 0x01, 0x55, 0x00, 0x03][..], b"sla"]
+            .concat();
+    assert_eq!(expected, to_vec(&extensions).unwrap());
+    assert_eq!(
+        extensions,
+        deserialize::<DealExtensions>(&RawBytes::from(expected), "extensions").unwrap()
+    );
+}
+
 #[test]
 fn adds_to_provider_escrow_funds() {
     struct TestCase {
@@ -233,6 +290,7 @@ fn fails_if_withdraw_from_non_provider_funds_is_not_initiated_by_the_recipient()
     let params = WithdrawBalanceParams {
         provider_or_client: CLIENT_ADDR,
         amount: TokenAmount::from_atto(1u8),
+        payee: None,
     };
 
     // caller is not the recipient
@@ -318,6 +376,124 @@ fn balance_after_withdrawal_must_always_be_greater_than_or_equal_to_locked_amoun
     check_state(&rt);
 }
 
+#[test]
+fn provider_owner_withdraws_balance_to_multiple_recipients() {
+    let rt = setup();
+    add_provider_funds(&rt, TokenAmount::from_atto(30u8), &MinerAddresses::default());
+
+    let recipient_a = Address::new_id(1001);
+    let recipient_b = Address::new_id(1002);
+    let params = WithdrawBalanceMultiParams {
+        provider: PROVIDER_ADDR,
+        withdrawals: vec![
+            WithdrawalTarget { recipient: recipient_a, amount: TokenAmount::from_atto(10u8) },
+            WithdrawalTarget { recipient: recipient_b, amount: TokenAmount::from_atto(15u8) },
+        ],
+    };
+
+    rt.set_caller(*ACCOUNT_ACTOR_CODE_ID, OWNER_ADDR);
+    expect_provider_control_address(&rt, PROVIDER_ADDR, OWNER_ADDR, WORKER_ADDR);
+    rt.expect_validate_caller_addr(vec![OWNER_ADDR]);
+    rt.expect_send_simple(
+        recipient_a,
+        METHOD_SEND,
+        None,
+        TokenAmount::from_atto(10u8),
+        None,
+        ExitCode::OK,
+    );
+    rt.expect_send_simple(
+        recipient_b,
+        METHOD_SEND,
+        None,
+        TokenAmount::from_atto(15u8),
+        None,
+        ExitCode::OK,
+    );
+    let ret: WithdrawBalanceMultiReturn = rt
+        .call::<MarketActor>(
+            Method::WithdrawBalanceMultiExported as u64,
+            IpldBlock::serialize_cbor(&params).unwrap(),
+        )
+        .unwrap()
+        .unwrap()
+        .deserialize()
+        .unwrap();
+    rt.verify();
+
+    assert_eq!(TokenAmount::from_atto(25u8), ret.amount_withdrawn);
+    assert_eq!(TokenAmount::from_atto(5u8), get_balance(&rt, &PROVIDER_ADDR).balance);
+    check_state(&rt);
+}
+
+#[test]
+fn withdraw_balance_multi_fails_beyond_unlocked_balance_or_from_worker() {
+    let start_epoch = ChainEpoch::from(10);
+    let end_epoch = start_epoch + 200 * EPOCHS_IN_DAY;
+    let rt = setup();
+    rt.set_epoch(5);
+    let (_, deal) = generate_and_publish_deal(
+        &rt,
+        CLIENT_ADDR,
+        &MinerAddresses::default(),
+        start_epoch,
+        end_epoch,
+    );
+    add_provider_funds(&rt, TokenAmount::from_atto(20u8), &MinerAddresses::default());
+    let balance = &deal.provider_collateral + TokenAmount::from_atto(20u8);
+
+    let withdraw = |amounts: &[u8]| WithdrawBalanceMultiParams {
+        provider: PROVIDER_ADDR,
+        withdrawals: amounts
+            .iter()
+            .enumerate()
+            .map(|(i, amount)| WithdrawalTarget {
+                recipient: Address::new_id(1001 + i as u64),
+                amount: TokenAmount::from_atto(*amount),
+            })
+            .collect(),
+    };
+
+    // Each amount is available alone, but not the total.
+    rt.set_caller(*ACCOUNT_ACTOR_CODE_ID, OWNER_ADDR);
+    expect_provider_control_address(&rt, PROVIDER_ADDR, OWNER_ADDR, WORKER_ADDR);
+    rt.expect_validate_caller_addr(vec![OWNER_ADDR]);
+    expect_abort(
+        ExitCode::USR_INSUFFICIENT_FUNDS,
+        rt.call::<MarketActor>(
+            Method::WithdrawBalanceMultiExported as u64,
+            IpldBlock::serialize_cbor(&withdraw(&[15, 10])).unwrap(),
+        ),
+    );
+    rt.verify();
+
+    // Amounts must be positive.
+    expect_abort(
+        ExitCode::USR_ILLEGAL_ARGUMENT,
+        rt.call::<MarketActor>(
+            Method::WithdrawBalanceMultiExported as u64,
+            IpldBlock::serialize_cbor(&withdraw(&[10, 0])).unwrap(),
+        ),
+    );
+    rt.verify();
+
+    // The worker may not choose recipients.
+    rt.set_caller(*ACCOUNT_ACTOR_CODE_ID, WORKER_ADDR);
+    expect_provider_control_address(&rt, PROVIDER_ADDR, OWNER_ADDR, WORKER_ADDR);
+    rt.expect_validate_caller_addr(vec![OWNER_ADDR]);
+    expect_abort(
+        ExitCode::USR_FORBIDDEN,
+        rt.call::<MarketActor>(
+            Method::WithdrawBalanceMultiExported as u64,
+            IpldBlock::serialize_cbor(&withdraw(&[10])).unwrap(),
+        ),
+    );
+    rt.verify();
+
+    assert_eq!(balance, get_balance(&rt, &PROVIDER_ADDR).balance);
+    check_state(&rt);
+}
+
 #[test]
 fn worker_balance_after_withdrawal_must_account_for_slashed_funds() {
     let start_epoch = ChainEpoch::from(10);
@@ -450,6 +626,121 @@ fn withdraws_from_non_provider_escrow_funds() {
     check_state(&rt);
 }
 
+#[test]
+fn funder_reclaims_unspent_allowance() {
+    let rt = setup();
+    let funder = Address::new_id(1234);
+    let other_funder = Address::new_id(1235);
+
+    add_balance_for(&rt, funder, CLIENT_ADDR, TokenAmount::from_atto(100));
+    add_participant_funds(&rt, CLIENT_ADDR, TokenAmount::from_atto(20));
+    assert_eq!(get_balance(&rt, &CLIENT_ADDR).balance, TokenAmount::from_atto(120));
+
+    // The client controls its escrow, including funds deposited for it.
+    withdraw_client_balance(
+        &rt,
+        TokenAmount::from_atto(50),
+        TokenAmount::from_atto(50),
+        CLIENT_ADDR,
+    );
+
+    // Reclaim is limited by the allowance.
+    reclaim_allowance(
+        &rt,
+        funder,
+        CLIENT_ADDR,
+        TokenAmount::from_atto(30),
+        TokenAmount::from_atto(30),
+    );
+    assert_eq!(get_balance(&rt, &CLIENT_ADDR).balance, TokenAmount::from_atto(40));
+
+    // A caller that deposited nothing reclaims nothing.
+    reclaim_allowance(
+        &rt,
+        other_funder,
+        CLIENT_ADDR,
+        TokenAmount::from_atto(10),
+        TokenAmount::zero(),
+    );
+
+    // Reclaim is limited by the beneficiary's remaining escrow.
+    reclaim_allowance(
+        &rt,
+        funder,
+        CLIENT_ADDR,
+        TokenAmount::from_atto(100),
+        TokenAmount::from_atto(40),
+    );
+    assert!(get_balance(&rt, &CLIENT_ADDR).balance.is_zero());
+
+    // The remaining allowance can't be reclaimed from the client's own later deposit.
+    add_participant_funds(&rt, CLIENT_ADDR, TokenAmount::from_atto(50));
+    reclaim_allowance(&rt, funder, CLIENT_ADDR, TokenAmount::from_atto(100), TokenAmount::zero());
+    assert_eq!(get_balance(&rt, &CLIENT_ADDR).balance, TokenAmount::from_atto(50));
+    check_state(&rt);
+}
+
+#[test]
+fn reclaim_is_limited_to_unspent_funded_amount() {
+    let rt = setup();
+    let funder = Address::new_id(1234);
+
+    add_participant_funds(&rt, CLIENT_ADDR, TokenAmount::from_atto(30));
+    add_balance_for(&rt, funder, CLIENT_ADDR, TokenAmount::from_atto(100));
+
+    // The client's own deposit is spent before the funded amount.
+    withdraw_client_balance(
+        &rt,
+        TokenAmount::from_atto(90),
+        TokenAmount::from_atto(90),
+        CLIENT_ADDR,
+    );
+    assert_eq!(get_balance(&rt, &CLIENT_ADDR).balance, TokenAmount::from_atto(40));
+
+    // A later deposit by the client doesn't replenish the funded amount.
+    add_participant_funds(&rt, CLIENT_ADDR, TokenAmount::from_atto(60));
+    reclaim_allowance(
+        &rt,
+        funder,
+        CLIENT_ADDR,
+        TokenAmount::from_atto(100),
+        TokenAmount::from_atto(40),
+    );
+    assert_eq!(get_balance(&rt, &CLIENT_ADDR).balance, TokenAmount::from_atto(60));
+    check_state(&rt);
+}
+
+#[test]
+fn upgrade_migrates_legacy_state() {
+    let rt = setup();
+    add_participant_funds(&rt, CLIENT_ADDR, TokenAmount::from_atto(20));
+    let st: State = rt.get_state();
+    rt.replace_state(&LegacyState {
+        proposals: st.proposals,
+        states: st.states,
+        pending_proposals: st.pending_proposals,
+        escrow_table: st.escrow_table,
+        locked_table: st.locked_table,
+        next_id: st.next_id,
+        deal_ops_by_epoch: st.deal_ops_by_epoch,
+        last_cron: st.last_cron,
+        total_client_locked_collateral: st.total_client_locked_collateral.clone(),
+        total_provider_locked_collateral: st.total_provider_locked_collateral.clone(),
+        total_client_storage_fee: st.total_client_storage_fee.clone(),
+        pending_deal_allocation_ids: st.pending_deal_allocation_ids,
+        provider_sectors: st.provider_sectors,
+    });
+    rt.upgrade::<MarketActor>();
+    let migrated: State = rt.get_state();
+    assert_eq!(st.escrow_table, migrated.escrow_table);
+    assert_eq!(st.allowance_table, migrated.allowance_table);
+    assert_eq!(st.funded_table, migrated.funded_table);
+    assert_eq!(st.terminated_sectors, migrated.terminated_sectors);
+    assert_eq!(st.cron_stats, migrated.cron_stats);
+    assert_eq!(TokenAmount::from_atto(20), get_balance(&rt, &CLIENT_ADDR).balance);
+    check_state(&rt);
+}
+
 #[test]
 fn client_withdrawing_more_than_escrow_balance_limits_to_available_funds() {
     let rt = setup();
@@ -507,6 +798,7 @@ fn fails_with_a_negative_withdraw_amount() {
     let params = WithdrawBalanceParams {
         provider_or_client: PROVIDER_ADDR,
         amount: TokenAmount::from_atto(-1_i32),
+        payee: None,
     };
 
     expect_abort(
@@ -534,6 +826,7 @@ fn fails_if_withdraw_from_provider_funds_is_not_initiated_by_the_owner_or_worker
     let params = WithdrawBalanceParams {
         provider_or_client: PROVIDER_ADDR,
         amount: TokenAmount::from_atto(1u8),
+        payee: None,
     };
 
     // caller is not owner or worker
@@ -554,6 +847,69 @@ fn fails_if_withdraw_from_provider_funds_is_not_initiated_by_the_owner_or_worker
     check_state(&rt);
 }
 
+#[test]
+fn withdraw_to_payee_designated_by_escrow_owner() {
+    let rt = setup();
+    let payee = Address::new_id(909);
+    let amount = TokenAmount::from_atto(20u8);
+    add_provider_funds(&rt, amount.clone(), &MinerAddresses::default());
+    add_participant_funds(&rt, CLIENT_ADDR, amount.clone());
+
+    let withdraw = |caller: Address, provider_or_client: Address| {
+        rt.set_caller(*ACCOUNT_ACTOR_CODE_ID, caller);
+        rt.call::<MarketActor>(
+            Method::WithdrawBalance as u64,
+            IpldBlock::serialize_cbor(&WithdrawBalanceParams {
+                provider_or_client,
+                amount: TokenAmount::from_atto(5u8),
+                payee: Some(payee),
+            })
+            .unwrap(),
+        )
+    };
+
+    // a provider's worker may withdraw, but only to the owner
+    rt.expect_validate_caller_addr(vec![OWNER_ADDR, WORKER_ADDR]);
+    expect_provider_control_address(&rt, PROVIDER_ADDR, OWNER_ADDR, WORKER_ADDR);
+    expect_abort_contains_message(
+        ExitCode::USR_FORBIDDEN,
+        "may designate a payee",
+        withdraw(WORKER_ADDR, PROVIDER_ADDR),
+    );
+    rt.verify();
+
+    rt.expect_validate_caller_addr(vec![OWNER_ADDR, WORKER_ADDR]);
+    expect_provider_control_address(&rt, PROVIDER_ADDR, OWNER_ADDR, WORKER_ADDR);
+    rt.expect_send_simple(
+        payee,
+        METHOD_SEND,
+        None,
+        TokenAmount::from_atto(5u8),
+        None,
+        ExitCode::OK,
+    );
+    expect_balance_withdrawn(&rt, PROVIDER_ADDR, payee, &TokenAmount::from_atto(5u8));
+    withdraw(OWNER_ADDR, PROVIDER_ADDR).unwrap();
+    rt.verify();
+
+    rt.expect_validate_caller_addr(vec![CLIENT_ADDR]);
+    rt.expect_send_simple(
+        payee,
+        METHOD_SEND,
+        None,
+        TokenAmount::from_atto(5u8),
+        None,
+        ExitCode::OK,
+    );
+    expect_balance_withdrawn(&rt, CLIENT_ADDR, payee, &TokenAmount::from_atto(5u8));
+    withdraw(CLIENT_ADDR, CLIENT_ADDR).unwrap();
+    rt.verify();
+
+    assert_eq!(TokenAmount::from_atto(15u8), get_balance(&rt, &PROVIDER_ADDR).balance);
+    assert_eq!(TokenAmount::from_atto(15u8), get_balance(&rt, &CLIENT_ADDR).balance);
+    check_state(&rt);
+}
+
 #[test]
 fn deal_starts_on_day_boundary() {
     let mut policy = Policy::default();
@@ -658,6 +1014,55 @@ fn deal_starts_partway_through_day() {
     }
 }
 
+#[test]
+fn deal_duration_bounds_are_taken_from_policy() {
+    let mut rt = setup();
+    rt.policy.min_deal_duration = 10;
+    rt.policy.max_deal_duration = 100;
+    rt.set_epoch(1);
+
+    // A deal far shorter than the default minimum duration is accepted under this policy.
+    generate_and_publish_deal(&rt, CLIENT_ADDR, &MinerAddresses::default(), 10, 60);
+
+    // And a deal exceeding the policy's maximum duration is rejected.
+    let deal = generate_deal_and_add_funds(&rt, CLIENT_ADDR, &MinerAddresses::default(), 10, 111);
+    rt.set_caller(*ACCOUNT_ACTOR_CODE_ID, WORKER_ADDR);
+    let buf = RawBytes::serialize(deal.clone()).unwrap();
+    rt.expect_validate_caller_any();
+    expect_provider_is_control_address(&rt, PROVIDER_ADDR, WORKER_ADDR, true);
+    expect_query_network_info(&rt);
+    rt.expect_send(
+        deal.client,
+        AUTHENTICATE_MESSAGE_METHOD,
+        IpldBlock::serialize_cbor(&AuthenticateMessageParams {
+            signature: buf.to_vec(),
+            message: buf.to_vec(),
+        })
+        .unwrap(),
+        TokenAmount::zero(),
+        None,
+        SendFlags::READ_ONLY,
+        AUTHENTICATE_MESSAGE_RESPONSE.clone(),
+        ExitCode::OK,
+        None,
+    );
+    let params = PublishStorageDealsParams {
+        deals: vec![ClientDealProposal {
+            proposal: deal,
+            client_signature: Signature::new_bls(buf.to_vec()),
+        }],
+    };
+    expect_abort(
+        ExitCode::USR_ILLEGAL_ARGUMENT,
+        rt.call::<MarketActor>(
+            Method::PublishStorageDeals as u64,
+            IpldBlock::serialize_cbor(&params).unwrap(),
+        ),
+    );
+    rt.verify();
+    check_state(&rt);
+}
+
 #[test]
 fn simple_deal() {
     let start_epoch = 1000;
@@ -1843,6 +2248,49 @@ fn max_deal_label_size() {
     check_state(&rt);
 }
 
+#[test]
+fn max_deal_metadata_cid_size() {
+    let rt = setup();
+    let funds = TokenAmount::from_atto(20_000_000);
+    add_provider_funds(&rt, funds.clone(), &MinerAddresses::default());
+    add_participant_funds(&rt, CLIENT_ADDR, funds);
+    let mut deal_proposal =
+        generate_deal_proposal(CLIENT_ADDR, PROVIDER_ADDR, 1, 200 * EPOCHS_IN_DAY);
+
+    // An identity-hashed raw CID has four bytes of prefix before its digest.
+    let metadata_cid = |size: usize| {
+        Cid::new_v1(IPLD_RAW, Multihash::wrap(IDENTITY_HASH, &vec![1; size - 4]).unwrap())
+    };
+
+    // Metadata CID at max size should work.
+    let mut extensions =
+        DealExtensions { notify: None, metadata: Some(metadata_cid(DEAL_MAX_METADATA_CID_SIZE)) };
+    assert_eq!(DEAL_MAX_METADATA_CID_SIZE, extensions.metadata.unwrap().to_bytes().len());
+    rt.set_caller(*ACCOUNT_ACTOR_CODE_ID, WORKER_ADDR);
+    let ids = publish_deals_with_extensions(
+        &rt,
+        &MinerAddresses::default(),
+        &[deal_proposal.clone()],
+        &[extensions.clone()],
+        TokenAmount::zero(),
+        1,
+    );
+    assert_eq!(1, ids.len());
+
+    // over max should fail
+    deal_proposal.label = Label::String("other".to_string());
+    extensions.metadata = Some(metadata_cid(DEAL_MAX_METADATA_CID_SIZE + 1));
+    publish_deals_with_extensions_expect_abort(
+        &rt,
+        &MinerAddresses::default(),
+        deal_proposal,
+        extensions,
+        ExitCode::USR_ILLEGAL_ARGUMENT,
+    );
+
+    check_state(&rt);
+}
+
 #[test]
 /// Tests that if 2 deals are published, and the client can't cover collateral for the first deal,
 /// but can cover the second, then the first deal fails, but the second passes
@@ -3,14 +3,17 @@
 
 use fvm_ipld_bitfield::BitField;
 
-use fil_actor_market::{Actor as MarketActor, Method, OnMinerSectorsTerminateParams};
+use fil_actor_market::migration::LegacyState;
+use fil_actor_market::{Actor as MarketActor, Method, OnMinerSectorsTerminateParams, State};
 use fil_actors_runtime::network::EPOCHS_IN_DAY;
 use fil_actors_runtime::runtime::builtins::Type;
 use fil_actors_runtime::test_utils::*;
+use fil_actors_runtime::BURNT_FUNDS_ACTOR_ADDR;
 use fvm_ipld_encoding::ipld_block::IpldBlock;
 use fvm_shared::address::Address;
 use fvm_shared::econ::TokenAmount;
 use fvm_shared::error::ExitCode;
+use fvm_shared::METHOD_SEND;
 use num_traits::Zero;
 
 mod harness;
@@ -330,6 +333,148 @@ fn do_not_terminate_deal_if_end_epoch_is_equal_to_or_less_than_current_epoch() {
     check_state(&rt);
 }
 
+#[test]
+fn defer_settlement_of_deals_beyond_synchronous_budget() {
+    let start_epoch = 10;
+    let end_epoch = start_epoch + 200 * EPOCHS_IN_DAY;
+    let sector_expiry = end_epoch + 100;
+    let current_epoch = 5;
+
+    let mut rt = setup();
+    rt.policy.market_max_synchronous_deal_terminations = 1;
+    rt.set_epoch(current_epoch);
+
+    let addrs = MinerAddresses::default();
+    let (id0, deal0) = generate_and_publish_deal(&rt, CLIENT_ADDR, &addrs, start_epoch, end_epoch);
+    let (id1, deal1) =
+        generate_and_publish_deal(&rt, CLIENT_ADDR, &addrs, start_epoch, end_epoch + 1);
+    activate_deals_legacy(&rt, sector_expiry, PROVIDER_ADDR, current_epoch, id0, &[id0]);
+    activate_deals_legacy(&rt, sector_expiry, PROVIDER_ADDR, current_epoch, id1, &[id1]);
+
+    // Only the deals of the first sector fit within the budget and are settled immediately.
+    let termination_epoch = start_epoch + 100;
+    rt.set_epoch(termination_epoch);
+    rt.expect_send_simple(
+        BURNT_FUNDS_ACTOR_ADDR,
+        METHOD_SEND,
+        None,
+        deal0.provider_collateral.clone(),
+        None,
+        ExitCode::OK,
+    );
+    let ret = terminate_deals_raw(&rt, PROVIDER_ADDR, &[id0, id1], &[id0]).unwrap();
+    assert!(ret.is_none());
+    rt.verify();
+    assert_deal_deleted(&rt, id0, &deal0, id0, true);
+
+    // The second sector is recorded as terminated, leaving its deals in place.
+    assert_deals_not_marked_terminated(&rt, &[id1]);
+    let st: State = rt.get_state();
+    let provider = PROVIDER_ADDR.id().unwrap();
+    assert_eq!(
+        Some(termination_epoch),
+        st.find_sector_termination(&rt.store, provider, id1).unwrap()
+    );
+    check_state(&rt);
+
+    // Settlement pays the deal up to the termination and slashes the provider collateral.
+    rt.set_epoch(termination_epoch + 50);
+    let provider_balance = get_balance(&rt, &PROVIDER_ADDR).balance;
+    rt.expect_send_simple(
+        BURNT_FUNDS_ACTOR_ADDR,
+        METHOD_SEND,
+        None,
+        deal1.provider_collateral.clone(),
+        None,
+        ExitCode::OK,
+    );
+    settle_deal_payments(&rt, PROVIDER_ADDR, &[id1], &[], &[id1]);
+    assert_eq!(
+        provider_balance + &deal1.storage_price_per_epoch * (termination_epoch - start_epoch)
+            - &deal1.provider_collateral,
+        get_balance(&rt, &PROVIDER_ADDR).balance
+    );
+    assert_deal_deleted(&rt, id1, &deal1, id1, true);
+    let st: State = rt.get_state();
+    assert_eq!(None, st.find_sector_termination(&rt.store, provider, id1).unwrap());
+    check_state(&rt);
+}
+
+#[test]
+fn defer_settlement_after_upgrade_from_legacy_state() {
+    let start_epoch = 10;
+    let end_epoch = start_epoch + 200 * EPOCHS_IN_DAY;
+    let sector_expiry = end_epoch + 100;
+    let current_epoch = 5;
+
+    let mut rt = setup();
+    rt.policy.market_max_synchronous_deal_terminations = 1;
+    rt.set_epoch(current_epoch);
+
+    let addrs = MinerAddresses::default();
+    let (id0, deal0) = generate_and_publish_deal(&rt, CLIENT_ADDR, &addrs, start_epoch, end_epoch);
+    let (id1, deal1) =
+        generate_and_publish_deal(&rt, CLIENT_ADDR, &addrs, start_epoch, end_epoch + 1);
+    activate_deals_legacy(&rt, sector_expiry, PROVIDER_ADDR, current_epoch, id0, &[id0]);
+    activate_deals_legacy(&rt, sector_expiry, PROVIDER_ADDR, current_epoch, id1, &[id1]);
+
+    // Rewrite the state in the layout from before terminated sectors were recorded, and upgrade.
+    let st: State = rt.get_state();
+    rt.replace_state(&LegacyState {
+        proposals: st.proposals,
+        states: st.states,
+        pending_proposals: st.pending_proposals,
+        escrow_table: st.escrow_table,
+        locked_table: st.locked_table,
+        next_id: st.next_id,
+        deal_ops_by_epoch: st.deal_ops_by_epoch,
+        last_cron: st.last_cron,
+        total_client_locked_collateral: st.total_client_locked_collateral.clone(),
+        total_provider_locked_collateral: st.total_provider_locked_collateral.clone(),
+        total_client_storage_fee: st.total_client_storage_fee.clone(),
+        pending_deal_allocation_ids: st.pending_deal_allocation_ids,
+        provider_sectors: st.provider_sectors,
+    });
+    rt.upgrade::<MarketActor>();
+    let migrated: State = rt.get_state();
+    assert_eq!(st.terminated_sectors, migrated.terminated_sectors);
+    check_state(&rt);
+
+    // Sectors terminated beyond the budget are recorded in the migrated state.
+    let termination_epoch = start_epoch + 100;
+    rt.set_epoch(termination_epoch);
+    rt.expect_send_simple(
+        BURNT_FUNDS_ACTOR_ADDR,
+        METHOD_SEND,
+        None,
+        deal0.provider_collateral.clone(),
+        None,
+        ExitCode::OK,
+    );
+    terminate_deals_raw(&rt, PROVIDER_ADDR, &[id0, id1], &[id0]).unwrap();
+    rt.verify();
+    assert_deal_deleted(&rt, id0, &deal0, id0, true);
+    let st: State = rt.get_state();
+    let provider = PROVIDER_ADDR.id().unwrap();
+    assert_eq!(
+        Some(termination_epoch),
+        st.find_sector_termination(&rt.store, provider, id1).unwrap()
+    );
+    check_state(&rt);
+
+    rt.expect_send_simple(
+        BURNT_FUNDS_ACTOR_ADDR,
+        METHOD_SEND,
+        None,
+        deal1.provider_collateral.clone(),
+        None,
+        ExitCode::OK,
+    );
+    settle_deal_payments(&rt, PROVIDER_ADDR, &[id1], &[], &[id1]);
+    assert_deal_deleted(&rt, id1, &deal1, id1, true);
+    check_state(&rt);
+}
+
 #[test]
 fn fail_when_caller_is_not_a_storage_miner_actor() {
     let rt = setup();
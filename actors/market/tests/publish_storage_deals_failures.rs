@@ -20,7 +20,6 @@ use fvm_shared::piece::PaddedPieceSize;
 
 use cid::Cid;
 use fil_actor_market::ext::account::{AuthenticateMessageParams, AUTHENTICATE_MESSAGE_METHOD};
-use fil_actor_market::policy::TOTAL_FILECOIN;
 
 mod harness;
 use fvm_ipld_encoding::ipld_block::IpldBlock;
@@ -64,8 +63,8 @@ fn negative_price_per_epoch() {
 
 #[test]
 fn price_per_epoch_greater_than_total_filecoin() {
-    let f = |_rt: &MockRuntime, d: &mut DealProposal| {
-        d.storage_price_per_epoch = &*TOTAL_FILECOIN + TokenAmount::from_atto(1);
+    let f = |rt: &MockRuntime, d: &mut DealProposal| {
+        d.storage_price_per_epoch = &rt.policy.max_deal_token_amount + TokenAmount::from_atto(1);
     };
     assert_deal_failure(true, f, ExitCode::USR_ILLEGAL_ARGUMENT, true);
 }
@@ -80,8 +79,8 @@ fn negative_provider_collateral() {
 
 #[test]
 fn provider_collateral_greater_than_max_collateral() {
-    let f = |_rt: &MockRuntime, d: &mut DealProposal| {
-        d.provider_collateral = &*TOTAL_FILECOIN + TokenAmount::from_atto(1);
+    let f = |rt: &MockRuntime, d: &mut DealProposal| {
+        d.provider_collateral = &rt.policy.max_deal_token_amount + TokenAmount::from_atto(1);
     };
     assert_deal_failure(true, f, ExitCode::USR_ILLEGAL_ARGUMENT, true);
 }
@@ -112,8 +111,8 @@ fn negative_client_collateral() {
 
 #[test]
 fn client_collateral_greater_than_max_collateral() {
-    let f = |_rt: &MockRuntime, d: &mut DealProposal| {
-        d.client_collateral = &*TOTAL_FILECOIN + TokenAmount::from_atto(1);
+    let f = |rt: &MockRuntime, d: &mut DealProposal| {
+        d.client_collateral = &rt.policy.max_deal_token_amount + TokenAmount::from_atto(1);
     };
     assert_deal_failure(true, f, ExitCode::USR_ILLEGAL_ARGUMENT, true);
 }
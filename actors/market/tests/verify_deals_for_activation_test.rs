@@ -6,12 +6,12 @@ use fvm_shared::address::Address;
 use fvm_shared::clock::ChainEpoch;
 use fvm_shared::econ::TokenAmount;
 use fvm_shared::error::ExitCode;
-use fvm_shared::piece::PieceInfo;
+use fvm_shared::piece::{PaddedPieceSize, PieceInfo};
 use fvm_shared::sector::RegisteredSealProof;
 
 use fil_actor_market::{
-    ActivatedDeal, Actor as MarketActor, Method, SectorDeals, VerifyDealsForActivationParams,
-    NO_ALLOCATION_ID,
+    ActivatedDeal, Actor as MarketActor, Method, SectorDeals, SectorPieceManifests,
+    VerifyDealsForActivationParams, NO_ALLOCATION_ID,
 };
 use fil_actors_runtime::runtime::builtins::Type;
 use fil_actors_runtime::test_utils::{
@@ -369,3 +369,68 @@ fn fail_when_the_same_deal_id_is_passed_multiple_times() {
     rt.verify();
     check_state(&rt);
 }
+
+#[test]
+fn verify_direct_piece_manifests_without_deals() {
+    let rt = setup();
+    rt.set_epoch(CURR_EPOCH);
+    let pieces = |seed: &str| {
+        vec![PieceInfo { cid: make_piece_cid(seed.as_bytes()), size: PaddedPieceSize(1024) }]
+    };
+    let first = signed_piece_manifest(CLIENT_ADDR, PROVIDER_ADDR, pieces("1"), CURR_EPOCH);
+    let second = signed_piece_manifest(CLIENT_ADDR, PROVIDER_ADDR, pieces("2"), CURR_EPOCH + 1);
+    let sector_type = RegisteredSealProof::StackedDRG2KiBV1P1;
+
+    expect_manifest_authentication(&rt, &first);
+    expect_manifest_authentication(&rt, &second);
+    rt.expect_compute_unsealed_sector_cid(
+        sector_type,
+        [pieces("1"), pieces("2")].concat(),
+        make_piece_cid("commd".as_bytes()),
+        ExitCode::OK,
+    );
+    let ret = verify_deals_for_activation_direct(
+        &rt,
+        PROVIDER_ADDR,
+        vec![
+            SectorPieceManifests { sector_number: 7, sector_type, manifests: vec![first, second] },
+            SectorPieceManifests { sector_number: 8, sector_type, manifests: vec![] },
+        ],
+    )
+    .unwrap();
+    assert_eq!(vec![Some(make_piece_cid("commd".as_bytes())), None], ret.unsealed_cids);
+
+    // Nothing is recorded and no funds are locked.
+    assert!(get_balance(&rt, &CLIENT_ADDR).locked.is_zero());
+    check_state(&rt);
+}
+
+#[test]
+fn fail_direct_piece_manifest_for_other_provider_or_expired() {
+    let rt = setup();
+    rt.set_epoch(CURR_EPOCH);
+    let pieces =
+        vec![PieceInfo { cid: make_piece_cid("1".as_bytes()), size: PaddedPieceSize(1024) }];
+    let sector = |manifest| SectorPieceManifests {
+        sector_number: 7,
+        sector_type: RegisteredSealProof::StackedDRG2KiBV1P1,
+        manifests: vec![manifest],
+    };
+
+    let other_provider = Address::new_id(205);
+    let manifest = signed_piece_manifest(CLIENT_ADDR, other_provider, pieces.clone(), CURR_EPOCH);
+    expect_abort(
+        ExitCode::USR_FORBIDDEN,
+        verify_deals_for_activation_direct(&rt, PROVIDER_ADDR, vec![sector(manifest)]),
+    );
+    rt.reset();
+
+    let manifest = signed_piece_manifest(CLIENT_ADDR, PROVIDER_ADDR, pieces, CURR_EPOCH - 1);
+    expect_abort_contains_message(
+        ExitCode::USR_ILLEGAL_ARGUMENT,
+        "manifest expired",
+        verify_deals_for_activation_direct(&rt, PROVIDER_ADDR, vec![sector(manifest)]),
+    );
+    rt.reset();
+    check_state(&rt);
+}
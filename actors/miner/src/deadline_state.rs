@@ -15,12 +15,14 @@ use fvm_shared::econ::TokenAmount;
 use fvm_shared::error::ExitCode;
 use fvm_shared::sector::{PoStProof, SectorSize};
 use multihash_codetable::Code;
-use num_traits::{Signed, Zero};
+use num_derive::FromPrimitive;
+use num_traits::{FromPrimitive, Signed, Zero};
+use serde_repr::{Deserialize_repr, Serialize_repr};
 
 use fil_actors_runtime::runtime::Policy;
 use fil_actors_runtime::{actor_error, ActorDowncast, ActorError, Array, AsActorError};
 
-use crate::SECTORS_AMT_BITWIDTH;
+use crate::{POST_HISTORY_LENGTH, SECTORS_AMT_BITWIDTH};
 
 use super::{
     BitFieldQueue, ExpirationSet, Partition, PartitionSectorMap, PoStPartition, PowerPair,
@@ -163,6 +165,31 @@ pub struct Deadline {
     // These proofs may be disputed via DisputeWindowedPoSt. Successfully
     // disputed window PoSts are removed from the snapshot.
     pub optimistic_post_submissions_snapshot: Cid,
+
+    /// Outcomes of this deadline's challenge window over the last POST_HISTORY_LENGTH proving
+    /// periods, two bits per period with the most recent in the lowest bits.
+    /// See [`PoStOutcome`].
+    pub post_history: u64,
+
+    /// Faulty power declared as planned downtime for this deadline's next challenge window,
+    /// which pays a reduced fault fee when the window ends.
+    pub planned_downtime_power: PowerPair,
+}
+
+/// Outcome of a deadline's challenge window in a single proving period.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, FromPrimitive, Serialize_repr, Deserialize_repr)]
+#[repr(u8)]
+pub enum PoStOutcome {
+    /// No outcome was recorded, as the deadline had no live sectors or the period predates
+    /// the history.
+    Empty = 0,
+    /// All live sectors were proven.
+    Proven = 1,
+    /// Some live power was faulty at the end of the challenge window, either declared or
+    /// detected from a missed PoSt.
+    Faulted = 2,
+    /// A PoSt submitted in the challenge window was successfully disputed.
+    Disputed = 3,
 }
 
 #[derive(Serialize_tuple, Deserialize_tuple, Clone)]
@@ -233,6 +260,8 @@ impl Deadline {
             partitions_snapshot: empty_partitions_array,
             sectors_snapshot: empty_sectors_array,
             optimistic_post_submissions_snapshot: empty_post_submissions_array,
+            post_history: 0,
+            planned_downtime_power: PowerPair::zero(),
         })
     }
 
@@ -973,6 +1002,14 @@ impl Deadline {
             )
         })?;
 
+        self.record_post_outcome(if self.live_sectors == 0 {
+            PoStOutcome::Empty
+        } else if !self.faulty_power.is_zero() {
+            PoStOutcome::Faulted
+        } else {
+            PoStOutcome::Proven
+        });
+
         // Reset PoSt submissions.
         self.partitions_posted = BitField::new();
         self.partitions_snapshot = self.partitions;
@@ -1012,6 +1049,26 @@ impl Deadline {
         Ok(())
     }
 
+    /// Records the outcome of the challenge window that just ended, evicting the oldest.
+    pub fn record_post_outcome(&mut self, outcome: PoStOutcome) {
+        self.post_history = (self.post_history << 2) | outcome as u64;
+    }
+
+    /// Marks the most recently ended challenge window as disputed.
+    pub fn record_post_disputed(&mut self) {
+        self.post_history = (self.post_history & !0b11) | PoStOutcome::Disputed as u64;
+    }
+
+    /// Returns the recorded PoSt outcomes, most recent first.
+    pub fn post_outcomes(&self) -> Vec<PoStOutcome> {
+        (0..POST_HISTORY_LENGTH)
+            .map(|i| {
+                PoStOutcome::from_u64((self.post_history >> (2 * i)) & 0b11)
+                    .unwrap_or(PoStOutcome::Empty)
+            })
+            .collect()
+    }
+
     pub fn validate_state(&self) -> anyhow::Result<()> {
         if self.live_sectors > self.total_sectors {
             return Err(anyhow!("deadline left with more live sectors than total"));
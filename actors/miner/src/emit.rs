@@ -1,8 +1,29 @@
 use cid::Cid;
 use fil_actors_runtime::runtime::Runtime;
 use fil_actors_runtime::{ActorError, EventBuilder};
+use fvm_shared::address::Address;
+use fvm_shared::bigint::bigint_ser::BigIntSer;
+use fvm_shared::clock::ChainEpoch;
+use fvm_shared::econ::TokenAmount;
 use fvm_shared::sector::SectorNumber;
 
+use crate::PowerPair;
+
+/// Indicates a cron callback expired or terminated more sectors than its budget.
+pub fn cron_budget_exceeded(
+    rt: &impl Runtime,
+    entries: u64,
+    budget: u64,
+) -> Result<(), ActorError> {
+    rt.emit_event(
+        &EventBuilder::new()
+            .typ("cron-budget-exceeded")
+            .field("entries", &entries)
+            .field("budget", &budget)
+            .build()?,
+    )
+}
+
 /// Indicates a sector has been pre-committed.
 pub fn sector_precommitted(rt: &impl Runtime, sector: SectorNumber) -> Result<(), ActorError> {
     rt.emit_event(
@@ -10,6 +31,21 @@ pub fn sector_precommitted(rt: &impl Runtime, sector: SectorNumber) -> Result<()
     )
 }
 
+/// Indicates a sector has been proven ahead of its activation at an epoch.
+pub fn sector_sealed_ahead(
+    rt: &impl Runtime,
+    sector: SectorNumber,
+    activation_epoch: ChainEpoch,
+) -> Result<(), ActorError> {
+    rt.emit_event(
+        &EventBuilder::new()
+            .typ("sector-sealed-ahead")
+            .field_indexed("sector", &sector)
+            .field("activation-epoch", &activation_epoch)
+            .build()?,
+    )
+}
+
 /// Indicates a sector has been activated.
 pub fn sector_activated(
     rt: &impl Runtime,
@@ -47,6 +83,81 @@ pub fn sector_terminated(rt: &impl Runtime, sector: SectorNumber) -> Result<(),
     )
 }
 
+/// Indicates a time-locked key rotation has been proposed.
+pub fn key_rotation_proposed(
+    rt: &impl Runtime,
+    new_worker: &Address,
+    effective_at: ChainEpoch,
+) -> Result<(), ActorError> {
+    rt.emit_event(
+        &EventBuilder::new()
+            .typ("key-rotation-proposed")
+            .field_indexed("new-worker", new_worker)
+            .field("effective-at", &effective_at)
+            .build()?,
+    )
+}
+
+/// Indicates a pending key rotation has been vetoed by the owner.
+pub fn key_rotation_vetoed(rt: &impl Runtime, new_worker: &Address) -> Result<(), ActorError> {
+    rt.emit_event(
+        &EventBuilder::new()
+            .typ("key-rotation-vetoed")
+            .field_indexed("new-worker", new_worker)
+            .build()?,
+    )
+}
+
+/// Indicates a pending key rotation has taken effect.
+pub fn key_rotation_applied(rt: &impl Runtime, new_worker: &Address) -> Result<(), ActorError> {
+    rt.emit_event(
+        &EventBuilder::new()
+            .typ("key-rotation-applied")
+            .field_indexed("new-worker", new_worker)
+            .build()?,
+    )
+}
+
+/// Indicates the miner's available balance, locked funds, pre-commit deposits or fee debt changed.
+/// Each field is the change over the invoking message.
+pub fn balance_changed(
+    rt: &impl Runtime,
+    available_balance_delta: &TokenAmount,
+    locked_funds_delta: &TokenAmount,
+    pre_commit_deposits_delta: &TokenAmount,
+    fee_debt_delta: &TokenAmount,
+) -> Result<(), ActorError> {
+    rt.emit_event(
+        &EventBuilder::new()
+            .typ("balance-changed")
+            .field("available-balance-delta", available_balance_delta)
+            .field("locked-funds-delta", locked_funds_delta)
+            .field("pre-commit-deposits-delta", pre_commit_deposits_delta)
+            .field("fee-debt-delta", fee_debt_delta)
+            .build()?,
+    )
+}
+
+/// Indicates live sectors in a deadline have been declared faulty as planned downtime
+/// for the challenge window opening at the given epoch.
+/// The power is that newly faulty as a result of the declaration.
+pub fn planned_downtime(
+    rt: &impl Runtime,
+    deadline: u64,
+    open_epoch: ChainEpoch,
+    power: &PowerPair,
+) -> Result<(), ActorError> {
+    rt.emit_event(
+        &EventBuilder::new()
+            .typ("planned-downtime")
+            .field_indexed("deadline", &deadline)
+            .field("open-epoch", &open_epoch)
+            .field("raw-power", &BigIntSer(&power.raw))
+            .field("qa-power", &BigIntSer(&power.qa))
+            .build()?,
+    )
+}
+
 trait WithSectorInfo {
     fn with_sector_info(
         self,
@@ -72,3 +183,21 @@ impl WithSectorInfo for EventBuilder {
         event
     }
 }
+
+/// Indicates the miner has been penalised for a consensus fault at the given epoch, either
+/// reported by a third party or self-reported for a reduced penalty.
+pub fn consensus_fault(
+    rt: &impl Runtime,
+    fault_epoch: ChainEpoch,
+    self_reported: bool,
+    penalty: &TokenAmount,
+) -> Result<(), ActorError> {
+    rt.emit_event(
+        &EventBuilder::new()
+            .typ("consensus-fault")
+            .field("fault-epoch", &fault_epoch)
+            .field_indexed("self-reported", &self_reported)
+            .field("penalty", penalty)
+            .build()?,
+    )
+}
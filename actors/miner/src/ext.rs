@@ -1,13 +1,15 @@
 use cid::Cid;
 use fvm_ipld_encoding::tuple::*;
 use fvm_ipld_encoding::RawBytes;
+use fvm_shared::address::Address;
 use fvm_shared::bigint::{bigint_ser, BigInt};
 use fvm_shared::clock::ChainEpoch;
+use fvm_shared::crypto::signature::Signature;
 use fvm_shared::deal::DealID;
 use fvm_shared::econ::TokenAmount;
-use fvm_shared::piece::PaddedPieceSize;
+use fvm_shared::piece::{PaddedPieceSize, PieceInfo};
 use fvm_shared::sector::SectorNumber;
-use fvm_shared::sector::{RegisteredSealProof, StoragePower};
+use fvm_shared::sector::{RegisteredPoStProof, RegisteredSealProof, StoragePower};
 use fvm_shared::ActorID;
 
 use fil_actors_runtime::reward::FilterEstimate;
@@ -24,6 +26,8 @@ pub mod market {
     pub const VERIFY_DEALS_FOR_ACTIVATION_METHOD: u64 = 5;
     pub const BATCH_ACTIVATE_DEALS_METHOD: u64 = 6;
     pub const ON_MINER_SECTORS_TERMINATE_METHOD: u64 = 7;
+    pub const VERIFY_DEALS_FOR_ACTIVATION_DIRECT_METHOD: u64 =
+        frc42_dispatch::method_hash!("VerifyDealsForActivationDirect");
 
     pub const NO_ALLOCATION_ID: u64 = 0;
 
@@ -82,6 +86,32 @@ pub mod market {
     pub struct VerifyDealsForActivationReturn {
         pub unsealed_cids: Vec<Option<Cid>>,
     }
+
+    #[derive(Serialize_tuple, Deserialize_tuple, Debug, Clone, Eq, PartialEq)]
+    pub struct PieceManifest {
+        pub client: Address,
+        pub provider: Address,
+        pub pieces: Vec<PieceInfo>,
+        pub expiration: ChainEpoch,
+    }
+
+    #[derive(Serialize_tuple, Deserialize_tuple, Debug, Clone, Eq, PartialEq)]
+    pub struct ClientPieceManifest {
+        pub manifest: PieceManifest,
+        pub client_signature: Signature,
+    }
+
+    #[derive(Serialize_tuple, Deserialize_tuple)]
+    pub struct SectorPieceManifests {
+        pub sector_number: SectorNumber,
+        pub sector_type: RegisteredSealProof,
+        pub manifests: Vec<ClientPieceManifest>,
+    }
+
+    #[derive(Serialize_tuple, Deserialize_tuple)]
+    pub struct VerifyDealsForActivationDirectParams {
+        pub sectors: Vec<SectorPieceManifests>,
+    }
 }
 
 pub mod power {
@@ -92,6 +122,7 @@ pub mod power {
     pub const UPDATE_PLEDGE_TOTAL_METHOD: u64 = 6;
     pub const SUBMIT_POREP_FOR_BULK_VERIFY_METHOD: u64 = 8;
     pub const CURRENT_TOTAL_POWER_METHOD: u64 = 9;
+    pub const SET_CONSENSUS_FAULT_EXCLUSION_METHOD: u64 = 12;
 
     #[derive(Serialize_tuple, Deserialize_tuple)]
     pub struct CurrentTotalPowerReturn {
@@ -103,13 +134,27 @@ pub mod power {
         pub quality_adj_power_smoothed: FilterEstimate,
         pub ramp_start_epoch: i64,
         pub ramp_duration_epochs: u64,
+        pub proof_type_allowlist: Option<ProofTypeAllowlist>,
     }
+
+    #[derive(Serialize_tuple, Deserialize_tuple, Debug, Clone, Eq, PartialEq)]
+    pub struct ProofTypeAllowlist {
+        pub seal_proof_types: Vec<RegisteredSealProof>,
+        pub post_proof_types: Vec<RegisteredPoStProof>,
+    }
+
     #[derive(Serialize_tuple, Deserialize_tuple)]
     pub struct EnrollCronEventParams {
         pub event_epoch: ChainEpoch,
         pub payload: RawBytes,
     }
 
+    #[derive(Serialize_tuple, Deserialize_tuple)]
+    #[serde(transparent)]
+    pub struct SetConsensusFaultExclusionParams {
+        pub excluded: bool,
+    }
+
     #[derive(Serialize_tuple, Deserialize_tuple)]
     pub struct UpdateClaimedPowerParams {
         #[serde(with = "bigint_ser")]
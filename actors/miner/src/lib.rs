@@ -28,6 +28,7 @@ use fvm_shared::sector::{
     RegisteredUpdateProof, ReplicaUpdateInfo, SealRandomness, SealVerifyInfo, SectorID, SectorInfo,
     SectorNumber, SectorSize, StoragePower, WindowPoStVerifyInfo,
 };
+use fvm_shared::version::NetworkVersion;
 use fvm_shared::{ActorID, MethodNum, METHOD_CONSTRUCTOR, METHOD_SEND};
 use itertools::Itertools;
 use log::{error, info, warn};
@@ -49,15 +50,17 @@ use fil_actors_runtime::runtime::builtins::Type;
 use fil_actors_runtime::runtime::policy_constants::MAX_SECTOR_NUMBER;
 use fil_actors_runtime::runtime::{ActorCode, DomainSeparationTag, Policy, Runtime};
 use fil_actors_runtime::{
-    actor_dispatch, actor_error, deserialize_block, extract_send_result, util, ActorContext,
-    ActorDowncast, ActorError, AsActorError, BatchReturn, BatchReturnGen, DealWeight,
-    BURNT_FUNDS_ACTOR_ADDR, INIT_ACTOR_ADDR, REWARD_ACTOR_ADDR, STORAGE_MARKET_ACTOR_ADDR,
-    STORAGE_POWER_ACTOR_ADDR, SYSTEM_ACTOR_ADDR, VERIFIED_REGISTRY_ACTOR_ADDR,
+    actor_dispatch, actor_error, deserialize_block, extract_send_result, migrate_state, util,
+    ActorContext, ActorDowncast, ActorError, AsActorError, BatchReturn, BatchReturnGen, DealWeight,
+    OnUpgradeParams, BURNT_FUNDS_ACTOR_ADDR, INIT_ACTOR_ADDR, REWARD_ACTOR_ADDR,
+    STORAGE_MARKET_ACTOR_ADDR, STORAGE_POWER_ACTOR_ADDR, SYSTEM_ACTOR_ADDR,
+    UPGRADE_NETWORK_VERSION, VERIFIED_REGISTRY_ACTOR_ADDR,
 };
 pub use monies::*;
 pub use partition_state::*;
 pub use policy::*;
 pub use quantize::*;
+pub use sampling::*;
 pub use sector_map::*;
 pub use sectors::*;
 pub use state::*;
@@ -65,6 +68,8 @@ pub use termination::*;
 pub use types::*;
 pub use vesting_state::*;
 
+use crate::migration::LegacyState;
+
 use crate::ext::market::NO_ALLOCATION_ID;
 use crate::notifications::{notify_data_consumers, ActivationNotifications};
 
@@ -84,17 +89,23 @@ mod deadline_state;
 mod deadlines;
 mod emit;
 mod expiration_queue;
-#[doc(hidden)]
+#[cfg(feature = "testing")]
 pub mod ext;
+#[cfg(not(feature = "testing"))]
+#[allow(dead_code)]
+mod ext;
+pub mod migration;
 mod monies;
 mod notifications;
 mod partition_state;
 mod policy;
 mod quantize;
+mod sampling;
 mod sector_map;
 mod sectors;
 mod state;
 mod termination;
+#[cfg(feature = "testing")]
 pub mod testing;
 mod types;
 mod vesting_state;
@@ -139,6 +150,11 @@ pub enum Method {
     ProveCommitSectors3 = 34,
     ProveReplicaUpdates3 = 35,
     ProveCommitSectorsNI = 36,
+    PreCommitSectorBatch3 = 37,
+    ExtendSectorProofExpiration = 38,
+    SubmitWindowedPoSt2 = 39,
+    ProveCommitSealedAhead = 40,
+    GetPledgeObligation = 41,
     // Method numbers derived from FRC-0042 standards
     ChangeWorkerAddressExported = frc42_dispatch::method_hash!("ChangeWorkerAddress"),
     ChangePeerIDExported = frc42_dispatch::method_hash!("ChangePeerID"),
@@ -156,6 +172,17 @@ pub enum Method {
     GetVestingFundsExported = frc42_dispatch::method_hash!("GetVestingFunds"),
     GetPeerIDExported = frc42_dispatch::method_hash!("GetPeerID"),
     GetMultiaddrsExported = frc42_dispatch::method_hash!("GetMultiaddrs"),
+    TerminationPenaltyEstimateExported = frc42_dispatch::method_hash!("TerminationPenaltyEstimate"),
+    ProposeKeyRotationExported = frc42_dispatch::method_hash!("ProposeKeyRotation"),
+    VetoKeyRotationExported = frc42_dispatch::method_hash!("VetoKeyRotation"),
+    GetDeadlinePoStHistoryExported = frc42_dispatch::method_hash!("GetDeadlinePoStHistory"),
+    GetPieceSectorsExported = frc42_dispatch::method_hash!("GetPieceSectors"),
+    SamplePartitionSectorsExported = frc42_dispatch::method_hash!("SamplePartitionSectors"),
+    GetSectorStatusExported = frc42_dispatch::method_hash!("GetSectorStatus"),
+    DeclarePlannedDowntimeExported = frc42_dispatch::method_hash!("DeclarePlannedDowntime"),
+    ChangeProvingKeyExported = frc42_dispatch::method_hash!("ChangeProvingKey"),
+    SelfReportConsensusFaultExported = frc42_dispatch::method_hash!("SelfReportConsensusFault"),
+    ComputeUnsealedSectorCIDExported = frc42_dispatch::method_hash!("ComputeUnsealedSectorCID"),
 }
 
 pub const SECTOR_CONTENT_CHANGED: MethodNum = frc42_dispatch::method_hash!("SectorContentChanged");
@@ -179,7 +206,11 @@ impl Actor {
 
         check_control_addresses(rt.policy(), &params.control_addresses)?;
         check_peer_info(rt.policy(), &params.peer_id, &params.multi_addresses)?;
-        check_valid_post_proof_type(rt.policy(), params.window_post_proof_type)?;
+        // From the upgrade network version, the power actor checks the proof type against
+        // the network's allowlist before creating the miner.
+        if rt.network_version() < UPGRADE_NETWORK_VERSION {
+            check_valid_post_proof_type(rt.policy(), params.window_post_proof_type)?;
+        }
 
         let owner = rt.resolve_address(&params.owner).ok_or_else(|| {
             actor_error!(illegal_argument, "unable to resolve owner address: {}", params.owner)
@@ -304,6 +335,16 @@ impl Actor {
         Ok(GetAvailableBalanceReturn { available_balance })
     }
 
+    /// Returns the pledge this miner has reported to the power actor's total pledge collateral:
+    /// its initial pledge and locked funds.
+    fn get_pledge_obligation(rt: &impl Runtime) -> Result<GetPledgeObligationReturn, ActorError> {
+        rt.validate_immediate_caller_accept_any()?;
+        let state: State = rt.state()?;
+        Ok(GetPledgeObligationReturn {
+            pledge_obligation: &state.initial_pledge + &state.locked_funds,
+        })
+    }
+
     /// Returns the funds vesting in this miner as a list of (vesting_epoch, vesting_amount) tuples.
     fn get_vesting_funds(rt: &impl Runtime) -> Result<GetVestingFundsReturn, ActorError> {
         rt.validate_immediate_caller_accept_any()?;
@@ -315,6 +356,177 @@ impl Actor {
         Ok(GetVestingFundsReturn { vesting_funds: ret })
     }
 
+    /// Returns the penalty that TerminateSectors would charge for terminating the specified
+    /// sectors at the current epoch, using the current network reward and power estimates.
+    fn termination_penalty_estimate(
+        rt: &impl Runtime,
+        params: TerminationPenaltyEstimateParams,
+    ) -> Result<TerminationPenaltyEstimateReturn, ActorError> {
+        rt.validate_immediate_caller_accept_any()?;
+        let sector_count = params.sectors.len();
+        if sector_count > rt.policy().addressed_sectors_max {
+            return Err(actor_error!(
+                illegal_argument,
+                "too many sectors for termination penalty estimate {} > {}",
+                sector_count,
+                rt.policy().addressed_sectors_max
+            ));
+        }
+
+        let state: State = rt.state()?;
+        let info = get_miner_info(rt.store(), &state)?;
+        let sectors = Sectors::load(rt.store(), &state.sectors).map_err(|e| {
+            e.downcast_default(ExitCode::USR_ILLEGAL_STATE, "failed to load sectors array")
+        })?;
+        let sector_infos = sectors
+            .load_sector(&params.sectors)
+            .map_err(|e| e.wrap("failed to load sector infos"))?;
+
+        let epoch_reward = request_current_epoch_block_reward(rt)?;
+        let pwr_total = request_current_total_power(rt)?;
+        let curr_epoch = rt.curr_epoch();
+        let mut penalty = TokenAmount::zero();
+        for sector in &sector_infos {
+            penalty += termination_penalty_for_sector(
+                info.sector_size,
+                curr_epoch,
+                sector,
+                &epoch_reward.this_epoch_reward_smoothed,
+                &pwr_total.quality_adj_power_smoothed,
+            );
+        }
+        Ok(TerminationPenaltyEstimateReturn { penalty })
+    }
+
+    /// Returns the PoSt outcomes recorded for a deadline over recent proving periods,
+    /// most recent first.
+    fn get_deadline_post_history(
+        rt: &impl Runtime,
+        params: GetDeadlinePoStHistoryParams,
+    ) -> Result<GetDeadlinePoStHistoryReturn, ActorError> {
+        rt.validate_immediate_caller_accept_any()?;
+        if params.deadline >= rt.policy().wpost_period_deadlines {
+            return Err(actor_error!(illegal_argument, "invalid deadline {}", params.deadline));
+        }
+        let state: State = rt.state()?;
+        let deadline =
+            state.load_deadlines(rt.store())?.load_deadline(rt.store(), params.deadline)?;
+        Ok(GetDeadlinePoStHistoryReturn { outcomes: deadline.post_outcomes() })
+    }
+
+    /// Returns the sectors recorded in the piece index as holding a piece.
+    /// The index records a bounded number of sectors for each piece, and retains a terminated
+    /// sector until its early termination is processed.
+    fn get_piece_sectors(
+        rt: &impl Runtime,
+        params: GetPieceSectorsParams,
+    ) -> Result<GetPieceSectorsReturn, ActorError> {
+        rt.validate_immediate_caller_accept_any()?;
+        let state: State = rt.state()?;
+        let sectors = state.sectors_for_piece(rt.store(), &params.piece_cid)?;
+        Ok(GetPieceSectorsReturn { sectors })
+    }
+
+    /// Samples sector numbers from a partition using the derivation of PoSt sector challenges,
+    /// so that light clients and contracts can derive the same challenge set as the actor.
+    /// Sectors are sampled with replacement from the partition's live, non-faulty sectors,
+    /// ordered by sector number. Nothing is sampled from a partition with no such sectors.
+    fn sample_partition_sectors(
+        rt: &impl Runtime,
+        params: SamplePartitionSectorsParams,
+    ) -> Result<SamplePartitionSectorsReturn, ActorError> {
+        rt.validate_immediate_caller_accept_any()?;
+        if params.deadline >= rt.policy().wpost_period_deadlines {
+            return Err(actor_error!(illegal_argument, "invalid deadline {}", params.deadline));
+        }
+        if params.count > MAX_SAMPLED_SECTORS {
+            return Err(actor_error!(
+                illegal_argument,
+                "sample count {} exceeds maximum {}",
+                params.count,
+                MAX_SAMPLED_SECTORS
+            ));
+        }
+        let randomness: &[u8; RANDOMNESS_LENGTH] =
+            params.randomness.0.as_slice().try_into().map_err(|_| {
+                actor_error!(
+                    illegal_argument,
+                    "randomness must be {} bytes, got {}",
+                    RANDOMNESS_LENGTH,
+                    params.randomness.0.len()
+                )
+            })?;
+
+        let state: State = rt.state()?;
+        let deadline =
+            state.load_deadlines(rt.store())?.load_deadline(rt.store(), params.deadline)?;
+        let partition = deadline.load_partition(rt.store(), params.partition)?;
+        let candidates: Vec<SectorNumber> =
+            (&partition.live_sectors() - &partition.faults).iter().collect();
+
+        let miner = rt.message().receiver().id().unwrap();
+        let sectors =
+            sector_challenge_indices(rt, miner, randomness, params.count, candidates.len() as u64)
+                .into_iter()
+                .map(|i| candidates[i as usize])
+                .collect();
+        Ok(SamplePartitionSectorsReturn { sectors })
+    }
+
+    /// Returns the liveness and expiration of a sector, and where it is proven.
+    /// Fails if the sector is not found, which includes sectors whose termination or expiration
+    /// has been processed.
+    fn get_sector_status(
+        rt: &impl Runtime,
+        params: GetSectorStatusParams,
+    ) -> Result<GetSectorStatusReturn, ActorError> {
+        rt.validate_immediate_caller_accept_any()?;
+        let state: State = rt.state()?;
+        let sector = state
+            .get_sector(rt.store(), params.sector_number)?
+            .ok_or_else(|| actor_error!(not_found, "sector {} not found", params.sector_number))?;
+        let (deadline, partition) = state
+            .find_sector(rt.store(), params.sector_number)
+            .with_context_code(ExitCode::USR_ILLEGAL_STATE, || {
+                format!("failed to find sector {}", params.sector_number)
+            })?;
+        let partition_state = state
+            .load_deadlines(rt.store())?
+            .load_deadline(rt.store(), deadline)?
+            .load_partition(rt.store(), partition)?;
+        let faulty = partition_state.faults.get(params.sector_number);
+        let live = !faulty
+            && !partition_state.terminated.get(params.sector_number)
+            && !partition_state.unproven.get(params.sector_number);
+        Ok(GetSectorStatusReturn {
+            live,
+            faulty,
+            activation: sector.activation,
+            expiration: sector.expiration,
+            deadline,
+            partition,
+        })
+    }
+
+    /// Computes the unsealed sector CID (CommD) of a sector of some type holding some pieces,
+    /// as the actor does when validating sector commitments.
+    /// A sector with no pieces has the CommD of a sector of zeros.
+    fn compute_unsealed_sector_cid(
+        rt: &impl Runtime,
+        params: ComputeUnsealedSectorCIDParams,
+    ) -> Result<ComputeUnsealedSectorCIDReturn, ActorError> {
+        rt.validate_immediate_caller_accept_any()?;
+        let unsealed_cid = if params.pieces.is_empty() {
+            CompactCommD::empty().get_cid(params.sector_type)?
+        } else {
+            rt.compute_unsealed_sector_cid(params.sector_type, &params.pieces).context_code(
+                ExitCode::USR_ILLEGAL_ARGUMENT,
+                "failed to compute unsealed sector CID",
+            )?
+        };
+        Ok(ComputeUnsealedSectorCIDReturn { unsealed_cid })
+    }
+
     /// Will ALWAYS overwrite the existing control addresses with the control addresses passed in the params.
     /// If an empty addresses vector is passed, the control addresses will be cleared.
     /// A worker change will be scheduled if the worker passed in the params is different from the existing worker.
@@ -325,16 +537,7 @@ impl Actor {
         check_control_addresses(rt.policy(), &params.new_control_addresses)?;
 
         let new_worker = Address::new_id(resolve_worker_address(rt, params.new_worker)?);
-        let control_addresses: Vec<Address> = params
-            .new_control_addresses
-            .into_iter()
-            .map(|address| {
-                rt.resolve_address(&address).ok_or_else(|| {
-                    actor_error!(illegal_argument, "unable to resolve control address: {}", address)
-                })
-            })
-            .map(|id_result| id_result.map(Address::new_id))
-            .collect::<Result<_, _>>()?;
+        let control_addresses = resolve_control_addresses(rt, params.new_control_addresses)?;
 
         rt.transaction(|state: &mut State, rt| {
             let mut info = get_miner_info(rt.store(), state)?;
@@ -363,6 +566,97 @@ impl Actor {
         Ok(())
     }
 
+    /// Proposes a time-locked rotation of the worker and control addresses, taking effect `delay`
+    /// epochs from now unless vetoed by the owner before then. The rotation is applied by the
+    /// first deadline cron at or after its effective epoch.
+    /// Only the owner may propose a rotation, replacing any pending one.
+    fn propose_key_rotation(
+        rt: &impl Runtime,
+        params: ProposeKeyRotationParams,
+    ) -> Result<(), ActorError> {
+        check_control_addresses(rt.policy(), &params.new_control_addresses)?;
+        if params.delay < rt.policy().worker_key_change_delay {
+            return Err(actor_error!(
+                illegal_argument,
+                "key rotation delay {} less than minimum {}",
+                params.delay,
+                rt.policy().worker_key_change_delay
+            ));
+        }
+
+        let new_worker = Address::new_id(resolve_worker_address(rt, params.new_worker)?);
+        let new_control_addresses = resolve_control_addresses(rt, params.new_control_addresses)?;
+        let effective_at = rt.curr_epoch() + params.delay;
+
+        rt.transaction(|state: &mut State, rt| {
+            let mut info = get_miner_info(rt.store(), state)?;
+
+            // Only the Owner is allowed to rotate the worker and control addresses.
+            rt.validate_immediate_caller_is(std::iter::once(&info.owner))?;
+
+            info.pending_key_rotation =
+                Some(KeyRotation { new_worker, new_control_addresses, effective_at });
+            state.save_info(rt.store(), &info).map_err(|e| {
+                e.downcast_default(ExitCode::USR_ILLEGAL_STATE, "could not save miner info")
+            })
+        })?;
+
+        emit::key_rotation_proposed(rt, &new_worker, effective_at)
+    }
+
+    /// Cancels a pending key rotation before it takes effect. Only the owner may veto a rotation.
+    fn veto_key_rotation(rt: &impl Runtime) -> Result<(), ActorError> {
+        let rotation = rt.transaction(|state: &mut State, rt| {
+            let mut info = get_miner_info(rt.store(), state)?;
+            rt.validate_immediate_caller_is(std::iter::once(&info.owner))?;
+
+            let rotation = info
+                .pending_key_rotation
+                .take()
+                .ok_or_else(|| actor_error!(not_found, "no pending key rotation"))?;
+            if rt.curr_epoch() >= rotation.effective_at {
+                return Err(actor_error!(
+                    forbidden,
+                    "key rotation effective at {} can no longer be vetoed",
+                    rotation.effective_at
+                ));
+            }
+            state.save_info(rt.store(), &info).map_err(|e| {
+                e.downcast_default(ExitCode::USR_ILLEGAL_STATE, "could not save miner info")
+            })?;
+            Ok(rotation)
+        })?;
+
+        emit::key_rotation_vetoed(rt, &rotation.new_worker)
+    }
+
+    /// Sets or removes the proofs-only key, which may submit Window PoSts and declare recoveries
+    /// but nothing else. This limits the damage a compromised proving machine can do.
+    /// Only the owner may change the proving key, and the change takes effect immediately.
+    fn change_proving_key(
+        rt: &impl Runtime,
+        params: ChangeProvingKeyParams,
+    ) -> Result<(), ActorError> {
+        let proving_key = params
+            .new_proving_key
+            .map(|address| {
+                rt.resolve_address(&address).map(Address::new_id).ok_or_else(|| {
+                    actor_error!(illegal_argument, "unable to resolve proving key: {}", address)
+                })
+            })
+            .transpose()?;
+
+        rt.transaction(|state: &mut State, rt| {
+            let mut info = get_miner_info(rt.store(), state)?;
+            rt.validate_immediate_caller_is(std::iter::once(&info.owner))?;
+
+            info.proving_key = proving_key;
+            state.save_info(rt.store(), &info).map_err(|e| {
+                e.downcast_default(ExitCode::USR_ILLEGAL_STATE, "could not save miner info")
+            })
+        })
+    }
+
     /// Triggers a worker address change if a change has been requested and its effective epoch has arrived.
     fn confirm_change_worker_address(rt: &impl Runtime) -> Result<(), ActorError> {
         rt.transaction(|state: &mut State, rt| {
@@ -501,260 +795,85 @@ impl Actor {
     /// Invoked by miner's worker address to submit their fallback post
     fn submit_windowed_post(
         rt: &impl Runtime,
-        mut params: SubmitWindowedPoStParams,
+        params: SubmitWindowedPoStParams,
     ) -> Result<(), ActorError> {
-        let current_epoch = rt.curr_epoch();
-
-        {
-            let policy = rt.policy();
-            if params.proofs.len() != 1 {
-                return Err(actor_error!(
-                    illegal_argument,
-                    "expected exactly one proof, got {}",
-                    params.proofs.len()
-                ));
-            }
-
-            if check_valid_post_proof_type(policy, params.proofs[0].post_proof).is_err() {
-                return Err(actor_error!(
-                    illegal_argument,
-                    "proof type {:?} not allowed",
-                    params.proofs[0].post_proof
-                ));
-            }
-
-            if params.deadline >= policy.wpost_period_deadlines {
-                return Err(actor_error!(
-                    illegal_argument,
-                    "invalid deadline {} of {}",
-                    params.deadline,
-                    policy.wpost_period_deadlines
-                ));
-            }
-
-            if params.chain_commit_rand.0.len() > RANDOMNESS_LENGTH {
-                return Err(actor_error!(
-                    illegal_argument,
-                    "expected at most {} bytes of randomness, got {}",
-                    RANDOMNESS_LENGTH,
-                    params.chain_commit_rand.0.len()
-                ));
-            }
-        }
+        validate_windowed_post_params(rt.policy(), rt.network_version(), &params)?;
 
         let post_result = rt.transaction(|state: &mut State, rt| {
             let info = get_miner_info(rt.store(), state)?;
+            rt.validate_immediate_caller_is(info.proving_addresses())?;
+            record_windowed_post(rt, state, &info, params)
+        })?;
 
-            let max_proof_size = info.window_post_proof_type.proof_size().map_err(|e| {
-                actor_error!(illegal_state, "failed to determine max window post proof size: {}", e)
-            })?;
-
-            rt.validate_immediate_caller_is(
-                info.control_addresses.iter().chain(&[info.worker, info.owner]),
-            )?;
-
-            // Make sure the miner is using the correct proof type.
-            if params.proofs[0].post_proof != info.window_post_proof_type {
-                return Err(actor_error!(
-                    illegal_argument,
-                    "expected proof of type {:?}, got {:?}",
-                    info.window_post_proof_type,
-                    params.proofs[0].post_proof
-                ));
-            }
-
-            // Make sure the proof size doesn't exceed the max. We could probably check for an exact match, but this is safer.
-            let max_size = max_proof_size * params.partitions.len();
-            if params.proofs[0].proof_bytes.len() > max_size {
-                return Err(actor_error!(
-                    illegal_argument,
-                    "expected proof to be smaller than {} bytes",
-                    max_size
-                ));
-            }
-
-            // Validate that the miner didn't try to prove too many partitions at once.
-            let submission_partition_limit = cmp::min(
-                load_partitions_sectors_max(rt.policy(), info.window_post_partition_sectors),
-                rt.policy().posted_partitions_max,
-            );
-
-            if params.partitions.len() as u64 > submission_partition_limit {
-                return Err(actor_error!(
-                    illegal_argument,
-                    "too many partitions {}, limit {}",
-                    params.partitions.len(),
-                    submission_partition_limit
-                ));
-            }
-            let current_deadline = state.deadline_info(rt.policy(), current_epoch);
-
-            // Check that the miner state indicates that the current proving deadline has started.
-            // This should only fail if the cron actor wasn't invoked, and matters only in case that it hasn't been
-            // invoked for a whole proving period, and hence the missed PoSt submissions from the prior occurrence
-            // of this deadline haven't been processed yet.
-            if !current_deadline.is_open() {
-                return Err(actor_error!(
-                    illegal_state,
-                    "proving period {} not yet open at {}",
-                    current_deadline.period_start,
-                    current_epoch
-                ));
-            }
-
-            // The miner may only submit a proof for the current deadline.
-            if params.deadline != current_deadline.index {
-                return Err(actor_error!(
-                    illegal_argument,
-                    "invalid deadline {} at epoch {}, expected {}",
-                    params.deadline,
-                    current_epoch,
-                    current_deadline.index
-                ));
-            }
-
-            // Verify that the PoSt was committed to the chain at most
-            // WPoStChallengeLookback+WPoStChallengeWindow in the past.
-            if params.chain_commit_epoch < current_deadline.challenge {
-                return Err(actor_error!(
-                    illegal_argument,
-                    "expected chain commit epoch {} to be after {}",
-                    params.chain_commit_epoch,
-                    current_deadline.challenge
-                ));
-            }
-
-            if params.chain_commit_epoch >= current_epoch {
-                return Err(actor_error!(
-                    illegal_argument,
-                    "chain commit epoch {} must be less than the current epoch {}",
-                    params.chain_commit_epoch,
-                    current_epoch
-                ));
-            }
-
-            // Verify the chain commit randomness
-            let comm_rand = rt.get_randomness_from_tickets(
-                DomainSeparationTag::PoStChainCommit,
-                params.chain_commit_epoch,
-                &[],
-            )?;
-            if Randomness(comm_rand.into()) != params.chain_commit_rand {
-                return Err(actor_error!(illegal_argument, "post commit randomness mismatched"));
-            }
+        // Restore power for recovered sectors. Remove power for new faults.
+        // NOTE: It would be permissible to delay the power loss until the deadline closes, but that would require
+        // additional accounting state.
+        // https://github.com/filecoin-project/specs-actors/issues/414
+        request_update_power(rt, post_result.power_delta)?;
 
-            let sectors = Sectors::load(rt.store(), &state.sectors).map_err(|e| {
-                e.downcast_default(ExitCode::USR_ILLEGAL_STATE, "failed to load sectors")
-            })?;
+        let state: State = rt.state()?;
+        state.check_balance_invariants(&rt.current_balance()).map_err(balance_invariants_broken)?;
 
-            let mut deadlines =
-                state.load_deadlines(rt.store()).map_err(|e| e.wrap("failed to load deadlines"))?;
+        Ok(())
+    }
 
-            let mut deadline = deadlines.load_deadline(rt.store(), params.deadline)?;
+    /// Submits Window PoSts for a number of deadlines in one message.
+    /// Each submission is validated against its own deadline's challenge window and recorded
+    /// independently, so that a failed submission doesn't prevent the others from being recorded.
+    /// Fails only if no submission could be recorded.
+    fn submit_windowed_post2(
+        rt: &impl Runtime,
+        params: SubmitWindowedPoSt2Params,
+    ) -> Result<SubmitWindowedPoSt2Return, ActorError> {
+        let policy = rt.policy();
+        let state: State = rt.state()?;
+        let info = get_miner_info(rt.store(), &state)?;
+        rt.validate_immediate_caller_is(info.proving_addresses())?;
 
-            // Record proven sectors/partitions, returning updates to power and the final set of sectors
-            // proven/skipped.
-            //
-            // NOTE: This function does not actually check the proofs but does assume that they're correct. Instead,
-            // it snapshots the deadline's state and the submitted proofs at the end of the challenge window and
-            // allows third-parties to dispute these proofs.
-            //
-            // While we could perform _all_ operations at the end of challenge window, we do as we can here to avoid
-            // overloading cron.
-            let policy = rt.policy();
-            let fault_expiration = current_deadline.last() + policy.fault_max_age;
-            let post_result = deadline
-                .record_proven_sectors(
-                    rt.store(),
-                    &sectors,
-                    info.sector_size,
-                    current_deadline.quant_spec(),
-                    fault_expiration,
-                    &mut params.partitions,
-                )
-                .map_err(|e| {
-                    e.downcast_default(
-                        ExitCode::USR_ILLEGAL_STATE,
-                        format!(
-                            "failed to process post submission for deadline {}",
-                            params.deadline
-                        ),
-                    )
-                })?;
+        if params.posts.is_empty() {
+            return Err(actor_error!(illegal_argument, "no posts submitted"));
+        }
+        if params.posts.len() as u64 > policy.wpost_period_deadlines {
+            return Err(actor_error!(
+                illegal_argument,
+                "too many posts {}, limit {}",
+                params.posts.len(),
+                policy.wpost_period_deadlines
+            ));
+        }
 
-            // Make sure we actually proved something.
-            let proven_sectors = &post_result.sectors - &post_result.ignored_sectors;
-            if proven_sectors.is_empty() {
-                // Abort verification if all sectors are (now) faults. There's nothing to prove.
-                // It's not rational for a miner to submit a Window PoSt marking *all* non-faulty sectors as skipped,
-                // since that will just cause them to pay a penalty at deadline end that would otherwise be zero
-                // if they had *not* declared them.
-                return Err(actor_error!(
-                    illegal_argument,
-                    "cannot prove partitions with no active sectors"
-                ));
-            }
-            // If we're not recovering power, record the proof for optimistic verification.
-            if post_result.recovered_power.is_zero() {
-                deadline
-                    .record_post_proofs(rt.store(), &post_result.partitions, &params.proofs)
-                    .map_err(|e| {
-                        e.downcast_default(
-                            ExitCode::USR_ILLEGAL_STATE,
-                            "failed to record proof for optimistic verification",
-                        )
-                    })?
-            } else {
-                // Load sector infos for proof, substituting a known-good sector for known-faulty sectors.
-                // Note: this is slightly sub-optimal, loading info for the recovering sectors again after they were already
-                // loaded above.
-                let sector_infos = sectors
-                    .load_for_proof(&post_result.sectors, &post_result.ignored_sectors)
-                    .map_err(|e| {
-                        e.downcast_default(
-                            ExitCode::USR_ILLEGAL_STATE,
-                            "failed to load sectors for post verification",
-                        )
-                    })?;
-                if !verify_windowed_post(
-                    rt,
-                    current_deadline.challenge,
-                    &sector_infos,
-                    params.proofs,
-                )
-                .map_err(|e| e.wrap("window post failed"))?
-                {
-                    return Err(actor_error!(illegal_argument, "invalid post was submitted"));
+        let mut batch = BatchReturnGen::new(params.posts.len());
+        let mut power_delta = PowerPair::zero();
+        for post in params.posts {
+            let deadline = post.deadline;
+            let result = validate_windowed_post_params(policy, rt.network_version(), &post)
+                .and_then(|_| {
+                    rt.transaction(|state: &mut State, rt| {
+                        record_windowed_post(rt, state, &info, post)
+                    })
+                });
+            match result {
+                Ok(post_result) => {
+                    power_delta = power_delta + post_result.power_delta;
+                    batch.add_success();
+                }
+                Err(e) => {
+                    info!("failed to record window post for deadline {}: {}", deadline, e);
+                    batch.add_fail(e.exit_code());
                 }
             }
+        }
+        let results = batch.gen();
+        if results.success_count == 0 {
+            return Err(actor_error!(illegal_argument, "no window posts recorded"));
+        }
 
-            let deadline_idx = params.deadline;
-            deadlines.update_deadline(policy, rt.store(), params.deadline, &deadline).map_err(
-                |e| {
-                    e.downcast_default(
-                        ExitCode::USR_ILLEGAL_STATE,
-                        format!("failed to update deadline {}", deadline_idx),
-                    )
-                },
-            )?;
-
-            state.save_deadlines(rt.store(), deadlines).map_err(|e| {
-                e.downcast_default(ExitCode::USR_ILLEGAL_STATE, "failed to save deadlines")
-            })?;
-
-            Ok(post_result)
-        })?;
-
-        // Restore power for recovered sectors. Remove power for new faults.
-        // NOTE: It would be permissible to delay the power loss until the deadline closes, but that would require
-        // additional accounting state.
-        // https://github.com/filecoin-project/specs-actors/issues/414
-        request_update_power(rt, post_result.power_delta)?;
+        request_update_power(rt, power_delta)?;
 
         let state: State = rt.state()?;
         state.check_balance_invariants(&rt.current_balance()).map_err(balance_invariants_broken)?;
 
-        Ok(())
+        Ok(SubmitWindowedPoSt2Return { results })
     }
     /// Checks state of the corresponding sector pre-commitments and verifies aggregate proof of replication
     /// of these sectors. If valid, the sectors' deals are activated, sectors are assigned a deadline and charged pledge
@@ -844,6 +963,10 @@ impl Actor {
             let unsealed_cid = pc.info.unsealed_cid.0;
             emit::sector_activated(rt, pc.info.sector_number, unsealed_cid, &data.pieces)?;
         }
+        index_activated_pieces(
+            rt,
+            activated_precommits.iter().map(|pc| pc.info.sector_number).zip(&activated_data),
+        )?;
 
         // The aggregate fee is paid on the sectors successfully proven.
         pay_aggregate_seal_proof_fee(rt, valid_precommits.len())?;
@@ -1003,6 +1126,14 @@ impl Actor {
         notify_pledge_changed(rt, &pledge_delta)?;
         request_update_power(rt, power_delta)?;
 
+        let updated_pieces = validated_updates
+            .iter()
+            .map(|(usi, data)| {
+                (usi.update.sector_number, data.pieces.iter().map(|(cid, _)| *cid).collect())
+            })
+            .collect();
+        index_sector_pieces(rt, updated_pieces)?;
+
         let updated_bitfield = BitField::try_from_bits(updated_sectors)
             .context_code(ExitCode::USR_ILLEGAL_ARGUMENT, "invalid sector number")?;
         Ok(updated_bitfield)
@@ -1214,6 +1345,12 @@ impl Actor {
         notify_pledge_changed(rt, &pledge_delta)?;
         request_update_power(rt, power_delta)?;
 
+        let updated_pieces = successful_manifests
+            .iter()
+            .map(|(update, _)| (update.sector, update.pieces.iter().map(|p| p.cid).collect()))
+            .collect();
+        index_sector_pieces(rt, updated_pieces)?;
+
         // Notify data consumers.
         let mut notifications: Vec<ActivationNotifications> = vec![];
         for (update, sector_info) in successful_manifests {
@@ -1372,6 +1509,7 @@ impl Actor {
                     .map_err(|e| {
                         e.downcast_default(ExitCode::USR_ILLEGAL_STATE, "failed to declare faults")
                     })?;
+                dl_current.record_post_disputed();
 
                 deadlines_current
                     .update_deadline(policy, rt.store(), params.deadline, &dl_current)
@@ -1456,31 +1594,43 @@ impl Actor {
         rt: &impl Runtime,
         params: PreCommitSectorBatchParams2,
     ) -> Result<(), ActorError> {
+        let all_or_nothing = true;
         Self::pre_commit_sector_batch_inner(
             rt,
-            params
-                .sectors
-                .into_iter()
-                .map(|spci| SectorPreCommitInfoInner {
-                    seal_proof: spci.seal_proof,
-                    sector_number: spci.sector_number,
-                    sealed_cid: spci.sealed_cid,
-                    seal_rand_epoch: spci.seal_rand_epoch,
-                    deal_ids: spci.deal_ids,
-                    expiration: spci.expiration,
-
-                    unsealed_cid: spci.unsealed_cid,
-                })
-                .collect(),
-        )
+            params.sectors.into_iter().map(SectorPreCommitInfoInner::from).collect(),
+            all_or_nothing,
+        )?;
+        Ok(())
+    }
+
+    /// Pledges the miner to seal and commit some new sectors, like PreCommitSectorBatch2,
+    /// but skipping sectors that fail validation or deal verification instead of aborting the
+    /// whole batch. If the available balance can't cover every deposit, the last sectors in the
+    /// batch are skipped too.
+    /// The aggregate fee and pre-commit deposits are charged only for the sectors that are
+    /// pre-committed, and the result reports an exit code for each sector in the batch.
+    fn pre_commit_sector_batch3(
+        rt: &impl Runtime,
+        params: PreCommitSectorBatchParams3,
+    ) -> Result<PreCommitSectorBatch3Return, ActorError> {
+        let all_or_nothing = false;
+        let precommit_results = Self::pre_commit_sector_batch_inner(
+            rt,
+            params.sectors.into_iter().map(SectorPreCommitInfoInner::from).collect(),
+            all_or_nothing,
+        )?;
+        Ok(PreCommitSectorBatch3Return { precommit_results })
     }
 
     /// This function combines old and new flows for PreCommit with use Option<CommpactCommD>
     /// The old PreCommits will call this with None, new ones with Some(CompactCommD).
+    /// If all_or_nothing is false, invalid sectors are skipped and reported in the returned
+    /// batch result rather than aborting the whole batch.
     fn pre_commit_sector_batch_inner(
         rt: &impl Runtime,
         sectors: Vec<SectorPreCommitInfoInner>,
-    ) -> Result<(), ActorError> {
+        all_or_nothing: bool,
+    ) -> Result<BatchReturn, ActorError> {
         let curr_epoch = rt.curr_epoch();
         {
             let policy = rt.policy();
@@ -1496,123 +1646,109 @@ impl Actor {
             }
         }
         // Check per-sector preconditions before opening state transaction or sending other messages.
-        let challenge_earliest = curr_epoch - rt.policy().max_pre_commit_randomness_lookback;
+        let mut precheck_batch = BatchReturnGen::new(sectors.len());
+        let mut valid_sectors = Vec::with_capacity(sectors.len());
         let mut sectors_deals = Vec::with_capacity(sectors.len());
         let mut sector_numbers = BitField::new();
-        for precommit in sectors.iter() {
-            let set = sector_numbers.get(precommit.sector_number);
-            if set {
-                return Err(actor_error!(
+        for precommit in sectors {
+            let validity = if sector_numbers.get(precommit.sector_number) {
+                Err(actor_error!(
                     illegal_argument,
                     "duplicate sector number {}",
                     precommit.sector_number
-                ));
-            }
-            sector_numbers.set(precommit.sector_number);
-
-            if !can_pre_commit_seal_proof(rt.policy(), precommit.seal_proof) {
-                return Err(actor_error!(
-                    illegal_argument,
-                    "unsupported seal proof type {}",
-                    i64::from(precommit.seal_proof)
-                ));
-            }
-            if precommit.sector_number > MAX_SECTOR_NUMBER {
-                return Err(actor_error!(
-                    illegal_argument,
-                    "sector number {} out of range 0..(2^63-1)",
-                    precommit.sector_number
-                ));
-            }
-            // Skip checking if CID is defined because it cannot be so in Rust
-
-            if !is_sealed_sector(&precommit.sealed_cid) {
-                return Err(actor_error!(illegal_argument, "sealed CID had wrong prefix"));
-            }
-            if precommit.seal_rand_epoch >= curr_epoch {
-                return Err(actor_error!(
-                    illegal_argument,
-                    "seal challenge epoch {} must be before now {}",
-                    precommit.seal_rand_epoch,
-                    curr_epoch
-                ));
-            }
-            if precommit.seal_rand_epoch < challenge_earliest {
-                return Err(actor_error!(
-                    illegal_argument,
-                    "seal challenge epoch {} too old, must be after {}",
-                    precommit.seal_rand_epoch,
-                    challenge_earliest
-                ));
-            }
-
-            if let Some(commd) = &precommit.unsealed_cid.0 {
-                if !is_unsealed_sector(commd) {
-                    return Err(actor_error!(illegal_argument, "unsealed CID had wrong prefix"));
+                ))
+            } else {
+                validate_precommit(rt.policy(), curr_epoch, &precommit)
+            };
+            if let Err(e) = validity {
+                if all_or_nothing {
+                    return Err(e);
                 }
+                info!("invalid pre-commit for sector {}: {}", precommit.sector_number, e);
+                precheck_batch.add_fail(e.exit_code());
+                continue;
             }
-
-            // Require sector lifetime meets minimum by assuming activation happens at last epoch permitted for seal proof.
-            // This could make sector maximum lifetime validation more lenient if the maximum sector limit isn't hit first.
-            let max_activation = curr_epoch
-                + max_prove_commit_duration(rt.policy(), precommit.seal_proof).unwrap_or_default();
-            validate_expiration(
-                rt.policy(),
-                curr_epoch,
-                max_activation,
-                precommit.expiration,
-                precommit.seal_proof,
-            )?;
+            sector_numbers.set(precommit.sector_number);
+            precheck_batch.add_success();
 
             sectors_deals.push(ext::market::SectorDeals {
                 sector_number: precommit.sector_number,
                 sector_type: precommit.seal_proof,
                 sector_expiry: precommit.expiration,
                 deal_ids: precommit.deal_ids.clone(),
-            })
+            });
+            valid_sectors.push(precommit);
+        }
+        let precheck_batch = precheck_batch.gen();
+        if valid_sectors.is_empty() {
+            // Every sector failed validation, so there is nothing to record.
+            // The caller must still be authorized to learn as much.
+            let state: State = rt.state()?;
+            let info = get_miner_info(rt.store(), &state)?;
+            rt.validate_immediate_caller_is(
+                info.control_addresses.iter().chain(&[info.worker, info.owner]),
+            )?;
+            return Ok(precheck_batch);
         }
+
         // gather information from other actors
         let reward_stats = request_current_epoch_block_reward(rt)?;
         let power_total = request_current_total_power(rt)?;
-        let verify_return = verify_deals(rt, &sectors_deals)?;
-        if verify_return.unsealed_cids.len() != sectors.len() {
-            return Err(actor_error!(
-                illegal_state,
-                "deal weight request returned {} records, expected {}",
-                verify_return.unsealed_cids.len(),
-                sectors.len()
-            ));
-        }
+        let allowlist = network_proof_type_allowlist(rt, &power_total);
+        // When skipping invalid sectors, a failure to verify the batch's deals falls back to
+        // verifying each sector's deals alone, dropping the sectors whose deals fail.
+        let mut deals_batch = BatchReturnGen::new(valid_sectors.len());
+        let unsealed_cids = match verify_deals(rt, &sectors_deals) {
+            Ok(verify_return) => {
+                if verify_return.unsealed_cids.len() != valid_sectors.len() {
+                    return Err(actor_error!(
+                        illegal_state,
+                        "deal weight request returned {} records, expected {}",
+                        verify_return.unsealed_cids.len(),
+                        valid_sectors.len()
+                    ));
+                }
+                deals_batch.add_successes(valid_sectors.len());
+                verify_return.unsealed_cids
+            }
+            Err(e) if all_or_nothing => return Err(e),
+            Err(e) => {
+                info!("failed to verify deals for pre-commit batch, verifying each sector: {}", e);
+                let mut unsealed_cids = Vec::with_capacity(valid_sectors.len());
+                let mut verified_sectors = Vec::with_capacity(valid_sectors.len());
+                for (precommit, sector_deals) in valid_sectors.into_iter().zip(sectors_deals) {
+                    match verify_deals(rt, std::slice::from_ref(&sector_deals)) {
+                        Ok(verify_return) => {
+                            let [unsealed_cid] = verify_return.unsealed_cids[..] else {
+                                return Err(actor_error!(
+                                    illegal_state,
+                                    "deal weight request returned {} records, expected 1",
+                                    verify_return.unsealed_cids.len()
+                                ));
+                            };
+                            deals_batch.add_success();
+                            unsealed_cids.push(unsealed_cid);
+                            verified_sectors.push(precommit);
+                        }
+                        Err(e) => {
+                            info!(
+                                "invalid deals for pre-commit of sector {}: {}",
+                                precommit.sector_number, e
+                            );
+                            deals_batch.add_fail(e.exit_code());
+                        }
+                    }
+                }
+                valid_sectors = verified_sectors;
+                unsealed_cids
+            }
+        };
+        let deals_batch = deals_batch.gen();
         let mut fee_to_burn = TokenAmount::zero();
         let mut needs_cron = false;
+        // The exit code for each sector which passed deal verification.
+        let mut state_codes = Vec::with_capacity(valid_sectors.len());
         rt.transaction(|state: &mut State, rt| {
-            // Aggregate fee applies only when batching.
-            if sectors.len() > 1 {
-                let aggregate_fee = aggregate_pre_commit_network_fee(sectors.len(), &rt.base_fee());
-                // AggregateFee applied to fee debt to consolidate burn with outstanding debts
-                state.apply_penalty(&aggregate_fee)
-                    .map_err(|e| {
-                        actor_error!(
-                        illegal_state,
-                        "failed to apply penalty: {}",
-                        e
-                    )
-                    })?;
-            }
-            // available balance already accounts for fee debt so it is correct to call
-            // this before RepayDebts. We would have to
-            // subtract fee debt explicitly if we called this after.
-            let available_balance = state
-                .get_available_balance(&rt.current_balance())
-                .map_err(|e| {
-                    actor_error!(
-                        illegal_state,
-                        "failed to calculate available balance: {}",
-                        e
-                    )
-                })?;
-            fee_to_burn = repay_debts_or_abort(rt, state)?;
-
             let info = get_miner_info(rt.store(), state)?;
 
             rt.validate_immediate_caller_is(
@@ -1625,64 +1761,67 @@ impl Actor {
                 return Err(actor_error!(forbidden, "pre-commit not allowed during active consensus fault"));
             }
 
-            let mut chain_infos = Vec::with_capacity(sectors.len());
+            // When skipping invalid sectors, find already-allocated sector numbers up front
+            // rather than letting the allocation below abort the whole batch.
+            let allocated_sectors: BitField = if all_or_nothing {
+                BitField::new()
+            } else {
+                store.get_cbor(&state.allocated_sectors)
+                    .map_err(|e| e.downcast_default(ExitCode::USR_ILLEGAL_STATE, "failed to load allocated sectors bitfield"))?
+                    .ok_or_else(|| actor_error!(illegal_state, "allocated sectors bitfield not found"))?
+            };
+
+            let mut chain_infos = Vec::with_capacity(valid_sectors.len());
             let mut total_deposit_required = TokenAmount::zero();
-            let mut clean_up_events = Vec::with_capacity(sectors.len());
+            let mut clean_up_events = Vec::with_capacity(valid_sectors.len());
             let deal_count_max = sector_deals_max(rt.policy(), info.sector_size);
 
             let sector_weight_for_deposit = qa_power_max(info.sector_size);
             let deposit_req = pre_commit_deposit_for_power(&reward_stats.this_epoch_reward_smoothed, &power_total.quality_adj_power_smoothed, &sector_weight_for_deposit);
 
-            for (i, precommit) in sectors.into_iter().enumerate() {
-                // Sector must have the same Window PoSt proof type as the miner's recorded seal type.
-                let sector_wpost_proof = precommit.seal_proof
-                    .registered_window_post_proof()
-                    .map_err(|_e|
-                        actor_error!(
-                        illegal_argument,
-                        "failed to lookup Window PoSt proof type for sector seal proof {}",
-                        i64::from(precommit.seal_proof)
-                    ))?;
-                if sector_wpost_proof != info.window_post_proof_type {
-                    return Err(actor_error!(illegal_argument, "sector Window PoSt proof type %d must match miner Window PoSt proof type {} (seal proof type {})", i64::from(sector_wpost_proof), i64::from(info.window_post_proof_type)));
-                }
-                if precommit.deal_ids.len() as u64 > deal_count_max {
-                    return Err(actor_error!(illegal_argument, "too many deals for sector {} > {}", precommit.deal_ids.len(), deal_count_max));
-                }
-
-                // 1. verify that precommit.unsealed_cid is correct
-                // 2. create a new on_chain_precommit
-
-                // Presence of unsealed CID is checked in the preconditions.
-                // It must always be specified from nv22 onwards.
-                let declared_commd = precommit.unsealed_cid;
-                // This is not a CompactCommD, None means that nothing was computed and nothing needs to be checked
-                if let Some(computed_cid) = verify_return.unsealed_cids[i] {
-                    // It is possible the computed commd is the zero commd so expand declared_commd
-                    if declared_commd.get_cid(precommit.seal_proof)? != computed_cid {
-                        return Err(actor_error!(illegal_argument, "computed {:?} and passed {:?} CommDs not equal",
-                                computed_cid, declared_commd));
+            for (i, precommit) in valid_sectors.into_iter().enumerate() {
+                let validate = || -> Result<(), ActorError> {
+                    if allocated_sectors.get(precommit.sector_number) {
+                        return Err(actor_error!(illegal_argument, "sector number {} already allocated", precommit.sector_number));
+                    }
+                    validate_seal_proof_allowed(allowlist, precommit.seal_proof, can_pre_commit_seal_proof(rt.policy(), precommit.seal_proof))?;
+                    // Sector must have the same Window PoSt proof type as the miner's recorded seal type.
+                    let sector_wpost_proof = precommit.seal_proof
+                        .registered_window_post_proof()
+                        .map_err(|_e|
+                            actor_error!(
+                            illegal_argument,
+                            "failed to lookup Window PoSt proof type for sector seal proof {}",
+                            i64::from(precommit.seal_proof)
+                        ))?;
+                    if sector_wpost_proof != info.window_post_proof_type {
+                        return Err(actor_error!(illegal_argument, "sector Window PoSt proof type %d must match miner Window PoSt proof type {} (seal proof type {})", i64::from(sector_wpost_proof), i64::from(info.window_post_proof_type)));
+                    }
+                    if precommit.deal_ids.len() as u64 > deal_count_max {
+                        return Err(actor_error!(illegal_argument, "too many deals for sector {} > {}", precommit.deal_ids.len(), deal_count_max));
                     }
-                }
 
-                let on_chain_precommit = SectorPreCommitInfo {
-                    seal_proof: precommit.seal_proof,
-                    sector_number: precommit.sector_number,
-                    sealed_cid: precommit.sealed_cid,
-                    seal_rand_epoch: precommit.seal_rand_epoch,
-                    deal_ids: precommit.deal_ids,
-                    expiration: precommit.expiration,
-                    unsealed_cid: declared_commd,
+                    // Presence of unsealed CID is checked in the preconditions.
+                    // It must always be specified from nv22 onwards.
+                    // This is not a CompactCommD, None means that nothing was computed and nothing needs to be checked
+                    if let Some(computed_cid) = unsealed_cids[i] {
+                        // It is possible the computed commd is the zero commd so expand declared_commd
+                        if precommit.unsealed_cid.get_cid(precommit.seal_proof)? != computed_cid {
+                            return Err(actor_error!(illegal_argument, "computed {:?} and passed {:?} CommDs not equal",
+                                    computed_cid, precommit.unsealed_cid));
+                        }
+                    }
+                    Ok(())
                 };
-
-                // Build on-chain record.
-                chain_infos.push(SectorPreCommitOnChainInfo {
-                    info: on_chain_precommit,
-                    pre_commit_deposit: deposit_req.clone(),
-                    pre_commit_epoch: curr_epoch,
-                });
-
-                total_deposit_required += &deposit_req;
+                if let Err(e) = validate() {
+                    if all_or_nothing {
+                        return Err(e);
+                    }
+                    info!("invalid pre-commit for sector {}: {}", precommit.sector_number, e);
+                    state_codes.push(e.exit_code());
+                    continue;
+                }
+                state_codes.push(ExitCode::OK);
 
                 // Calculate pre-commit cleanup
                 let seal_proof = precommit.seal_proof;
@@ -1693,7 +1832,85 @@ impl Actor {
                 // ConfirmSectorProofsValid would fail to find it.
                 let clean_up_bound = curr_epoch + msd + rt.policy().expired_pre_commit_clean_up_delay;
                 clean_up_events.push((clean_up_bound, precommit.sector_number));
+
+                // Build on-chain record.
+                chain_infos.push(SectorPreCommitOnChainInfo {
+                    info: SectorPreCommitInfo {
+                        seal_proof: precommit.seal_proof,
+                        sector_number: precommit.sector_number,
+                        sealed_cid: precommit.sealed_cid,
+                        seal_rand_epoch: precommit.seal_rand_epoch,
+                        deal_ids: precommit.deal_ids,
+                        expiration: precommit.expiration,
+                        unsealed_cid: precommit.unsealed_cid,
+                    },
+                    pre_commit_deposit: deposit_req.clone(),
+                    pre_commit_epoch: curr_epoch,
+                });
+
+                total_deposit_required += &deposit_req;
+            }
+            let aggregate_fee = |count: usize| {
+                // Aggregate fee applies only when batching.
+                if count > 1 {
+                    aggregate_pre_commit_network_fee(count, &rt.base_fee())
+                } else {
+                    TokenAmount::zero()
+                }
+            };
+            if !all_or_nothing {
+                // Drop the last sectors until the deposits for the rest, and their aggregate fee,
+                // are covered by the available balance.
+                let available_balance = state
+                    .get_available_balance(&rt.current_balance())
+                    .map_err(|e| actor_error!(illegal_state, "failed to calculate available balance: {}", e))?;
+                let mut affordable = chain_infos.len();
+                while affordable > 0
+                    && available_balance.clone() - aggregate_fee(affordable) < &deposit_req * affordable as u64
+                {
+                    affordable -= 1;
+                }
+                for code in state_codes.iter_mut().filter(|code| code.is_success()).skip(affordable) {
+                    *code = ExitCode::USR_INSUFFICIENT_FUNDS;
+                }
+                chain_infos.truncate(affordable);
+                clean_up_events.truncate(affordable);
+                total_deposit_required = &deposit_req * affordable as u64;
+            }
+            if chain_infos.is_empty() {
+                // Every remaining sector was invalid or unaffordable, leave state untouched.
+                return Ok(());
+            }
+            let precommitted_numbers =
+                BitField::try_from_bits(chain_infos.iter().map(|precommit| precommit.info.sector_number))
+                    .map_err(|e| actor_error!(illegal_argument, "invalid sector numbers: {}", e))?;
+
+            if chain_infos.len() > 1 {
+                let aggregate_fee = aggregate_fee(chain_infos.len());
+                // AggregateFee applied to fee debt to consolidate burn with outstanding debts
+                state.apply_penalty(&aggregate_fee)
+                    .map_err(|e| {
+                        actor_error!(
+                        illegal_state,
+                        "failed to apply penalty: {}",
+                        e
+                    )
+                    })?;
             }
+            // available balance already accounts for fee debt so it is correct to call
+            // this before RepayDebts. We would have to
+            // subtract fee debt explicitly if we called this after.
+            let available_balance = state
+                .get_available_balance(&rt.current_balance())
+                .map_err(|e| {
+                    actor_error!(
+                        illegal_state,
+                        "failed to calculate available balance: {}",
+                        e
+                    )
+                })?;
+            fee_to_burn = repay_debts_or_abort(rt, state)?;
+
             // Batch update actor state.
             if available_balance < total_deposit_required {
                 return Err(actor_error!(insufficient_funds, "insufficient funds {} for pre-commit deposit: {}", available_balance, total_deposit_required));
@@ -1705,7 +1922,7 @@ impl Actor {
                         "failed to add pre-commit deposit {}: {}",
                         total_deposit_required, e
                 ))?;
-            state.allocate_sector_numbers(store, &sector_numbers, CollisionPolicy::DenyCollisions)?;
+            state.allocate_sector_numbers(store, &precommitted_numbers, CollisionPolicy::DenyCollisions)?;
             state.put_precommitted_sectors(store, chain_infos)
                 .map_err(|e|
                     e.downcast_default(ExitCode::USR_ILLEGAL_STATE, "failed to write pre-committed sectors")
@@ -1715,7 +1932,7 @@ impl Actor {
                     e.downcast_default(ExitCode::USR_ILLEGAL_STATE, "failed to add pre-commit expiry to queue")
                 })?;
 
-            for sector_num in sector_numbers.iter() {
+            for sector_num in precommitted_numbers.iter() {
                 emit::sector_precommitted(rt, sector_num)?;
             }
             // Activate miner cron
@@ -1734,7 +1951,7 @@ impl Actor {
                 CronEventPayload { event_type: CRON_EVENT_PROVING_DEADLINE },
             )?;
         }
-        Ok(())
+        Ok(util::stack(&[precheck_batch, deals_batch, BatchReturn::of(&state_codes)]))
     }
 
     fn prove_commit_sectors3(
@@ -1875,16 +2092,21 @@ impl Actor {
             return Err(actor_error!(illegal_argument, "no valid proofs specified"));
         }
 
+        // Verify client-signed manifests, for which the market checks CommD.
+        let manifest_sectors = verify_client_manifests(rt, &proven_activation_inputs)?;
+
         // Activate data and verify CommD matches the declared one.
         let data_activation_inputs = proven_activation_inputs
             .iter()
             .map(|(activation, precommit)| -> SectorPiecesActivationInput {
+                let commd_verified = manifest_sectors.contains(&precommit.info.sector_number);
                 SectorPiecesActivationInput {
                     piece_manifests: activation.pieces.clone(),
                     sector_expiry: precommit.info.expiration,
                     sector_number: precommit.info.sector_number,
                     sector_type: precommit.info.seal_proof,
-                    expected_commd: Some(precommit.info.unsealed_cid.clone()), // Check CommD
+                    // Check CommD, unless already checked against the client manifests.
+                    expected_commd: (!commd_verified).then(|| precommit.info.unsealed_cid.clone()),
                 }
             })
             .collect();
@@ -1943,12 +2165,143 @@ impl Actor {
 
             emit::sector_activated(rt, sector.info.sector_number, unsealed_cid, &pieces)?;
         }
+        let activated_pieces = successful_sector_activations
+            .iter()
+            .filter(|(activations, _)| !activations.pieces.is_empty())
+            .map(|(activations, _)| {
+                (activations.sector_number, activations.pieces.iter().map(|p| p.cid).collect())
+            })
+            .collect();
+        index_sector_pieces(rt, activated_pieces)?;
         notify_data_consumers(rt, &notifications, params.require_notification_success)?;
 
         let result = util::stack(&[validation_batch, proven_batch, data_batch]);
         Ok(ProveCommitSectors3Return { activation_results: result })
     }
 
+    /// Proves pre-committed sectors ahead of their activation ("sealing ahead"), deferring the
+    /// activation of their power and initial pledge to a later epoch, within the policy's
+    /// maximum sealed-ahead delay. The sectors must hold no data, which may be added later by a
+    /// replica update. Until activated, the sectors keep their pre-commits and pre-commit
+    /// deposits. They are activated by the deadline cron at the end of the deadline in which the
+    /// activation epoch falls.
+    fn prove_commit_sealed_ahead(
+        rt: &impl Runtime,
+        params: ProveCommitSealedAheadParams,
+    ) -> Result<(), ActorError> {
+        let state: State = rt.state()?;
+        let store = rt.store();
+        let policy = rt.policy();
+        let curr_epoch = rt.curr_epoch();
+        let miner_id = rt.message().receiver().id().unwrap();
+        let info = get_miner_info(store, &state)?;
+
+        rt.validate_immediate_caller_is(
+            info.control_addresses.iter().chain(&[info.worker, info.owner]),
+        )?;
+
+        if params.activation_epoch <= curr_epoch
+            || params.activation_epoch > curr_epoch + policy.max_sealed_ahead_delay
+        {
+            return Err(actor_error!(
+                illegal_argument,
+                "activation epoch {} must be after the current epoch {} and at most {} epochs later",
+                params.activation_epoch,
+                curr_epoch,
+                policy.max_sealed_ahead_delay
+            ));
+        }
+        if params.sector_numbers.len() != params.sector_proofs.len() {
+            return Err(actor_error!(
+                illegal_argument,
+                "mismatched lengths: {} sectors, {} proofs",
+                params.sector_numbers.len(),
+                params.sector_proofs.len()
+            ));
+        }
+        let unique_sectors: BTreeSet<&SectorNumber> = params.sector_numbers.iter().collect();
+        if unique_sectors.len() != params.sector_numbers.len() {
+            return Err(actor_error!(illegal_argument, "duplicate sectors"));
+        }
+
+        let precommits = state
+            .get_precommitted_sectors(store, &params.sector_numbers)
+            .context("loading precommits")?;
+        if precommits.is_empty() {
+            return Err(actor_error!(illegal_argument, "no sectors to prove"));
+        }
+
+        // The sectors are activated by the cron at the end of the activation epoch's deadline.
+        let activation_epoch =
+            state.quant_spec_every_deadline(policy).quantize_up(params.activation_epoch);
+        for precommit in &precommits {
+            let sector_number = precommit.info.sector_number;
+            if state.sealed_ahead_sectors.get(sector_number) {
+                return Err(actor_error!(
+                    illegal_argument,
+                    "sector {} is already sealed ahead",
+                    sector_number
+                ));
+            }
+            if precommit.info.unsealed_cid.0.is_some() {
+                return Err(actor_error!(
+                    illegal_argument,
+                    "sector {} holds data, only sectors without data may be sealed ahead",
+                    sector_number
+                ));
+            }
+            let lifetime = precommit.info.expiration - activation_epoch;
+            if lifetime < policy.min_sector_expiration {
+                return Err(actor_error!(
+                    illegal_argument,
+                    "sector {} would have lifetime {} from activation, less than minimum {}",
+                    sector_number,
+                    lifetime,
+                    policy.min_sector_expiration
+                ));
+            }
+        }
+
+        validate_seal_proofs(precommits[0].info.seal_proof, &params.sector_proofs)?;
+        let (_, proof_inputs) = validate_precommits(rt, &precommits, false, true)?;
+        let seal_verify_inputs: Vec<SealVerifyInfo> = proof_inputs
+            .iter()
+            .zip(&params.sector_proofs)
+            .map(|(info, proof)| info.to_seal_verify_info(miner_id, proof))
+            .collect();
+        let res = rt
+            .batch_verify_seals(&seal_verify_inputs)
+            .context_code(ExitCode::USR_ILLEGAL_ARGUMENT, "failed to batch verify")?;
+        if let Some(i) = res.iter().position(|verified| !verified) {
+            return Err(actor_error!(
+                illegal_argument,
+                "invalid proof for sector {}",
+                precommits[i].info.sector_number
+            ));
+        }
+
+        rt.transaction(|state: &mut State, rt| {
+            state
+                .add_sealed_ahead_sectors(
+                    rt.policy(),
+                    rt.store(),
+                    activation_epoch,
+                    &params.sector_numbers,
+                )
+                .map_err(|e| {
+                    e.downcast_default(
+                        ExitCode::USR_ILLEGAL_STATE,
+                        "failed to record sealed-ahead sectors",
+                    )
+                })
+        })?;
+
+        for &sector_number in &params.sector_numbers {
+            emit::sector_sealed_ahead(rt, sector_number, activation_epoch)?;
+        }
+        Ok(())
+    }
+
     fn internal_sector_setup_preseal(
         rt: &impl Runtime,
         params: InternalSectorSetupForPresealParams,
@@ -2000,6 +2353,10 @@ impl Actor {
             let unsealed_cid = pc.info.unsealed_cid.0;
             emit::sector_activated(rt, pc.info.sector_number, unsealed_cid, &data.pieces)?;
         }
+        index_activated_pieces(
+            rt,
+            successful_activations.iter().map(|pc| pc.info.sector_number).zip(&data_activations),
+        )?;
 
         Ok(())
     }
@@ -2053,13 +2410,20 @@ impl Actor {
             ));
         }
 
-        if !can_prove_commit_ni_seal_proof(rt.policy(), params.seal_proof_type) {
+        if !is_ni_seal_proof(params.seal_proof_type) {
             return Err(actor_error!(
                 illegal_argument,
                 "unsupported seal proof type {}",
                 i64::from(params.seal_proof_type)
             ));
         }
+        let rew = request_current_epoch_block_reward(rt)?;
+        let pwr = request_current_total_power(rt)?;
+        validate_seal_proof_allowed(
+            network_proof_type_allowlist(rt, &pwr),
+            params.seal_proof_type,
+            can_prove_commit_ni_seal_proof(rt.policy(), params.seal_proof_type),
+        )?;
 
         if params.aggregate_proof_type != RegisteredAggregateProof::SnarkPackV2 {
             return Err(actor_error!(illegal_argument, "aggregate proof type must be SnarkPackV2"));
@@ -2091,8 +2455,6 @@ impl Actor {
         // With no data, QA power = raw power
         let qa_sector_power = raw_power_for_sector(info.sector_size);
 
-        let rew = request_current_epoch_block_reward(rt)?;
-        let pwr = request_current_total_power(rt)?;
         let circulating_supply = rt.total_fil_circ_supply();
         let pledge_inputs = NetworkPledgeInputs {
             network_qap: pwr.quality_adj_power_smoothed,
@@ -2145,6 +2507,7 @@ impl Actor {
                 replaced_day_reward: TokenAmount::zero(),
                 sector_key_cid: None,
                 flags: SectorOnChainInfoFlags::SIMPLE_QA_POWER,
+                proof_refreshed: None,
             })
             .collect::<Vec<SectorOnChainInfo>>();
 
@@ -2198,37 +2561,194 @@ impl Actor {
 
             let fee_to_burn = repay_debts_or_abort(rt, state)?;
 
-            Ok((needs_cron, fee_to_burn))
+            Ok((needs_cron, fee_to_burn))
+        })?;
+
+        burn_funds(rt, fee_to_burn)?;
+
+        let len_for_aggregate_fee = if sectors_len <= NI_AGGREGATE_FEE_BASE_SECTOR_COUNT {
+            0
+        } else {
+            sectors_len - NI_AGGREGATE_FEE_BASE_SECTOR_COUNT
+        };
+        pay_aggregate_seal_proof_fee(rt, len_for_aggregate_fee)?;
+
+        notify_pledge_changed(rt, &total_pledge)?;
+
+        let state: State = rt.state()?;
+        state.check_balance_invariants(&rt.current_balance()).map_err(balance_invariants_broken)?;
+
+        for sector in valid_sectors.iter() {
+            emit::sector_activated(rt, sector.sector_number, None, &[])?;
+        }
+
+        if needs_cron {
+            let new_dl_info = state.deadline_info(rt.policy(), curr_epoch);
+            enroll_cron_event(
+                rt,
+                new_dl_info.last(),
+                CronEventPayload { event_type: CRON_EVENT_PROVING_DEADLINE },
+            )?;
+        }
+
+        Ok(ProveCommitSectorsNIReturn { activation_results: validation_batch })
+    }
+
+    /// Renews the proof validity of sectors whose seal proof type has a limited validity period,
+    /// with a fresh non-interactive aggregate proof of their existing replicas.
+    /// The sectors' maximum lifetime is then measured from the current epoch, so they may be
+    /// extended further without a replica update.
+    fn extend_sector_proof_expiration(
+        rt: &impl Runtime,
+        params: ExtendSectorProofExpirationParams,
+    ) -> Result<(), ActorError> {
+        let policy = rt.policy();
+        let curr_epoch = rt.curr_epoch();
+        let state: State = rt.state()?;
+        let info = get_miner_info(rt.store(), &state)?;
+
+        validate_seal_aggregate_proof(
+            &params.aggregate_proof,
+            params.sectors.len() as u64,
+            policy,
+            false,
+        )?;
+
+        rt.validate_immediate_caller_is(
+            info.control_addresses.iter().chain(&[info.worker, info.owner]),
+        )?;
+
+        if consensus_fault_active(&info, curr_epoch) {
+            return Err(actor_error!(
+                forbidden,
+                "ExtendSectorProofExpiration not allowed during active consensus fault"
+            ));
+        }
+
+        if !is_ni_seal_proof(params.seal_proof_type) {
+            return Err(actor_error!(
+                illegal_argument,
+                "unsupported seal proof type {}",
+                i64::from(params.seal_proof_type)
+            ));
+        }
+        validate_seal_proof_allowed(
+            request_proof_type_allowlist(rt)?.as_ref(),
+            params.seal_proof_type,
+            can_prove_commit_ni_seal_proof(policy, params.seal_proof_type),
+        )?;
+
+        if params.aggregate_proof_type != RegisteredAggregateProof::SnarkPackV2 {
+            return Err(actor_error!(illegal_argument, "aggregate proof type must be SnarkPackV2"));
+        }
+
+        let receiver = rt.message().receiver();
+        let entropy = serialize(&receiver, "address for get verify info")?;
+        let challenge_earliest = curr_epoch - policy.max_prove_commit_ni_randomness_lookback;
+        let sectors = Sectors::load(rt.store(), &state.sectors).map_err(|e| {
+            e.downcast_default(ExitCode::USR_ILLEGAL_STATE, "failed to load sectors array")
         })?;
 
-        burn_funds(rt, fee_to_burn)?;
+        let mut sector_numbers = BitField::new();
+        let mut proof_inputs = Vec::with_capacity(params.sectors.len());
+        let mut refreshed_sectors = Vec::with_capacity(params.sectors.len());
+        for refresh in &params.sectors {
+            if sector_numbers.get(refresh.sector_number) {
+                return Err(actor_error!(
+                    illegal_argument,
+                    "duplicate sector number {}",
+                    refresh.sector_number
+                ));
+            }
+            sector_numbers.set(refresh.sector_number);
 
-        let len_for_aggregate_fee = if sectors_len <= NI_AGGREGATE_FEE_BASE_SECTOR_COUNT {
-            0
-        } else {
-            sectors_len - NI_AGGREGATE_FEE_BASE_SECTOR_COUNT
-        };
-        pay_aggregate_seal_proof_fee(rt, len_for_aggregate_fee)?;
+            let sector = sectors.must_get(refresh.sector_number)?;
+            if !can_extend_sector_proof_expiration(sector.seal_proof) {
+                return Err(actor_error!(
+                    forbidden,
+                    "cannot extend proof expiration for sector {} with seal type {:?}",
+                    sector.sector_number,
+                    sector.seal_proof
+                ));
+            }
+            if sector.seal_proof.sector_size() != params.seal_proof_type.sector_size() {
+                return Err(actor_error!(
+                    illegal_argument,
+                    "seal proof type {:?} does not match sector {} size",
+                    params.seal_proof_type,
+                    sector.sector_number
+                ));
+            }
+            if sector.expiration < curr_epoch {
+                return Err(actor_error!(
+                    forbidden,
+                    "cannot extend proof expiration for expired sector {} at {}",
+                    sector.sector_number,
+                    sector.expiration
+                ));
+            }
 
-        notify_pledge_changed(rt, &total_pledge)?;
+            if refresh.seal_rand_epoch >= curr_epoch {
+                return Err(actor_error!(
+                    illegal_argument,
+                    "seal challenge epoch {} must be before now {}",
+                    refresh.seal_rand_epoch,
+                    curr_epoch
+                ));
+            }
+            if refresh.seal_rand_epoch < challenge_earliest {
+                return Err(actor_error!(
+                    illegal_argument,
+                    "seal challenge epoch {} too old, must be after {}",
+                    refresh.seal_rand_epoch,
+                    challenge_earliest
+                ));
+            }
 
-        let state: State = rt.state()?;
-        state.check_balance_invariants(&rt.current_balance()).map_err(balance_invariants_broken)?;
+            proof_inputs.push(SectorSealProofInput {
+                registered_proof: params.seal_proof_type,
+                sector_number: sector.sector_number,
+                randomness: Randomness(
+                    rt.get_randomness_from_tickets(
+                        DomainSeparationTag::SealRandomness,
+                        refresh.seal_rand_epoch,
+                        &entropy,
+                    )?
+                    .into(),
+                ),
+                interactive_randomness: Randomness(vec![1u8; 32]),
+                sealed_cid: sector.sealed_cid,
+                unsealed_cid: refresh.unsealed_cid.get_cid(params.seal_proof_type)?,
+            });
 
-        for sector in valid_sectors.iter() {
-            emit::sector_activated(rt, sector.sector_number, None, &[])?;
+            let mut refreshed = sector;
+            refreshed.proof_refreshed = Some(curr_epoch);
+            refreshed_sectors.push(refreshed);
         }
 
-        if needs_cron {
-            let new_dl_info = state.deadline_info(rt.policy(), curr_epoch);
-            enroll_cron_event(
-                rt,
-                new_dl_info.last(),
-                CronEventPayload { event_type: CRON_EVENT_PROVING_DEADLINE },
-            )?;
+        if refreshed_sectors.is_empty() {
+            return Err(actor_error!(illegal_argument, "no sectors specified"));
         }
 
-        Ok(ProveCommitSectorsNIReturn { activation_results: validation_batch })
+        verify_aggregate_seal(
+            rt,
+            &proof_inputs,
+            receiver.id().unwrap(),
+            params.seal_proof_type,
+            params.aggregate_proof_type,
+            &params.aggregate_proof,
+        )?;
+
+        rt.transaction(|state: &mut State, rt| {
+            state
+                .put_sectors(rt.store(), refreshed_sectors)
+                .with_context_code(ExitCode::USR_ILLEGAL_STATE, || "failed to put sectors")
+        })?;
+
+        pay_aggregate_seal_proof_fee(
+            rt,
+            params.sectors.len().saturating_sub(NI_AGGREGATE_FEE_BASE_SECTOR_COUNT),
+        )
     }
 
     fn check_sector_proven(
@@ -2634,7 +3154,7 @@ impl Actor {
         let pwr_total = request_current_total_power(rt)?;
 
         // Now, try to process these sectors.
-        let more = process_early_terminations(
+        let (more, _) = process_early_terminations(
             rt,
             &epoch_reward.this_epoch_reward_smoothed,
             &pwr_total.quality_adj_power_smoothed,
@@ -2785,6 +3305,152 @@ impl Actor {
         Ok(())
     }
 
+    /// Declares all live sectors in the given deadlines faulty for their next challenge windows,
+    /// as planned downtime (e.g. for maintenance).
+    /// Power newly faulty as a result pays a reduced fault fee at the end of that challenge window,
+    /// after which any sectors still faulty pay the continued fault fee as usual.
+    fn declare_planned_downtime(
+        rt: &impl Runtime,
+        params: DeclarePlannedDowntimeParams,
+    ) -> Result<(), ActorError> {
+        {
+            let policy = rt.policy();
+            if params.deadlines.is_empty() {
+                return Err(actor_error!(illegal_argument, "no deadlines declared"));
+            }
+            if params.deadlines.len() as u64 > policy.declarations_max {
+                return Err(actor_error!(
+                    illegal_argument,
+                    "too many planned downtime declarations for a single message: {} > {}",
+                    params.deadlines.len(),
+                    policy.declarations_max
+                ));
+            }
+            if !params.deadlines.iter().all_unique() {
+                return Err(actor_error!(illegal_argument, "duplicate deadlines declared"));
+            }
+        }
+
+        let (power_delta, declared) = rt.transaction(|state: &mut State, rt| {
+            let info = get_miner_info(rt.store(), state)?;
+
+            rt.validate_immediate_caller_is(
+                info.control_addresses.iter().chain(&[info.worker, info.owner]),
+            )?;
+
+            let store = rt.store();
+            let policy = rt.policy();
+            let curr_epoch = rt.curr_epoch();
+
+            let mut deadlines =
+                state.load_deadlines(store).map_err(|e| e.wrap("failed to load deadlines"))?;
+
+            let sectors = Sectors::load(store, &state.sectors).map_err(|e| {
+                e.downcast_default(ExitCode::USR_ILLEGAL_STATE, "failed to load sectors array")
+            })?;
+
+            let mut new_fault_power_total = PowerPair::zero();
+            let mut declared = Vec::with_capacity(params.deadlines.len());
+            let mut addressed_partitions = 0;
+            for &deadline_idx in &params.deadlines {
+                let target_deadline = declaration_deadline_info(
+                    policy,
+                    state.current_proving_period_start(policy, curr_epoch),
+                    deadline_idx,
+                    curr_epoch,
+                )
+                .map_err(|e| {
+                    actor_error!(
+                        illegal_argument,
+                        "invalid planned downtime deadline {}: {}",
+                        deadline_idx,
+                        e
+                    )
+                })?;
+
+                validate_fr_declaration_deadline(&target_deadline).map_err(|e| {
+                    actor_error!(
+                        illegal_argument,
+                        "failed planned downtime declaration at deadline {}: {}",
+                        deadline_idx,
+                        e
+                    )
+                })?;
+
+                let mut deadline = deadlines.load_deadline(store, deadline_idx)?;
+
+                let mut partition_map = PartitionSectorMap::default();
+                deadline
+                    .for_each(store, |partition_idx, partition| {
+                        partition_map.add(partition_idx, partition.live_sectors())
+                    })
+                    .map_err(|e| {
+                        e.downcast_default(
+                            ExitCode::USR_ILLEGAL_STATE,
+                            format!("failed to load partitions for deadline {}", deadline_idx),
+                        )
+                    })?;
+
+                addressed_partitions += partition_map.len() as u64;
+                if addressed_partitions > policy.addressed_partitions_max {
+                    return Err(actor_error!(
+                        illegal_argument,
+                        "too many partitions for a single message: more than {}",
+                        policy.addressed_partitions_max
+                    ));
+                }
+
+                let faulty_power_before = deadline.faulty_power.clone();
+                let fault_expiration_epoch = target_deadline.last() + policy.fault_max_age;
+
+                let deadline_power_delta = deadline
+                    .record_faults(
+                        store,
+                        &sectors,
+                        info.sector_size,
+                        target_deadline.quant_spec(),
+                        fault_expiration_epoch,
+                        &mut partition_map,
+                    )
+                    .map_err(|e| {
+                        e.downcast_default(
+                            ExitCode::USR_ILLEGAL_STATE,
+                            format!("failed to declare faults for deadline {}", deadline_idx),
+                        )
+                    })?;
+
+                let planned_power = &deadline.faulty_power - &faulty_power_before;
+                deadline.planned_downtime_power += &planned_power;
+
+                deadlines.update_deadline(policy, store, deadline_idx, &deadline).map_err(|e| {
+                    e.downcast_default(
+                        ExitCode::USR_ILLEGAL_STATE,
+                        format!("failed to store deadline {} partitions", deadline_idx),
+                    )
+                })?;
+
+                new_fault_power_total += &deadline_power_delta;
+                declared.push((deadline_idx, target_deadline.open, planned_power));
+            }
+
+            state.save_deadlines(store, deadlines).map_err(|e| {
+                e.downcast_default(ExitCode::USR_ILLEGAL_STATE, "failed to save deadlines")
+            })?;
+
+            Ok((new_fault_power_total, declared))
+        })?;
+
+        // Remove power for new faulty sectors, as for declared faults.
+        request_update_power(rt, power_delta)?;
+
+        for (deadline_idx, open, power) in declared {
+            emit::planned_downtime(rt, deadline_idx, open, &power)?;
+        }
+
+        // Payment of the reduced penalty is deferred to the deadline cron.
+        Ok(())
+    }
+
     fn declare_faults_recovered(
         rt: &impl Runtime,
         params: DeclareFaultsRecoveredParams,
@@ -2834,9 +3500,7 @@ impl Actor {
 
             let info = get_miner_info(rt.store(), state)?;
 
-            rt.validate_immediate_caller_is(
-                info.control_addresses.iter().chain(&[info.worker, info.owner]),
-            )?;
+            rt.validate_immediate_caller_is(info.proving_addresses())?;
 
             if consensus_fault_active(&info, rt.curr_epoch()) {
                 return Err(actor_error!(
@@ -3105,6 +3769,7 @@ impl Actor {
             ));
         }
 
+        let balance_before = BalanceSnapshot::load(rt)?;
         let (pledge_delta_total, to_burn) = rt.transaction(|st: &mut State, rt| {
             let mut pledge_delta_total = TokenAmount::zero();
 
@@ -3165,112 +3830,43 @@ impl Actor {
         burn_funds(rt, to_burn)?;
         let st: State = rt.state()?;
         st.check_balance_invariants(&rt.current_balance()).map_err(balance_invariants_broken)?;
+        emit_balance_changed(rt, &balance_before)?;
         Ok(())
     }
 
     fn report_consensus_fault(
-        rt: &impl Runtime,
-        params: ReportConsensusFaultParams,
-    ) -> Result<(), ActorError> {
-        // Note: only the first report of any fault is processed because it sets the
-        // ConsensusFaultElapsed state variable to an epoch after the fault, and reports prior to
-        // that epoch are no longer valid
-        rt.validate_immediate_caller_accept_any()?;
-        let reporter = rt.message().caller();
-
-        let fault = rt
-            .verify_consensus_fault(&params.header1, &params.header2, &params.header_extra)
-            .map_err(|e| e.downcast_default(ExitCode::USR_ILLEGAL_ARGUMENT, "fault not verified"))?
-            .ok_or_else(|| actor_error!(illegal_argument, "No consensus fault found"))?;
-        if fault.target != rt.message().receiver() {
-            return Err(actor_error!(
-                illegal_argument,
-                "fault by {} reported to miner {}",
-                fault.target,
-                rt.message().receiver()
-            ));
-        }
-
-        // Elapsed since the fault (i.e. since the higher of the two blocks)
-        let fault_age = rt.curr_epoch() - fault.epoch;
-        if fault_age <= 0 {
-            return Err(actor_error!(
-                illegal_argument,
-                "invalid fault epoch {} ahead of current {}",
-                fault.epoch,
-                rt.curr_epoch()
-            ));
-        }
-
-        // Reward reporter with a share of the miner's current balance.
-        let reward_stats = request_current_epoch_block_reward(rt)?;
-
-        // The policy amounts we should burn and send to reporter
-        // These may differ from actual funds send when miner goes into fee debt
-        let this_epoch_reward =
-            TokenAmount::from_atto(reward_stats.this_epoch_reward_smoothed.estimate());
-        let fault_penalty = consensus_fault_penalty(this_epoch_reward.clone());
-        let slasher_reward = reward_for_consensus_slash_report(&this_epoch_reward);
-
-        let mut pledge_delta = TokenAmount::zero();
-
-        let (burn_amount, reward_amount) = rt.transaction(|st: &mut State, rt| {
-            let mut info = get_miner_info(rt.store(), st)?;
-
-            // Verify miner hasn't already been faulted
-            if fault.epoch < info.consensus_fault_elapsed {
-                return Err(actor_error!(
-                    forbidden,
-                    "fault epoch {} is too old, last exclusion period ended at {}",
-                    fault.epoch,
-                    info.consensus_fault_elapsed
-                ));
-            }
-
-            st.apply_penalty(&fault_penalty).map_err(|e| {
-                actor_error!(illegal_state, format!("failed to apply penalty: {}", e))
-            })?;
-
-            // Pay penalty
-            let (penalty_from_vesting, penalty_from_balance) = st
-                .repay_partial_debt_in_priority_order(
-                    rt.store(),
-                    rt.curr_epoch(),
-                    &rt.current_balance(),
-                )
-                .map_err(|e| {
-                    e.downcast_default(ExitCode::USR_ILLEGAL_STATE, "failed to pay fees")
-                })?;
-
-            let mut burn_amount = &penalty_from_vesting + &penalty_from_balance;
-            pledge_delta -= penalty_from_vesting;
-
-            // clamp reward at funds burnt
-            let reward_amount = std::cmp::min(&burn_amount, &slasher_reward).clone();
-            burn_amount -= &reward_amount;
-
-            info.consensus_fault_elapsed =
-                rt.curr_epoch() + rt.policy().consensus_fault_ineligibility_duration;
-
-            st.save_info(rt.store(), &info).map_err(|e| {
-                e.downcast_default(ExitCode::USR_SERIALIZATION, "failed to save miner info")
-            })?;
-
-            Ok((burn_amount, reward_amount))
-        })?;
-
-        if let Err(e) =
-            extract_send_result(rt.send_simple(&reporter, METHOD_SEND, None, reward_amount))
-        {
-            error!("failed to send reward: {}", e);
-        }
-
-        burn_funds(rt, burn_amount)?;
-        notify_pledge_changed(rt, &pledge_delta)?;
+        rt: &impl Runtime,
+        params: ReportConsensusFaultParams,
+    ) -> Result<(), ActorError> {
+        // Note: only the first report of any fault is processed because it sets the
+        // ConsensusFaultElapsed state variable to an epoch after the fault, and reports prior to
+        // that epoch are no longer valid
+        rt.validate_immediate_caller_accept_any()?;
+        let reporter = rt.message().caller();
+        penalize_consensus_fault(rt, params, Some(reporter))
+    }
 
+    /// Reports a consensus fault by this miner, such as a double-sign from a misconfigured
+    /// redundant setup, for a reduced penalty and no reporter reward.
+    /// The report must be made by a control address within the policy's self-report window
+    /// after the fault, during which third parties may not yet report it.
+    fn self_report_consensus_fault(
+        rt: &impl Runtime,
+        params: ReportConsensusFaultParams,
+    ) -> Result<(), ActorError> {
         let state: State = rt.state()?;
-        state.check_balance_invariants(&rt.current_balance()).map_err(balance_invariants_broken)?;
-        Ok(())
+        let info = get_miner_info(rt.store(), &state)?;
+        rt.validate_immediate_caller_is(
+            info.control_addresses.iter().chain(&[info.worker, info.owner]),
+        )?;
+        if rt.network_version() < UPGRADE_NETWORK_VERSION {
+            return Err(actor_error!(
+                forbidden,
+                "consensus faults cannot be self-reported before network version {}",
+                UPGRADE_NETWORK_VERSION
+            ));
+        }
+        penalize_consensus_fault(rt, params, None)
     }
 
     fn withdraw_balance(
@@ -3285,6 +3881,7 @@ impl Actor {
             ));
         }
 
+        let balance_before = BalanceSnapshot::load(rt)?;
         let (info, amount_withdrawn, newly_vested, fee_to_burn, state) =
             rt.transaction(|state: &mut State, rt| {
                 let mut info = get_miner_info(rt.store(), state)?;
@@ -3372,6 +3969,7 @@ impl Actor {
         notify_pledge_changed(rt, &newly_vested.neg())?;
 
         state.check_balance_invariants(&rt.current_balance()).map_err(balance_invariants_broken)?;
+        emit_balance_changed(rt, &balance_before)?;
         Ok(WithdrawBalanceReturn { amount_withdrawn })
     }
 
@@ -3524,6 +4122,7 @@ impl Actor {
     }
 
     fn repay_debt(rt: &impl Runtime) -> Result<(), ActorError> {
+        let balance_before = BalanceSnapshot::load(rt)?;
         let (from_vesting, from_balance, state) = rt.transaction(|state: &mut State, rt| {
             let info = get_miner_info(rt.store(), state)?;
             rt.validate_immediate_caller_is(
@@ -3549,6 +4148,7 @@ impl Actor {
         burn_funds(rt, burn_amount)?;
 
         state.check_balance_invariants(&rt.current_balance()).map_err(balance_invariants_broken)?;
+        emit_balance_changed(rt, &balance_before)?;
         Ok(())
     }
 
@@ -3565,25 +4165,44 @@ impl Actor {
             )
         })?;
 
-        match payload.event_type {
+        let processed = match payload.event_type {
             CRON_EVENT_PROVING_DEADLINE => handle_proving_deadline(
                 rt,
                 &params.reward_smoothed,
                 &params.quality_adj_power_smoothed,
             )?,
             CRON_EVENT_PROCESS_EARLY_TERMINATIONS => {
-                if process_early_terminations(
+                let (more, processed) = process_early_terminations(
                     rt,
                     &params.reward_smoothed,
                     &params.quality_adj_power_smoothed,
-                )? {
+                )?;
+                if more {
                     schedule_early_termination_work(rt)?
                 }
+                processed
+            }
+            CRON_EVENT_CONSENSUS_FAULT_ELAPSED => {
+                let state: State = rt.state()?;
+                let info = get_miner_info(rt.store(), &state)?;
+                // A later fault extends the ineligibility, and schedules its own event.
+                if !consensus_fault_active(&info, rt.curr_epoch()) {
+                    request_consensus_fault_exclusion(rt, false)?;
+                }
+                0
             }
             _ => {
                 error!("onDeferredCronEvent invalid event type: {}", payload.event_type);
+                0
             }
         };
+        let budget = rt.policy().miner_cron_entries_budget;
+        if processed > budget {
+            // The report is best-effort, and must not abort cron.
+            if let Err(e) = emit::cron_budget_exceeded(rt, processed, budget) {
+                warn!("failed to report cron budget overrun: {}", e);
+            }
+        }
         let state: State = rt.state()?;
         state.check_balance_invariants(&rt.current_balance()).map_err(balance_invariants_broken)?;
         Ok(())
@@ -3603,6 +4222,20 @@ struct SectorPreCommitInfoInner {
     pub unsealed_cid: CompactCommD,
 }
 
+impl From<SectorPreCommitInfo> for SectorPreCommitInfoInner {
+    fn from(spci: SectorPreCommitInfo) -> Self {
+        SectorPreCommitInfoInner {
+            seal_proof: spci.seal_proof,
+            sector_number: spci.sector_number,
+            sealed_cid: spci.sealed_cid,
+            seal_rand_epoch: spci.seal_rand_epoch,
+            deal_ids: spci.deal_ids,
+            expiration: spci.expiration,
+            unsealed_cid: spci.unsealed_cid,
+        }
+    }
+}
+
 /// ReplicaUpdate param with Option<Cid> for CommD
 /// None means unknown
 #[derive(Debug, Clone)]
@@ -3834,7 +4467,14 @@ fn validate_extended_expiration(
         ));
     }
 
-    validate_expiration(policy, curr_epoch, sector.activation, new_expiration, sector.seal_proof)?;
+    validate_expiration(
+        policy,
+        curr_epoch,
+        sector.activation,
+        sector.proof_validity_start(),
+        new_expiration,
+        sector.seal_proof,
+    )?;
     Ok(())
 }
 
@@ -4326,12 +4966,33 @@ fn update_existing_sector_info(
     new_sector_info
 }
 
+/// Computes the penalty for terminating a sector at some epoch.
+fn termination_penalty_for_sector(
+    sector_size: SectorSize,
+    termination_epoch: ChainEpoch,
+    sector: &SectorOnChainInfo,
+    reward_smoothed: &FilterEstimate,
+    quality_adj_power_smoothed: &FilterEstimate,
+) -> TokenAmount {
+    let sector_power = qa_power_for_sector(sector_size, sector);
+    pledge_penalty_for_termination(
+        &sector.expected_day_reward,
+        termination_epoch - sector.power_base_epoch,
+        &sector.expected_storage_pledge,
+        quality_adj_power_smoothed,
+        &sector_power,
+        reward_smoothed,
+        &sector.replaced_day_reward,
+        sector.power_base_epoch - sector.activation,
+    )
+}
+
 // Note: We're using the current power+epoch reward, rather than at time of termination.
 fn process_early_terminations(
     rt: &impl Runtime,
     reward_smoothed: &FilterEstimate,
     quality_adj_power_smoothed: &FilterEstimate,
-) -> Result</* more */ bool, ActorError> {
+) -> Result<(/* more */ bool, /* processed */ u64), ActorError> {
     let mut terminated_sector_nums = vec![];
     let mut sectors_with_data = vec![];
     let (result, more, penalty, pledge_delta) = rt.transaction(|state: &mut State, rt| {
@@ -4370,17 +5031,13 @@ fn process_early_terminations(
 
             for sector in &sectors {
                 total_initial_pledge += &sector.initial_pledge;
-                let sector_power = qa_power_for_sector(info.sector_size, sector);
                 terminated_sector_nums.push(sector.sector_number);
-                total_penalty += pledge_penalty_for_termination(
-                    &sector.expected_day_reward,
-                    epoch - sector.power_base_epoch,
-                    &sector.expected_storage_pledge,
-                    quality_adj_power_smoothed,
-                    &sector_power,
+                total_penalty += termination_penalty_for_sector(
+                    info.sector_size,
+                    epoch,
+                    sector,
                     reward_smoothed,
-                    &sector.replaced_day_reward,
-                    sector.power_base_epoch - sector.activation,
+                    quality_adj_power_smoothed,
                 );
                 if sector.deal_weight.is_positive() || sector.verified_deal_weight.is_positive() {
                     sectors_with_data.push(sector.sector_number);
@@ -4388,6 +5045,8 @@ fn process_early_terminations(
             }
         }
 
+        state.unindex_sector_pieces(store, terminated_sector_nums.iter().copied())?;
+
         // Apply penalty (add to fee debt)
         state
             .apply_penalty(&total_penalty)
@@ -4419,7 +5078,7 @@ fn process_early_terminations(
     // We didn't do anything, abort.
     if result.is_empty() {
         info!("no early terminations");
-        return Ok(more);
+        return Ok((more, 0));
     }
 
     // Burn penalty.
@@ -4438,21 +5097,24 @@ fn process_early_terminations(
         .context_code(ExitCode::USR_ILLEGAL_STATE, "invalid sector number")?;
     request_terminate_deals(rt, rt.curr_epoch(), &terminated_data)?;
 
+    let processed = terminated_sector_nums.len() as u64;
     for sector in terminated_sector_nums {
         emit::sector_terminated(rt, sector)?;
     }
 
     // reschedule cron worker, if necessary.
-    Ok(more)
+    Ok((more, processed))
 }
 
 /// Invoked at the end of the last epoch for each proving deadline.
+/// Returns the number of sectors expired or terminated.
 fn handle_proving_deadline(
     rt: &impl Runtime,
     reward_smoothed: &FilterEstimate,
     quality_adj_power_smoothed: &FilterEstimate,
-) -> Result<(), ActorError> {
+) -> Result<u64, ActorError> {
     let curr_epoch = rt.curr_epoch();
+    let mut processed: u64 = 0;
 
     let mut had_early_terminations = false;
 
@@ -4483,9 +5145,10 @@ fn handle_proving_deadline(
             pledge_delta_total -= newly_vested;
         }
 
-        // Process pending worker change if any
+        // Process pending worker change and key rotation if any
         let mut info = get_miner_info(rt.store(), state)?;
         process_pending_worker(&mut info, rt, state)?;
+        process_pending_key_rotation(&mut info, rt, state)?;
 
         let deposit_to_burn = state
             .cleanup_expired_pre_commits(policy, rt.store(), rt.curr_epoch())
@@ -4515,14 +5178,24 @@ fn handle_proving_deadline(
 
         // Faults detected by this missed PoSt pay no penalty, but sectors that were already faulty
         // and remain faulty through this deadline pay the fault fee.
-        let penalty_target = pledge_penalty_for_continued_fault(
+        // Power declared as planned downtime for this deadline pays a reduced fee instead.
+        let mut penalty_target = pledge_penalty_for_continued_fault(
             reward_smoothed,
             quality_adj_power_smoothed,
-            &result.previously_faulty_power.qa,
+            &(&result.previously_faulty_power.qa - &result.planned_downtime_power.qa),
         );
+        if !result.planned_downtime_power.is_zero() {
+            penalty_target += pledge_penalty_for_planned_downtime(
+                policy,
+                reward_smoothed,
+                quality_adj_power_smoothed,
+                &result.planned_downtime_power.qa,
+            );
+        }
 
         power_delta_total += &result.power_delta;
         pledge_delta_total += &result.pledge_delta;
+        processed += result.expired_sectors;
 
         state
             .apply_penalty(&penalty_target)
@@ -4579,7 +5252,10 @@ fn handle_proving_deadline(
     // handle them at the next epoch.
     if !had_early_terminations && has_early_terminations {
         // First, try to process some of these terminations.
-        if process_early_terminations(rt, reward_smoothed, quality_adj_power_smoothed)? {
+        let (more, terminated) =
+            process_early_terminations(rt, reward_smoothed, quality_adj_power_smoothed)?;
+        processed += terminated;
+        if more {
             // If that doesn't work, just defer till the next epoch.
             schedule_early_termination_work(rt)?;
         }
@@ -4589,13 +5265,154 @@ fn handle_proving_deadline(
         // processed AddressedSectorsMax terminations this epoch.
     }
 
+    activate_sealed_ahead_sectors(rt)?;
+
+    Ok(processed)
+}
+
+/// Activates the sealed-ahead sectors due by the current epoch, as if they were proven now.
+/// If they can't be activated, e.g. for want of funds for their initial pledge, the sectors are
+/// abandoned and their pre-commit deposits burnt, as for expired pre-commits.
+fn activate_sealed_ahead_sectors(rt: &impl Runtime) -> Result<(), ActorError> {
+    let (precommits, info) = rt.transaction(|state: &mut State, rt| {
+        let precommits = state
+            .pop_sealed_ahead_activations(rt.policy(), rt.store(), rt.curr_epoch())
+            .map_err(|e| {
+                e.downcast_default(
+                    ExitCode::USR_ILLEGAL_STATE,
+                    "failed to pop sealed-ahead activations",
+                )
+            })?;
+        Ok((precommits, get_miner_info(rt.store(), state)?))
+    })?;
+    if precommits.is_empty() {
+        return Ok(());
+    }
+
+    let rew = request_current_epoch_block_reward(rt)?;
+    let pwr = request_current_total_power(rt)?;
+    let pledge_inputs = NetworkPledgeInputs {
+        network_qap: pwr.quality_adj_power_smoothed,
+        network_baseline: rew.this_epoch_baseline_power,
+        circulating_supply: rt.total_fil_circ_supply(),
+        epoch_reward: rew.this_epoch_reward_smoothed,
+        epochs_since_ramp_start: rt.curr_epoch() - pwr.ramp_start_epoch,
+        ramp_duration_epochs: pwr.ramp_duration_epochs,
+    };
+    // Sealed-ahead sectors hold no data.
+    let data_activations = precommits
+        .iter()
+        .map(|_| DataActivationOutput {
+            unverified_space: BigInt::zero(),
+            verified_space: BigInt::zero(),
+            unsealed_cid: None,
+            pieces: vec![],
+        })
+        .collect();
+
+    match activate_new_sector_infos(
+        rt,
+        precommits.iter().collect(),
+        data_activations,
+        &pledge_inputs,
+        &info,
+    ) {
+        Ok(()) => {
+            for precommit in &precommits {
+                emit::sector_activated(rt, precommit.info.sector_number, None, &[])?;
+            }
+        }
+        Err(e) => {
+            log::warn!(
+                "storage provider {} abandoning sealed-ahead sectors: {}",
+                rt.message().receiver(),
+                e
+            );
+            let sector_numbers: Vec<SectorNumber> =
+                precommits.iter().map(|precommit| precommit.info.sector_number).collect();
+            let deposit_to_burn: TokenAmount =
+                precommits.iter().map(|precommit| &precommit.pre_commit_deposit).sum();
+            rt.transaction(|state: &mut State, rt| {
+                state.delete_precommitted_sectors(rt.store(), &sector_numbers)?;
+                state.add_pre_commit_deposit(&(-deposit_to_burn.clone())).map_err(|e| {
+                    actor_error!(illegal_state, "failed to add precommit deposit: {}", e)
+                })
+            })?;
+            burn_funds(rt, deposit_to_burn)?;
+        }
+    }
     Ok(())
 }
 
+/// Checks the preconditions of a single pre-commit that don't depend on state or other actors.
+fn validate_precommit(
+    policy: &Policy,
+    curr_epoch: ChainEpoch,
+    precommit: &SectorPreCommitInfoInner,
+) -> Result<(), ActorError> {
+    // Whether the network allows the proof type is checked once the allowlist is known.
+    let Some(max_prove_commit_duration) = max_prove_commit_duration(policy, precommit.seal_proof)
+    else {
+        return Err(actor_error!(
+            illegal_argument,
+            "unsupported seal proof type {}",
+            i64::from(precommit.seal_proof)
+        ));
+    };
+    if precommit.sector_number > MAX_SECTOR_NUMBER {
+        return Err(actor_error!(
+            illegal_argument,
+            "sector number {} out of range 0..(2^63-1)",
+            precommit.sector_number
+        ));
+    }
+    // Skip checking if CID is defined because it cannot be so in Rust
+
+    if !is_sealed_sector(&precommit.sealed_cid) {
+        return Err(actor_error!(illegal_argument, "sealed CID had wrong prefix"));
+    }
+    if precommit.seal_rand_epoch >= curr_epoch {
+        return Err(actor_error!(
+            illegal_argument,
+            "seal challenge epoch {} must be before now {}",
+            precommit.seal_rand_epoch,
+            curr_epoch
+        ));
+    }
+    let challenge_earliest = curr_epoch - policy.max_pre_commit_randomness_lookback;
+    if precommit.seal_rand_epoch < challenge_earliest {
+        return Err(actor_error!(
+            illegal_argument,
+            "seal challenge epoch {} too old, must be after {}",
+            precommit.seal_rand_epoch,
+            challenge_earliest
+        ));
+    }
+
+    if let Some(commd) = &precommit.unsealed_cid.0 {
+        if !is_unsealed_sector(commd) {
+            return Err(actor_error!(illegal_argument, "unsealed CID had wrong prefix"));
+        }
+    }
+
+    // Require sector lifetime meets minimum by assuming activation happens at last epoch permitted for seal proof.
+    // This could make sector maximum lifetime validation more lenient if the maximum sector limit isn't hit first.
+    let max_activation = curr_epoch + max_prove_commit_duration;
+    validate_expiration(
+        policy,
+        curr_epoch,
+        max_activation,
+        max_activation,
+        precommit.expiration,
+        precommit.seal_proof,
+    )
+}
+
 fn validate_expiration(
     policy: &Policy,
     curr_epoch: ChainEpoch,
     activation: ChainEpoch,
+    proof_validity_start: ChainEpoch,
     expiration: ChainEpoch,
     seal_proof: RegisteredSealProof,
 ) -> Result<(), ActorError> {
@@ -4632,19 +5449,20 @@ fn validate_expiration(
         ));
     }
 
-    // total sector lifetime cannot exceed SectorMaximumLifetime for the sector's seal proof
+    // total sector lifetime cannot exceed SectorMaximumLifetime for the sector's seal proof,
+    // measured from the most recent proof of the sector's replica
     let max_lifetime = seal_proof_sector_maximum_lifetime(seal_proof).ok_or_else(|| {
         actor_error!(illegal_argument, "unrecognized seal proof type {:?}", seal_proof)
     })?;
-    if expiration - activation > max_lifetime {
+    if expiration - proof_validity_start > max_lifetime {
         return Err(actor_error!(
-        illegal_argument,
-        "invalid expiration {}, total sector lifetime ({}) cannot exceed {} after activation {}",
-        expiration,
-        expiration - activation,
-        max_lifetime,
-        activation
-    ));
+            illegal_argument,
+            "invalid expiration {}, total sector lifetime ({}) cannot exceed {} after proof at {}",
+            expiration,
+            expiration - proof_validity_start,
+            max_lifetime,
+            proof_validity_start
+        ));
     }
 
     Ok(())
@@ -4668,70 +5486,350 @@ fn enroll_cron_event(
     Ok(())
 }
 
-fn request_update_power(rt: &impl Runtime, delta: PowerPair) -> Result<(), ActorError> {
-    if delta.is_zero() {
-        return Ok(());
+fn request_consensus_fault_exclusion(rt: &impl Runtime, excluded: bool) -> Result<(), ActorError> {
+    extract_send_result(rt.send_simple(
+        &STORAGE_POWER_ACTOR_ADDR,
+        ext::power::SET_CONSENSUS_FAULT_EXCLUSION_METHOD,
+        IpldBlock::serialize_cbor(&ext::power::SetConsensusFaultExclusionParams { excluded })?,
+        TokenAmount::zero(),
+    ))?;
+    Ok(())
+}
+
+fn request_update_power(rt: &impl Runtime, delta: PowerPair) -> Result<(), ActorError> {
+    if delta.is_zero() {
+        return Ok(());
+    }
+
+    let delta_clone = delta.clone();
+
+    extract_send_result(rt.send_simple(
+        &STORAGE_POWER_ACTOR_ADDR,
+        ext::power::UPDATE_CLAIMED_POWER_METHOD,
+        IpldBlock::serialize_cbor(&ext::power::UpdateClaimedPowerParams {
+            raw_byte_delta: delta.raw,
+            quality_adjusted_delta: delta.qa,
+        })?,
+        TokenAmount::zero(),
+    ))
+    .map_err(|e| e.wrap(format!("failed to update power with {:?}", delta_clone)))?;
+
+    Ok(())
+}
+
+// Records the pieces of activated or updated sectors in the piece index.
+// Sectors are listed with all their pieces, replacing any recorded for an updated sector.
+fn index_sector_pieces(
+    rt: &impl Runtime,
+    sectors: Vec<(SectorNumber, Vec<Cid>)>,
+) -> Result<(), ActorError> {
+    if sectors.is_empty() {
+        return Ok(());
+    }
+    rt.transaction(|state: &mut State, rt| {
+        state.index_sector_pieces(rt.policy(), rt.store(), &sectors)
+    })
+}
+
+// Records the pieces of newly activated sectors holding data in the piece index.
+fn index_activated_pieces<'a>(
+    rt: &impl Runtime,
+    activations: impl Iterator<Item = (SectorNumber, &'a DataActivationOutput)>,
+) -> Result<(), ActorError> {
+    let sectors = activations
+        .filter(|(_, data)| !data.pieces.is_empty())
+        .map(|(sector_number, data)| {
+            (sector_number, data.pieces.iter().map(|(cid, _)| *cid).collect())
+        })
+        .collect();
+    index_sector_pieces(rt, sectors)
+}
+
+fn request_terminate_deals(
+    rt: &impl Runtime,
+    epoch: ChainEpoch,
+    sectors: &BitField,
+) -> Result<(), ActorError> {
+    if !sectors.is_empty() {
+        // The sectors bitfield could be large, but will fit into a single parameters block.
+        // The FVM max block size of 1MiB supports 130K 8-byte integers, but the policy parameter
+        // ADDRESSED_SECTORS_MAX (currently 25k) will avoid reaching that.
+        let res = extract_send_result(rt.send_simple(
+            &STORAGE_MARKET_ACTOR_ADDR,
+            ext::market::ON_MINER_SECTORS_TERMINATE_METHOD,
+            IpldBlock::serialize_cbor(&ext::market::OnMinerSectorsTerminateParams {
+                epoch,
+                sectors: sectors.clone(),
+            })?,
+            TokenAmount::zero(),
+        ));
+        // If running in a system / cron context intentionally swallow this error to prevent
+        // frozen market cron corruption from also freezing this miner cron.
+        if rt.message().origin() == SYSTEM_ACTOR_ADDR {
+            if let Err(e) = res {
+                error!("OnSectorsTerminate event failed from cron caller {}", e)
+            }
+        } else {
+            res?;
+        }
+    }
+    Ok(())
+}
+
+fn schedule_early_termination_work(rt: &impl Runtime) -> Result<(), ActorError> {
+    info!("scheduling early terminations with cron...");
+    enroll_cron_event(
+        rt,
+        rt.curr_epoch() + 1,
+        CronEventPayload { event_type: CRON_EVENT_PROCESS_EARLY_TERMINATIONS },
+    )
+}
+
+fn have_pending_early_terminations(state: &State) -> bool {
+    let no_early_terminations = state.early_terminations.is_empty();
+    !no_early_terminations
+}
+
+/// Validates the parameters of a Window PoSt submission that don't depend on miner state.
+fn validate_windowed_post_params(
+    policy: &Policy,
+    network_version: NetworkVersion,
+    params: &SubmitWindowedPoStParams,
+) -> Result<(), ActorError> {
+    if params.proofs.len() != 1 {
+        return Err(actor_error!(
+            illegal_argument,
+            "expected exactly one proof, got {}",
+            params.proofs.len()
+        ));
+    }
+
+    // From the upgrade network version, the proof type need only match the miner's own,
+    // which the network allowed when the miner was created.
+    if network_version < UPGRADE_NETWORK_VERSION
+        && check_valid_post_proof_type(policy, params.proofs[0].post_proof).is_err()
+    {
+        return Err(actor_error!(
+            illegal_argument,
+            "proof type {:?} not allowed",
+            params.proofs[0].post_proof
+        ));
+    }
+
+    if params.deadline >= policy.wpost_period_deadlines {
+        return Err(actor_error!(
+            illegal_argument,
+            "invalid deadline {} of {}",
+            params.deadline,
+            policy.wpost_period_deadlines
+        ));
+    }
+
+    if params.chain_commit_rand.0.len() > RANDOMNESS_LENGTH {
+        return Err(actor_error!(
+            illegal_argument,
+            "expected at most {} bytes of randomness, got {}",
+            RANDOMNESS_LENGTH,
+            params.chain_commit_rand.0.len()
+        ));
+    }
+
+    Ok(())
+}
+
+/// Records a Window PoSt submission for the current deadline, verifying the proof immediately
+/// if it recovers power and otherwise storing it for optimistic verification.
+/// The caller must already have been validated as one of the miner's proving addresses.
+fn record_windowed_post(
+    rt: &impl Runtime,
+    state: &mut State,
+    info: &MinerInfo,
+    mut params: SubmitWindowedPoStParams,
+) -> Result<PoStResult, ActorError> {
+    let current_epoch = rt.curr_epoch();
+    let max_proof_size = info.window_post_proof_type.proof_size().map_err(|e| {
+        actor_error!(illegal_state, "failed to determine max window post proof size: {}", e)
+    })?;
+
+    // Make sure the miner is using the correct proof type.
+    if params.proofs[0].post_proof != info.window_post_proof_type {
+        return Err(actor_error!(
+            illegal_argument,
+            "expected proof of type {:?}, got {:?}",
+            info.window_post_proof_type,
+            params.proofs[0].post_proof
+        ));
+    }
+
+    // Make sure the proof size doesn't exceed the max. We could probably check for an exact match, but this is safer.
+    let max_size = max_proof_size * params.partitions.len();
+    if params.proofs[0].proof_bytes.len() > max_size {
+        return Err(actor_error!(
+            illegal_argument,
+            "expected proof to be smaller than {} bytes",
+            max_size
+        ));
+    }
+
+    // Validate that the miner didn't try to prove too many partitions at once.
+    let submission_partition_limit = cmp::min(
+        load_partitions_sectors_max(rt.policy(), info.window_post_partition_sectors),
+        rt.policy().posted_partitions_max,
+    );
+
+    if params.partitions.len() as u64 > submission_partition_limit {
+        return Err(actor_error!(
+            illegal_argument,
+            "too many partitions {}, limit {}",
+            params.partitions.len(),
+            submission_partition_limit
+        ));
+    }
+    let current_deadline = state.deadline_info(rt.policy(), current_epoch);
+
+    // Check that the miner state indicates that the current proving deadline has started.
+    // This should only fail if the cron actor wasn't invoked, and matters only in case that it hasn't been
+    // invoked for a whole proving period, and hence the missed PoSt submissions from the prior occurrence
+    // of this deadline haven't been processed yet.
+    if !current_deadline.is_open() {
+        return Err(actor_error!(
+            illegal_state,
+            "proving period {} not yet open at {}",
+            current_deadline.period_start,
+            current_epoch
+        ));
+    }
+
+    // The miner may only submit a proof for the current deadline.
+    if params.deadline != current_deadline.index {
+        return Err(actor_error!(
+            illegal_argument,
+            "invalid deadline {} at epoch {}, expected {}",
+            params.deadline,
+            current_epoch,
+            current_deadline.index
+        ));
+    }
+
+    // Verify that the PoSt was committed to the chain at most
+    // WPoStChallengeLookback+WPoStChallengeWindow in the past.
+    if params.chain_commit_epoch < current_deadline.challenge {
+        return Err(actor_error!(
+            illegal_argument,
+            "expected chain commit epoch {} to be after {}",
+            params.chain_commit_epoch,
+            current_deadline.challenge
+        ));
     }
 
-    let delta_clone = delta.clone();
+    if params.chain_commit_epoch >= current_epoch {
+        return Err(actor_error!(
+            illegal_argument,
+            "chain commit epoch {} must be less than the current epoch {}",
+            params.chain_commit_epoch,
+            current_epoch
+        ));
+    }
 
-    extract_send_result(rt.send_simple(
-        &STORAGE_POWER_ACTOR_ADDR,
-        ext::power::UPDATE_CLAIMED_POWER_METHOD,
-        IpldBlock::serialize_cbor(&ext::power::UpdateClaimedPowerParams {
-            raw_byte_delta: delta.raw,
-            quality_adjusted_delta: delta.qa,
-        })?,
-        TokenAmount::zero(),
-    ))
-    .map_err(|e| e.wrap(format!("failed to update power with {:?}", delta_clone)))?;
+    // Verify the chain commit randomness
+    let comm_rand = rt.get_randomness_from_tickets(
+        DomainSeparationTag::PoStChainCommit,
+        params.chain_commit_epoch,
+        &[],
+    )?;
+    if Randomness(comm_rand.into()) != params.chain_commit_rand {
+        return Err(actor_error!(illegal_argument, "post commit randomness mismatched"));
+    }
 
-    Ok(())
-}
+    let sectors = Sectors::load(rt.store(), &state.sectors)
+        .map_err(|e| e.downcast_default(ExitCode::USR_ILLEGAL_STATE, "failed to load sectors"))?;
+
+    let mut deadlines =
+        state.load_deadlines(rt.store()).map_err(|e| e.wrap("failed to load deadlines"))?;
+
+    let mut deadline = deadlines.load_deadline(rt.store(), params.deadline)?;
+
+    // Record proven sectors/partitions, returning updates to power and the final set of sectors
+    // proven/skipped.
+    //
+    // NOTE: This function does not actually check the proofs but does assume that they're correct. Instead,
+    // it snapshots the deadline's state and the submitted proofs at the end of the challenge window and
+    // allows third-parties to dispute these proofs.
+    //
+    // While we could perform _all_ operations at the end of challenge window, we do as we can here to avoid
+    // overloading cron.
+    let policy = rt.policy();
+    let fault_expiration = current_deadline.last() + policy.fault_max_age;
+    let post_result = deadline
+        .record_proven_sectors(
+            rt.store(),
+            &sectors,
+            info.sector_size,
+            current_deadline.quant_spec(),
+            fault_expiration,
+            &mut params.partitions,
+        )
+        .map_err(|e| {
+            e.downcast_default(
+                ExitCode::USR_ILLEGAL_STATE,
+                format!("failed to process post submission for deadline {}", params.deadline),
+            )
+        })?;
 
-fn request_terminate_deals(
-    rt: &impl Runtime,
-    epoch: ChainEpoch,
-    sectors: &BitField,
-) -> Result<(), ActorError> {
-    if !sectors.is_empty() {
-        // The sectors bitfield could be large, but will fit into a single parameters block.
-        // The FVM max block size of 1MiB supports 130K 8-byte integers, but the policy parameter
-        // ADDRESSED_SECTORS_MAX (currently 25k) will avoid reaching that.
-        let res = extract_send_result(rt.send_simple(
-            &STORAGE_MARKET_ACTOR_ADDR,
-            ext::market::ON_MINER_SECTORS_TERMINATE_METHOD,
-            IpldBlock::serialize_cbor(&ext::market::OnMinerSectorsTerminateParams {
-                epoch,
-                sectors: sectors.clone(),
-            })?,
-            TokenAmount::zero(),
+    // Make sure we actually proved something.
+    let proven_sectors = &post_result.sectors - &post_result.ignored_sectors;
+    if proven_sectors.is_empty() {
+        // Abort verification if all sectors are (now) faults. There's nothing to prove.
+        // It's not rational for a miner to submit a Window PoSt marking *all* non-faulty sectors as skipped,
+        // since that will just cause them to pay a penalty at deadline end that would otherwise be zero
+        // if they had *not* declared them.
+        return Err(actor_error!(
+            illegal_argument,
+            "cannot prove partitions with no active sectors"
         ));
-        // If running in a system / cron context intentionally swallow this error to prevent
-        // frozen market cron corruption from also freezing this miner cron.
-        if rt.message().origin() == SYSTEM_ACTOR_ADDR {
-            if let Err(e) = res {
-                error!("OnSectorsTerminate event failed from cron caller {}", e)
-            }
-        } else {
-            res?;
+    }
+    // If we're not recovering power, record the proof for optimistic verification.
+    if post_result.recovered_power.is_zero() {
+        deadline.record_post_proofs(rt.store(), &post_result.partitions, &params.proofs).map_err(
+            |e| {
+                e.downcast_default(
+                    ExitCode::USR_ILLEGAL_STATE,
+                    "failed to record proof for optimistic verification",
+                )
+            },
+        )?
+    } else {
+        // Load sector infos for proof, substituting a known-good sector for known-faulty sectors.
+        // Note: this is slightly sub-optimal, loading info for the recovering sectors again after they were already
+        // loaded above.
+        let sector_infos = sectors
+            .load_for_proof(&post_result.sectors, &post_result.ignored_sectors)
+            .map_err(|e| {
+                e.downcast_default(
+                    ExitCode::USR_ILLEGAL_STATE,
+                    "failed to load sectors for post verification",
+                )
+            })?;
+        if !verify_windowed_post(rt, current_deadline.challenge, &sector_infos, params.proofs)
+            .map_err(|e| e.wrap("window post failed"))?
+        {
+            return Err(actor_error!(illegal_argument, "invalid post was submitted"));
         }
     }
-    Ok(())
-}
 
-fn schedule_early_termination_work(rt: &impl Runtime) -> Result<(), ActorError> {
-    info!("scheduling early terminations with cron...");
-    enroll_cron_event(
-        rt,
-        rt.curr_epoch() + 1,
-        CronEventPayload { event_type: CRON_EVENT_PROCESS_EARLY_TERMINATIONS },
-    )
-}
+    let deadline_idx = params.deadline;
+    deadlines.update_deadline(policy, rt.store(), params.deadline, &deadline).map_err(|e| {
+        e.downcast_default(
+            ExitCode::USR_ILLEGAL_STATE,
+            format!("failed to update deadline {}", deadline_idx),
+        )
+    })?;
 
-fn have_pending_early_terminations(state: &State) -> bool {
-    let no_early_terminations = state.early_terminations.is_empty();
-    !no_early_terminations
+    state
+        .save_deadlines(rt.store(), deadlines)
+        .map_err(|e| e.downcast_default(ExitCode::USR_ILLEGAL_STATE, "failed to save deadlines"))?;
+
+    Ok(post_result)
 }
 
 // returns true if valid, false if invalid, error if failed to validate either way!
@@ -4971,6 +6069,7 @@ fn validate_ni_sectors(
             rt.policy(),
             curr_epoch,
             activation_epoch,
+            activation_epoch,
             sector.expiration,
             seal_proof_type,
         ) {
@@ -5171,6 +6270,220 @@ fn verify_deals(
     ))?)
 }
 
+// Verifies the client-signed piece manifests of sectors being activated with the market actor,
+// returning the numbers of the sectors that have manifests.
+// A sector's manifests must authorize exactly its pieces, in order, and the market's CommD
+// for them must match the sector's declared CommD.
+// The market rejects the whole batch if any manifest is invalid.
+fn verify_client_manifests(
+    rt: &impl Runtime,
+    activations: &[(&SectorActivationManifest, &SectorPreCommitOnChainInfo)],
+) -> Result<BTreeSet<SectorNumber>, ActorError> {
+    let mut sectors = vec![];
+    let mut declared = vec![];
+    for (activation, precommit) in activations {
+        if activation.client_manifests.is_empty() {
+            continue;
+        }
+        let manifest_pieces =
+            activation.client_manifests.iter().flat_map(|signed| &signed.manifest.pieces);
+        if !manifest_pieces
+            .map(|piece| (piece.cid, piece.size))
+            .eq(activation.pieces.iter().map(|piece| (piece.cid, piece.size)))
+        {
+            return Err(actor_error!(
+                illegal_argument,
+                "client manifests do not match pieces for sector {}",
+                activation.sector_number
+            ));
+        }
+        sectors.push(ext::market::SectorPieceManifests {
+            sector_number: activation.sector_number,
+            sector_type: precommit.info.seal_proof,
+            manifests: activation.client_manifests.clone(),
+        });
+        declared.push(&precommit.info);
+    }
+    if sectors.is_empty() {
+        return Ok(BTreeSet::new());
+    }
+
+    let ret: ext::market::VerifyDealsForActivationReturn =
+        deserialize_block(extract_send_result(rt.send_simple(
+            &STORAGE_MARKET_ACTOR_ADDR,
+            ext::market::VERIFY_DEALS_FOR_ACTIVATION_DIRECT_METHOD,
+            IpldBlock::serialize_cbor(&ext::market::VerifyDealsForActivationDirectParams {
+                sectors,
+            })?,
+            TokenAmount::zero(),
+        ))?)?;
+    if ret.unsealed_cids.len() != declared.len() {
+        return Err(actor_error!(
+            illegal_state,
+            "market returned {} unsealed CIDs for {} sectors",
+            ret.unsealed_cids.len(),
+            declared.len()
+        ));
+    }
+    for (computed, info) in ret.unsealed_cids.into_iter().zip(&declared) {
+        let computed = CompactCommD::new(computed).get_cid(info.seal_proof)?;
+        if info.unsealed_cid.get_cid(info.seal_proof)? != computed {
+            return Err(actor_error!(
+                illegal_argument,
+                "unsealed CID does not match client manifests for sector {}, computed {:?} declared {:?}",
+                info.sector_number,
+                computed,
+                info.unsealed_cid
+            ));
+        }
+    }
+    Ok(declared.iter().map(|info| info.sector_number).collect())
+}
+
+// Verifies a consensus fault by this miner and applies the penalty for it.
+// A fault reported by a third party rewards the reporter. A fault reported by the miner itself
+// carries a reduced penalty, if reported within the self-report window.
+fn penalize_consensus_fault(
+    rt: &impl Runtime,
+    params: ReportConsensusFaultParams,
+    reporter: Option<Address>,
+) -> Result<(), ActorError> {
+    let fault = rt
+        .verify_consensus_fault(&params.header1, &params.header2, &params.header_extra)
+        .map_err(|e| e.downcast_default(ExitCode::USR_ILLEGAL_ARGUMENT, "fault not verified"))?
+        .ok_or_else(|| actor_error!(illegal_argument, "No consensus fault found"))?;
+    if fault.target != rt.message().receiver() {
+        return Err(actor_error!(
+            illegal_argument,
+            "fault by {} reported to miner {}",
+            fault.target,
+            rt.message().receiver()
+        ));
+    }
+
+    // Elapsed since the fault (i.e. since the higher of the two blocks)
+    let fault_age = rt.curr_epoch() - fault.epoch;
+    if fault_age <= 0 {
+        return Err(actor_error!(
+            illegal_argument,
+            "invalid fault epoch {} ahead of current {}",
+            fault.epoch,
+            rt.curr_epoch()
+        ));
+    }
+    // From the upgrade network version, the miner alone may report a fault during the
+    // self-report window, so a self-report cannot pre-empt evidence already submitted by others.
+    let self_report_window = rt.policy().consensus_fault_self_report_window;
+    if reporter.is_none() && fault_age > self_report_window {
+        return Err(actor_error!(
+            forbidden,
+            "fault epoch {} is too old to self-report at {}",
+            fault.epoch,
+            rt.curr_epoch()
+        ));
+    }
+    if reporter.is_some()
+        && rt.network_version() >= UPGRADE_NETWORK_VERSION
+        && fault_age <= self_report_window
+    {
+        return Err(actor_error!(
+            forbidden,
+            "fault epoch {} may only be self-reported until {}",
+            fault.epoch,
+            fault.epoch + self_report_window
+        ));
+    }
+
+    // Reward reporter with a share of the miner's current balance.
+    let reward_stats = request_current_epoch_block_reward(rt)?;
+
+    // The policy amounts we should burn and send to reporter
+    // These may differ from actual funds send when miner goes into fee debt
+    let this_epoch_reward =
+        TokenAmount::from_atto(reward_stats.this_epoch_reward_smoothed.estimate());
+    let (fault_penalty, slasher_reward) = match reporter {
+        Some(_) => (
+            consensus_fault_penalty(this_epoch_reward.clone()),
+            reward_for_consensus_slash_report(&this_epoch_reward),
+        ),
+        None => (consensus_fault_self_report_penalty(this_epoch_reward), TokenAmount::zero()),
+    };
+
+    let mut pledge_delta = TokenAmount::zero();
+
+    let (burn_amount, reward_amount, consensus_fault_elapsed) =
+        rt.transaction(|st: &mut State, rt| {
+            let mut info = get_miner_info(rt.store(), st)?;
+
+            // Verify miner hasn't already been faulted
+            if fault.epoch < info.consensus_fault_elapsed {
+                return Err(actor_error!(
+                    forbidden,
+                    "fault epoch {} is too old, last exclusion period ended at {}",
+                    fault.epoch,
+                    info.consensus_fault_elapsed
+                ));
+            }
+
+            st.apply_penalty(&fault_penalty).map_err(|e| {
+                actor_error!(illegal_state, format!("failed to apply penalty: {}", e))
+            })?;
+
+            // Pay penalty
+            let (penalty_from_vesting, penalty_from_balance) = st
+                .repay_partial_debt_in_priority_order(
+                    rt.store(),
+                    rt.curr_epoch(),
+                    &rt.current_balance(),
+                )
+                .map_err(|e| {
+                    e.downcast_default(ExitCode::USR_ILLEGAL_STATE, "failed to pay fees")
+                })?;
+
+            let mut burn_amount = &penalty_from_vesting + &penalty_from_balance;
+            pledge_delta -= penalty_from_vesting;
+
+            // clamp reward at funds burnt
+            let reward_amount = std::cmp::min(&burn_amount, &slasher_reward).clone();
+            burn_amount -= &reward_amount;
+
+            info.consensus_fault_elapsed =
+                rt.curr_epoch() + rt.policy().consensus_fault_ineligibility_duration;
+
+            st.save_info(rt.store(), &info).map_err(|e| {
+                e.downcast_default(ExitCode::USR_SERIALIZATION, "failed to save miner info")
+            })?;
+
+            Ok((burn_amount, reward_amount, info.consensus_fault_elapsed))
+        })?;
+
+    if let Some(reporter) = reporter {
+        if let Err(e) =
+            extract_send_result(rt.send_simple(&reporter, METHOD_SEND, None, reward_amount))
+        {
+            error!("failed to send reward: {}", e);
+        }
+    }
+
+    burn_funds(rt, burn_amount)?;
+    notify_pledge_changed(rt, &pledge_delta)?;
+
+    // The miner's power is excluded from the network totals while it is ineligible.
+    if rt.network_version() >= UPGRADE_NETWORK_VERSION {
+        request_consensus_fault_exclusion(rt, true)?;
+        enroll_cron_event(
+            rt,
+            consensus_fault_elapsed + 1,
+            CronEventPayload { event_type: CRON_EVENT_CONSENSUS_FAULT_ELAPSED },
+        )?;
+    }
+    emit::consensus_fault(rt, fault.epoch, reporter.is_none(), &fault_penalty)?;
+
+    let state: State = rt.state()?;
+    state.check_balance_invariants(&rt.current_balance()).map_err(balance_invariants_broken)?;
+    Ok(())
+}
+
 /// Requests the current epoch target block reward from the reward actor.
 /// return value includes reward, smoothed estimate of reward, and baseline power
 fn request_current_epoch_block_reward(
@@ -5202,8 +6515,67 @@ fn request_current_total_power(
     )
 }
 
+/// Returns the proof types the network allows, as reported with its total power,
+/// if the allowlist is enforced at the current network version.
+fn network_proof_type_allowlist<'a>(
+    rt: &impl Runtime,
+    power: &'a ext::power::CurrentTotalPowerReturn,
+) -> Option<&'a ext::power::ProofTypeAllowlist> {
+    if rt.network_version() < UPGRADE_NETWORK_VERSION {
+        return None;
+    }
+    power.proof_type_allowlist.as_ref()
+}
+
+/// Requests the proof types the network allows, as reported with its total power,
+/// if the allowlist is enforced at the current network version.
+fn request_proof_type_allowlist(
+    rt: &impl Runtime,
+) -> Result<Option<ext::power::ProofTypeAllowlist>, ActorError> {
+    if rt.network_version() < UPGRADE_NETWORK_VERSION {
+        return Ok(None);
+    }
+    Ok(request_current_total_power(rt)?.proof_type_allowlist)
+}
+
+/// Checks that a seal proof type may be used for new sectors.
+/// The network's allowlist, if set, decides in place of the proof types supported by policy.
+fn validate_seal_proof_allowed(
+    allowlist: Option<&ext::power::ProofTypeAllowlist>,
+    seal_proof: RegisteredSealProof,
+    supported_by_policy: bool,
+) -> Result<(), ActorError> {
+    match allowlist {
+        Some(allowlist) if !allowlist.seal_proof_types.contains(&seal_proof) => Err(actor_error!(
+            illegal_argument,
+            "seal proof type {} not allowed by the network",
+            i64::from(seal_proof)
+        )),
+        None if !supported_by_policy => Err(actor_error!(
+            illegal_argument,
+            "unsupported seal proof type {}",
+            i64::from(seal_proof)
+        )),
+        _ => Ok(()),
+    }
+}
+
 /// Resolves an address to an ID address and verifies that it is address of an account actor with an associated BLS key.
 /// The worker must be BLS since the worker key will be used alongside a BLS-VRF.
+fn resolve_control_addresses(
+    rt: &impl Runtime,
+    raw: Vec<Address>,
+) -> Result<Vec<Address>, ActorError> {
+    raw.into_iter()
+        .map(|address| {
+            rt.resolve_address(&address).ok_or_else(|| {
+                actor_error!(illegal_argument, "unable to resolve control address: {}", address)
+            })
+        })
+        .map(|id_result| id_result.map(Address::new_id))
+        .collect()
+}
+
 fn resolve_worker_address(rt: &impl Runtime, raw: Address) -> Result<ActorID, ActorError> {
     let resolved = rt
         .resolve_address(&raw)
@@ -5259,6 +6631,49 @@ fn notify_pledge_changed(rt: &impl Runtime, pledge_delta: &TokenAmount) -> Resul
     Ok(())
 }
 
+/// The balance components reported by balance-changed events.
+#[derive(PartialEq)]
+struct BalanceSnapshot {
+    available_balance: TokenAmount,
+    locked_funds: TokenAmount,
+    pre_commit_deposits: TokenAmount,
+    fee_debt: TokenAmount,
+}
+
+impl BalanceSnapshot {
+    fn load(rt: &impl Runtime) -> Result<Self, ActorError> {
+        let st: State = rt.state()?;
+        // Computed directly rather than with get_available_balance, which fails
+        // when the unlocked balance is negative.
+        let available_balance = rt.current_balance()
+            - &st.locked_funds
+            - &st.pre_commit_deposits
+            - &st.initial_pledge
+            - &st.fee_debt;
+        Ok(Self {
+            available_balance,
+            locked_funds: st.locked_funds,
+            pre_commit_deposits: st.pre_commit_deposits,
+            fee_debt: st.fee_debt,
+        })
+    }
+}
+
+/// Emits a balance-changed event if any balance component differs from the snapshot.
+fn emit_balance_changed(rt: &impl Runtime, before: &BalanceSnapshot) -> Result<(), ActorError> {
+    let after = BalanceSnapshot::load(rt)?;
+    if after == *before {
+        return Ok(());
+    }
+    emit::balance_changed(
+        rt,
+        &(&after.available_balance - &before.available_balance),
+        &(&after.locked_funds - &before.locked_funds),
+        &(&after.pre_commit_deposits - &before.pre_commit_deposits),
+        &(&after.fee_debt - &before.fee_debt),
+    )
+}
+
 fn get_claims(
     rt: &impl Runtime,
     ids: &[ext::verifreg::ClaimID],
@@ -5423,6 +6838,31 @@ fn process_pending_worker(
         .map_err(|e| e.downcast_default(ExitCode::USR_ILLEGAL_STATE, "failed to save miner info"))
 }
 
+/// Applies a pending key rotation if its effective epoch has arrived.
+/// The rotation supersedes any pending worker key change.
+fn process_pending_key_rotation(
+    info: &mut MinerInfo,
+    rt: &impl Runtime,
+    state: &mut State,
+) -> Result<(), ActorError> {
+    let rotation = match info.pending_key_rotation.take() {
+        Some(rotation) if rt.curr_epoch() >= rotation.effective_at => rotation,
+        pending => {
+            info.pending_key_rotation = pending;
+            return Ok(());
+        }
+    };
+
+    info.worker = rotation.new_worker;
+    info.control_addresses = rotation.new_control_addresses;
+    info.pending_worker_key = None;
+
+    state.save_info(rt.store(), info).map_err(|e| {
+        e.downcast_default(ExitCode::USR_ILLEGAL_STATE, "failed to save miner info")
+    })?;
+    emit::key_rotation_applied(rt, &info.worker)
+}
+
 /// Repays all fee debt and then verifies that the miner has amount needed to cover
 /// the pledge requirement after burning all fee debt.  If not aborts.
 /// Returns an amount that must be burnt by the actor.
@@ -5583,6 +7023,7 @@ fn activate_new_sector_infos(
                 replaced_day_reward: TokenAmount::zero(),
                 sector_key_cid: None,
                 flags: SectorOnChainInfoFlags::SIMPLE_QA_POWER,
+                proof_refreshed: None,
             };
 
             new_sector_numbers.push(new_sector_info.sector_number);
@@ -5993,12 +7434,23 @@ impl ActorCode for Actor {
         "StorageMiner"
     }
 
+    fn on_upgrade<RT>(rt: &RT, params: OnUpgradeParams) -> Result<(), ActorError>
+    where
+        RT: Runtime,
+        RT::Blockstore: Clone,
+    {
+        migrate_state(rt, &params.old_state, |rt, legacy: LegacyState| legacy.migrate(rt.store()))
+    }
+
     actor_dispatch! {
         Constructor => constructor,
         ControlAddresses => control_addresses,
         ChangeWorkerAddress|ChangeWorkerAddressExported => change_worker_address,
         ChangePeerID|ChangePeerIDExported => change_peer_id,
         SubmitWindowedPoSt => submit_windowed_post,
+        SubmitWindowedPoSt2 => submit_windowed_post2,
+        ProveCommitSealedAhead => prove_commit_sealed_ahead,
+        GetPledgeObligation => get_pledge_obligation,
         ExtendSectorExpiration => extend_sector_expiration,
         TerminateSectors => terminate_sectors,
         DeclareFaults => declare_faults,
@@ -6019,6 +7471,8 @@ impl ActorCode for Actor {
         ProveCommitAggregate => prove_commit_aggregate,
         ProveReplicaUpdates => prove_replica_updates,
         PreCommitSectorBatch2 => pre_commit_sector_batch2,
+        PreCommitSectorBatch3 => pre_commit_sector_batch3,
+        ExtendSectorProofExpiration => extend_sector_proof_expiration,
         ChangeBeneficiary|ChangeBeneficiaryExported => change_beneficiary,
         GetBeneficiary|GetBeneficiaryExported => get_beneficiary,
         ExtendSectorExpiration2 => extend_sector_expiration2,
@@ -6029,6 +7483,17 @@ impl ActorCode for Actor {
         GetVestingFundsExported => get_vesting_funds,
         GetPeerIDExported => get_peer_id,
         GetMultiaddrsExported => get_multiaddresses,
+        TerminationPenaltyEstimateExported => termination_penalty_estimate,
+        ProposeKeyRotationExported => propose_key_rotation,
+        VetoKeyRotationExported => veto_key_rotation,
+        GetDeadlinePoStHistoryExported => get_deadline_post_history,
+        GetPieceSectorsExported => get_piece_sectors,
+        SamplePartitionSectorsExported => sample_partition_sectors,
+        GetSectorStatusExported => get_sector_status,
+        DeclarePlannedDowntimeExported => declare_planned_downtime,
+        ChangeProvingKeyExported => change_proving_key,
+        SelfReportConsensusFaultExported => self_report_consensus_fault,
+        ComputeUnsealedSectorCIDExported => compute_unsealed_sector_cid,
         ProveCommitSectors3 => prove_commit_sectors3,
         ProveReplicaUpdates3 => prove_replica_updates3,
         ProveCommitSectorsNI => prove_commit_sectors_ni,
@@ -0,0 +1,187 @@
+// Copyright 2019-2022 ChainSafe Systems
+// SPDX-License-Identifier: Apache-2.0, MIT
+
+use cid::Cid;
+use fvm_ipld_bitfield::BitField;
+use fvm_ipld_blockstore::Blockstore;
+use fvm_ipld_encoding::tuple::*;
+use fvm_ipld_encoding::{strict_bytes, BytesDe, CborStore};
+use fvm_shared::address::Address;
+use fvm_shared::clock::ChainEpoch;
+use fvm_shared::econ::TokenAmount;
+use fvm_shared::error::ExitCode;
+use fvm_shared::sector::{RegisteredPoStProof, SectorSize};
+use multihash_codetable::Code;
+
+use fil_actors_runtime::{actor_error, ActorError, Array, AsActorError};
+
+use crate::state::PRECOMMIT_EXPIRY_AMT_BITWIDTH;
+use crate::{
+    BeneficiaryTerm, Deadline, Deadlines, MinerInfo, PendingBeneficiaryChange, PieceIndexMap,
+    PowerPair, SectorPiecesMap, State, WorkerKeyChange, PIECE_INDEX_CONFIG,
+};
+
+/// Miner state as stored before the piece index and sealed-ahead sectors were introduced.
+#[derive(Serialize_tuple, Deserialize_tuple, Clone, Debug)]
+pub struct LegacyState {
+    pub info: Cid,
+    pub pre_commit_deposits: TokenAmount,
+    pub locked_funds: TokenAmount,
+    pub vesting_funds: Cid,
+    pub fee_debt: TokenAmount,
+    pub initial_pledge: TokenAmount,
+    pub pre_committed_sectors: Cid,
+    pub pre_committed_sectors_cleanup: Cid,
+    pub allocated_sectors: Cid,
+    pub sectors: Cid,
+    pub proving_period_start: ChainEpoch,
+    pub current_deadline: u64,
+    pub deadlines: Cid,
+    pub early_terminations: BitField,
+    pub deadline_cron_active: bool,
+}
+
+/// Miner info as stored before key rotations and proving keys were introduced.
+#[derive(Serialize_tuple, Deserialize_tuple, Debug)]
+pub struct LegacyMinerInfo {
+    pub owner: Address,
+    pub worker: Address,
+    pub control_addresses: Vec<Address>,
+    pub pending_worker_key: Option<WorkerKeyChange>,
+    #[serde(with = "strict_bytes")]
+    pub peer_id: Vec<u8>,
+    pub multi_address: Vec<BytesDe>,
+    pub window_post_proof_type: RegisteredPoStProof,
+    pub sector_size: SectorSize,
+    pub window_post_partition_sectors: u64,
+    pub consensus_fault_elapsed: ChainEpoch,
+    pub pending_owner_address: Option<Address>,
+    pub beneficiary: Address,
+    pub beneficiary_term: BeneficiaryTerm,
+    pub pending_beneficiary_term: Option<PendingBeneficiaryChange>,
+}
+
+impl From<LegacyMinerInfo> for MinerInfo {
+    /// Migrates to the current schema, with no pending key rotation or proving key.
+    fn from(legacy: LegacyMinerInfo) -> Self {
+        MinerInfo {
+            owner: legacy.owner,
+            worker: legacy.worker,
+            control_addresses: legacy.control_addresses,
+            pending_worker_key: legacy.pending_worker_key,
+            peer_id: legacy.peer_id,
+            multi_address: legacy.multi_address,
+            window_post_proof_type: legacy.window_post_proof_type,
+            sector_size: legacy.sector_size,
+            window_post_partition_sectors: legacy.window_post_partition_sectors,
+            consensus_fault_elapsed: legacy.consensus_fault_elapsed,
+            pending_owner_address: legacy.pending_owner_address,
+            beneficiary: legacy.beneficiary,
+            beneficiary_term: legacy.beneficiary_term,
+            pending_beneficiary_term: legacy.pending_beneficiary_term,
+            pending_key_rotation: None,
+            proving_key: None,
+        }
+    }
+}
+
+/// Deadline as stored before PoSt history and planned downtime were introduced.
+#[derive(Serialize_tuple, Deserialize_tuple, Debug)]
+pub struct LegacyDeadline {
+    pub partitions: Cid,
+    pub expirations_epochs: Cid,
+    pub partitions_posted: BitField,
+    pub early_terminations: BitField,
+    pub live_sectors: u64,
+    pub total_sectors: u64,
+    pub faulty_power: PowerPair,
+    pub optimistic_post_submissions: Cid,
+    pub sectors_snapshot: Cid,
+    pub partitions_snapshot: Cid,
+    pub optimistic_post_submissions_snapshot: Cid,
+}
+
+impl From<LegacyDeadline> for Deadline {
+    /// Migrates to the current schema, with no PoSt history and no planned downtime.
+    fn from(legacy: LegacyDeadline) -> Self {
+        Deadline {
+            partitions: legacy.partitions,
+            expirations_epochs: legacy.expirations_epochs,
+            partitions_posted: legacy.partitions_posted,
+            early_terminations: legacy.early_terminations,
+            live_sectors: legacy.live_sectors,
+            total_sectors: legacy.total_sectors,
+            faulty_power: legacy.faulty_power,
+            optimistic_post_submissions: legacy.optimistic_post_submissions,
+            sectors_snapshot: legacy.sectors_snapshot,
+            partitions_snapshot: legacy.partitions_snapshot,
+            optimistic_post_submissions_snapshot: legacy.optimistic_post_submissions_snapshot,
+            post_history: 0,
+            planned_downtime_power: PowerPair::zero(),
+        }
+    }
+}
+
+/// Rewrites each deadline in the current schema, returning the new deadlines root.
+fn migrate_deadlines<BS: Blockstore>(store: &BS, deadlines: &Cid) -> Result<Cid, ActorError> {
+    let mut deadlines: Deadlines = store
+        .get_cbor(deadlines)
+        .context_code(ExitCode::USR_ILLEGAL_STATE, "failed to load deadlines to migrate")?
+        .ok_or_else(|| actor_error!(illegal_state, "deadlines {} not found", deadlines))?;
+    for due in deadlines.due.iter_mut() {
+        let deadline: LegacyDeadline = store
+            .get_cbor(due)
+            .context_code(ExitCode::USR_ILLEGAL_STATE, "failed to load deadline to migrate")?
+            .ok_or_else(|| actor_error!(illegal_state, "deadline {} not found", due))?;
+        *due = store
+            .put_cbor(&Deadline::from(deadline), Code::Blake2b256)
+            .context_code(ExitCode::USR_ILLEGAL_STATE, "failed to write migrated deadline")?;
+    }
+    store
+        .put_cbor(&deadlines, Code::Blake2b256)
+        .context_code(ExitCode::USR_ILLEGAL_STATE, "failed to write migrated deadlines")
+}
+
+impl LegacyState {
+    /// Migrates to the current state schema, with an empty piece index and no sealed-ahead
+    /// sectors.
+    /// The miner info and deadlines are rewritten in the current schema too.
+    pub fn migrate<BS: Blockstore>(self, store: &BS) -> Result<State, ActorError> {
+        let info: LegacyMinerInfo = store
+            .get_cbor(&self.info)
+            .context_code(ExitCode::USR_ILLEGAL_STATE, "failed to load miner info to migrate")?
+            .ok_or_else(|| actor_error!(illegal_state, "miner info {} not found", self.info))?;
+        let info = store
+            .put_cbor(&MinerInfo::from(info), Code::Blake2b256)
+            .context_code(ExitCode::USR_ILLEGAL_STATE, "failed to write migrated miner info")?;
+        let deadlines = migrate_deadlines(store, &self.deadlines)?;
+        let sealed_ahead_activations =
+            Array::<BitField, BS>::new_with_bit_width(store, PRECOMMIT_EXPIRY_AMT_BITWIDTH)
+                .flush()
+                .context_code(
+                    ExitCode::USR_ILLEGAL_STATE,
+                    "failed to construct sealed-ahead activation queue",
+                )?;
+        Ok(State {
+            info,
+            pre_commit_deposits: self.pre_commit_deposits,
+            locked_funds: self.locked_funds,
+            vesting_funds: self.vesting_funds,
+            fee_debt: self.fee_debt,
+            initial_pledge: self.initial_pledge,
+            pre_committed_sectors: self.pre_committed_sectors,
+            pre_committed_sectors_cleanup: self.pre_committed_sectors_cleanup,
+            allocated_sectors: self.allocated_sectors,
+            sectors: self.sectors,
+            proving_period_start: self.proving_period_start,
+            current_deadline: self.current_deadline,
+            deadlines,
+            early_terminations: self.early_terminations,
+            deadline_cron_active: self.deadline_cron_active,
+            piece_index: PieceIndexMap::flush_empty(store, PIECE_INDEX_CONFIG)?,
+            sector_pieces: SectorPiecesMap::flush_empty(store, PIECE_INDEX_CONFIG)?,
+            sealed_ahead_sectors: BitField::new(),
+            sealed_ahead_activations,
+        })
+    }
+}
@@ -6,6 +6,7 @@ use std::cmp::{self, max};
 use fil_actors_runtime::network::EPOCHS_IN_DAY;
 use fil_actors_runtime::reward::math::PRECISION;
 use fil_actors_runtime::reward::{smooth, FilterEstimate};
+use fil_actors_runtime::runtime::Policy;
 use fil_actors_runtime::EXPECTED_LEADERS_PER_EPOCH;
 use fvm_shared::bigint::{BigInt, Integer};
 use fvm_shared::clock::ChainEpoch;
@@ -74,6 +75,8 @@ pub const TERMINATION_LIFETIME_CAP: ChainEpoch = 140;
 
 // Multiplier of whole per-winner rewards for a consensus fault penalty.
 const CONSENSUS_FAULT_FACTOR: u64 = 5;
+// Multiplier of whole per-winner rewards for a consensus fault reported by the miner itself.
+const CONSENSUS_FAULT_SELF_REPORT_FACTOR: u64 = 2;
 
 const GAMMA_FIXED_POINT_FACTOR: u64 = 1000; // 3 decimal places
 
@@ -158,6 +161,22 @@ pub fn pledge_penalty_for_continued_fault(
     )
 }
 
+/// The penalty for a sector declared faulty as planned downtime, charged in place of the
+/// continued fault fee at the end of the deadline for which the downtime was declared.
+pub fn pledge_penalty_for_planned_downtime(
+    policy: &Policy,
+    reward_estimate: &FilterEstimate,
+    network_qa_power_estimate: &FilterEstimate,
+    qa_sector_power: &StoragePower,
+) -> TokenAmount {
+    (pledge_penalty_for_continued_fault(
+        reward_estimate,
+        network_qa_power_estimate,
+        qa_sector_power,
+    ) * policy.planned_downtime_fault_fee_num)
+        .div_floor(policy.planned_downtime_fault_fee_denom)
+}
+
 /// This is the SP(t) penalty for a newly faulty sector that has not been declared.
 /// SP(t) = UndeclaredFaultFactor * BR(t)
 pub fn pledge_penalty_for_termination_lower_bound(
@@ -317,6 +336,11 @@ pub fn consensus_fault_penalty(this_epoch_reward: TokenAmount) -> TokenAmount {
     (this_epoch_reward * CONSENSUS_FAULT_FACTOR).div_floor(EXPECTED_LEADERS_PER_EPOCH)
 }
 
+/// The penalty for a consensus fault reported by the miner itself, promptly after the fault.
+pub fn consensus_fault_self_report_penalty(this_epoch_reward: TokenAmount) -> TokenAmount {
+    (this_epoch_reward * CONSENSUS_FAULT_SELF_REPORT_FACTOR).div_floor(EXPECTED_LEADERS_PER_EPOCH)
+}
+
 /// Returns the amount of a reward to vest, and the vesting schedule, for a reward amount.
 pub fn locked_reward_from_reward(reward: TokenAmount) -> (TokenAmount, &'static VestSpec) {
     let lock_amount = (reward * LOCKED_REWARD_FACTOR_NUM).div_floor(LOCKED_REWARD_FACTOR_DENOM);
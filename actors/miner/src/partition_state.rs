@@ -53,6 +53,9 @@ pub struct Partition {
     /// Termination fees have not yet been calculated or paid and associated deals have not yet been
     /// canceled but effective power has already been adjusted.
     /// Not quantized.
+    /// This is a BitFieldQueue rather than an EpochQueue: run-length encoded bitfields store
+    /// the sectors terminated at an epoch far more compactly than a set entry per sector, and
+    /// changing the layout would require migrating every partition.
     pub early_terminated: Cid, // AMT[ChainEpoch]BitField
 
     /// Power of not-yet-terminated sectors (incl faulty & unproven).
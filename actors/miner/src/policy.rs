@@ -23,6 +23,13 @@ pub const SECTOR_QUALITY_PRECISION: i64 = 20;
 /// Base number of sectors before imposing the additional aggregate fee in ProveCommitSectorsNI
 pub const NI_AGGREGATE_FEE_BASE_SECTOR_COUNT: usize = 5;
 
+/// Number of proving periods for which each deadline retains its PoSt outcomes.
+/// Outcomes are packed two bits per period into a single word.
+pub const POST_HISTORY_LENGTH: u64 = 32;
+
+/// Maximum number of sectors sampled from a partition by one SamplePartitionSectors call.
+pub const MAX_SAMPLED_SECTORS: u64 = 1000;
+
 lazy_static! {
     /// Quality multiplier for committed capacity (no deals) in a sector
     pub static ref QUALITY_BASE_MULTIPLIER: BigInt = BigInt::from(10);
@@ -56,11 +63,38 @@ pub fn can_prove_commit_ni_seal_proof(policy: &Policy, proof: RegisteredSealProo
     policy.valid_prove_commit_ni_proof_type.contains(proof)
 }
 
+/// Checks whether a seal proof type is a non-interactive PoRep proof type.
+pub fn is_ni_seal_proof(proof: RegisteredSealProof) -> bool {
+    use RegisteredSealProof::*;
+    matches!(
+        proof,
+        StackedDRG32GiBV1P2_Feat_NiPoRep
+            | StackedDRG2KiBV1P2_Feat_NiPoRep
+            | StackedDRG8MiBV1P2_Feat_NiPoRep
+            | StackedDRG512MiBV1P2_Feat_NiPoRep
+            | StackedDRG64GiBV1P2_Feat_NiPoRep
+    )
+}
+
 /// Checks whether a seal proof type is supported for new miners and sectors.
 pub fn can_extend_seal_proof_type(_proof: RegisteredSealProof) -> bool {
     true
 }
 
+/// Checks whether sectors sealed with a seal proof type have a proof validity period short enough
+/// to be renewed with ExtendSectorProofExpiration.
+pub fn can_extend_sector_proof_expiration(proof: RegisteredSealProof) -> bool {
+    use RegisteredSealProof::*;
+    matches!(
+        proof,
+        StackedDRG32GiBV1
+            | StackedDRG2KiBV1
+            | StackedDRG8MiBV1
+            | StackedDRG512MiBV1
+            | StackedDRG64GiBV1
+    )
+}
+
 /// Maximum duration to allow for the sealing process for seal algorithms.
 /// Dependent on algorithm and sector size
 pub fn max_prove_commit_duration(
@@ -0,0 +1,44 @@
+use fil_actors_runtime::runtime::Runtime;
+use fvm_shared::address::Address;
+use fvm_shared::crypto::hash::SupportedHashes;
+use fvm_shared::randomness::RANDOMNESS_LENGTH;
+use fvm_shared::ActorID;
+
+/// Derives the indices of `count` challenged sectors among `sector_count` sectors, in the same
+/// way as the proofs derive PoSt sector challenges.
+///
+/// The randomness is first reduced to a valid field element by clearing the top two bits of its
+/// last byte. The i-th index is then the first eight bytes of
+/// `SHA-256(prover_id || randomness || i)` read as a little-endian integer, modulo the number of
+/// sectors, where the prover ID is the payload of the miner's ID address zero-padded to 32 bytes
+/// and `i` is encoded as eight little-endian bytes.
+/// Indices are sampled with replacement. No indices are derived from an empty set of sectors.
+pub fn sector_challenge_indices(
+    rt: &impl Runtime,
+    miner: ActorID,
+    randomness: &[u8; RANDOMNESS_LENGTH],
+    count: u64,
+    sector_count: u64,
+) -> Vec<u64> {
+    if sector_count == 0 {
+        return vec![];
+    }
+    let mut prover_id = [0u8; 32];
+    let payload = Address::new_id(miner).payload_bytes();
+    prover_id[..payload.len()].copy_from_slice(&payload);
+    let mut randomness = *randomness;
+    randomness[31] &= 0x3f;
+
+    (0..count)
+        .map(|i| {
+            let mut preimage = Vec::with_capacity(prover_id.len() + randomness.len() + 8);
+            preimage.extend_from_slice(&prover_id);
+            preimage.extend_from_slice(&randomness);
+            preimage.extend_from_slice(&i.to_le_bytes());
+            let digest = rt.hash(SupportedHashes::Sha2_256, &preimage);
+            let mut challenge = [0u8; 8];
+            challenge.copy_from_slice(&digest[..8]);
+            u64::from_le_bytes(challenge) % sector_count
+        })
+        .collect()
+}
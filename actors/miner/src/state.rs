@@ -25,8 +25,8 @@ use num_traits::Zero;
 use fil_actors_runtime::runtime::policy_constants::MAX_SECTOR_NUMBER;
 use fil_actors_runtime::runtime::Policy;
 use fil_actors_runtime::{
-    actor_error, ActorContext, ActorDowncast, ActorError, Array, AsActorError, Config, Map2,
-    DEFAULT_HAMT_CONFIG,
+    actor_error, ActorContext, ActorDowncast, ActorError, ActorState, Array, AsActorError, Config,
+    Map2, DEFAULT_HAMT_CONFIG,
 };
 
 use super::beneficiary::*;
@@ -36,13 +36,19 @@ use super::types::*;
 use super::{
     assign_deadlines, deadline_is_mutable, new_deadline_info_from_offset_and_epoch,
     quant_spec_for_deadline, BitFieldQueue, Deadline, DeadlineInfo, DeadlineSectorMap, Deadlines,
-    PowerPair, QuantSpec, Sectors, TerminationResult, VestingFunds,
+    PoStOutcome, PowerPair, QuantSpec, Sectors, TerminationResult, VestingFunds,
 };
 
 pub type PreCommitMap<BS> = Map2<BS, SectorNumber, SectorPreCommitOnChainInfo>;
 pub const PRECOMMIT_CONFIG: Config = Config { bit_width: HAMT_BIT_WIDTH, ..DEFAULT_HAMT_CONFIG };
 
-const PRECOMMIT_EXPIRY_AMT_BITWIDTH: u32 = 6;
+/// Maps a piece CID to the sectors holding the piece.
+pub type PieceIndexMap<BS> = Map2<BS, Cid, Vec<SectorNumber>>;
+/// Maps a sector number to the pieces recorded for the sector in the piece index.
+pub type SectorPiecesMap<BS> = Map2<BS, SectorNumber, Vec<Cid>>;
+pub const PIECE_INDEX_CONFIG: Config = Config { bit_width: HAMT_BIT_WIDTH, ..DEFAULT_HAMT_CONFIG };
+
+pub(crate) const PRECOMMIT_EXPIRY_AMT_BITWIDTH: u32 = 6;
 pub const SECTORS_AMT_BITWIDTH: u32 = 5;
 
 /// Balance of Miner Actor should be greater than or equal to
@@ -52,7 +58,7 @@ pub const SECTORS_AMT_BITWIDTH: u32 = 5;
 /// that limits a miner actor's behavior (i.e. no balance withdrawals)
 /// Excess balance as computed by st.GetAvailableBalance will be
 /// withdrawable or usable for pre-commit deposit or pledge lock-up.
-#[derive(Serialize_tuple, Deserialize_tuple, Clone, Debug)]
+#[derive(Serialize_tuple, Deserialize_tuple, Clone, Debug, ActorState)]
 pub struct State {
     /// Contains static info about this miner
     pub info: Cid,
@@ -112,6 +118,20 @@ pub struct State {
 
     // True when miner cron is active, false otherwise
     pub deadline_cron_active: bool,
+
+    /// Index of the sectors holding each piece activated in a sector, bounded by policy.
+    /// Sectors activated before the index was introduced are not recorded, since their
+    /// pieces are not stored on chain.
+    pub piece_index: Cid, // PieceIndexMap
+    /// The pieces recorded in the piece index for each sector, used to remove them
+    /// when the sector expires or is terminated.
+    pub sector_pieces: Cid, // SectorPiecesMap
+
+    /// Sectors proven ahead of their activation ("sealed ahead"). Their pre-commits are retained,
+    /// and not cleaned up, until the sectors are activated.
+    pub sealed_ahead_sectors: BitField,
+    /// The sealed-ahead sectors to activate at each epoch, quantized to deadline ends.
+    pub sealed_ahead_activations: Cid, // BitFieldQueue (AMT[Epoch]*BitField)
 }
 
 #[derive(PartialEq, Eq)]
@@ -131,6 +151,8 @@ impl State {
     ) -> Result<Self, ActorError> {
         let empty_precommit_map =
             PreCommitMap::empty(store, PRECOMMIT_CONFIG, "precommits").flush()?;
+        let empty_piece_index = PieceIndexMap::flush_empty(store, PIECE_INDEX_CONFIG)?;
+        let empty_sector_pieces = SectorPiecesMap::flush_empty(store, PIECE_INDEX_CONFIG)?;
 
         let empty_precommits_cleanup_array =
             Array::<BitField, BS>::new_with_bit_width(store, PRECOMMIT_EXPIRY_AMT_BITWIDTH)
@@ -189,6 +211,10 @@ impl State {
             early_terminations: BitField::new(),
             deadline_cron_active: false,
             pre_committed_sectors_cleanup: empty_precommits_cleanup_array,
+            piece_index: empty_piece_index,
+            sector_pieces: empty_sector_pieces,
+            sealed_ahead_sectors: BitField::new(),
+            sealed_ahead_activations: empty_precommits_cleanup_array,
         })
     }
 
@@ -352,6 +378,7 @@ impl State {
             if prev_entry.is_none() {
                 return Err(actor_error!(illegal_state, "sector {} not pre-committed", sector_num));
             }
+            self.sealed_ahead_sectors.unset(sector_num);
         }
 
         self.pre_committed_sectors = precommitted.flush()?;
@@ -1068,6 +1095,58 @@ impl State {
         Ok(())
     }
 
+    /// Records pre-committed sectors as proven ahead of their activation at an epoch.
+    pub fn add_sealed_ahead_sectors<BS: Blockstore>(
+        &mut self,
+        policy: &Policy,
+        store: &BS,
+        activation_epoch: ChainEpoch,
+        sector_numbers: &[SectorNumber],
+    ) -> anyhow::Result<()> {
+        let quant = self.quant_spec_every_deadline(policy);
+        let mut queue = BitFieldQueue::new(store, &self.sealed_ahead_activations, quant)
+            .map_err(|e| e.downcast_wrap("failed to load sealed-ahead activation queue"))?;
+        queue.add_to_queue_values(activation_epoch, sector_numbers.iter().copied())?;
+        self.sealed_ahead_activations = queue.amt.flush()?;
+
+        for &sector_number in sector_numbers {
+            self.sealed_ahead_sectors.set(sector_number);
+        }
+        Ok(())
+    }
+
+    /// Removes the sealed-ahead sectors due for activation by the current epoch from the
+    /// activation queue, returning their pre-commits.
+    pub fn pop_sealed_ahead_activations<BS: Blockstore>(
+        &mut self,
+        policy: &Policy,
+        store: &BS,
+        current_epoch: ChainEpoch,
+    ) -> anyhow::Result<Vec<SectorPreCommitOnChainInfo>> {
+        let mut queue = BitFieldQueue::new(
+            store,
+            &self.sealed_ahead_activations,
+            self.quant_spec_every_deadline(policy),
+        )?;
+        let (sectors, modified) = queue.pop_until(current_epoch)?;
+        if modified {
+            self.sealed_ahead_activations = queue.amt.flush()?;
+        }
+
+        let precommitted =
+            PreCommitMap::load(store, &self.pre_committed_sectors, PRECOMMIT_CONFIG, "precommits")?;
+        let mut due = Vec::new();
+        for sector_number in sectors.iter() {
+            if !self.sealed_ahead_sectors.get(sector_number) {
+                continue;
+            }
+            if let Some(precommit) = precommitted.get(&sector_number)? {
+                due.push(precommit.clone());
+            }
+        }
+        Ok(due)
+    }
+
     pub fn cleanup_expired_pre_commits<BS: Blockstore>(
         &mut self,
         policy: &Policy,
@@ -1095,6 +1174,10 @@ impl State {
 
         for i in sectors.iter() {
             let sector_number = i as SectorNumber;
+            // sealed-ahead sectors are proven, and kept until activated
+            if self.sealed_ahead_sectors.get(sector_number) {
+                continue;
+            }
             let sector: SectorPreCommitOnChainInfo =
                 match precommitted.get(&sector_number)?.cloned() {
                     Some(sector) => sector,
@@ -1142,6 +1225,8 @@ impl State {
                 previously_faulty_power: PowerPair::zero(),
                 detected_faulty_power: PowerPair::zero(),
                 total_faulty_power: PowerPair::zero(),
+                planned_downtime_power: PowerPair::zero(),
+                expired_sectors: 0,
             });
         }
 
@@ -1156,13 +1241,33 @@ impl State {
 
         let previously_faulty_power = deadline.faulty_power.clone();
 
+        // Planned downtime applies only to the challenge window that is ending, and never exceeds
+        // the power still faulty (some may have been recovered or terminated since declaration).
+        let planned = std::mem::take(&mut deadline.planned_downtime_power);
+        let had_planned_downtime = !planned.is_zero();
+        let planned_downtime_power = PowerPair {
+            raw: planned.raw.min(previously_faulty_power.raw.clone()),
+            qa: planned.qa.min(previously_faulty_power.qa.clone()),
+        };
+
         if !deadline.is_live() {
+            // Keep the PoSt history aligned with proving periods while the deadline is idle.
+            let has_history = deadline.post_history != 0;
+            if has_history {
+                deadline.record_post_outcome(PoStOutcome::Empty);
+            }
+            if has_history || had_planned_downtime {
+                deadlines.update_deadline(policy, store, dl_info.index, &deadline)?;
+                self.save_deadlines(store, deadlines)?;
+            }
             return Ok(AdvanceDeadlineResult {
                 pledge_delta,
                 power_delta: PowerPair::zero(),
                 previously_faulty_power,
                 detected_faulty_power: PowerPair::zero(),
                 total_faulty_power: deadline.faulty_power,
+                planned_downtime_power,
+                expired_sectors: 0,
             });
         }
 
@@ -1191,6 +1296,9 @@ impl State {
         // Faulty power has already been lost, so the amount expiring can be excluded from the delta.
         power_delta -= &expired.active_power;
 
+        // Sectors expiring early are removed from the piece index when their termination is processed.
+        self.unindex_sector_pieces(store, expired.on_time_sectors.iter())?;
+
         let no_early_terminations = expired.early_sectors.is_empty();
         if !no_early_terminations {
             self.early_terminations.set(dl_info.index);
@@ -1206,9 +1314,86 @@ impl State {
             previously_faulty_power,
             detected_faulty_power,
             total_faulty_power,
+            planned_downtime_power,
+            expired_sectors: expired.on_time_sectors.len(),
         })
     }
 
+    /// Records the pieces held by each sector in the piece index, replacing any pieces
+    /// previously recorded for the sector.
+    /// A piece already recorded in the policy's maximum number of sectors is not recorded again.
+    pub fn index_sector_pieces<BS: Blockstore>(
+        &mut self,
+        policy: &Policy,
+        store: &BS,
+        sectors: &[(SectorNumber, Vec<Cid>)],
+    ) -> Result<(), ActorError> {
+        if policy.piece_index_max_sectors == 0 {
+            return Ok(());
+        }
+        let mut index =
+            PieceIndexMap::load(store, &self.piece_index, PIECE_INDEX_CONFIG, "piece index")?;
+        let mut sector_pieces =
+            SectorPiecesMap::load(store, &self.sector_pieces, PIECE_INDEX_CONFIG, "sector pieces")?;
+        for (sector_number, pieces) in sectors {
+            unindex_sector(&mut index, &mut sector_pieces, *sector_number)?;
+            let mut indexed: Vec<Cid> = vec![];
+            for piece in pieces {
+                if indexed.contains(piece) {
+                    continue;
+                }
+                let mut piece_sectors = index.get(piece)?.cloned().unwrap_or_default();
+                if piece_sectors.len() as u64 >= policy.piece_index_max_sectors {
+                    continue;
+                }
+                piece_sectors.push(*sector_number);
+                index.set(piece, piece_sectors)?;
+                indexed.push(*piece);
+            }
+            if !indexed.is_empty() {
+                sector_pieces.set(sector_number, indexed)?;
+            }
+        }
+        self.piece_index = index.flush()?;
+        self.sector_pieces = sector_pieces.flush()?;
+        Ok(())
+    }
+
+    /// Removes any pieces recorded for the sectors from the piece index.
+    pub fn unindex_sector_pieces<BS: Blockstore>(
+        &mut self,
+        store: &BS,
+        sector_numbers: impl IntoIterator<Item = SectorNumber>,
+    ) -> Result<(), ActorError> {
+        let mut sector_pieces =
+            SectorPiecesMap::load(store, &self.sector_pieces, PIECE_INDEX_CONFIG, "sector pieces")?;
+        if sector_pieces.is_empty() {
+            return Ok(());
+        }
+        let mut index =
+            PieceIndexMap::load(store, &self.piece_index, PIECE_INDEX_CONFIG, "piece index")?;
+        let mut changed = false;
+        for sector_number in sector_numbers {
+            changed |= unindex_sector(&mut index, &mut sector_pieces, sector_number)?;
+        }
+        if changed {
+            self.piece_index = index.flush()?;
+            self.sector_pieces = sector_pieces.flush()?;
+        }
+        Ok(())
+    }
+
+    /// Returns the sectors recorded in the piece index as holding a piece, in activation order.
+    pub fn sectors_for_piece<BS: Blockstore>(
+        &self,
+        store: &BS,
+        piece: &Cid,
+    ) -> Result<Vec<SectorNumber>, ActorError> {
+        let index =
+            PieceIndexMap::load(store, &self.piece_index, PIECE_INDEX_CONFIG, "piece index")?;
+        Ok(index.get(piece)?.cloned().unwrap_or_default())
+    }
+
     // Loads sectors precommit information from store, requiring it to exist.
     pub fn get_precommitted_sectors<BS: Blockstore>(
         &self,
@@ -1233,6 +1418,30 @@ impl State {
     }
 }
 
+// Removes a sector's pieces from the piece index, returning whether any were recorded.
+fn unindex_sector<BS: Blockstore>(
+    index: &mut PieceIndexMap<BS>,
+    sector_pieces: &mut SectorPiecesMap<BS>,
+    sector_number: SectorNumber,
+) -> Result<bool, ActorError> {
+    let pieces = match sector_pieces.delete(&sector_number)? {
+        Some(pieces) => pieces,
+        None => return Ok(false),
+    };
+    for piece in pieces {
+        let mut piece_sectors = index.get(&piece)?.cloned().unwrap_or_default();
+        piece_sectors.retain(|s| *s != sector_number);
+        if piece_sectors.is_empty() {
+            index.delete(&piece)?;
+        } else {
+            index.set(&piece, piece_sectors)?;
+        }
+    }
+    Ok(true)
+}
+
+/// Outcome of advancing the proving deadline, returned to deadline cron.
+/// It is not part of the miner's state and is never stored.
 pub struct AdvanceDeadlineResult {
     pub pledge_delta: TokenAmount,
     pub power_delta: PowerPair,
@@ -1244,6 +1453,10 @@ pub struct AdvanceDeadlineResult {
     /// Note that failed recovery power is included in both PreviouslyFaultyPower and
     /// DetectedFaultyPower, so TotalFaultyPower is not simply their sum.
     pub total_faulty_power: PowerPair,
+    /// Portion of PreviouslyFaultyPower that was declared as planned downtime for this deadline
+    pub planned_downtime_power: PowerPair,
+    /// Number of sectors that expired on time
+    pub expired_sectors: u64,
 }
 
 /// Static information about miner
@@ -1301,9 +1514,26 @@ pub struct MinerInfo {
 
     /// A proposal new beneficiary message for this miner
     pub pending_beneficiary_term: Option<PendingBeneficiaryChange>,
+
+    /// Optional time-locked rotation of the worker and control addresses,
+    /// applied by deadline cron once effective unless vetoed by the owner first.
+    pub pending_key_rotation: Option<KeyRotation>,
+
+    /// Optional proofs-only key, permitted to submit Window PoSts and declare recoveries
+    /// but not to call any other method. Must be an ID address.
+    pub proving_key: Option<Address>,
 }
 
 impl MinerInfo {
+    /// Addresses permitted to submit Window PoSts and declare recoveries:
+    /// the control addresses, worker, owner and proving key.
+    pub fn proving_addresses(&self) -> impl Iterator<Item = &Address> {
+        self.control_addresses
+            .iter()
+            .chain([&self.worker, &self.owner])
+            .chain(self.proving_key.as_ref())
+    }
+
     pub fn new(
         owner: ActorID,
         worker: ActorID,
@@ -1336,6 +1566,8 @@ impl MinerInfo {
             window_post_partition_sectors,
             consensus_fault_elapsed: EPOCH_UNDEFINED,
             pending_owner_address: None,
+            pending_key_rotation: None,
+            proving_key: None,
         })
     }
 }
@@ -4,7 +4,7 @@ use crate::{
     NO_QUANTIZATION, PRECOMMIT_CONFIG,
 };
 use fil_actors_runtime::runtime::Policy;
-use fil_actors_runtime::{DealWeight, MessageAccumulator};
+use fil_actors_runtime::{ActorState, DealWeight, MessageAccumulator};
 use fvm_ipld_bitfield::BitField;
 use fvm_ipld_blockstore::Blockstore;
 use fvm_ipld_encoding::CborStore;
@@ -12,7 +12,7 @@ use fvm_shared::address::Protocol;
 use fvm_shared::clock::ChainEpoch;
 use fvm_shared::econ::TokenAmount;
 use fvm_shared::sector::{RegisteredPoStProof, SectorNumber, SectorSize};
-use num_traits::Zero;
+use num_traits::{Signed, Zero};
 use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
 
 pub fn check_state_invariants<BS: Blockstore>(
@@ -22,10 +22,14 @@ pub fn check_state_invariants<BS: Blockstore>(
     balance: &TokenAmount,
 ) -> (StateSummary, MessageAccumulator) {
     let acc = MessageAccumulator::default();
+    state.check_fields(store, &acc);
     let sector_size;
 
-    let mut miner_summary =
-        StateSummary { deadline_cron_active: state.deadline_cron_active, ..Default::default() };
+    let mut miner_summary = StateSummary {
+        deadline_cron_active: state.deadline_cron_active,
+        pledge_obligation: &state.initial_pledge + &state.locked_funds,
+        ..Default::default()
+    };
 
     // load data from linked structures
     match state.get_info(store) {
@@ -158,6 +162,8 @@ pub struct StateSummary {
     pub faulty_power: PowerPair,
     pub window_post_proof_type: RegisteredPoStProof,
     pub deadline_cron_active: bool,
+    // initial pledge and locked funds, as accounted in the power actor's total pledge collateral
+    pub pledge_obligation: TokenAmount,
     // sectors with non zero (verified) deal weight that may carry deals
     pub live_data_sectors: BTreeMap<SectorNumber, DataSummary>,
 }
@@ -170,6 +176,7 @@ impl Default for StateSummary {
             faulty_power: PowerPair::zero(),
             window_post_proof_type: RegisteredPoStProof::Invalid(0),
             deadline_cron_active: false,
+            pledge_obligation: TokenAmount::zero(),
             live_data_sectors: BTreeMap::new(),
         }
     }
@@ -208,6 +215,26 @@ fn check_miner_info(info: MinerInfo, acc: &MessageAccumulator) {
         );
     }
 
+    if let Some(rotation) = &info.pending_key_rotation {
+        acc.require(
+            rotation.new_worker.protocol() == Protocol::ID,
+            format!("key rotation worker address {} is not an ID address", rotation.new_worker),
+        );
+        rotation.new_control_addresses.iter().for_each(|address| {
+            acc.require(
+                address.protocol() == Protocol::ID,
+                format!("key rotation control address {} is not an ID address", address),
+            )
+        });
+    }
+
+    if let Some(proving_key) = info.proving_key {
+        acc.require(
+            proving_key.protocol() == Protocol::ID,
+            format!("proving key {} is not an ID address", proving_key),
+        );
+    }
+
     if let Some(pending_owner_address) = info.pending_owner_address {
         acc.require(
             pending_owner_address.protocol() == Protocol::ID,
@@ -358,7 +385,8 @@ fn check_precommits<BS: Blockstore>(
                 );
 
                 acc.require(
-                    cleanup_epochs.contains_key(&sector_number),
+                    cleanup_epochs.contains_key(&sector_number)
+                        || state.sealed_ahead_sectors.get(sector_number),
                     format!("no clean-up epoch for pre-commit at {}", precommit.pre_commit_epoch),
                 );
                 precommit_total += &precommit.pre_commit_deposit;
@@ -372,6 +400,54 @@ fn check_precommits<BS: Blockstore>(
     };
 
     acc.require(state.pre_commit_deposits == precommit_total, format!("sum of pre-commit deposits {precommit_total} does not equal recorded pre-commit deposit {}", state.pre_commit_deposits));
+
+    check_sealed_ahead(policy, state, store, acc);
+}
+
+fn check_sealed_ahead<BS: Blockstore>(
+    policy: &Policy,
+    state: &State,
+    store: &BS,
+    acc: &MessageAccumulator,
+) {
+    let quant = state.quant_spec_every_deadline(policy);
+    let mut queued = BitField::new();
+    match BitFieldQueue::new(store, &state.sealed_ahead_activations, quant) {
+        Ok(queue) => {
+            let ret = queue.amt.for_each(|epoch, sectors| {
+                let epoch = epoch as ChainEpoch;
+                acc.require(
+                    quant.quantize_up(epoch) == epoch,
+                    format!("sealed-ahead activation {epoch} is not quantized"),
+                );
+                queued |= sectors;
+                Ok(())
+            });
+            acc.require_no_error(ret, "error iterating sealed-ahead activation queue");
+        }
+        Err(e) => {
+            acc.add(format!("error loading sealed-ahead activation queue: {e}"));
+            return;
+        }
+    }
+
+    match PreCommitMap::load(store, &state.pre_committed_sectors, PRECOMMIT_CONFIG, "precommits") {
+        Ok(precommitted) => {
+            for sector_number in state.sealed_ahead_sectors.iter() {
+                acc.require(
+                    queued.get(sector_number),
+                    format!("sealed-ahead sector {sector_number} has no activation epoch"),
+                );
+                acc.require(
+                    matches!(precommitted.get(&sector_number), Ok(Some(_))),
+                    format!("sealed-ahead sector {sector_number} is not pre-committed"),
+                );
+            }
+        }
+        Err(e) => {
+            acc.add(format!("error loading precommited_sectors: {e}"));
+        }
+    }
 }
 
 #[derive(Default)]
@@ -956,6 +1032,14 @@ pub fn check_deadline_state_invariants<BS: Blockstore>(
             deadline.faulty_power
         ),
     );
+    acc.require(
+        !deadline.planned_downtime_power.raw.is_negative()
+            && !deadline.planned_downtime_power.qa.is_negative(),
+        format!(
+            "deadline planned downtime power {:?} is negative",
+            deadline.planned_downtime_power
+        ),
+    );
 
     // Validate partition expiration queue contains an entry for each partition and epoch with an expiration.
     // The queue may be a superset of the partitions that have expirations because we never remove from it.
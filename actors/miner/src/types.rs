@@ -6,23 +6,31 @@ use fvm_ipld_bitfield::BitField;
 use fvm_ipld_encoding::{strict_bytes, BytesDe};
 use fvm_ipld_encoding::{tuple::*, RawBytes};
 use fvm_shared::address::Address;
-use fvm_shared::bigint::bigint_ser;
+use fvm_shared::bigint::bigint_ser::{self, BigIntDe};
 use fvm_shared::clock::ChainEpoch;
 use fvm_shared::deal::DealID;
 use fvm_shared::econ::TokenAmount;
-use fvm_shared::piece::PaddedPieceSize;
+use fvm_shared::piece::{PaddedPieceSize, PieceInfo};
 use fvm_shared::randomness::Randomness;
 use fvm_shared::sector::{
     PoStProof, RegisteredAggregateProof, RegisteredPoStProof, RegisteredSealProof,
     RegisteredUpdateProof, SectorNumber, SectorSize, StoragePower,
 };
 use fvm_shared::ActorID;
-use serde::{Deserialize, Serialize};
+use serde::de::{self, SeqAccess, Visitor};
+use serde::{Deserialize, Deserializer, Serialize};
+use std::fmt;
 
 use fil_actors_runtime::reward::FilterEstimate;
+pub use fil_actors_runtime::sector_content::{
+    PieceChange, PieceReturn, SectorChanges, SectorContentChangedParams,
+    SectorContentChangedReturn, SectorReturn,
+};
 use fil_actors_runtime::{BatchReturn, DealWeight};
 
 use crate::commd::CompactCommD;
+use crate::deadline_state::PoStOutcome;
+use crate::ext::market::ClientPieceManifest;
 use crate::ext::verifreg::AllocationID;
 use crate::ext::verifreg::ClaimID;
 
@@ -33,6 +41,7 @@ pub type CronEvent = i64;
 pub const CRON_EVENT_WORKER_KEY_CHANGE: CronEvent = 0;
 pub const CRON_EVENT_PROVING_DEADLINE: CronEvent = 1;
 pub const CRON_EVENT_PROCESS_EARLY_TERMINATIONS: CronEvent = 2;
+pub const CRON_EVENT_CONSENSUS_FAULT_ELAPSED: CronEvent = 3;
 
 /// Storage miner actor constructor params are defined here so the power actor can send them to the init actor
 /// to instantiate miners.
@@ -129,6 +138,19 @@ pub struct SubmitWindowedPoStParams {
     pub chain_commit_rand: Randomness,
 }
 
+/// Window PoSts submitted together for a number of deadlines.
+#[derive(Serialize_tuple, Deserialize_tuple)]
+pub struct SubmitWindowedPoSt2Params {
+    /// The submissions, each for a distinct deadline.
+    pub posts: Vec<SubmitWindowedPoStParams>,
+}
+
+#[derive(Debug, PartialEq, Eq, Serialize_tuple, Deserialize_tuple)]
+pub struct SubmitWindowedPoSt2Return {
+    /// The result of each submission, in the order submitted.
+    pub results: BatchReturn,
+}
+
 // Deprecated as of FIP 0084 -- kept for legacy testing
 #[derive(Serialize_tuple, Deserialize_tuple)]
 pub struct ProveCommitSectorParams {
@@ -157,6 +179,32 @@ pub struct ProveCommitSectorsNIParams {
     pub require_activation_success: bool, // Whether to abort if any sector activation fails
 }
 
+#[derive(Clone, Debug, Eq, PartialEq, Serialize_tuple, Deserialize_tuple)]
+pub struct ExtendSectorProofExpirationParams {
+    pub sectors: Vec<SectorProofRefreshInfo>, // Sectors whose seal proofs are refreshed
+    pub aggregate_proof: RawBytes,            // Aggregate proof for all sectors
+    pub seal_proof_type: RegisteredSealProof, // Proof type for each seal (must be an NI-PoRep variant)
+    pub aggregate_proof_type: RegisteredAggregateProof, // Proof type for aggregation
+}
+
+#[derive(Clone, Debug, Eq, PartialEq, Serialize_tuple, Deserialize_tuple)]
+pub struct SectorProofRefreshInfo {
+    pub sector_number: SectorNumber,
+    pub unsealed_cid: CompactCommD, // CommD of the sector's data, a witness to the proof
+    pub seal_rand_epoch: ChainEpoch,
+}
+
+/// Pre-committed sectors proven ahead of their activation ("sealed ahead").
+#[derive(Serialize_tuple, Deserialize_tuple, Debug, Clone, Eq, PartialEq)]
+pub struct ProveCommitSealedAheadParams {
+    /// The pre-committed sectors to prove, which must hold no data.
+    pub sector_numbers: Vec<SectorNumber>,
+    /// A seal proof for each sector, parallel to the sector numbers.
+    pub sector_proofs: Vec<RawBytes>,
+    /// The epoch from which the sectors' power and initial pledge are activated.
+    pub activation_epoch: ChainEpoch,
+}
+
 #[derive(Clone, Debug, Eq, PartialEq, Serialize_tuple, Deserialize_tuple)]
 pub struct ProveCommitSectors3Params {
     // Activation manifest for each sector being proven.
@@ -182,12 +230,44 @@ pub struct ProveCommitSectors3Params {
 // Note: we should consider fast tracking the special case where there is only
 //  one piece not claiming or notifying other actors to allow an empty piece vector.
 //  We could interpret this as a single piece, size == sector size, cid == commD, empty allocation empty notify vector
-#[derive(Clone, Debug, Eq, PartialEq, Serialize_tuple, Deserialize_tuple)]
+#[derive(Clone, Debug, Eq, PartialEq, Serialize_tuple)]
 pub struct SectorActivationManifest {
     // Sector to be activated.
     pub sector_number: SectorNumber,
     // Pieces comprising the sector content, in order.
     pub pieces: Vec<PieceActivationManifest>,
+    // Client-signed manifests authorizing the pieces without a published deal, verified by the
+    // market actor. If non-empty, the manifests' pieces must be exactly the sector's pieces.
+    pub client_manifests: Vec<ClientPieceManifest>,
+}
+
+// Callers that predate client manifests encode a manifest without them,
+// so the trailing field is optional when decoding.
+impl<'de> Deserialize<'de> for SectorActivationManifest {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_seq(SectorActivationManifestVisitor)
+    }
+}
+
+struct SectorActivationManifestVisitor;
+
+impl<'de> Visitor<'de> for SectorActivationManifestVisitor {
+    type Value = SectorActivationManifest;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("a sector number and pieces, optionally followed by client manifests")
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+        let sector_number =
+            seq.next_element()?.ok_or_else(|| de::Error::invalid_length(0, &self))?;
+        let pieces = seq.next_element()?.ok_or_else(|| de::Error::invalid_length(1, &self))?;
+        let client_manifests = seq.next_element()?.unwrap_or_default();
+        if seq.next_element::<de::IgnoredAny>()?.is_some() {
+            return Err(de::Error::invalid_length(4, &self));
+        }
+        Ok(SectorActivationManifest { sector_number, pieces, client_manifests })
+    }
 }
 
 #[derive(Clone, Debug, Eq, PartialEq, Serialize_tuple, Deserialize_tuple)]
@@ -318,6 +398,14 @@ pub struct FaultDeclaration {
     pub sectors: BitField,
 }
 
+#[derive(Serialize_tuple, Deserialize_tuple)]
+#[serde(transparent)]
+pub struct DeclarePlannedDowntimeParams {
+    /// Deadlines whose live sectors will be faulty for their next challenge window,
+    /// each in range [0..WPoStPeriodDeadlines).
+    pub deadlines: Vec<u64>,
+}
+
 #[derive(Serialize_tuple, Deserialize_tuple)]
 pub struct DeclareFaultsRecoveredParams {
     pub recoveries: Vec<RecoveryDeclaration>,
@@ -372,6 +460,31 @@ pub struct WorkerKeyChange {
     pub effective_at: ChainEpoch,
 }
 
+#[derive(Debug, Clone, PartialEq, Eq, Serialize_tuple, Deserialize_tuple)]
+pub struct KeyRotation {
+    /// Must be an ID address
+    pub new_worker: Address,
+    /// Must all be ID addresses
+    pub new_control_addresses: Vec<Address>,
+    pub effective_at: ChainEpoch,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize_tuple, Deserialize_tuple)]
+pub struct ProposeKeyRotationParams {
+    pub new_worker: Address,
+    pub new_control_addresses: Vec<Address>,
+    /// Epochs until the rotation takes effect.
+    /// Must be at least the policy's worker key change delay.
+    pub delay: ChainEpoch,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize_tuple, Deserialize_tuple)]
+#[serde(transparent)]
+pub struct ChangeProvingKeyParams {
+    /// The new proving key, or none to remove it.
+    pub new_proving_key: Option<Address>,
+}
+
 #[derive(Debug, Default, PartialEq, Eq, Clone, Serialize_tuple, Deserialize_tuple)]
 pub struct PreCommitSectorParams {
     pub seal_proof: RegisteredSealProof,
@@ -401,6 +514,17 @@ pub struct PreCommitSectorBatchParams2 {
     pub sectors: Vec<SectorPreCommitInfo>,
 }
 
+#[derive(Debug, PartialEq, Eq, Clone, Serialize_tuple, Deserialize_tuple)]
+pub struct PreCommitSectorBatchParams3 {
+    pub sectors: Vec<SectorPreCommitInfo>,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq, Serialize_tuple, Deserialize_tuple)]
+#[serde(transparent)]
+pub struct PreCommitSectorBatch3Return {
+    pub precommit_results: BatchReturn,
+}
+
 #[derive(Debug, Default, PartialEq, Eq, Clone, Serialize_tuple, Deserialize_tuple)]
 pub struct SectorPreCommitInfo {
     pub seal_proof: RegisteredSealProof,
@@ -423,7 +547,7 @@ pub struct SectorPreCommitOnChainInfo {
 }
 
 /// Information stored on-chain for a proven sector.
-#[derive(Debug, Default, PartialEq, Eq, Clone, Serialize_tuple, Deserialize_tuple)]
+#[derive(Debug, Default, PartialEq, Eq, Clone, Serialize_tuple)]
 pub struct SectorOnChainInfo {
     pub sector_number: SectorNumber,
     /// The seal proof type implies the PoSt proofs
@@ -455,6 +579,74 @@ pub struct SectorOnChainInfo {
     pub sector_key_cid: Option<Cid>,
     /// Additional flags, see [`SectorOnChainInfoFlags`]
     pub flags: SectorOnChainInfoFlags,
+    /// Epoch at which the seal proof's validity was last renewed with ExtendSectorProofExpiration.
+    /// Absent from sectors stored before it was introduced, which decode as never refreshed.
+    pub proof_refreshed: Option<ChainEpoch>,
+}
+
+// Sectors stored before `proof_refreshed` was introduced are encoded without it,
+// so the trailing field is optional when decoding.
+impl<'de> Deserialize<'de> for SectorOnChainInfo {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_seq(SectorOnChainInfoVisitor)
+    }
+}
+
+struct SectorOnChainInfoVisitor;
+
+impl<'de> Visitor<'de> for SectorOnChainInfoVisitor {
+    type Value = SectorOnChainInfo;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("a sector on-chain info, optionally followed by its proof refresh epoch")
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+        macro_rules! field {
+            ($index:expr) => {
+                seq.next_element()?.ok_or_else(|| de::Error::invalid_length($index, &self))?
+            };
+        }
+        // Fields must be read in their encoded order.
+        let sector_number = field!(0);
+        let seal_proof = field!(1);
+        let sealed_cid = field!(2);
+        let deprecated_deal_ids = field!(3);
+        let activation = field!(4);
+        let expiration = field!(5);
+        let BigIntDe(deal_weight) = field!(6);
+        let BigIntDe(verified_deal_weight) = field!(7);
+        let info = SectorOnChainInfo {
+            sector_number,
+            seal_proof,
+            sealed_cid,
+            deprecated_deal_ids,
+            activation,
+            expiration,
+            deal_weight,
+            verified_deal_weight,
+            initial_pledge: field!(8),
+            expected_day_reward: field!(9),
+            expected_storage_pledge: field!(10),
+            power_base_epoch: field!(11),
+            replaced_day_reward: field!(12),
+            sector_key_cid: field!(13),
+            flags: field!(14),
+            proof_refreshed: seq.next_element()?.flatten(),
+        };
+        if seq.next_element::<de::IgnoredAny>()?.is_some() {
+            return Err(de::Error::invalid_length(17, &self));
+        }
+        Ok(info)
+    }
+}
+
+impl SectorOnChainInfo {
+    /// Epoch from which the sector's maximum lifetime is measured: the activation epoch, or the
+    /// epoch the seal proof was last refreshed.
+    pub fn proof_validity_start(&self) -> ChainEpoch {
+        self.proof_refreshed.unwrap_or(self.activation)
+    }
 }
 
 bitflags::bitflags! {
@@ -604,6 +796,12 @@ pub struct GetAvailableBalanceReturn {
     pub available_balance: TokenAmount,
 }
 
+#[derive(Debug, Clone, PartialEq, Eq, Serialize_tuple, Deserialize_tuple)]
+#[serde(transparent)]
+pub struct GetPledgeObligationReturn {
+    pub pledge_obligation: TokenAmount,
+}
+
 #[derive(Serialize_tuple, Deserialize_tuple)]
 pub struct GetVestingFundsReturn {
     pub vesting_funds: Vec<(ChainEpoch, TokenAmount)>,
@@ -620,62 +818,87 @@ pub struct GetMultiaddrsReturn {
     pub multi_addrs: Vec<BytesDe>,
 }
 
-// Notification of change committed to one or more sectors.
-// The relevant state must be already committed so the receiver can observe any impacts
-// at the sending miner actor.
-#[derive(Clone, Debug, PartialEq, Eq, Serialize_tuple, Deserialize_tuple)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize_tuple, Deserialize_tuple)]
 #[serde(transparent)]
-pub struct SectorContentChangedParams {
-    // Distinct sectors with changed content.
-    pub sectors: Vec<SectorChanges>,
+pub struct TerminationPenaltyEstimateParams {
+    pub sectors: BitField,
 }
 
-// Description of changes to one sector's content.
-#[derive(Clone, Debug, PartialEq, Eq, Serialize_tuple, Deserialize_tuple)]
-pub struct SectorChanges {
-    // Identifier of sector being updated.
-    pub sector: SectorNumber,
-    // Minimum epoch until which the data is committed to the sector.
-    // Note the sector may later be extended without necessarily another notification.
-    pub minimum_commitment_epoch: ChainEpoch,
-    // Information about some pieces added to (or retained in) the sector.
-    // This may be only a subset of sector content.
-    // Inclusion here does not mean the piece was definitely absent previously.
-    // Exclusion here does not mean a piece has been removed since a prior notification.
-    pub added: Vec<PieceChange>,
-}
-
-// Description of a piece of data committed to a sector.
-#[derive(Clone, Debug, PartialEq, Eq, Serialize_tuple, Deserialize_tuple)]
-pub struct PieceChange {
-    pub data: Cid,
-    pub size: PaddedPieceSize,
-    // A receiver-specific identifier.
-    // E.g. an encoded deal ID which the provider claims this piece satisfies.
-    pub payload: RawBytes,
+#[derive(Debug, Clone, PartialEq, Eq, Serialize_tuple, Deserialize_tuple)]
+#[serde(transparent)]
+pub struct TerminationPenaltyEstimateReturn {
+    pub penalty: TokenAmount,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize_tuple, Deserialize_tuple)]
+#[serde(transparent)]
+pub struct GetDeadlinePoStHistoryParams {
+    pub deadline: u64,
 }
 
-// For each piece in each sector, the notifee returns an exit code and
-// (possibly-empty) result data.
-// The miner actor will pass through results to its caller.
-#[derive(Clone, Debug, PartialEq, Eq, Serialize_tuple, Deserialize_tuple)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize_tuple, Deserialize_tuple)]
+#[serde(transparent)]
+pub struct GetDeadlinePoStHistoryReturn {
+    pub outcomes: Vec<PoStOutcome>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct GetPieceSectorsParams {
+    pub piece_cid: Cid,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize_tuple, Deserialize_tuple)]
 #[serde(transparent)]
-pub struct SectorContentChangedReturn {
-    // A result for each sector that was notified, in the same order.
-    pub sectors: Vec<SectorReturn>,
+pub struct GetPieceSectorsReturn {
+    pub sectors: Vec<SectorNumber>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize_tuple, Deserialize_tuple)]
+pub struct SamplePartitionSectorsParams {
+    pub deadline: u64,
+    pub partition: u64,
+    /// The challenge randomness, which must be 32 bytes.
+    pub randomness: Randomness,
+    /// The number of sectors to sample, with replacement.
+    pub count: u64,
 }
 
-#[derive(Clone, Debug, PartialEq, Eq, Serialize_tuple, Deserialize_tuple)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize_tuple, Deserialize_tuple)]
 #[serde(transparent)]
-pub struct SectorReturn {
-    // A result for each piece for the sector that was notified, in the same order.
-    pub added: Vec<PieceReturn>,
+pub struct SamplePartitionSectorsReturn {
+    pub sectors: Vec<SectorNumber>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize_tuple, Deserialize_tuple)]
+#[serde(transparent)]
+pub struct GetSectorStatusParams {
+    pub sector_number: SectorNumber,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize_tuple, Deserialize_tuple)]
+pub struct GetSectorStatusReturn {
+    /// Whether the sector is proven, and neither faulty nor terminated.
+    pub live: bool,
+    /// Whether the sector is faulty.
+    pub faulty: bool,
+    /// Epoch during which the sector proof was accepted.
+    pub activation: ChainEpoch,
+    /// Epoch during which the sector expires.
+    pub expiration: ChainEpoch,
+    /// The deadline and partition holding the sector.
+    pub deadline: u64,
+    pub partition: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize_tuple, Deserialize_tuple)]
+pub struct ComputeUnsealedSectorCIDParams {
+    pub sector_type: RegisteredSealProof,
+    pub pieces: Vec<PieceInfo>,
 }
 
-#[derive(Clone, Debug, PartialEq, Eq, Serialize_tuple, Deserialize_tuple)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(transparent)]
-pub struct PieceReturn {
-    // Indicates whether the receiver accepted the notification.
-    // The caller is free to ignore this, but may chose to abort and roll back.
-    pub accepted: bool,
+pub struct ComputeUnsealedSectorCIDReturn {
+    pub unsealed_cid: Cid,
 }
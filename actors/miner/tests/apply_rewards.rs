@@ -14,6 +14,7 @@ use fvm_shared::clock::ChainEpoch;
 use fvm_shared::econ::TokenAmount;
 use fvm_shared::error::ExitCode;
 use fvm_shared::METHOD_SEND;
+use std::ops::Neg;
 
 mod util;
 
@@ -37,6 +38,20 @@ fn funds_are_locked() {
     assert_eq!(expected, h.get_locked_funds(&rt));
 }
 
+#[test]
+fn pledge_obligation_includes_locked_funds() {
+    let h = ActorHarness::new(PERIOD_OFFSET);
+    let rt = h.new_runtime();
+    rt.set_balance(BIG_BALANCE.clone());
+    h.construct_and_verify(&rt);
+    assert_eq!(TokenAmount::zero(), h.get_pledge_obligation(&rt));
+
+    h.apply_rewards(&rt, TokenAmount::from_atto(1_000_000), TokenAmount::zero());
+    let st = h.get_state(&rt);
+    assert_eq!(&st.initial_pledge + &st.locked_funds, h.get_pledge_obligation(&rt));
+    assert_eq!(TokenAmount::from_atto(750_000), h.get_pledge_obligation(&rt));
+}
+
 #[test]
 fn funds_vest() {
     let h = ActorHarness::new(PERIOD_OFFSET);
@@ -140,6 +155,14 @@ fn penalty_is_partially_burnt_and_stored_as_fee_debt() {
         None,
         ExitCode::OK,
     );
+    // the whole balance is burnt and the remaining penalty is left as fee debt
+    expect_balance_changed(
+        &rt,
+        &penalty.clone().neg(),
+        &TokenAmount::zero(),
+        &TokenAmount::zero(),
+        &amt,
+    );
 
     let params = ApplyRewardParams { reward, penalty };
     rt.call::<Actor>(Method::ApplyRewards as u64, IpldBlock::serialize_cbor(&params).unwrap())
@@ -209,6 +232,13 @@ fn rewards_pay_back_fee_debt() {
         None,
         ExitCode::OK,
     );
+    expect_balance_changed(
+        &rt,
+        &remaining_locked.clone().neg(),
+        &remaining_locked,
+        &TokenAmount::zero(),
+        &init_fee_debt.clone().neg(),
+    );
 
     let params = ApplyRewardParams { reward: reward.clone(), penalty };
     rt.call::<Actor>(Method::ApplyRewards as u64, IpldBlock::serialize_cbor(&params).unwrap())
@@ -1,12 +1,17 @@
 use fil_actor_account::Method as AccountMethod;
-use fil_actor_miner::{Actor, ChangeWorkerAddressParams, Method};
+use fil_actor_miner::{
+    new_deadline_info_from_offset_and_epoch, Actor, ChangeWorkerAddressParams, Method,
+    ProposeKeyRotationParams,
+};
 use fil_actors_runtime::{
     runtime::RuntimePolicy,
     test_utils::{
         expect_abort, expect_abort_contains_message, new_bls_addr, MockRuntime,
         ACCOUNT_ACTOR_CODE_ID, MINER_ACTOR_CODE_ID,
     },
+    ActorError, EventBuilder,
 };
+use fvm_shared::clock::ChainEpoch;
 use fvm_shared::{address::Address, econ::TokenAmount, error::ExitCode};
 
 mod util;
@@ -370,3 +375,135 @@ fn fails_when_caller_is_not_the_owner() {
 
     h.check_state(&rt);
 }
+
+fn propose_key_rotation(
+    h: &ActorHarness,
+    rt: &MockRuntime,
+    new_worker: Address,
+    new_control_addresses: Vec<Address>,
+    delay: ChainEpoch,
+) -> Result<Option<IpldBlock>, ActorError> {
+    rt.set_address_actor_type(new_worker, *ACCOUNT_ACTOR_CODE_ID);
+    rt.expect_send_simple(
+        new_worker,
+        AccountMethod::PubkeyAddress as u64,
+        None,
+        TokenAmount::zero(),
+        IpldBlock::serialize_cbor(&h.worker_key).unwrap(),
+        ExitCode::OK,
+    );
+    rt.expect_validate_caller_addr(vec![h.owner]);
+    rt.expect_emitted_event(
+        EventBuilder::new()
+            .typ("key-rotation-proposed")
+            .field_indexed("new-worker", &new_worker)
+            .field("effective-at", &(*rt.epoch.borrow() + delay))
+            .build()
+            .unwrap(),
+    );
+    rt.set_caller(*ACCOUNT_ACTOR_CODE_ID, h.owner);
+
+    let params = ProposeKeyRotationParams { new_worker, new_control_addresses, delay };
+    let ret = rt.call::<Actor>(
+        Method::ProposeKeyRotationExported as u64,
+        IpldBlock::serialize_cbor(&params).unwrap(),
+    );
+    if ret.is_err() {
+        rt.reset();
+        return ret;
+    }
+    rt.verify();
+    ret
+}
+
+#[test]
+fn key_rotation_can_be_vetoed_and_applies_after_delay() {
+    let (h, rt) = setup();
+
+    let new_worker = Address::new_id(999);
+    let new_control_addresses = vec![Address::new_id(1000)];
+    let delay = rt.policy().worker_key_change_delay;
+
+    // delays shorter than the minimum are rejected
+    let result =
+        propose_key_rotation(&h, &rt, new_worker, new_control_addresses.clone(), delay - 1);
+    expect_abort(ExitCode::USR_ILLEGAL_ARGUMENT, result);
+
+    // the worker may not propose a rotation of its own key
+    rt.set_address_actor_type(new_worker, *ACCOUNT_ACTOR_CODE_ID);
+    rt.expect_send_simple(
+        new_worker,
+        AccountMethod::PubkeyAddress as u64,
+        None,
+        TokenAmount::zero(),
+        IpldBlock::serialize_cbor(&h.worker_key).unwrap(),
+        ExitCode::OK,
+    );
+    rt.expect_validate_caller_addr(vec![h.owner]);
+    rt.set_caller(*ACCOUNT_ACTOR_CODE_ID, h.worker);
+    let params = ProposeKeyRotationParams {
+        new_worker,
+        new_control_addresses: new_control_addresses.clone(),
+        delay,
+    };
+    expect_abort(
+        ExitCode::USR_FORBIDDEN,
+        rt.call::<Actor>(
+            Method::ProposeKeyRotationExported as u64,
+            IpldBlock::serialize_cbor(&params).unwrap(),
+        ),
+    );
+    rt.reset();
+
+    // the owner may veto a pending rotation
+    propose_key_rotation(&h, &rt, new_worker, new_control_addresses.clone(), delay).unwrap();
+    assert_eq!(new_worker, h.get_info(&rt).pending_key_rotation.unwrap().new_worker);
+
+    rt.set_caller(*ACCOUNT_ACTOR_CODE_ID, h.owner);
+    rt.expect_validate_caller_addr(vec![h.owner]);
+    rt.expect_emitted_event(
+        EventBuilder::new()
+            .typ("key-rotation-vetoed")
+            .field_indexed("new-worker", &new_worker)
+            .build()
+            .unwrap(),
+    );
+    rt.call::<Actor>(Method::VetoKeyRotationExported as u64, None).unwrap();
+    rt.verify();
+    assert!(h.get_info(&rt).pending_key_rotation.is_none());
+
+    // a rotation that is not vetoed is applied by the first deadline cron after it is effective
+    let effective_at = *rt.epoch.borrow() + delay;
+    propose_key_rotation(&h, &rt, new_worker, new_control_addresses.clone(), delay).unwrap();
+
+    let deadline = new_deadline_info_from_offset_and_epoch(
+        &rt.policy,
+        h.get_state(&rt).proving_period_start,
+        effective_at,
+    );
+    rt.set_epoch(deadline.last());
+    rt.expect_emitted_event(
+        EventBuilder::new()
+            .typ("key-rotation-applied")
+            .field_indexed("new-worker", &new_worker)
+            .build()
+            .unwrap(),
+    );
+    h.on_deadline_cron(&rt, CronConfig { no_enrollment: true, ..CronConfig::empty() });
+
+    let info = h.get_info(&rt);
+    assert_eq!(new_worker, info.worker);
+    assert_eq!(new_control_addresses, info.control_addresses);
+    assert!(info.pending_key_rotation.is_none());
+
+    // once applied there is nothing left to veto
+    rt.set_caller(*ACCOUNT_ACTOR_CODE_ID, h.owner);
+    rt.expect_validate_caller_addr(vec![h.owner]);
+    expect_abort(
+        ExitCode::USR_NOT_FOUND,
+        rt.call::<Actor>(Method::VetoKeyRotationExported as u64, None),
+    );
+    rt.reset();
+
+    h.check_state(&rt);
+}
@@ -5,7 +5,7 @@ use fil_actor_miner::{
 };
 use fil_actors_runtime::runtime::RuntimePolicy;
 use fil_actors_runtime::test_utils::MockRuntime;
-use fil_actors_runtime::{MessageAccumulator, EPOCHS_IN_DAY};
+use fil_actors_runtime::{EventBuilder, MessageAccumulator, EPOCHS_IN_DAY};
 use fvm_ipld_bitfield::BitField;
 use fvm_shared::bigint::Zero;
 use fvm_shared::clock::ChainEpoch;
@@ -153,6 +153,53 @@ fn sector_expires() {
     h.check_state(&rt);
 }
 
+#[test]
+fn sector_expiry_over_cron_budget_reported() {
+    let mut h = ActorHarness::new(PERIOD_OFFSET);
+    let mut rt = h.new_runtime();
+    rt.set_balance(BIG_BALANCE.clone());
+    h.construct_and_verify(&rt);
+
+    let sectors =
+        h.commit_and_prove_sectors(&rt, 1, DEFAULT_SECTOR_EXPIRATION as u64, vec![], true);
+    h.advance_and_submit_posts(&rt, &sectors);
+    let active_power = power_for_sectors(h.sector_size, &sectors);
+
+    let mut st = h.get_state(&rt);
+    let initial_pledge = &st.initial_pledge;
+    let (dl_idx, _) = st.find_sector(&rt.store, sectors[0].sector_number).unwrap();
+    let expiration =
+        st.quant_spec_for_deadline(&rt.policy, dl_idx).quantize_up(sectors[0].expiration);
+    let remaining_period =
+        (expiration - st.proving_period_start) / rt.policy.wpost_proving_period + 1;
+    st.proving_period_start += remaining_period * rt.policy.wpost_proving_period;
+    st.current_deadline = dl_idx;
+    rt.replace_state(&st);
+
+    // The expiring sector exceeds a budget of zero.
+    rt.policy.miner_cron_entries_budget = 0;
+    rt.expect_emitted_event(
+        EventBuilder::new()
+            .typ("cron-budget-exceeded")
+            .field("entries", &1u64)
+            .field("budget", &0u64)
+            .build()
+            .unwrap(),
+    );
+
+    rt.set_epoch(expiration);
+    h.advance_deadline(
+        &rt,
+        CronConfig {
+            no_enrollment: true,
+            expired_sectors_power_delta: Some(active_power.neg()),
+            expired_sectors_pledge_delta: initial_pledge.neg(),
+            ..CronConfig::default()
+        },
+    );
+    h.check_state(&rt);
+}
+
 #[test]
 fn sector_expires_and_repays_fee_debt() {
     let mut h = ActorHarness::new(PERIOD_OFFSET);
@@ -1,12 +1,20 @@
 use fil_actor_miner::{
-    Actor, GetAvailableBalanceReturn, GetOwnerReturn, GetSectorSizeReturn,
-    IsControllingAddressParam, IsControllingAddressReturn, Method,
+    Actor, CompactCommD, ComputeUnsealedSectorCIDParams, ComputeUnsealedSectorCIDReturn,
+    GetAvailableBalanceReturn, GetDeadlinePoStHistoryParams, GetDeadlinePoStHistoryReturn,
+    GetOwnerReturn, GetSectorSizeReturn, GetSectorStatusParams, GetSectorStatusReturn,
+    IsControllingAddressParam, IsControllingAddressReturn, Method, PoStOutcome, State,
+    POST_HISTORY_LENGTH,
 };
 use fil_actors_runtime::runtime::policy_constants::MAX_SECTOR_NUMBER;
-use fil_actors_runtime::test_utils::EVM_ACTOR_CODE_ID;
+use fil_actors_runtime::test_utils::{
+    expect_abort, make_piece_cid, make_sealed_cid, EVM_ACTOR_CODE_ID,
+};
 use fil_actors_runtime::INIT_ACTOR_ADDR;
 use fvm_ipld_encoding::ipld_block::IpldBlock;
 use fvm_shared::address::Address;
+use fvm_shared::error::ExitCode;
+use fvm_shared::piece::{PaddedPieceSize, PieceInfo};
+use fvm_shared::sector::RegisteredSealProof;
 use fvm_shared::{clock::ChainEpoch, econ::TokenAmount};
 use std::ops::Sub;
 
@@ -144,3 +152,133 @@ fn collateral_getters() {
 
     h.check_state(&rt);
 }
+
+#[test]
+fn deadline_post_history_getter() {
+    let h = ActorHarness::new(PERIOD_OFFSET);
+    let rt = h.new_runtime();
+    h.construct_and_verify(&rt);
+    rt.set_caller(*EVM_ACTOR_CODE_ID, Address::new_id(1234));
+
+    // nothing has been recorded for a new miner
+    rt.expect_validate_caller_any();
+    let ret: GetDeadlinePoStHistoryReturn = rt
+        .call::<Actor>(
+            Method::GetDeadlinePoStHistoryExported as u64,
+            IpldBlock::serialize_cbor(&GetDeadlinePoStHistoryParams { deadline: 0 }).unwrap(),
+        )
+        .unwrap()
+        .unwrap()
+        .deserialize()
+        .unwrap();
+    rt.verify();
+    assert_eq!(POST_HISTORY_LENGTH as usize, ret.outcomes.len());
+    assert!(ret.outcomes.iter().all(|o| *o == PoStOutcome::Empty));
+
+    // deadline index must be in range
+    rt.expect_validate_caller_any();
+    expect_abort(
+        ExitCode::USR_ILLEGAL_ARGUMENT,
+        rt.call::<Actor>(
+            Method::GetDeadlinePoStHistoryExported as u64,
+            IpldBlock::serialize_cbor(&GetDeadlinePoStHistoryParams {
+                deadline: rt.policy.wpost_period_deadlines,
+            })
+            .unwrap(),
+        ),
+    );
+    rt.reset();
+
+    h.check_state(&rt);
+}
+
+#[test]
+fn sector_status_getter() {
+    let mut h = ActorHarness::new(PERIOD_OFFSET);
+    let rt = h.new_runtime();
+    h.construct_and_verify(&rt);
+    rt.set_balance(BIG_BALANCE.clone());
+    let sectors =
+        h.commit_and_prove_sectors(&rt, 1, DEFAULT_SECTOR_EXPIRATION as u64, vec![], true);
+    let sector = &sectors[0];
+    let st: State = rt.get_state();
+    let (deadline, partition) = st.find_sector(&rt.store, sector.sector_number).unwrap();
+
+    let get_status = |sector_number| {
+        rt.set_caller(*EVM_ACTOR_CODE_ID, Address::new_id(1234));
+        rt.expect_validate_caller_any();
+        let ret = rt.call::<Actor>(
+            Method::GetSectorStatusExported as u64,
+            IpldBlock::serialize_cbor(&GetSectorStatusParams { sector_number }).unwrap(),
+        );
+        rt.verify();
+        ret.map(|r| r.unwrap().deserialize::<GetSectorStatusReturn>().unwrap())
+    };
+
+    // the sector is live once its first Window PoSt is accepted
+    h.advance_and_submit_posts(&rt, &sectors);
+    let expected = GetSectorStatusReturn {
+        live: true,
+        faulty: false,
+        activation: sector.activation,
+        expiration: sector.expiration,
+        deadline,
+        partition,
+    };
+    assert_eq!(expected, get_status(sector.sector_number).unwrap());
+
+    h.declare_faults(&rt, &sectors);
+    assert_eq!(
+        GetSectorStatusReturn { live: false, faulty: true, ..expected },
+        get_status(sector.sector_number).unwrap()
+    );
+
+    expect_abort(ExitCode::USR_NOT_FOUND, get_status(sector.sector_number + 1));
+    rt.reset();
+
+    h.check_state(&rt);
+}
+
+#[test]
+fn compute_unsealed_sector_cid_getter() {
+    let h = ActorHarness::new(PERIOD_OFFSET);
+    let rt = h.new_runtime();
+    h.construct_and_verify(&rt);
+    rt.set_caller(*EVM_ACTOR_CODE_ID, Address::new_id(1234));
+
+    let sector_type = RegisteredSealProof::StackedDRG32GiBV1P1;
+    let compute = |pieces: Vec<PieceInfo>| {
+        rt.expect_validate_caller_any();
+        let ret = rt.call::<Actor>(
+            Method::ComputeUnsealedSectorCIDExported as u64,
+            IpldBlock::serialize_cbor(&ComputeUnsealedSectorCIDParams { sector_type, pieces })
+                .unwrap(),
+        );
+        rt.verify();
+        ret.map(|r| r.unwrap().deserialize::<ComputeUnsealedSectorCIDReturn>().unwrap())
+    };
+
+    // Delegates to the runtime for sectors with pieces.
+    let pieces = vec![
+        PieceInfo { cid: make_piece_cid(b"piece-1"), size: PaddedPieceSize(1 << 34) },
+        PieceInfo { cid: make_piece_cid(b"piece-2"), size: PaddedPieceSize(1 << 34) },
+    ];
+    let unsealed_cid = make_sealed_cid(b"unsealed");
+    rt.expect_compute_unsealed_sector_cid(sector_type, pieces.clone(), unsealed_cid, ExitCode::OK);
+    assert_eq!(unsealed_cid, compute(pieces.clone()).unwrap().unsealed_cid);
+
+    rt.expect_compute_unsealed_sector_cid(
+        sector_type,
+        pieces.clone(),
+        unsealed_cid,
+        ExitCode::USR_ILLEGAL_ARGUMENT,
+    );
+    expect_abort(ExitCode::USR_ILLEGAL_ARGUMENT, compute(pieces));
+    rt.reset();
+
+    // A sector without pieces is all zeros.
+    assert_eq!(
+        CompactCommD::empty().get_cid(sector_type).unwrap(),
+        compute(vec![]).unwrap().unsealed_cid
+    );
+}
@@ -1,24 +1,33 @@
 use fil_actor_market::ActivatedDeal;
 use fil_actor_miner::ext::verifreg::Claim as FILPlusClaim;
 use fil_actor_miner::{
-    power_for_sector, seal_proof_sector_maximum_lifetime, ExpirationExtension,
+    power_for_sector, seal_proof_sector_maximum_lifetime, Actor, CompactCommD, ExpirationExtension,
     ExpirationExtension2, ExtendSectorExpiration2Params, ExtendSectorExpirationParams,
-    PoStPartition, SectorClaim, SectorOnChainInfo, State,
+    ExtendSectorProofExpirationParams, Method, PoStPartition, SectorClaim, SectorOnChainInfo,
+    SectorProofRefreshInfo, State,
 };
+use fil_actors_runtime::cbor::serialize;
 use fil_actors_runtime::DealWeight;
 use fil_actors_runtime::{
     actor_error,
-    runtime::{Runtime, RuntimePolicy},
-    test_utils::{expect_abort_contains_message, make_piece_cid, MockRuntime},
-    EPOCHS_IN_DAY,
+    runtime::{DomainSeparationTag, Runtime, RuntimePolicy},
+    test_utils::{
+        expect_abort_contains_message, make_piece_cid, MockRuntime, ACCOUNT_ACTOR_CODE_ID,
+    },
+    EPOCHS_IN_DAY, UPGRADE_NETWORK_VERSION,
 };
 use fvm_ipld_bitfield::BitField;
+use fvm_ipld_encoding::ipld_block::IpldBlock;
+use fvm_ipld_encoding::RawBytes;
 use fvm_shared::deal::DealID;
 use fvm_shared::{
     address::Address,
     clock::ChainEpoch,
     error::ExitCode,
-    sector::{RegisteredSealProof, SectorNumber},
+    randomness::Randomness,
+    sector::{
+        AggregateSealVerifyInfo, RegisteredAggregateProof, RegisteredSealProof, SectorNumber,
+    },
     ActorID,
 };
 use std::collections::HashMap;
@@ -173,6 +182,111 @@ fn rejects_extension_past_max_for_seal_proof(v2: bool) {
     h.check_state(&rt);
 }
 
+#[test]
+fn proof_refresh_restarts_max_lifetime() {
+    let (mut h, mut rt) = setup();
+    let ni_proof_type = RegisteredSealProof::StackedDRG512MiBV1P2_Feat_NiPoRep;
+    rt.policy.valid_prove_commit_ni_proof_type.insert(ni_proof_type);
+    let sector = commit_sector(&mut h, &rt);
+    h.advance_and_submit_posts(&rt, &vec![sector.clone()]);
+
+    let max_lifetime = seal_proof_sector_maximum_lifetime(sector.seal_proof).unwrap();
+    let state: State = rt.get_state();
+    let (deadline_index, partition_index) =
+        state.find_sector(rt.store(), sector.sector_number).unwrap();
+    let extend_params = || ExtendSectorExpirationParams {
+        extensions: vec![ExpirationExtension {
+            deadline: deadline_index,
+            partition: partition_index,
+            sectors: make_bitfield(&[sector.sector_number]),
+            new_expiration: sector.activation + max_lifetime + 1,
+        }],
+    };
+
+    // extending past the maximum lifetime from activation fails
+    let res = h.extend_sectors(&rt, extend_params());
+    expect_abort_contains_message(ExitCode::USR_ILLEGAL_ARGUMENT, "total sector lifetime", res);
+    rt.reset();
+
+    // refresh the sector's proof
+    let refresh_epoch = *rt.epoch.borrow();
+    let params = ExtendSectorProofExpirationParams {
+        sectors: vec![SectorProofRefreshInfo {
+            sector_number: sector.sector_number,
+            unsealed_cid: CompactCommD::empty(),
+            seal_rand_epoch: refresh_epoch - 1,
+        }],
+        aggregate_proof: RawBytes::new(vec![1, 2, 3, 4]),
+        seal_proof_type: ni_proof_type,
+        aggregate_proof_type: RegisteredAggregateProof::SnarkPackV2,
+    };
+    rt.set_caller(*ACCOUNT_ACTOR_CODE_ID, h.worker);
+    rt.expect_validate_caller_addr(h.caller_addrs());
+    rt.expect_get_randomness_from_tickets(
+        DomainSeparationTag::SealRandomness,
+        refresh_epoch - 1,
+        serialize(&rt.receiver, "address for get verify info").unwrap().to_vec(),
+        TEST_RANDOMNESS_ARRAY_FROM_ONE,
+    );
+    rt.expect_aggregate_verify_seals(
+        vec![AggregateSealVerifyInfo {
+            sector_number: sector.sector_number,
+            randomness: Randomness(TEST_RANDOMNESS_ARRAY_FROM_ONE.to_vec()),
+            interactive_randomness: Randomness(vec![1u8; 32]),
+            sealed_cid: sector.sealed_cid,
+            unsealed_cid: CompactCommD::empty().get_cid(ni_proof_type).unwrap(),
+        }],
+        params.aggregate_proof.to_vec(),
+        Ok(()),
+    );
+    rt.call::<Actor>(
+        Method::ExtendSectorProofExpiration as u64,
+        IpldBlock::serialize_cbor(&params).unwrap(),
+    )
+    .unwrap();
+    rt.verify();
+    assert_eq!(Some(refresh_epoch), h.get_sector(&rt, sector.sector_number).proof_refreshed);
+
+    // the maximum lifetime is now measured from the refresh
+    h.extend_sectors(&rt, extend_params()).unwrap();
+    h.check_state(&rt);
+}
+
+#[test]
+fn proof_refresh_fails_with_seal_proof_not_allowed_by_network() {
+    let (mut h, mut rt) = setup();
+    let ni_proof_type = RegisteredSealProof::StackedDRG512MiBV1P2_Feat_NiPoRep;
+    rt.policy.valid_prove_commit_ni_proof_type.insert(ni_proof_type);
+    let sector = commit_sector(&mut h, &rt);
+
+    rt.network_version = UPGRADE_NETWORK_VERSION;
+    h.seal_proof_allowlist = Some(vec![sector.seal_proof]);
+    let params = ExtendSectorProofExpirationParams {
+        sectors: vec![SectorProofRefreshInfo {
+            sector_number: sector.sector_number,
+            unsealed_cid: CompactCommD::empty(),
+            seal_rand_epoch: *rt.epoch.borrow() - 1,
+        }],
+        aggregate_proof: RawBytes::new(vec![1, 2, 3, 4]),
+        seal_proof_type: ni_proof_type,
+        aggregate_proof_type: RegisteredAggregateProof::SnarkPackV2,
+    };
+    rt.set_caller(*ACCOUNT_ACTOR_CODE_ID, h.worker);
+    rt.expect_validate_caller_addr(h.caller_addrs());
+    h.expect_query_current_total_power(&rt);
+    expect_abort_contains_message(
+        ExitCode::USR_ILLEGAL_ARGUMENT,
+        "not allowed by the network",
+        rt.call::<Actor>(
+            Method::ExtendSectorProofExpiration as u64,
+            IpldBlock::serialize_cbor(&params).unwrap(),
+        ),
+    );
+    rt.verify();
+    assert_eq!(None, h.get_sector(&rt, sector.sector_number).proof_refreshed);
+    h.check_state(&rt);
+}
+
 #[test_case(false; "v1")]
 #[test_case(true; "v2")]
 fn updates_expiration_with_valid_params(v2: bool) {
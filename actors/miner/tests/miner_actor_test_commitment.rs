@@ -2,7 +2,7 @@ use fil_actor_miner::{
     max_prove_commit_duration, pre_commit_deposit_for_power, qa_power_max, DeadlineInfo, State,
     VestSpec,
 };
-use fil_actors_runtime::network::EPOCHS_IN_DAY;
+use fil_actors_runtime::network::{EPOCHS_IN_DAY, UPGRADE_NETWORK_VERSION};
 use fil_actors_runtime::runtime::policy_constants::MAX_SECTOR_NUMBER;
 use fil_actors_runtime::test_utils::*;
 use fvm_shared::address::Address;
@@ -12,6 +12,7 @@ use fvm_shared::deal::DealID;
 use fvm_shared::econ::TokenAmount;
 use fvm_shared::error::ExitCode;
 use fvm_shared::sector::{RegisteredSealProof, SectorNumber};
+use fvm_shared::version::NetworkVersion;
 
 use num_traits::Zero;
 
@@ -237,8 +238,10 @@ mod miner_actor_test_commitment {
 
         // Bad seal proof type
         {
+            let expiration =
+                deadline.period_end() + DEFAULT_SECTOR_EXPIRATION * rt.policy.wpost_proving_period;
             let mut precommit_params =
-                h.make_pre_commit_params(102, challenge_epoch, deadline.period_end(), vec![]);
+                h.make_pre_commit_params(102, challenge_epoch, expiration, vec![]);
             precommit_params.seal_proof = RegisteredSealProof::StackedDRG8MiBV1;
             let ret =
                 h.pre_commit_sector(&rt, precommit_params, util::PreCommitConfig::default(), false);
@@ -475,6 +478,69 @@ mod miner_actor_test_commitment {
         }
     }
 
+    #[test]
+    fn fails_with_seal_proof_not_allowed_by_network() {
+        let setup = |allowlist: Vec<RegisteredSealProof>,
+                     network_version: NetworkVersion|
+         -> (MockRuntime, ActorHarness, DeadlineInfo) {
+            let period_offset = ChainEpoch::from(100);
+
+            let mut h = ActorHarness::new(period_offset);
+            h.seal_proof_allowlist = Some(allowlist);
+            let mut rt = h.new_runtime();
+            rt.network_version = network_version;
+
+            rt.set_balance(BIG_BALANCE.clone());
+            rt.set_received(TokenAmount::zero());
+
+            rt.set_epoch(period_offset + 1);
+            h.construct_and_verify(&rt);
+            let deadline = h.deadline(&rt);
+            (rt, h, deadline)
+        };
+
+        let sector_number: SectorNumber = 100;
+        let (rt, h, deadline) =
+            setup(vec![RegisteredSealProof::StackedDRG64GiBV1P1], UPGRADE_NETWORK_VERSION);
+        let expiration =
+            deadline.period_end() + DEFAULT_SECTOR_EXPIRATION * rt.policy.wpost_proving_period;
+        let precommit_params =
+            h.make_pre_commit_params(sector_number, *rt.epoch.borrow() - 1, expiration, vec![]);
+        let ret =
+            h.pre_commit_sector(&rt, precommit_params, util::PreCommitConfig::default(), true);
+        expect_abort_contains_message(
+            ExitCode::USR_ILLEGAL_ARGUMENT,
+            "not allowed by the network",
+            ret,
+        );
+        rt.reset();
+
+        // The miner's seal proof type succeeds once the network allows it.
+        let (rt, h, _) =
+            setup(vec![RegisteredSealProof::StackedDRG32GiBV1P1], UPGRADE_NETWORK_VERSION);
+        let precommit_params =
+            h.make_pre_commit_params(sector_number, *rt.epoch.borrow() - 1, expiration, vec![]);
+        h.pre_commit_sector_and_get(&rt, precommit_params, util::PreCommitConfig::default(), true);
+        util::check_state_invariants_from_mock_runtime(&rt);
+
+        // The allowlist is not enforced before the upgrade network version.
+        let (rt, h, _) = setup(vec![RegisteredSealProof::StackedDRG64GiBV1P1], NetworkVersion::V24);
+        let precommit_params =
+            h.make_pre_commit_params(sector_number, *rt.epoch.borrow() - 1, expiration, vec![]);
+        h.pre_commit_sector_and_get(&rt, precommit_params, util::PreCommitConfig::default(), true);
+        util::check_state_invariants_from_mock_runtime(&rt);
+
+        // The allowlist decides in place of the policy, so it may allow a proof type that the
+        // policy doesn't support.
+        let (mut rt, h, _) =
+            setup(vec![RegisteredSealProof::StackedDRG32GiBV1P1], UPGRADE_NETWORK_VERSION);
+        rt.policy.valid_pre_commit_proof_type.remove(RegisteredSealProof::StackedDRG32GiBV1P1);
+        let precommit_params =
+            h.make_pre_commit_params(sector_number, *rt.epoch.borrow() - 1, expiration, vec![]);
+        h.pre_commit_sector_and_get(&rt, precommit_params, util::PreCommitConfig::default(), true);
+        util::check_state_invariants_from_mock_runtime(&rt);
+    }
+
     #[test]
     fn precommit_checks_seal_proof_version() {
         let period_offset = ChainEpoch::from(100);
@@ -149,10 +149,13 @@ mod miner_actor_precommit_batch {
     };
     use fil_actor_miner::{
         new_deadline_info_from_offset_and_epoch, Actor, CompactCommD, Method,
-        PreCommitSectorBatchParams2,
+        PreCommitSectorBatch3Return, PreCommitSectorBatchParams2, PreCommitSectorBatchParams3,
+    };
+    use fil_actors_runtime::{
+        BatchReturn, BURNT_FUNDS_ACTOR_ADDR, STORAGE_MARKET_ACTOR_ADDR, STORAGE_POWER_ACTOR_ADDR,
     };
-    use fil_actors_runtime::{STORAGE_MARKET_ACTOR_ADDR, STORAGE_POWER_ACTOR_ADDR};
     use fvm_ipld_encoding::ipld_block::IpldBlock;
+    use fvm_shared::METHOD_SEND;
 
     #[test]
     fn one_sector() {
@@ -396,4 +399,348 @@ mod miner_actor_precommit_batch {
             rt.reset();
         }
     }
+
+    #[test]
+    fn batch3_skips_bad_apples() {
+        let period_offset = ChainEpoch::from(100);
+
+        let h =
+            ActorHarness::new_with_options(HarnessOptions { proving_period_offset: period_offset });
+        let rt = h.new_runtime();
+
+        rt.set_balance(BIG_BALANCE.clone());
+        rt.set_received(TokenAmount::zero());
+
+        let precommit_epoch = period_offset + 1;
+        rt.set_epoch(precommit_epoch);
+        h.construct_and_verify(&rt);
+        let dl_info = h.deadline(&rt);
+
+        let sector_expiration =
+            dl_info.period_end() + DEFAULT_SECTOR_EXPIRATION * rt.policy.wpost_proving_period;
+        let no_commd = CompactCommD::new(None);
+        let sectors = vec![
+            h.make_pre_commit_params_v2(
+                100,
+                precommit_epoch - 1,
+                sector_expiration,
+                vec![],
+                no_commd.clone(),
+            ),
+            h.make_pre_commit_params_v2(
+                101,
+                precommit_epoch - 1,
+                *rt.epoch.borrow(),
+                vec![],
+                no_commd.clone(),
+            ), // Expires too soon
+            h.make_pre_commit_params_v2(
+                102,
+                precommit_epoch - 1,
+                sector_expiration,
+                vec![],
+                no_commd.clone(),
+            ),
+            h.make_pre_commit_params_v2(
+                100,
+                precommit_epoch - 1,
+                sector_expiration,
+                vec![],
+                no_commd.clone(),
+            ), // Duplicated
+        ];
+
+        rt.set_caller(*ACCOUNT_ACTOR_CODE_ID, h.worker);
+        rt.expect_validate_caller_addr(h.caller_addrs());
+        h.expect_query_network_info(&rt);
+        // The aggregate fee is charged only for the two sectors pre-committed.
+        rt.expect_send_simple(
+            BURNT_FUNDS_ACTOR_ADDR,
+            METHOD_SEND,
+            None,
+            aggregate_pre_commit_network_fee(2, &TokenAmount::zero()),
+            None,
+            ExitCode::OK,
+        );
+        let state = h.get_state(&rt);
+        let dlinfo = new_deadline_info_from_offset_and_epoch(
+            &rt.policy,
+            state.proving_period_start,
+            *rt.epoch.borrow(),
+        );
+        rt.expect_send_simple(
+            STORAGE_POWER_ACTOR_ADDR,
+            PowerMethod::EnrollCronEvent as u64,
+            IpldBlock::serialize_cbor(&make_deadline_cron_event_params(dlinfo.last())).unwrap(),
+            TokenAmount::zero(),
+            None,
+            ExitCode::OK,
+        );
+        expect_event(&rt, "sector-precommitted", &100);
+        expect_event(&rt, "sector-precommitted", &102);
+
+        let ret: PreCommitSectorBatch3Return = rt
+            .call::<Actor>(
+                Method::PreCommitSectorBatch3 as u64,
+                IpldBlock::serialize_cbor(&PreCommitSectorBatchParams3 { sectors }).unwrap(),
+            )
+            .unwrap()
+            .unwrap()
+            .deserialize()
+            .unwrap();
+        rt.verify();
+        assert_eq!(
+            BatchReturn::of(&[
+                ExitCode::OK,
+                ExitCode::USR_ILLEGAL_ARGUMENT,
+                ExitCode::OK,
+                ExitCode::USR_ILLEGAL_ARGUMENT
+            ]),
+            ret.precommit_results
+        );
+
+        let precommit = h.get_precommit(&rt, 100);
+        assert_eq!(sector_expiration, precommit.info.expiration);
+        h.get_precommit(&rt, 102);
+        let st: State = rt.get_state();
+        assert_eq!(&precommit.pre_commit_deposit * 2, st.pre_commit_deposits);
+
+        // A batch with no valid sectors changes nothing.
+        let sectors = vec![h.make_pre_commit_params_v2(
+            103,
+            precommit_epoch - 1,
+            *rt.epoch.borrow(),
+            vec![],
+            no_commd.clone(),
+        )];
+        rt.expect_validate_caller_addr(h.caller_addrs());
+        let ret: PreCommitSectorBatch3Return = rt
+            .call::<Actor>(
+                Method::PreCommitSectorBatch3 as u64,
+                IpldBlock::serialize_cbor(&PreCommitSectorBatchParams3 { sectors }).unwrap(),
+            )
+            .unwrap()
+            .unwrap()
+            .deserialize()
+            .unwrap();
+        rt.verify();
+        assert_eq!(BatchReturn::of(&[ExitCode::USR_ILLEGAL_ARGUMENT]), ret.precommit_results);
+        let st: State = rt.get_state();
+        assert_eq!(&precommit.pre_commit_deposit * 2, st.pre_commit_deposits);
+        h.check_state(&rt);
+    }
+
+    #[test]
+    fn batch3_drops_sectors_with_invalid_deals() {
+        let period_offset = ChainEpoch::from(100);
+        let h =
+            ActorHarness::new_with_options(HarnessOptions { proving_period_offset: period_offset });
+        let rt = h.new_runtime();
+        rt.set_balance(BIG_BALANCE.clone());
+        let precommit_epoch = period_offset + 1;
+        rt.set_epoch(precommit_epoch);
+        h.construct_and_verify(&rt);
+        let dl_info = h.deadline(&rt);
+
+        let sector_expiration =
+            dl_info.period_end() + DEFAULT_SECTOR_EXPIRATION * rt.policy.wpost_proving_period;
+        let commd = |s: &str| CompactCommD::of(make_piece_cid(s.as_bytes()));
+        let sectors = vec![
+            h.make_pre_commit_params_v2(
+                100,
+                precommit_epoch - 1,
+                sector_expiration,
+                vec![1],
+                commd("1"),
+            ),
+            h.make_pre_commit_params_v2(
+                101,
+                precommit_epoch - 1,
+                sector_expiration,
+                vec![2],
+                commd("2"),
+            ),
+            h.make_pre_commit_params_v2(
+                102,
+                precommit_epoch - 1,
+                sector_expiration,
+                vec![],
+                CompactCommD::empty(),
+            ),
+        ];
+        let sector_deals: Vec<_> = sectors
+            .iter()
+            .map(|s| SectorDeals {
+                sector_number: s.sector_number,
+                sector_type: s.seal_proof,
+                sector_expiry: s.expiration,
+                deal_ids: s.deal_ids.clone(),
+            })
+            .collect();
+
+        rt.set_caller(*ACCOUNT_ACTOR_CODE_ID, h.worker);
+        rt.expect_validate_caller_addr(h.caller_addrs());
+        h.expect_query_network_info(&rt);
+        // The batch fails verification, so each sector with deals is verified alone.
+        rt.expect_send_simple(
+            STORAGE_MARKET_ACTOR_ADDR,
+            MarketMethod::VerifyDealsForActivation as u64,
+            IpldBlock::serialize_cbor(&VerifyDealsForActivationParams {
+                sectors: sector_deals.clone(),
+            })
+            .unwrap(),
+            TokenAmount::zero(),
+            None,
+            ExitCode::USR_ILLEGAL_ARGUMENT,
+        );
+        rt.expect_send_simple(
+            STORAGE_MARKET_ACTOR_ADDR,
+            MarketMethod::VerifyDealsForActivation as u64,
+            IpldBlock::serialize_cbor(&VerifyDealsForActivationParams {
+                sectors: vec![sector_deals[0].clone()],
+            })
+            .unwrap(),
+            TokenAmount::zero(),
+            IpldBlock::serialize_cbor(&VerifyDealsForActivationReturn {
+                unsealed_cids: vec![sectors[0].unsealed_cid.0],
+            })
+            .unwrap(),
+            ExitCode::OK,
+        );
+        rt.expect_send_simple(
+            STORAGE_MARKET_ACTOR_ADDR,
+            MarketMethod::VerifyDealsForActivation as u64,
+            IpldBlock::serialize_cbor(&VerifyDealsForActivationParams {
+                sectors: vec![sector_deals[1].clone()],
+            })
+            .unwrap(),
+            TokenAmount::zero(),
+            None,
+            ExitCode::USR_ILLEGAL_ARGUMENT,
+        );
+        rt.expect_send_simple(
+            BURNT_FUNDS_ACTOR_ADDR,
+            METHOD_SEND,
+            None,
+            aggregate_pre_commit_network_fee(2, &TokenAmount::zero()),
+            None,
+            ExitCode::OK,
+        );
+        let state = h.get_state(&rt);
+        let dlinfo = new_deadline_info_from_offset_and_epoch(
+            &rt.policy,
+            state.proving_period_start,
+            *rt.epoch.borrow(),
+        );
+        rt.expect_send_simple(
+            STORAGE_POWER_ACTOR_ADDR,
+            PowerMethod::EnrollCronEvent as u64,
+            IpldBlock::serialize_cbor(&make_deadline_cron_event_params(dlinfo.last())).unwrap(),
+            TokenAmount::zero(),
+            None,
+            ExitCode::OK,
+        );
+        expect_event(&rt, "sector-precommitted", &100);
+        expect_event(&rt, "sector-precommitted", &102);
+
+        let ret: PreCommitSectorBatch3Return = rt
+            .call::<Actor>(
+                Method::PreCommitSectorBatch3 as u64,
+                IpldBlock::serialize_cbor(&PreCommitSectorBatchParams3 { sectors }).unwrap(),
+            )
+            .unwrap()
+            .unwrap()
+            .deserialize()
+            .unwrap();
+        rt.verify();
+        assert_eq!(
+            BatchReturn::of(&[ExitCode::OK, ExitCode::USR_ILLEGAL_ARGUMENT, ExitCode::OK]),
+            ret.precommit_results
+        );
+        assert!(h.has_precommit(&rt, 100));
+        assert!(!h.has_precommit(&rt, 101));
+        assert!(h.has_precommit(&rt, 102));
+        h.check_state(&rt);
+    }
+
+    #[test]
+    fn batch3_drops_sectors_beyond_balance() {
+        let period_offset = ChainEpoch::from(100);
+        let h =
+            ActorHarness::new_with_options(HarnessOptions { proving_period_offset: period_offset });
+        let rt = h.new_runtime();
+        let precommit_epoch = period_offset + 1;
+        rt.set_epoch(precommit_epoch);
+        h.construct_and_verify(&rt);
+        let dl_info = h.deadline(&rt);
+
+        // The balance covers the deposits and aggregate fee for two sectors, but not three.
+        let deposit = pre_commit_deposit_for_power(
+            &h.epoch_reward_smooth,
+            &h.epoch_qa_power_smooth,
+            &qa_power_max(h.sector_size),
+        );
+        rt.set_balance(&deposit * 2 + aggregate_pre_commit_network_fee(2, &TokenAmount::zero()));
+
+        let sector_expiration =
+            dl_info.period_end() + DEFAULT_SECTOR_EXPIRATION * rt.policy.wpost_proving_period;
+        let sectors: Vec<_> = (100..103)
+            .map(|sector_number| {
+                h.make_pre_commit_params_v2(
+                    sector_number,
+                    precommit_epoch - 1,
+                    sector_expiration,
+                    vec![],
+                    CompactCommD::empty(),
+                )
+            })
+            .collect();
+
+        rt.set_caller(*ACCOUNT_ACTOR_CODE_ID, h.worker);
+        rt.expect_validate_caller_addr(h.caller_addrs());
+        h.expect_query_network_info(&rt);
+        rt.expect_send_simple(
+            BURNT_FUNDS_ACTOR_ADDR,
+            METHOD_SEND,
+            None,
+            aggregate_pre_commit_network_fee(2, &TokenAmount::zero()),
+            None,
+            ExitCode::OK,
+        );
+        let state = h.get_state(&rt);
+        let dlinfo = new_deadline_info_from_offset_and_epoch(
+            &rt.policy,
+            state.proving_period_start,
+            *rt.epoch.borrow(),
+        );
+        rt.expect_send_simple(
+            STORAGE_POWER_ACTOR_ADDR,
+            PowerMethod::EnrollCronEvent as u64,
+            IpldBlock::serialize_cbor(&make_deadline_cron_event_params(dlinfo.last())).unwrap(),
+            TokenAmount::zero(),
+            None,
+            ExitCode::OK,
+        );
+        expect_event(&rt, "sector-precommitted", &100);
+        expect_event(&rt, "sector-precommitted", &101);
+
+        let ret: PreCommitSectorBatch3Return = rt
+            .call::<Actor>(
+                Method::PreCommitSectorBatch3 as u64,
+                IpldBlock::serialize_cbor(&PreCommitSectorBatchParams3 { sectors }).unwrap(),
+            )
+            .unwrap()
+            .unwrap()
+            .deserialize()
+            .unwrap();
+        rt.verify();
+        assert_eq!(
+            BatchReturn::of(&[ExitCode::OK, ExitCode::OK, ExitCode::USR_INSUFFICIENT_FUNDS]),
+            ret.precommit_results
+        );
+        assert!(!h.has_precommit(&rt, 102));
+        let st: State = rt.get_state();
+        assert_eq!(deposit * 2, st.pre_commit_deposits);
+        h.check_state(&rt);
+    }
 }
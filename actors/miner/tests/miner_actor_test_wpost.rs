@@ -76,6 +76,7 @@ fn basic_post_and_dispute() {
         &deadline.optimistic_post_submissions,
         &deadline2.optimistic_post_submissions_snapshot
     );
+    assert_eq!(miner::PoStOutcome::Proven, deadline2.post_outcomes()[0]);
 
     // Try a failed dispute.
     let dispute_sectors = vec![sector];
@@ -94,6 +95,7 @@ fn basic_post_and_dispute() {
         expected_pledge_delta: None,
     };
     h.dispute_window_post(&rt, &dlinfo, 0, &dispute_sectors, Some(expected_result));
+    assert_eq!(miner::PoStOutcome::Disputed, h.get_deadline(&rt, dlidx).post_outcomes()[0]);
 }
 
 #[test]
@@ -0,0 +1,56 @@
+use fil_actors_runtime::runtime::Policy;
+use fil_actors_runtime::test_utils::make_piece_cid;
+
+mod state_harness;
+use state_harness::*;
+
+#[test]
+fn index_lookup_and_remove_sector_pieces() {
+    let mut h = StateHarness::new(0);
+    let policy = Policy::default();
+    let piece_a = make_piece_cid("a".as_bytes());
+    let piece_b = make_piece_cid("b".as_bytes());
+
+    h.st.index_sector_pieces(
+        &policy,
+        &h.store,
+        &[(1, vec![piece_a, piece_b, piece_a]), (2, vec![piece_a])],
+    )
+    .unwrap();
+    assert_eq!(vec![1, 2], h.st.sectors_for_piece(&h.store, &piece_a).unwrap());
+    assert_eq!(vec![1], h.st.sectors_for_piece(&h.store, &piece_b).unwrap());
+
+    // Re-indexing a sector replaces its pieces.
+    h.st.index_sector_pieces(&policy, &h.store, &[(1, vec![piece_b])]).unwrap();
+    assert_eq!(vec![2], h.st.sectors_for_piece(&h.store, &piece_a).unwrap());
+    assert_eq!(vec![1], h.st.sectors_for_piece(&h.store, &piece_b).unwrap());
+
+    h.st.unindex_sector_pieces(&h.store, [1, 2]).unwrap();
+    assert!(h.st.sectors_for_piece(&h.store, &piece_a).unwrap().is_empty());
+    assert!(h.st.sectors_for_piece(&h.store, &piece_b).unwrap().is_empty());
+}
+
+#[test]
+fn piece_index_is_bounded_by_policy() {
+    let mut h = StateHarness::new(0);
+    let policy = Policy { piece_index_max_sectors: 2, ..Policy::default() };
+    let piece = make_piece_cid("a".as_bytes());
+
+    h.st.index_sector_pieces(
+        &policy,
+        &h.store,
+        &[(1, vec![piece]), (2, vec![piece]), (3, vec![piece])],
+    )
+    .unwrap();
+    assert_eq!(vec![1, 2], h.st.sectors_for_piece(&h.store, &piece).unwrap());
+
+    // Removing a sector makes room for another.
+    h.st.unindex_sector_pieces(&h.store, [1]).unwrap();
+    h.st.index_sector_pieces(&policy, &h.store, &[(3, vec![piece])]).unwrap();
+    assert_eq!(vec![2, 3], h.st.sectors_for_piece(&h.store, &piece).unwrap());
+
+    // A zero bound disables the index.
+    let disabled = Policy { piece_index_max_sectors: 0, ..Policy::default() };
+    h.st.index_sector_pieces(&disabled, &h.store, &[(4, vec![piece])]).unwrap();
+    assert_eq!(vec![2, 3], h.st.sectors_for_piece(&h.store, &piece).unwrap());
+}
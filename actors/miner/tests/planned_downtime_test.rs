@@ -0,0 +1,145 @@
+use fil_actor_miner::{
+    pledge_penalty_for_continued_fault, pledge_penalty_for_planned_downtime, power_for_sectors,
+    PowerPair,
+};
+use fil_actors_runtime::test_utils::{expect_abort_contains_message, MockRuntime};
+use fvm_shared::clock::ChainEpoch;
+use fvm_shared::econ::TokenAmount;
+use fvm_shared::error::ExitCode;
+
+use num_traits::Zero;
+
+mod util;
+use crate::util::*;
+
+// an expriration ~10 days greater than effective min expiration taking into account 30 days max
+// between pre and prove commit
+const DEFAULT_SECTOR_EXPIRATION: ChainEpoch = 220;
+
+const PERIOD_OFFSET: ChainEpoch = 100;
+
+fn setup() -> (ActorHarness, MockRuntime) {
+    let h = ActorHarness::new(PERIOD_OFFSET);
+    let rt = h.new_runtime();
+    rt.set_balance(BIG_BALANCE.clone());
+    h.construct_and_verify(&rt);
+    (h, rt)
+}
+
+#[test]
+fn planned_downtime_pays_reduced_fee_at_window_post() {
+    let (mut h, rt) = setup();
+    let all_sectors =
+        h.commit_and_prove_sectors(&rt, 1, DEFAULT_SECTOR_EXPIRATION as u64, vec![], true);
+    let pwr = power_for_sectors(h.sector_size, &all_sectors);
+
+    // add lots of funds so penalties come from vesting funds
+    h.apply_rewards(&rt, TokenAmount::from_whole(1000), TokenAmount::zero());
+
+    let st = h.get_state(&rt);
+    let (dl_idx, _) = st.find_sector(&rt.store, all_sectors[0].sector_number).unwrap();
+
+    // advance to first proving period and submit so we'll have time to declare the downtime next cycle
+    h.advance_and_submit_posts(&rt, &all_sectors);
+
+    h.declare_planned_downtime(&rt, &[(dl_idx, pwr.clone())]).unwrap();
+
+    // the deadline's live sectors are faulty, and their power is recorded as planned
+    let dl = h.get_deadline(&rt, dl_idx);
+    assert_eq!(pwr, dl.faulty_power);
+    assert_eq!(pwr, dl.planned_downtime_power);
+
+    // declaring again for the same window changes nothing
+    h.declare_planned_downtime(&rt, &[(dl_idx, PowerPair::zero())]).unwrap();
+    assert_eq!(pwr, h.get_deadline(&rt, dl_idx).planned_downtime_power);
+
+    h.advance_to_deadline(&rt, dl_idx);
+
+    // the planned power is charged at the reduced rate instead of the continued fault fee
+    let planned_penalty = pledge_penalty_for_planned_downtime(
+        &rt.policy,
+        &h.epoch_reward_smooth,
+        &h.epoch_qa_power_smooth,
+        &pwr.qa,
+    );
+    let continued_penalty = pledge_penalty_for_continued_fault(
+        &h.epoch_reward_smooth,
+        &h.epoch_qa_power_smooth,
+        &pwr.qa,
+    );
+    assert!(planned_penalty < continued_penalty);
+    h.advance_deadline(
+        &rt,
+        CronConfig { continued_faults_penalty: planned_penalty, ..Default::default() },
+    );
+
+    // the reduction applies only to the declared challenge window
+    let dl = h.get_deadline(&rt, dl_idx);
+    assert_eq!(pwr, dl.faulty_power);
+    assert!(dl.planned_downtime_power.is_zero());
+    h.check_state(&rt);
+}
+
+#[test]
+fn previously_declared_faults_pay_full_fee() {
+    let (mut h, rt) = setup();
+    let all_sectors =
+        h.commit_and_prove_sectors(&rt, 1, DEFAULT_SECTOR_EXPIRATION as u64, vec![], true);
+
+    h.apply_rewards(&rt, TokenAmount::from_whole(1000), TokenAmount::zero());
+
+    let st = h.get_state(&rt);
+    let (dl_idx, _) = st.find_sector(&rt.store, all_sectors[0].sector_number).unwrap();
+
+    h.advance_and_submit_posts(&rt, &all_sectors);
+
+    // sectors already faulty are not newly faulty as planned downtime
+    h.declare_faults(&rt, &all_sectors);
+    h.declare_planned_downtime(&rt, &[(dl_idx, PowerPair::zero())]).unwrap();
+    assert!(h.get_deadline(&rt, dl_idx).planned_downtime_power.is_zero());
+
+    h.advance_to_deadline(&rt, dl_idx);
+    h.advance_deadline(
+        &rt,
+        CronConfig {
+            continued_faults_penalty: h.continued_fault_penalty(&all_sectors),
+            ..Default::default()
+        },
+    );
+    h.check_state(&rt);
+}
+
+#[test]
+fn rejects_invalid_declarations() {
+    let (mut h, rt) = setup();
+    let all_sectors =
+        h.commit_and_prove_sectors(&rt, 1, DEFAULT_SECTOR_EXPIRATION as u64, vec![], true);
+    h.advance_and_submit_posts(&rt, &all_sectors);
+
+    expect_abort_contains_message(
+        ExitCode::USR_ILLEGAL_ARGUMENT,
+        "no deadlines declared",
+        h.declare_planned_downtime(&rt, &[]),
+    );
+
+    expect_abort_contains_message(
+        ExitCode::USR_ILLEGAL_ARGUMENT,
+        "duplicate deadlines declared",
+        h.declare_planned_downtime(&rt, &[(1, PowerPair::zero()), (1, PowerPair::zero())]),
+    );
+
+    expect_abort_contains_message(
+        ExitCode::USR_ILLEGAL_ARGUMENT,
+        "invalid planned downtime deadline",
+        h.declare_planned_downtime(&rt, &[(rt.policy.wpost_period_deadlines, PowerPair::zero())]),
+    );
+
+    // the current deadline's fault cutoff has passed
+    let dl_info = h.deadline(&rt);
+    expect_abort_contains_message(
+        ExitCode::USR_ILLEGAL_ARGUMENT,
+        "late fault or recovery declaration",
+        h.declare_planned_downtime(&rt, &[(dl_info.index, PowerPair::zero())]),
+    );
+    h.check_state(&rt);
+}
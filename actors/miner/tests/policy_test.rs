@@ -3,7 +3,7 @@ use fil_actor_miner::{
     QUALITY_BASE_MULTIPLIER, SECTOR_QUALITY_PRECISION, VERIFIED_DEAL_WEIGHT_MULTIPLIER,
 };
 use fil_actors_runtime::DealWeight;
-use fil_actors_runtime::{EPOCHS_IN_DAY, SECONDS_IN_DAY};
+use fil_actors_runtime::EPOCHS_IN_DAY;
 use fvm_shared::bigint::{BigInt, Integer, Zero};
 use fvm_shared::clock::ChainEpoch;
 use fvm_shared::sector::SectorSize;
@@ -172,7 +172,7 @@ fn verified_sector_has_power_a_multiple_of_size() {
 #[test]
 fn verified_weight_adds_proportional_power() {
     let sector_size = SectorSize::_64GiB;
-    let sector_duration = 180 * SECONDS_IN_DAY;
+    let sector_duration = 180 * EPOCHS_IN_DAY;
     let sector_weight = weight(sector_size, sector_duration);
 
     let fully_empty_power = BigInt::from(sector_size as i64);
@@ -205,6 +205,26 @@ fn reject_mismatched_commd() {
     );
 }
 
+#[test]
+fn reject_client_manifests_mismatching_pieces() {
+    let (h, rt, mut activations) = setup_precommits(&[(0, 0, 0); 2]);
+    // Authorize the second sector's pieces in the first sector's manifests.
+    let expiration = *rt.epoch.borrow();
+    activations[0].client_manifests = vec![make_client_manifest(
+        Address::new_id(CLIENT_ID),
+        h.receiver,
+        &activations[1],
+        expiration,
+    )];
+
+    let cfg = ProveCommitSectors3Config::default();
+    expect_abort_contains_message(
+        ExitCode::USR_ILLEGAL_ARGUMENT,
+        "client manifests do not match pieces for sector 100",
+        h.prove_commit_sectors3(&rt, &activations, false, false, false, cfg),
+    );
+}
+
 #[test]
 fn reject_required_claim_failure() {
     let (h, rt, activations) = setup_precommits(&[(0, 0, 0), (CLIENT_ID, 1, 0)]);
@@ -1,4 +1,5 @@
 use fvm_ipld_encoding::RawBytes;
+use fvm_shared::address::Address;
 use fvm_shared::error::ExitCode;
 use fvm_shared::sector::SectorNumber;
 use fvm_shared::{bigint::Zero, clock::ChainEpoch, econ::TokenAmount, ActorID};
@@ -489,6 +490,36 @@ fn rejected_notification_dropped() {
     verify_weights(&rt, &h, snos[1], 0, piece_size);
 }
 
+#[test]
+fn commit_with_client_manifests() {
+    let (h, mut rt) = setup_basic();
+    let piece_size = h.sector_size as u64;
+    let precommits = precommit_sectors(&mut rt, &h, &[&[piece_size], &[piece_size]]);
+    let snos: Vec<SectorNumber> =
+        precommits.iter().map(|pci: &SectorPreCommitInfo| pci.sector_number).collect();
+
+    // The first sector's piece is authorized by a client manifest, verified by the market.
+    let mut manifests = vec![
+        make_activation_manifest(snos[0], &[(piece_size, 0, 0, 0)]),
+        make_activation_manifest(snos[1], &[(piece_size, 0, 0, 0)]),
+    ];
+    let expiration = *rt.epoch.borrow();
+    manifests[0].client_manifests = vec![make_client_manifest(
+        Address::new_id(CLIENT_ID),
+        h.receiver,
+        &manifests[0],
+        expiration,
+    )];
+
+    let cfg = ProveCommitSectors3Config::default();
+    let (result, _, _) = h.prove_commit_sectors3(&rt, &manifests, true, true, false, cfg).unwrap();
+    assert_commit_result(&[ExitCode::OK; 2], &result);
+    for sno in &snos {
+        assert_eq!(*sno, h.get_sector(&rt, *sno).sector_number);
+    }
+    h.check_state(&rt);
+}
+
 fn setup_basic() -> (ActorHarness, MockRuntime) {
     let h = ActorHarness::new_with_options(HarnessOptions::default());
     let rt = h.new_runtime();
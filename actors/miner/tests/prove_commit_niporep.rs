@@ -1,5 +1,8 @@
+use fil_actors_runtime::network::UPGRADE_NETWORK_VERSION;
 use fil_actors_runtime::runtime::RuntimePolicy;
+use fil_actors_runtime::test_utils::{expect_abort_contains_message, ACCOUNT_ACTOR_CODE_ID};
 use fvm_ipld_encoding::ipld_block::IpldBlock;
+use fvm_shared::sector::RegisteredSealProof;
 use fvm_shared::{bigint::BigInt, clock::ChainEpoch, error::ExitCode};
 
 use fil_actor_miner::{
@@ -448,6 +451,48 @@ fn prove_too_many_sectors_ni_fail() {
     assert_eq!(res.unwrap_err().exit_code(), ExitCode::USR_ILLEGAL_ARGUMENT);
 }
 
+#[test]
+fn ni_prove_fail_seal_proof_not_allowed_by_network() {
+    let mut h = ActorHarness::new(PERIOD_OFFSET);
+    let mut rt = h.new_runtime();
+    rt.network_version = UPGRADE_NETWORK_VERSION;
+    rt.balance.replace(BIG_BALANCE.clone());
+    let miner = rt.receiver.id().unwrap();
+    let policy = rt.policy();
+
+    let seal_randomness_epoch = PERIOD_OFFSET + 1;
+    let activation_epoch = seal_randomness_epoch + 400;
+    let expiration = activation_epoch + policy.min_sector_expiration + 1;
+
+    rt.set_epoch(seal_randomness_epoch);
+    h.construct_and_verify(&rt);
+
+    rt.set_epoch(activation_epoch);
+
+    let sector_nums = (0..1).collect::<Vec<_>>();
+    let params =
+        h.make_prove_commit_ni_params(miner, &sector_nums, seal_randomness_epoch, expiration, 0);
+
+    h.seal_proof_allowlist = Some(vec![RegisteredSealProof::StackedDRG32GiBV1P1]);
+    rt.set_caller(*ACCOUNT_ACTOR_CODE_ID, h.worker);
+    rt.expect_validate_caller_addr(h.caller_addrs());
+    h.expect_query_network_info(&rt);
+    expect_abort_contains_message(
+        ExitCode::USR_ILLEGAL_ARGUMENT,
+        "not allowed by the network",
+        rt.call::<Actor>(
+            Method::ProveCommitSectorsNI as u64,
+            IpldBlock::serialize_cbor(&params).unwrap(),
+        ),
+    );
+    rt.verify();
+
+    // The NI seal proof type succeeds once the network allows it.
+    h.seal_proof_allowlist = Some(vec![params.seal_proof_type]);
+    let res = h.prove_commit_sectors_ni(&rt, params, true, noop());
+    assert!(res.unwrap().activation_results.all_ok());
+}
+
 #[test]
 fn ni_prove_fail_sector_number_already_in_use() {
     let h = ActorHarness::new(PERIOD_OFFSET);
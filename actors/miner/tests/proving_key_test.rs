@@ -0,0 +1,102 @@
+use fil_actor_miner::{Actor, ChangePeerIDParams, ChangeProvingKeyParams, Method};
+use fil_actors_runtime::test_utils::{expect_abort, MockRuntime, ACCOUNT_ACTOR_CODE_ID};
+use fil_actors_runtime::ActorError;
+use fvm_ipld_bitfield::BitField;
+use fvm_ipld_encoding::ipld_block::IpldBlock;
+use fvm_shared::address::Address;
+use fvm_shared::clock::ChainEpoch;
+use fvm_shared::econ::TokenAmount;
+use fvm_shared::error::ExitCode;
+
+use num_traits::Zero;
+
+mod util;
+use crate::util::*;
+
+const PERIOD_OFFSET: ChainEpoch = 100;
+const DEFAULT_SECTOR_EXPIRATION: u64 = 220;
+const PROVING_KEY: Address = Address::new_id(1234);
+
+fn setup() -> (ActorHarness, MockRuntime) {
+    let h = ActorHarness::new(PERIOD_OFFSET);
+    let rt = h.new_runtime();
+    h.construct_and_verify(&rt);
+    rt.set_balance(BIG_BALANCE.clone());
+
+    (h, rt)
+}
+
+fn change_proving_key(
+    h: &ActorHarness,
+    rt: &MockRuntime,
+    caller: Address,
+    new_proving_key: Option<Address>,
+) -> Result<(), ActorError> {
+    rt.set_caller(*ACCOUNT_ACTOR_CODE_ID, caller);
+    rt.expect_validate_caller_addr(vec![h.owner]);
+    let ret = rt.call::<Actor>(
+        Method::ChangeProvingKeyExported as u64,
+        IpldBlock::serialize_cbor(&ChangeProvingKeyParams { new_proving_key }).unwrap(),
+    );
+    if ret.is_err() {
+        rt.reset();
+        return ret.map(|_| ());
+    }
+    rt.verify();
+    Ok(())
+}
+
+#[test]
+fn proving_key_submits_posts_and_declares_recoveries() {
+    let (mut h, rt) = setup();
+    let one_sector = h.commit_and_prove_sectors(&rt, 1, DEFAULT_SECTOR_EXPIRATION, vec![], true);
+
+    change_proving_key(&h, &rt, h.owner, Some(PROVING_KEY)).unwrap();
+    assert_eq!(Some(PROVING_KEY), h.get_info(&rt).proving_key);
+
+    // The harness submits Window PoSts and recoveries from the proving key once set.
+    h.proving_key = Some(PROVING_KEY);
+    h.advance_and_submit_posts(&rt, &one_sector);
+
+    h.declare_faults(&rt, &one_sector);
+    let st = h.get_state(&rt);
+    let (dl_idx, p_idx) = st.find_sector(&rt.store, one_sector[0].sector_number).unwrap();
+    h.declare_recoveries(
+        &rt,
+        dl_idx,
+        p_idx,
+        BitField::try_from_bits([one_sector[0].sector_number]).unwrap(),
+        TokenAmount::zero(),
+    )
+    .unwrap();
+
+    let p = h.get_deadline(&rt, dl_idx).load_partition(&rt.store, p_idx).unwrap();
+    assert_eq!(p.faults, p.recoveries);
+    h.check_state(&rt);
+}
+
+#[test]
+fn proving_key_cannot_call_other_methods() {
+    let (h, rt) = setup();
+    change_proving_key(&h, &rt, h.owner, Some(PROVING_KEY)).unwrap();
+
+    rt.set_caller(*ACCOUNT_ACTOR_CODE_ID, PROVING_KEY);
+    rt.expect_validate_caller_addr(h.caller_addrs());
+    expect_abort(
+        ExitCode::USR_FORBIDDEN,
+        rt.call::<Actor>(
+            Method::ChangePeerID as u64,
+            IpldBlock::serialize_cbor(&ChangePeerIDParams { new_id: vec![1, 2, 3] }).unwrap(),
+        ),
+    );
+    rt.reset();
+
+    // Only the owner may change the proving key.
+    expect_abort(ExitCode::USR_FORBIDDEN, change_proving_key(&h, &rt, PROVING_KEY, None));
+    expect_abort(ExitCode::USR_FORBIDDEN, change_proving_key(&h, &rt, h.worker, None));
+    assert_eq!(Some(PROVING_KEY), h.get_info(&rt).proving_key);
+
+    change_proving_key(&h, &rt, h.owner, None).unwrap();
+    assert_eq!(None, h.get_info(&rt).proving_key);
+    h.check_state(&rt);
+}
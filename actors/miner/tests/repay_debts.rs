@@ -6,6 +6,7 @@ use fvm_shared::clock::ChainEpoch;
 use fvm_shared::econ::TokenAmount;
 use fvm_shared::error::ExitCode;
 use fvm_shared::METHOD_SEND;
+use std::ops::Neg;
 
 mod util;
 
@@ -80,7 +81,21 @@ fn repay_debt_restricted_correctly() {
     rt.add_balance(fee_debt.clone());
     rt.set_received(fee_debt.clone());
 
-    rt.expect_send_simple(BURNT_FUNDS_ACTOR_ADDR, METHOD_SEND, None, fee_debt, None, ExitCode::OK);
+    rt.expect_send_simple(
+        BURNT_FUNDS_ACTOR_ADDR,
+        METHOD_SEND,
+        None,
+        fee_debt.clone(),
+        None,
+        ExitCode::OK,
+    );
+    expect_balance_changed(
+        &rt,
+        &TokenAmount::zero(),
+        &TokenAmount::zero(),
+        &TokenAmount::zero(),
+        &fee_debt.neg(),
+    );
 
     rt.call::<Actor>(Method::RepayDebtExported as u64, None).unwrap();
 
@@ -1,6 +1,7 @@
 use fil_actor_miner::testing::check_state_invariants;
 use fil_actors_runtime::runtime::{Runtime, RuntimePolicy};
 use fil_actors_runtime::test_utils::{expect_abort, expect_abort_contains_message, MockRuntime};
+use fil_actors_runtime::UPGRADE_NETWORK_VERSION;
 use fvm_shared::address::Address;
 use fvm_shared::clock::ChainEpoch;
 use fvm_shared::consensus::{ConsensusFault, ConsensusFaultType};
@@ -246,3 +247,117 @@ fn double_report_of_consensus_fault_fails() {
     rt.reset();
     check_state_invariants(rt.policy(), &h.get_state(&rt), rt.store(), &rt.get_balance());
 }
+
+#[test]
+fn self_reported_consensus_fault_charges_reduced_fee() {
+    let (h, mut rt) = setup();
+    rt.network_version = UPGRADE_NETWORK_VERSION;
+    let report_epoch = 333;
+    rt.set_epoch(report_epoch);
+
+    let fault = ConsensusFault {
+        target: rt.receiver,
+        epoch: report_epoch - rt.policy.consensus_fault_self_report_window,
+        fault_type: ConsensusFaultType::DoubleForkMining,
+    };
+    h.self_report_consensus_fault(&rt, h.worker, Some(fault)).unwrap();
+
+    // the miner is ineligible just as if the fault had been reported by a third party
+    let elapsed = report_epoch + rt.policy.consensus_fault_ineligibility_duration;
+    assert_eq!(elapsed, h.get_info(&rt).consensus_fault_elapsed);
+
+    // its power is restored to the network totals once the ineligibility has elapsed
+    rt.set_epoch(elapsed + 1);
+    h.on_consensus_fault_elapsed(&rt, true);
+    check_state_invariants(rt.policy(), &h.get_state(&rt), rt.store(), &rt.get_balance());
+}
+
+#[test]
+fn self_report_rejected_after_window_or_from_non_control_address() {
+    let (h, mut rt) = setup();
+    rt.network_version = UPGRADE_NETWORK_VERSION;
+    let report_epoch = 333;
+    rt.set_epoch(report_epoch);
+
+    let fault = ConsensusFault {
+        target: rt.receiver,
+        epoch: report_epoch - rt.policy.consensus_fault_self_report_window - 1,
+        fault_type: ConsensusFaultType::DoubleForkMining,
+    };
+    expect_abort_contains_message(
+        ExitCode::USR_FORBIDDEN,
+        "too old to self-report",
+        h.self_report_consensus_fault(&rt, h.owner, Some(fault)),
+    );
+    rt.reset();
+
+    let fault = ConsensusFault {
+        target: rt.receiver,
+        epoch: report_epoch - 1,
+        fault_type: ConsensusFaultType::DoubleForkMining,
+    };
+    expect_abort(
+        ExitCode::USR_FORBIDDEN,
+        h.self_report_consensus_fault(&rt, Address::new_id(1234), Some(fault)),
+    );
+    rt.reset();
+
+    assert_eq!(-1, h.get_info(&rt).consensus_fault_elapsed);
+    check_state_invariants(rt.policy(), &h.get_state(&rt), rt.store(), &rt.get_balance());
+}
+
+#[test]
+fn self_report_rejected_before_upgrade() {
+    let (h, rt) = setup();
+    let report_epoch = 333;
+    rt.set_epoch(report_epoch);
+
+    let fault = ConsensusFault {
+        target: rt.receiver,
+        epoch: report_epoch - 1,
+        fault_type: ConsensusFaultType::DoubleForkMining,
+    };
+    expect_abort_contains_message(
+        ExitCode::USR_FORBIDDEN,
+        "cannot be self-reported",
+        h.self_report_consensus_fault(&rt, h.worker, Some(fault)),
+    );
+    rt.reset();
+    assert_eq!(-1, h.get_info(&rt).consensus_fault_elapsed);
+}
+
+#[test]
+fn third_party_report_waits_for_self_report_window() {
+    let (h, mut rt) = setup();
+    rt.network_version = UPGRADE_NETWORK_VERSION;
+    let fault_epoch = 333;
+    let fault = ConsensusFault {
+        target: rt.receiver,
+        epoch: fault_epoch,
+        fault_type: ConsensusFaultType::DoubleForkMining,
+    };
+
+    // Within the window only the miner may report the fault.
+    rt.set_epoch(fault_epoch + rt.policy.consensus_fault_self_report_window);
+    expect_abort_contains_message(
+        ExitCode::USR_FORBIDDEN,
+        "may only be self-reported",
+        h.report_consensus_fault(&rt, Address::new_id(1234), Some(fault.clone()), ExitCode::OK),
+    );
+    rt.reset();
+    assert_eq!(-1, h.get_info(&rt).consensus_fault_elapsed);
+
+    // Once the window has passed, a third party may report it for the full penalty and reward.
+    let report_epoch = fault_epoch + rt.policy.consensus_fault_self_report_window + 1;
+    rt.set_epoch(report_epoch);
+    h.report_consensus_fault(&rt, Address::new_id(1234), Some(fault), ExitCode::OK).unwrap();
+    let elapsed = report_epoch + rt.policy.consensus_fault_ineligibility_duration;
+    assert_eq!(elapsed, h.get_info(&rt).consensus_fault_elapsed);
+
+    // The miner's power stays excluded until the ineligibility has elapsed.
+    rt.set_epoch(elapsed);
+    h.on_consensus_fault_elapsed(&rt, false);
+    rt.set_epoch(elapsed + 1);
+    h.on_consensus_fault_elapsed(&rt, true);
+    check_state_invariants(rt.policy(), &h.get_state(&rt), rt.store(), &rt.get_balance());
+}
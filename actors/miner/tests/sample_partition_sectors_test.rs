@@ -0,0 +1,117 @@
+use fil_actor_miner::{
+    Actor, Method, SamplePartitionSectorsParams, SamplePartitionSectorsReturn, State,
+    MAX_SAMPLED_SECTORS,
+};
+use fil_actors_runtime::test_utils::{expect_abort, hash, MockRuntime, EVM_ACTOR_CODE_ID};
+use fil_actors_runtime::ActorError;
+use fvm_ipld_encoding::ipld_block::IpldBlock;
+use fvm_shared::address::Address;
+use fvm_shared::error::ExitCode;
+use fvm_shared::randomness::Randomness;
+use fvm_shared::sector::SectorNumber;
+use multihash_codetable::{Code, MultihashDigest};
+
+mod util;
+use util::*;
+
+fn setup() -> (ActorHarness, MockRuntime) {
+    let h = ActorHarness::new(100);
+    let rt = h.new_runtime();
+    h.construct_and_verify(&rt);
+    rt.balance.replace(BIG_BALANCE.clone());
+    (h, rt)
+}
+
+fn sample(
+    rt: &MockRuntime,
+    params: &SamplePartitionSectorsParams,
+) -> Result<Vec<SectorNumber>, ActorError> {
+    rt.set_caller(*EVM_ACTOR_CODE_ID, Address::new_id(1234));
+    rt.expect_validate_caller_any();
+    let ret = rt.call::<Actor>(
+        Method::SamplePartitionSectorsExported as u64,
+        IpldBlock::serialize_cbor(params).unwrap(),
+    );
+    rt.verify();
+    Ok(ret?.unwrap().deserialize::<SamplePartitionSectorsReturn>().unwrap().sectors)
+}
+
+// Derives the expected challenges independently of the actor.
+fn expected_sample(
+    miner: &Address,
+    randomness: &[u8],
+    count: u64,
+    candidates: &[SectorNumber],
+) -> Vec<SectorNumber> {
+    let mut prover_id = [0u8; 32];
+    let payload = miner.payload_bytes();
+    prover_id[..payload.len()].copy_from_slice(&payload);
+    let mut randomness = randomness.to_vec();
+    randomness[31] &= 0x3f;
+    (0..count)
+        .map(|i| {
+            let preimage = [&prover_id[..], &randomness, &i.to_le_bytes()].concat();
+            let digest = Code::Sha2_256.digest(&preimage);
+            let challenge = u64::from_le_bytes(digest.digest()[..8].try_into().unwrap());
+            candidates[(challenge % candidates.len() as u64) as usize]
+        })
+        .collect()
+}
+
+#[test]
+fn samples_partition_sectors_by_challenge_derivation() {
+    let (mut h, mut rt) = setup();
+    let sectors = h.commit_and_prove_sectors(&rt, 3, DEFAULT_SECTOR_EXPIRATION, vec![], true);
+    // The harness fixes hashes to place the proving period, but challenges need real digests.
+    rt.hash_func = Box::new(hash);
+    let st: State = rt.get_state();
+    let (deadline, partition) = st.find_sector(&rt.store, sectors[0].sector_number).unwrap();
+    let (_, part) = h.get_deadline_and_partition(&rt, deadline, partition);
+    let candidates: Vec<SectorNumber> = part.sectors.iter().collect();
+    assert_eq!(3, candidates.len());
+
+    // The top bits of the randomness are ignored, as in PoSt challenge derivation.
+    let mut randomness = vec![7u8; 32];
+    randomness[31] = 0xff;
+    let params = SamplePartitionSectorsParams {
+        deadline,
+        partition,
+        randomness: Randomness(randomness.clone()),
+        count: 16,
+    };
+    let sampled = sample(&rt, &params).unwrap();
+    assert_eq!(expected_sample(&h.receiver, &randomness, 16, &candidates), sampled);
+
+    randomness[31] = 0x3f;
+    let masked = SamplePartitionSectorsParams { randomness: Randomness(randomness), ..params };
+    assert_eq!(sampled, sample(&rt, &masked).unwrap());
+    h.check_state(&rt);
+}
+
+#[test]
+fn rejects_invalid_sample_requests() {
+    let (mut h, rt) = setup();
+    let sectors = h.commit_and_prove_sectors(&rt, 1, DEFAULT_SECTOR_EXPIRATION, vec![], true);
+    let st: State = rt.get_state();
+    let (deadline, partition) = st.find_sector(&rt.store, sectors[0].sector_number).unwrap();
+    let params = SamplePartitionSectorsParams {
+        deadline,
+        partition,
+        randomness: Randomness(vec![1; 32]),
+        count: 1,
+    };
+
+    let bad_deadline = SamplePartitionSectorsParams {
+        deadline: rt.policy.wpost_period_deadlines,
+        ..params.clone()
+    };
+    expect_abort(ExitCode::USR_ILLEGAL_ARGUMENT, sample(&rt, &bad_deadline));
+    let bad_partition = SamplePartitionSectorsParams { partition: partition + 1, ..params.clone() };
+    expect_abort(ExitCode::USR_NOT_FOUND, sample(&rt, &bad_partition));
+    let short_randomness =
+        SamplePartitionSectorsParams { randomness: Randomness(vec![1; 31]), ..params.clone() };
+    expect_abort(ExitCode::USR_ILLEGAL_ARGUMENT, sample(&rt, &short_randomness));
+    let too_many = SamplePartitionSectorsParams { count: MAX_SAMPLED_SECTORS + 1, ..params };
+    expect_abort(ExitCode::USR_ILLEGAL_ARGUMENT, sample(&rt, &too_many));
+    h.check_state(&rt);
+}
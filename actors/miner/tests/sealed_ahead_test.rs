@@ -0,0 +1,137 @@
+use fvm_shared::bigint::Zero;
+use fvm_shared::clock::ChainEpoch;
+use fvm_shared::econ::TokenAmount;
+use fvm_shared::error::ExitCode;
+use fvm_shared::sector::SectorNumber;
+
+use fil_actors_runtime::test_utils::{expect_abort_contains_message, MockRuntime};
+use fil_actors_runtime::EPOCHS_IN_DAY;
+use util::*;
+
+mod util;
+
+const DEFAULT_SECTOR_EXPIRATION_DAYS: ChainEpoch = 220;
+const FIRST_SECTOR_NUMBER: SectorNumber = 100;
+
+#[test]
+fn sealed_ahead_sectors_activate_at_deadline_cron() {
+    let (h, mut rt) = setup_basic();
+    let snos = precommit_sectors(&mut rt, &h, &[&[], &[]]);
+
+    // Activate beyond the point at which the pre-commits would otherwise expire.
+    let activation_epoch = *rt.epoch.borrow() + 35 * EPOCHS_IN_DAY;
+    let activation = h.prove_commit_sealed_ahead(&rt, &snos, activation_epoch).unwrap();
+    assert!(activation >= activation_epoch);
+
+    let state = h.get_state(&rt);
+    for sno in &snos {
+        assert!(state.sealed_ahead_sectors.get(*sno));
+        assert!(state.get_sector(&rt.store, *sno).unwrap().is_none());
+    }
+    h.check_state(&rt);
+
+    // The pre-commits survive their expiry without penalty.
+    while h.deadline(&rt).last() < activation {
+        h.advance_deadline(&rt, CronConfig::empty());
+    }
+    for sno in &snos {
+        h.get_precommit(&rt, *sno);
+    }
+
+    h.advance_deadline(
+        &rt,
+        CronConfig { activated_sealed_ahead: snos.clone(), ..CronConfig::empty() },
+    );
+    let state = h.get_state(&rt);
+    for sno in &snos {
+        let sector = h.get_sector(&rt, *sno);
+        assert!(sector.activation >= activation);
+        assert!(!state.sealed_ahead_sectors.get(*sno));
+        assert!(state.get_precommitted_sector(&rt.store, *sno).unwrap().is_none());
+    }
+    assert!(state.pre_commit_deposits.is_zero());
+    h.check_state(&rt);
+}
+
+#[test]
+fn rejects_activation_epoch_out_of_range() {
+    let (h, mut rt) = setup_basic();
+    let snos = precommit_sectors(&mut rt, &h, &[&[]]);
+    let epoch = *rt.epoch.borrow();
+
+    expect_abort_contains_message(
+        ExitCode::USR_ILLEGAL_ARGUMENT,
+        "activation epoch",
+        h.prove_commit_sealed_ahead(&rt, &snos, epoch),
+    );
+    expect_abort_contains_message(
+        ExitCode::USR_ILLEGAL_ARGUMENT,
+        "activation epoch",
+        h.prove_commit_sealed_ahead(&rt, &snos, epoch + rt.policy.max_sealed_ahead_delay + 1),
+    );
+    h.check_state(&rt);
+}
+
+#[test]
+fn rejects_sectors_with_data() {
+    let (h, mut rt) = setup_basic();
+    let piece_size = h.sector_size as u64;
+    let snos = precommit_sectors(&mut rt, &h, &[&[], &[piece_size]]);
+    let activation_epoch = *rt.epoch.borrow() + EPOCHS_IN_DAY;
+
+    expect_abort_contains_message(
+        ExitCode::USR_ILLEGAL_ARGUMENT,
+        "holds data",
+        h.prove_commit_sealed_ahead(&rt, &snos, activation_epoch),
+    );
+    assert!(h.get_state(&rt).sealed_ahead_sectors.is_empty());
+    h.check_state(&rt);
+}
+
+#[test]
+fn rejects_duplicate_and_repeated_sectors() {
+    let (h, mut rt) = setup_basic();
+    let snos = precommit_sectors(&mut rt, &h, &[&[]]);
+    let activation_epoch = *rt.epoch.borrow() + EPOCHS_IN_DAY;
+
+    expect_abort_contains_message(
+        ExitCode::USR_ILLEGAL_ARGUMENT,
+        "duplicate sectors",
+        h.prove_commit_sealed_ahead(&rt, &[snos[0], snos[0]], activation_epoch),
+    );
+
+    h.prove_commit_sealed_ahead(&rt, &snos, activation_epoch).unwrap();
+    expect_abort_contains_message(
+        ExitCode::USR_ILLEGAL_ARGUMENT,
+        "already sealed ahead",
+        h.prove_commit_sealed_ahead(&rt, &snos, activation_epoch),
+    );
+    h.check_state(&rt);
+}
+
+fn setup_basic() -> (ActorHarness, MockRuntime) {
+    let h = ActorHarness::new_with_options(HarnessOptions::default());
+    let rt = h.new_runtime();
+    rt.set_balance(BIG_BALANCE.clone());
+    h.construct_and_verify(&rt);
+    (h, rt)
+}
+
+fn precommit_sectors(
+    rt: &mut MockRuntime,
+    h: &ActorHarness,
+    piece_sizes: &[&[u64]],
+) -> Vec<SectorNumber> {
+    let precommit_epoch = *rt.epoch.borrow();
+    let sector_expiry = precommit_epoch + DEFAULT_SECTOR_EXPIRATION_DAYS * EPOCHS_IN_DAY;
+    let precommits = make_fake_precommits(
+        h,
+        FIRST_SECTOR_NUMBER,
+        precommit_epoch - 1,
+        sector_expiry,
+        piece_sizes,
+    );
+    h.pre_commit_sector_batch_v2(rt, &precommits, true, &TokenAmount::zero()).unwrap();
+    rt.set_epoch(precommit_epoch + rt.policy.pre_commit_challenge_delay + 1);
+    precommits.iter().map(|pc| pc.sector_number).collect()
+}
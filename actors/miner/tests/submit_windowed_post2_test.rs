@@ -0,0 +1,112 @@
+use fil_actor_miner as miner;
+use fil_actor_miner::{SubmitWindowedPoSt2Params, SubmitWindowedPoSt2Return};
+use fil_actors_runtime::test_utils::*;
+use fil_actors_runtime::{BatchReturn, FailCode};
+use fvm_shared::clock::ChainEpoch;
+use fvm_shared::error::ExitCode;
+use fvm_shared::randomness::Randomness;
+
+mod util;
+use util::*;
+
+const DEFAULT_SECTOR_EXPIRATION: u64 = 220;
+
+fn post_params(
+    h: &ActorHarness,
+    deadline: u64,
+    pidx: u64,
+    chain_commit_epoch: ChainEpoch,
+) -> miner::SubmitWindowedPoStParams {
+    miner::SubmitWindowedPoStParams {
+        deadline,
+        partitions: vec![miner::PoStPartition { index: pidx, skipped: make_empty_bitfield() }],
+        proofs: make_post_proofs(h.window_post_proof_type),
+        chain_commit_epoch,
+        chain_commit_rand: Randomness(TEST_RANDOMNESS_ARRAY_FROM_ONE.into()),
+    }
+}
+
+#[test]
+fn records_open_deadline_and_fails_others_independently() {
+    let mut h = ActorHarness::new(100);
+    let rt = h.new_runtime();
+    rt.set_balance(BIG_BALANCE.clone());
+    h.construct_and_verify(&rt);
+
+    let sector =
+        h.commit_and_prove_sectors(&rt, 1, DEFAULT_SECTOR_EXPIRATION, vec![], true)[0].clone();
+    let pwr = miner::power_for_sector(h.sector_size, &sector);
+    let state = h.get_state(&rt);
+    let (dlidx, pidx) = state.find_sector(&rt.store, sector.sector_number).unwrap();
+    let dlinfo = h.advance_to_deadline(&rt, dlidx);
+    let next_dlidx = (dlidx + 1) % rt.policy.wpost_period_deadlines;
+
+    // Only the open deadline's submission is recorded. The one for the next deadline, whose
+    // challenge window hasn't opened, and the malformed one fail without aborting the batch.
+    let mut malformed = post_params(&h, dlidx, pidx, dlinfo.challenge);
+    malformed.proofs = vec![];
+    let params = SubmitWindowedPoSt2Params {
+        posts: vec![
+            post_params(&h, next_dlidx, pidx, dlinfo.challenge),
+            post_params(&h, dlidx, pidx, dlinfo.challenge),
+            malformed,
+        ],
+    };
+    let ret: SubmitWindowedPoSt2Return = h
+        .submit_window_post2_raw(&rt, params, &[dlinfo.challenge], Some(pwr))
+        .unwrap()
+        .unwrap()
+        .deserialize()
+        .unwrap();
+    rt.verify();
+    assert_eq!(
+        BatchReturn {
+            success_count: 1,
+            fail_codes: vec![
+                FailCode { idx: 0, code: ExitCode::USR_ILLEGAL_ARGUMENT },
+                FailCode { idx: 2, code: ExitCode::USR_ILLEGAL_ARGUMENT },
+            ],
+        },
+        ret.results
+    );
+
+    let deadline = h.get_deadline(&rt, dlidx);
+    assert_bitfield_equals(&deadline.partitions_posted, &[pidx]);
+    assert!(h.get_deadline(&rt, next_dlidx).partitions_posted.is_empty());
+    h.check_state(&rt);
+}
+
+#[test]
+fn fails_when_nothing_recorded() {
+    let mut h = ActorHarness::new(100);
+    let rt = h.new_runtime();
+    rt.set_balance(BIG_BALANCE.clone());
+    h.construct_and_verify(&rt);
+
+    let sector =
+        h.commit_and_prove_sectors(&rt, 1, DEFAULT_SECTOR_EXPIRATION, vec![], true)[0].clone();
+    let state = h.get_state(&rt);
+    let (dlidx, pidx) = state.find_sector(&rt.store, sector.sector_number).unwrap();
+    let dlinfo = h.advance_to_deadline(&rt, dlidx);
+    let next_dlidx = (dlidx + 1) % rt.policy.wpost_period_deadlines;
+
+    let params = SubmitWindowedPoSt2Params {
+        posts: vec![post_params(&h, next_dlidx, pidx, dlinfo.challenge)],
+    };
+    expect_abort_contains_message(
+        ExitCode::USR_ILLEGAL_ARGUMENT,
+        "no window posts recorded",
+        h.submit_window_post2_raw(&rt, params, &[], None),
+    );
+    rt.reset();
+
+    expect_abort_contains_message(
+        ExitCode::USR_ILLEGAL_ARGUMENT,
+        "no posts submitted",
+        h.submit_window_post2_raw(&rt, SubmitWindowedPoSt2Params { posts: vec![] }, &[], None),
+    );
+    rt.reset();
+
+    assert!(h.get_deadline(&rt, dlidx).partitions_posted.is_empty());
+    h.check_state(&rt);
+}
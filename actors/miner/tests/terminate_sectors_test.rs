@@ -1,7 +1,8 @@
 use fil_actor_miner::{
     expected_reward_for_power, pledge_penalty_for_termination, qa_power_for_sector, Actor,
     CronEventPayload, DeferredCronEventParams, Method, SectorOnChainInfo, State,
-    TerminateSectorsParams, TerminationDeclaration, CRON_EVENT_PROCESS_EARLY_TERMINATIONS,
+    TerminateSectorsParams, TerminationDeclaration, TerminationPenaltyEstimateParams,
+    TerminationPenaltyEstimateReturn, CRON_EVENT_PROCESS_EARLY_TERMINATIONS,
     INITIAL_PLEDGE_PROJECTION_PERIOD,
 };
 use fil_actors_runtime::{
@@ -309,6 +310,66 @@ fn system_can_terminate_if_market_cron_fails() {
     h.check_state(&rt);
 }
 
+#[test]
+fn termination_penalty_estimate_matches_termination_fee() {
+    let (mut h, rt) = setup();
+
+    let sector_info =
+        h.commit_and_prove_sectors(&rt, 2, DEFAULT_SECTOR_EXPIRATION, Vec::new(), true);
+    h.advance_and_submit_posts(&rt, &sector_info);
+    h.apply_rewards(&rt, BIG_REWARDS.clone(), TokenAmount::zero());
+
+    let expected_fee: TokenAmount =
+        sector_info.iter().map(|s| calc_expected_fee_for_termination(&h, &rt, s)).sum();
+    let sector_nos: Vec<SectorNumber> = sector_info.iter().map(|s| s.sector_number).collect();
+    let sectors = bitfield_from_slice(&sector_nos);
+
+    rt.set_caller(*ACCOUNT_ACTOR_CODE_ID, h.worker);
+    rt.expect_validate_caller_any();
+    h.expect_query_network_info(&rt);
+    let ret: TerminationPenaltyEstimateReturn = rt
+        .call::<Actor>(
+            Method::TerminationPenaltyEstimateExported as u64,
+            IpldBlock::serialize_cbor(&TerminationPenaltyEstimateParams {
+                sectors: sectors.clone(),
+            })
+            .unwrap(),
+        )
+        .unwrap()
+        .unwrap()
+        .deserialize()
+        .unwrap();
+    rt.verify();
+    assert_eq!(expected_fee, ret.penalty);
+
+    // terminating at the same epoch charges the quoted penalty
+    h.terminate_sectors(&rt, &sectors, ret.penalty);
+    h.check_state(&rt);
+}
+
+#[test]
+fn termination_penalty_estimate_fails_for_missing_sector() {
+    let (mut h, rt) = setup();
+    let sector_info =
+        h.commit_and_prove_sectors(&rt, 1, DEFAULT_SECTOR_EXPIRATION, Vec::new(), true);
+    let missing = sector_info[0].sector_number + 1;
+
+    rt.expect_validate_caller_any();
+    expect_abort_contains_message(
+        ExitCode::USR_NOT_FOUND,
+        "sector not found",
+        rt.call::<Actor>(
+            Method::TerminationPenaltyEstimateExported as u64,
+            IpldBlock::serialize_cbor(&TerminationPenaltyEstimateParams {
+                sectors: bitfield_from_slice(&[sector_info[0].sector_number, missing]),
+            })
+            .unwrap(),
+        ),
+    );
+    rt.reset();
+    h.check_state(&rt);
+}
+
 fn calc_expected_fee_for_termination(
     h: &ActorHarness,
     rt: &MockRuntime,
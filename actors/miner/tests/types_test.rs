@@ -5,7 +5,10 @@ mod serialization {
     use cid::Cid;
     use hex_literal::hex;
 
-    use fil_actor_miner::{ProveCommitSectorsNIParams, SectorNIActivationInfo};
+    use fil_actor_miner::{
+        ProveCommitSectorsNIParams, SectorActivationManifest, SectorNIActivationInfo,
+        SectorOnChainInfo,
+    };
     use fvm_ipld_encoding::ipld_block::IpldBlock;
     use fvm_shared::sector::{RegisteredAggregateProof, RegisteredSealProof};
 
@@ -81,4 +84,47 @@ mod serialization {
             assert_eq!(params, decoded);
         }
     }
+
+    #[test]
+    fn sector_on_chain_info_without_proof_refreshed() {
+        let info = SectorOnChainInfo {
+            sector_number: 1,
+            sealed_cid: Cid::from_str("bagboea4seaaqa").unwrap(),
+            ..Default::default()
+        };
+        let encoded = fvm_ipld_encoding::to_vec(&info).unwrap();
+        // [..., null] with 16 fields
+        assert_eq!(0x90, encoded[0]);
+        assert_eq!(0xf6, *encoded.last().unwrap());
+
+        // Sectors stored before proof_refreshed was added have one field fewer.
+        let mut legacy = encoded[..encoded.len() - 1].to_vec();
+        legacy[0] = 0x8f;
+        let decoded: SectorOnChainInfo = fvm_ipld_encoding::from_slice(&legacy).unwrap();
+        assert_eq!(info, decoded);
+
+        let refreshed = SectorOnChainInfo { proof_refreshed: Some(10), ..info };
+        let encoded = fvm_ipld_encoding::to_vec(&refreshed).unwrap();
+        let decoded: SectorOnChainInfo = fvm_ipld_encoding::from_slice(&encoded).unwrap();
+        assert_eq!(refreshed, decoded);
+    }
+
+    #[test]
+    fn sector_activation_manifest_without_client_manifests() {
+        let manifest =
+            SectorActivationManifest { sector_number: 7, pieces: vec![], client_manifests: vec![] };
+        let encoded = fvm_ipld_encoding::to_vec(&manifest).unwrap();
+        // [7,[],[]]
+        assert_eq!(hex!("83078080")[..], encoded[..]);
+
+        // Callers that predate client manifests send [7,[]].
+        let decoded: SectorActivationManifest =
+            fvm_ipld_encoding::from_slice(&hex!("820780")).unwrap();
+        assert_eq!(manifest, decoded);
+
+        // Extra fields are rejected.
+        assert!(
+            fvm_ipld_encoding::from_slice::<SectorActivationManifest>(&hex!("8407808080")).is_err()
+        );
+    }
 }
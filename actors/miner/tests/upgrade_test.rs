@@ -0,0 +1,214 @@
+use fil_actor_miner::migration::{LegacyDeadline, LegacyMinerInfo, LegacyState};
+use fil_actor_miner::{power_for_sectors, Actor, ChangeProvingKeyParams, Deadline, Method, State};
+use fil_actors_runtime::test_utils::{make_piece_cid, MockRuntime, ACCOUNT_ACTOR_CODE_ID};
+use fil_actors_runtime::EPOCHS_IN_DAY;
+use fvm_ipld_encoding::ipld_block::IpldBlock;
+use fvm_ipld_encoding::CborStore;
+use fvm_shared::address::Address;
+use fvm_shared::clock::ChainEpoch;
+use fvm_shared::econ::TokenAmount;
+use multihash_codetable::Code;
+use num_traits::Zero;
+
+mod util;
+use crate::util::*;
+
+const PERIOD_OFFSET: ChainEpoch = 100;
+const DEFAULT_SECTOR_EXPIRATION: u64 = 220;
+
+/// Rewrites the miner's state in the layout stored before the current schema.
+fn replace_with_legacy_state(h: &ActorHarness, rt: &MockRuntime) -> State {
+    let st = h.get_state(rt);
+    let info = h.get_info(rt);
+    let legacy_info = LegacyMinerInfo {
+        owner: info.owner,
+        worker: info.worker,
+        control_addresses: info.control_addresses,
+        pending_worker_key: info.pending_worker_key,
+        peer_id: info.peer_id,
+        multi_address: info.multi_address,
+        window_post_proof_type: info.window_post_proof_type,
+        sector_size: info.sector_size,
+        window_post_partition_sectors: info.window_post_partition_sectors,
+        consensus_fault_elapsed: info.consensus_fault_elapsed,
+        pending_owner_address: info.pending_owner_address,
+        beneficiary: info.beneficiary,
+        beneficiary_term: info.beneficiary_term,
+        pending_beneficiary_term: info.pending_beneficiary_term,
+    };
+    let mut deadlines = st.load_deadlines(&rt.store).unwrap();
+    for due in deadlines.due.iter_mut() {
+        let deadline: Deadline = rt.store.get_cbor(due).unwrap().unwrap();
+        let legacy_deadline = LegacyDeadline {
+            partitions: deadline.partitions,
+            expirations_epochs: deadline.expirations_epochs,
+            partitions_posted: deadline.partitions_posted,
+            early_terminations: deadline.early_terminations,
+            live_sectors: deadline.live_sectors,
+            total_sectors: deadline.total_sectors,
+            faulty_power: deadline.faulty_power,
+            optimistic_post_submissions: deadline.optimistic_post_submissions,
+            sectors_snapshot: deadline.sectors_snapshot,
+            partitions_snapshot: deadline.partitions_snapshot,
+            optimistic_post_submissions_snapshot: deadline.optimistic_post_submissions_snapshot,
+        };
+        *due = rt.store.put_cbor(&legacy_deadline, Code::Blake2b256).unwrap();
+    }
+    rt.replace_state(&LegacyState {
+        info: rt.store.put_cbor(&legacy_info, Code::Blake2b256).unwrap(),
+        pre_commit_deposits: st.pre_commit_deposits.clone(),
+        locked_funds: st.locked_funds.clone(),
+        vesting_funds: st.vesting_funds,
+        fee_debt: st.fee_debt.clone(),
+        initial_pledge: st.initial_pledge.clone(),
+        pre_committed_sectors: st.pre_committed_sectors,
+        pre_committed_sectors_cleanup: st.pre_committed_sectors_cleanup,
+        allocated_sectors: st.allocated_sectors,
+        sectors: st.sectors,
+        proving_period_start: st.proving_period_start,
+        current_deadline: st.current_deadline,
+        deadlines: rt.store.put_cbor(&deadlines, Code::Blake2b256).unwrap(),
+        early_terminations: st.early_terminations.clone(),
+        deadline_cron_active: st.deadline_cron_active,
+    });
+    st
+}
+
+#[test]
+fn upgrade_migrates_legacy_state() {
+    let mut h = ActorHarness::new(PERIOD_OFFSET);
+    let rt = h.new_runtime();
+    rt.set_balance(BIG_BALANCE.clone());
+    h.construct_and_verify(&rt);
+    let sectors = h.commit_and_prove_sectors(&rt, 1, DEFAULT_SECTOR_EXPIRATION, vec![], true);
+
+    let old = replace_with_legacy_state(&h, &rt);
+    rt.upgrade::<Actor>();
+    let st = h.get_state(&rt);
+    assert_eq!(old.sectors, st.sectors);
+    let deadlines = st.load_deadlines(&rt.store).unwrap();
+    let old_deadlines = old.load_deadlines(&rt.store).unwrap();
+    for dl_idx in 0..rt.policy.wpost_period_deadlines {
+        let deadline = deadlines.load_deadline(&rt.store, dl_idx).unwrap();
+        let old_deadline = old_deadlines.load_deadline(&rt.store, dl_idx).unwrap();
+        assert_eq!(old_deadline.partitions, deadline.partitions);
+        assert_eq!(old_deadline.live_sectors, deadline.live_sectors);
+        assert_eq!(0, deadline.post_history);
+    }
+    let info = h.get_info(&rt);
+    assert_eq!(h.worker, info.worker);
+    assert_eq!(None, info.pending_key_rotation);
+    assert_eq!(None, info.proving_key);
+    h.check_state(&rt);
+
+    // The migrated miner keeps proving its sectors.
+    h.advance_and_submit_posts(&rt, &sectors);
+    h.check_state(&rt);
+}
+
+#[test]
+fn upgrade_starts_an_empty_piece_index() {
+    let mut h = ActorHarness::new(PERIOD_OFFSET);
+    let rt = h.new_runtime();
+    rt.set_balance(BIG_BALANCE.clone());
+    h.construct_and_verify(&rt);
+    let sectors = h.commit_and_prove_sectors(&rt, 1, DEFAULT_SECTOR_EXPIRATION, vec![], true);
+    let piece = make_piece_cid("a".as_bytes());
+
+    replace_with_legacy_state(&h, &rt);
+    rt.upgrade::<Actor>();
+
+    // Pieces of sectors activated before the upgrade are not on chain to index.
+    let mut st = h.get_state(&rt);
+    assert!(st.sectors_for_piece(&rt.store, &piece).unwrap().is_empty());
+
+    // The migrated index records pieces activated afterwards.
+    let sector_number = sectors[0].sector_number;
+    st.index_sector_pieces(&rt.policy, &rt.store, &[(sector_number, vec![piece])]).unwrap();
+    assert_eq!(vec![sector_number], st.sectors_for_piece(&rt.store, &piece).unwrap());
+    st.unindex_sector_pieces(&rt.store, [sector_number]).unwrap();
+    assert!(st.sectors_for_piece(&rt.store, &piece).unwrap().is_empty());
+}
+
+#[test]
+fn upgrade_allows_planned_downtime() {
+    let mut h = ActorHarness::new(PERIOD_OFFSET);
+    let rt = h.new_runtime();
+    rt.set_balance(BIG_BALANCE.clone());
+    h.construct_and_verify(&rt);
+    let sectors = h.commit_and_prove_sectors(&rt, 1, DEFAULT_SECTOR_EXPIRATION, vec![], true);
+    let pwr = power_for_sectors(h.sector_size, &sectors);
+    let (dl_idx, _) = h.get_state(&rt).find_sector(&rt.store, sectors[0].sector_number).unwrap();
+    h.advance_and_submit_posts(&rt, &sectors);
+
+    replace_with_legacy_state(&h, &rt);
+    rt.upgrade::<Actor>();
+    assert!(h.get_deadline(&rt, dl_idx).planned_downtime_power.is_zero());
+
+    h.declare_planned_downtime(&rt, &[(dl_idx, pwr.clone())]).unwrap();
+    assert_eq!(pwr, h.get_deadline(&rt, dl_idx).planned_downtime_power);
+    h.check_state(&rt);
+}
+
+#[test]
+fn upgrade_allows_proving_key() {
+    let mut h = ActorHarness::new(PERIOD_OFFSET);
+    let rt = h.new_runtime();
+    rt.set_balance(BIG_BALANCE.clone());
+    h.construct_and_verify(&rt);
+    let sectors = h.commit_and_prove_sectors(&rt, 1, DEFAULT_SECTOR_EXPIRATION, vec![], true);
+
+    replace_with_legacy_state(&h, &rt);
+    rt.upgrade::<Actor>();
+
+    let proving_key = Address::new_id(1234);
+    rt.set_caller(*ACCOUNT_ACTOR_CODE_ID, h.owner);
+    rt.expect_validate_caller_addr(vec![h.owner]);
+    rt.call::<Actor>(
+        Method::ChangeProvingKeyExported as u64,
+        IpldBlock::serialize_cbor(&ChangeProvingKeyParams { new_proving_key: Some(proving_key) })
+            .unwrap(),
+    )
+    .unwrap();
+    rt.verify();
+    assert_eq!(Some(proving_key), h.get_info(&rt).proving_key);
+
+    h.proving_key = Some(proving_key);
+    h.advance_and_submit_posts(&rt, &sectors);
+    h.check_state(&rt);
+}
+
+#[test]
+fn upgrade_allows_sealing_ahead() {
+    let h = ActorHarness::new(PERIOD_OFFSET);
+    let rt = h.new_runtime();
+    rt.set_balance(BIG_BALANCE.clone());
+    h.construct_and_verify(&rt);
+
+    // Pre-commits made before the upgrade may be sealed ahead after it.
+    let precommit_epoch = *rt.epoch.borrow();
+    let expiration = precommit_epoch + DEFAULT_SECTOR_EXPIRATION as ChainEpoch * EPOCHS_IN_DAY;
+    let precommits = make_fake_precommits(&h, 100, precommit_epoch - 1, expiration, &[&[]]);
+    h.pre_commit_sector_batch_v2(&rt, &precommits, true, &TokenAmount::zero()).unwrap();
+    let snos: Vec<_> = precommits.iter().map(|pc| pc.sector_number).collect();
+
+    replace_with_legacy_state(&h, &rt);
+    rt.upgrade::<Actor>();
+    assert!(h.get_state(&rt).sealed_ahead_sectors.is_empty());
+
+    rt.set_epoch(precommit_epoch + rt.policy.pre_commit_challenge_delay + 1);
+    let activation_epoch = *rt.epoch.borrow() + EPOCHS_IN_DAY;
+    let activation = h.prove_commit_sealed_ahead(&rt, &snos, activation_epoch).unwrap();
+    assert!(h.get_state(&rt).sealed_ahead_sectors.get(snos[0]));
+
+    while h.deadline(&rt).last() < activation {
+        h.advance_deadline(&rt, CronConfig::empty());
+    }
+    h.advance_deadline(
+        &rt,
+        CronConfig { activated_sealed_ahead: snos.clone(), ..CronConfig::empty() },
+    );
+    assert!(h.get_state(&rt).sealed_ahead_sectors.is_empty());
+    assert!(h.get_sector(&rt, snos[0]).activation >= activation);
+    h.check_state(&rt);
+}
@@ -17,12 +17,14 @@ use fvm_ipld_encoding::ipld_block::IpldBlock;
 use fvm_ipld_encoding::ser::Serialize;
 use fvm_ipld_encoding::{BytesDe, CborStore, RawBytes};
 use fvm_shared::address::Address;
+use fvm_shared::bigint::bigint_ser::BigIntSer;
 use fvm_shared::bigint::BigInt;
 use fvm_shared::bigint::Zero;
 use fvm_shared::clock::ChainEpoch;
 use fvm_shared::commcid::{FIL_COMMITMENT_SEALED, FIL_COMMITMENT_UNSEALED};
 use fvm_shared::consensus::ConsensusFault;
 use fvm_shared::crypto::hash::SupportedHashes;
+use fvm_shared::crypto::signature::Signature;
 use fvm_shared::deal::DealID;
 use fvm_shared::econ::TokenAmount;
 use fvm_shared::error::ExitCode;
@@ -48,7 +50,7 @@ use fil_actor_market::{
 };
 use fil_actor_miner::{
     aggregate_pre_commit_network_fee, aggregate_prove_commit_network_fee, consensus_fault_penalty,
-    ext,
+    consensus_fault_self_report_penalty, ext,
     ext::market::ON_MINER_SECTORS_TERMINATE_METHOD,
     ext::power::UPDATE_CLAIMED_POWER_METHOD,
     ext::verifreg::{
@@ -57,36 +59,39 @@ use fil_actor_miner::{
     },
     ext::verifreg::{Claim as FILPlusClaim, ClaimID, GetClaimsParams, GetClaimsReturn},
     initial_pledge_for_power, locked_reward_from_reward, max_prove_commit_duration,
-    new_deadline_info_from_offset_and_epoch, pledge_penalty_for_continued_fault, power_for_sectors,
-    qa_power_for_sector, qa_power_for_weight, reward_for_consensus_slash_report,
+    new_deadline_info, new_deadline_info_from_offset_and_epoch, pledge_penalty_for_continued_fault,
+    power_for_sectors, qa_power_for_sector, qa_power_for_weight, reward_for_consensus_slash_report,
     testing::{check_deadline_state_invariants, check_state_invariants, DeadlineStateSummary},
     ActiveBeneficiary, Actor, ApplyRewardParams, BeneficiaryTerm, BitFieldQueue,
     ChangeBeneficiaryParams, ChangeMultiaddrsParams, ChangePeerIDParams, ChangeWorkerAddressParams,
     CheckSectorProvenParams, CompactCommD, CompactPartitionsParams, CompactSectorNumbersParams,
     CronEventPayload, DataActivationNotification, Deadline, DeadlineInfo, Deadlines,
-    DeclareFaultsParams, DeclareFaultsRecoveredParams, DeferredCronEventParams,
-    DisputeWindowedPoStParams, ExpirationQueue, ExpirationSet, ExtendSectorExpiration2Params,
-    ExtendSectorExpirationParams, FaultDeclaration, GetAvailableBalanceReturn,
-    GetBeneficiaryReturn, GetControlAddressesReturn, GetMultiaddrsReturn, GetPeerIDReturn, Method,
-    Method as MinerMethod, MinerConstructorParams as ConstructorParams, MinerInfo, Partition,
-    PendingBeneficiaryChange, PieceActivationManifest, PieceChange, PieceReturn, PoStPartition,
-    PowerPair, PreCommitSectorBatchParams, PreCommitSectorBatchParams2, PreCommitSectorParams,
-    ProveCommitAggregateParams, ProveCommitSectorParams, ProveCommitSectors3Params,
-    ProveCommitSectors3Return, QuantSpec, RecoveryDeclaration, ReportConsensusFaultParams,
-    SectorActivationManifest, SectorChanges, SectorContentChangedParams,
-    SectorContentChangedReturn, SectorOnChainInfo, SectorPreCommitInfo, SectorPreCommitOnChainInfo,
-    SectorReturn, SectorUpdateManifest, Sectors, State, SubmitWindowedPoStParams,
-    TerminateSectorsParams, TerminationDeclaration, VerifiedAllocationKey, VestingFunds,
-    WindowedPoSt, WithdrawBalanceParams, WithdrawBalanceReturn, CRON_EVENT_PROVING_DEADLINE,
-    NI_AGGREGATE_FEE_BASE_SECTOR_COUNT, NO_QUANTIZATION, REWARD_VESTING_SPEC, SECTORS_AMT_BITWIDTH,
-    SECTOR_CONTENT_CHANGED,
+    DeclareFaultsParams, DeclareFaultsRecoveredParams, DeclarePlannedDowntimeParams,
+    DeferredCronEventParams, DisputeWindowedPoStParams, ExpirationQueue, ExpirationSet,
+    ExtendSectorExpiration2Params, ExtendSectorExpirationParams, FaultDeclaration,
+    GetAvailableBalanceReturn, GetBeneficiaryReturn, GetControlAddressesReturn,
+    GetMultiaddrsReturn, GetPeerIDReturn, GetPledgeObligationReturn, Method, Method as MinerMethod,
+    MinerConstructorParams as ConstructorParams, MinerInfo, Partition, PendingBeneficiaryChange,
+    PieceActivationManifest, PieceChange, PieceReturn, PoStPartition, PowerPair,
+    PreCommitSectorBatchParams, PreCommitSectorBatchParams2, PreCommitSectorParams,
+    ProveCommitAggregateParams, ProveCommitSealedAheadParams, ProveCommitSectorParams,
+    ProveCommitSectors3Params, ProveCommitSectors3Return, QuantSpec, RecoveryDeclaration,
+    ReportConsensusFaultParams, SectorActivationManifest, SectorChanges,
+    SectorContentChangedParams, SectorContentChangedReturn, SectorOnChainInfo, SectorPreCommitInfo,
+    SectorPreCommitOnChainInfo, SectorReturn, SectorUpdateManifest, Sectors, State,
+    SubmitWindowedPoSt2Params, SubmitWindowedPoStParams, TerminateSectorsParams,
+    TerminationDeclaration, VerifiedAllocationKey, VestingFunds, WindowedPoSt,
+    WithdrawBalanceParams, WithdrawBalanceReturn, CRON_EVENT_CONSENSUS_FAULT_ELAPSED,
+    CRON_EVENT_PROVING_DEADLINE, NI_AGGREGATE_FEE_BASE_SECTOR_COUNT, NO_QUANTIZATION,
+    REWARD_VESTING_SPEC, SECTORS_AMT_BITWIDTH, SECTOR_CONTENT_CHANGED,
 };
 use fil_actor_miner::{
     raw_power_for_sector, ProveCommitSectorsNIParams, ProveCommitSectorsNIReturn,
     ProveReplicaUpdates3Params, ProveReplicaUpdates3Return, SectorNIActivationInfo,
 };
 use fil_actor_power::{
-    CurrentTotalPowerReturn, EnrollCronEventParams, Method as PowerMethod, UpdateClaimedPowerParams,
+    CurrentTotalPowerReturn, EnrollCronEventParams, Method as PowerMethod, ProofTypeAllowlist,
+    SetConsensusFaultExclusionParams, UpdateClaimedPowerParams,
 };
 use fil_actor_reward::{Method as RewardMethod, ThisEpochRewardReturn};
 use fil_actors_runtime::cbor::serialize;
@@ -96,7 +101,7 @@ use fil_actors_runtime::{test_utils::*, BatchReturn, BatchReturnGen, EventBuilde
 use fil_actors_runtime::{
     ActorDowncast, ActorError, Array, DealWeight, MessageAccumulator, BURNT_FUNDS_ACTOR_ADDR,
     INIT_ACTOR_ADDR, REWARD_ACTOR_ADDR, STORAGE_MARKET_ACTOR_ADDR, STORAGE_POWER_ACTOR_ADDR,
-    VERIFIED_REGISTRY_ACTOR_ADDR,
+    UPGRADE_NETWORK_VERSION, VERIFIED_REGISTRY_ACTOR_ADDR,
 };
 
 const DEFAULT_PIECE_SIZE: u64 = 128;
@@ -143,6 +148,8 @@ pub struct ActorHarness {
     pub beneficiary: Address,
 
     pub control_addrs: Vec<Address>,
+    // Proofs-only key, which submits Window PoSts and declares recoveries when set.
+    pub proving_key: Option<Address>,
 
     pub seal_proof_type: RegisteredSealProof,
     pub window_post_proof_type: RegisteredPoStProof,
@@ -159,6 +166,8 @@ pub struct ActorHarness {
     pub epoch_qa_power_smooth: FilterEstimate,
 
     pub base_fee: TokenAmount,
+    // Seal proof types reported as allowed by the power actor, if restricted.
+    pub seal_proof_allowlist: Option<Vec<RegisteredSealProof>>,
 
     pub options: HarnessOptions,
 }
@@ -192,6 +201,7 @@ impl ActorHarness {
             worker,
             worker_key,
             control_addrs,
+            proving_key: None,
 
             beneficiary: owner,
             seal_proof_type: proof_type,
@@ -210,6 +220,7 @@ impl ActorHarness {
             epoch_qa_power_smooth: FilterEstimate::new(pwr, BigInt::from(0)),
 
             base_fee: TokenAmount::zero(),
+            seal_proof_allowlist: None,
 
             options,
         }
@@ -762,7 +773,12 @@ impl ActorHarness {
         state.get_precommitted_sector(&rt.store, sector_number).unwrap().unwrap()
     }
 
-    pub fn expect_query_network_info(&self, rt: &MockRuntime) {
+    pub fn expect_query_current_total_power(&self, rt: &MockRuntime) {
+        let proof_type_allowlist =
+            self.seal_proof_allowlist.clone().map(|seal_proof_types| ProofTypeAllowlist {
+                seal_proof_types,
+                post_proof_types: vec![self.window_post_proof_type],
+            });
         let current_power = CurrentTotalPowerReturn {
             raw_byte_power: self.network_raw_power.clone(),
             quality_adj_power: self.network_qa_power.clone(),
@@ -770,7 +786,19 @@ impl ActorHarness {
             quality_adj_power_smoothed: self.epoch_qa_power_smooth.clone(),
             ramp_start_epoch: 0,
             ramp_duration_epochs: 0,
+            proof_type_allowlist,
         };
+        rt.expect_send_simple(
+            STORAGE_POWER_ACTOR_ADDR,
+            PowerMethod::CurrentTotalPower as u64,
+            None,
+            TokenAmount::zero(),
+            IpldBlock::serialize_cbor(&current_power).unwrap(),
+            ExitCode::OK,
+        );
+    }
+
+    pub fn expect_query_network_info(&self, rt: &MockRuntime) {
         let current_reward = ThisEpochRewardReturn {
             this_epoch_baseline_power: self.baseline_power.clone(),
             this_epoch_reward_smoothed: self.epoch_reward_smooth.clone(),
@@ -783,14 +811,7 @@ impl ActorHarness {
             IpldBlock::serialize_cbor(&current_reward).unwrap(),
             ExitCode::OK,
         );
-        rt.expect_send_simple(
-            STORAGE_POWER_ACTOR_ADDR,
-            PowerMethod::CurrentTotalPower as u64,
-            None,
-            TokenAmount::zero(),
-            IpldBlock::serialize_cbor(&current_power).unwrap(),
-            ExitCode::OK,
-        );
+        self.expect_query_current_total_power(rt);
     }
 
     // deprecated flow calling prove commit sector and then confirm sector proofs valid
@@ -1330,13 +1351,27 @@ impl ActorHarness {
         let mut expected_qa_power = StoragePower::zero();
         let mut expected_sector_notifications = Vec::new(); // Assuming all to f05
         let mut unsealed_cids: HashMap<SectorNumber, Option<Cid>> = HashMap::new();
+        let mut manifest_sectors = Vec::new();
+        let mut manifest_commds = Vec::new();
 
         for (i, sa) in sector_activations.iter().enumerate() {
             if cfg.validation_failure.contains(&i) || cfg.proof_failure.contains(&i) {
                 continue;
             }
-            let comm_d =
-                expect_compute_unsealed_cid_from_pieces(rt, self.seal_proof_type, &sa.pieces);
+            let comm_d = if sa.client_manifests.is_empty() {
+                expect_compute_unsealed_cid_from_pieces(rt, self.seal_proof_type, &sa.pieces)
+            } else {
+                // The market computes CommD for sectors with client manifests.
+                let comm_d =
+                    sector_commd_from_pieces(&sa.pieces.iter().map(|p| p.cid).collect::<Vec<_>>());
+                manifest_sectors.push(ext::market::SectorPieceManifests {
+                    sector_number: sa.sector_number,
+                    sector_type: self.seal_proof_type,
+                    manifests: sa.client_manifests.clone(),
+                });
+                manifest_commds.push(comm_d.0);
+                comm_d
+            };
             let unsealed_cid = comm_d.0;
             unsealed_cids.insert(sa.sector_number, unsealed_cid);
 
@@ -1381,6 +1416,23 @@ impl ActorHarness {
             expected_pledge += self.initial_pledge_for_power(rt, &qa_power_delta);
         }
 
+        if !manifest_sectors.is_empty() {
+            rt.expect_send_simple(
+                STORAGE_MARKET_ACTOR_ADDR,
+                MarketMethod::VerifyDealsForActivationDirectExported as u64,
+                IpldBlock::serialize_cbor(&ext::market::VerifyDealsForActivationDirectParams {
+                    sectors: manifest_sectors,
+                })
+                .unwrap(),
+                TokenAmount::zero(),
+                IpldBlock::serialize_cbor(&VerifyDealsForActivationReturn {
+                    unsealed_cids: manifest_commds,
+                })
+                .unwrap(),
+                ExitCode::OK,
+            );
+        }
+
         // Expect claiming of verified space for each piece that specified an allocation ID.
         if !sector_allocation_claims.iter().all(|sector| sector.claims.is_empty()) {
             let claim_count = sector_allocation_claims.len();
@@ -1496,6 +1548,73 @@ impl ActorHarness {
     // sets and checks mock expectations for the expected interactions.
     // Returns the result of the invocation along with the expected sector claims and notifications
     // (which match the actual, if mock verification succeeded).
+    /// Proves pre-committed sectors without data ahead of their activation epoch,
+    /// returning the (quantized) epoch at which the sectors will be activated by cron.
+    pub fn prove_commit_sealed_ahead(
+        &self,
+        rt: &MockRuntime,
+        sector_numbers: &[SectorNumber],
+        activation_epoch: ChainEpoch,
+    ) -> Result<ChainEpoch, ActorError> {
+        fn make_proof(i: u8) -> RawBytes {
+            RawBytes::new(vec![i, i, i, i])
+        }
+        rt.set_caller(*ACCOUNT_ACTOR_CODE_ID, self.worker);
+        rt.expect_validate_caller_addr(self.caller_addrs());
+
+        let params = ProveCommitSealedAheadParams {
+            sector_numbers: sector_numbers.to_vec(),
+            sector_proofs: sector_numbers.iter().map(|sno| make_proof(*sno as u8)).collect(),
+            activation_epoch,
+        };
+
+        let precommits: Vec<SectorPreCommitOnChainInfo> =
+            sector_numbers.iter().map(|sno| self.get_precommit(rt, *sno)).collect();
+        let (_, seal_int_rands) = expect_validate_precommits(rt, &precommits)?;
+        let svis = precommits
+            .iter()
+            .enumerate()
+            .map(|(i, pci)| SealVerifyInfo {
+                registered_proof: self.seal_proof_type,
+                sector_id: SectorID {
+                    miner: STORAGE_MARKET_ACTOR_ADDR.id().unwrap(),
+                    number: pci.info.sector_number,
+                },
+                deal_ids: vec![],
+                randomness: Randomness(seal_int_rands[i].into()),
+                interactive_randomness: Randomness(seal_int_rands[i].into()),
+                proof: make_proof(pci.info.sector_number as u8).into(),
+                sealed_cid: pci.info.sealed_cid,
+                unsealed_cid: pci.info.unsealed_cid.get_cid(pci.info.seal_proof).unwrap(),
+            })
+            .collect();
+        rt.expect_batch_verify_seals(svis, Ok(vec![true; sector_numbers.len()]));
+
+        let quantized_activation =
+            self.get_state(rt).quant_spec_every_deadline(&rt.policy).quantize_up(activation_epoch);
+        for sno in sector_numbers {
+            rt.expect_emitted_event(
+                EventBuilder::new()
+                    .typ("sector-sealed-ahead")
+                    .field_indexed("sector", sno)
+                    .field("activation-epoch", &quantized_activation)
+                    .build()
+                    .unwrap(),
+            );
+        }
+
+        rt.call::<Actor>(
+            MinerMethod::ProveCommitSealedAhead as u64,
+            IpldBlock::serialize_cbor(&params).unwrap(),
+        )
+        .or_else(|e| {
+            rt.reset();
+            Err(e)
+        })?;
+        rt.verify();
+        Ok(quantized_activation)
+    }
+
     pub fn prove_replica_updates2_batch(
         &self,
         rt: &MockRuntime,
@@ -1799,6 +1918,16 @@ impl ActorHarness {
             );
         }
 
+        if !cfg.activated_sealed_ahead.is_empty() {
+            self.expect_query_network_info(rt);
+            let sector_pledge =
+                self.initial_pledge_for_power(rt, &raw_power_for_sector(self.sector_size));
+            expect_update_pledge(rt, &(sector_pledge * cfg.activated_sealed_ahead.len() as u64));
+            for sector_number in &cfg.activated_sealed_ahead {
+                expect_sector_event(rt, "sector-activated", sector_number, None, &vec![]);
+            }
+        }
+
         let params = make_deferred_cron_event_params(
             self.epoch_reward_smooth.clone(),
             self.epoch_qa_power_smooth.clone(),
@@ -1839,7 +1968,7 @@ impl ActorHarness {
         params: SubmitWindowedPoStParams,
         cfg: PoStConfig,
     ) -> Result<Option<IpldBlock>, ActorError> {
-        rt.set_caller(*ACCOUNT_ACTOR_CODE_ID, self.worker);
+        rt.set_caller(*ACCOUNT_ACTOR_CODE_ID, self.prover());
         let chain_commit_rand = match cfg.chain_randomness {
             Some(r) => r,
             None => TEST_RANDOMNESS_ARRAY_FROM_ONE.into(),
@@ -1850,7 +1979,7 @@ impl ActorHarness {
             Vec::new(),
             chain_commit_rand,
         );
-        rt.expect_validate_caller_addr(self.caller_addrs());
+        rt.expect_validate_caller_addr(self.proving_addrs());
 
         let challenge_rand = TEST_RANDOMNESS_ARRAY_FROM_TWO;
 
@@ -1913,6 +2042,36 @@ impl ActorHarness {
         )
     }
 
+    /// Submits Window PoSts for several deadlines at once, expecting the chain commit randomness
+    /// to be fetched at each of `commit_epochs`, in order. Doesn't support submissions that
+    /// recover power, which verify their proofs immediately.
+    pub fn submit_window_post2_raw(
+        &self,
+        rt: &MockRuntime,
+        params: SubmitWindowedPoSt2Params,
+        commit_epochs: &[ChainEpoch],
+        expected_power_delta: Option<PowerPair>,
+    ) -> Result<Option<IpldBlock>, ActorError> {
+        rt.set_caller(*ACCOUNT_ACTOR_CODE_ID, self.prover());
+        rt.expect_validate_caller_addr(self.proving_addrs());
+        for epoch in commit_epochs {
+            rt.expect_get_randomness_from_tickets(
+                DomainSeparationTag::PoStChainCommit,
+                *epoch,
+                Vec::new(),
+                TEST_RANDOMNESS_ARRAY_FROM_ONE,
+            );
+        }
+        if let Some(power_delta) = expected_power_delta {
+            expect_update_power(rt, power_delta);
+        }
+
+        rt.call::<Actor>(
+            Method::SubmitWindowedPoSt2 as u64,
+            IpldBlock::serialize_cbor(&params).unwrap(),
+        )
+    }
+
     fn make_window_post_verify_info(
         &self,
         infos: &[SectorOnChainInfo],
@@ -2080,6 +2239,18 @@ impl ActorHarness {
         caller_addrs
     }
 
+    // Addresses permitted to submit Window PoSts and declare recoveries.
+    pub fn proving_addrs(&self) -> Vec<Address> {
+        let mut proving_addrs = self.caller_addrs();
+        proving_addrs.extend(self.proving_key);
+        proving_addrs
+    }
+
+    // The caller of Window PoSts and recoveries.
+    fn prover(&self) -> Address {
+        self.proving_key.unwrap_or(self.worker)
+    }
+
     pub fn apply_rewards(&self, rt: &MockRuntime, amt: TokenAmount, penalty: TokenAmount) {
         // This harness function does not handle the state where apply rewards is
         // on a miner with existing fee debt.  This state is not protocol reachable
@@ -2089,7 +2260,7 @@ impl ActorHarness {
         // goes into debt we can't rely on the harness call
         // TODO unify those cases
         let (lock_amt, _) = locked_reward_from_reward(amt.clone());
-        let pledge_delta = lock_amt - &penalty;
+        let pledge_delta = &lock_amt - &penalty;
 
         rt.set_caller(*REWARD_ACTOR_CODE_ID, REWARD_ACTOR_ADDR);
         rt.expect_validate_caller_addr(vec![REWARD_ACTOR_ADDR]);
@@ -2105,6 +2276,14 @@ impl ActorHarness {
                 ExitCode::OK,
             );
         }
+        // The penalty is paid from the newly locked reward.
+        expect_balance_changed(
+            rt,
+            &lock_amt.neg(),
+            &pledge_delta,
+            &TokenAmount::zero(),
+            &TokenAmount::zero(),
+        );
 
         let params = ApplyRewardParams { reward: amt, penalty: penalty };
         rt.call::<Actor>(Method::ApplyRewards as u64, IpldBlock::serialize_cbor(&params).unwrap())
@@ -2245,6 +2424,52 @@ impl ActorHarness {
         expected_delta
     }
 
+    /// Declares planned downtime for deadlines, each with the power expected to become faulty.
+    pub fn declare_planned_downtime(
+        &self,
+        rt: &MockRuntime,
+        declared: &[(u64, PowerPair)],
+    ) -> Result<Option<IpldBlock>, ActorError> {
+        rt.set_caller(*ACCOUNT_ACTOR_CODE_ID, self.worker);
+        rt.expect_validate_caller_addr(self.caller_addrs());
+
+        let expected_delta =
+            declared.iter().fold(PowerPair::zero(), |total, (_, power)| total + power.clone());
+        expect_update_power(rt, expected_delta.neg());
+
+        let state = self.get_state(rt);
+        let epoch = *rt.epoch.borrow();
+        let period_start = state.current_proving_period_start(&rt.policy, epoch);
+        for (dl_idx, power) in declared {
+            let open =
+                new_deadline_info(&rt.policy, period_start, *dl_idx, epoch).next_not_elapsed().open;
+            rt.expect_emitted_event(
+                EventBuilder::new()
+                    .typ("planned-downtime")
+                    .field_indexed("deadline", dl_idx)
+                    .field("open-epoch", &open)
+                    .field("raw-power", &BigIntSer(&power.raw))
+                    .field("qa-power", &BigIntSer(&power.qa))
+                    .build()
+                    .unwrap(),
+            );
+        }
+
+        let params = DeclarePlannedDowntimeParams {
+            deadlines: declared.iter().map(|(dl_idx, _)| *dl_idx).collect(),
+        };
+        let ret = rt.call::<Actor>(
+            MinerMethod::DeclarePlannedDowntimeExported as u64,
+            IpldBlock::serialize_cbor(&params).unwrap(),
+        );
+        if ret.is_ok() {
+            rt.verify();
+        } else {
+            rt.reset();
+        }
+        ret
+    }
+
     pub fn declare_recoveries(
         &self,
         rt: &MockRuntime,
@@ -2253,8 +2478,8 @@ impl ActorHarness {
         recovery_sectors: BitField,
         expected_debt_repaid: TokenAmount,
     ) -> Result<Option<IpldBlock>, ActorError> {
-        rt.set_caller(*ACCOUNT_ACTOR_CODE_ID, self.worker);
-        rt.expect_validate_caller_addr(self.caller_addrs());
+        rt.set_caller(*ACCOUNT_ACTOR_CODE_ID, self.prover());
+        rt.expect_validate_caller_addr(self.proving_addrs());
 
         if expected_debt_repaid.is_positive() {
             rt.expect_send_simple(
@@ -2364,10 +2589,32 @@ impl ActorHarness {
         verify_exit_code: ExitCode,
     ) -> Result<(), ActorError> {
         rt.expect_validate_caller_any();
+        self.penalize_consensus_fault(rt, from, fault, verify_exit_code, false)
+    }
+
+    pub fn self_report_consensus_fault(
+        &self,
+        rt: &MockRuntime,
+        from: Address,
+        fault: Option<ConsensusFault>,
+    ) -> Result<(), ActorError> {
+        rt.expect_validate_caller_addr(self.caller_addrs());
+        self.penalize_consensus_fault(rt, from, fault, ExitCode::OK, true)
+    }
+
+    fn penalize_consensus_fault(
+        &self,
+        rt: &MockRuntime,
+        from: Address,
+        fault: Option<ConsensusFault>,
+        verify_exit_code: ExitCode,
+        self_report: bool,
+    ) -> Result<(), ActorError> {
         rt.set_caller(*ACCOUNT_ACTOR_CODE_ID, from);
         let params =
             ReportConsensusFaultParams { header1: vec![], header2: vec![], header_extra: vec![] };
 
+        let fault_epoch = fault.as_ref().map(|f| f.epoch);
         rt.expect_verify_consensus_fault(
             params.header1.clone(),
             params.header2.clone(),
@@ -2389,9 +2636,20 @@ impl ActorHarness {
             ExitCode::OK,
         );
         let this_epoch_reward = TokenAmount::from_atto(self.epoch_reward_smooth.estimate());
-        let penalty_total = consensus_fault_penalty(this_epoch_reward.clone());
-        let reward_total = reward_for_consensus_slash_report(&this_epoch_reward);
-        rt.expect_send_simple(from, METHOD_SEND, None, reward_total.clone(), None, ExitCode::OK);
+        let (penalty_total, reward_total) = if self_report {
+            (consensus_fault_self_report_penalty(this_epoch_reward), TokenAmount::zero())
+        } else {
+            let reward_total = reward_for_consensus_slash_report(&this_epoch_reward);
+            rt.expect_send_simple(
+                from,
+                METHOD_SEND,
+                None,
+                reward_total.clone(),
+                None,
+                ExitCode::OK,
+            );
+            (consensus_fault_penalty(this_epoch_reward), reward_total)
+        };
 
         // pay fault fee
         let to_burn = &penalty_total - &reward_total;
@@ -2403,16 +2661,68 @@ impl ActorHarness {
             None,
             ExitCode::OK,
         );
+        if fault_epoch.is_some() && rt.network_version >= UPGRADE_NETWORK_VERSION {
+            expect_consensus_fault_exclusion(rt, true);
+            let elapsed = *rt.epoch.borrow() + rt.policy.consensus_fault_ineligibility_duration;
+            let payload = CronEventPayload { event_type: CRON_EVENT_CONSENSUS_FAULT_ELAPSED };
+            rt.expect_send_simple(
+                STORAGE_POWER_ACTOR_ADDR,
+                PowerMethod::EnrollCronEvent as u64,
+                IpldBlock::serialize_cbor(&EnrollCronEventParams {
+                    event_epoch: elapsed + 1,
+                    payload: RawBytes::serialize(payload).unwrap(),
+                })
+                .unwrap(),
+                TokenAmount::zero(),
+                None,
+                ExitCode::OK,
+            );
+        }
+        if let Some(fault_epoch) = fault_epoch {
+            rt.expect_emitted_event(
+                EventBuilder::new()
+                    .typ("consensus-fault")
+                    .field("fault-epoch", &fault_epoch)
+                    .field_indexed("self-reported", &self_report)
+                    .field("penalty", &penalty_total)
+                    .build()
+                    .unwrap(),
+            );
+        }
 
-        let result = rt.call::<Actor>(
-            Method::ReportConsensusFault as u64,
-            IpldBlock::serialize_cbor(&params).unwrap(),
-        )?;
+        let method = if self_report {
+            Method::SelfReportConsensusFaultExported
+        } else {
+            Method::ReportConsensusFault
+        };
+        let result =
+            rt.call::<Actor>(method as u64, IpldBlock::serialize_cbor(&params).unwrap())?;
         expect_empty(result);
         rt.verify();
         Ok(())
     }
 
+    // Invokes the cron event scheduled for the end of the miner's consensus fault ineligibility.
+    pub fn on_consensus_fault_elapsed(&self, rt: &MockRuntime, expect_restored: bool) {
+        rt.expect_validate_caller_addr(vec![STORAGE_POWER_ACTOR_ADDR]);
+        if expect_restored {
+            expect_consensus_fault_exclusion(rt, false);
+        }
+        let payload = CronEventPayload { event_type: CRON_EVENT_CONSENSUS_FAULT_ELAPSED };
+        rt.set_caller(*POWER_ACTOR_CODE_ID, STORAGE_POWER_ACTOR_ADDR);
+        rt.call::<Actor>(
+            Method::OnDeferredCronEvent as u64,
+            IpldBlock::serialize_cbor(&DeferredCronEventParams {
+                event_payload: Vec::from(RawBytes::serialize(payload).unwrap().bytes()),
+                reward_smoothed: self.epoch_reward_smooth.clone(),
+                quality_adj_power_smoothed: self.epoch_qa_power_smooth.clone(),
+            })
+            .unwrap(),
+        )
+        .unwrap();
+        rt.verify();
+    }
+
     pub fn collect_deadline_expirations(
         &self,
         rt: &MockRuntime,
@@ -2602,6 +2912,13 @@ impl ActorHarness {
                 ExitCode::OK,
             );
         }
+        expect_balance_changed(
+            rt,
+            expected_repaid_from_vest,
+            &expected_repaid_from_vest.neg(),
+            &TokenAmount::zero(),
+            &total_repaid.neg(),
+        );
         let result = rt.call::<Actor>(Method::RepayDebt as u64, None)?;
         expect_empty(result);
         Ok(())
@@ -2640,6 +2957,15 @@ impl ActorHarness {
                 ExitCode::OK,
             );
         }
+        let newly_vested =
+            self.get_state(rt).check_vested_funds(&rt.store, *rt.epoch.borrow()).unwrap();
+        expect_balance_changed(
+            rt,
+            &(&newly_vested - expected_withdrawn),
+            &newly_vested.neg(),
+            &TokenAmount::zero(),
+            &expected_debt_repaid.neg(),
+        );
         let ret = rt
             .call::<Actor>(
                 Method::WithdrawBalance as u64,
@@ -3009,6 +3335,19 @@ impl ActorHarness {
         rt.verify();
         Ok(available_balance_ret.available_balance)
     }
+
+    pub fn get_pledge_obligation(&self, rt: &MockRuntime) -> TokenAmount {
+        rt.set_caller(*POWER_ACTOR_CODE_ID, STORAGE_POWER_ACTOR_ADDR);
+        rt.expect_validate_caller_any();
+        let ret: GetPledgeObligationReturn = rt
+            .call::<Actor>(Method::GetPledgeObligation as u64, None)
+            .unwrap()
+            .unwrap()
+            .deserialize()
+            .unwrap();
+        rt.verify();
+        ret.pledge_obligation
+    }
 }
 
 pub fn expect_sector_event(
@@ -3030,6 +3369,33 @@ pub fn expect_sector_event(
     rt.expect_emitted_event(base_event.build().unwrap());
 }
 
+/// Expects a balance-changed event, unless every delta is zero.
+pub fn expect_balance_changed(
+    rt: &MockRuntime,
+    available_balance_delta: &TokenAmount,
+    locked_funds_delta: &TokenAmount,
+    pre_commit_deposits_delta: &TokenAmount,
+    fee_debt_delta: &TokenAmount,
+) {
+    if available_balance_delta.is_zero()
+        && locked_funds_delta.is_zero()
+        && pre_commit_deposits_delta.is_zero()
+        && fee_debt_delta.is_zero()
+    {
+        return;
+    }
+    rt.expect_emitted_event(
+        EventBuilder::new()
+            .typ("balance-changed")
+            .field("available-balance-delta", available_balance_delta)
+            .field("locked-funds-delta", locked_funds_delta)
+            .field("pre-commit-deposits-delta", pre_commit_deposits_delta)
+            .field("fee-debt-delta", fee_debt_delta)
+            .build()
+            .unwrap(),
+    );
+}
+
 pub fn expect_event(rt: &MockRuntime, typ: &str, sector: &SectorNumber) {
     rt.expect_emitted_event(
         EventBuilder::new().typ(typ).field_indexed("sector", sector).build().unwrap(),
@@ -3184,6 +3550,7 @@ pub struct CronConfig {
     pub repaid_fee_debt: TokenAmount,
     // Expected amount burnt to repay fee debt.
     pub penalty_from_unlocked: TokenAmount, // Expected reduction in unlocked balance from penalties exceeding vesting funds.
+    pub activated_sealed_ahead: Vec<SectorNumber>, // Sealed-ahead sectors expected to be activated.
 }
 
 #[allow(dead_code)]
@@ -3199,6 +3566,7 @@ impl CronConfig {
             expired_precommit_penalty: TokenAmount::zero(),
             repaid_fee_debt: TokenAmount::zero(),
             penalty_from_unlocked: TokenAmount::zero(),
+            activated_sealed_ahead: vec![],
         }
     }
 
@@ -3469,7 +3837,7 @@ pub fn make_activation_manifest(
             make_piece_manifest(sector_number, i, *sz, *client, *alloc, *deal)
         })
         .collect();
-    SectorActivationManifest { sector_number, pieces }
+    SectorActivationManifest { sector_number, pieces, client_manifests: vec![] }
 }
 
 #[allow(dead_code)]
@@ -3519,6 +3887,30 @@ pub fn make_piece_manifest(
     }
 }
 
+/// Builds a client manifest authorizing the pieces of a sector activation, with a placeholder
+/// signature that the market is expected to accept.
+#[allow(dead_code)]
+pub fn make_client_manifest(
+    client: Address,
+    provider: Address,
+    activation: &SectorActivationManifest,
+    expiration: ChainEpoch,
+) -> ext::market::ClientPieceManifest {
+    ext::market::ClientPieceManifest {
+        manifest: ext::market::PieceManifest {
+            client,
+            provider,
+            pieces: activation
+                .pieces
+                .iter()
+                .map(|piece| PieceInfo { cid: piece.cid, size: piece.size })
+                .collect(),
+            expiration,
+        },
+        client_signature: Signature::new_bls(b"does not matter".to_vec()),
+    }
+}
+
 pub fn make_piece_specs_from_configs(
     sector_number: u64,
     deal_ids: &Vec<DealID>,
@@ -3588,6 +3980,17 @@ fn make_sector_commr(sector: SectorNumber) -> Cid {
     make_sealed_cid(format!("sealed-{}", sector).as_bytes())
 }
 
+fn expect_consensus_fault_exclusion(rt: &MockRuntime, excluded: bool) {
+    rt.expect_send_simple(
+        STORAGE_POWER_ACTOR_ADDR,
+        PowerMethod::SetConsensusFaultExclusion as u64,
+        IpldBlock::serialize_cbor(&SetConsensusFaultExclusionParams { excluded }).unwrap(),
+        TokenAmount::zero(),
+        None,
+        ExitCode::OK,
+    );
+}
+
 pub fn make_deadline_cron_event_params(epoch: ChainEpoch) -> EnrollCronEventParams {
     let payload = CronEventPayload { event_type: CRON_EVENT_PROVING_DEADLINE };
     EnrollCronEventParams { event_epoch: epoch, payload: RawBytes::serialize(payload).unwrap() }
@@ -11,6 +11,7 @@ use fvm_shared::clock::ChainEpoch;
 use fvm_shared::econ::TokenAmount;
 use fvm_shared::error::ExitCode;
 use fvm_shared::METHOD_SEND;
+use std::ops::Neg;
 
 mod util;
 
@@ -68,6 +69,13 @@ fn withdraw_funds_restricted_correctly() {
         None,
         ExitCode::OK,
     );
+    expect_balance_changed(
+        &rt,
+        &amount_requested.clone().neg(),
+        &TokenAmount::zero(),
+        &TokenAmount::zero(),
+        &TokenAmount::zero(),
+    );
 
     let ret = rt
         .call::<Actor>(Method::WithdrawBalanceExported as u64, params)
@@ -0,0 +1,63 @@
+// A namespace for helpers that build and emit multisig events.
+//
+// A transaction emits an event at each stage of its lifecycle, each indexed by the transaction ID
+// and the signer responsible:
+// - "transaction-proposed" when a signer proposes a transaction (which counts as their approval);
+// - "transaction-approved" when another signer approves a pending transaction;
+// - "transaction-cancelled" when the proposer cancels a pending transaction;
+// - "transaction-executed" when a transaction meets the approval threshold and is sent,
+//   whether or not the invoked method succeeds.
+// These event types are part of the actor's public interface and must not be renamed.
+
+use fil_actors_runtime::runtime::Runtime;
+use fil_actors_runtime::{ActorError, EventBuilder};
+use fvm_shared::address::Address;
+
+use crate::TxnID;
+
+/// Indicates a transaction has been proposed.
+pub fn transaction_proposed(
+    rt: &impl Runtime,
+    id: TxnID,
+    signer: &Address,
+) -> Result<(), ActorError> {
+    rt.emit_event(&EventBuilder::new().typ("transaction-proposed").with_txn(id, signer).build()?)
+}
+
+/// Indicates a pending transaction has been approved by a signer.
+pub fn transaction_approved(
+    rt: &impl Runtime,
+    id: TxnID,
+    signer: &Address,
+) -> Result<(), ActorError> {
+    rt.emit_event(&EventBuilder::new().typ("transaction-approved").with_txn(id, signer).build()?)
+}
+
+/// Indicates a pending transaction has been cancelled by its proposer.
+pub fn transaction_cancelled(
+    rt: &impl Runtime,
+    id: TxnID,
+    signer: &Address,
+) -> Result<(), ActorError> {
+    rt.emit_event(&EventBuilder::new().typ("transaction-cancelled").with_txn(id, signer).build()?)
+}
+
+/// Indicates a transaction has been executed.
+/// The signer is the one whose proposal or approval caused the execution.
+pub fn transaction_executed(
+    rt: &impl Runtime,
+    id: TxnID,
+    signer: &Address,
+) -> Result<(), ActorError> {
+    rt.emit_event(&EventBuilder::new().typ("transaction-executed").with_txn(id, signer).build()?)
+}
+
+trait WithTxn {
+    fn with_txn(self, id: TxnID, signer: &Address) -> EventBuilder;
+}
+
+impl WithTxn for EventBuilder {
+    fn with_txn(self, id: TxnID, signer: &Address) -> EventBuilder {
+        self.field_indexed("id", &id).field_indexed("signer", signer)
+    }
+}
@@ -10,6 +10,7 @@ use fvm_ipld_encoding::RawBytes;
 use fvm_shared::address::Address;
 use fvm_shared::econ::TokenAmount;
 use fvm_shared::error::ExitCode;
+use fvm_shared::sys::SendFlags;
 use fvm_shared::MethodNum;
 use fvm_shared::METHOD_CONSTRUCTOR;
 use num_derive::FromPrimitive;
@@ -19,17 +20,27 @@ use fil_actors_runtime::cbor::serialize_vec;
 use fil_actors_runtime::runtime::{ActorCode, Primitives, Runtime};
 use fil_actors_runtime::FIRST_EXPORTED_METHOD_NUMBER;
 use fil_actors_runtime::{
-    actor_dispatch, actor_error, extract_send_result, resolve_to_actor_id, ActorContext,
-    ActorError, AsActorError, INIT_ACTOR_ADDR,
+    actor_dispatch, actor_error, deserialize_block, extract_send_result, forward_call,
+    migrate_state, resolve_to_actor_id, ActorContext, ActorError, AsActorError, ForwardCallParams,
+    ForwardCallReturn, OnUpgradeParams, INIT_ACTOR_ADDR,
 };
 
 pub use self::state::*;
 pub use self::types::*;
+use crate::migration::LegacyState;
 
 #[cfg(feature = "fil-actor")]
 fil_actors_runtime::wasm_trampoline!(Actor);
 
+mod emit;
+#[cfg(feature = "testing")]
+pub mod ext;
+#[cfg(not(feature = "testing"))]
+#[allow(dead_code)]
+mod ext;
+pub mod migration;
 mod state;
+#[cfg(feature = "testing")]
 pub mod testing;
 mod types;
 
@@ -46,8 +57,15 @@ pub enum Method {
     SwapSigner = 7,
     ChangeNumApprovalsThreshold = 8,
     LockBalance = 9,
+    SetSignerWeight = 10,
+    SetSignerRole = 11,
     // Method numbers derived from FRC-0042 standards
     UniversalReceiverHook = frc42_dispatch::method_hash!("Receive"),
+    ProposeWithApprovalsExported = frc42_dispatch::method_hash!("ProposeWithApprovals"),
+    PruneExpiredExported = frc42_dispatch::method_hash!("PruneExpired"),
+    GetTransactionExported = frc42_dispatch::method_hash!("GetTransaction"),
+    ListPendingTransactionsExported = frc42_dispatch::method_hash!("ListPendingTransactions"),
+    ForwardCallExported = frc42_dispatch::method_hash!("ForwardCall"),
 }
 
 /// Multisig Actor
@@ -83,18 +101,23 @@ impl Actor {
             resolved_signers.push(Address::new_id(resolved));
         }
 
-        if params.num_approvals_threshold > params.signers.len() as u64 {
-            return Err(
-                actor_error!(illegal_argument; "must not require more approvals than signers"),
-            );
-        }
-
-        if params.num_approvals_threshold < 1 {
-            return Err(actor_error!(illegal_argument; "must require at least one approval"));
-        }
-
-        if params.unlock_duration < 0 {
-            return Err(actor_error!(illegal_argument; "negative unlock duration disallowed"));
+        let mut signer_weights = Vec::with_capacity(params.signer_weights.len());
+        let mut dedup_weighted = BTreeSet::new();
+        for sw in &params.signer_weights {
+            let resolved = resolve_to_actor_id(rt, &sw.signer, false)?;
+            if !dedup_signers.contains(&resolved) {
+                return Err(actor_error!(illegal_argument; "{} is not a signer", sw.signer));
+            }
+            if !dedup_weighted.insert(resolved) {
+                return Err(
+                    actor_error!(illegal_argument; "duplicate signer weight not allowed: {}", sw.signer),
+                );
+            }
+            validate_signer_weight(sw.weight)?;
+            if sw.weight != DEFAULT_SIGNER_WEIGHT {
+                signer_weights
+                    .push(SignerWeight { signer: Address::new_id(resolved), weight: sw.weight });
+            }
         }
 
         let empty_root = PendingTxnMap::empty(rt.store(), PENDING_TXN_CONFIG, "empty").flush()?;
@@ -107,8 +130,24 @@ impl Actor {
             next_tx_id: Default::default(),
             start_epoch: Default::default(),
             unlock_duration: Default::default(),
+            signer_weights,
+            signer_roles: Vec::new(),
         };
 
+        if params.num_approvals_threshold > st.total_signer_weight() {
+            return Err(
+                actor_error!(illegal_argument; "must not require more approvals than signers"),
+            );
+        }
+
+        if params.num_approvals_threshold < 1 {
+            return Err(actor_error!(illegal_argument; "must require at least one approval"));
+        }
+
+        if params.unlock_duration < 0 {
+            return Err(actor_error!(illegal_argument; "negative unlock duration disallowed"));
+        }
+
         if params.unlock_duration != 0 {
             st.set_locked(
                 params.start_epoch,
@@ -134,10 +173,23 @@ impl Actor {
             ));
         }
 
+        if let Some(expiration) = params.expiration {
+            if expiration < rt.curr_epoch() {
+                return Err(actor_error!(
+                    illegal_argument,
+                    "expiration {} must not be before current epoch {}",
+                    expiration,
+                    rt.curr_epoch()
+                ));
+            }
+        }
+
+        let target = resolve_call_target(rt, &params.to);
         let (txn_id, txn) = rt.transaction(|st: &mut State, rt| {
             if !st.is_signer(&proposer) {
                 return Err(actor_error!(forbidden, "{} is not a signer", proposer));
             }
+            check_signer_may_call(st, &proposer, &target, params.method)?;
 
             let mut ptx = PendingTxnMap::load(
                 rt.store(),
@@ -154,6 +206,7 @@ impl Actor {
                 method: params.method,
                 params: params.params,
                 approved: Vec::new(),
+                expiration: params.expiration,
             };
 
             ptx.set(&t_id, txn.clone())?;
@@ -165,6 +218,90 @@ impl Actor {
         Ok(ProposeReturn { txn_id, applied, code, ret })
     }
 
+    /// Proposes a transaction together with approvals by other signers collected off-chain.
+    /// Each approval is a signature over the proposal's approval signing data, which is
+    /// authenticated by the signer's account actor.
+    /// The proposal is executed immediately if the approvals, including the proposer's own,
+    /// meet the threshold. Otherwise it remains pending with those approvals recorded.
+    pub fn propose_with_approvals(
+        rt: &impl Runtime,
+        params: ProposeWithApprovalsParams,
+    ) -> Result<ProposeReturn, ActorError> {
+        rt.validate_immediate_caller_accept_any()?;
+        let proposer: Address = rt.message().caller();
+
+        if params.value.is_negative() {
+            return Err(actor_error!(
+                illegal_argument,
+                "proposed value must be non-negative, was {}",
+                params.value
+            ));
+        }
+
+        let st: State = rt.state()?;
+        if !st.is_signer(&proposer) {
+            return Err(actor_error!(forbidden, "{} is not a signer", proposer));
+        }
+        let target = resolve_call_target(rt, &params.to);
+        check_signer_may_call(&st, &proposer, &target, params.method)?;
+
+        let txn_id = st.next_tx_id;
+        let mut txn = Transaction {
+            to: params.to,
+            value: params.value,
+            method: params.method,
+            params: params.params,
+            approved: vec![proposer],
+            expiration: None,
+        };
+        let message = compute_approval_signing_bytes(&rt.message().receiver(), txn_id, &txn)?;
+        for approval in &params.approvals {
+            let signer = Address::new_id(resolve_to_actor_id(rt, &approval.signer, false)?);
+            if !st.is_signer(&signer) {
+                return Err(actor_error!(forbidden, "{} is not a signer", approval.signer));
+            }
+            check_signer_may_call(&st, &signer, &target, txn.method)?;
+            if txn.approved.contains(&signer) {
+                return Err(actor_error!(
+                    forbidden,
+                    "{} already approved this message",
+                    approval.signer
+                ));
+            }
+            authenticate_approval(rt, &signer, &approval.signature, &message)?;
+            txn.approved.push(signer);
+        }
+
+        let st = rt.transaction(|st: &mut State, rt| {
+            // Authentication can't modify state, so the proposal receives the ID that was signed.
+            if st.next_tx_id != txn_id {
+                return Err(actor_error!(illegal_state, "transaction ID changed during approval"));
+            }
+            let mut ptx = PendingTxnMap::load(
+                rt.store(),
+                &st.pending_txs,
+                PENDING_TXN_CONFIG,
+                "pending txns",
+            )?;
+            st.next_tx_id.0 += 1;
+
+            ptx.set(&txn_id, txn.clone())?;
+            st.pending_txs = ptx.flush()?;
+
+            // Go implementation holds reference to state after transaction so this must be cloned
+            // to match to handle possible exit code inconsistency
+            Ok(st.clone())
+        })?;
+
+        emit::transaction_proposed(rt, txn_id, &proposer)?;
+        for signer in &txn.approved[1..] {
+            emit::transaction_approved(rt, txn_id, signer)?;
+        }
+
+        let (applied, ret, code) = execute_transaction_if_approved(rt, &st, txn_id, &txn)?;
+        Ok(ProposeReturn { txn_id, applied, code, ret })
+    }
+
     /// Multisig actor approve function
     pub fn approve(rt: &impl Runtime, params: TxnIDParams) -> Result<ApproveReturn, ActorError> {
         rt.validate_immediate_caller_accept_any()?;
@@ -183,6 +320,10 @@ impl Actor {
             )?;
 
             let txn = get_transaction(rt, &ptx, params.id, params.proposal_hash)?;
+            if txn.is_expired(rt.curr_epoch()) {
+                return Err(actor_error!(forbidden, "transaction {} has expired", params.id));
+            }
+            check_signer_may_call(st, &approver, &resolve_call_target(rt, &txn.to), txn.method)?;
 
             // Go implementation holds reference to state after transaction so state must be cloned
             // to match to handle possible exit code inconsistency
@@ -237,9 +378,86 @@ impl Actor {
 
             st.pending_txs = ptx.flush()?;
             Ok(())
+        })?;
+
+        emit::transaction_cancelled(rt, params.id, &caller_addr)
+    }
+
+    /// Removes pending transactions that have expired.
+    /// Anyone may call this method. Transactions that don't exist or haven't expired are ignored.
+    pub fn prune_expired(
+        rt: &impl Runtime,
+        params: PruneExpiredParams,
+    ) -> Result<PruneExpiredReturn, ActorError> {
+        rt.validate_immediate_caller_accept_any()?;
+        let curr_epoch = rt.curr_epoch();
+
+        rt.transaction(|st: &mut State, rt| {
+            let mut ptx = PendingTxnMap::load(
+                rt.store(),
+                &st.pending_txs,
+                PENDING_TXN_CONFIG,
+                "pending txns",
+            )?;
+
+            let mut pruned = Vec::new();
+            for id in params.ids {
+                let expired = ptx.get(&id)?.map_or(false, |txn| txn.is_expired(curr_epoch));
+                if expired {
+                    ptx.delete(&id)?;
+                    pruned.push(id);
+                }
+            }
+
+            st.pending_txs = ptx.flush()?;
+            Ok(PruneExpiredReturn { pruned })
         })
     }
 
+    /// Returns a pending transaction, including its current approvals.
+    pub fn get_transaction(
+        rt: &impl Runtime,
+        params: GetTransactionParams,
+    ) -> Result<GetTransactionReturn, ActorError> {
+        rt.validate_immediate_caller_accept_any()?;
+        let st: State = rt.state()?;
+        let ptx =
+            PendingTxnMap::load(rt.store(), &st.pending_txs, PENDING_TXN_CONFIG, "pending txns")?;
+        let transaction = ptx
+            .get(&params.id)?
+            .ok_or_else(|| actor_error!(not_found, "no such transaction {}", params.id))?
+            .clone();
+        Ok(GetTransactionReturn { transaction })
+    }
+
+    /// Lists pending transactions, a page at a time.
+    /// At most `limit` transactions, and no more than `LIST_PENDING_TRANSACTIONS_MAX`, are
+    /// returned per call. The returned cursor should be passed to a subsequent call to resume
+    /// the listing, and is absent once it is complete. The listing resumes correctly even if
+    /// the transaction at the cursor has since been removed.
+    /// Transactions are not listed in order of ID.
+    pub fn list_pending_transactions(
+        rt: &impl Runtime,
+        params: ListPendingTransactionsParams,
+    ) -> Result<ListPendingTransactionsReturn, ActorError> {
+        rt.validate_immediate_caller_accept_any()?;
+        if params.limit == 0 {
+            return Err(actor_error!(illegal_argument, "limit must be positive"));
+        }
+        let limit = usize::try_from(params.limit.min(LIST_PENDING_TRANSACTIONS_MAX))
+            .context_code(ExitCode::USR_ILLEGAL_ARGUMENT, "invalid limit")?;
+        let st: State = rt.state()?;
+        let mut ptx =
+            PendingTxnMap::load(rt.store(), &st.pending_txs, PENDING_TXN_CONFIG, "pending txns")?;
+        let mut transactions = Vec::new();
+        let (_, next_cursor) =
+            ptx.for_each_ranged_resuming(params.cursor.as_ref(), Some(limit), |id, txn| {
+                transactions.push((id, txn.clone()));
+                Ok(())
+            })?;
+        Ok(ListPendingTransactionsReturn { transactions, next_cursor })
+    }
+
     /// Multisig actor function to add signers to multisig
     pub fn add_signer(rt: &impl Runtime, params: AddSignerParams) -> Result<(), ActorError> {
         let receiver = rt.message().receiver();
@@ -283,7 +501,9 @@ impl Actor {
                 return Err(actor_error!(forbidden; "Cannot remove only signer"));
             }
 
-            if !params.decrease && ((st.signers.len() - 1) as u64) < st.num_approvals_threshold {
+            let old_signer_weight = st.signer_weight(&Address::new_id(resolved_old_signer));
+            let remaining_weight = st.total_signer_weight() - old_signer_weight;
+            if !params.decrease && remaining_weight < st.num_approvals_threshold {
                 return Err(actor_error!(
                     illegal_argument,
                     "can't reduce signers to {} below threshold {} with decrease=false",
@@ -292,22 +512,33 @@ impl Actor {
                 ));
             }
 
+            // Decreasing the threshold removes the removed signer's weight from it.
             if params.decrease {
-                if st.num_approvals_threshold < 2 {
+                if st.num_approvals_threshold <= old_signer_weight {
                     return Err(actor_error!(
                         illegal_argument,
-                        "can't decrease approvals from {} to {}",
+                        "can't decrease approvals from {} by {}",
                         st.num_approvals_threshold,
-                        st.num_approvals_threshold - 1
+                        old_signer_weight
+                    ));
+                }
+                st.num_approvals_threshold -= old_signer_weight;
+                if remaining_weight < st.num_approvals_threshold {
+                    return Err(actor_error!(
+                        illegal_argument,
+                        "can't reduce signer weight to {} below threshold {}",
+                        remaining_weight,
+                        st.num_approvals_threshold
                     ));
                 }
-                st.num_approvals_threshold -= 1;
             }
 
             // Remove approvals from removed signer
             st.purge_approvals(rt.store(), &Address::new_id(resolved_old_signer))
                 .context("failed to purge approvals of removed signer")?;
             st.signers.retain(|s| s != &Address::new_id(resolved_old_signer));
+            st.set_signer_weight(&Address::new_id(resolved_old_signer), DEFAULT_SIGNER_WEIGHT);
+            st.set_signer_role(&Address::new_id(resolved_old_signer), Vec::new());
 
             Ok(())
         })?;
@@ -334,8 +565,14 @@ impl Actor {
             // Remove signer from state (retain preserves order of elements)
             st.signers.retain(|s| s != &Address::new_id(from_resolved));
 
-            // Add new signer
+            // Add new signer, which takes over the weight and role of the one it replaces
             st.signers.push(Address::new_id(to_resolved));
+            let weight = st.signer_weight(&Address::new_id(from_resolved));
+            st.set_signer_weight(&Address::new_id(from_resolved), DEFAULT_SIGNER_WEIGHT);
+            st.set_signer_weight(&Address::new_id(to_resolved), weight);
+            let allowed = st.signer_allowed_calls(&Address::new_id(from_resolved)).to_vec();
+            st.set_signer_role(&Address::new_id(from_resolved), Vec::new());
+            st.set_signer_role(&Address::new_id(to_resolved), allowed);
 
             st.purge_approvals(rt.store(), &Address::new_id(from_resolved))?;
             Ok(())
@@ -354,7 +591,7 @@ impl Actor {
 
         rt.transaction(|st: &mut State, _| {
             // Check if valid threshold value
-            if params.new_threshold == 0 || params.new_threshold > st.signers.len() as u64 {
+            if params.new_threshold == 0 || params.new_threshold > st.total_signer_weight() {
                 return Err(actor_error!(illegal_argument; "New threshold value not supported"));
             }
 
@@ -366,6 +603,77 @@ impl Actor {
         Ok(())
     }
 
+    /// Multisig actor function to set the weight of a signer's approval
+    pub fn set_signer_weight(
+        rt: &impl Runtime,
+        params: SetSignerWeightParams,
+    ) -> Result<(), ActorError> {
+        let receiver = rt.message().receiver();
+        rt.validate_immediate_caller_is(std::iter::once(&receiver))?;
+        validate_signer_weight(params.weight)?;
+        let resolved_signer = resolve_to_actor_id(rt, &params.signer, false)?;
+
+        rt.transaction(|st: &mut State, _| {
+            if !st.is_signer(&Address::new_id(resolved_signer)) {
+                return Err(actor_error!(forbidden, "{} is not a signer", resolved_signer));
+            }
+
+            st.set_signer_weight(&Address::new_id(resolved_signer), params.weight);
+            if st.total_signer_weight() < st.num_approvals_threshold {
+                return Err(actor_error!(
+                    illegal_argument,
+                    "can't reduce signer weight to {} below threshold {}",
+                    st.total_signer_weight(),
+                    st.num_approvals_threshold
+                ));
+            }
+            Ok(())
+        })?;
+
+        Ok(())
+    }
+
+    /// Multisig actor function to restrict the calls a signer may propose or approve
+    pub fn set_signer_role(
+        rt: &impl Runtime,
+        params: SetSignerRoleParams,
+    ) -> Result<(), ActorError> {
+        let receiver = rt.message().receiver();
+        rt.validate_immediate_caller_is(std::iter::once(&receiver))?;
+        if params.allowed.len() > ALLOWED_CALLS_MAX {
+            return Err(actor_error!(
+                illegal_argument,
+                "cannot allow more than {} calls",
+                ALLOWED_CALLS_MAX
+            ));
+        }
+        let resolved_signer = resolve_to_actor_id(rt, &params.signer, false)?;
+
+        let mut allowed = Vec::with_capacity(params.allowed.len());
+        for call in &params.allowed {
+            let call = AllowedCall { to: resolve_call_target(rt, &call.to), method: call.method };
+            if allowed.contains(&call) {
+                return Err(actor_error!(
+                    illegal_argument,
+                    "duplicate allowed call to method {} of {}",
+                    call.method,
+                    call.to
+                ));
+            }
+            allowed.push(call);
+        }
+
+        rt.transaction(|st: &mut State, _| {
+            if !st.is_signer(&Address::new_id(resolved_signer)) {
+                return Err(actor_error!(forbidden, "{} is not a signer", resolved_signer));
+            }
+            st.set_signer_role(&Address::new_id(resolved_signer), allowed);
+            Ok(())
+        })?;
+
+        Ok(())
+    }
+
     /// Multisig actor function to change number of approvals needed
     pub fn lock_balance(rt: &impl Runtime, params: LockBalanceParams) -> Result<(), ActorError> {
         let receiver = rt.message().receiver();
@@ -390,6 +698,21 @@ impl Actor {
         Ok(())
     }
 
+    /// Forwards a call with parameters in any supported codec, which a transaction proposes by
+    /// calling this method on the multisig itself. The value sent is subject to the locked balance.
+    /// Signers restricted by role must be permitted this method to propose such a transaction.
+    pub fn forward_call(
+        rt: &impl Runtime,
+        params: ForwardCallParams,
+    ) -> Result<ForwardCallReturn, ActorError> {
+        let receiver = rt.message().receiver();
+        rt.validate_immediate_caller_is(std::iter::once(&receiver))?;
+
+        let st: State = rt.state()?;
+        st.check_available(rt.current_balance(), &params.value, rt.curr_epoch())?;
+        forward_call(rt, params)
+    }
+
     fn approve_transaction(
         rt: &impl Runtime,
         tx_id: TxnID,
@@ -424,6 +747,14 @@ impl Actor {
             Ok(st.clone())
         })?;
 
+        // The first approval of a transaction is its proposal.
+        let signer = rt.message().caller();
+        if txn.approved.len() == 1 {
+            emit::transaction_proposed(rt, tx_id, &signer)?;
+        } else {
+            emit::transaction_approved(rt, tx_id, &signer)?;
+        }
+
         execute_transaction_if_approved(rt, &st, tx_id, &txn)
     }
 
@@ -459,7 +790,7 @@ fn execute_transaction_if_approved(
     let mut out = RawBytes::default();
     let mut code = ExitCode::OK;
     let mut applied = false;
-    let threshold_met = txn.approved.len() as u64 >= st.num_approvals_threshold;
+    let threshold_met = st.approval_weight(&txn.approved) >= st.num_approvals_threshold;
     if threshold_met {
         st.check_available(rt.current_balance(), &txn.value, rt.curr_epoch())?;
 
@@ -494,11 +825,50 @@ fn execute_transaction_if_approved(
             st.pending_txs = ptx.flush()?;
             Ok(())
         })?;
+
+        emit::transaction_executed(rt, txn_id, &rt.message().caller())?;
     }
 
     Ok((applied, out, code))
 }
 
+/// Resolves the target of a call to an ID address where possible, so that it can be compared
+/// with the calls allowed to a signer.
+fn resolve_call_target(rt: &impl Runtime, to: &Address) -> Address {
+    rt.resolve_address(to).map_or(*to, Address::new_id)
+}
+
+/// Checks that a signer's role, if any, allows it to propose or approve a call.
+fn check_signer_may_call(
+    st: &State,
+    signer: &Address,
+    to: &Address,
+    method: MethodNum,
+) -> Result<(), ActorError> {
+    if !st.signer_may_call(signer, to, method) {
+        return Err(actor_error!(
+            forbidden,
+            "{} is not allowed to call method {} of {}",
+            signer,
+            method,
+            to
+        ));
+    }
+    Ok(())
+}
+
+fn validate_signer_weight(weight: u64) -> Result<(), ActorError> {
+    if weight == 0 || weight > SIGNER_WEIGHT_MAX {
+        return Err(actor_error!(
+            illegal_argument,
+            "signer weight {} must be between 1 and {}",
+            weight,
+            SIGNER_WEIGHT_MAX
+        ));
+    }
+    Ok(())
+}
+
 fn get_transaction<'m, BS, RT>(
     rt: &RT,
     ptx: &'m PendingTxnMap<BS>,
@@ -544,6 +914,54 @@ pub fn compute_proposal_hash(txn: &Transaction, sys: &dyn Primitives) -> anyhow:
     Ok(sys.hash_blake2b(&data))
 }
 
+/// Computes the data a signer signs to approve a proposal off-chain, for the proposal to
+/// receive an ID in a multisig.
+pub fn compute_approval_signing_bytes(
+    multisig: &Address,
+    txn_id: TxnID,
+    txn: &Transaction,
+) -> Result<Vec<u8>, ActorError> {
+    let data = ApprovalSigningData {
+        multisig,
+        txn_id,
+        to: &txn.to,
+        value: &txn.value,
+        method: &txn.method,
+        params: &txn.params,
+    };
+    let bytes = serialize_vec(&data, "approval signing data")?;
+    Ok([SIGNATURE_DOMAIN_SEPARATION_APPROVAL, &bytes].concat())
+}
+
+fn authenticate_approval(
+    rt: &impl Runtime,
+    signer: &Address,
+    signature: &[u8],
+    message: &[u8],
+) -> Result<(), ActorError> {
+    if !extract_send_result(rt.send(
+        signer,
+        ext::account::AUTHENTICATE_MESSAGE_METHOD,
+        IpldBlock::serialize_cbor(&ext::account::AuthenticateMessageParams {
+            signature: signature.to_vec(),
+            message: message.to_vec(),
+        })?,
+        TokenAmount::zero(),
+        None,
+        SendFlags::READ_ONLY,
+    ))
+    .and_then(deserialize_block)
+    .with_context(|| format!("approval authentication failed for {}", signer))?
+    {
+        return Err(actor_error!(
+            illegal_argument,
+            "approval signature authentication failed for {}",
+            signer
+        ));
+    }
+    Ok(())
+}
+
 impl ActorCode for Actor {
     type Methods = Method;
 
@@ -551,6 +969,14 @@ impl ActorCode for Actor {
         "Multisig"
     }
 
+    fn on_upgrade<RT>(rt: &RT, params: OnUpgradeParams) -> Result<(), ActorError>
+    where
+        RT: Runtime,
+        RT::Blockstore: Clone,
+    {
+        migrate_state(rt, &params.old_state, |rt, legacy: LegacyState| legacy.migrate(rt.store()))
+    }
+
     actor_dispatch! {
       Constructor => constructor,
       Propose => propose,
@@ -561,7 +987,14 @@ impl ActorCode for Actor {
       SwapSigner => swap_signer,
       ChangeNumApprovalsThreshold => change_num_approvals_threshold,
       LockBalance => lock_balance,
+      SetSignerWeight => set_signer_weight,
+      SetSignerRole => set_signer_role,
       UniversalReceiverHook => universal_receiver_hook,
+      ProposeWithApprovalsExported => propose_with_approvals,
+      PruneExpiredExported => prune_expired,
+      GetTransactionExported => get_transaction,
+      ListPendingTransactionsExported => list_pending_transactions,
+      ForwardCallExported => forward_call,
       _ => fallback,
     }
 }
@@ -0,0 +1,80 @@
+// Copyright 2019-2022 ChainSafe Systems
+// SPDX-License-Identifier: Apache-2.0, MIT
+
+use cid::Cid;
+use fvm_ipld_blockstore::Blockstore;
+use fvm_ipld_encoding::tuple::*;
+use fvm_ipld_encoding::RawBytes;
+use fvm_shared::address::Address;
+use fvm_shared::clock::ChainEpoch;
+use fvm_shared::econ::TokenAmount;
+use fvm_shared::MethodNum;
+
+use fil_actors_runtime::{ActorError, Map2};
+
+use crate::{PendingTxnMap, State, Transaction, TxnID, PENDING_TXN_CONFIG};
+
+/// Multisig state as stored before signers were weighted.
+#[derive(Serialize_tuple, Deserialize_tuple, Debug, Clone)]
+pub struct LegacyState {
+    pub signers: Vec<Address>,
+    pub num_approvals_threshold: u64,
+    pub next_tx_id: TxnID,
+    pub initial_balance: TokenAmount,
+    pub start_epoch: ChainEpoch,
+    pub unlock_duration: ChainEpoch,
+    pub pending_txs: Cid,
+}
+
+/// Pending transaction as stored before transactions could expire.
+#[derive(Serialize_tuple, Deserialize_tuple, Clone, PartialEq, Eq, Debug)]
+pub struct LegacyTransaction {
+    pub to: Address,
+    pub value: TokenAmount,
+    pub method: MethodNum,
+    pub params: RawBytes,
+    pub approved: Vec<Address>,
+}
+
+impl From<LegacyTransaction> for Transaction {
+    /// Migrates to the current schema, with no expiration.
+    fn from(legacy: LegacyTransaction) -> Self {
+        Transaction {
+            to: legacy.to,
+            value: legacy.value,
+            method: legacy.method,
+            params: legacy.params,
+            approved: legacy.approved,
+            expiration: None,
+        }
+    }
+}
+
+impl LegacyState {
+    /// Migrates to the current state schema, with every signer at the default weight.
+    /// Pending transactions are rewritten in the current schema too.
+    pub fn migrate<BS: Blockstore>(self, store: &BS) -> Result<State, ActorError> {
+        let legacy_txs = Map2::<&BS, TxnID, LegacyTransaction>::load(
+            store,
+            &self.pending_txs,
+            PENDING_TXN_CONFIG,
+            "legacy pending txns",
+        )?;
+        let mut pending_txs = PendingTxnMap::empty(store, PENDING_TXN_CONFIG, "pending txns");
+        legacy_txs.for_each(|id, txn| {
+            pending_txs.set(&id, Transaction::from(txn.clone()))?;
+            Ok(())
+        })?;
+        Ok(State {
+            signers: self.signers,
+            num_approvals_threshold: self.num_approvals_threshold,
+            next_tx_id: self.next_tx_id,
+            initial_balance: self.initial_balance,
+            start_epoch: self.start_epoch,
+            unlock_duration: self.unlock_duration,
+            pending_txs: pending_txs.flush()?,
+            signer_weights: vec![],
+            signer_roles: vec![],
+        })
+    }
+}
@@ -9,21 +9,23 @@ use fvm_shared::bigint::BigInt;
 use fvm_shared::bigint::Integer;
 use fvm_shared::clock::ChainEpoch;
 use fvm_shared::econ::TokenAmount;
+use fvm_shared::MethodNum;
 use indexmap::IndexMap;
 use num_traits::Zero;
 
-use fil_actors_runtime::{actor_error, ActorError, Config, Map2, DEFAULT_HAMT_CONFIG};
+use fil_actors_runtime::{actor_error, ActorError, ActorState, Config, Map2, DEFAULT_HAMT_CONFIG};
 
-use super::types::Transaction;
+use super::types::{AllowedCall, SignerRole, SignerWeight, Transaction, DEFAULT_SIGNER_WEIGHT};
 use super::TxnID;
 
 pub type PendingTxnMap<BS> = Map2<BS, TxnID, Transaction>;
 pub const PENDING_TXN_CONFIG: Config = DEFAULT_HAMT_CONFIG;
 
 /// Multisig actor state
-#[derive(Serialize_tuple, Deserialize_tuple, Clone, Debug)]
+#[derive(Serialize_tuple, Deserialize_tuple, Clone, Debug, ActorState)]
 pub struct State {
     pub signers: Vec<Address>,
+    /// Total weight of approvals required to execute a transaction.
     pub num_approvals_threshold: u64,
     pub next_tx_id: TxnID,
 
@@ -33,6 +35,11 @@ pub struct State {
     pub unlock_duration: ChainEpoch,
 
     pub pending_txs: Cid,
+
+    /// Weights of signers that differ from DEFAULT_SIGNER_WEIGHT.
+    pub signer_weights: Vec<SignerWeight>,
+    /// Roles of signers restricted to an allowlist of calls.
+    pub signer_roles: Vec<SignerRole>,
 }
 
 impl State {
@@ -41,6 +48,58 @@ impl State {
         self.signers.contains(address)
     }
 
+    /// Returns the weight of a signer's approval.
+    pub fn signer_weight(&self, signer: &Address) -> u64 {
+        self.signer_weights
+            .iter()
+            .find(|w| &w.signer == signer)
+            .map_or(DEFAULT_SIGNER_WEIGHT, |w| w.weight)
+    }
+
+    /// Sets the weight of a signer's approval.
+    pub fn set_signer_weight(&mut self, signer: &Address, weight: u64) {
+        self.signer_weights.retain(|w| &w.signer != signer);
+        if weight != DEFAULT_SIGNER_WEIGHT {
+            self.signer_weights.push(SignerWeight { signer: *signer, weight });
+        }
+    }
+
+    /// Returns the total weight of all signers.
+    pub fn total_signer_weight(&self) -> u64 {
+        self.signers.iter().map(|s| self.signer_weight(s)).sum()
+    }
+
+    /// Returns the total weight of a list of approvals.
+    pub fn approval_weight(&self, approved: &[Address]) -> u64 {
+        approved.iter().map(|a| self.signer_weight(a)).sum()
+    }
+
+    /// Checks whether a signer may propose or approve a call to `method` of `to`.
+    /// The target is compared as given, so callers should resolve it to an ID address first.
+    pub fn signer_may_call(&self, signer: &Address, to: &Address, method: MethodNum) -> bool {
+        match self.signer_roles.iter().find(|r| &r.signer == signer) {
+            Some(role) => role.allowed.iter().any(|c| &c.to == to && c.method == method),
+            None => true,
+        }
+    }
+
+    /// Returns the calls a signer is restricted to, which are empty if the signer is unrestricted.
+    pub fn signer_allowed_calls(&self, signer: &Address) -> &[AllowedCall] {
+        self.signer_roles
+            .iter()
+            .find(|r| &r.signer == signer)
+            .map(|r| r.allowed.as_slice())
+            .unwrap_or_default()
+    }
+
+    /// Restricts a signer to a list of allowed calls, or lifts the restriction if empty.
+    pub fn set_signer_role(&mut self, signer: &Address, allowed: Vec<AllowedCall>) {
+        self.signer_roles.retain(|r| &r.signer != signer);
+        if !allowed.is_empty() {
+            self.signer_roles.push(SignerRole { signer: *signer, allowed });
+        }
+    }
+
     /// Set locked amount in multisig state.
     pub fn set_locked(
         &mut self,
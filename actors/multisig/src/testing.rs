@@ -3,9 +3,12 @@ use std::{collections::HashSet, iter::FromIterator};
 use fvm_ipld_blockstore::Blockstore;
 use fvm_shared::address::Address;
 
-use fil_actors_runtime::MessageAccumulator;
+use fil_actors_runtime::{ActorState, MessageAccumulator};
 
-use crate::{PendingTxnMap, State, TxnID, PENDING_TXN_CONFIG, SIGNERS_MAX};
+use crate::{
+    PendingTxnMap, State, TxnID, ALLOWED_CALLS_MAX, DEFAULT_SIGNER_WEIGHT, PENDING_TXN_CONFIG,
+    SIGNERS_MAX, SIGNER_WEIGHT_MAX,
+};
 
 pub struct StateSummary {
     pub pending_tx_count: u64,
@@ -19,6 +22,7 @@ pub fn check_state_invariants<BS: Blockstore>(
     store: &BS,
 ) -> (StateSummary, MessageAccumulator) {
     let acc = MessageAccumulator::default();
+    state.check_fields(store, &acc);
 
     // assert invariants involving signers
     acc.require(
@@ -26,14 +30,52 @@ pub fn check_state_invariants<BS: Blockstore>(
         format!("multisig has too many signers: {}", state.signers.len()),
     );
     acc.require(
-        state.signers.len() as u64 >= state.num_approvals_threshold,
+        state.total_signer_weight() >= state.num_approvals_threshold,
         format!(
-            "multisig has insufficient signers to meet threshold ({} < {})",
-            state.signers.len(),
+            "multisig has insufficient signer weight to meet threshold ({} < {})",
+            state.total_signer_weight(),
             state.num_approvals_threshold
         ),
     );
 
+    // assert invariants involving signer weights
+    let mut weighted_signers = HashSet::<&Address>::new();
+    for sw in state.signer_weights.iter() {
+        acc.require(
+            state.signers.contains(&sw.signer),
+            format!("weighted signer {} is not in signers list", sw.signer),
+        );
+        acc.require(
+            weighted_signers.insert(&sw.signer),
+            format!("duplicate weight for signer {}", sw.signer),
+        );
+        acc.require(
+            sw.weight != DEFAULT_SIGNER_WEIGHT && sw.weight > 0 && sw.weight <= SIGNER_WEIGHT_MAX,
+            format!("invalid weight {} for signer {}", sw.weight, sw.signer),
+        );
+    }
+
+    // assert invariants involving signer roles
+    let mut restricted_signers = HashSet::<&Address>::new();
+    for role in state.signer_roles.iter() {
+        acc.require(
+            state.signers.contains(&role.signer),
+            format!("restricted signer {} is not in signers list", role.signer),
+        );
+        acc.require(
+            restricted_signers.insert(&role.signer),
+            format!("duplicate role for signer {}", role.signer),
+        );
+        acc.require(
+            !role.allowed.is_empty() && role.allowed.len() <= ALLOWED_CALLS_MAX,
+            format!(
+                "invalid number {} of allowed calls for signer {}",
+                role.allowed.len(),
+                role.signer
+            ),
+        );
+    }
+
     // See https://github.com/filecoin-project/specs-actors/issues/1185
     if state.unlock_duration == 0 {
         acc.require(
@@ -75,9 +117,10 @@ pub fn check_state_invariants<BS: Blockstore>(
                     );
                     seen_approvals.insert(approval);
                 });
-                acc.require((seen_approvals.len() as u64) < state.num_approvals_threshold,
-                    format!("number of approvals ({}) meets the approvals threshold ({}), transaction should not be pending",
-                    seen_approvals.len(), state.num_approvals_threshold));
+                let approval_weight = state.approval_weight(&transaction.approved);
+                acc.require(approval_weight < state.num_approvals_threshold,
+                    format!("weight of approvals ({}) meets the approvals threshold ({}), transaction should not be pending",
+                    approval_weight, state.num_approvals_threshold));
 
                 pending_tx_count += 1;
 
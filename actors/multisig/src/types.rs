@@ -18,6 +18,19 @@ use fil_actors_runtime::MapKey;
 /// are required, please use a combining tree of multisigs.
 pub const SIGNERS_MAX: usize = 256;
 
+/// Weight of a signer's approval unless otherwise configured.
+/// With all signers at the default weight, the approval threshold is a number of signers.
+pub const DEFAULT_SIGNER_WEIGHT: u64 = 1;
+
+/// SignerWeightMax is the maximum weight of a single signer.
+pub const SIGNER_WEIGHT_MAX: u64 = 1 << 32;
+
+/// AllowedCallsMax is the maximum number of calls a signer's role may allow.
+pub const ALLOWED_CALLS_MAX: usize = 256;
+
+/// ListPendingTransactionsMax is the maximum number of transactions returned by one listing call.
+pub const LIST_PENDING_TRANSACTIONS_MAX: u64 = 1000;
+
 /// Transaction ID type
 #[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, Hash, Eq, PartialEq, PartialOrd)]
 #[serde(transparent)]
@@ -48,6 +61,15 @@ pub struct Transaction {
     pub params: RawBytes,
 
     pub approved: Vec<Address>,
+    /// Epoch after which the transaction can no longer be approved, if any.
+    pub expiration: Option<ChainEpoch>,
+}
+
+impl Transaction {
+    /// Whether the transaction can no longer be approved at an epoch.
+    pub fn is_expired(&self, epoch: ChainEpoch) -> bool {
+        matches!(self.expiration, Some(expiration) if epoch > expiration)
+    }
 }
 
 /// Data for a BLAKE2B-256 to be attached to methods referencing proposals via TXIDs.
@@ -65,6 +87,44 @@ pub struct ProposalHashData<'a> {
     pub params: &'a RawBytes,
 }
 
+/// Domain separation tag for signatures approving a proposal off-chain.
+pub const SIGNATURE_DOMAIN_SEPARATION_APPROVAL: &[u8] = b"fil_msig_approval:";
+
+/// Data signed by a signer to approve a proposal off-chain.
+/// The multisig address and the ID the proposal will receive bind the signature
+/// to a single proposal, so it cannot be replayed.
+#[derive(Serialize_tuple, Debug)]
+pub struct ApprovalSigningData<'a> {
+    pub multisig: &'a Address,
+    pub txn_id: TxnID,
+    pub to: &'a Address,
+    pub value: &'a TokenAmount,
+    pub method: &'a MethodNum,
+    pub params: &'a RawBytes,
+}
+
+/// The weight of a signer's approval towards the approvals threshold.
+#[derive(Clone, PartialEq, Eq, Debug, Serialize_tuple, Deserialize_tuple)]
+pub struct SignerWeight {
+    pub signer: Address,
+    pub weight: u64,
+}
+
+/// A call that a restricted signer may propose or approve.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Serialize_tuple, Deserialize_tuple)]
+pub struct AllowedCall {
+    pub to: Address,
+    pub method: MethodNum,
+}
+
+/// The calls to which a signer's proposals and approvals are restricted.
+/// Signers without a role may propose and approve any transaction.
+#[derive(Clone, PartialEq, Eq, Debug, Serialize_tuple, Deserialize_tuple)]
+pub struct SignerRole {
+    pub signer: Address,
+    pub allowed: Vec<AllowedCall>,
+}
+
 /// Constructor parameters for multisig actor.
 #[derive(Serialize_tuple, Deserialize_tuple)]
 pub struct ConstructorParams {
@@ -73,6 +133,8 @@ pub struct ConstructorParams {
     pub unlock_duration: ChainEpoch,
     // * Added in v2
     pub start_epoch: ChainEpoch,
+    /// Weights of signers, which default to DEFAULT_SIGNER_WEIGHT if not listed.
+    pub signer_weights: Vec<SignerWeight>,
 }
 
 /// Propose method call parameters.
@@ -82,10 +144,12 @@ pub struct ProposeParams {
     pub value: TokenAmount,
     pub method: MethodNum,
     pub params: RawBytes,
+    /// Epoch after which the transaction can no longer be approved, if any.
+    pub expiration: Option<ChainEpoch>,
 }
 
 /// Propose method call return.
-#[derive(Serialize_tuple, Deserialize_tuple)]
+#[derive(Debug, Serialize_tuple, Deserialize_tuple)]
 pub struct ProposeReturn {
     /// TxnID is the ID of the proposed transaction.
     pub txn_id: TxnID,
@@ -99,6 +163,26 @@ pub struct ProposeReturn {
     pub ret: RawBytes,
 }
 
+/// A signer's approval of a proposal, collected off-chain.
+#[derive(Clone, PartialEq, Eq, Debug, Serialize_tuple, Deserialize_tuple)]
+pub struct SignerApproval {
+    pub signer: Address,
+    /// Signature over the proposal's approval signing data, verified by the signer's account.
+    #[serde(with = "strict_bytes")]
+    pub signature: Vec<u8>,
+}
+
+/// ProposeWithApprovals method call parameters.
+#[derive(Serialize_tuple, Deserialize_tuple)]
+pub struct ProposeWithApprovalsParams {
+    pub to: Address,
+    pub value: TokenAmount,
+    pub method: MethodNum,
+    pub params: RawBytes,
+    /// Approvals of the proposal by signers other than the proposer.
+    pub approvals: Vec<SignerApproval>,
+}
+
 /// Parameters for approve and cancel multisig functions.
 #[derive(Clone, PartialEq, Eq, Debug, Serialize_tuple, Deserialize_tuple)]
 pub struct TxnIDParams {
@@ -122,6 +206,54 @@ pub struct ApproveReturn {
     pub ret: RawBytes,
 }
 
+/// PruneExpired method call parameters.
+#[derive(Serialize_tuple, Deserialize_tuple)]
+#[serde(transparent)]
+pub struct PruneExpiredParams {
+    /// IDs of transactions to prune.
+    pub ids: Vec<TxnID>,
+}
+
+/// PruneExpired method call return.
+#[derive(Serialize_tuple, Deserialize_tuple)]
+#[serde(transparent)]
+pub struct PruneExpiredReturn {
+    /// IDs of the transactions that had expired and were pruned.
+    pub pruned: Vec<TxnID>,
+}
+
+/// GetTransaction method call parameters.
+#[derive(Serialize_tuple, Deserialize_tuple)]
+#[serde(transparent)]
+pub struct GetTransactionParams {
+    pub id: TxnID,
+}
+
+/// GetTransaction method call return.
+#[derive(Serialize_tuple, Deserialize_tuple)]
+#[serde(transparent)]
+pub struct GetTransactionReturn {
+    pub transaction: Transaction,
+}
+
+/// ListPendingTransactions method call parameters.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize_tuple, Deserialize_tuple)]
+pub struct ListPendingTransactionsParams {
+    /// Transaction from which to resume a previous listing, as returned in `next_cursor`.
+    pub cursor: Option<TxnID>,
+    /// Maximum number of transactions to return, capped at `LIST_PENDING_TRANSACTIONS_MAX`.
+    pub limit: u64,
+}
+
+/// ListPendingTransactions method call return.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize_tuple, Deserialize_tuple)]
+pub struct ListPendingTransactionsReturn {
+    /// Pending transactions listed, with their IDs.
+    pub transactions: Vec<(TxnID, Transaction)>,
+    /// Transaction from which to resume the listing, or None if it is complete.
+    pub next_cursor: Option<TxnID>,
+}
+
 /// Add signer params.
 #[derive(Serialize_tuple, Deserialize_tuple)]
 pub struct AddSignerParams {
@@ -143,6 +275,21 @@ pub struct SwapSignerParams {
     pub to: Address,
 }
 
+/// Set signer weight params.
+#[derive(Serialize_tuple, Deserialize_tuple)]
+pub struct SetSignerWeightParams {
+    pub signer: Address,
+    pub weight: u64,
+}
+
+/// SetSignerRole method call parameters.
+#[derive(Serialize_tuple, Deserialize_tuple)]
+pub struct SetSignerRoleParams {
+    pub signer: Address,
+    /// Calls the signer may propose or approve. An empty list lifts any restriction.
+    pub allowed: Vec<AllowedCall>,
+}
+
 /// Propose method call parameters
 #[derive(Serialize_tuple, Deserialize_tuple)]
 pub struct ChangeNumApprovalsThresholdParams {
@@ -75,6 +75,7 @@ mod constructor_tests {
             num_approvals_threshold: 2,
             unlock_duration: 200,
             start_epoch: 100,
+            signer_weights: vec![],
         };
 
         rt.set_received(TokenAmount::from_atto(100u8));
@@ -112,6 +113,7 @@ mod constructor_tests {
             num_approvals_threshold: 2,
             unlock_duration: 0,
             start_epoch: 0,
+            signer_weights: vec![],
         };
 
         rt.expect_validate_caller_addr(vec![INIT_ACTOR_ADDR]);
@@ -136,6 +138,7 @@ mod constructor_tests {
             num_approvals_threshold: 3,
             unlock_duration: 100,
             start_epoch: 1234,
+            signer_weights: vec![],
         };
         rt.expect_validate_caller_addr(vec![INIT_ACTOR_ADDR]);
         rt.set_caller(*INIT_ACTOR_CODE_ID, INIT_ACTOR_ADDR);
@@ -165,6 +168,7 @@ mod constructor_tests {
             num_approvals_threshold: 1,
             unlock_duration: 1,
             start_epoch: 0,
+            signer_weights: vec![],
         };
         rt.expect_validate_caller_addr(vec![INIT_ACTOR_ADDR]);
         rt.set_caller(*INIT_ACTOR_CODE_ID, INIT_ACTOR_ADDR);
@@ -193,6 +197,7 @@ mod constructor_tests {
             num_approvals_threshold: 1,
             unlock_duration: 1,
             start_epoch: 0,
+            signer_weights: vec![],
         };
         rt.expect_validate_caller_addr(vec![INIT_ACTOR_ADDR]);
         rt.set_caller(*INIT_ACTOR_CODE_ID, INIT_ACTOR_ADDR);
@@ -214,6 +219,7 @@ mod constructor_tests {
             num_approvals_threshold: 2,
             unlock_duration: 0,
             start_epoch: 0,
+            signer_weights: vec![],
         };
         rt.expect_validate_caller_addr(vec![INIT_ACTOR_ADDR]);
         rt.set_caller(*INIT_ACTOR_CODE_ID, INIT_ACTOR_ADDR);
@@ -237,6 +243,7 @@ mod constructor_tests {
             num_approvals_threshold: 2,
             unlock_duration: 1,
             start_epoch: 0,
+            signer_weights: vec![],
         };
         rt.expect_validate_caller_addr(vec![INIT_ACTOR_ADDR]);
         rt.expect_send_simple(
@@ -266,6 +273,7 @@ mod constructor_tests {
             num_approvals_threshold: 2,
             unlock_duration: 0,
             start_epoch: 0,
+            signer_weights: vec![],
         };
         rt.expect_validate_caller_addr(vec![INIT_ACTOR_ADDR]);
         rt.set_caller(*INIT_ACTOR_CODE_ID, INIT_ACTOR_ADDR);
@@ -289,6 +297,7 @@ mod constructor_tests {
             num_approvals_threshold: 2,
             unlock_duration: 0,
             start_epoch: 0,
+            signer_weights: vec![],
         };
         rt.expect_validate_caller_addr(vec![INIT_ACTOR_ADDR]);
         rt.set_caller(*INIT_ACTOR_CODE_ID, INIT_ACTOR_ADDR);
@@ -574,6 +583,7 @@ fn test_simple_propose() {
         method: METHOD_SEND,
         params: RawBytes::default(),
         approved: vec![anne],
+        expiration: None,
     };
     let expect_txns = vec![(TxnID(0), txn0)];
     h.assert_transactions(&rt, expect_txns);
@@ -1144,6 +1154,7 @@ fn test_swap_signer_removes_approvals() {
                     method: METHOD_SEND,
                     params: RawBytes::default(),
                     approved: vec![bob],
+                    expiration: None,
                 },
             ),
             (
@@ -1154,6 +1165,7 @@ fn test_swap_signer_removes_approvals() {
                     method: METHOD_SEND,
                     params: RawBytes::default(),
                     approved: vec![bob],
+                    expiration: None,
                 },
             ),
         ],
@@ -1230,6 +1242,7 @@ fn test_remove_signer_removes_approvals() {
                     method: METHOD_SEND,
                     params: RawBytes::default(),
                     approved: vec![bob],
+                    expiration: None,
                 },
             ),
             (
@@ -1240,6 +1253,7 @@ fn test_remove_signer_removes_approvals() {
                     method: METHOD_SEND,
                     params: RawBytes::default(),
                     approved: vec![bob],
+                    expiration: None,
                 },
             ),
         ],
@@ -1305,6 +1319,7 @@ mod approval_tests {
             method: fake_method,
             params: fake_params.clone(),
             approved: vec![anne],
+            expiration: None,
         };
         h.assert_transactions(&rt, vec![(TxnID(0), expect_txn)]);
 
@@ -1391,6 +1406,7 @@ mod approval_tests {
                     method: fake_method,
                     params: fake_params.clone(),
                     approved: vec![anne],
+                    expiration: None,
                 },
             )],
         );
@@ -1441,6 +1457,7 @@ mod approval_tests {
                     method: fake_method,
                     params: fake_params,
                     approved: vec![anne],
+                    expiration: None,
                 },
             )],
         );
@@ -1478,6 +1495,7 @@ mod approval_tests {
                     method: fake_method,
                     params: fake_params,
                     approved: vec![anne],
+                    expiration: None,
                 },
             )],
         );
@@ -1512,6 +1530,7 @@ mod approval_tests {
                 method: fake_method,
                 params: fake_params,
                 approved: vec![bob], //mismatch
+                expiration: None,
             },
             &rt,
         )
@@ -1550,6 +1569,7 @@ mod approval_tests {
             method: fake_method,
             params: fake_params.clone(),
             approved: vec![anne],
+            expiration: None,
         };
         h.assert_transactions(&rt, vec![(TxnID(0), expect_txn)]);
 
@@ -1603,6 +1623,8 @@ mod approval_tests {
             None,
             ExitCode::OK,
         );
+        util::expect_txn_event(&rt, "transaction-approved", TxnID(0), &bob);
+        util::expect_txn_event(&rt, "transaction-executed", TxnID(0), &bob);
         rt.expect_validate_caller_any();
         let params = TxnIDParams { id: TxnID(0), proposal_hash: Vec::<u8>::new() };
         rt.call::<MultisigActor>(
@@ -1647,6 +1669,7 @@ mod approval_tests {
                     method: fake_method,
                     params: fake_params,
                     approved: vec![anne],
+                    expiration: None,
                 },
             )],
         );
@@ -1713,6 +1736,7 @@ mod approval_tests {
                     method: fake_method,
                     params: fake_params,
                     approved: vec![anne],
+                    expiration: None,
                 },
             )],
         );
@@ -1852,6 +1876,720 @@ mod approval_tests {
     }
 }
 
+mod propose_with_approvals_tests {
+    use super::*;
+    use fil_actor_multisig::ext::account::{
+        AuthenticateMessageParams, AUTHENTICATE_MESSAGE_METHOD,
+    };
+    use fil_actor_multisig::{compute_approval_signing_bytes, SignerApproval};
+    use fvm_shared::sys::SendFlags;
+
+    const MSIG: u64 = 1000;
+
+    fn setup(threshold: u64) -> (MockRuntime, util::ActorHarness) {
+        let rt = construct_runtime(Address::new_id(MSIG));
+        let h = util::ActorHarness::new();
+        let signers = vec![
+            Address::new_id(TEST_ANNE_ADDR),
+            Address::new_id(TEST_BOB_ADDR),
+            Address::new_id(TEST_CHUCK_ADDR),
+        ];
+        rt.set_balance(TokenAmount::from_atto(10u8));
+        rt.set_received(TokenAmount::zero());
+        h.construct_and_verify(&rt, threshold, 0, 0, signers);
+        (rt, h)
+    }
+
+    fn approval(signer: u64) -> SignerApproval {
+        SignerApproval { signer: Address::new_id(signer), signature: vec![signer as u8] }
+    }
+
+    fn expect_authenticate(
+        rt: &MockRuntime,
+        txn_id: TxnID,
+        txn: &Transaction,
+        signer: u64,
+        ok: bool,
+    ) {
+        let message = compute_approval_signing_bytes(&Address::new_id(MSIG), txn_id, txn).unwrap();
+        rt.expect_send(
+            Address::new_id(signer),
+            AUTHENTICATE_MESSAGE_METHOD,
+            IpldBlock::serialize_cbor(&AuthenticateMessageParams {
+                signature: vec![signer as u8],
+                message,
+            })
+            .unwrap(),
+            TokenAmount::zero(),
+            None,
+            SendFlags::READ_ONLY,
+            IpldBlock::serialize_cbor(&ok).unwrap(),
+            ExitCode::OK,
+            None,
+        );
+    }
+
+    #[test]
+    fn executes_when_approvals_meet_threshold() {
+        let (rt, h) = setup(3);
+        let to = Address::new_id(TEST_DARLENE_ADDR);
+        let value = TokenAmount::from_atto(10u8);
+        let params = RawBytes::from(vec![1, 2, 3]);
+        let txn = Transaction {
+            to,
+            value: value.clone(),
+            method: METHOD_SEND,
+            params: params.clone(),
+            approved: vec![Address::new_id(TEST_ANNE_ADDR)],
+            expiration: None,
+        };
+
+        expect_authenticate(&rt, TxnID(0), &txn, TEST_BOB_ADDR, true);
+        expect_authenticate(&rt, TxnID(0), &txn, TEST_CHUCK_ADDR, true);
+        rt.expect_send_simple(
+            to,
+            METHOD_SEND,
+            to_ipld_block(params.clone()),
+            value.clone(),
+            None,
+            ExitCode::OK,
+        );
+        rt.set_caller(*ACCOUNT_ACTOR_CODE_ID, Address::new_id(TEST_ANNE_ADDR));
+        let ret = h
+            .propose_with_approvals(
+                &rt,
+                to,
+                value,
+                METHOD_SEND,
+                params,
+                vec![approval(TEST_BOB_ADDR), approval(TEST_CHUCK_ADDR)],
+            )
+            .unwrap();
+        assert_eq!(TxnID(0), ret.txn_id);
+        assert!(ret.applied);
+        assert_eq!(ExitCode::OK, ret.code);
+
+        h.assert_transactions(&rt, vec![]);
+        let st: State = rt.get_state();
+        assert_eq!(TxnID(1), st.next_tx_id);
+        check_state(&rt);
+    }
+
+    #[test]
+    fn remains_pending_below_threshold() {
+        let (rt, h) = setup(3);
+        let to = Address::new_id(TEST_DARLENE_ADDR);
+        let value = TokenAmount::from_atto(10u8);
+        let params = RawBytes::from(vec![1, 2, 3]);
+        let mut txn = Transaction {
+            to,
+            value: value.clone(),
+            method: METHOD_SEND,
+            params: params.clone(),
+            approved: vec![Address::new_id(TEST_ANNE_ADDR)],
+            expiration: None,
+        };
+
+        expect_authenticate(&rt, TxnID(0), &txn, TEST_BOB_ADDR, true);
+        rt.set_caller(*ACCOUNT_ACTOR_CODE_ID, Address::new_id(TEST_ANNE_ADDR));
+        let ret = h
+            .propose_with_approvals(
+                &rt,
+                to,
+                value.clone(),
+                METHOD_SEND,
+                params.clone(),
+                vec![approval(TEST_BOB_ADDR)],
+            )
+            .unwrap();
+        assert!(!ret.applied);
+
+        txn.approved.push(Address::new_id(TEST_BOB_ADDR));
+        h.assert_transactions(&rt, vec![(TxnID(0), txn.clone())]);
+
+        // The remaining signer approves on-chain.
+        let proposal_hash = compute_proposal_hash(&txn, &rt).unwrap();
+        rt.expect_send_simple(to, METHOD_SEND, to_ipld_block(params), value, None, ExitCode::OK);
+        rt.set_caller(*ACCOUNT_ACTOR_CODE_ID, Address::new_id(TEST_CHUCK_ADDR));
+        h.approve_ok(&rt, TxnID(0), proposal_hash);
+        h.assert_transactions(&rt, vec![]);
+        check_state(&rt);
+    }
+
+    #[test]
+    fn rejects_invalid_approvals() {
+        let (rt, h) = setup(3);
+        let to = Address::new_id(TEST_DARLENE_ADDR);
+        let value = TokenAmount::from_atto(10u8);
+        let txn = Transaction {
+            to,
+            value: value.clone(),
+            method: METHOD_SEND,
+            params: RawBytes::default(),
+            approved: vec![Address::new_id(TEST_ANNE_ADDR)],
+            expiration: None,
+        };
+        rt.set_caller(*ACCOUNT_ACTOR_CODE_ID, Address::new_id(TEST_ANNE_ADDR));
+
+        // Approval by a non-signer.
+        expect_abort_contains_message(
+            ExitCode::USR_FORBIDDEN,
+            "is not a signer",
+            h.propose_with_approvals(
+                &rt,
+                to,
+                value.clone(),
+                METHOD_SEND,
+                RawBytes::default(),
+                vec![approval(TEST_DARLENE_ADDR)],
+            ),
+        );
+        rt.reset();
+
+        // Approval by the proposer.
+        expect_abort_contains_message(
+            ExitCode::USR_FORBIDDEN,
+            "already approved",
+            h.propose_with_approvals(
+                &rt,
+                to,
+                value.clone(),
+                METHOD_SEND,
+                RawBytes::default(),
+                vec![approval(TEST_ANNE_ADDR)],
+            ),
+        );
+        rt.reset();
+
+        // Duplicate approval.
+        expect_authenticate(&rt, TxnID(0), &txn, TEST_BOB_ADDR, true);
+        expect_abort_contains_message(
+            ExitCode::USR_FORBIDDEN,
+            "already approved",
+            h.propose_with_approvals(
+                &rt,
+                to,
+                value.clone(),
+                METHOD_SEND,
+                RawBytes::default(),
+                vec![approval(TEST_BOB_ADDR), approval(TEST_BOB_ADDR)],
+            ),
+        );
+        rt.reset();
+
+        // Signature rejected by the signer's account.
+        expect_authenticate(&rt, TxnID(0), &txn, TEST_BOB_ADDR, false);
+        expect_abort_contains_message(
+            ExitCode::USR_ILLEGAL_ARGUMENT,
+            "authentication failed",
+            h.propose_with_approvals(
+                &rt,
+                to,
+                value,
+                METHOD_SEND,
+                RawBytes::default(),
+                vec![approval(TEST_BOB_ADDR)],
+            ),
+        );
+        rt.reset();
+
+        h.assert_transactions(&rt, vec![]);
+        check_state(&rt);
+    }
+}
+
+mod expiration_tests {
+    use super::*;
+
+    fn setup() -> (MockRuntime, util::ActorHarness, Transaction) {
+        let rt = construct_runtime(Address::new_id(1000));
+        let h = util::ActorHarness::new();
+        let anne = Address::new_id(TEST_ANNE_ADDR);
+        let bob = Address::new_id(TEST_BOB_ADDR);
+        rt.set_balance(TokenAmount::from_atto(10u8));
+        rt.set_received(TokenAmount::zero());
+        h.construct_and_verify(&rt, 2, 0, 0, vec![anne, bob]);
+
+        rt.set_epoch(100);
+        rt.set_caller(*ACCOUNT_ACTOR_CODE_ID, anne);
+        let txn = Transaction {
+            to: Address::new_id(TEST_CHUCK_ADDR),
+            value: TokenAmount::from_atto(10u8),
+            method: METHOD_SEND,
+            params: RawBytes::default(),
+            approved: vec![anne],
+            expiration: Some(200),
+        };
+        (rt, h, txn)
+    }
+
+    fn propose(rt: &MockRuntime, h: &util::ActorHarness, txn: &Transaction) -> [u8; 32] {
+        h.propose_expiring(
+            rt,
+            txn.to,
+            txn.value.clone(),
+            txn.method,
+            txn.params.clone(),
+            txn.expiration,
+        )
+        .unwrap();
+        compute_proposal_hash(txn, rt).unwrap()
+    }
+
+    #[test]
+    fn approve_until_expiration() {
+        let (rt, h, txn) = setup();
+        let proposal_hash = propose(&rt, &h, &txn);
+        h.assert_transactions(&rt, vec![(TxnID(0), txn.clone())]);
+
+        rt.set_epoch(200);
+        rt.expect_send_simple(txn.to, METHOD_SEND, None, txn.value, None, ExitCode::OK);
+        rt.set_caller(*ACCOUNT_ACTOR_CODE_ID, Address::new_id(TEST_BOB_ADDR));
+        h.approve_ok(&rt, TxnID(0), proposal_hash);
+        h.assert_transactions(&rt, vec![]);
+        check_state(&rt);
+    }
+
+    #[test]
+    fn expired_transaction_cannot_be_approved() {
+        let (rt, h, txn) = setup();
+        let proposal_hash = propose(&rt, &h, &txn);
+
+        rt.set_epoch(201);
+        rt.set_caller(*ACCOUNT_ACTOR_CODE_ID, Address::new_id(TEST_BOB_ADDR));
+        expect_abort_contains_message(
+            ExitCode::USR_FORBIDDEN,
+            "has expired",
+            h.approve(&rt, TxnID(0), proposal_hash),
+        );
+        rt.reset();
+        h.assert_transactions(&rt, vec![(TxnID(0), txn)]);
+        check_state(&rt);
+    }
+
+    #[test]
+    fn expiration_must_not_be_in_past() {
+        let (rt, h, txn) = setup();
+        expect_abort_contains_message(
+            ExitCode::USR_ILLEGAL_ARGUMENT,
+            "must not be before current epoch",
+            h.propose_expiring(&rt, txn.to, txn.value, txn.method, txn.params, Some(99)),
+        );
+        rt.reset();
+    }
+
+    #[test]
+    fn anyone_can_prune_expired() {
+        let (rt, h, txn) = setup();
+        propose(&rt, &h, &txn);
+        let unexpiring = Transaction { expiration: None, ..txn.clone() };
+        propose(&rt, &h, &unexpiring);
+
+        // Nothing has expired yet.
+        rt.set_caller(*ACCOUNT_ACTOR_CODE_ID, Address::new_id(TEST_DARLENE_ADDR));
+        let ret = h.prune_expired(&rt, vec![TxnID(0), TxnID(1)]).unwrap();
+        assert!(ret.pruned.is_empty());
+
+        // Only the expired transaction is pruned, and unknown IDs are ignored.
+        rt.set_epoch(201);
+        let ret = h.prune_expired(&rt, vec![TxnID(0), TxnID(1), TxnID(5)]).unwrap();
+        assert_eq!(vec![TxnID(0)], ret.pruned);
+        h.assert_transactions(&rt, vec![(TxnID(1), unexpiring)]);
+        check_state(&rt);
+    }
+}
+
+mod weighted_signer_tests {
+    use super::*;
+    use fil_actor_multisig::{ApproveReturn, SignerWeight};
+
+    const MSIG: u64 = 1000;
+
+    // Anne's approval weighs as much as Bob's and Chuck's together.
+    fn setup(threshold: u64) -> (MockRuntime, util::ActorHarness) {
+        let rt = construct_runtime(Address::new_id(MSIG));
+        let h = util::ActorHarness::new();
+        let signers = vec![
+            Address::new_id(TEST_ANNE_ADDR),
+            Address::new_id(TEST_BOB_ADDR),
+            Address::new_id(TEST_CHUCK_ADDR),
+        ];
+        let weights = vec![SignerWeight { signer: Address::new_id(TEST_ANNE_ADDR), weight: 2 }];
+        rt.set_balance(TokenAmount::from_atto(10u8));
+        rt.set_received(TokenAmount::zero());
+        h.construct_weighted_and_verify(&rt, threshold, 0, 0, signers, weights);
+        (rt, h)
+    }
+
+    #[test]
+    fn approvals_are_weighted() {
+        let (rt, h) = setup(3);
+        let st: State = rt.get_state();
+        assert_eq!(4, st.total_signer_weight());
+        let to = Address::new_id(TEST_DARLENE_ADDR);
+        let value = TokenAmount::from_atto(1u8);
+
+        // Bob and Chuck together don't meet the threshold.
+        rt.set_caller(*ACCOUNT_ACTOR_CODE_ID, Address::new_id(TEST_BOB_ADDR));
+        let hash = h.propose_ok(&rt, to, value.clone(), METHOD_SEND, RawBytes::default());
+        rt.set_caller(*ACCOUNT_ACTOR_CODE_ID, Address::new_id(TEST_CHUCK_ADDR));
+        let ret = h.approve(&rt, TxnID(0), hash).unwrap().unwrap();
+        assert!(!ret.deserialize::<ApproveReturn>().unwrap().applied);
+        check_state(&rt);
+
+        // Anne's approval executes it.
+        rt.set_caller(*ACCOUNT_ACTOR_CODE_ID, Address::new_id(TEST_ANNE_ADDR));
+        rt.expect_send_simple(to, METHOD_SEND, None, value.clone(), None, ExitCode::OK);
+        h.approve_ok(&rt, TxnID(0), hash);
+
+        // Anne's proposal needs just one more approval.
+        let hash = h.propose_ok(&rt, to, value.clone(), METHOD_SEND, RawBytes::default());
+        rt.set_caller(*ACCOUNT_ACTOR_CODE_ID, Address::new_id(TEST_CHUCK_ADDR));
+        rt.expect_send_simple(to, METHOD_SEND, None, value, None, ExitCode::OK);
+        h.approve_ok(&rt, TxnID(1), hash);
+        h.assert_transactions(&rt, vec![]);
+        check_state(&rt);
+    }
+
+    #[test]
+    fn construction_validates_weights() {
+        let anne = Address::new_id(TEST_ANNE_ADDR);
+        let bob = Address::new_id(TEST_BOB_ADDR);
+        let cases = vec![
+            (3, vec![SignerWeight { signer: anne, weight: 2 }], ExitCode::OK),
+            (4, vec![SignerWeight { signer: anne, weight: 2 }], ExitCode::USR_ILLEGAL_ARGUMENT),
+            (1, vec![SignerWeight { signer: anne, weight: 0 }], ExitCode::USR_ILLEGAL_ARGUMENT),
+            (
+                1,
+                vec![SignerWeight { signer: Address::new_id(TEST_CHUCK_ADDR), weight: 2 }],
+                ExitCode::USR_ILLEGAL_ARGUMENT,
+            ),
+            (
+                1,
+                vec![
+                    SignerWeight { signer: anne, weight: 2 },
+                    SignerWeight { signer: anne, weight: 3 },
+                ],
+                ExitCode::USR_ILLEGAL_ARGUMENT,
+            ),
+        ];
+        for (threshold, signer_weights, code) in cases {
+            let rt = construct_runtime(Address::new_id(MSIG));
+            let params = ConstructorParams {
+                signers: vec![anne, bob],
+                num_approvals_threshold: threshold,
+                unlock_duration: 0,
+                start_epoch: 0,
+                signer_weights,
+            };
+            rt.set_caller(*INIT_ACTOR_CODE_ID, INIT_ACTOR_ADDR);
+            rt.expect_validate_caller_addr(vec![INIT_ACTOR_ADDR]);
+            let ret = rt.call::<MultisigActor>(
+                Method::Constructor as u64,
+                IpldBlock::serialize_cbor(&params).unwrap(),
+            );
+            match code {
+                ExitCode::OK => {
+                    ret.unwrap();
+                    check_state(&rt);
+                }
+                _ => expect_abort(code, ret),
+            }
+        }
+    }
+
+    #[test]
+    fn set_signer_weight() {
+        let (rt, h) = setup(3);
+        let msig = Address::new_id(MSIG);
+        let anne = Address::new_id(TEST_ANNE_ADDR);
+        let bob = Address::new_id(TEST_BOB_ADDR);
+
+        rt.set_caller(*MULTISIG_ACTOR_CODE_ID, msig);
+        h.set_signer_weight(&rt, bob, 5).unwrap();
+        let st: State = rt.get_state();
+        assert_eq!(5, st.signer_weight(&bob));
+        assert_eq!(8, st.total_signer_weight());
+
+        // Returning to the default weight removes the entry.
+        h.set_signer_weight(&rt, anne, 1).unwrap();
+        let st: State = rt.get_state();
+        assert_eq!(vec![SignerWeight { signer: bob, weight: 5 }], st.signer_weights);
+        check_state(&rt);
+
+        expect_abort(ExitCode::USR_ILLEGAL_ARGUMENT, h.set_signer_weight(&rt, bob, 0));
+        expect_abort(
+            ExitCode::USR_FORBIDDEN,
+            h.set_signer_weight(&rt, Address::new_id(TEST_DARLENE_ADDR), 2),
+        );
+        h.change_num_approvals_threshold(&rt, 7).unwrap();
+        expect_abort_contains_message(
+            ExitCode::USR_ILLEGAL_ARGUMENT,
+            "below threshold",
+            h.set_signer_weight(&rt, bob, 1),
+        );
+
+        // Only the multisig itself may set weights.
+        rt.set_caller(*ACCOUNT_ACTOR_CODE_ID, bob);
+        expect_abort(ExitCode::USR_FORBIDDEN, h.set_signer_weight(&rt, bob, 2));
+        check_state(&rt);
+    }
+
+    #[test]
+    fn remove_and_swap_weighted_signer() {
+        let (rt, h) = setup(4);
+        let msig = Address::new_id(MSIG);
+        let anne = Address::new_id(TEST_ANNE_ADDR);
+        let darlene = Address::new_id(TEST_DARLENE_ADDR);
+        rt.set_caller(*MULTISIG_ACTOR_CODE_ID, msig);
+
+        // The swapped in signer takes over the weight.
+        h.swap_signers(&rt, anne, darlene).unwrap();
+        let st: State = rt.get_state();
+        assert_eq!(2, st.signer_weight(&darlene));
+        assert_eq!(1, st.signer_weight(&anne));
+        check_state(&rt);
+
+        // Removing without decreasing would leave the threshold unreachable.
+        expect_abort(ExitCode::USR_ILLEGAL_ARGUMENT, h.remove_signer(&rt, darlene, false));
+
+        // Decreasing lowers the threshold by the removed signer's weight.
+        h.remove_signer(&rt, darlene, true).unwrap();
+        let st: State = rt.get_state();
+        assert_eq!(2, st.num_approvals_threshold);
+        assert!(st.signer_weights.is_empty());
+        check_state(&rt);
+    }
+}
+
+mod signer_role_tests {
+    use super::*;
+    use fil_actor_multisig::{AllowedCall, ALLOWED_CALLS_MAX};
+
+    const MSIG: u64 = 1000;
+    const TARGET: u64 = 1234;
+    const RELAY_METHOD: MethodNum = 42;
+
+    // Bob may only relay calls to the target.
+    fn setup() -> (MockRuntime, util::ActorHarness) {
+        let rt = construct_runtime(Address::new_id(MSIG));
+        let h = util::ActorHarness::new();
+        let bob = Address::new_id(TEST_BOB_ADDR);
+        let signers = vec![Address::new_id(TEST_ANNE_ADDR), bob, Address::new_id(TEST_CHUCK_ADDR)];
+        rt.set_balance(TokenAmount::from_atto(10u8));
+        rt.set_received(TokenAmount::zero());
+        h.construct_and_verify(&rt, 2, 0, 0, signers);
+
+        rt.set_caller(*MULTISIG_ACTOR_CODE_ID, Address::new_id(MSIG));
+        h.set_signer_role(&rt, bob, vec![relay_call()]).unwrap();
+        (rt, h)
+    }
+
+    fn relay_call() -> AllowedCall {
+        AllowedCall { to: Address::new_id(TARGET), method: RELAY_METHOD }
+    }
+
+    #[test]
+    fn restricted_signer_limited_to_allowed_calls() {
+        let (rt, h) = setup();
+        let target = Address::new_id(TARGET);
+        let darlene = Address::new_id(TEST_DARLENE_ADDR);
+        let zero = TokenAmount::zero();
+
+        rt.set_caller(*ACCOUNT_ACTOR_CODE_ID, Address::new_id(TEST_BOB_ADDR));
+        let relay_hash = h.propose_ok(&rt, target, zero.clone(), RELAY_METHOD, RawBytes::default());
+        expect_abort_contains_message(
+            ExitCode::USR_FORBIDDEN,
+            "not allowed to call",
+            h.propose(&rt, target, zero.clone(), METHOD_SEND, RawBytes::default()),
+        );
+        expect_abort(
+            ExitCode::USR_FORBIDDEN,
+            h.propose(&rt, darlene, zero.clone(), RELAY_METHOD, RawBytes::default()),
+        );
+
+        // Bob can't approve a transfer, but Chuck can.
+        rt.set_caller(*ACCOUNT_ACTOR_CODE_ID, Address::new_id(TEST_ANNE_ADDR));
+        let value = TokenAmount::from_atto(1u8);
+        let send_hash = h.propose_ok(&rt, darlene, value.clone(), METHOD_SEND, RawBytes::default());
+        rt.set_caller(*ACCOUNT_ACTOR_CODE_ID, Address::new_id(TEST_BOB_ADDR));
+        expect_abort(ExitCode::USR_FORBIDDEN, h.approve(&rt, TxnID(1), send_hash));
+        rt.set_caller(*ACCOUNT_ACTOR_CODE_ID, Address::new_id(TEST_CHUCK_ADDR));
+        rt.expect_send_simple(darlene, METHOD_SEND, None, value, None, ExitCode::OK);
+        h.approve_ok(&rt, TxnID(1), send_hash);
+
+        // Bob's relay is executed with Anne's approval.
+        rt.set_caller(*ACCOUNT_ACTOR_CODE_ID, Address::new_id(TEST_ANNE_ADDR));
+        rt.expect_send_simple(target, RELAY_METHOD, None, zero, None, ExitCode::OK);
+        h.approve_ok(&rt, TxnID(0), relay_hash);
+        h.assert_transactions(&rt, vec![]);
+        check_state(&rt);
+    }
+
+    #[test]
+    fn set_signer_role() {
+        let (rt, h) = setup();
+        let bob = Address::new_id(TEST_BOB_ADDR);
+        let robust_target = new_bls_addr(1);
+        rt.add_id_address(robust_target, Address::new_id(TARGET));
+
+        // Allowed targets are stored resolved.
+        let other = AllowedCall { to: robust_target, method: METHOD_SEND };
+        h.set_signer_role(&rt, bob, vec![relay_call(), other]).unwrap();
+        let st: State = rt.get_state();
+        assert_eq!(
+            vec![relay_call(), AllowedCall { to: Address::new_id(TARGET), method: METHOD_SEND }],
+            st.signer_allowed_calls(&bob)
+        );
+        assert!(st.signer_may_call(&bob, &Address::new_id(TARGET), METHOD_SEND));
+        assert!(!st.signer_may_call(&bob, &Address::new_id(TEST_DARLENE_ADDR), METHOD_SEND));
+        check_state(&rt);
+
+        let duplicate = AllowedCall { to: robust_target, method: RELAY_METHOD };
+        expect_abort_contains_message(
+            ExitCode::USR_ILLEGAL_ARGUMENT,
+            "duplicate allowed call",
+            h.set_signer_role(&rt, bob, vec![relay_call(), duplicate]),
+        );
+        let too_many = (0..=ALLOWED_CALLS_MAX as u64)
+            .map(|method| AllowedCall { to: Address::new_id(TARGET), method })
+            .collect();
+        expect_abort(ExitCode::USR_ILLEGAL_ARGUMENT, h.set_signer_role(&rt, bob, too_many));
+        expect_abort(
+            ExitCode::USR_FORBIDDEN,
+            h.set_signer_role(&rt, Address::new_id(TEST_DARLENE_ADDR), vec![relay_call()]),
+        );
+
+        // An empty list lifts the restriction.
+        h.set_signer_role(&rt, bob, vec![]).unwrap();
+        let st: State = rt.get_state();
+        assert!(st.signer_roles.is_empty());
+        assert!(st.signer_may_call(&bob, &Address::new_id(TEST_DARLENE_ADDR), METHOD_SEND));
+        check_state(&rt);
+
+        // Only the multisig itself may set roles.
+        rt.set_caller(*ACCOUNT_ACTOR_CODE_ID, bob);
+        expect_abort(ExitCode::USR_FORBIDDEN, h.set_signer_role(&rt, bob, vec![relay_call()]));
+        check_state(&rt);
+    }
+
+    #[test]
+    fn remove_and_swap_restricted_signer() {
+        let (rt, h) = setup();
+        let bob = Address::new_id(TEST_BOB_ADDR);
+        let darlene = Address::new_id(TEST_DARLENE_ADDR);
+
+        // The swapped in signer takes over the role.
+        h.swap_signers(&rt, bob, darlene).unwrap();
+        let st: State = rt.get_state();
+        assert_eq!(vec![relay_call()], st.signer_allowed_calls(&darlene));
+        assert!(st.signer_allowed_calls(&bob).is_empty());
+        check_state(&rt);
+
+        h.remove_signer(&rt, darlene, false).unwrap();
+        let st: State = rt.get_state();
+        assert!(st.signer_roles.is_empty());
+        check_state(&rt);
+    }
+}
+
+mod pending_transaction_getter_tests {
+    use super::*;
+
+    fn setup() -> (MockRuntime, util::ActorHarness, Vec<(TxnID, Transaction)>) {
+        let rt = construct_runtime(Address::new_id(1000));
+        let h = util::ActorHarness::new();
+        let anne = Address::new_id(TEST_ANNE_ADDR);
+        h.construct_and_verify(&rt, 2, 0, 0, vec![anne, Address::new_id(TEST_BOB_ADDR)]);
+
+        rt.set_caller(*ACCOUNT_ACTOR_CODE_ID, anne);
+        let txns: Vec<_> = (0..3u8)
+            .map(|i| {
+                let txn = Transaction {
+                    to: Address::new_id(TEST_CHUCK_ADDR),
+                    value: TokenAmount::from_atto(i),
+                    method: METHOD_SEND,
+                    params: RawBytes::default(),
+                    approved: vec![anne],
+                    expiration: None,
+                };
+                h.propose_ok(&rt, txn.to, txn.value.clone(), txn.method, txn.params.clone());
+                (TxnID(i as i64), txn)
+            })
+            .collect();
+        (rt, h, txns)
+    }
+
+    #[test]
+    fn get_transaction() {
+        let (rt, h, txns) = setup();
+        rt.set_caller(*EVM_ACTOR_CODE_ID, Address::new_id(TEST_DARLENE_ADDR));
+        for (id, txn) in &txns {
+            assert_eq!(*txn, h.get_transaction(&rt, *id).unwrap());
+        }
+        expect_abort(ExitCode::USR_NOT_FOUND, h.get_transaction(&rt, TxnID(3)));
+    }
+
+    #[test]
+    fn list_pending_transactions() {
+        let (rt, h, txns) = setup();
+        rt.set_caller(*EVM_ACTOR_CODE_ID, Address::new_id(TEST_DARLENE_ADDR));
+
+        let page = h.list_pending_transactions(&rt, None, 10).unwrap();
+        assert_eq!(None, page.next_cursor);
+        let mut listed = page.transactions;
+        listed.sort_by_key(|(TxnID(id), _)| *id);
+        assert_eq!(txns, listed);
+
+        // Listing a page at a time covers every transaction once.
+        let mut listed = Vec::new();
+        let mut cursor = None;
+        loop {
+            let page = h.list_pending_transactions(&rt, cursor, 2).unwrap();
+            assert!(page.transactions.len() <= 2);
+            listed.extend(page.transactions);
+            cursor = page.next_cursor;
+            if cursor.is_none() {
+                break;
+            }
+        }
+        listed.sort_by_key(|(TxnID(id), _)| *id);
+        assert_eq!(txns, listed);
+
+        // An oversized limit is capped rather than rejected.
+        let page = h.list_pending_transactions(&rt, None, u64::MAX).unwrap();
+        assert_eq!(txns.len(), page.transactions.len());
+        assert_eq!(None, page.next_cursor);
+
+        expect_abort(ExitCode::USR_ILLEGAL_ARGUMENT, h.list_pending_transactions(&rt, None, 0));
+    }
+
+    #[test]
+    fn list_resumes_after_cursor_transaction_removed() {
+        let (rt, h, mut txns) = setup();
+        rt.set_caller(*EVM_ACTOR_CODE_ID, Address::new_id(TEST_DARLENE_ADDR));
+        let first = h.list_pending_transactions(&rt, None, 1).unwrap();
+        let cursor = first.next_cursor.unwrap();
+
+        // The proposer cancels the transaction at the cursor between pages.
+        let (_, cancelled) = txns.iter().find(|(id, _)| *id == cursor).unwrap();
+        let proposal_hash = compute_proposal_hash(cancelled, &rt).unwrap();
+        rt.set_caller(*ACCOUNT_ACTOR_CODE_ID, Address::new_id(TEST_ANNE_ADDR));
+        h.cancel(&rt, cursor, proposal_hash).unwrap();
+
+        rt.set_caller(*EVM_ACTOR_CODE_ID, Address::new_id(TEST_DARLENE_ADDR));
+        let second = h.list_pending_transactions(&rt, Some(cursor), 10).unwrap();
+        assert_eq!(None, second.next_cursor);
+        let mut listed = [first.transactions, second.transactions].concat();
+        listed.sort_by_key(|(TxnID(id), _)| *id);
+        txns.retain(|(id, _)| *id != cursor);
+        assert_eq!(txns, listed);
+
+        // A cursor naming a transaction that never existed resumes where it would have been.
+        h.list_pending_transactions(&rt, Some(TxnID(3)), 1).unwrap();
+    }
+}
+
 // Cancel
 mod cancel_tests {
     use super::*;
@@ -1948,6 +2686,7 @@ mod cancel_tests {
                     method: fake_method,
                     params: RawBytes::default(),
                     approved: vec![anne],
+                    expiration: None,
                 },
             )],
         );
@@ -1989,6 +2728,7 @@ mod cancel_tests {
                     method: fake_method,
                     params: RawBytes::default(),
                     approved: vec![anne],
+                    expiration: None,
                 },
             )],
         );
@@ -2031,6 +2771,7 @@ mod cancel_tests {
                     method: fake_method,
                     params: RawBytes::default(),
                     approved: vec![anne],
+                    expiration: None,
                 },
             )],
         );
@@ -2083,6 +2824,7 @@ mod cancel_tests {
             method: fake_method,
             params: RawBytes::default(),
             approved: vec![bob], // anne's approval is gone
+            expiration: None,
         };
         let new_proposal_hash = compute_proposal_hash(&new_tx, &rt).unwrap();
         h.assert_transactions(&rt, vec![(TxnID(0), new_tx)]);
@@ -2485,3 +3227,235 @@ fn test_fallback() {
 
     rt.verify();
 }
+
+mod forward_call_tests {
+    use super::*;
+    use fil_actors_runtime::{ActorError, ForwardCallParams, ForwardCallReturn};
+    use fvm_ipld_encoding::IPLD_RAW;
+
+    fn forward_call(
+        rt: &MockRuntime,
+        to: Address,
+        params: Vec<u8>,
+        value: TokenAmount,
+    ) -> Result<Option<IpldBlock>, ActorError> {
+        rt.expect_validate_caller_addr(vec![rt.receiver]);
+        let ret = rt.call::<MultisigActor>(
+            Method::ForwardCallExported as u64,
+            IpldBlock::serialize_cbor(&ForwardCallParams {
+                to,
+                method: 3844450837,
+                codec: IPLD_RAW,
+                params,
+                value,
+            })
+            .unwrap(),
+        );
+        rt.verify();
+        ret
+    }
+
+    #[test]
+    fn forwards_raw_params_within_vested_balance() {
+        let msig = Address::new_id(100);
+        let anne = Address::new_id(101);
+        let target = Address::new_id(102);
+
+        let rt = construct_runtime(msig);
+        let h = util::ActorHarness::new();
+        h.construct_and_verify(&rt, 1, 0, 0, vec![anne]);
+
+        let lock_amount = TokenAmount::from_atto(100_000u32);
+        rt.set_caller(*MULTISIG_ACTOR_CODE_ID, msig);
+        h.lock_balance(&rt, 0, 1000, lock_amount.clone()).unwrap();
+        rt.set_balance(lock_amount);
+        rt.set_epoch(300);
+
+        // only the multisig itself may forward a call, by approving a transaction to do so
+        rt.set_caller(*ACCOUNT_ACTOR_CODE_ID, anne);
+        expect_abort(
+            ExitCode::USR_FORBIDDEN,
+            forward_call(&rt, target, vec![1], TokenAmount::zero()),
+        );
+        rt.reset();
+
+        // the forwarded value can't exceed the vested amount
+        rt.set_caller(*MULTISIG_ACTOR_CODE_ID, msig);
+        expect_abort(
+            ExitCode::USR_INSUFFICIENT_FUNDS,
+            forward_call(&rt, target, vec![1], TokenAmount::from_atto(30_001)),
+        );
+        rt.reset();
+
+        // params are limited in size
+        let max_size = rt.policy.forward_call_max_params_size;
+        expect_abort(
+            ExitCode::USR_ILLEGAL_ARGUMENT,
+            forward_call(&rt, target, vec![0; max_size + 1], TokenAmount::zero()),
+        );
+        rt.reset();
+
+        // the params are sent in their own codec and the return passed back
+        let value = TokenAmount::from_atto(30_000);
+        rt.expect_send_simple(
+            target,
+            3844450837,
+            Some(IpldBlock { codec: IPLD_RAW, data: vec![1, 2] }),
+            value.clone(),
+            Some(IpldBlock { codec: IPLD_RAW, data: vec![3] }),
+            ExitCode::OK,
+        );
+        let ret: ForwardCallReturn =
+            forward_call(&rt, target, vec![1, 2], value).unwrap().unwrap().deserialize().unwrap();
+        assert_eq!(ForwardCallReturn { codec: IPLD_RAW, data: vec![3] }, ret);
+
+        // a failed call aborts with the callee's exit code
+        rt.expect_send_simple(
+            target,
+            3844450837,
+            None,
+            TokenAmount::zero(),
+            None,
+            ExitCode::USR_NOT_FOUND,
+        );
+        rt.expect_validate_caller_addr(vec![rt.receiver]);
+        let params = ForwardCallParams {
+            to: target,
+            method: 3844450837,
+            codec: 0,
+            params: vec![],
+            value: TokenAmount::zero(),
+        };
+        expect_abort(
+            ExitCode::USR_NOT_FOUND,
+            rt.call::<MultisigActor>(
+                Method::ForwardCallExported as u64,
+                IpldBlock::serialize_cbor(&params).unwrap(),
+            ),
+        );
+        rt.verify();
+        check_state(&rt);
+    }
+}
+
+mod upgrade_tests {
+    use super::*;
+    use fil_actor_multisig::migration::{LegacyState, LegacyTransaction};
+    use fil_actor_multisig::{AllowedCall, PendingTxnMap, PENDING_TXN_CONFIG};
+    use fil_actors_runtime::Map2;
+
+    const MSIG: u64 = 1000;
+
+    // A multisig of Anne, Bob and Chuck.
+    fn construct() -> (MockRuntime, util::ActorHarness) {
+        let rt = construct_runtime(Address::new_id(MSIG));
+        let h = util::ActorHarness::new();
+        let signers = vec![
+            Address::new_id(TEST_ANNE_ADDR),
+            Address::new_id(TEST_BOB_ADDR),
+            Address::new_id(TEST_CHUCK_ADDR),
+        ];
+        rt.set_balance(TokenAmount::from_atto(10u8));
+        rt.set_received(TokenAmount::zero());
+        h.construct_and_verify(&rt, 2, 0, 0, signers);
+        (rt, h)
+    }
+
+    // Rewrites the multisig's state and pending transactions in the legacy layout.
+    fn replace_with_legacy_state(rt: &MockRuntime) {
+        let st: State = rt.get_state();
+        let txs =
+            PendingTxnMap::load(&rt.store, &st.pending_txs, PENDING_TXN_CONFIG, "txs").unwrap();
+        let mut legacy_txs =
+            Map2::<_, TxnID, LegacyTransaction>::empty(&rt.store, PENDING_TXN_CONFIG, "legacy txs");
+        txs.for_each(|id, txn| {
+            let txn = txn.clone();
+            let legacy = LegacyTransaction {
+                to: txn.to,
+                value: txn.value,
+                method: txn.method,
+                params: txn.params,
+                approved: txn.approved,
+            };
+            legacy_txs.set(&id, legacy).map(|_| ())
+        })
+        .unwrap();
+        rt.replace_state(&LegacyState {
+            signers: st.signers,
+            num_approvals_threshold: st.num_approvals_threshold,
+            next_tx_id: st.next_tx_id,
+            initial_balance: st.initial_balance,
+            start_epoch: st.start_epoch,
+            unlock_duration: st.unlock_duration,
+            pending_txs: legacy_txs.flush().unwrap(),
+        });
+    }
+
+    fn setup() -> (MockRuntime, util::ActorHarness) {
+        let (rt, h) = construct();
+        replace_with_legacy_state(&rt);
+        (rt, h)
+    }
+
+    #[test]
+    fn upgrade_migrates_legacy_state() {
+        let (rt, h) = setup();
+        rt.upgrade::<MultisigActor>();
+        let st: State = rt.get_state();
+        assert_eq!(3, st.signers.len());
+        assert_eq!(2, st.num_approvals_threshold);
+        // every signer has the default weight
+        assert!(st.signer_weights.is_empty());
+        assert_eq!(3, st.total_signer_weight());
+        check_state(&rt);
+
+        // Signers of a migrated multisig can be weighted.
+        rt.set_caller(*MULTISIG_ACTOR_CODE_ID, Address::new_id(MSIG));
+        h.set_signer_weight(&rt, Address::new_id(TEST_ANNE_ADDR), 2).unwrap();
+        let st: State = rt.get_state();
+        assert_eq!(4, st.total_signer_weight());
+        check_state(&rt);
+    }
+
+    #[test]
+    fn upgrade_leaves_signers_unrestricted() {
+        let (rt, h) = setup();
+        rt.upgrade::<MultisigActor>();
+        let st: State = rt.get_state();
+        assert!(st.signer_roles.is_empty());
+
+        // Signers of a migrated multisig can be restricted to allowed calls.
+        let bob = Address::new_id(TEST_BOB_ADDR);
+        let target = Address::new_id(1234);
+        let allowed = vec![AllowedCall { to: target, method: 42 }];
+        rt.set_caller(*MULTISIG_ACTOR_CODE_ID, Address::new_id(MSIG));
+        h.set_signer_role(&rt, bob, allowed.clone()).unwrap();
+        let st: State = rt.get_state();
+        assert_eq!(allowed, st.signer_allowed_calls(&bob));
+        assert!(!st.signer_may_call(&bob, &target, METHOD_SEND));
+        check_state(&rt);
+    }
+
+    #[test]
+    fn upgrade_migrates_pending_transactions() {
+        let (rt, h) = construct();
+        let to = Address::new_id(TEST_DARLENE_ADDR);
+        let value = TokenAmount::from_atto(1u8);
+        rt.set_caller(*ACCOUNT_ACTOR_CODE_ID, Address::new_id(TEST_ANNE_ADDR));
+        let hash = h.propose_ok(&rt, to, value.clone(), METHOD_SEND, RawBytes::default());
+        replace_with_legacy_state(&rt);
+
+        rt.upgrade::<MultisigActor>();
+        let txn = h.get_transaction(&rt, TxnID(0)).unwrap();
+        assert_eq!(vec![Address::new_id(TEST_ANNE_ADDR)], txn.approved);
+        assert_eq!(None, txn.expiration);
+        check_state(&rt);
+
+        // The migrated transaction executes once approved.
+        rt.set_caller(*ACCOUNT_ACTOR_CODE_ID, Address::new_id(TEST_BOB_ADDR));
+        rt.expect_send_simple(to, METHOD_SEND, None, value, None, ExitCode::OK);
+        h.approve_ok(&rt, TxnID(0), hash);
+        h.assert_transactions(&rt, vec![]);
+        check_state(&rt);
+    }
+}
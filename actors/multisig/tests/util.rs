@@ -1,11 +1,15 @@
 use fil_actor_multisig::{
-    compute_proposal_hash, Actor, AddSignerParams, ApproveReturn, ConstructorParams, Method,
-    PendingTxnMap, ProposeParams, ProposeReturn, RemoveSignerParams, State, SwapSignerParams,
-    Transaction, TxnID, TxnIDParams, PENDING_TXN_CONFIG,
+    compute_proposal_hash, Actor, AddSignerParams, ApproveReturn, ConstructorParams,
+    GetTransactionParams, GetTransactionReturn, ListPendingTransactionsParams,
+    ListPendingTransactionsReturn, Method, PendingTxnMap, ProposeParams, ProposeReturn,
+    ProposeWithApprovalsParams, PruneExpiredParams, PruneExpiredReturn, RemoveSignerParams,
+    SetSignerRoleParams, SetSignerWeightParams, SignerApproval, SignerWeight, State,
+    SwapSignerParams, Transaction, TxnID, TxnIDParams, PENDING_TXN_CONFIG,
 };
-use fil_actor_multisig::{ChangeNumApprovalsThresholdParams, LockBalanceParams};
+use fil_actor_multisig::{AllowedCall, ChangeNumApprovalsThresholdParams, LockBalanceParams};
 use fil_actors_runtime::test_utils::*;
 use fil_actors_runtime::ActorError;
+use fil_actors_runtime::EventBuilder;
 use fil_actors_runtime::INIT_ACTOR_ADDR;
 use fvm_ipld_encoding::RawBytes;
 use fvm_shared::address::Address;
@@ -31,12 +35,32 @@ impl ActorHarness {
         unlock_duration: ChainEpoch,
         start_epoch: ChainEpoch,
         initial_signers: Vec<Address>,
+    ) {
+        self.construct_weighted_and_verify(
+            rt,
+            initial_approvals,
+            unlock_duration,
+            start_epoch,
+            initial_signers,
+            vec![],
+        )
+    }
+
+    pub fn construct_weighted_and_verify(
+        &self,
+        rt: &MockRuntime,
+        initial_approvals: u64,
+        unlock_duration: ChainEpoch,
+        start_epoch: ChainEpoch,
+        initial_signers: Vec<Address>,
+        signer_weights: Vec<SignerWeight>,
     ) {
         let params = ConstructorParams {
             signers: initial_signers,
             num_approvals_threshold: initial_approvals,
             unlock_duration,
             start_epoch,
+            signer_weights,
         };
         rt.set_caller(*INIT_ACTOR_CODE_ID, INIT_ACTOR_ADDR);
         rt.expect_validate_caller_addr(vec![INIT_ACTOR_ADDR]);
@@ -91,6 +115,38 @@ impl ActorHarness {
         ret
     }
 
+    pub fn set_signer_weight(
+        &self,
+        rt: &MockRuntime,
+        signer: Address,
+        weight: u64,
+    ) -> Result<Option<IpldBlock>, ActorError> {
+        rt.expect_validate_caller_addr(vec![rt.receiver]);
+        let params = SetSignerWeightParams { signer, weight };
+        let ret = rt.call::<Actor>(
+            Method::SetSignerWeight as u64,
+            IpldBlock::serialize_cbor(&params).unwrap(),
+        );
+        rt.verify();
+        ret
+    }
+
+    pub fn set_signer_role(
+        &self,
+        rt: &MockRuntime,
+        signer: Address,
+        allowed: Vec<AllowedCall>,
+    ) -> Result<Option<IpldBlock>, ActorError> {
+        rt.expect_validate_caller_addr(vec![rt.receiver]);
+        let params = SetSignerRoleParams { signer, allowed };
+        let ret = rt.call::<Actor>(
+            Method::SetSignerRole as u64,
+            IpldBlock::serialize_cbor(&params).unwrap(),
+        );
+        rt.verify();
+        ret
+    }
+
     pub fn propose_ok(
         &self,
         rt: &MockRuntime,
@@ -102,7 +158,14 @@ impl ActorHarness {
         let ret = self.propose(rt, to, value.clone(), method, params.clone());
         ret.unwrap().unwrap().deserialize::<ProposeReturn>().unwrap();
         // compute proposal hash
-        let txn = Transaction { to, value, method, params, approved: vec![*rt.caller.borrow()] };
+        let txn = Transaction {
+            to,
+            value,
+            method,
+            params,
+            approved: vec![*rt.caller.borrow()],
+            expiration: None,
+        };
         compute_proposal_hash(&txn, rt).unwrap()
     }
 
@@ -123,22 +186,65 @@ impl ActorHarness {
         method: MethodNum,
         params: RawBytes,
     ) -> Result<Option<IpldBlock>, ActorError> {
+        self.propose_expiring(rt, to, value, method, params, None)
+    }
+
+    pub fn propose_expiring(
+        &self,
+        rt: &MockRuntime,
+        to: Address,
+        value: TokenAmount,
+        method: MethodNum,
+        params: RawBytes,
+        expiration: Option<ChainEpoch>,
+    ) -> Result<Option<IpldBlock>, ActorError> {
+        let st: State = rt.get_state();
+        let caller = *rt.caller.borrow();
+        expect_approval_events(rt, &st, st.next_tx_id, &[], &[caller]);
         rt.expect_validate_caller_any();
-        let propose_params = ProposeParams { to, value, method, params };
+        let propose_params = ProposeParams { to, value, method, params, expiration };
         let ret = rt.call::<Actor>(
             Method::Propose as u64,
             IpldBlock::serialize_cbor(&propose_params).unwrap(),
         );
-        rt.verify();
+        verify_call(rt, &ret);
         ret
     }
 
+    pub fn propose_with_approvals(
+        &self,
+        rt: &MockRuntime,
+        to: Address,
+        value: TokenAmount,
+        method: MethodNum,
+        params: RawBytes,
+        approvals: Vec<SignerApproval>,
+    ) -> Result<ProposeReturn, ActorError> {
+        let st: State = rt.get_state();
+        let mut approvers = vec![*rt.caller.borrow()];
+        approvers.extend(approvals.iter().map(|a| a.signer));
+        expect_approval_events(rt, &st, st.next_tx_id, &[], &approvers);
+        rt.expect_validate_caller_any();
+        let propose_params = ProposeWithApprovalsParams { to, value, method, params, approvals };
+        let ret = rt.call::<Actor>(
+            Method::ProposeWithApprovalsExported as u64,
+            IpldBlock::serialize_cbor(&propose_params).unwrap(),
+        );
+        verify_call(rt, &ret);
+        Ok(ret?.unwrap().deserialize().unwrap())
+    }
+
     pub fn approve(
         &self,
         rt: &MockRuntime,
         txn_id: TxnID,
         proposal_hash: [u8; 32],
     ) -> Result<Option<IpldBlock>, ActorError> {
+        let st: State = rt.get_state();
+        if let Some(txn) = get_pending_transaction(rt, &st, txn_id) {
+            let caller = *rt.caller.borrow();
+            expect_approval_events(rt, &st, txn_id, &txn.approved, &[caller]);
+        }
         rt.expect_validate_caller_any();
         let approve_params =
             TxnIDParams { id: txn_id, proposal_hash: Vec::<u8>::from(proposal_hash) };
@@ -146,16 +252,56 @@ impl ActorHarness {
             Method::Approve as u64,
             IpldBlock::serialize_cbor(&approve_params).unwrap(),
         );
-        rt.verify();
+        verify_call(rt, &ret);
         ret
     }
 
+    pub fn prune_expired(
+        &self,
+        rt: &MockRuntime,
+        ids: Vec<TxnID>,
+    ) -> Result<PruneExpiredReturn, ActorError> {
+        rt.expect_validate_caller_any();
+        let ret = rt.call::<Actor>(
+            Method::PruneExpiredExported as u64,
+            IpldBlock::serialize_cbor(&PruneExpiredParams { ids }).unwrap(),
+        );
+        rt.verify();
+        Ok(ret?.unwrap().deserialize().unwrap())
+    }
+
+    pub fn get_transaction(&self, rt: &MockRuntime, id: TxnID) -> Result<Transaction, ActorError> {
+        rt.expect_validate_caller_any();
+        let ret = rt.call::<Actor>(
+            Method::GetTransactionExported as u64,
+            IpldBlock::serialize_cbor(&GetTransactionParams { id }).unwrap(),
+        );
+        rt.verify();
+        Ok(ret?.unwrap().deserialize::<GetTransactionReturn>().unwrap().transaction)
+    }
+
+    pub fn list_pending_transactions(
+        &self,
+        rt: &MockRuntime,
+        cursor: Option<TxnID>,
+        limit: u64,
+    ) -> Result<ListPendingTransactionsReturn, ActorError> {
+        rt.expect_validate_caller_any();
+        let ret = rt.call::<Actor>(
+            Method::ListPendingTransactionsExported as u64,
+            IpldBlock::serialize_cbor(&ListPendingTransactionsParams { cursor, limit }).unwrap(),
+        );
+        rt.verify();
+        Ok(ret?.unwrap().deserialize().unwrap())
+    }
+
     pub fn cancel(
         &self,
         rt: &MockRuntime,
         txn_id: TxnID,
         proposal_hash: [u8; 32],
     ) -> Result<Option<IpldBlock>, ActorError> {
+        expect_txn_event(rt, "transaction-cancelled", txn_id, &rt.caller.borrow());
         rt.expect_validate_caller_any();
         let cancel_params =
             TxnIDParams { id: txn_id, proposal_hash: Vec::<u8>::from(proposal_hash) };
@@ -163,7 +309,7 @@ impl ActorHarness {
             Method::Cancel as u64,
             IpldBlock::serialize_cbor(&cancel_params).unwrap(),
         );
-        rt.verify();
+        verify_call(rt, &ret);
         ret
     }
 
@@ -219,3 +365,53 @@ impl ActorHarness {
         assert_eq!(expect_txns, actual_txns);
     }
 }
+
+pub fn expect_txn_event(rt: &MockRuntime, typ: &str, id: TxnID, signer: &Address) {
+    rt.expect_emitted_event(
+        EventBuilder::new()
+            .typ(typ)
+            .field_indexed("id", &id)
+            .field_indexed("signer", signer)
+            .build()
+            .unwrap(),
+    );
+}
+
+// Expects the events of a successful call by the current caller which records approvals from
+// `approvers` on a transaction already approved by `approved` (and proposes it if that is empty),
+// executing the transaction once the approval threshold is met.
+pub fn expect_approval_events(
+    rt: &MockRuntime,
+    st: &State,
+    id: TxnID,
+    approved: &[Address],
+    approvers: &[Address],
+) {
+    let mut approved = approved.to_vec();
+    // A transaction that already meets the threshold is executed without a further approval.
+    if st.approval_weight(&approved) < st.num_approvals_threshold {
+        for approver in approvers {
+            let typ =
+                if approved.is_empty() { "transaction-proposed" } else { "transaction-approved" };
+            expect_txn_event(rt, typ, id, approver);
+            approved.push(*approver);
+        }
+    }
+    if st.approval_weight(&approved) >= st.num_approvals_threshold {
+        expect_txn_event(rt, "transaction-executed", id, &rt.caller.borrow());
+    }
+}
+
+fn get_pending_transaction(rt: &MockRuntime, st: &State, id: TxnID) -> Option<Transaction> {
+    let ptx =
+        PendingTxnMap::load(&rt.store, &st.pending_txs, PENDING_TXN_CONFIG, "pending").unwrap();
+    ptx.get(&id).unwrap().cloned()
+}
+
+// Verifies expectations after a call, discarding any events that a failed call didn't emit.
+fn verify_call<T>(rt: &MockRuntime, ret: &Result<T, ActorError>) {
+    if ret.is_err() {
+        rt.expectations.borrow_mut().expect_emitted_events.clear();
+    }
+    rt.verify();
+}
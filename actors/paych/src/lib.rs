@@ -4,8 +4,8 @@
 use fil_actors_runtime::runtime::builtins::Type;
 use fil_actors_runtime::runtime::{ActorCode, Runtime};
 use fil_actors_runtime::{
-    actor_dispatch, actor_error, deserialize_block, extract_send_result, resolve_to_actor_id,
-    ActorContext, ActorDowncast, ActorError, Array,
+    actor_dispatch, actor_error, deserialize_block, extract_send_result, migrate_state,
+    resolve_to_actor_id, ActorContext, ActorDowncast, ActorError, Array, OnUpgradeParams,
 };
 use fvm_ipld_blockstore::Blockstore;
 use fvm_ipld_encoding::CBOR;
@@ -21,12 +21,19 @@ use num_traits::Zero;
 
 pub use self::state::{LaneState, Merge, State};
 pub use self::types::*;
+use crate::migration::LegacyState;
 
 #[cfg(feature = "fil-actor")]
 fil_actors_runtime::wasm_trampoline!(Actor);
 
+#[cfg(feature = "testing")]
 pub mod ext;
+#[cfg(not(feature = "testing"))]
+#[allow(dead_code)]
+mod ext;
+pub mod migration;
 mod state;
+#[cfg(feature = "testing")]
 pub mod testing;
 mod types;
 
@@ -40,6 +47,10 @@ pub enum Method {
     UpdateChannelState = 2,
     Settle = 3,
     Collect = 4,
+    // Method numbers derived from FRC-0042 standards
+    MergeLanesExported = frc42_dispatch::method_hash!("MergeLanes"),
+    CloseLaneExported = frc42_dispatch::method_hash!("CloseLane"),
+    ValidateVoucherExported = frc42_dispatch::method_hash!("ValidateVoucher"),
 }
 
 pub const ERR_CHANNEL_STATE_UPDATE_AFTER_SETTLED: ExitCode = ExitCode::new(32);
@@ -79,184 +90,117 @@ impl Actor {
         let signer = if rt.message().caller() == st.from { st.to } else { st.from };
         let sv = params.sv;
 
-        // Pull signature from signed voucher
-        let sig = &sv
-            .signature
-            .as_ref()
-            .ok_or_else(|| actor_error!(illegal_argument, "voucher has no signature"))?
-            .bytes;
-
-        if st.settling_at != 0 && rt.curr_epoch() >= st.settling_at {
-            return Err(ActorError::unchecked(
-                ERR_CHANNEL_STATE_UPDATE_AFTER_SETTLED,
-                "no vouchers can be processed after settling at epoch".to_string(),
-            ));
-        }
+        verify_voucher(rt, &st, &signer, &sv, &params.secret, SendFlags::empty())?;
 
-        if params.secret.len() > MAX_SECRET_SIZE {
-            return Err(actor_error!(illegal_argument, "secret must be at most 256 bytes long"));
-        }
+        rt.transaction(|st: &mut State, rt| {
+            let mut l_states = Array::load(&st.lane_states, rt.store()).map_err(|e| {
+                e.downcast_default(ExitCode::USR_ILLEGAL_STATE, "failed to load lane states")
+            })?;
 
-        // Generate unsigned bytes
-        let sv_bz = sv.signing_bytes().map_err(|e| {
-            ActorError::serialization(format!("failed to serialized SignedVoucher: {}", e))
-        })?;
+            apply_voucher(st, &mut l_states, &sv, &rt.current_balance())?;
 
-        // Validate signature
+            st.lane_states = l_states.flush().map_err(|e| {
+                e.downcast_default(ExitCode::USR_ILLEGAL_STATE, "failed to save lanes")
+            })?;
+            Ok(())
+        })
+    }
 
-        if !extract_send_result(rt.send(
-            &signer,
-            ext::account::AUTHENTICATE_MESSAGE_METHOD,
-            IpldBlock::serialize_cbor(&ext::account::AuthenticateMessageParams {
-                signature: sig.to_vec(),
-                message: sv_bz,
-            })?,
-            TokenAmount::zero(),
-            None,
-            SendFlags::READ_ONLY,
-        ))
-        .and_then(deserialize_block)
-        .context("proposal authentication failed")?
-        {
-            return Err(actor_error!(illegal_argument, "voucher sig authentication failed"));
-        }
+    /// Checks that a voucher signed by the channel's payer could be redeemed by the recipient
+    /// now, without updating the channel.
+    /// Returns the amount by which redeeming it would increase the amount payable to the
+    /// recipient, or aborts with the error redemption would fail with.
+    /// Any verification method specified by the voucher is invoked read-only.
+    pub fn validate_voucher(
+        rt: &impl Runtime,
+        params: ValidateVoucherParams,
+    ) -> Result<ValidateVoucherReturn, ActorError> {
+        rt.validate_immediate_caller_accept_any()?;
+        let mut st: State = rt.state()?;
+        let signer = st.from;
 
-        let pch_addr = rt.message().receiver();
-        let svpch_id = rt.resolve_address(&sv.channel_addr).ok_or_else(|| {
-            actor_error!(
-                illegal_argument,
-                "voucher payment channel address {} does not resolve to an ID address",
-                sv.channel_addr
-            )
-        })?;
-        if pch_addr != Address::new_id(svpch_id) {
-            return Err(actor_error!(illegal_argument;
-                    "voucher payment channel address {} does not match receiver {}",
-                    svpch_id, pch_addr));
-        }
+        verify_voucher(rt, &st, &signer, &params.sv, &params.secret, SendFlags::READ_ONLY)?;
 
-        if rt.curr_epoch() < sv.time_lock_min {
-            return Err(actor_error!(illegal_argument; "cannot use this voucher yet"));
-        }
+        let mut l_states = Array::load(&st.lane_states, rt.store()).map_err(|e| {
+            e.downcast_default(ExitCode::USR_ILLEGAL_STATE, "failed to load lane states")
+        })?;
+        let amount = apply_voucher(&mut st, &mut l_states, &params.sv, &rt.current_balance())?;
+        Ok(ValidateVoucherReturn { amount })
+    }
 
-        if sv.time_lock_max != 0 && rt.curr_epoch() > sv.time_lock_max {
-            return Err(actor_error!(illegal_argument; "this voucher has expired"));
-        }
+    /// Merges the redeemed amounts of lanes into another existing lane, closing the merged lanes.
+    /// Vouchers for a closed lane can no longer be redeemed, and subsequent vouchers for the
+    /// lane merged into are redeemed net of the merged amounts.
+    /// Only the recipient may merge lanes, since doing so can only reduce its future payouts.
+    pub fn merge_lanes(rt: &impl Runtime, params: MergeLanesParams) -> Result<(), ActorError> {
+        let st: State = rt.state()?;
+        rt.validate_immediate_caller_is(std::iter::once(&st.to))?;
 
-        if sv.amount.is_negative() {
-            return Err(actor_error!(illegal_argument;
-                    "voucher amount must be non-negative, was {}", sv.amount));
+        if params.merges.is_empty() {
+            return Err(actor_error!(illegal_argument, "no lanes to merge"));
         }
 
-        if !sv.secret_pre_image.is_empty() {
-            let hashed_secret: &[u8] = &rt.hash_blake2b(&params.secret);
-            if hashed_secret != sv.secret_pre_image.as_slice() {
-                return Err(actor_error!(illegal_argument; "incorrect secret"));
+        rt.transaction(|st: &mut State, rt| {
+            if st.settling_at != 0 && rt.curr_epoch() >= st.settling_at {
+                return Err(ActorError::unchecked(
+                    ERR_CHANNEL_STATE_UPDATE_AFTER_SETTLED,
+                    "no lanes can be merged after settling at epoch".to_string(),
+                ));
             }
-        }
-
-        if let Some(extra) = &sv.extra {
-            extract_send_result(rt.send_simple(
-                &extra.actor,
-                extra.method,
-                Some(IpldBlock { codec: CBOR, data: extra.data.to_vec() }),
-                TokenAmount::zero(),
-            ))
-            .map_err(|e| e.wrap("spend voucher verification failed"))?;
-        }
 
-        rt.transaction(|st: &mut State, rt| {
             let mut l_states = Array::load(&st.lane_states, rt.store()).map_err(|e| {
                 e.downcast_default(ExitCode::USR_ILLEGAL_STATE, "failed to load lane states")
             })?;
 
-            // Find the voucher lane, create and insert it in sorted order if necessary.
-            let lane_id = sv.lane;
-            let lane_state = find_lane(&l_states, lane_id)?;
-
-            let mut lane_state = if let Some(state) = lane_state {
-                if state.nonce >= sv.nonce {
-                    return Err(actor_error!(illegal_argument;
-                        "voucher has an outdated nonce, existing: {}, voucher: {}, cannot redeem",
-                        state.nonce, sv.nonce));
-                }
-                state.clone()
-            } else {
-                LaneState::default()
-            };
-
-            // The next section actually calculates the payment amounts to update
-            // the payment channel state
-            // 1. (optional) sum already redeemed value of all merging lanes
-            let mut redeemed_from_others = TokenAmount::zero();
-            for merge in sv.merges {
-                if merge.lane == sv.lane {
-                    return Err(actor_error!(illegal_argument;
-                        "voucher cannot merge lanes into it's own lane"));
-                }
-                let mut other_ls = find_lane(&l_states, merge.lane)?
-                    .ok_or_else(|| {
-                        actor_error!(illegal_argument;
-                        "voucher specifies invalid merge lane {}", merge.lane)
-                    })?
-                    .clone();
-
-                if other_ls.nonce >= merge.nonce {
+            let mut lane_state = find_lane(&l_states, params.lane)?
+                .ok_or_else(|| actor_error!(not_found; "lane {} not found", params.lane))?
+                .clone();
+            for merge in params.merges {
+                if merge == params.lane {
                     return Err(actor_error!(illegal_argument;
-                            "merged lane in voucher has outdated nonce, cannot redeem"));
+                        "cannot merge lane {} into itself", merge));
                 }
-
-                redeemed_from_others += &other_ls.redeemed;
-                other_ls.nonce = merge.nonce;
-                l_states.set(merge.lane, other_ls).map_err(|e| {
-                    e.downcast_default(
-                        ExitCode::USR_ILLEGAL_STATE,
-                        format!("failed to store lane {}", merge.lane),
-                    )
-                })?;
+                lane_state.redeemed += remove_lane(&mut l_states, merge)?.redeemed;
+                st.closed_lanes.set(merge);
             }
 
-            // 2. To prevent double counting, remove already redeemed amounts (from
-            // voucher or other lanes) from the voucher amount
-            lane_state.nonce = sv.nonce;
-            let balance_delta = &sv.amount - (redeemed_from_others + &lane_state.redeemed);
-
-            // 3. set new redeemed value for merged-into lane
-            lane_state.redeemed = sv.amount;
-
-            // 4. check operation validity
-            let new_send_balance = balance_delta + &st.to_send;
-
-            if new_send_balance < TokenAmount::zero() {
-                return Err(actor_error!(illegal_argument;
-                    "voucher would leave channel balance negative"));
-            }
+            l_states.set(params.lane, lane_state).map_err(|e| {
+                e.downcast_default(
+                    ExitCode::USR_ILLEGAL_STATE,
+                    format!("failed to store lane {}", params.lane),
+                )
+            })?;
 
-            if new_send_balance > rt.current_balance() {
-                return Err(actor_error!(illegal_argument;
-                    "not enough funds in channel to cover voucher"));
-            }
+            st.lane_states = l_states.flush().map_err(|e| {
+                e.downcast_default(ExitCode::USR_ILLEGAL_STATE, "failed to save lanes")
+            })?;
+            Ok(())
+        })
+    }
 
-            // 5. add new redemption ToSend
-            st.to_send = new_send_balance;
+    /// Closes a lane, removing it from the channel's state.
+    /// The amount already redeemed by the lane remains payable, but vouchers for the lane can no
+    /// longer be redeemed.
+    /// Only the recipient may close a lane, since doing so can only reduce its future payouts.
+    pub fn close_lane(rt: &impl Runtime, params: CloseLaneParams) -> Result<(), ActorError> {
+        let st: State = rt.state()?;
+        rt.validate_immediate_caller_is(std::iter::once(&st.to))?;
 
-            // update channel settlingAt and MinSettleHeight if delayed by voucher
-            if sv.min_settle_height != 0 {
-                if st.settling_at != 0 && st.settling_at < sv.min_settle_height {
-                    st.settling_at = sv.min_settle_height;
-                }
-                if st.min_settle_height < sv.min_settle_height {
-                    st.min_settle_height = sv.min_settle_height;
-                }
+        rt.transaction(|st: &mut State, rt| {
+            if st.settling_at != 0 && rt.curr_epoch() >= st.settling_at {
+                return Err(ActorError::unchecked(
+                    ERR_CHANNEL_STATE_UPDATE_AFTER_SETTLED,
+                    "no lanes can be closed after settling at epoch".to_string(),
+                ));
             }
 
-            l_states.set(lane_id, lane_state).map_err(|e| {
-                e.downcast_default(
-                    ExitCode::USR_ILLEGAL_STATE,
-                    format!("failed to store lane {}", lane_id),
-                )
+            let mut l_states = Array::load(&st.lane_states, rt.store()).map_err(|e| {
+                e.downcast_default(ExitCode::USR_ILLEGAL_STATE, "failed to load lane states")
             })?;
 
+            remove_lane(&mut l_states, params.lane)?;
+            st.closed_lanes.set(params.lane);
+
             st.lane_states = l_states.flush().map_err(|e| {
                 e.downcast_default(ExitCode::USR_ILLEGAL_STATE, "failed to save lanes")
             })?;
@@ -303,6 +247,208 @@ impl Actor {
     }
 }
 
+/// Checks a voucher's signature by the given signer, and that it is redeemable against this
+/// channel at the current epoch, independent of the channel's lanes.
+/// The voucher's verification method, if any, is invoked with the given send flags.
+fn verify_voucher(
+    rt: &impl Runtime,
+    st: &State,
+    signer: &Address,
+    sv: &SignedVoucher,
+    secret: &[u8],
+    extra_flags: SendFlags,
+) -> Result<(), ActorError> {
+    // Pull signature from signed voucher
+    let sig = &sv
+        .signature
+        .as_ref()
+        .ok_or_else(|| actor_error!(illegal_argument, "voucher has no signature"))?
+        .bytes;
+
+    if st.settling_at != 0 && rt.curr_epoch() >= st.settling_at {
+        return Err(ActorError::unchecked(
+            ERR_CHANNEL_STATE_UPDATE_AFTER_SETTLED,
+            "no vouchers can be processed after settling at epoch".to_string(),
+        ));
+    }
+
+    if secret.len() > MAX_SECRET_SIZE {
+        return Err(actor_error!(illegal_argument, "secret must be at most 256 bytes long"));
+    }
+
+    // Generate unsigned bytes
+    let sv_bz = sv.signing_bytes().map_err(|e| {
+        ActorError::serialization(format!("failed to serialized SignedVoucher: {}", e))
+    })?;
+
+    // Validate signature
+
+    if !extract_send_result(rt.send(
+        signer,
+        ext::account::AUTHENTICATE_MESSAGE_METHOD,
+        IpldBlock::serialize_cbor(&ext::account::AuthenticateMessageParams {
+            signature: sig.to_vec(),
+            message: sv_bz,
+        })?,
+        TokenAmount::zero(),
+        None,
+        SendFlags::READ_ONLY,
+    ))
+    .and_then(deserialize_block)
+    .context("proposal authentication failed")?
+    {
+        return Err(actor_error!(illegal_argument, "voucher sig authentication failed"));
+    }
+
+    let pch_addr = rt.message().receiver();
+    let svpch_id = rt.resolve_address(&sv.channel_addr).ok_or_else(|| {
+        actor_error!(
+            illegal_argument,
+            "voucher payment channel address {} does not resolve to an ID address",
+            sv.channel_addr
+        )
+    })?;
+    if pch_addr != Address::new_id(svpch_id) {
+        return Err(actor_error!(illegal_argument;
+                "voucher payment channel address {} does not match receiver {}",
+                svpch_id, pch_addr));
+    }
+
+    if rt.curr_epoch() < sv.time_lock_min {
+        return Err(actor_error!(illegal_argument; "cannot use this voucher yet"));
+    }
+
+    if sv.time_lock_max != 0 && rt.curr_epoch() > sv.time_lock_max {
+        return Err(actor_error!(illegal_argument; "this voucher has expired"));
+    }
+
+    if sv.amount.is_negative() {
+        return Err(actor_error!(illegal_argument;
+                "voucher amount must be non-negative, was {}", sv.amount));
+    }
+
+    if !sv.secret_pre_image.is_empty() {
+        let hashed_secret: &[u8] = &rt.hash_blake2b(secret);
+        if hashed_secret != sv.secret_pre_image.as_slice() {
+            return Err(actor_error!(illegal_argument; "incorrect secret"));
+        }
+    }
+
+    if let Some(extra) = &sv.extra {
+        extract_send_result(rt.send(
+            &extra.actor,
+            extra.method,
+            Some(IpldBlock { codec: CBOR, data: extra.data.to_vec() }),
+            TokenAmount::zero(),
+            None,
+            extra_flags,
+        ))
+        .map_err(|e| e.wrap("spend voucher verification failed"))?;
+    }
+    Ok(())
+}
+
+/// Redeems a voucher against the channel's lanes, given the channel's balance.
+/// The lanes are updated but not flushed.
+/// Returns the increase in the amount payable to the recipient.
+fn apply_voucher<BS>(
+    st: &mut State,
+    l_states: &mut Array<LaneState, BS>,
+    sv: &SignedVoucher,
+    balance: &TokenAmount,
+) -> Result<TokenAmount, ActorError>
+where
+    BS: Blockstore,
+{
+    // Find the voucher lane, create and insert it in sorted order if necessary.
+    let lane_id = sv.lane;
+    let lane_state = find_lane(l_states, lane_id)?;
+    if st.closed_lanes.get(lane_id) {
+        return Err(actor_error!(illegal_argument; "voucher lane {} is closed", lane_id));
+    }
+
+    let mut lane_state = if let Some(state) = lane_state {
+        if state.nonce >= sv.nonce {
+            return Err(actor_error!(illegal_argument;
+                "voucher has an outdated nonce, existing: {}, voucher: {}, cannot redeem",
+                state.nonce, sv.nonce));
+        }
+        state.clone()
+    } else {
+        LaneState::default()
+    };
+
+    // The next section actually calculates the payment amounts to update
+    // the payment channel state
+    // 1. (optional) sum already redeemed value of all merging lanes
+    let mut redeemed_from_others = TokenAmount::zero();
+    for merge in &sv.merges {
+        if merge.lane == sv.lane {
+            return Err(actor_error!(illegal_argument;
+                "voucher cannot merge lanes into it's own lane"));
+        }
+        let mut other_ls = find_lane(l_states, merge.lane)?
+            .ok_or_else(|| {
+                actor_error!(illegal_argument;
+                "voucher specifies invalid merge lane {}", merge.lane)
+            })?
+            .clone();
+
+        if other_ls.nonce >= merge.nonce {
+            return Err(actor_error!(illegal_argument;
+                    "merged lane in voucher has outdated nonce, cannot redeem"));
+        }
+
+        redeemed_from_others += &other_ls.redeemed;
+        other_ls.nonce = merge.nonce;
+        l_states.set(merge.lane, other_ls).map_err(|e| {
+            e.downcast_default(
+                ExitCode::USR_ILLEGAL_STATE,
+                format!("failed to store lane {}", merge.lane),
+            )
+        })?;
+    }
+
+    // 2. To prevent double counting, remove already redeemed amounts (from
+    // voucher or other lanes) from the voucher amount
+    lane_state.nonce = sv.nonce;
+    let balance_delta = &sv.amount - (redeemed_from_others + &lane_state.redeemed);
+
+    // 3. set new redeemed value for merged-into lane
+    lane_state.redeemed = sv.amount.clone();
+
+    // 4. check operation validity
+    let new_send_balance = &balance_delta + &st.to_send;
+
+    if new_send_balance < TokenAmount::zero() {
+        return Err(actor_error!(illegal_argument;
+            "voucher would leave channel balance negative"));
+    }
+
+    if &new_send_balance > balance {
+        return Err(actor_error!(illegal_argument;
+            "not enough funds in channel to cover voucher"));
+    }
+
+    // 5. add new redemption ToSend
+    st.to_send = new_send_balance;
+
+    // update channel settlingAt and MinSettleHeight if delayed by voucher
+    if sv.min_settle_height != 0 {
+        if st.settling_at != 0 && st.settling_at < sv.min_settle_height {
+            st.settling_at = sv.min_settle_height;
+        }
+        if st.min_settle_height < sv.min_settle_height {
+            st.min_settle_height = sv.min_settle_height;
+        }
+    }
+
+    l_states.set(lane_id, lane_state).map_err(|e| {
+        e.downcast_default(ExitCode::USR_ILLEGAL_STATE, format!("failed to store lane {}", lane_id))
+    })?;
+    Ok(balance_delta)
+}
+
 #[inline]
 fn find_lane<'a, BS>(
     ls: &'a Array<LaneState, BS>,
@@ -320,6 +466,21 @@ where
     })
 }
 
+fn remove_lane<BS>(ls: &mut Array<LaneState, BS>, id: u64) -> Result<LaneState, ActorError>
+where
+    BS: Blockstore,
+{
+    if id > MAX_LANE {
+        return Err(actor_error!(illegal_argument; "maximum lane ID is 2^63-1"));
+    }
+
+    ls.delete(id)
+        .map_err(|e| {
+            e.downcast_default(ExitCode::USR_ILLEGAL_STATE, format!("failed to delete lane {}", id))
+        })?
+        .ok_or_else(|| actor_error!(not_found; "lane {} not found", id))
+}
+
 impl ActorCode for Actor {
     type Methods = Method;
 
@@ -327,10 +488,21 @@ impl ActorCode for Actor {
         "PaymentChannel"
     }
 
+    fn on_upgrade<RT>(rt: &RT, params: OnUpgradeParams) -> Result<(), ActorError>
+    where
+        RT: Runtime,
+        RT::Blockstore: Clone,
+    {
+        migrate_state(rt, &params.old_state, |_, legacy: LegacyState| Ok(State::from(legacy)))
+    }
+
     actor_dispatch! {
         Constructor => constructor,
         UpdateChannelState => update_channel_state,
         Settle => settle,
         Collect => collect,
+        MergeLanesExported => merge_lanes,
+        CloseLaneExported => close_lane,
+        ValidateVoucherExported => validate_voucher,
     }
 }
@@ -0,0 +1,38 @@
+// Copyright 2019-2022 ChainSafe Systems
+// SPDX-License-Identifier: Apache-2.0, MIT
+
+use cid::Cid;
+use fvm_ipld_bitfield::BitField;
+use fvm_ipld_encoding::tuple::*;
+use fvm_shared::address::Address;
+use fvm_shared::clock::ChainEpoch;
+use fvm_shared::econ::TokenAmount;
+
+use crate::State;
+
+/// Payment channel state as stored before lanes could be closed.
+#[derive(Debug, Serialize_tuple, Deserialize_tuple, Clone)]
+pub struct LegacyState {
+    pub from: Address,
+    pub to: Address,
+    pub to_send: TokenAmount,
+    pub settling_at: ChainEpoch,
+    pub min_settle_height: ChainEpoch,
+    pub lane_states: Cid, // AMT<LaneState>
+}
+
+impl From<LegacyState> for State {
+    /// Migrates to the current state schema. Lanes were never removed from `lane_states`
+    /// before they could be closed, so no lane is closed.
+    fn from(legacy: LegacyState) -> Self {
+        State {
+            from: legacy.from,
+            to: legacy.to,
+            to_send: legacy.to_send,
+            settling_at: legacy.settling_at,
+            min_settle_height: legacy.min_settle_height,
+            lane_states: legacy.lane_states,
+            closed_lanes: BitField::new(),
+        }
+    }
+}
@@ -2,6 +2,8 @@
 // SPDX-License-Identifier: Apache-2.0, MIT
 
 use cid::Cid;
+use fil_actors_runtime::ActorState;
+use fvm_ipld_bitfield::BitField;
 use fvm_ipld_encoding::tuple::*;
 use fvm_shared::address::Address;
 
@@ -11,7 +13,7 @@ use fvm_shared::econ::TokenAmount;
 /// A given payment channel actor is established by `from`
 /// to enable off-chain microtransactions to `to` address
 /// to be reconciled and tallied on chain.
-#[derive(Debug, Serialize_tuple, Deserialize_tuple, Clone)]
+#[derive(Debug, Serialize_tuple, Deserialize_tuple, Clone, ActorState)]
 pub struct State {
     /// Channel owner, who has funded the actor.
     pub from: Address,
@@ -25,6 +27,9 @@ pub struct State {
     pub min_settle_height: ChainEpoch,
     /// Collections of lane states for the channel, maintained in ID order.
     pub lane_states: Cid, // AMT<LaneState>
+    /// Lanes that have been closed or merged into another lane, and removed from `lane_states`.
+    /// A closed lane cannot be redeemed against again.
+    pub closed_lanes: BitField,
 }
 
 impl State {
@@ -36,6 +41,7 @@ impl State {
             settling_at: 0,
             min_settle_height: 0,
             lane_states: empty_arr_cid,
+            closed_lanes: BitField::new(),
         }
     }
 }
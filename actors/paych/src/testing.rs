@@ -1,5 +1,5 @@
 use fil_actors_runtime::fvm_ipld_amt;
-use fil_actors_runtime::MessageAccumulator;
+use fil_actors_runtime::{ActorState, MessageAccumulator};
 use fvm_ipld_amt::Amt;
 use fvm_ipld_blockstore::Blockstore;
 use fvm_shared::{address::Protocol, econ::TokenAmount};
@@ -18,6 +18,7 @@ pub fn check_state_invariants<BS: Blockstore>(
     balance: &TokenAmount,
 ) -> (StateSummary, MessageAccumulator) {
     let acc = MessageAccumulator::default();
+    state.check_fields(store, &acc);
     let mut redeemed = TokenAmount::zero();
 
     acc.require(
@@ -43,6 +44,7 @@ pub fn check_state_invariants<BS: Blockstore>(
                     lane.redeemed.is_positive(),
                     format!("lane {i} redeemed is not greater than zero {}", lane.redeemed),
                 );
+                acc.require(!state.closed_lanes.get(i), format!("lane {i} is closed"));
                 redeemed += &lane.redeemed;
                 Ok(())
             });
@@ -126,3 +126,35 @@ impl From<SignedVoucher> for UpdateChannelStateParams {
         UpdateChannelStateParams { secret: vec![], sv }
     }
 }
+
+/// Parameters for merging lanes into another lane.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize_tuple, Deserialize_tuple)]
+pub struct MergeLanesParams {
+    /// The lane into which the other lanes are merged, which must exist.
+    pub lane: u64,
+    /// The lanes to merge, which are closed.
+    pub merges: Vec<u64>,
+}
+
+/// Parameters for closing a lane.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize_tuple, Deserialize_tuple)]
+#[serde(transparent)]
+pub struct CloseLaneParams {
+    pub lane: u64,
+}
+
+/// Parameters for validating a voucher without redeeming it.
+#[derive(Serialize_tuple, Deserialize_tuple)]
+pub struct ValidateVoucherParams {
+    pub sv: SignedVoucher,
+    #[serde(with = "strict_bytes")]
+    pub secret: Vec<u8>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize_tuple, Deserialize_tuple)]
+#[serde(transparent)]
+pub struct ValidateVoucherReturn {
+    /// The amount by which redeeming the voucher would increase the amount payable to the
+    /// recipient.
+    pub amount: TokenAmount,
+}
@@ -7,10 +7,13 @@ use std::collections::HashMap;
 use cid::Cid;
 use derive_builder::Builder;
 use fil_actor_paych::ext::account::{AuthenticateMessageParams, AUTHENTICATE_MESSAGE_METHOD};
+use fil_actor_paych::migration::LegacyState;
 use fil_actor_paych::testing::check_state_invariants;
 use fil_actor_paych::{
-    Actor as PaychActor, ConstructorParams, LaneState, Merge, Method, ModVerifyParams,
-    SignedVoucher, State as PState, UpdateChannelStateParams, MAX_LANE, SETTLE_DELAY,
+    Actor as PaychActor, CloseLaneParams, ConstructorParams, LaneState, Merge, MergeLanesParams,
+    Method, ModVerifyParams, SignedVoucher, State as PState, UpdateChannelStateParams,
+    ValidateVoucherParams, ValidateVoucherReturn, ERR_CHANNEL_STATE_UPDATE_AFTER_SETTLED, MAX_LANE,
+    SETTLE_DELAY,
 };
 
 use fil_actors_runtime::runtime::builtins::Type;
@@ -18,6 +21,7 @@ use fil_actors_runtime::runtime::Runtime;
 use fil_actors_runtime::test_utils::*;
 use fil_actors_runtime::INIT_ACTOR_ADDR;
 use fvm_ipld_amt::Amt;
+use fvm_ipld_bitfield::BitField;
 use fvm_ipld_encoding::ipld_block::IpldBlock;
 use fvm_ipld_encoding::RawBytes;
 use fvm_shared::address::Address;
@@ -463,6 +467,7 @@ mod update_channel_state_redeem {
             settling_at: state.settling_at,
             min_settle_height: state.min_settle_height,
             lane_states: construct_lane_state_amt(&rt, vec![exp_ls]),
+            closed_lanes: BitField::new(),
         };
         verify_state(&rt, Some(1), exp_state);
     }
@@ -672,6 +677,206 @@ mod merge_tests {
     }
 }
 
+mod lane_management_tests {
+    use super::*;
+
+    fn merge_lanes(rt: &MockRuntime, lane: u64, merges: Vec<u64>) -> Option<IpldBlock> {
+        let state: PState = rt.get_state();
+        rt.set_caller(*ACCOUNT_ACTOR_CODE_ID, state.to);
+        rt.expect_validate_caller_addr(vec![state.to]);
+        let params = MergeLanesParams { lane, merges };
+        rt.call::<PaychActor>(
+            Method::MergeLanesExported as u64,
+            IpldBlock::serialize_cbor(&params).unwrap(),
+        )
+        .unwrap()
+    }
+
+    fn expect_merge_lanes_abort(rt: &MockRuntime, lane: u64, merges: Vec<u64>, exp: ExitCode) {
+        let state: PState = rt.get_state();
+        rt.set_caller(*ACCOUNT_ACTOR_CODE_ID, state.to);
+        rt.expect_validate_caller_addr(vec![state.to]);
+        let params = MergeLanesParams { lane, merges };
+        expect_abort(
+            rt,
+            Method::MergeLanesExported as u64,
+            IpldBlock::serialize_cbor(&params).unwrap(),
+            exp,
+        );
+        rt.verify();
+    }
+
+    fn close_lane(rt: &MockRuntime, lane: u64) -> Option<IpldBlock> {
+        let state: PState = rt.get_state();
+        rt.set_caller(*ACCOUNT_ACTOR_CODE_ID, state.to);
+        rt.expect_validate_caller_addr(vec![state.to]);
+        rt.call::<PaychActor>(
+            Method::CloseLaneExported as u64,
+            IpldBlock::serialize_cbor(&CloseLaneParams { lane }).unwrap(),
+        )
+        .unwrap()
+    }
+
+    fn expect_voucher_rejected(rt: &MockRuntime, sv: SignedVoucher) {
+        let state: PState = rt.get_state();
+        rt.set_caller(*ACCOUNT_ACTOR_CODE_ID, state.from);
+        rt.expect_validate_caller_addr(vec![state.from, state.to]);
+        expect_authenticate_message(rt, state.to, sv.clone(), ExitCode::OK);
+        expect_abort(
+            rt,
+            Method::UpdateChannelState as u64,
+            IpldBlock::serialize_cbor(&UpdateChannelStateParams::from(sv)).unwrap(),
+            ExitCode::USR_ILLEGAL_ARGUMENT,
+        );
+        rt.verify();
+    }
+
+    #[test]
+    fn merge_lanes_success() {
+        let (rt, mut sv) = require_create_channel_with_lanes(3);
+        let mut state: PState = rt.get_state();
+        let lane0 = get_lane_state(&rt, &state.lane_states, 0);
+
+        merge_lanes(&rt, 0, vec![1, 2]);
+        rt.verify();
+
+        // Lane 0 carries the amounts redeemed by all three lanes, and the channel still owes them.
+        let exp_lane0 =
+            LaneState { redeemed: TokenAmount::from_atto(1 + 2 + 3), nonce: lane0.nonce };
+        state.lane_states = construct_lane_state_amt(&rt, vec![exp_lane0]);
+        verify_state(&rt, Some(1), state.clone());
+        let closed: Vec<u64> = rt.get_state::<PState>().closed_lanes.iter().collect();
+        assert_eq!(vec![1, 2], closed);
+
+        // A voucher for a merged lane can't be redeemed again.
+        sv.lane = 1;
+        sv.nonce = 10;
+        expect_voucher_rejected(&rt, sv.clone());
+
+        // A subsequent voucher for lane 0 is redeemed net of the merged amounts.
+        sv.lane = 0;
+        sv.amount = TokenAmount::from_atto(10);
+        rt.set_caller(*ACCOUNT_ACTOR_CODE_ID, state.from);
+        rt.expect_validate_caller_addr(vec![state.from, state.to]);
+        expect_authenticate_message(&rt, state.to, sv.clone(), ExitCode::OK);
+        call(
+            &rt,
+            Method::UpdateChannelState as u64,
+            IpldBlock::serialize_cbor(&UpdateChannelStateParams::from(sv.clone())).unwrap(),
+        );
+        rt.verify();
+        state = rt.get_state();
+        assert_eq!(TokenAmount::from_atto(10), state.to_send);
+        check_state(&rt);
+    }
+
+    #[test]
+    fn merge_lanes_failures() {
+        let (rt, _) = require_create_channel_with_lanes(3);
+        let state: PState = rt.get_state();
+
+        // Only the recipient may merge lanes.
+        rt.set_caller(*ACCOUNT_ACTOR_CODE_ID, state.from);
+        rt.expect_validate_caller_addr(vec![state.to]);
+        expect_abort(
+            &rt,
+            Method::MergeLanesExported as u64,
+            IpldBlock::serialize_cbor(&MergeLanesParams { lane: 0, merges: vec![1] }).unwrap(),
+            ExitCode::USR_FORBIDDEN,
+        );
+        rt.verify();
+
+        expect_merge_lanes_abort(&rt, 0, vec![], ExitCode::USR_ILLEGAL_ARGUMENT);
+        expect_merge_lanes_abort(&rt, 0, vec![0], ExitCode::USR_ILLEGAL_ARGUMENT);
+        expect_merge_lanes_abort(&rt, 0, vec![1, 1], ExitCode::USR_NOT_FOUND);
+        expect_merge_lanes_abort(&rt, 5, vec![1], ExitCode::USR_NOT_FOUND);
+        expect_merge_lanes_abort(&rt, 0, vec![5], ExitCode::USR_NOT_FOUND);
+        expect_merge_lanes_abort(&rt, 0, vec![MAX_LANE + 1], ExitCode::USR_ILLEGAL_ARGUMENT);
+
+        // Nothing changed.
+        let after: PState = rt.get_state();
+        assert_eq!(state.lane_states, after.lane_states);
+        assert!(after.closed_lanes.is_empty());
+        check_state(&rt);
+    }
+
+    #[test]
+    fn close_lane_success() {
+        let (rt, mut sv) = require_create_channel_with_lanes(3);
+        let mut state: PState = rt.get_state();
+        let lane0 = get_lane_state(&rt, &state.lane_states, 0);
+        let lane2 = get_lane_state(&rt, &state.lane_states, 2);
+
+        close_lane(&rt, 1);
+        rt.verify();
+
+        // The amount redeemed by the closed lane is still owed.
+        let mut lanes = Amt::new(&rt.store);
+        lanes.set(0, lane0).unwrap();
+        lanes.set(2, lane2).unwrap();
+        state.lane_states = lanes.flush().unwrap();
+        verify_state(&rt, Some(2), state);
+        let closed: Vec<u64> = rt.get_state::<PState>().closed_lanes.iter().collect();
+        assert_eq!(vec![1], closed);
+
+        // A voucher for the closed lane can't be redeemed, nor merged into another lane.
+        sv.lane = 1;
+        sv.nonce = 10;
+        expect_voucher_rejected(&rt, sv.clone());
+        sv.lane = 0;
+        sv.merges = vec![Merge { lane: 1, nonce: 10 }];
+        expect_voucher_rejected(&rt, sv);
+
+        // The lane can't be closed again.
+        let state: PState = rt.get_state();
+        rt.set_caller(*ACCOUNT_ACTOR_CODE_ID, state.to);
+        rt.expect_validate_caller_addr(vec![state.to]);
+        expect_abort(
+            &rt,
+            Method::CloseLaneExported as u64,
+            IpldBlock::serialize_cbor(&CloseLaneParams { lane: 1 }).unwrap(),
+            ExitCode::USR_NOT_FOUND,
+        );
+        rt.verify();
+        check_state(&rt);
+    }
+
+    #[test]
+    fn close_lane_failures() {
+        let (rt, _) = require_create_channel_with_lanes(1);
+        let state: PState = rt.get_state();
+
+        // Only the recipient may close a lane.
+        rt.set_caller(*ACCOUNT_ACTOR_CODE_ID, state.from);
+        rt.expect_validate_caller_addr(vec![state.to]);
+        expect_abort(
+            &rt,
+            Method::CloseLaneExported as u64,
+            IpldBlock::serialize_cbor(&CloseLaneParams { lane: 0 }).unwrap(),
+            ExitCode::USR_FORBIDDEN,
+        );
+        rt.verify();
+
+        // Lanes can't be closed or merged once the channel has settled.
+        rt.set_caller(*ACCOUNT_ACTOR_CODE_ID, state.to);
+        rt.expect_validate_caller_addr(vec![state.from, state.to]);
+        call(&rt, Method::Settle as u64, None);
+        rt.verify();
+        let state: PState = rt.get_state();
+        rt.epoch.replace(state.settling_at);
+        rt.expect_validate_caller_addr(vec![state.to]);
+        expect_abort(
+            &rt,
+            Method::CloseLaneExported as u64,
+            IpldBlock::serialize_cbor(&CloseLaneParams { lane: 0 }).unwrap(),
+            ERR_CHANNEL_STATE_UPDATE_AFTER_SETTLED,
+        );
+        rt.verify();
+        expect_merge_lanes_abort(&rt, 0, vec![1], ERR_CHANNEL_STATE_UPDATE_AFTER_SETTLED);
+        check_state(&rt);
+    }
+}
+
 mod update_channel_state_extra {
     use super::*;
     use fvm_ipld_encoding::CBOR;
@@ -730,6 +935,109 @@ mod update_channel_state_extra {
     }
 }
 
+mod validate_voucher_tests {
+    use super::*;
+    use fvm_ipld_encoding::CBOR;
+
+    const OTHER_ADDR: u64 = 104;
+
+    fn validate_voucher(rt: &MockRuntime, sv: SignedVoucher) -> Result<TokenAmount, ExitCode> {
+        rt.set_caller(*EVM_ACTOR_CODE_ID, Address::new_id(OTHER_ADDR));
+        rt.expect_validate_caller_any();
+        let params = ValidateVoucherParams { sv, secret: vec![] };
+        let ret = rt.call::<PaychActor>(
+            Method::ValidateVoucherExported as u64,
+            IpldBlock::serialize_cbor(&params).unwrap(),
+        );
+        rt.verify();
+        ret.map(|ret| ret.unwrap().deserialize::<ValidateVoucherReturn>().unwrap().amount)
+            .map_err(|e| e.exit_code())
+    }
+
+    #[test]
+    fn validate_voucher_does_not_update_state() {
+        let (rt, mut sv) = require_create_channel_with_lanes(1);
+        let state: PState = rt.get_state();
+        let state_root = *rt.state.borrow();
+
+        sv.amount = TokenAmount::from_atto(9);
+        expect_authenticate_message(&rt, state.from, sv.clone(), ExitCode::OK);
+        assert_eq!(Ok(TokenAmount::from_atto(8)), validate_voucher(&rt, sv.clone()));
+        assert_eq!(state_root, *rt.state.borrow());
+
+        // The voucher can subsequently be redeemed for the validated amount.
+        rt.set_caller(*ACCOUNT_ACTOR_CODE_ID, state.to);
+        rt.expect_validate_caller_addr(vec![state.from, state.to]);
+        expect_authenticate_message(&rt, state.from, sv.clone(), ExitCode::OK);
+        call(
+            &rt,
+            Method::UpdateChannelState as u64,
+            IpldBlock::serialize_cbor(&UpdateChannelStateParams::from(sv)).unwrap(),
+        );
+        rt.verify();
+        let redeemed: PState = rt.get_state();
+        assert_eq!(&state.to_send + TokenAmount::from_atto(8), redeemed.to_send);
+        check_state(&rt);
+    }
+
+    #[test]
+    fn validate_voucher_failures() {
+        let (rt, mut sv) = require_create_channel_with_lanes(1);
+        let state: PState = rt.get_state();
+
+        // Signature must be authenticated by the payer.
+        expect_authenticate_message(&rt, state.from, sv.clone(), ExitCode::USR_FORBIDDEN);
+        assert_eq!(Err(ExitCode::USR_FORBIDDEN), validate_voucher(&rt, sv.clone()));
+
+        // Nonce must exceed the lane's.
+        sv.nonce = 1;
+        expect_authenticate_message(&rt, state.from, sv.clone(), ExitCode::OK);
+        assert_eq!(Err(ExitCode::USR_ILLEGAL_ARGUMENT), validate_voucher(&rt, sv.clone()));
+
+        // Channel must hold enough funds.
+        sv.nonce = 3;
+        sv.amount = &rt.get_balance() + TokenAmount::from_atto(1);
+        expect_authenticate_message(&rt, state.from, sv.clone(), ExitCode::OK);
+        assert_eq!(Err(ExitCode::USR_ILLEGAL_ARGUMENT), validate_voucher(&rt, sv.clone()));
+
+        // Vouchers are not valid after the channel has settled.
+        sv.amount = TokenAmount::from_atto(9);
+        let mut settled = state.clone();
+        settled.settling_at = *rt.epoch.borrow();
+        rt.replace_state(&settled);
+        assert_eq!(Err(ERR_CHANNEL_STATE_UPDATE_AFTER_SETTLED), validate_voucher(&rt, sv));
+    }
+
+    #[test]
+    fn validate_voucher_extra_call_is_read_only() {
+        let (rt, mut sv) = require_create_channel_with_lanes(1);
+        let state: PState = rt.get_state();
+        let state_root = *rt.state.borrow();
+        let other_addr = Address::new_id(OTHER_ADDR);
+        let fake_params = RawBytes::new(vec![1, 2, 3, 4]);
+
+        sv.extra = Some(ModVerifyParams {
+            actor: other_addr,
+            method: Method::UpdateChannelState as u64,
+            data: fake_params.clone(),
+        });
+        expect_authenticate_message(&rt, state.from, sv.clone(), ExitCode::OK);
+        rt.expect_send(
+            other_addr,
+            Method::UpdateChannelState as u64,
+            Some(IpldBlock { codec: CBOR, data: fake_params.to_vec() }),
+            TokenAmount::zero(),
+            None,
+            SendFlags::READ_ONLY,
+            None,
+            ExitCode::OK,
+            None,
+        );
+        assert_eq!(Ok(TokenAmount::zero()), validate_voucher(&rt, sv));
+        assert_eq!(state_root, *rt.state.borrow());
+    }
+}
+
 #[test]
 fn update_channel_settling() {
     let (rt, sv) = require_create_channel_with_lanes(1);
@@ -1022,6 +1330,28 @@ mod actor_collect {
     }
 }
 
+#[test]
+fn upgrade_migrates_legacy_state() {
+    let (rt, _sv) = require_create_channel_with_lanes(2);
+    let st: PState = rt.get_state();
+    rt.replace_state(&LegacyState {
+        from: st.from,
+        to: st.to,
+        to_send: st.to_send.clone(),
+        settling_at: st.settling_at,
+        min_settle_height: st.min_settle_height,
+        lane_states: st.lane_states,
+    });
+    rt.upgrade::<PaychActor>();
+    let migrated: PState = rt.get_state();
+    assert_eq!(st.from, migrated.from);
+    assert_eq!(st.to, migrated.to);
+    assert_eq!(st.to_send, migrated.to_send);
+    assert_eq!(st.lane_states, migrated.lane_states);
+    assert!(migrated.closed_lanes.is_empty());
+    check_state(&rt);
+}
+
 fn require_create_channel_with_lanes(num_lanes: u64) -> (MockRuntime, SignedVoucher) {
     let paych_addr = Address::new_id(100);
     let payer_addr = Address::new_id(PAYER_ID);
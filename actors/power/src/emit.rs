@@ -0,0 +1,51 @@
+use fil_actors_runtime::runtime::Runtime;
+use fil_actors_runtime::{ActorError, EventBuilder};
+use fvm_shared::econ::TokenAmount;
+use fvm_shared::ActorID;
+
+/// Indicates the total pledge collateral has been corrected to match the miners' pledge
+/// obligations, after drifting from them.
+pub fn pledge_total_reconciled(
+    rt: &impl Runtime,
+    previous: &TokenAmount,
+    reconciled: &TokenAmount,
+) -> Result<(), ActorError> {
+    rt.emit_event(
+        &EventBuilder::new()
+            .typ("pledge-total-reconciled")
+            .field("previous", previous)
+            .field("reconciled", reconciled)
+            .build()?,
+    )
+}
+
+/// Indicates cron processed more entries in an epoch than its configured budget.
+pub fn cron_budget_exceeded(
+    rt: &impl Runtime,
+    entries: u64,
+    budget: u64,
+) -> Result<(), ActorError> {
+    rt.emit_event(
+        &EventBuilder::new()
+            .typ("cron-budget-exceeded")
+            .field("entries", &entries)
+            .field("budget", &budget)
+            .build()?,
+    )
+}
+
+/// Indicates a miner's power has been excluded from the network totals after a consensus fault,
+/// or restored to them once the miner's ineligibility has elapsed.
+pub fn consensus_fault_exclusion(
+    rt: &impl Runtime,
+    miner: ActorID,
+    excluded: bool,
+) -> Result<(), ActorError> {
+    rt.emit_event(
+        &EventBuilder::new()
+            .typ("consensus-fault-exclusion")
+            .field_indexed("miner", &miner)
+            .field("excluded", &excluded)
+            .build()?,
+    )
+}
@@ -3,6 +3,7 @@ use fvm_ipld_encoding::tuple::*;
 use fvm_ipld_encoding::{strict_bytes, BytesDe};
 
 use fvm_shared::address::Address;
+use fvm_shared::econ::TokenAmount;
 use fvm_shared::sector::RegisteredPoStProof;
 use fvm_shared::METHOD_CONSTRUCTOR;
 use num_derive::FromPrimitive;
@@ -36,6 +37,7 @@ pub mod miner {
     use super::*;
 
     pub const ON_DEFERRED_CRON_EVENT_METHOD: u64 = 12;
+    pub const GET_PLEDGE_OBLIGATION_METHOD: u64 = 41;
 
     #[derive(Serialize_tuple, Deserialize_tuple)]
     pub struct MinerConstructorParams {
@@ -48,6 +50,12 @@ pub mod miner {
         pub multi_addresses: Vec<BytesDe>,
     }
 
+    #[derive(Serialize_tuple, Deserialize_tuple)]
+    #[serde(transparent)]
+    pub struct GetPledgeObligationReturn {
+        pub pledge_obligation: TokenAmount,
+    }
+
     #[derive(Serialize_tuple, Deserialize_tuple)]
     pub struct DeferredCronEventParams {
         #[serde(with = "strict_bytes")]
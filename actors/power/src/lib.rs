@@ -4,20 +4,24 @@
 use fil_actors_runtime::reward::ThisEpochRewardReturn;
 use fvm_ipld_encoding::ipld_block::IpldBlock;
 use fvm_ipld_encoding::RawBytes;
+use fvm_shared::address::Address;
 use fvm_shared::bigint::bigint_ser::BigIntSer;
 use fvm_shared::econ::TokenAmount;
 use fvm_shared::error::ExitCode;
+use fvm_shared::sys::SendFlags;
 use fvm_shared::{MethodNum, METHOD_CONSTRUCTOR};
 use log::{debug, error};
 use num_derive::FromPrimitive;
 use num_traits::Zero;
 
+use crate::migration::LegacyState;
 use ext::init;
 use fil_actors_runtime::runtime::builtins::Type;
-use fil_actors_runtime::runtime::{ActorCode, Runtime};
+use fil_actors_runtime::runtime::{ActorCode, Policy, Runtime};
 use fil_actors_runtime::{
-    actor_dispatch, actor_error, deserialize_block, extract_send_result, ActorDowncast, ActorError,
-    Multimap, CRON_ACTOR_ADDR, INIT_ACTOR_ADDR, REWARD_ACTOR_ADDR, SYSTEM_ACTOR_ADDR,
+    actor_dispatch, actor_error, deserialize_block, extract_send_result, migrate_state,
+    ActorDowncast, ActorError, Multimap, OnUpgradeParams, CRON_ACTOR_ADDR, INIT_ACTOR_ADDR,
+    REWARD_ACTOR_ADDR, SYSTEM_ACTOR_ADDR, UPGRADE_NETWORK_VERSION,
 };
 
 pub use self::policy::*;
@@ -27,10 +31,16 @@ pub use self::types::*;
 #[cfg(feature = "fil-actor")]
 fil_actors_runtime::wasm_trampoline!(Actor);
 
-#[doc(hidden)]
+mod emit;
+#[cfg(feature = "testing")]
 pub mod ext;
+#[cfg(not(feature = "testing"))]
+#[allow(dead_code)]
+mod ext;
+pub mod migration;
 mod policy;
 mod state;
+#[cfg(feature = "testing")]
 pub mod testing;
 mod types;
 
@@ -50,15 +60,27 @@ pub enum Method {
     // OnConsensusFault = 7, // Deprecated v2
     // SubmitPoRepForBulkVerify = 8, // Deprecated
     CurrentTotalPower = 9,
+    SetProofTypeAllowlist = 10,
+    ReconcilePledgeTotal = 11,
+    SetConsensusFaultExclusion = 12,
     // Method numbers derived from FRC-0042 standards
     CreateMinerExported = frc42_dispatch::method_hash!("CreateMiner"),
     NetworkRawPowerExported = frc42_dispatch::method_hash!("NetworkRawPower"),
     MinerRawPowerExported = frc42_dispatch::method_hash!("MinerRawPower"),
     MinerCountExported = frc42_dispatch::method_hash!("MinerCount"),
     MinerConsensusCountExported = frc42_dispatch::method_hash!("MinerConsensusCount"),
+    MinerPowerAtExported = frc42_dispatch::method_hash!("MinerPowerAt"),
+    ProofTypeAllowlistExported = frc42_dispatch::method_hash!("ProofTypeAllowlist"),
+    CronWorkStatsExported = frc42_dispatch::method_hash!("CronWorkStats"),
 }
 
 pub const ERR_TOO_MANY_PROVE_COMMITS: ExitCode = ExitCode::new(32);
+/// A new miner's peer ID is too large.
+pub const ERR_INVALID_PEER_ID: ExitCode = ExitCode::new(33);
+/// A new miner's multiaddrs are empty or too large.
+pub const ERR_INVALID_MULTIADDRS: ExitCode = ExitCode::new(34);
+/// A new miner's PoSt proof type is not supported by the network policy.
+pub const ERR_UNSUPPORTED_POST_PROOF_TYPE: ExitCode = ExitCode::new(35);
 
 /// Storage Power Actor
 pub struct Actor;
@@ -75,12 +97,22 @@ impl Actor {
         Ok(())
     }
 
+    /// Creates a new miner actor, forwarding the value received to it.
+    /// The parameters are checked before the miner is constructed, so that an invalid request
+    /// aborts without any effect and the value is returned to the caller.
     fn create_miner(
         rt: &impl Runtime,
         params: CreateMinerParams,
     ) -> Result<CreateMinerReturn, ActorError> {
         rt.validate_immediate_caller_accept_any()?;
         let value = rt.message().value_received();
+        let st: State = rt.state()?;
+        let allowlist = if rt.network_version() >= UPGRADE_NETWORK_VERSION {
+            st.proof_type_allowlist.as_ref()
+        } else {
+            None
+        };
+        validate_create_miner_params(rt.policy(), allowlist, &params)?;
 
         let constructor_params = RawBytes::serialize(ext::miner::MinerConstructorParams {
             owner: params.owner,
@@ -113,6 +145,7 @@ impl Actor {
                     window_post_proof_type,
                     quality_adj_power: Default::default(),
                     raw_byte_power: Default::default(),
+                    consensus_fault_excluded: false,
                 },
             )?;
             st.miner_count += 1;
@@ -144,6 +177,16 @@ impl Actor {
         rt.transaction(|st: &mut State, rt| {
             let mut claims = st.load_claims(rt.store())?;
 
+            if let Some(claim) = claims.get(&miner_addr)? {
+                st.record_claim_checkpoint(
+                    rt.policy(),
+                    rt.store(),
+                    &miner_addr,
+                    claim,
+                    rt.curr_epoch(),
+                )?;
+            }
+
             st.add_to_claim(
                 rt.policy(),
                 &mut claims,
@@ -212,7 +255,7 @@ impl Actor {
 
         Self::process_deferred_cron_events(rt, rewret)?;
 
-        let this_epoch_raw_byte_power = rt.transaction(|st: &mut State, _| {
+        let this_epoch_raw_byte_power = rt.transaction(|st: &mut State, rt| {
             let (raw_byte_power, qa_power) = st.current_total_power();
             st.this_epoch_pledge_collateral = st.total_pledge_collateral.clone();
             st.this_epoch_quality_adj_power = qa_power;
@@ -220,6 +263,13 @@ impl Actor {
             // Can assume delta is one since cron is invoked every epoch.
             st.update_smoothed_estimate(1);
 
+            st.prune_claim_history(
+                rt.policy(),
+                rt.store(),
+                rt.curr_epoch(),
+                MAX_CLAIM_HISTORY_PRUNES_PER_EPOCH,
+            )?;
+
             Ok(IpldBlock::serialize_cbor(&BigIntSer(&st.this_epoch_raw_byte_power))?)
         })?;
 
@@ -254,6 +304,76 @@ impl Actor {
         })
     }
 
+    /// Excludes the calling miner's power from the network totals while the miner is
+    /// ineligible after a consensus fault, or restores it once the ineligibility has elapsed.
+    fn set_consensus_fault_exclusion(
+        rt: &impl Runtime,
+        params: SetConsensusFaultExclusionParams,
+    ) -> Result<(), ActorError> {
+        rt.validate_immediate_caller_type(std::iter::once(&Type::Miner))?;
+        let miner_addr = rt.message().caller();
+
+        let changed = rt.transaction(|st: &mut State, rt| {
+            let mut claims = st.load_claims(rt.store())?;
+            let changed = st.set_consensus_fault_exclusion(
+                rt.policy(),
+                &mut claims,
+                &miner_addr,
+                params.excluded,
+            )?;
+            st.save_claims(&mut claims)?;
+            Ok(changed)
+        })?;
+        if changed {
+            emit::consensus_fault_exclusion(rt, miner_addr.id().unwrap(), params.excluded)?;
+        }
+        Ok(())
+    }
+
+    /// Corrects the total pledge collateral to the sum of the miners' pledge obligations,
+    /// should it have drifted from them. Each miner with a claim reports its own obligation.
+    /// Only the system actor can call this method, at network upgrades.
+    fn reconcile_pledge_total(rt: &impl Runtime) -> Result<(), ActorError> {
+        rt.validate_immediate_caller_is(std::iter::once(&SYSTEM_ACTOR_ADDR))?;
+
+        let st: State = rt.state()?;
+        let mut miners = Vec::new();
+        st.load_claims(rt.store())?.for_each(|miner, _| {
+            miners.push(miner);
+            Ok(())
+        })?;
+
+        let mut total_pledge_collateral = TokenAmount::zero();
+        for miner in miners {
+            let ret: ext::miner::GetPledgeObligationReturn =
+                deserialize_block(extract_send_result(rt.send(
+                    &miner,
+                    ext::miner::GET_PLEDGE_OBLIGATION_METHOD,
+                    None,
+                    TokenAmount::zero(),
+                    None,
+                    SendFlags::READ_ONLY,
+                ))?)?;
+            if ret.pledge_obligation.is_negative() {
+                return Err(actor_error!(
+                    illegal_state,
+                    "miner {} reported negative pledge obligation {}",
+                    miner,
+                    ret.pledge_obligation
+                ));
+            }
+            total_pledge_collateral += ret.pledge_obligation;
+        }
+
+        let previous = rt.transaction(|st: &mut State, _| {
+            Ok(std::mem::replace(&mut st.total_pledge_collateral, total_pledge_collateral.clone()))
+        })?;
+        if previous != total_pledge_collateral {
+            emit::pledge_total_reconciled(rt, &previous, &total_pledge_collateral)?;
+        }
+        Ok(())
+    }
+
     /// Returns the total power and pledge recorded by the power actor.
     /// The returned values are frozen during the cron tick before this epoch
     /// so that this method returns consistent values while processing all messages
@@ -269,9 +389,49 @@ impl Actor {
             quality_adj_power_smoothed: st.this_epoch_qa_power_smoothed,
             ramp_start_epoch: st.ramp_start_epoch,
             ramp_duration_epochs: st.ramp_duration_epochs,
+            proof_type_allowlist: st.proof_type_allowlist,
         })
     }
 
+    /// Sets the proof types the network allows for new miners and sectors.
+    /// Only the system actor can call this method, at network upgrades.
+    /// The allowlist is enforced only from the upgrade network version.
+    fn set_proof_type_allowlist(
+        rt: &impl Runtime,
+        params: SetProofTypeAllowlistParams,
+    ) -> Result<(), ActorError> {
+        rt.validate_immediate_caller_is(std::iter::once(&SYSTEM_ACTOR_ADDR))?;
+        if let Some(allowlist) = &params.allowlist {
+            if allowlist.seal_proof_types.is_empty() || allowlist.post_proof_types.is_empty() {
+                return Err(actor_error!(
+                    illegal_argument,
+                    "proof type allowlist must allow at least one seal and one PoSt proof type"
+                ));
+            }
+        }
+
+        rt.transaction(|st: &mut State, _| {
+            st.proof_type_allowlist = params.allowlist;
+            Ok(())
+        })
+    }
+
+    /// Returns the proof types the network allows for new miners and sectors,
+    /// or None if all proof types supported by policy are allowed.
+    fn proof_type_allowlist(rt: &impl Runtime) -> Result<ProofTypeAllowlistReturn, ActorError> {
+        rt.validate_immediate_caller_accept_any()?;
+        let st: State = rt.state()?;
+        Ok(ProofTypeAllowlistReturn { allowlist: st.proof_type_allowlist })
+    }
+
+    /// Returns counts of the cron events processed by cron, each of which invokes a miner
+    /// callback, and of the epochs in which they exceeded the policy's budget.
+    fn cron_work_stats(rt: &impl Runtime) -> Result<CronWorkStatsReturn, ActorError> {
+        rt.validate_immediate_caller_accept_any()?;
+        let st: State = rt.state()?;
+        Ok(CronWorkStatsReturn { stats: st.cron_stats })
+    }
+
     /// Returns the total raw power of the network.
     /// This is defined as the sum of the active (i.e. non-faulty) byte commitments
     /// of all miners that have more than the consensus minimum amount of storage active.
@@ -318,6 +478,32 @@ impl Actor {
         Ok(MinerConsensusCountReturn { miner_consensus_count: st.miner_above_min_power_count })
     }
 
+    /// Returns the power claimed by the specified miner as of a past epoch.
+    /// Claimed power is checkpointed at intervals, so the returned power is that claimed
+    /// at the start of the interval containing the requested epoch.
+    /// Only a bounded history is retained, beyond which the lookup fails.
+    fn miner_power_at(
+        rt: &impl Runtime,
+        params: MinerPowerAtParams,
+    ) -> Result<MinerPowerAtReturn, ActorError> {
+        rt.validate_immediate_caller_accept_any()?;
+        let st: State = rt.state()?;
+
+        let checkpoint = st.miner_power_at(
+            rt.policy(),
+            rt.store(),
+            &Address::new_id(params.miner),
+            params.epoch,
+            rt.curr_epoch(),
+        )?;
+
+        Ok(MinerPowerAtReturn {
+            epoch: checkpoint.epoch,
+            raw_byte_power: checkpoint.raw_byte_power,
+            quality_adj_power: checkpoint.quality_adj_power,
+        })
+    }
+
     fn process_deferred_cron_events(
         rt: &impl Runtime,
         rewret: ThisEpochRewardReturn,
@@ -325,7 +511,7 @@ impl Actor {
         let rt_epoch = rt.curr_epoch();
         let mut cron_events = Vec::new();
         let st: State = rt.state()?;
-        rt.transaction(|st: &mut State, rt| {
+        let budget_overrun = rt.transaction(|st: &mut State, rt| {
             let mut events = Multimap::from_root(
                 rt.store(),
                 &st.cron_event_queue,
@@ -337,6 +523,7 @@ impl Actor {
             })?;
 
             let claims = st.load_claims(rt.store())?;
+            let mut processed: u64 = 0;
             for epoch in st.first_cron_epoch..=rt_epoch {
                 let epoch_events = load_cron_events(&events, epoch).map_err(|e| {
                     e.downcast_default(
@@ -349,6 +536,7 @@ impl Actor {
                     continue;
                 }
 
+                processed += epoch_events.len() as u64;
                 for evt in epoch_events.into_iter() {
                     let miner_has_claim = claims.contains_key(&evt.miner_addr)?;
                     if !miner_has_claim {
@@ -371,9 +559,17 @@ impl Actor {
                 e.downcast_default(ExitCode::USR_ILLEGAL_STATE, "failed to flush events")
             })?;
 
-            Ok(())
+            let budget = rt.policy().power_cron_entries_budget;
+            Ok(st.cron_stats.record(rt_epoch, processed, budget).then_some((processed, budget)))
         })?;
 
+        if let Some((processed, budget)) = budget_overrun {
+            // The report is best-effort, and must not abort cron.
+            if let Err(e) = emit::cron_budget_exceeded(rt, processed, budget) {
+                error!("failed to report cron budget overrun: {}", e);
+            }
+        }
+
         let mut failed_miner_crons = Vec::new();
         for event in cron_events {
             let params = IpldBlock::serialize_cbor(&ext::miner::DeferredCronEventParams {
@@ -403,6 +599,15 @@ impl Actor {
 
                 // Remove power and leave miner frozen
                 for miner_addr in failed_miner_crons {
+                    if let Some(claim) = claims.get(&miner_addr)? {
+                        st.record_claim_checkpoint(
+                            rt.policy(),
+                            rt.store(),
+                            &miner_addr,
+                            claim,
+                            rt.curr_epoch(),
+                        )?;
+                    }
                     if let Err(e) = st.delete_claim(rt.policy(), &mut claims, &miner_addr) {
                         error!(
                             "failed to delete claim for miner {} after\
@@ -421,6 +626,67 @@ impl Actor {
     }
 }
 
+// Checks a new miner's parameters against the limits the miner constructor enforces.
+// The network's proof type allowlist, if set, decides which proof types are accepted,
+// in place of the policy.
+fn validate_create_miner_params(
+    policy: &Policy,
+    allowlist: Option<&ProofTypeAllowlist>,
+    params: &CreateMinerParams,
+) -> Result<(), ActorError> {
+    match allowlist {
+        Some(allowlist) => {
+            if !allowlist.post_proof_types.contains(&params.window_post_proof_type) {
+                return Err(actor_error!(
+                    illegal_argument,
+                    "proof type {:?} not allowed for new miner actors",
+                    params.window_post_proof_type
+                ));
+            }
+        }
+        None => {
+            if !policy.valid_post_proof_type.contains(params.window_post_proof_type) {
+                return Err(ActorError::unchecked(
+                    ERR_UNSUPPORTED_POST_PROOF_TYPE,
+                    format!("proof type {:?} not supported", params.window_post_proof_type),
+                ));
+            }
+        }
+    }
+
+    if params.peer.len() > policy.max_peer_id_length {
+        return Err(ActorError::unchecked(
+            ERR_INVALID_PEER_ID,
+            format!(
+                "peer ID size of {} exceeds maximum size of {}",
+                params.peer.len(),
+                policy.max_peer_id_length
+            ),
+        ));
+    }
+
+    let mut total_size = 0;
+    for ma in &params.multiaddrs {
+        if ma.0.is_empty() {
+            return Err(ActorError::unchecked(
+                ERR_INVALID_MULTIADDRS,
+                "invalid empty multiaddr".to_string(),
+            ));
+        }
+        total_size += ma.0.len();
+    }
+    if total_size > policy.max_multiaddr_data {
+        return Err(ActorError::unchecked(
+            ERR_INVALID_MULTIADDRS,
+            format!(
+                "multiaddr size of {} exceeds maximum of {}",
+                total_size, policy.max_multiaddr_data
+            ),
+        ));
+    }
+    Ok(())
+}
+
 impl ActorCode for Actor {
     type Methods = Method;
 
@@ -428,6 +694,14 @@ impl ActorCode for Actor {
         "StoragePower"
     }
 
+    fn on_upgrade<RT>(rt: &RT, params: OnUpgradeParams) -> Result<(), ActorError>
+    where
+        RT: Runtime,
+        RT::Blockstore: Clone,
+    {
+        migrate_state(rt, &params.old_state, |rt, legacy: LegacyState| legacy.migrate(rt.store()))
+    }
+
     actor_dispatch! {
         Constructor => constructor,
         CreateMiner|CreateMinerExported => create_miner,
@@ -435,10 +709,16 @@ impl ActorCode for Actor {
         EnrollCronEvent => enroll_cron_event,
         OnEpochTickEnd => on_epoch_tick_end,
         UpdatePledgeTotal => update_pledge_total,
+        ReconcilePledgeTotal => reconcile_pledge_total,
+        SetConsensusFaultExclusion => set_consensus_fault_exclusion,
         CurrentTotalPower => current_total_power,
         NetworkRawPowerExported => network_raw_power,
         MinerRawPowerExported => miner_raw_power,
         MinerCountExported => miner_count,
         MinerConsensusCountExported => miner_consensus_count,
+        MinerPowerAtExported => miner_power_at,
+        SetProofTypeAllowlist => set_proof_type_allowlist,
+        ProofTypeAllowlistExported => proof_type_allowlist,
+        CronWorkStatsExported => cron_work_stats,
     }
 }
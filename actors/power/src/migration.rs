@@ -0,0 +1,75 @@
+// Copyright 2019-2022 ChainSafe Systems
+// SPDX-License-Identifier: Apache-2.0, MIT
+
+use cid::Cid;
+use fvm_ipld_blockstore::Blockstore;
+use fvm_ipld_encoding::tuple::*;
+use fvm_shared::bigint::bigint_ser;
+use fvm_shared::clock::ChainEpoch;
+use fvm_shared::econ::TokenAmount;
+use fvm_shared::sector::StoragePower;
+
+use fil_actors_runtime::reward::FilterEstimate;
+use fil_actors_runtime::{ActorError, CronWorkStats};
+
+use crate::{ClaimHistoryMap, State, CLAIM_HISTORY_CONFIG};
+
+/// Power state as stored before claim history, the proof type allowlist and cron work
+/// statistics were introduced.
+#[derive(Serialize_tuple, Deserialize_tuple, Clone, Debug)]
+pub struct LegacyState {
+    #[serde(with = "bigint_ser")]
+    pub total_raw_byte_power: StoragePower,
+    #[serde(with = "bigint_ser")]
+    pub total_bytes_committed: StoragePower,
+    #[serde(with = "bigint_ser")]
+    pub total_quality_adj_power: StoragePower,
+    #[serde(with = "bigint_ser")]
+    pub total_qa_bytes_committed: StoragePower,
+    pub total_pledge_collateral: TokenAmount,
+    #[serde(with = "bigint_ser")]
+    pub this_epoch_raw_byte_power: StoragePower,
+    #[serde(with = "bigint_ser")]
+    pub this_epoch_quality_adj_power: StoragePower,
+    pub this_epoch_pledge_collateral: TokenAmount,
+    pub this_epoch_qa_power_smoothed: FilterEstimate,
+    pub miner_count: i64,
+    pub miner_above_min_power_count: i64,
+    pub ramp_start_epoch: i64,
+    pub ramp_duration_epochs: u64,
+    pub cron_event_queue: Cid,
+    pub first_cron_epoch: ChainEpoch,
+    pub claims: Cid,
+    pub proof_validation_batch: Option<Cid>,
+}
+
+impl LegacyState {
+    /// Migrates to the current state schema, with no claim history, no proof type allowlist
+    /// and zero cron work statistics.
+    pub fn migrate<BS: Blockstore>(self, store: &BS) -> Result<State, ActorError> {
+        Ok(State {
+            total_raw_byte_power: self.total_raw_byte_power,
+            total_bytes_committed: self.total_bytes_committed,
+            total_quality_adj_power: self.total_quality_adj_power,
+            total_qa_bytes_committed: self.total_qa_bytes_committed,
+            total_pledge_collateral: self.total_pledge_collateral,
+            this_epoch_raw_byte_power: self.this_epoch_raw_byte_power,
+            this_epoch_quality_adj_power: self.this_epoch_quality_adj_power,
+            this_epoch_pledge_collateral: self.this_epoch_pledge_collateral,
+            this_epoch_qa_power_smoothed: self.this_epoch_qa_power_smoothed,
+            miner_count: self.miner_count,
+            miner_above_min_power_count: self.miner_above_min_power_count,
+            ramp_start_epoch: self.ramp_start_epoch,
+            ramp_duration_epochs: self.ramp_duration_epochs,
+            cron_event_queue: self.cron_event_queue,
+            first_cron_epoch: self.first_cron_epoch,
+            claims: self.claims,
+            proof_validation_batch: self.proof_validation_batch,
+            claim_history: ClaimHistoryMap::empty(store, CLAIM_HISTORY_CONFIG, "claim history")
+                .flush()?,
+            proof_type_allowlist: None,
+            cron_stats: CronWorkStats::default(),
+            claim_history_prune_cursor: None,
+        })
+    }
+}
@@ -11,3 +11,9 @@ pub const CONSENSUS_MINER_MIN_MINERS: i64 = 4;
 ///
 /// To support onboarding 1EiB/year, we need to allow at least 32 prove commits per epoch.
 pub const MAX_MINER_PROVE_COMMITS_PER_EPOCH: u64 = 200;
+
+/// Maximum number of miners whose claim history is pruned in one cron tick.
+///
+/// A pruning pass over all miners is spread over as many epochs as needed to stay within this
+/// bound, so that cron work does not grow with the number of miners.
+pub const MAX_CLAIM_HISTORY_PRUNES_PER_EPOCH: usize = 1000;
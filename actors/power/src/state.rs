@@ -10,7 +10,7 @@ use fvm_ipld_encoding::tuple::*;
 use fvm_ipld_encoding::RawBytes;
 use fvm_ipld_hamt::BytesKey;
 use fvm_shared::address::Address;
-use fvm_shared::bigint::bigint_ser;
+use fvm_shared::bigint::bigint_ser::{self, BigIntDe, BigIntSer};
 use fvm_shared::clock::ChainEpoch;
 use fvm_shared::econ::TokenAmount;
 use fvm_shared::error::ExitCode;
@@ -19,17 +19,21 @@ use fvm_shared::ActorID;
 use integer_encoding::VarInt;
 use lazy_static::lazy_static;
 use num_traits::Signed;
+use serde::de::{self, SeqAccess, Visitor};
+use serde::ser::SerializeTuple;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::fmt;
 
 use fil_actors_runtime::builtin::reward::smooth::{
     AlphaBetaFilter, FilterEstimate, DEFAULT_ALPHA, DEFAULT_BETA,
 };
 use fil_actors_runtime::runtime::Policy;
 use fil_actors_runtime::{
-    actor_error, ActorContext, ActorDowncast, ActorError, AsActorError, Config, Map2, Multimap,
-    DEFAULT_HAMT_CONFIG,
+    actor_error, ActorContext, ActorDowncast, ActorError, ActorState, AsActorError, Config,
+    CronWorkStats, Map2, Multimap, DEFAULT_HAMT_CONFIG,
 };
 
-use super::CONSENSUS_MINER_MIN_MINERS;
+use super::{ProofTypeAllowlist, CONSENSUS_MINER_MIN_MINERS};
 
 lazy_static! {
     /// genesis power in bytes = 750,000 GiB
@@ -44,8 +48,11 @@ pub const CRON_QUEUE_AMT_BITWIDTH: u32 = 6;
 pub type ClaimsMap<BS> = Map2<BS, Address, Claim>;
 pub const CLAIMS_CONFIG: Config = DEFAULT_HAMT_CONFIG;
 
+pub type ClaimHistoryMap<BS> = Map2<BS, Address, Vec<ClaimCheckpoint>>;
+pub const CLAIM_HISTORY_CONFIG: Config = DEFAULT_HAMT_CONFIG;
+
 /// Storage power actor state
-#[derive(Default, Serialize_tuple, Deserialize_tuple, Clone, Debug)]
+#[derive(Default, Serialize_tuple, Deserialize_tuple, Clone, Debug, ActorState)]
 pub struct State {
     #[serde(with = "bigint_ser")]
     pub total_raw_byte_power: StoragePower,
@@ -89,17 +96,35 @@ pub struct State {
 
     // Deprecated as of FIP 0084
     pub proof_validation_batch: Option<Cid>,
+
+    /// Checkpoints of claimed power for each miner, in increasing epoch order.
+    /// A checkpoint is recorded only for intervals in which a miner's claim changed,
+    /// and checkpoints older than the retention horizon are pruned by cron.
+    pub claim_history: Cid, // Map, HAMT[address][]ClaimCheckpoint
+
+    /// Proof types the network allows, if restricted beyond those supported by policy.
+    /// Set by the system actor at network upgrades.
+    pub proof_type_allowlist: Option<ProofTypeAllowlist>,
+
+    /// Counts of the cron events processed by cron, each of which invokes a miner callback.
+    pub cron_stats: CronWorkStats,
+
+    /// Miner from which cron resumes pruning claim history, if a pruning pass is under way.
+    pub claim_history_prune_cursor: Option<Address>,
 }
 
 impl State {
     pub fn new<BS: Blockstore>(store: &BS) -> anyhow::Result<State> {
         let empty_claims = ClaimsMap::empty(store, CLAIMS_CONFIG, "empty").flush()?;
+        let empty_claim_history =
+            ClaimHistoryMap::empty(store, CLAIM_HISTORY_CONFIG, "empty").flush()?;
         let empty_mmap = Multimap::new(store, CRON_QUEUE_HAMT_BITWIDTH, CRON_QUEUE_AMT_BITWIDTH)
             .root()
             .context_code(ExitCode::USR_ILLEGAL_STATE, "Failed to get empty multimap cid")?;
         Ok(State {
             cron_event_queue: empty_mmap,
             claims: empty_claims,
+            claim_history: empty_claim_history,
             this_epoch_qa_power_smoothed: FilterEstimate::new(
                 INITIAL_QA_POWER_ESTIMATE_POSITION.clone(),
                 INITIAL_QA_POWER_ESTIMATE_VELOCITY.clone(),
@@ -163,7 +188,8 @@ impl State {
     ) -> Result<(), ActorError> {
         let old_claim = claims
             .get(miner)?
-            .ok_or_else(|| actor_error!(not_found, "no claim for actor {}", miner))?;
+            .ok_or_else(|| actor_error!(not_found, "no claim for actor {}", miner))?
+            .clone();
 
         self.total_qa_bytes_committed += qa_power;
         self.total_bytes_committed += power;
@@ -171,22 +197,71 @@ impl State {
         let new_claim = Claim {
             raw_byte_power: old_claim.raw_byte_power.clone() + power,
             quality_adj_power: old_claim.quality_adj_power.clone() + qa_power,
-            window_post_proof_type: old_claim.window_post_proof_type,
+            ..old_claim.clone()
         };
 
+        if new_claim.raw_byte_power.is_negative() {
+            return Err(actor_error!(
+                illegal_state,
+                "negative claimed raw byte power: {}",
+                new_claim.raw_byte_power
+            ));
+        }
+        if new_claim.quality_adj_power.is_negative() {
+            return Err(actor_error!(
+                illegal_state,
+                "negative claimed quality adjusted power: {}",
+                new_claim.quality_adj_power
+            ));
+        }
+
+        self.replace_claim(policy, claims, miner, &old_claim, new_claim)
+    }
+
+    /// Excludes a miner's claimed power from, or restores it to, the network totals.
+    /// Returns whether the exclusion changed.
+    pub(super) fn set_consensus_fault_exclusion<BS: Blockstore>(
+        &mut self,
+        policy: &Policy,
+        claims: &mut ClaimsMap<BS>,
+        miner: &Address,
+        excluded: bool,
+    ) -> Result<bool, ActorError> {
+        let old_claim = claims
+            .get(miner)?
+            .ok_or_else(|| actor_error!(not_found, "no claim for actor {}", miner))?
+            .clone();
+        if old_claim.consensus_fault_excluded == excluded {
+            return Ok(false);
+        }
+        let new_claim = Claim { consensus_fault_excluded: excluded, ..old_claim.clone() };
+        self.replace_claim(policy, claims, miner, &old_claim, new_claim)?;
+        Ok(true)
+    }
+
+    // Stores a miner's updated claim, moving its power into or out of the network totals
+    // as the claim starts or stops counting towards them.
+    fn replace_claim<BS: Blockstore>(
+        &mut self,
+        policy: &Policy,
+        claims: &mut ClaimsMap<BS>,
+        miner: &Address,
+        old_claim: &Claim,
+        new_claim: Claim,
+    ) -> Result<(), ActorError> {
         let min_power: StoragePower =
             consensus_miner_min_power(policy, old_claim.window_post_proof_type)
                 .exit_code(ExitCode::USR_ILLEGAL_STATE)?;
-        let prev_below: bool = old_claim.raw_byte_power < min_power;
-        let still_below: bool = new_claim.raw_byte_power < min_power;
+        let prev_counted = old_claim.counts_towards_total(&min_power);
+        let now_counted = new_claim.counts_towards_total(&min_power);
 
-        if prev_below && !still_below {
-            // Just passed min miner size
+        if !prev_counted && now_counted {
+            // Just passed min miner size, or returned from exclusion
             self.miner_above_min_power_count += 1;
             self.total_quality_adj_power += &new_claim.quality_adj_power;
             self.total_raw_byte_power += &new_claim.raw_byte_power;
-        } else if !prev_below && still_below {
-            // just went below min miner size
+        } else if prev_counted && !now_counted {
+            // Just went below min miner size, or was excluded
             self.miner_above_min_power_count -= 1;
             self.total_quality_adj_power = self
                 .total_quality_adj_power
@@ -196,26 +271,13 @@ impl State {
                 .total_raw_byte_power
                 .checked_sub(&old_claim.raw_byte_power)
                 .expect("Negative raw byte power");
-        } else if !prev_below && !still_below {
-            // Was above the threshold, still above
-            self.total_quality_adj_power += qa_power;
-            self.total_raw_byte_power += power;
+        } else if prev_counted && now_counted {
+            // Was counted, still counted
+            self.total_quality_adj_power +=
+                &new_claim.quality_adj_power - &old_claim.quality_adj_power;
+            self.total_raw_byte_power += &new_claim.raw_byte_power - &old_claim.raw_byte_power;
         }
 
-        if new_claim.raw_byte_power.is_negative() {
-            return Err(actor_error!(
-                illegal_state,
-                "negative claimed raw byte power: {}",
-                new_claim.raw_byte_power
-            ));
-        }
-        if new_claim.quality_adj_power.is_negative() {
-            return Err(actor_error!(
-                illegal_state,
-                "negative claimed quality adjusted power: {}",
-                new_claim.quality_adj_power
-            ));
-        }
         if self.miner_above_min_power_count < 0 {
             return Err(actor_error!(
                 illegal_state,
@@ -239,6 +301,138 @@ impl State {
         Ok(())
     }
 
+    pub fn load_claim_history<BS: Blockstore>(
+        &self,
+        s: BS,
+    ) -> Result<ClaimHistoryMap<BS>, ActorError> {
+        ClaimHistoryMap::load(s, &self.claim_history, CLAIM_HISTORY_CONFIG, "claim history")
+    }
+
+    /// Records a miner's claim as of the start of the checkpoint interval containing `epoch`,
+    /// unless a checkpoint has already been recorded for that interval.
+    /// Must be called with the claim prior to its first change in the interval.
+    pub(super) fn record_claim_checkpoint<BS: Blockstore>(
+        &mut self,
+        policy: &Policy,
+        store: &BS,
+        miner: &Address,
+        claim: &Claim,
+        epoch: ChainEpoch,
+    ) -> Result<(), ActorError> {
+        let checkpoint_epoch = claim_checkpoint_epoch(policy, epoch);
+        let mut history = self.load_claim_history(store)?;
+        let mut checkpoints = history.get(miner)?.cloned().unwrap_or_default();
+        if checkpoints.last().map_or(false, |c| c.epoch >= checkpoint_epoch) {
+            return Ok(());
+        }
+        checkpoints.push(ClaimCheckpoint {
+            epoch: checkpoint_epoch,
+            raw_byte_power: claim.raw_byte_power.clone(),
+            quality_adj_power: claim.quality_adj_power.clone(),
+        });
+        history.set(miner, checkpoints)?;
+        self.claim_history = history.flush()?;
+        Ok(())
+    }
+
+    /// Removes checkpoints older than the retention horizon at `epoch` from the history of at
+    /// most `max` miners, resuming from the miner at which the previous call stopped.
+    /// A pass over all miners begins at the start of each checkpoint interval, when the horizon
+    /// advances, and continues over subsequent calls until every miner has been visited.
+    pub(super) fn prune_claim_history<BS: Blockstore>(
+        &mut self,
+        policy: &Policy,
+        store: &BS,
+        epoch: ChainEpoch,
+        max: usize,
+    ) -> Result<(), ActorError> {
+        if self.claim_history_prune_cursor.is_none()
+            && claim_checkpoint_epoch(policy, epoch) != epoch
+        {
+            return Ok(());
+        }
+        let horizon = claim_history_horizon(policy, epoch);
+        let mut history = self.load_claim_history(store)?;
+        let mut updates = Vec::new();
+        let (_, next) = history.for_each_ranged_resuming(
+            self.claim_history_prune_cursor.as_ref(),
+            Some(max),
+            |miner, checkpoints| {
+                if checkpoints.first().map_or(false, |c| c.epoch < horizon) {
+                    let retained: Vec<ClaimCheckpoint> =
+                        checkpoints.iter().filter(|c| c.epoch >= horizon).cloned().collect();
+                    updates.push((miner, retained));
+                }
+                Ok(())
+            },
+        )?;
+        self.claim_history_prune_cursor = next;
+        if updates.is_empty() {
+            return Ok(());
+        }
+        for (miner, retained) in updates {
+            if retained.is_empty() {
+                history.delete(&miner)?;
+            } else {
+                history.set(&miner, retained)?;
+            }
+        }
+        self.claim_history = history.flush()?;
+        Ok(())
+    }
+
+    /// Returns a miner's claimed power as of the start of the checkpoint interval containing
+    /// `epoch`, which must lie within the retained history at `curr_epoch`.
+    pub fn miner_power_at<BS: Blockstore>(
+        &self,
+        policy: &Policy,
+        store: &BS,
+        miner: &Address,
+        epoch: ChainEpoch,
+        curr_epoch: ChainEpoch,
+    ) -> Result<ClaimCheckpoint, ActorError> {
+        if epoch > curr_epoch {
+            return Err(actor_error!(
+                illegal_argument,
+                "epoch {} is in the future (current epoch {})",
+                epoch,
+                curr_epoch
+            ));
+        }
+        let horizon = claim_history_horizon(policy, curr_epoch);
+        if epoch < horizon {
+            return Err(actor_error!(
+                illegal_argument,
+                "epoch {} precedes retained claim history from {}",
+                epoch,
+                horizon
+            ));
+        }
+
+        let checkpoint_epoch = claim_checkpoint_epoch(policy, epoch);
+        let history = self.load_claim_history(store)?;
+        // The earliest checkpoint at or after the interval start holds the claim at that time,
+        // since a checkpoint is recorded before the first change in each interval.
+        let recorded = history
+            .get(miner)?
+            .and_then(|checkpoints| checkpoints.iter().find(|c| c.epoch >= checkpoint_epoch));
+        if let Some(c) = recorded {
+            return Ok(ClaimCheckpoint { epoch: checkpoint_epoch, ..c.clone() });
+        }
+
+        // The claim has not changed since the interval start.
+        let claim = self
+            .load_claims(store)?
+            .get(miner)?
+            .cloned()
+            .ok_or_else(|| actor_error!(not_found, "no claim for actor {}", miner))?;
+        Ok(ClaimCheckpoint {
+            epoch: checkpoint_epoch,
+            raw_byte_power: claim.raw_byte_power,
+            quality_adj_power: claim.quality_adj_power,
+        })
+    }
+
     pub(super) fn add_pledge_total(&mut self, amount: TokenAmount) {
         self.total_pledge_collateral += amount;
     }
@@ -383,19 +577,100 @@ pub fn set_claim<BS: Blockstore>(
     Ok(())
 }
 
+/// The first epoch of the claim checkpoint interval containing `epoch`.
+pub fn claim_checkpoint_epoch(policy: &Policy, epoch: ChainEpoch) -> ChainEpoch {
+    epoch - epoch.rem_euclid(policy.claim_history_interval)
+}
+
+/// The earliest epoch for which claimed power is retained at `epoch`.
+pub fn claim_history_horizon(policy: &Policy, epoch: ChainEpoch) -> ChainEpoch {
+    claim_checkpoint_epoch(policy, epoch)
+        - policy.claim_history_interval * policy.claim_history_length as ChainEpoch
+}
+
 pub fn epoch_key(e: ChainEpoch) -> BytesKey {
     let bz = e.encode_var_vec();
     bz.into()
 }
 
-#[derive(Debug, Serialize_tuple, Deserialize_tuple, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Claim {
     /// Miner's proof type used to determine minimum miner size
     pub window_post_proof_type: RegisteredPoStProof,
     /// Sum of raw byte power for a miner's sectors.
-    #[serde(with = "bigint_ser")]
     pub raw_byte_power: StoragePower,
     /// Sum of quality adjusted power for a miner's sectors.
+    pub quality_adj_power: StoragePower,
+    /// Whether the miner's power is excluded from the network totals while it is ineligible
+    /// after a consensus fault.
+    pub consensus_fault_excluded: bool,
+}
+
+// The exclusion flag is encoded only while set, so claims that are not excluded keep the
+// encoding they had before it was introduced.
+impl Serialize for Claim {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut tuple =
+            serializer.serialize_tuple(if self.consensus_fault_excluded { 4 } else { 3 })?;
+        tuple.serialize_element(&self.window_post_proof_type)?;
+        tuple.serialize_element(&BigIntSer(&self.raw_byte_power))?;
+        tuple.serialize_element(&BigIntSer(&self.quality_adj_power))?;
+        if self.consensus_fault_excluded {
+            tuple.serialize_element(&self.consensus_fault_excluded)?;
+        }
+        tuple.end()
+    }
+}
+
+impl<'de> Deserialize<'de> for Claim {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_seq(ClaimVisitor)
+    }
+}
+
+struct ClaimVisitor;
+
+impl<'de> Visitor<'de> for ClaimVisitor {
+    type Value = Claim;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("a claim, optionally followed by its consensus fault exclusion")
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+        let window_post_proof_type =
+            seq.next_element()?.ok_or_else(|| de::Error::invalid_length(0, &self))?;
+        let BigIntDe(raw_byte_power) =
+            seq.next_element()?.ok_or_else(|| de::Error::invalid_length(1, &self))?;
+        let BigIntDe(quality_adj_power) =
+            seq.next_element()?.ok_or_else(|| de::Error::invalid_length(2, &self))?;
+        let consensus_fault_excluded = seq.next_element()?.unwrap_or_default();
+        if seq.next_element::<de::IgnoredAny>()?.is_some() {
+            return Err(de::Error::invalid_length(5, &self));
+        }
+        Ok(Claim {
+            window_post_proof_type,
+            raw_byte_power,
+            quality_adj_power,
+            consensus_fault_excluded,
+        })
+    }
+}
+
+impl Claim {
+    /// Whether the claim counts towards the network's total power and the count of miners
+    /// above the minimum, given the minimum power for its proof type.
+    pub fn counts_towards_total(&self, min_power: &StoragePower) -> bool {
+        !self.consensus_fault_excluded && self.raw_byte_power >= *min_power
+    }
+}
+
+/// A miner's claimed power at the start of a checkpoint interval.
+#[derive(Debug, Serialize_tuple, Deserialize_tuple, Clone, PartialEq, Eq)]
+pub struct ClaimCheckpoint {
+    pub epoch: ChainEpoch,
+    #[serde(with = "bigint_ser")]
+    pub raw_byte_power: StoragePower,
     #[serde(with = "bigint_ser")]
     pub quality_adj_power: StoragePower,
 }
@@ -431,6 +706,8 @@ pub fn consensus_miner_min_power(
 mod test {
     use fvm_shared::clock::ChainEpoch;
 
+    use fil_actors_runtime::test_blockstores::MemoryBlockstore;
+
     use super::*;
 
     #[test]
@@ -450,4 +727,67 @@ mod test {
         assert_eq!(b3, epoch_key(e3));
         assert_eq!(b4, epoch_key(e4));
     }
+
+    #[test]
+    fn claim_encodes_exclusion_only_while_set() {
+        let claim = Claim {
+            window_post_proof_type: RegisteredPoStProof::StackedDRGWindow32GiBV1P1,
+            raw_byte_power: StoragePower::from(1),
+            quality_adj_power: StoragePower::from(2),
+            consensus_fault_excluded: false,
+        };
+        let encoded = fvm_ipld_encoding::to_vec(&claim).unwrap();
+        // [13,byte[0001],byte[0002]], as encoded before the exclusion was introduced
+        assert_eq!(hex_literal::hex!("830d420001420002"), encoded[..]);
+        assert_eq!(claim, fvm_ipld_encoding::from_slice(&encoded).unwrap());
+
+        let excluded = Claim { consensus_fault_excluded: true, ..claim };
+        let encoded = fvm_ipld_encoding::to_vec(&excluded).unwrap();
+        assert_eq!(0x84, encoded[0]);
+        assert_eq!(excluded, fvm_ipld_encoding::from_slice(&encoded).unwrap());
+    }
+
+    #[test]
+    fn prune_claim_history_incrementally() {
+        let policy = Policy::default();
+        let interval = policy.claim_history_interval;
+        let store = MemoryBlockstore::new();
+        let mut st = State::new(&store).unwrap();
+        let claim = Claim {
+            window_post_proof_type: RegisteredPoStProof::StackedDRGWindow32GiBV1P1,
+            raw_byte_power: StoragePower::from(1),
+            quality_adj_power: StoragePower::from(1),
+            consensus_fault_excluded: false,
+        };
+        let miners: Vec<Address> = (100..105).map(Address::new_id).collect();
+        for miner in &miners {
+            st.record_claim_checkpoint(&policy, &store, miner, &claim, interval).unwrap();
+        }
+
+        // Nothing is pruned between interval starts when no pass is under way.
+        let horizon_passed = (policy.claim_history_length as ChainEpoch + 2) * interval;
+        st.prune_claim_history(&policy, &store, horizon_passed + 1, 2).unwrap();
+        assert_eq!(miners.len(), count_histories(&st, &store));
+
+        // A pass begins at the interval start and continues two miners at a time.
+        let mut epoch = horizon_passed;
+        for remaining in [3, 1, 0] {
+            st.prune_claim_history(&policy, &store, epoch, 2).unwrap();
+            assert_eq!(remaining, count_histories(&st, &store));
+            epoch += 1;
+        }
+        assert_eq!(None, st.claim_history_prune_cursor);
+    }
+
+    fn count_histories(st: &State, store: &MemoryBlockstore) -> usize {
+        let mut count = 0;
+        st.load_claim_history(store)
+            .unwrap()
+            .for_each(|_, _| {
+                count += 1;
+                Ok(())
+            })
+            .unwrap();
+        count
+    }
 }
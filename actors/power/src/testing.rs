@@ -2,14 +2,16 @@ use std::collections::HashMap;
 
 use fvm_ipld_blockstore::Blockstore;
 use fvm_ipld_encoding::RawBytes;
-use fvm_shared::{address::Address, clock::ChainEpoch, sector::StoragePower};
+use fvm_shared::{address::Address, clock::ChainEpoch, econ::TokenAmount, sector::StoragePower};
 use num_traits::{Signed, Zero};
 
-use fil_actors_runtime::{parse_uint_key, runtime::Policy, MessageAccumulator, Multimap};
+use fil_actors_runtime::{
+    parse_uint_key, runtime::Policy, ActorState, MessageAccumulator, Multimap,
+};
 
 use crate::{
-    consensus_miner_min_power, Claim, ClaimsMap, CronEvent, State, CLAIMS_CONFIG,
-    CRON_QUEUE_AMT_BITWIDTH, CRON_QUEUE_HAMT_BITWIDTH,
+    consensus_miner_min_power, Claim, ClaimHistoryMap, ClaimsMap, CronEvent, State, CLAIMS_CONFIG,
+    CLAIM_HISTORY_CONFIG, CRON_QUEUE_AMT_BITWIDTH, CRON_QUEUE_HAMT_BITWIDTH,
 };
 
 pub struct MinerCronEvent {
@@ -23,6 +25,7 @@ type ClaimsByAddress = HashMap<Address, Claim>;
 pub struct StateSummary {
     pub crons: CronEventsByAddress,
     pub claims: ClaimsByAddress,
+    pub total_pledge_collateral: TokenAmount,
 }
 
 /// Checks internal invariants of power state
@@ -32,6 +35,7 @@ pub fn check_state_invariants<BS: Blockstore>(
     store: &BS,
 ) -> (StateSummary, MessageAccumulator) {
     let acc = MessageAccumulator::default();
+    state.check_fields(store, &acc);
 
     // basic invariants around recorded power
     acc.require(
@@ -82,9 +86,17 @@ pub fn check_state_invariants<BS: Blockstore>(
 
     let crons = check_cron_invariants(state, store, &acc);
     let claims = check_claims_invariants(policy, state, store, &acc);
+    check_claim_history_invariants(policy, state, store, &acc);
     check_proofs_invariants(state, &acc);
 
-    (StateSummary { crons, claims }, acc)
+    (
+        StateSummary {
+            crons,
+            claims,
+            total_pledge_collateral: state.total_pledge_collateral.clone(),
+        },
+        acc,
+    )
 }
 
 fn check_cron_invariants<BS: Blockstore>(
@@ -168,7 +180,7 @@ fn check_claims_invariants<BS: Blockstore>(
                         }
                     };
 
-                if claim.raw_byte_power >= min_power {
+                if claim.counts_towards_total(&min_power) {
                     claims_with_sufficient_power_count += 1;
                     raw_power += &claim.raw_byte_power;
                     qa_power += &claim.quality_adj_power;
@@ -201,6 +213,53 @@ fn check_claims_invariants<BS: Blockstore>(
 
     claims_by_address
 }
+fn check_claim_history_invariants<BS: Blockstore>(
+    policy: &Policy,
+    state: &State,
+    store: &BS,
+    acc: &MessageAccumulator,
+) {
+    match ClaimHistoryMap::load(store, &state.claim_history, CLAIM_HISTORY_CONFIG, "claim history")
+    {
+        Ok(history) => {
+            let ret = history.for_each(|address, checkpoints| {
+                acc.require(
+                    !checkpoints.is_empty(),
+                    format!("empty claim history for miner {address}"),
+                );
+                let mut prev_epoch = None;
+                for c in checkpoints {
+                    acc.require(
+                        c.epoch % policy.claim_history_interval == 0,
+                        format!(
+                            "claim checkpoint for miner {address} at unaligned epoch {}",
+                            c.epoch
+                        ),
+                    );
+                    acc.require(
+                        prev_epoch.map_or(true, |prev| c.epoch > prev),
+                        format!(
+                            "claim checkpoints for miner {address} out of order at {}",
+                            c.epoch
+                        ),
+                    );
+                    acc.require(
+                        !c.raw_byte_power.is_negative() && !c.quality_adj_power.is_negative(),
+                        format!(
+                            "negative power in claim checkpoint for miner {address} at {}",
+                            c.epoch
+                        ),
+                    );
+                    prev_epoch = Some(c.epoch);
+                }
+                Ok(())
+            });
+            acc.require_no_error(ret, "error iterating claim history");
+        }
+        Err(e) => acc.add(format!("error loading claim history: {e}")),
+    }
+}
+
 fn check_proofs_invariants(state: &State, acc: &MessageAccumulator) {
     if state.proof_validation_batch.is_some() {
         acc.add("proof validation batch should be empty after FIP 0084");
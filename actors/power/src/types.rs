@@ -2,13 +2,14 @@
 // SPDX-License-Identifier: Apache-2.0, MIT
 
 use fil_actors_runtime::reward::FilterEstimate;
+use fil_actors_runtime::CronWorkStats;
 use fvm_ipld_encoding::tuple::*;
 use fvm_ipld_encoding::{strict_bytes, BytesDe, RawBytes};
 use fvm_shared::address::Address;
 use fvm_shared::bigint::bigint_ser;
 use fvm_shared::clock::ChainEpoch;
 use fvm_shared::econ::TokenAmount;
-use fvm_shared::sector::{RegisteredPoStProof, StoragePower};
+use fvm_shared::sector::{RegisteredPoStProof, RegisteredSealProof, StoragePower};
 use fvm_shared::ActorID;
 
 use serde::{Deserialize, Serialize};
@@ -70,6 +71,8 @@ pub struct CurrentTotalPowerReturn {
     pub quality_adj_power_smoothed: FilterEstimate,
     pub ramp_start_epoch: i64,
     pub ramp_duration_epochs: u64,
+    /// The proof types the network allows, or None if all proof types supported by policy are.
+    pub proof_type_allowlist: Option<ProofTypeAllowlist>,
 }
 
 #[derive(Serialize_tuple, Deserialize_tuple, Debug, Clone, Eq, PartialEq)]
@@ -103,3 +106,58 @@ pub struct MinerCountReturn {
 pub struct MinerConsensusCountReturn {
     pub miner_consensus_count: i64,
 }
+
+#[derive(Serialize_tuple, Deserialize_tuple, Debug, Clone, Eq, PartialEq)]
+pub struct MinerPowerAtParams {
+    pub miner: ActorID,
+    /// The epoch at which to look up the miner's claimed power.
+    /// Must not precede the retained claim history.
+    pub epoch: ChainEpoch,
+}
+
+#[derive(Serialize_tuple, Deserialize_tuple, Debug, Clone, Eq, PartialEq)]
+pub struct MinerPowerAtReturn {
+    /// The start of the checkpoint interval containing the requested epoch,
+    /// at which the returned power was claimed.
+    pub epoch: ChainEpoch,
+    #[serde(with = "bigint_ser")]
+    pub raw_byte_power: StoragePower,
+    #[serde(with = "bigint_ser")]
+    pub quality_adj_power: StoragePower,
+}
+
+/// Proof types the network allows, set by the system actor at network upgrades.
+/// These are a subset of the proof types supported by the actors' policy.
+#[derive(Serialize_tuple, Deserialize_tuple, Debug, Clone, Eq, PartialEq)]
+pub struct ProofTypeAllowlist {
+    /// Seal proof types allowed for new sectors.
+    pub seal_proof_types: Vec<RegisteredSealProof>,
+    /// Window PoSt proof types allowed for new miners.
+    pub post_proof_types: Vec<RegisteredPoStProof>,
+}
+
+#[derive(Serialize_tuple, Deserialize_tuple, Debug, Clone, Eq, PartialEq)]
+#[serde(transparent)]
+pub struct SetConsensusFaultExclusionParams {
+    /// Whether the calling miner's power is excluded from the network totals.
+    pub excluded: bool,
+}
+
+#[derive(Serialize_tuple, Deserialize_tuple, Debug, Clone, Eq, PartialEq)]
+#[serde(transparent)]
+pub struct SetProofTypeAllowlistParams {
+    /// The new allowlist, or None to allow all proof types supported by policy.
+    pub allowlist: Option<ProofTypeAllowlist>,
+}
+
+#[derive(Serialize_tuple, Deserialize_tuple, Debug, Clone, Eq, PartialEq)]
+#[serde(transparent)]
+pub struct ProofTypeAllowlistReturn {
+    pub allowlist: Option<ProofTypeAllowlist>,
+}
+
+#[derive(Serialize_tuple, Deserialize_tuple, Debug, Clone, Eq, PartialEq)]
+#[serde(transparent)]
+pub struct CronWorkStatsReturn {
+    pub stats: CronWorkStats,
+}
@@ -11,6 +11,7 @@ use fvm_shared::clock::ChainEpoch;
 use fvm_shared::econ::TokenAmount;
 use fvm_shared::error::ExitCode;
 use fvm_shared::sector::{RegisteredPoStProof, RegisteredSealProof, StoragePower};
+use fvm_shared::sys::SendFlags;
 use fvm_shared::MethodNum;
 use lazy_static::lazy_static;
 use num_traits::Zero;
@@ -30,7 +31,11 @@ use fil_actor_power::{
     ext, Claim, CreateMinerParams, CreateMinerReturn, CurrentTotalPowerReturn, Method, State,
     UpdateClaimedPowerParams,
 };
-use fil_actor_power::{CronEvent, MinerConsensusCountReturn};
+use fil_actor_power::{
+    CronEvent, MinerConsensusCountReturn, MinerPowerAtParams, MinerPowerAtReturn,
+    ProofTypeAllowlist, ProofTypeAllowlistReturn, SetConsensusFaultExclusionParams,
+    SetProofTypeAllowlistParams,
+};
 use fil_actors_runtime::builtin::reward::{FilterEstimate, ThisEpochRewardReturn};
 use fil_actors_runtime::runtime::builtins::Type;
 use fil_actors_runtime::runtime::Runtime;
@@ -41,7 +46,7 @@ use fil_actors_runtime::test_utils::{
 };
 use fil_actors_runtime::REWARD_ACTOR_ADDR;
 use fil_actors_runtime::{
-    ActorError, INIT_ACTOR_ADDR, STORAGE_POWER_ACTOR_ADDR, SYSTEM_ACTOR_ADDR,
+    ActorError, EventBuilder, INIT_ACTOR_ADDR, STORAGE_POWER_ACTOR_ADDR, SYSTEM_ACTOR_ADDR,
 };
 use fil_actors_runtime::{Map2, MapKey, Multimap};
 use fil_actors_runtime::{CRON_ACTOR_ADDR, DEFAULT_HAMT_CONFIG};
@@ -170,6 +175,34 @@ impl Harness {
         Ok(())
     }
 
+    pub fn set_proof_type_allowlist(
+        &self,
+        rt: &MockRuntime,
+        allowlist: Option<ProofTypeAllowlist>,
+    ) -> Result<(), ActorError> {
+        rt.set_caller(*SYSTEM_ACTOR_CODE_ID, SYSTEM_ACTOR_ADDR);
+        rt.expect_validate_caller_addr(vec![SYSTEM_ACTOR_ADDR]);
+        let ret = rt.call::<PowerActor>(
+            Method::SetProofTypeAllowlist as MethodNum,
+            IpldBlock::serialize_cbor(&SetProofTypeAllowlistParams { allowlist }).unwrap(),
+        )?;
+        assert!(ret.is_none());
+        rt.verify();
+        Ok(())
+    }
+
+    pub fn proof_type_allowlist(&self, rt: &MockRuntime) -> Option<ProofTypeAllowlist> {
+        rt.expect_validate_caller_any();
+        let ret: ProofTypeAllowlistReturn = rt
+            .call::<PowerActor>(Method::ProofTypeAllowlistExported as MethodNum, None)
+            .unwrap()
+            .unwrap()
+            .deserialize()
+            .unwrap();
+        rt.verify();
+        ret.allowlist
+    }
+
     pub fn create_miner_basic(
         &mut self,
         rt: &MockRuntime,
@@ -339,6 +372,86 @@ impl Harness {
         }
     }
 
+    pub fn set_consensus_fault_exclusion(
+        &self,
+        rt: &MockRuntime,
+        miner: Address,
+        excluded: bool,
+        expect_changed: bool,
+    ) {
+        rt.set_caller(*MINER_ACTOR_CODE_ID, miner);
+        rt.expect_validate_caller_type(vec![Type::Miner]);
+        if expect_changed {
+            rt.expect_emitted_event(
+                EventBuilder::new()
+                    .typ("consensus-fault-exclusion")
+                    .field_indexed("miner", &miner.id().unwrap())
+                    .field("excluded", &excluded)
+                    .build()
+                    .unwrap(),
+            );
+        }
+        rt.call::<PowerActor>(
+            Method::SetConsensusFaultExclusion as MethodNum,
+            IpldBlock::serialize_cbor(&SetConsensusFaultExclusionParams { excluded }).unwrap(),
+        )
+        .unwrap();
+        rt.verify();
+        assert_eq!(excluded, self.get_claim(rt, &miner).unwrap().consensus_fault_excluded);
+    }
+
+    // Reconciles the total pledge with the miners reporting the given pledge obligations,
+    // or zero for miners not listed.
+    pub fn reconcile_pledge_total(
+        &self,
+        rt: &MockRuntime,
+        obligations: &[(Address, TokenAmount)],
+    ) -> Result<(), ActorError> {
+        let st: State = rt.get_state();
+        let claims = st.load_claims(rt.store()).unwrap();
+        let mut total_pledge_collateral = TokenAmount::zero();
+        claims
+            .for_each(|miner, _| {
+                let obligation = obligations
+                    .iter()
+                    .find(|(addr, _)| *addr == miner)
+                    .map(|(_, obligation)| obligation.clone())
+                    .unwrap_or_default();
+                rt.expect_send(
+                    miner,
+                    ext::miner::GET_PLEDGE_OBLIGATION_METHOD,
+                    None,
+                    TokenAmount::zero(),
+                    None,
+                    SendFlags::READ_ONLY,
+                    IpldBlock::serialize_cbor(&obligation).unwrap(),
+                    ExitCode::OK,
+                    None,
+                );
+                total_pledge_collateral += obligation;
+                Ok(())
+            })
+            .unwrap();
+
+        let previous = st.total_pledge_collateral;
+        if previous != total_pledge_collateral {
+            rt.expect_emitted_event(
+                EventBuilder::new()
+                    .typ("pledge-total-reconciled")
+                    .field("previous", &previous)
+                    .field("reconciled", &total_pledge_collateral)
+                    .build()
+                    .unwrap(),
+            );
+        }
+        rt.set_caller(*SYSTEM_ACTOR_CODE_ID, SYSTEM_ACTOR_ADDR);
+        rt.expect_validate_caller_addr(vec![SYSTEM_ACTOR_ADDR]);
+        let ret = rt.call::<PowerActor>(Method::ReconcilePledgeTotal as MethodNum, None)?;
+        assert!(ret.is_none());
+        rt.verify();
+        Ok(())
+    }
+
     pub fn expect_total_power_eager(
         &self,
         rt: &MockRuntime,
@@ -369,6 +482,22 @@ impl Harness {
         assert_eq!(count, ret.miner_consensus_count);
     }
 
+    pub fn miner_power_at(
+        &self,
+        rt: &MockRuntime,
+        miner: Address,
+        epoch: ChainEpoch,
+    ) -> Result<MinerPowerAtReturn, ActorError> {
+        rt.expect_validate_caller_any();
+        let params = MinerPowerAtParams { miner: miner.id().unwrap(), epoch };
+        let ret = rt.call::<PowerActor>(
+            Method::MinerPowerAtExported as MethodNum,
+            IpldBlock::serialize_cbor(&params).unwrap(),
+        );
+        rt.verify();
+        ret.map(|r| r.unwrap().deserialize().unwrap())
+    }
+
     pub fn expect_query_network_info(&self, rt: &MockRuntime) {
         let current_reward = ThisEpochRewardReturn {
             this_epoch_baseline_power: self.this_epoch_baseline_power.clone(),
@@ -5,22 +5,28 @@ use fil_actors_runtime::test_utils::{
     expect_abort, expect_abort_contains_message, ACCOUNT_ACTOR_CODE_ID, EVM_ACTOR_CODE_ID,
     MINER_ACTOR_CODE_ID, SYSTEM_ACTOR_CODE_ID,
 };
-use fil_actors_runtime::{runtime::Policy, INIT_ACTOR_ADDR};
+use fil_actors_runtime::{
+    runtime::Policy, INIT_ACTOR_ADDR, SYSTEM_ACTOR_ADDR, UPGRADE_NETWORK_VERSION,
+};
 use fvm_ipld_encoding::{BytesDe, RawBytes};
 use fvm_shared::address::Address;
 use fvm_shared::bigint::bigint_ser::BigIntSer;
 use fvm_shared::clock::ChainEpoch;
 use fvm_shared::econ::TokenAmount;
 use fvm_shared::error::ExitCode;
-use fvm_shared::sector::{RegisteredPoStProof, StoragePower};
+use fvm_shared::sector::{RegisteredPoStProof, RegisteredSealProof, StoragePower};
+use fvm_shared::version::NetworkVersion;
 use fvm_shared::MethodNum;
 use num_traits::Zero;
 use std::ops::Neg;
 
+use fil_actor_power::migration::LegacyState;
 use fil_actor_power::{
     consensus_miner_min_power, Actor as PowerActor, Actor, CreateMinerParams, CreateMinerReturn,
     EnrollCronEventParams, Method, MinerRawPowerParams, MinerRawPowerReturn, NetworkRawPowerReturn,
-    State, UpdateClaimedPowerParams, CONSENSUS_MINER_MIN_MINERS,
+    ProofTypeAllowlist, SetProofTypeAllowlistParams, State, UpdateClaimedPowerParams,
+    CONSENSUS_MINER_MIN_MINERS, ERR_INVALID_MULTIADDRS, ERR_INVALID_PEER_ID,
+    ERR_UNSUPPORTED_POST_PROOF_TYPE,
 };
 
 use fvm_ipld_encoding::ipld_block::IpldBlock;
@@ -133,6 +139,307 @@ fn create_miner_given_send_to_init_actor_fails_should_fail() {
     h.check_state(&rt);
 }
 
+#[test]
+fn create_miner_rejects_invalid_params_before_sending_funds() {
+    let (h, rt) = setup();
+    let policy = Policy::default();
+    let valid = CreateMinerParams {
+        owner: *OWNER,
+        worker: *OWNER,
+        window_post_proof_type: RegisteredPoStProof::StackedDRGWindow32GiBV1P1,
+        peer: "miner".as_bytes().to_vec(),
+        multiaddrs: vec![BytesDe("multiaddr".as_bytes().to_vec())],
+    };
+
+    let cases = [
+        (
+            CreateMinerParams {
+                window_post_proof_type: RegisteredPoStProof::StackedDRGWinning32GiBV1,
+                ..valid.clone()
+            },
+            ERR_UNSUPPORTED_POST_PROOF_TYPE,
+            "not supported",
+        ),
+        (
+            CreateMinerParams { peer: vec![0; policy.max_peer_id_length + 1], ..valid.clone() },
+            ERR_INVALID_PEER_ID,
+            "peer ID size",
+        ),
+        (
+            CreateMinerParams {
+                multiaddrs: vec![BytesDe(vec![1]), BytesDe(vec![])],
+                ..valid.clone()
+            },
+            ERR_INVALID_MULTIADDRS,
+            "invalid empty multiaddr",
+        ),
+        (
+            CreateMinerParams {
+                multiaddrs: vec![
+                    BytesDe(vec![0; policy.max_multiaddr_data / 2]),
+                    BytesDe(vec![0; policy.max_multiaddr_data / 2 + 1]),
+                ],
+                ..valid
+            },
+            ERR_INVALID_MULTIADDRS,
+            "multiaddr size",
+        ),
+    ];
+
+    for (params, code, message) in cases {
+        // The call aborts before the value is forwarded to the init actor,
+        // so the value is returned to the caller.
+        rt.set_caller(*ACCOUNT_ACTOR_CODE_ID, *OWNER);
+        rt.value_received.replace(TokenAmount::from_atto(10));
+        rt.set_balance(TokenAmount::from_atto(10));
+        rt.expect_validate_caller_any();
+        expect_abort_contains_message(
+            code,
+            message,
+            rt.call::<PowerActor>(
+                Method::CreateMiner as u64,
+                IpldBlock::serialize_cbor(&params).unwrap(),
+            ),
+        );
+        rt.verify();
+        assert_eq!(TokenAmount::from_atto(10), rt.get_balance());
+    }
+
+    let st: State = rt.get_state();
+    assert_eq!(0, st.miner_count);
+    h.check_state(&rt);
+}
+
+#[test]
+fn proof_type_allowlist() {
+    let (h, mut rt) = setup();
+    rt.network_version = UPGRADE_NETWORK_VERSION;
+    assert_eq!(None, h.proof_type_allowlist(&rt));
+
+    // Only the system actor can set the allowlist.
+    rt.set_caller(*ACCOUNT_ACTOR_CODE_ID, *OWNER);
+    rt.expect_validate_caller_addr(vec![SYSTEM_ACTOR_ADDR]);
+    expect_abort(
+        ExitCode::USR_FORBIDDEN,
+        rt.call::<PowerActor>(
+            Method::SetProofTypeAllowlist as MethodNum,
+            IpldBlock::serialize_cbor(&SetProofTypeAllowlistParams { allowlist: None }).unwrap(),
+        ),
+    );
+    rt.reset();
+
+    expect_abort_contains_message(
+        ExitCode::USR_ILLEGAL_ARGUMENT,
+        "at least one seal and one PoSt proof type",
+        h.set_proof_type_allowlist(
+            &rt,
+            Some(ProofTypeAllowlist {
+                seal_proof_types: vec![],
+                post_proof_types: vec![RegisteredPoStProof::StackedDRGWindow32GiBV1P1],
+            }),
+        ),
+    );
+    rt.reset();
+
+    let allowlist = ProofTypeAllowlist {
+        seal_proof_types: vec![RegisteredSealProof::StackedDRG64GiBV1P1],
+        post_proof_types: vec![RegisteredPoStProof::StackedDRGWindow64GiBV1P1],
+    };
+    h.set_proof_type_allowlist(&rt, Some(allowlist.clone())).unwrap();
+    assert_eq!(Some(allowlist.clone()), h.proof_type_allowlist(&rt));
+
+    // A miner can't be created with a PoSt proof type outside the allowlist.
+    expect_abort_contains_message(
+        ExitCode::USR_ILLEGAL_ARGUMENT,
+        "not allowed for new miner actors",
+        h.create_miner(
+            &rt,
+            &OWNER,
+            &OWNER,
+            &MINER,
+            &ACTOR,
+            vec![],
+            vec![],
+            RegisteredPoStProof::StackedDRGWindow32GiBV1P1,
+            &TokenAmount::zero(),
+        ),
+    );
+    rt.reset();
+
+    h.create_miner(
+        &rt,
+        &OWNER,
+        &OWNER,
+        &MINER,
+        &ACTOR,
+        vec![],
+        vec![],
+        RegisteredPoStProof::StackedDRGWindow64GiBV1P1,
+        &TokenAmount::zero(),
+    )
+    .unwrap();
+
+    // Clearing the allowlist allows all proof types supported by policy.
+    h.set_proof_type_allowlist(&rt, None).unwrap();
+    assert_eq!(None, h.proof_type_allowlist(&rt));
+    h.check_state(&rt);
+}
+
+#[test]
+fn proof_type_allowlist_overrides_policy() {
+    let (h, mut rt) = setup();
+    rt.network_version = UPGRADE_NETWORK_VERSION;
+    rt.policy.valid_post_proof_type.remove(RegisteredPoStProof::StackedDRGWindow64GiBV1P1);
+
+    // Without an allowlist, the policy decides.
+    expect_abort(
+        ERR_UNSUPPORTED_POST_PROOF_TYPE,
+        h.create_miner(
+            &rt,
+            &OWNER,
+            &OWNER,
+            &MINER,
+            &ACTOR,
+            vec![],
+            vec![],
+            RegisteredPoStProof::StackedDRGWindow64GiBV1P1,
+            &TokenAmount::zero(),
+        ),
+    );
+    rt.reset();
+
+    // A proof type in the allowlist is accepted even if the policy doesn't support it.
+    h.set_proof_type_allowlist(
+        &rt,
+        Some(ProofTypeAllowlist {
+            seal_proof_types: vec![RegisteredSealProof::StackedDRG64GiBV1P1],
+            post_proof_types: vec![RegisteredPoStProof::StackedDRGWindow64GiBV1P1],
+        }),
+    )
+    .unwrap();
+    h.create_miner(
+        &rt,
+        &OWNER,
+        &OWNER,
+        &MINER,
+        &ACTOR,
+        vec![],
+        vec![],
+        RegisteredPoStProof::StackedDRGWindow64GiBV1P1,
+        &TokenAmount::zero(),
+    )
+    .unwrap();
+    assert_eq!(
+        Some(vec![RegisteredPoStProof::StackedDRGWindow64GiBV1P1]),
+        h.current_power_total(&rt).proof_type_allowlist.map(|allowlist| allowlist.post_proof_types)
+    );
+    h.check_state(&rt);
+}
+
+#[test]
+fn proof_type_allowlist_not_enforced_before_upgrade_network_version() {
+    let (h, mut rt) = setup();
+    rt.network_version = NetworkVersion::V24;
+
+    let allowlist = ProofTypeAllowlist {
+        seal_proof_types: vec![RegisteredSealProof::StackedDRG64GiBV1P1],
+        post_proof_types: vec![RegisteredPoStProof::StackedDRGWindow64GiBV1P1],
+    };
+    h.set_proof_type_allowlist(&rt, Some(allowlist.clone())).unwrap();
+    assert_eq!(Some(allowlist), h.proof_type_allowlist(&rt));
+
+    h.create_miner(
+        &rt,
+        &OWNER,
+        &OWNER,
+        &MINER,
+        &ACTOR,
+        vec![],
+        vec![],
+        RegisteredPoStProof::StackedDRGWindow32GiBV1P1,
+        &TokenAmount::zero(),
+    )
+    .unwrap();
+    h.check_state(&rt);
+}
+
+#[test]
+fn upgrade_migrates_legacy_state() {
+    let (mut h, rt) = setup();
+    h.create_miner_basic(&rt, *OWNER, *OWNER, *MINER).unwrap();
+    h.update_pledge_total(&rt, *MINER, &TokenAmount::from_atto(1000));
+    let st: State = rt.get_state();
+    rt.replace_state(&LegacyState {
+        total_raw_byte_power: st.total_raw_byte_power.clone(),
+        total_bytes_committed: st.total_bytes_committed.clone(),
+        total_quality_adj_power: st.total_quality_adj_power.clone(),
+        total_qa_bytes_committed: st.total_qa_bytes_committed.clone(),
+        total_pledge_collateral: st.total_pledge_collateral.clone(),
+        this_epoch_raw_byte_power: st.this_epoch_raw_byte_power.clone(),
+        this_epoch_quality_adj_power: st.this_epoch_quality_adj_power.clone(),
+        this_epoch_pledge_collateral: st.this_epoch_pledge_collateral.clone(),
+        this_epoch_qa_power_smoothed: st.this_epoch_qa_power_smoothed.clone(),
+        miner_count: st.miner_count,
+        miner_above_min_power_count: st.miner_above_min_power_count,
+        ramp_start_epoch: st.ramp_start_epoch,
+        ramp_duration_epochs: st.ramp_duration_epochs,
+        cron_event_queue: st.cron_event_queue,
+        first_cron_epoch: st.first_cron_epoch,
+        claims: st.claims,
+        proof_validation_batch: st.proof_validation_batch,
+    });
+    rt.upgrade::<PowerActor>();
+    let migrated: State = rt.get_state();
+    assert_eq!(st.claims, migrated.claims);
+    assert_eq!(st.claim_history, migrated.claim_history);
+    assert_eq!(None, migrated.proof_type_allowlist);
+    assert_eq!(st.cron_stats, migrated.cron_stats);
+    assert_eq!(TokenAmount::from_atto(1000), migrated.total_pledge_collateral);
+    h.check_state(&rt);
+}
+
+#[test]
+fn reconcile_pledge_total() {
+    let (mut h, rt) = setup();
+    h.create_miner_basic(&rt, *OWNER, *OWNER, MINER1).unwrap();
+    h.create_miner_basic(&rt, *OWNER, *OWNER, MINER2).unwrap();
+    h.update_pledge_total(&rt, MINER1, &TokenAmount::from_atto(600));
+    h.update_pledge_total(&rt, MINER2, &TokenAmount::from_atto(400));
+
+    // Only the system actor can reconcile the total.
+    rt.set_caller(*ACCOUNT_ACTOR_CODE_ID, *OWNER);
+    rt.expect_validate_caller_addr(vec![SYSTEM_ACTOR_ADDR]);
+    expect_abort(
+        ExitCode::USR_FORBIDDEN,
+        rt.call::<PowerActor>(Method::ReconcilePledgeTotal as MethodNum, None),
+    );
+    rt.reset();
+
+    // A negative obligation is rejected and leaves the total unchanged.
+    expect_abort_contains_message(
+        ExitCode::USR_ILLEGAL_STATE,
+        "negative pledge obligation",
+        h.reconcile_pledge_total(&rt, &[(MINER1, TokenAmount::from_atto(-1))]),
+    );
+    rt.reset();
+    h.expect_total_pledge_eager(&rt, &TokenAmount::from_atto(1000));
+
+    // A drifted total is corrected to the sum of the miners' obligations,
+    // and subsequent updates apply to the corrected total.
+    h.reconcile_pledge_total(
+        &rt,
+        &[(MINER1, TokenAmount::from_atto(500)), (MINER2, TokenAmount::from_atto(200))],
+    )
+    .unwrap();
+    h.expect_total_pledge_eager(&rt, &TokenAmount::from_atto(700));
+    h.update_pledge_total(&rt, MINER1, &TokenAmount::from_atto(-500));
+    h.expect_total_pledge_eager(&rt, &TokenAmount::from_atto(200));
+
+    // No event is emitted when there is nothing to correct.
+    h.reconcile_pledge_total(&rt, &[(MINER2, TokenAmount::from_atto(200))]).unwrap();
+    h.check_state(&rt);
+}
+
 #[test]
 fn claimed_power_given_caller_is_not_storage_miner_should_fail() {
     let (h, rt) = setup();
@@ -324,7 +631,8 @@ fn new_miner_updates_miner_above_min_power_count() {
     ];
 
     for test in test_cases {
-        let (mut h, rt) = setup();
+        let (mut h, mut rt) = setup();
+        rt.policy.valid_post_proof_type.insert(test.proof);
         h.window_post_proof = test.proof;
         h.create_miner_basic(&rt, *OWNER, *OWNER, MINER1).unwrap();
 
@@ -381,6 +689,41 @@ fn power_accounting_crossing_threshold() {
     h.check_state(&rt);
 }
 
+#[test]
+fn consensus_fault_exclusion_removes_miner_power_from_totals() {
+    let power_unit = &consensus_miner_min_power(
+        &Policy::default(),
+        RegisteredPoStProof::StackedDRGWindow32GiBV1P1,
+    )
+    .unwrap();
+
+    let (mut h, rt) = setup();
+    for miner in [MINER1, MINER2, MINER3, MINER4, MINER5] {
+        h.create_miner_basic(&rt, *OWNER, *OWNER, miner).unwrap();
+        h.update_claimed_power(&rt, miner, power_unit, &(power_unit * 2));
+    }
+    h.expect_total_power_eager(&rt, &(power_unit * 5), &(power_unit * 10));
+    h.expect_miners_above_min_power(&rt, 5);
+
+    h.set_consensus_fault_exclusion(&rt, MINER1, true, true);
+    h.expect_total_power_eager(&rt, &(power_unit * 4), &(power_unit * 8));
+    h.expect_miners_above_min_power(&rt, 4);
+    h.check_state(&rt);
+
+    // Excluding again changes nothing.
+    h.set_consensus_fault_exclusion(&rt, MINER1, true, false);
+
+    // Power claimed while excluded is not counted until the miner is restored.
+    h.update_claimed_power(&rt, MINER1, power_unit, power_unit);
+    h.expect_total_power_eager(&rt, &(power_unit * 4), &(power_unit * 8));
+    h.check_state(&rt);
+
+    h.set_consensus_fault_exclusion(&rt, MINER1, false, true);
+    h.expect_total_power_eager(&rt, &(power_unit * 6), &(power_unit * 11));
+    h.expect_miners_above_min_power(&rt, 5);
+    h.check_state(&rt);
+}
+
 #[test]
 fn all_of_one_miners_power_disappears_when_that_miner_dips_below_min_power_threshold() {
     let small_power_unit = &StoragePower::from(1_000_000);
@@ -611,6 +954,59 @@ fn get_network_and_miner_power() {
     h.check_state(&rt);
 }
 
+#[test]
+fn claim_history_lookback() {
+    let policy = Policy::default();
+    let interval = policy.claim_history_interval;
+    let power_unit =
+        &consensus_miner_min_power(&policy, RegisteredPoStProof::StackedDRGWindow32GiBV1P1)
+            .unwrap();
+
+    let (mut h, rt) = setup();
+    h.create_miner_basic(&rt, *OWNER, *OWNER, MINER1).unwrap();
+
+    // Power changes in two intervals record the claim at the start of each.
+    rt.set_epoch(interval + 10);
+    h.update_claimed_power(&rt, MINER1, power_unit, power_unit);
+    rt.set_epoch(2 * interval + 5);
+    h.update_claimed_power(&rt, MINER1, power_unit, power_unit);
+    rt.set_epoch(3 * interval + 1);
+    h.update_claimed_power(&rt, MINER1, power_unit, power_unit);
+    h.check_state(&rt);
+
+    let expectations = [
+        (0, 0, StoragePower::zero()),
+        (interval + 100, interval, StoragePower::zero()),
+        (2 * interval, 2 * interval, power_unit.clone()),
+        (3 * interval - 1, 2 * interval, power_unit.clone()),
+        (3 * interval, 3 * interval, power_unit * 2),
+        (3 * interval + 1, 3 * interval, power_unit * 2),
+    ];
+    for (epoch, checkpoint_epoch, power) in expectations {
+        let ret = h.miner_power_at(&rt, MINER1, epoch).unwrap();
+        assert_eq!(checkpoint_epoch, ret.epoch);
+        assert_eq!(power, ret.raw_byte_power);
+        assert_eq!(power, ret.quality_adj_power);
+    }
+
+    // Lookups must not be in the future.
+    expect_abort(ExitCode::USR_ILLEGAL_ARGUMENT, h.miner_power_at(&rt, MINER1, 3 * interval + 2));
+    rt.reset();
+
+    // Cron prunes checkpoints older than the retained history.
+    let length = policy.claim_history_length as ChainEpoch;
+    let epoch = (length + 4) * interval;
+    h.on_epoch_tick_end(&rt, epoch, &(power_unit * 3));
+    let st: State = rt.get_state();
+    verify_empty_map(&rt, st.claim_history);
+
+    expect_abort(ExitCode::USR_ILLEGAL_ARGUMENT, h.miner_power_at(&rt, MINER1, 4 * interval - 1));
+    rt.reset();
+    let ret = h.miner_power_at(&rt, MINER1, 4 * interval).unwrap();
+    assert_eq!(power_unit * 3, ret.raw_byte_power);
+    h.check_state(&rt);
+}
+
 #[test]
 fn given_no_miner_claim_update_pledge_total_should_abort() {
     let (mut h, rt) = setup();
@@ -644,7 +1040,11 @@ mod cron_tests {
         miner::{DeferredCronEventParams, ON_DEFERRED_CRON_EVENT_METHOD},
         reward::UPDATE_NETWORK_KPI,
     };
-    use fil_actors_runtime::{test_utils::CRON_ACTOR_CODE_ID, CRON_ACTOR_ADDR, REWARD_ACTOR_ADDR};
+    use fil_actor_power::CronWorkStatsReturn;
+    use fil_actors_runtime::{
+        test_utils::CRON_ACTOR_CODE_ID, CronWorkStats, EventBuilder, CRON_ACTOR_ADDR,
+        REWARD_ACTOR_ADDR,
+    };
     use fvm_shared::bigint::BigInt;
 
     const OWNER: Address = Address::new_id(103);
@@ -784,6 +1184,83 @@ mod cron_tests {
         h.check_state(&rt);
     }
 
+    #[test]
+    fn cron_work_counted_and_budget_overrun_reported() {
+        let (mut h, mut rt) = setup();
+        rt.policy.power_cron_entries_budget = 1;
+
+        let miner1 = Address::new_id(101);
+        let miner2 = Address::new_id(102);
+        h.create_miner_basic(&rt, OWNER, OWNER, miner1).unwrap();
+        h.create_miner_basic(&rt, OWNER, OWNER, miner2).unwrap();
+
+        rt.set_epoch(1);
+        h.enroll_cron_event(&rt, 2, &miner1, &RawBytes::from(vec![0x01])).unwrap();
+        h.enroll_cron_event(&rt, 2, &miner2, &RawBytes::from(vec![0x02])).unwrap();
+
+        rt.set_epoch(2);
+        rt.expect_validate_caller_addr(vec![CRON_ACTOR_ADDR]);
+        h.expect_query_network_info(&rt);
+        let state: State = rt.get_state();
+        for (miner, payload) in [(miner1, vec![0x01]), (miner2, vec![0x02])] {
+            let params = DeferredCronEventParams {
+                event_payload: payload,
+                reward_smoothed: h.this_epoch_reward_smoothed.clone(),
+                quality_adj_power_smoothed: state.this_epoch_qa_power_smoothed.clone(),
+            };
+            rt.expect_send_simple(
+                miner,
+                ON_DEFERRED_CRON_EVENT_METHOD,
+                IpldBlock::serialize_cbor(&params).unwrap(),
+                TokenAmount::zero(),
+                None,
+                ExitCode::OK,
+            );
+        }
+        rt.expect_emitted_event(
+            EventBuilder::new()
+                .typ("cron-budget-exceeded")
+                .field("entries", &2u64)
+                .field("budget", &1u64)
+                .build()
+                .unwrap(),
+        );
+        rt.expect_send_simple(
+            REWARD_ACTOR_ADDR,
+            UPDATE_NETWORK_KPI,
+            IpldBlock::serialize_cbor(&BigIntSer(&BigInt::zero())).unwrap(),
+            TokenAmount::zero(),
+            None,
+            ExitCode::OK,
+        );
+        rt.set_caller(*CRON_ACTOR_CODE_ID, CRON_ACTOR_ADDR);
+        rt.call::<PowerActor>(Method::OnEpochTickEnd as u64, None).unwrap();
+        rt.verify();
+
+        // An epoch with no events is within budget.
+        h.on_epoch_tick_end(&rt, 3, &BigInt::zero());
+
+        rt.expect_validate_caller_any();
+        let ret: CronWorkStatsReturn = rt
+            .call::<PowerActor>(Method::CronWorkStatsExported as u64, None)
+            .unwrap()
+            .unwrap()
+            .deserialize()
+            .unwrap();
+        rt.verify();
+        assert_eq!(
+            CronWorkStats {
+                last_epoch: 3,
+                last_entries: 0,
+                max_entries: 2,
+                total_entries: 2,
+                overruns: 1
+            },
+            ret.stats
+        );
+        h.check_state(&rt);
+    }
+
     #[test]
     fn event_scheduled_in_past_called_next_round() {
         let (mut h, rt) = setup();
@@ -1035,7 +1512,7 @@ fn create_miner_restricted_correctly() {
     let params = IpldBlock::serialize_cbor(&CreateMinerParams {
         owner: *OWNER,
         worker: *OWNER,
-        window_post_proof_type: RegisteredPoStProof::StackedDRGWinning2KiBV1,
+        window_post_proof_type: RegisteredPoStProof::StackedDRGWindow32GiBV1P1,
         peer: peer.clone(),
         multiaddrs: multiaddrs.clone(),
     })
@@ -1059,7 +1536,7 @@ fn create_miner_restricted_correctly() {
             owner: *OWNER,
             worker: *OWNER,
             control_addresses: vec![],
-            window_post_proof_type: RegisteredPoStProof::StackedDRGWinning2KiBV1,
+            window_post_proof_type: RegisteredPoStProof::StackedDRGWindow32GiBV1P1,
             peer_id: peer,
             multi_addresses: multiaddrs,
         })
@@ -21,9 +21,10 @@ mod serialization {
                     quality_adj_power_smoothed: Default::default(),
                     ramp_start_epoch: Default::default(),
                     ramp_duration_epochs: Default::default(),
+                    proof_type_allowlist: None,
                 },
-                // [byte[],byte[],byte[],[byte[],byte[]],0,0]
-                &hex!("864040408240400000")[..],
+                // [byte[],byte[],byte[],[byte[],byte[]],0,0,null]
+                &hex!("874040408240400000f6")[..],
             ),
             (
                 CurrentTotalPowerReturn {
@@ -33,10 +34,11 @@ mod serialization {
                     quality_adj_power_smoothed: FilterEstimate::new(BigInt::from(1 << 23), BigInt::from(1 << 24)),
                     ramp_start_epoch: 25,
                     ramp_duration_epochs: 26,
+                    proof_type_allowlist: None,
                 },
                 // FilterEstimate BigInts have a precision shift of 128, so they end up larger than the others.
-                // [byte[00100000],byte[00200000],byte[00400000],[byte[0080000000000000000000000000000000000000],byte[000100000000000000000000000000000000000000]],25,26]
-                &hex!("8644001000004400200000440040000082540080000000000000000000000000000000000000550001000000000000000000000000000000000000001819181a"),
+                // [byte[00100000],byte[00200000],byte[00400000],[byte[0080000000000000000000000000000000000000],byte[000100000000000000000000000000000000000000]],25,26,null]
+                &hex!("8744001000004400200000440040000082540080000000000000000000000000000000000000550001000000000000000000000000000000000000001819181af6"),
             ),
         ];
 
@@ -0,0 +1,23 @@
+use fil_actors_runtime::runtime::Runtime;
+use fil_actors_runtime::{ActorError, EventBuilder};
+use fvm_shared::clock::ChainEpoch;
+use fvm_shared::econ::TokenAmount;
+use fvm_shared::ActorID;
+
+/// Indicates a block reward could not be applied to its miner, and is held for the miner to
+/// reclaim until the expiration epoch, after which it is burnt.
+pub fn award_redirected(
+    rt: &impl Runtime,
+    miner: ActorID,
+    reward: &TokenAmount,
+    expiration: ChainEpoch,
+) -> Result<(), ActorError> {
+    rt.emit_event(
+        &EventBuilder::new()
+            .typ("award-redirected")
+            .field_indexed("miner", &miner)
+            .field("reward", reward)
+            .field("expiration", &expiration)
+            .build()?,
+    )
+}
@@ -3,39 +3,57 @@
 
 use fil_actors_runtime::runtime::{ActorCode, Runtime};
 use fil_actors_runtime::{
-    actor_dispatch, actor_error, extract_send_result, ActorError, BURNT_FUNDS_ACTOR_ADDR,
-    EXPECTED_LEADERS_PER_EPOCH, STORAGE_POWER_ACTOR_ADDR, SYSTEM_ACTOR_ADDR,
+    actor_dispatch, actor_error, extract_send_result, migrate_state, ActorError, OnUpgradeParams,
+    BURNT_FUNDS_ACTOR_ADDR, EPOCHS_IN_DAY, EXPECTED_LEADERS_PER_EPOCH, STORAGE_POWER_ACTOR_ADDR,
+    SYSTEM_ACTOR_ADDR,
 };
 
 use fvm_ipld_encoding::ipld_block::IpldBlock;
 use fvm_shared::address::Address;
+use fvm_shared::clock::ChainEpoch;
 use fvm_shared::econ::TokenAmount;
+use fvm_shared::ActorID;
 use fvm_shared::{METHOD_CONSTRUCTOR, METHOD_SEND};
 use log::{error, warn};
 use num_derive::FromPrimitive;
+use num_traits::Zero;
 
 pub use self::logic::*;
-pub use self::state::{Reward, State, VestingFunction};
+pub use self::state::{FailedAward, Reward, State, VestingFunction};
 pub use self::types::*;
+use crate::migration::LegacyState;
 
 #[cfg(feature = "fil-actor")]
 fil_actors_runtime::wasm_trampoline!(Actor);
 
+mod emit;
 pub(crate) mod expneg;
 mod logic;
+pub mod migration;
 mod state;
+#[cfg(feature = "testing")]
 pub mod testing;
 mod types;
 
-// only exported for tests
-#[doc(hidden)]
+#[cfg(feature = "testing")]
 pub mod ext;
+#[cfg(not(feature = "testing"))]
+#[allow(dead_code)]
+mod ext;
 
 // * Updated to specs-actors commit: 999e57a151cc7ada020ca2844b651499ab8c0dec (v3.0.1)
 
 /// PenaltyMultiplier is the factor miner penalties are scaled up by
 pub const PENALTY_MULTIPLIER: u64 = 3;
 
+/// Number of epochs for which a block reward that could not be applied to its miner
+/// can be reclaimed, before it is burnt
+pub const FAILED_AWARD_RECLAIM_EPOCHS: ChainEpoch = EPOCHS_IN_DAY;
+
+/// Maximum number of failed block rewards held for reclaiming.
+/// When full, the oldest is burnt to make room for a new one.
+pub const MAX_FAILED_AWARDS: usize = 64;
+
 /// Reward actor methods available
 #[derive(FromPrimitive)]
 #[repr(u64)]
@@ -44,6 +62,8 @@ pub enum Method {
     AwardBlockReward = 2,
     ThisEpochReward = 3,
     UpdateNetworkKPI = 4,
+    // Method numbers derived from FRC-0042 standards
+    ReclaimAwardExported = frc42_dispatch::method_hash!("ReclaimAward"),
 }
 
 /// Reward Actor
@@ -72,12 +92,21 @@ impl Actor {
     ///
     /// The reward is reduced before the residual is credited to the block producer, by:
     /// - a penalty amount, provided as a parameter, which is burnt,
+    ///
+    /// If the reward can't be applied to the miner, it is held for the miner to reclaim
+    /// for a limited time, after which it is burnt.
     fn award_block_reward(
         rt: &impl Runtime,
         params: AwardBlockRewardParams,
     ) -> Result<(), ActorError> {
         rt.validate_immediate_caller_is(std::iter::once(&SYSTEM_ACTOR_ADDR))?;
-        let prior_balance = rt.current_balance();
+
+        burn_expired_awards(rt)?;
+
+        // Held rewards are not available for paying new rewards.
+        let st: State = rt.state()?;
+        let held = st.failed_award_total();
+        let prior_balance = rt.current_balance() - &held;
         if params.penalty.is_negative() {
             return Err(actor_error!(illegal_argument, "negative penalty {}", params.penalty));
         }
@@ -110,7 +139,7 @@ impl Actor {
             let mut block_reward: TokenAmount =
                 (&st.this_epoch_reward * params.win_count).div_floor(EXPECTED_LEADERS_PER_EPOCH);
             let mut total_reward = &params.gas_reward + &block_reward;
-            let curr_balance = rt.current_balance();
+            let curr_balance = rt.current_balance() - &held;
             if total_reward > curr_balance {
                 warn!(
                     "reward actor balance {} below totalReward expected {},\
@@ -143,36 +172,66 @@ impl Actor {
         }
 
         // if this fails, we can assume the miner is responsible and avoid failing here.
-        let reward_params = ext::miner::ApplyRewardParams { reward: total_reward.clone(), penalty };
-        let res = extract_send_result(rt.send_simple(
-            &Address::new_id(miner_id),
-            ext::miner::APPLY_REWARDS_METHOD,
-            IpldBlock::serialize_cbor(&reward_params)?,
-            total_reward.clone(),
-        ));
+        let res = apply_rewards(rt, miner_id, total_reward.clone(), penalty.clone());
         if let Err(e) = res {
             error!(
                 "failed to send ApplyRewards call to the miner actor with funds {}, code: {:?}",
                 total_reward,
                 e.exit_code()
             );
-            let res = extract_send_result(rt.send_simple(
-                &BURNT_FUNDS_ACTOR_ADDR,
-                METHOD_SEND,
-                None,
-                total_reward,
-            ));
-            if let Err(e) = res {
-                error!(
-                    "failed to send unsent reward to the burnt funds actor, code: {:?}",
-                    e.exit_code()
-                );
-            }
+            let expiration = rt.curr_epoch() + FAILED_AWARD_RECLAIM_EPOCHS;
+            let evicted = rt.transaction(|st: &mut State, _| {
+                let evicted = if st.failed_awards.len() >= MAX_FAILED_AWARDS {
+                    st.failed_awards.remove(0).reward
+                } else {
+                    TokenAmount::zero()
+                };
+                st.failed_awards.push(FailedAward {
+                    miner: miner_id,
+                    reward: total_reward.clone(),
+                    penalty,
+                    expiration,
+                });
+                Ok(evicted)
+            })?;
+            burn(rt, evicted);
+            emit::award_redirected(rt, miner_id, &total_reward, expiration)?;
         }
 
         Ok(())
     }
 
+    /// Applies the unexpired rewards held for a miner whose block rewards could not be
+    /// applied when awarded. Anyone may trigger this, as the rewards can only go to the miner.
+    fn reclaim_award(
+        rt: &impl Runtime,
+        params: ReclaimAwardParams,
+    ) -> Result<ReclaimAwardReturn, ActorError> {
+        rt.validate_immediate_caller_accept_any()?;
+        let miner_id = rt
+            .resolve_address(&params.miner)
+            .ok_or_else(|| actor_error!(not_found, "failed to resolve miner {}", params.miner))?;
+
+        let (reward, penalty) = rt.transaction(|st: &mut State, rt| {
+            let curr_epoch = rt.curr_epoch();
+            let (reclaimed, held): (Vec<FailedAward>, _) = st
+                .failed_awards
+                .drain(..)
+                .partition(|a| a.miner == miner_id && a.expiration >= curr_epoch);
+            st.failed_awards = held;
+            if reclaimed.is_empty() {
+                return Err(actor_error!(not_found, "no rewards held for miner {}", miner_id));
+            }
+            let reward: TokenAmount = reclaimed.iter().map(|a| &a.reward).sum();
+            let penalty: TokenAmount = reclaimed.iter().map(|a| &a.penalty).sum();
+            Ok((reward, penalty))
+        })?;
+
+        // If this fails again, the rewards remain held.
+        apply_rewards(rt, miner_id, reward.clone(), penalty)?;
+        Ok(ReclaimAwardReturn { reclaimed: reward })
+    }
+
     /// The award value used for the current epoch, updated at the end of an epoch
     /// through cron tick.  In the case previous epochs were null blocks this
     /// is the reward value as calculated at the last non-null epoch.
@@ -222,10 +281,64 @@ impl ActorCode for Actor {
         "Reward"
     }
 
+    fn on_upgrade<RT>(rt: &RT, params: OnUpgradeParams) -> Result<(), ActorError>
+    where
+        RT: Runtime,
+        RT::Blockstore: Clone,
+    {
+        migrate_state(rt, &params.old_state, |_, legacy: LegacyState| Ok(State::from(legacy)))
+    }
+
     actor_dispatch! {
         Constructor => constructor,
         AwardBlockReward => award_block_reward,
         ThisEpochReward => this_epoch_reward,
         UpdateNetworkKPI => update_network_kpi,
+        ReclaimAwardExported => reclaim_award,
+    }
+}
+
+fn apply_rewards(
+    rt: &impl Runtime,
+    miner: ActorID,
+    reward: TokenAmount,
+    penalty: TokenAmount,
+) -> Result<(), ActorError> {
+    let reward_params = ext::miner::ApplyRewardParams { reward: reward.clone(), penalty };
+    extract_send_result(rt.send_simple(
+        &Address::new_id(miner),
+        ext::miner::APPLY_REWARDS_METHOD,
+        IpldBlock::serialize_cbor(&reward_params)?,
+        reward,
+    ))?;
+    Ok(())
+}
+
+// Burns the held rewards that were not reclaimed in time.
+fn burn_expired_awards(rt: &impl Runtime) -> Result<(), ActorError> {
+    let st: State = rt.state()?;
+    if st.failed_awards.iter().all(|a| a.expiration >= rt.curr_epoch()) {
+        return Ok(());
+    }
+    let expired = rt.transaction(|st: &mut State, rt| {
+        let curr_epoch = rt.curr_epoch();
+        let (expired, held): (Vec<FailedAward>, Vec<FailedAward>) =
+            st.failed_awards.drain(..).partition(|a| a.expiration < curr_epoch);
+        st.failed_awards = held;
+        Ok(expired.iter().map(|a| &a.reward).sum())
+    })?;
+    burn(rt, expired);
+    Ok(())
+}
+
+// Burns funds, logging rather than failing if that is not possible.
+fn burn(rt: &impl Runtime, amount: TokenAmount) {
+    if amount.is_zero() {
+        return;
+    }
+    let res =
+        extract_send_result(rt.send_simple(&BURNT_FUNDS_ACTOR_ADDR, METHOD_SEND, None, amount));
+    if let Err(e) = res {
+        error!("failed to send unsent reward to the burnt funds actor, code: {:?}", e.exit_code());
     }
 }
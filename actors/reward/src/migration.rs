@@ -0,0 +1,52 @@
+// Copyright 2019-2022 ChainSafe Systems
+// SPDX-License-Identifier: Apache-2.0, MIT
+
+use fil_actors_runtime::builtin::reward::smooth::FilterEstimate;
+use fvm_ipld_encoding::tuple::*;
+use fvm_shared::bigint::bigint_ser;
+use fvm_shared::bigint::BigInt;
+use fvm_shared::clock::ChainEpoch;
+use fvm_shared::econ::TokenAmount;
+use fvm_shared::sector::StoragePower;
+
+use crate::State;
+
+/// Reward actor state as stored before failed awards were held for reclaim.
+#[derive(Serialize_tuple, Deserialize_tuple, Debug, Clone)]
+pub struct LegacyState {
+    #[serde(with = "bigint_ser")]
+    pub cumsum_baseline: BigInt,
+    #[serde(with = "bigint_ser")]
+    pub cumsum_realized: BigInt,
+    pub effective_network_time: ChainEpoch,
+    #[serde(with = "bigint_ser")]
+    pub effective_baseline_power: StoragePower,
+    pub this_epoch_reward: TokenAmount,
+    pub this_epoch_reward_smoothed: FilterEstimate,
+    #[serde(with = "bigint_ser")]
+    pub this_epoch_baseline_power: StoragePower,
+    pub epoch: ChainEpoch,
+    pub total_storage_power_reward: TokenAmount,
+    pub simple_total: TokenAmount,
+    pub baseline_total: TokenAmount,
+}
+
+impl From<LegacyState> for State {
+    /// Migrates to the current state schema, with no failed awards held.
+    fn from(legacy: LegacyState) -> Self {
+        State {
+            cumsum_baseline: legacy.cumsum_baseline,
+            cumsum_realized: legacy.cumsum_realized,
+            effective_network_time: legacy.effective_network_time,
+            effective_baseline_power: legacy.effective_baseline_power,
+            this_epoch_reward: legacy.this_epoch_reward,
+            this_epoch_reward_smoothed: legacy.this_epoch_reward_smoothed,
+            this_epoch_baseline_power: legacy.this_epoch_baseline_power,
+            epoch: legacy.epoch,
+            total_storage_power_reward: legacy.total_storage_power_reward,
+            simple_total: legacy.simple_total,
+            baseline_total: legacy.baseline_total,
+            failed_awards: Vec::new(),
+        }
+    }
+}
@@ -8,6 +8,7 @@ use fvm_shared::bigint::BigInt;
 use fvm_shared::clock::{ChainEpoch, EPOCH_UNDEFINED};
 use fvm_shared::econ::TokenAmount;
 use fvm_shared::sector::StoragePower;
+use fvm_shared::ActorID;
 use lazy_static::lazy_static;
 use num_derive::FromPrimitive;
 
@@ -74,6 +75,23 @@ pub struct State {
     // into a code constant in a subsequent upgrade.
     pub simple_total: TokenAmount,
     pub baseline_total: TokenAmount,
+
+    /// Block rewards that could not be applied to their miner, oldest first.
+    /// These are held for the miner to reclaim until they expire and are burnt.
+    pub failed_awards: Vec<FailedAward>,
+}
+
+/// A block reward that could not be applied to its miner.
+#[derive(Serialize_tuple, Deserialize_tuple, Clone, Debug, PartialEq, Eq)]
+pub struct FailedAward {
+    /// The miner the reward was awarded to.
+    pub miner: ActorID,
+    /// The reward, held in the reward actor's balance.
+    pub reward: TokenAmount,
+    /// The penalty to be applied along with the reward.
+    pub penalty: TokenAmount,
+    /// The last epoch at which the reward can be reclaimed.
+    pub expiration: ChainEpoch,
 }
 
 impl State {
@@ -95,6 +113,11 @@ impl State {
         st
     }
 
+    /// The total reward held for miners to reclaim.
+    pub fn failed_award_total(&self) -> TokenAmount {
+        self.failed_awards.iter().map(|a| &a.reward).sum()
+    }
+
     /// Takes in current realized power and updates internal state
     /// Used for update of internal state during null rounds
     pub(super) fn update_to_next_epoch(&mut self, curr_realized_power: &StoragePower) {
@@ -52,5 +52,16 @@ pub fn check_state_invariants(
         format!("cumsum realized negative ({})", state.cumsum_realized),
     );
 
+    acc.require(
+        state.failed_award_total() <= *balance,
+        format!("held failed awards {} exceed balance {}", state.failed_award_total(), balance),
+    );
+    for award in &state.failed_awards {
+        acc.require(
+            !award.reward.is_negative() && !award.penalty.is_negative(),
+            format!("failed award {:?} has a negative amount", award),
+        );
+    }
+
     (StateSummary::default(), acc)
 }
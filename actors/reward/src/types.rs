@@ -27,3 +27,16 @@ pub use fil_actors_runtime::builtin::reward::ThisEpochRewardReturn;
 pub struct UpdateNetworkKPIParams {
     pub curr_realized_power: Option<BigIntDe>,
 }
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize_tuple, Deserialize_tuple)]
+#[serde(transparent)]
+pub struct ReclaimAwardParams {
+    pub miner: Address,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize_tuple, Deserialize_tuple)]
+#[serde(transparent)]
+pub struct ReclaimAwardReturn {
+    /// The total reward applied to the miner.
+    pub reclaimed: TokenAmount,
+}
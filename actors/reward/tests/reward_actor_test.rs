@@ -3,14 +3,16 @@
 
 use std::cell::RefCell;
 
+use fil_actor_reward::migration::LegacyState;
 use fil_actor_reward::{
-    ext, Actor as RewardActor, AwardBlockRewardParams, Method, State, ThisEpochRewardReturn,
-    BASELINE_INITIAL_VALUE, PENALTY_MULTIPLIER,
+    ext, Actor as RewardActor, AwardBlockRewardParams, FailedAward, Method, ReclaimAwardParams,
+    ReclaimAwardReturn, State, ThisEpochRewardReturn, BASELINE_INITIAL_VALUE,
+    FAILED_AWARD_RECLAIM_EPOCHS, MAX_FAILED_AWARDS, PENALTY_MULTIPLIER,
 };
 use fil_actors_runtime::test_utils::*;
 use fil_actors_runtime::EXPECTED_LEADERS_PER_EPOCH;
 use fil_actors_runtime::{
-    ActorError, BURNT_FUNDS_ACTOR_ADDR, REWARD_ACTOR_ADDR, STORAGE_POWER_ACTOR_ADDR,
+    ActorError, EventBuilder, BURNT_FUNDS_ACTOR_ADDR, REWARD_ACTOR_ADDR, STORAGE_POWER_ACTOR_ADDR,
     SYSTEM_ACTOR_ADDR,
 };
 use fvm_ipld_encoding::ipld_block::IpldBlock;
@@ -22,7 +24,7 @@ use fvm_shared::error::ExitCode;
 use fvm_shared::sector::StoragePower;
 use fvm_shared::{METHOD_CONSTRUCTOR, METHOD_SEND};
 use lazy_static::lazy_static;
-use num_traits::FromPrimitive;
+use num_traits::{FromPrimitive, Zero};
 
 lazy_static! {
     static ref EPOCH_ZERO_REWARD: TokenAmount =
@@ -249,7 +251,7 @@ mod test_award_block_reward {
     }
 
     #[test]
-    fn funds_are_sent_to_burnt_funds_actor_if_sending_locked_funds_to_miner_fails() {
+    fn funds_are_held_then_sent_to_burnt_funds_actor_if_sending_locked_funds_to_miner_fails() {
         let rt = construct_and_verify(&StoragePower::from(1));
         let mut state: State = rt.get_state();
 
@@ -259,22 +261,36 @@ mod test_award_block_reward {
         // enough balance to pay 3 full rewards and one partial
         rt.set_balance(TokenAmount::from_atto(3500));
 
-        rt.expect_validate_caller_addr(vec![SYSTEM_ACTOR_ADDR]);
         let expected_reward = TokenAmount::from_atto(1000);
-        let miner_penalty = TokenAmount::zero();
-        let params = IpldBlock::serialize_cbor(&ext::miner::ApplyRewardParams {
-            reward: expected_reward.clone(),
-            penalty: miner_penalty,
-        })
-        .unwrap();
-        rt.expect_send_simple(
-            *WINNER,
-            ext::miner::APPLY_REWARDS_METHOD,
-            params,
-            expected_reward.clone(),
-            None,
-            ExitCode::USR_FORBIDDEN,
+        fail_award_block_reward(&rt, *WINNER, expected_reward.clone(), None);
+
+        let state: State = rt.get_state();
+        let expiration = FAILED_AWARD_RECLAIM_EPOCHS;
+        assert_eq!(
+            vec![FailedAward {
+                miner: WINNER.id().unwrap(),
+                reward: expected_reward.clone(),
+                penalty: TokenAmount::zero(),
+                expiration,
+            }],
+            state.failed_awards
         );
+
+        // The held reward is not available to pay others.
+        rt.epoch.replace(expiration);
+        award_block_reward(
+            &rt,
+            *WINNER,
+            TokenAmount::zero(),
+            TokenAmount::zero(),
+            3,
+            TokenAmount::from_atto(2500),
+        )
+        .unwrap();
+        rt.set_balance(TokenAmount::from_atto(2000));
+
+        // Once expired, the held reward is burnt at the next award.
+        rt.epoch.replace(expiration + 1);
         rt.expect_send_simple(
             BURNT_FUNDS_ACTOR_ADDR,
             METHOD_SEND,
@@ -283,18 +299,94 @@ mod test_award_block_reward {
             None,
             ExitCode::OK,
         );
-
-        let inner_params = IpldBlock::serialize_cbor(&AwardBlockRewardParams {
-            miner: *WINNER,
-            penalty: TokenAmount::zero(),
-            gas_reward: TokenAmount::zero(),
-            win_count: 1,
-        })
+        award_block_reward(
+            &rt,
+            *WINNER,
+            TokenAmount::zero(),
+            TokenAmount::zero(),
+            1,
+            TokenAmount::from_atto(1000),
+        )
         .unwrap();
+        let state: State = rt.get_state();
+        assert!(state.failed_awards.is_empty());
+    }
 
-        assert!(rt.call::<RewardActor>(Method::AwardBlockReward as u64, inner_params).is_ok());
+    #[test]
+    fn miner_reclaims_failed_award() {
+        let rt = construct_and_verify(&StoragePower::from(1));
+        let mut state: State = rt.get_state();
+        state.this_epoch_reward = TokenAmount::from_atto(5000);
+        rt.replace_state(&state);
+        rt.set_balance(TokenAmount::from_atto(5000));
 
-        rt.verify();
+        let expected_reward = TokenAmount::from_atto(1000);
+        fail_award_block_reward(&rt, *WINNER, expected_reward.clone(), None);
+        fail_award_block_reward(&rt, *WINNER, expected_reward.clone(), None);
+        let other = Address::new_id(1001);
+        fail_award_block_reward(&rt, other, expected_reward.clone(), None);
+
+        // A failure to apply the reclaimed rewards leaves them held.
+        let total = TokenAmount::from_atto(2000);
+        let prior_state = *rt.state.borrow();
+        expect_abort(
+            ExitCode::USR_FORBIDDEN,
+            reclaim_award(&rt, *WINNER, &total, ExitCode::USR_FORBIDDEN),
+        );
+        assert_eq!(prior_state, *rt.state.borrow());
+        rt.add_balance(total.clone());
+
+        assert_eq!(total, reclaim_award(&rt, *WINNER, &total, ExitCode::OK).unwrap());
+        let state: State = rt.get_state();
+        assert_eq!(1, state.failed_awards.len());
+        assert_eq!(other.id().unwrap(), state.failed_awards[0].miner);
+
+        // Nothing is left to reclaim, nor can expired rewards be reclaimed.
+        rt.expect_validate_caller_any();
+        expect_abort(
+            ExitCode::USR_NOT_FOUND,
+            rt.call::<RewardActor>(
+                Method::ReclaimAwardExported as u64,
+                IpldBlock::serialize_cbor(&ReclaimAwardParams { miner: *WINNER }).unwrap(),
+            ),
+        );
+        rt.reset();
+        rt.epoch.replace(FAILED_AWARD_RECLAIM_EPOCHS + 1);
+        rt.expect_validate_caller_any();
+        expect_abort(
+            ExitCode::USR_NOT_FOUND,
+            rt.call::<RewardActor>(
+                Method::ReclaimAwardExported as u64,
+                IpldBlock::serialize_cbor(&ReclaimAwardParams { miner: other }).unwrap(),
+            ),
+        );
+        rt.reset();
+    }
+
+    #[test]
+    fn oldest_failed_award_burnt_when_full() {
+        let rt = construct_and_verify(&StoragePower::from(1));
+        let mut state: State = rt.get_state();
+        state.this_epoch_reward = TokenAmount::from_atto(5000);
+        rt.replace_state(&state);
+        rt.set_balance(TokenAmount::from_atto(1_000_000));
+
+        for i in 0..MAX_FAILED_AWARDS as i64 {
+            rt.epoch.replace(i);
+            fail_award_block_reward(&rt, *WINNER, TokenAmount::from_atto(1000), None);
+        }
+
+        rt.epoch.replace(MAX_FAILED_AWARDS as i64);
+        fail_award_block_reward(
+            &rt,
+            *WINNER,
+            TokenAmount::from_atto(1000),
+            Some(TokenAmount::from_atto(1000)),
+        );
+
+        let state: State = rt.get_state();
+        assert_eq!(MAX_FAILED_AWARDS, state.failed_awards.len());
+        assert_eq!(1 + FAILED_AWARD_RECLAIM_EPOCHS, state.failed_awards[0].expiration);
     }
 }
 
@@ -325,6 +417,38 @@ fn test_successive_kpi_updates() {
     }
 }
 
+#[test]
+fn upgrade_migrates_legacy_state() {
+    let power = StoragePower::from_i128(1 << 50).unwrap();
+    let rt = construct_and_verify(&power);
+    let st: State = rt.get_state();
+    rt.replace_state(&LegacyState {
+        cumsum_baseline: st.cumsum_baseline.clone(),
+        cumsum_realized: st.cumsum_realized.clone(),
+        effective_network_time: st.effective_network_time,
+        effective_baseline_power: st.effective_baseline_power.clone(),
+        this_epoch_reward: st.this_epoch_reward.clone(),
+        this_epoch_reward_smoothed: st.this_epoch_reward_smoothed.clone(),
+        this_epoch_baseline_power: st.this_epoch_baseline_power.clone(),
+        epoch: st.epoch,
+        total_storage_power_reward: st.total_storage_power_reward.clone(),
+        simple_total: st.simple_total.clone(),
+        baseline_total: st.baseline_total.clone(),
+    });
+    rt.upgrade::<RewardActor>();
+    let migrated: State = rt.get_state();
+    assert_eq!(st.cumsum_baseline, migrated.cumsum_baseline);
+    assert_eq!(st.this_epoch_reward, migrated.this_epoch_reward);
+    assert_eq!(st.this_epoch_reward_smoothed, migrated.this_epoch_reward_smoothed);
+    assert_eq!(st.epoch, migrated.epoch);
+    assert_eq!(st.baseline_total, migrated.baseline_total);
+    assert!(migrated.failed_awards.is_empty());
+
+    // The migrated state continues to advance.
+    rt.epoch.replace(1);
+    update_network_kpi(&rt, &power);
+}
+
 fn construct_and_verify(curr_power: &StoragePower) -> MockRuntime {
     let rt = MockRuntime {
         receiver: REWARD_ACTOR_ADDR,
@@ -394,6 +518,91 @@ fn award_block_reward(
     Ok(serialized_bytes)
 }
 
+// Awards a block reward with a win count of 1, which the miner fails to apply.
+// Any held reward evicted to make room is burnt.
+fn fail_award_block_reward(
+    rt: &MockRuntime,
+    miner: Address,
+    expected_reward: TokenAmount,
+    evicted: Option<TokenAmount>,
+) {
+    rt.expect_validate_caller_addr(vec![SYSTEM_ACTOR_ADDR]);
+    rt.expect_send_simple(
+        miner,
+        ext::miner::APPLY_REWARDS_METHOD,
+        IpldBlock::serialize_cbor(&ext::miner::ApplyRewardParams {
+            reward: expected_reward.clone(),
+            penalty: TokenAmount::zero(),
+        })
+        .unwrap(),
+        expected_reward.clone(),
+        None,
+        ExitCode::USR_FORBIDDEN,
+    );
+    if let Some(evicted) = evicted {
+        rt.expect_send_simple(
+            BURNT_FUNDS_ACTOR_ADDR,
+            METHOD_SEND,
+            None,
+            evicted,
+            None,
+            ExitCode::OK,
+        );
+    }
+    rt.expect_emitted_event(
+        EventBuilder::new()
+            .typ("award-redirected")
+            .field_indexed("miner", &miner.id().unwrap())
+            .field("reward", &expected_reward)
+            .field("expiration", &(*rt.epoch.borrow() + FAILED_AWARD_RECLAIM_EPOCHS))
+            .build()
+            .unwrap(),
+    );
+
+    let params = IpldBlock::serialize_cbor(&AwardBlockRewardParams {
+        miner,
+        penalty: TokenAmount::zero(),
+        gas_reward: TokenAmount::zero(),
+        win_count: 1,
+    })
+    .unwrap();
+    rt.call::<RewardActor>(Method::AwardBlockReward as u64, params).unwrap();
+    rt.verify();
+    // The mock runtime transfers funds even when the receiver aborts.
+    rt.add_balance(expected_reward);
+}
+
+fn reclaim_award(
+    rt: &MockRuntime,
+    miner: Address,
+    expected_reward: &TokenAmount,
+    exit_code: ExitCode,
+) -> Result<TokenAmount, ActorError> {
+    rt.expect_validate_caller_any();
+    rt.expect_send_simple(
+        miner,
+        ext::miner::APPLY_REWARDS_METHOD,
+        IpldBlock::serialize_cbor(&ext::miner::ApplyRewardParams {
+            reward: expected_reward.clone(),
+            penalty: TokenAmount::zero(),
+        })
+        .unwrap(),
+        expected_reward.clone(),
+        None,
+        exit_code,
+    );
+    let ret: ReclaimAwardReturn = rt
+        .call::<RewardActor>(
+            Method::ReclaimAwardExported as u64,
+            IpldBlock::serialize_cbor(&ReclaimAwardParams { miner }).unwrap(),
+        )?
+        .unwrap()
+        .deserialize()
+        .unwrap();
+    rt.verify();
+    Ok(ret.reclaimed)
+}
+
 fn this_epoch_reward(rt: &MockRuntime) -> ThisEpochRewardReturn {
     rt.expect_validate_caller_any();
     let serialized_result = rt.call::<RewardActor>(Method::ThisEpochReward as u64, None).unwrap();
@@ -3,16 +3,22 @@
 use cid::Cid;
 use fvm_ipld_blockstore::Blockstore;
 use fvm_ipld_encoding::tuple::*;
-use fvm_ipld_encoding::CborStore;
+use fvm_ipld_encoding::{CborStore, RawBytes};
+use fvm_shared::address::Address;
 use fvm_shared::error::ExitCode;
 use fvm_shared::METHOD_CONSTRUCTOR;
 use num_derive::FromPrimitive;
 
 use fil_actors_runtime::runtime::{ActorCode, Runtime};
 use fil_actors_runtime::{
-    actor_dispatch, actor_error, ActorContext, ActorError, AsActorError, SYSTEM_ACTOR_ADDR,
+    actor_dispatch, actor_error, migrate_state, ActorContext, ActorError, AsActorError, Map2,
+    OnUpgradeParams, DEFAULT_HAMT_CONFIG, SYSTEM_ACTOR_ADDR,
 };
 
+use crate::migration::LegacyState;
+
+pub mod migration;
+
 #[cfg(feature = "fil-actor")]
 fil_actors_runtime::wasm_trampoline!(Actor);
 
@@ -21,21 +27,69 @@ fil_actors_runtime::wasm_trampoline!(Actor);
 #[repr(u64)]
 pub enum Method {
     Constructor = METHOD_CONSTRUCTOR,
+    SetParameter = 2,
+    // Method numbers derived from FRC-0042 standards
+    GetCallFilterExported = frc42_dispatch::method_hash!("GetCallFilter"),
+    GetParameterExported = frc42_dispatch::method_hash!("GetParameter"),
 }
 
+/// Maximum length in bytes of a network parameter key.
+pub const MAX_PARAMETER_KEY_LENGTH: usize = 64;
+
 /// System actor state.
 #[derive(Default, Deserialize_tuple, Serialize_tuple, Debug, Clone)]
 pub struct State {
     // builtin actor registry: Vec<(String, Cid)>
     pub builtin_actors: Cid,
+    // actor consulted by EVM contracts before executing a call, set at genesis by permissioned
+    // deployments
+    pub call_filter: Option<Address>,
+    // network parameter registry: HAMT[key]CBOR value
+    pub parameters: Cid,
 }
 
+pub type ParameterMap<BS> = Map2<BS, Vec<u8>, RawBytes>;
+
 impl State {
     pub fn new<BS: Blockstore>(store: &BS) -> Result<Self, ActorError> {
         let c = store
             .put_cbor(&Vec::<(String, Cid)>::new(), multihash_codetable::Code::Blake2b256)
             .context_code(ExitCode::USR_ILLEGAL_STATE, "failed to store system state")?;
-        Ok(Self { builtin_actors: c })
+        let parameters = ParameterMap::flush_empty(store, DEFAULT_HAMT_CONFIG)?;
+        Ok(Self { builtin_actors: c, call_filter: None, parameters })
+    }
+
+    /// Returns the CBOR value of a network parameter, if set.
+    pub fn get_parameter<BS: Blockstore>(
+        &self,
+        store: &BS,
+        key: &str,
+    ) -> Result<Option<RawBytes>, ActorError> {
+        let parameters =
+            ParameterMap::load(store, &self.parameters, DEFAULT_HAMT_CONFIG, "parameters")?;
+        Ok(parameters.get(&key.as_bytes().to_vec())?.cloned())
+    }
+
+    /// Sets the CBOR value of a network parameter, or removes it if the value is none.
+    pub fn set_parameter<BS: Blockstore>(
+        &mut self,
+        store: &BS,
+        key: &str,
+        value: Option<RawBytes>,
+    ) -> Result<(), ActorError> {
+        let mut parameters =
+            ParameterMap::load(store, &self.parameters, DEFAULT_HAMT_CONFIG, "parameters")?;
+        let key = key.as_bytes().to_vec();
+        match value {
+            Some(value) => {
+                parameters.set(&key, value)?;
+            }
+            None => {
+                parameters.delete(&key)?;
+            }
+        }
+        self.parameters = parameters.flush()?;
+        Ok(())
     }
 
     pub fn get_builtin_actors<B: Blockstore>(
@@ -62,6 +116,65 @@ impl Actor {
         rt.create(&state)?;
         Ok(())
     }
+
+    /// Returns the call filter registered for EVM contracts, if any.
+    pub fn get_call_filter(rt: &impl Runtime) -> Result<GetCallFilterReturn, ActorError> {
+        rt.validate_immediate_caller_accept_any()?;
+        let state: State = rt.state()?;
+        Ok(GetCallFilterReturn { call_filter: state.call_filter })
+    }
+
+    /// Sets or removes a network parameter.
+    /// Parameters are written only through the system actor, as part of a network upgrade.
+    pub fn set_parameter(rt: &impl Runtime, params: SetParameterParams) -> Result<(), ActorError> {
+        rt.validate_immediate_caller_is(std::iter::once(&SYSTEM_ACTOR_ADDR))?;
+        if params.key.is_empty() || params.key.len() > MAX_PARAMETER_KEY_LENGTH {
+            return Err(actor_error!(
+                illegal_argument,
+                "parameter key length {} must be between 1 and {}",
+                params.key.len(),
+                MAX_PARAMETER_KEY_LENGTH
+            ));
+        }
+
+        rt.transaction(|st: &mut State, rt| st.set_parameter(rt.store(), &params.key, params.value))
+    }
+
+    /// Returns the CBOR value of a network parameter, if set.
+    pub fn get_parameter(
+        rt: &impl Runtime,
+        params: GetParameterParams,
+    ) -> Result<GetParameterReturn, ActorError> {
+        rt.validate_immediate_caller_accept_any()?;
+        let state: State = rt.state()?;
+        let value = state.get_parameter(rt.store(), &params.key)?;
+        Ok(GetParameterReturn { value })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize_tuple, Deserialize_tuple)]
+#[serde(transparent)]
+pub struct GetCallFilterReturn {
+    pub call_filter: Option<Address>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize_tuple, Deserialize_tuple)]
+pub struct SetParameterParams {
+    pub key: String,
+    /// The parameter's CBOR value, or none to remove the parameter.
+    pub value: Option<RawBytes>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize_tuple, Deserialize_tuple)]
+#[serde(transparent)]
+pub struct GetParameterParams {
+    pub key: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize_tuple, Deserialize_tuple)]
+#[serde(transparent)]
+pub struct GetParameterReturn {
+    pub value: Option<RawBytes>,
 }
 
 impl ActorCode for Actor {
@@ -71,8 +184,19 @@ impl ActorCode for Actor {
         "System"
     }
 
+    fn on_upgrade<RT>(rt: &RT, params: OnUpgradeParams) -> Result<(), ActorError>
+    where
+        RT: Runtime,
+        RT::Blockstore: Clone,
+    {
+        migrate_state(rt, &params.old_state, |rt, legacy: LegacyState| legacy.migrate(rt.store()))
+    }
+
     actor_dispatch! {
         Constructor => constructor,
+        SetParameter => set_parameter,
+        GetCallFilterExported => get_call_filter,
+        GetParameterExported => get_parameter,
     }
 }
 
@@ -80,12 +204,22 @@ impl ActorCode for Actor {
 mod tests {
     use std::cell::RefCell;
 
+    use fvm_ipld_encoding::ipld_block::IpldBlock;
+    use fvm_ipld_encoding::RawBytes;
+    use fvm_shared::address::Address;
+    use fvm_shared::error::ExitCode;
     use fvm_shared::MethodNum;
 
-    use fil_actors_runtime::test_utils::{MockRuntime, SYSTEM_ACTOR_CODE_ID};
+    use fil_actors_runtime::test_utils::{
+        expect_abort, MockRuntime, ACCOUNT_ACTOR_CODE_ID, EVM_ACTOR_CODE_ID, SYSTEM_ACTOR_CODE_ID,
+    };
     use fil_actors_runtime::SYSTEM_ACTOR_ADDR;
 
-    use crate::{Actor, Method, State};
+    use crate::migration::LegacyState;
+    use crate::{
+        Actor, GetCallFilterReturn, GetParameterParams, GetParameterReturn, Method,
+        SetParameterParams, State, MAX_PARAMETER_KEY_LENGTH,
+    };
 
     pub fn new_runtime() -> MockRuntime {
         MockRuntime {
@@ -106,5 +240,138 @@ mod tests {
         let state: State = rt.get_state();
         let builtin_actors = state.get_builtin_actors(&rt.store).unwrap();
         assert!(builtin_actors.is_empty());
+        assert_eq!(None, state.call_filter);
+    }
+
+    #[test]
+    fn upgrade_migrates_legacy_state() {
+        let rt = new_runtime();
+        rt.expect_validate_caller_addr(vec![SYSTEM_ACTOR_ADDR]);
+        rt.set_caller(*SYSTEM_ACTOR_CODE_ID, SYSTEM_ACTOR_ADDR);
+        rt.call::<Actor>(Method::Constructor as MethodNum, None).unwrap();
+        let st: State = rt.get_state();
+        rt.replace_state(&LegacyState { builtin_actors: st.builtin_actors });
+
+        rt.upgrade::<Actor>();
+        let migrated: State = rt.get_state();
+        assert_eq!(st.builtin_actors, migrated.builtin_actors);
+        assert_eq!(None, migrated.call_filter);
+    }
+
+    #[test]
+    fn upgrade_starts_empty_parameters() {
+        let rt = new_runtime();
+        rt.expect_validate_caller_addr(vec![SYSTEM_ACTOR_ADDR]);
+        rt.set_caller(*SYSTEM_ACTOR_CODE_ID, SYSTEM_ACTOR_ADDR);
+        rt.call::<Actor>(Method::Constructor as MethodNum, None).unwrap();
+        let st: State = rt.get_state();
+        rt.replace_state(&LegacyState { builtin_actors: st.builtin_actors });
+        rt.upgrade::<Actor>();
+
+        let mut migrated: State = rt.get_state();
+        assert_eq!(None, migrated.get_parameter(&rt.store, "max-batch-size").unwrap());
+        let value = RawBytes::serialize(200u64).unwrap();
+        migrated.set_parameter(&rt.store, "max-batch-size", Some(value.clone())).unwrap();
+        rt.replace_state(&migrated);
+
+        // Parameters set since the migration are kept by a repeated upgrade.
+        rt.upgrade::<Actor>();
+        let st: State = rt.get_state();
+        assert_eq!(Some(value), st.get_parameter(&rt.store, "max-batch-size").unwrap());
+    }
+
+    #[test]
+    fn get_call_filter() {
+        let rt = new_runtime();
+        rt.expect_validate_caller_addr(vec![SYSTEM_ACTOR_ADDR]);
+        rt.set_caller(*SYSTEM_ACTOR_CODE_ID, SYSTEM_ACTOR_ADDR);
+        rt.call::<Actor>(Method::Constructor as MethodNum, None).unwrap();
+
+        let get_call_filter = || {
+            rt.set_caller(*EVM_ACTOR_CODE_ID, Address::new_id(1000));
+            rt.expect_validate_caller_any();
+            let ret: GetCallFilterReturn = rt
+                .call::<Actor>(Method::GetCallFilterExported as MethodNum, None)
+                .unwrap()
+                .unwrap()
+                .deserialize()
+                .unwrap();
+            rt.verify();
+            ret.call_filter
+        };
+        assert_eq!(None, get_call_filter());
+
+        let mut state: State = rt.get_state();
+        state.call_filter = Some(Address::new_id(1001));
+        rt.replace_state(&state);
+        assert_eq!(Some(Address::new_id(1001)), get_call_filter());
+    }
+
+    #[test]
+    fn set_and_get_parameters() {
+        let rt = new_runtime();
+        rt.expect_validate_caller_addr(vec![SYSTEM_ACTOR_ADDR]);
+        rt.set_caller(*SYSTEM_ACTOR_CODE_ID, SYSTEM_ACTOR_ADDR);
+        rt.call::<Actor>(Method::Constructor as MethodNum, None).unwrap();
+
+        let get_parameter = |key: &str| {
+            rt.set_caller(*EVM_ACTOR_CODE_ID, Address::new_id(1000));
+            rt.expect_validate_caller_any();
+            let ret: GetParameterReturn = rt
+                .call::<Actor>(
+                    Method::GetParameterExported as MethodNum,
+                    IpldBlock::serialize_cbor(&GetParameterParams { key: key.to_string() })
+                        .unwrap(),
+                )
+                .unwrap()
+                .unwrap()
+                .deserialize()
+                .unwrap();
+            rt.verify();
+            ret.value
+        };
+        let set_parameter = |caller: Address, key: &str, value: Option<RawBytes>| {
+            rt.set_caller(*ACCOUNT_ACTOR_CODE_ID, caller);
+            rt.expect_validate_caller_addr(vec![SYSTEM_ACTOR_ADDR]);
+            let ret = rt.call::<Actor>(
+                Method::SetParameter as MethodNum,
+                IpldBlock::serialize_cbor(&SetParameterParams { key: key.to_string(), value })
+                    .unwrap(),
+            );
+            if ret.is_ok() {
+                rt.verify();
+            } else {
+                rt.reset();
+            }
+            ret
+        };
+
+        let value = RawBytes::serialize(200u64).unwrap();
+        assert_eq!(None, get_parameter("max-batch-size"));
+        set_parameter(SYSTEM_ACTOR_ADDR, "max-batch-size", Some(value.clone())).unwrap();
+        assert_eq!(Some(value.clone()), get_parameter("max-batch-size"));
+        assert_eq!(None, get_parameter("other"));
+
+        // Only the system actor may write parameters.
+        expect_abort(
+            ExitCode::USR_FORBIDDEN,
+            set_parameter(Address::new_id(1000), "max-batch-size", None),
+        );
+        expect_abort(
+            ExitCode::USR_ILLEGAL_ARGUMENT,
+            set_parameter(SYSTEM_ACTOR_ADDR, "", Some(value.clone())),
+        );
+        expect_abort(
+            ExitCode::USR_ILLEGAL_ARGUMENT,
+            set_parameter(
+                SYSTEM_ACTOR_ADDR,
+                &"k".repeat(MAX_PARAMETER_KEY_LENGTH + 1),
+                Some(value.clone()),
+            ),
+        );
+        assert_eq!(Some(value), get_parameter("max-batch-size"));
+
+        set_parameter(SYSTEM_ACTOR_ADDR, "max-batch-size", None).unwrap();
+        assert_eq!(None, get_parameter("max-batch-size"));
     }
 }
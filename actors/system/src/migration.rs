@@ -0,0 +1,27 @@
+// Copyright 2019-2022 ChainSafe Systems
+// SPDX-License-Identifier: Apache-2.0, MIT
+use cid::Cid;
+use fvm_ipld_blockstore::Blockstore;
+use fvm_ipld_encoding::tuple::*;
+
+use fil_actors_runtime::{ActorError, DEFAULT_HAMT_CONFIG};
+
+use crate::{ParameterMap, State};
+
+/// System actor state as stored before the call filter and network parameters.
+#[derive(Deserialize_tuple, Serialize_tuple, Debug, Clone)]
+pub struct LegacyState {
+    // builtin actor registry: Vec<(String, Cid)>
+    pub builtin_actors: Cid,
+}
+
+impl LegacyState {
+    /// Migrates to the current state schema, with no call filter and no parameters set.
+    pub fn migrate<BS: Blockstore>(self, store: &BS) -> Result<State, ActorError> {
+        Ok(State {
+            builtin_actors: self.builtin_actors,
+            call_filter: None,
+            parameters: ParameterMap::flush_empty(store, DEFAULT_HAMT_CONFIG)?,
+        })
+    }
+}
@@ -1,4 +1,16 @@
 // A namespace for helpers that build and emit verified registry events.
+//
+// Allocations and claims emit an event at each stage of their lifecycle, each indexed by the
+// allocation or claim ID, client and provider:
+// - "allocation-created" when an allocation is created;
+// - "allocation-removed" when an expired allocation is removed (a claimed allocation is instead
+//   succeeded by its claim, which keeps the allocation's ID);
+// - "claim-created" when an allocation is claimed, creating a claim;
+// - "claim-extended" when a claim's term is extended;
+// - "claim-expired" when an expired claim is removed.
+// During a deprecation period each of these is preceded by an identical event under its legacy
+// type, respectively "allocation", "claim", "claim-updated" and "claim-removed".
+// These event types are part of the actor's public interface and must not be renamed.
 
 use crate::{ActorError, Allocation, AllocationID, Claim};
 use crate::{ClaimID, DataCap};
@@ -34,15 +46,8 @@ pub fn allocation(
     id: AllocationID,
     alloc: &Allocation,
 ) -> Result<(), ActorError> {
-    rt.emit_event(
-        &EventBuilder::new()
-            .typ("allocation")
-            .with_parties(id, alloc.client, alloc.provider)
-            .with_piece(&alloc.data, alloc.size.0)
-            .with_term(alloc.term_min, alloc.term_max)
-            .field("expiration", &alloc.expiration)
-            .build()?,
-    )
+    allocation_event(rt, "allocation", id, alloc)?;
+    allocation_event(rt, "allocation-created", id, alloc)
 }
 
 /// Indicates an expired allocation has been removed.
@@ -50,51 +55,98 @@ pub fn allocation_removed(
     rt: &impl Runtime,
     id: AllocationID,
     alloc: &Allocation,
+) -> Result<(), ActorError> {
+    allocation_event(rt, "allocation-removed", id, alloc)
+}
+
+/// Indicates an allocation has been claimed.
+pub fn claim(rt: &impl Runtime, id: ClaimID, claim: &Claim) -> Result<(), ActorError> {
+    claim_event(rt, "claim", id, claim)?;
+    claim_event(rt, "claim-created", id, claim)
+}
+
+/// Indicates an existing claim has been updated (e.g. with a longer term).
+pub fn claim_updated(rt: &impl Runtime, id: ClaimID, claim: &Claim) -> Result<(), ActorError> {
+    claim_event(rt, "claim-updated", id, claim)?;
+    claim_event(rt, "claim-extended", id, claim)
+}
+
+/// Indicates an expired claim has been removed.
+pub fn claim_removed(rt: &impl Runtime, id: ClaimID, claim: &Claim) -> Result<(), ActorError> {
+    claim_event(rt, "claim-removed", id, claim)?;
+    claim_event(rt, "claim-expired", id, claim)
+}
+
+/// Indicates the root key has suspended or resumed new allocations and claims.
+pub fn paused(rt: &impl Runtime, paused: bool) -> Result<(), ActorError> {
+    rt.emit_event(&EventBuilder::new().typ("paused").field("paused", &paused).build()?)
+}
+
+/// Indicates the root key has proposed a new root key, which may accept the role from an epoch.
+pub fn root_key_proposed(
+    rt: &impl Runtime,
+    new_root_key: ActorID,
+    effective_epoch: ChainEpoch,
 ) -> Result<(), ActorError> {
     rt.emit_event(
         &EventBuilder::new()
-            .typ("allocation-removed")
-            .with_parties(id, alloc.client, alloc.provider)
-            .with_piece(&alloc.data, alloc.size.0)
-            .with_term(alloc.term_min, alloc.term_max)
-            .field("expiration", &alloc.expiration)
+            .typ("root-key-proposed")
+            .field_indexed("new-root-key", &new_root_key)
+            .field("effective-epoch", &effective_epoch)
             .build()?,
     )
 }
 
-/// Indicates an allocation has been claimed.
-pub fn claim(rt: &impl Runtime, id: ClaimID, claim: &Claim) -> Result<(), ActorError> {
+/// Indicates the root key has cancelled a pending proposal of a new root key.
+pub fn root_key_proposal_cancelled(
+    rt: &impl Runtime,
+    new_root_key: ActorID,
+) -> Result<(), ActorError> {
     rt.emit_event(
         &EventBuilder::new()
-            .typ("claim")
-            .with_parties(id, claim.client, claim.provider)
-            .with_piece(&claim.data, claim.size.0)
-            .with_term(claim.term_min, claim.term_max)
-            .field("term-start", &claim.term_start)
-            .field_indexed("sector", &claim.sector)
+            .typ("root-key-proposal-cancelled")
+            .field_indexed("new-root-key", &new_root_key)
             .build()?,
     )
 }
 
-/// Indicates an existing claim has been updated (e.g. with a longer term).
-pub fn claim_updated(rt: &impl Runtime, id: ClaimID, claim: &Claim) -> Result<(), ActorError> {
+/// Indicates a proposed root key has accepted the role, replacing the previous root key.
+pub fn root_key_changed(
+    rt: &impl Runtime,
+    old_root_key: ActorID,
+    new_root_key: ActorID,
+) -> Result<(), ActorError> {
     rt.emit_event(
         &EventBuilder::new()
-            .typ("claim-updated")
-            .with_parties(id, claim.client, claim.provider)
-            .with_piece(&claim.data, claim.size.0)
-            .with_term(claim.term_min, claim.term_max)
-            .field("term-start", &claim.term_start)
-            .field_indexed("sector", &claim.sector)
+            .typ("root-key-changed")
+            .field_indexed("old-root-key", &old_root_key)
+            .field_indexed("new-root-key", &new_root_key)
             .build()?,
     )
 }
 
-/// Indicates an expired claim has been removed.
-pub fn claim_removed(rt: &impl Runtime, id: ClaimID, claim: &Claim) -> Result<(), ActorError> {
+// Private helpers //
+fn allocation_event(
+    rt: &impl Runtime,
+    typ: &str,
+    id: AllocationID,
+    alloc: &Allocation,
+) -> Result<(), ActorError> {
+    rt.emit_event(
+        &EventBuilder::new()
+            .typ(typ)
+            .with_parties(id, alloc.client, alloc.provider)
+            .with_piece(&alloc.data, alloc.size.0)
+            .with_term(alloc.term_min, alloc.term_max)
+            .field("expiration", &alloc.expiration)
+            .build()?,
+    )
+}
+
+fn claim_event(rt: &impl Runtime, typ: &str, id: ClaimID, claim: &Claim) -> Result<(), ActorError> {
     rt.emit_event(
         &EventBuilder::new()
-            .typ("claim-removed")
+            .typ(typ)
             .with_parties(id, claim.client, claim.provider)
             .with_piece(&claim.data, claim.size.0)
             .with_term(claim.term_min, claim.term_max)
@@ -104,7 +156,6 @@ pub fn claim_removed(rt: &impl Runtime, id: ClaimID, claim: &Claim) -> Result<()
     )
 }
 
-// Private helpers //
 trait WithParties {
     fn with_parties(self, id: AllocationID, client: ActorID, provider: ActorID) -> EventBuilder;
 }
@@ -29,6 +29,8 @@ pub mod datacap {
         Balance = frc42_dispatch::method_hash!("Balance"),
         Transfer = frc42_dispatch::method_hash!("Transfer"),
         Burn = frc42_dispatch::method_hash!("Burn"),
+        SetPaused = frc42_dispatch::method_hash!("SetPaused"),
+        SetTransferRules = frc42_dispatch::method_hash!("SetTransferRules"),
     }
 
     #[derive(Clone, Debug, PartialEq, Eq, Serialize_tuple, Deserialize_tuple)]
@@ -43,4 +45,16 @@ pub mod datacap {
         pub owner: Address,
         pub amount: TokenAmount,
     }
+
+    #[derive(Clone, Debug, PartialEq, Eq, Serialize_tuple, Deserialize_tuple)]
+    #[serde(transparent)]
+    pub struct SetPausedParams {
+        pub paused: bool,
+    }
+
+    #[derive(Clone, Debug, PartialEq, Eq, Serialize_tuple, Deserialize_tuple)]
+    #[serde(transparent)]
+    pub struct SetTransferRulesParams {
+        pub rules: Vec<crate::DatacapTransferRule>,
+    }
 }
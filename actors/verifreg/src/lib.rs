@@ -23,19 +23,21 @@ use fil_actors_runtime::cbor::deserialize;
 use fil_actors_runtime::runtime::builtins::Type;
 use fil_actors_runtime::runtime::{ActorCode, Policy, Runtime};
 use fil_actors_runtime::{
-    actor_dispatch, actor_error, deserialize_block, extract_send_result, resolve_to_actor_id,
-    ActorError, BatchReturn, DATACAP_TOKEN_ACTOR_ADDR, STORAGE_MARKET_ACTOR_ADDR,
-    SYSTEM_ACTOR_ADDR, VERIFIED_REGISTRY_ACTOR_ADDR,
+    actor_dispatch, actor_error, deserialize_block, extract_send_result, migrate_state,
+    resolve_to_actor_id, ActorError, BatchReturn, OnUpgradeParams, DATACAP_TOKEN_ACTOR_ADDR,
+    STORAGE_MARKET_ACTOR_ADDR, SYSTEM_ACTOR_ADDR, VERIFIED_REGISTRY_ACTOR_ADDR,
 };
 use fil_actors_runtime::{ActorContext, AsActorError, BatchReturnGen};
 
 use crate::ext::datacap::{DestroyParams, MintParams};
+use crate::migration::LegacyState;
 use crate::state::{
     DataCapMap, RemoveDataCapProposalMap, DATACAP_MAP_CONFIG, REMOVE_DATACAP_PROPOSALS_CONFIG,
 };
 
 pub use self::state::Allocation;
 pub use self::state::Claim;
+pub use self::state::RootKeyProposal;
 pub use self::state::State;
 pub use self::types::*;
 
@@ -45,8 +47,14 @@ fil_actors_runtime::wasm_trampoline!(Actor);
 mod emit;
 
 pub mod expiration;
+#[cfg(feature = "testing")]
 pub mod ext;
+#[cfg(not(feature = "testing"))]
+#[allow(dead_code)]
+mod ext;
+pub mod migration;
 pub mod state;
+#[cfg(feature = "testing")]
 pub mod testing;
 pub mod types;
 
@@ -72,6 +80,17 @@ pub enum Method {
     GetClaimsExported = frc42_dispatch::method_hash!("GetClaims"),
     ExtendClaimTermsExported = frc42_dispatch::method_hash!("ExtendClaimTerms"),
     RemoveExpiredClaimsExported = frc42_dispatch::method_hash!("RemoveExpiredClaims"),
+    SetPausedExported = frc42_dispatch::method_hash!("SetPaused"),
+    IsPausedExported = frc42_dispatch::method_hash!("IsPaused"),
+    SetDatacapTransferRulesExported = frc42_dispatch::method_hash!("SetDatacapTransferRules"),
+    ProposeRootKeyExported = frc42_dispatch::method_hash!("ProposeRootKey"),
+    CancelRootKeyProposalExported = frc42_dispatch::method_hash!("CancelRootKeyProposal"),
+    AcceptRootKeyExported = frc42_dispatch::method_hash!("AcceptRootKey"),
+    ExtendClaimsWithDatacapExported = frc42_dispatch::method_hash!("ExtendClaimsWithDatacap"),
+    ListAllocationsExported = frc42_dispatch::method_hash!("ListAllocations"),
+    ListClaimsExported = frc42_dispatch::method_hash!("ListClaims"),
+    AddVerifiedClientWithAllocationsExported =
+        frc42_dispatch::method_hash!("AddVerifiedClientWithAllocations"),
     UniversalReceiverHook = frc42_dispatch::method_hash!("Receive"),
 }
 
@@ -114,6 +133,14 @@ impl Actor {
         if verifier_addr == st.root_key {
             return Err(actor_error!(illegal_argument, "Rootkey cannot be added as verifier"));
         }
+        // Disallow a proposed root key as a verifier.
+        if st.root_key_proposal.as_ref().is_some_and(|p| p.new_root_key == verifier_addr) {
+            return Err(actor_error!(
+                illegal_argument,
+                "proposed root key {} cannot be added as verifier",
+                verifier_addr
+            ));
+        }
 
         // Disallow existing clients as verifiers.
         let token_balance = balance(rt, &verifier_addr)?;
@@ -169,47 +196,7 @@ impl Actor {
         let client = Address::new_id(client_id);
 
         rt.transaction(|st: &mut State, rt| {
-            if client == st.root_key {
-                return Err(actor_error!(illegal_argument, "root cannot be added as client"));
-            }
-
-            // Validate caller is one of the verifiers, i.e. has an allowance (even if zero).
-            let verifier_addr = rt.message().caller();
-            let verifier_cap =
-                st.get_verifier_cap(rt.store(), &verifier_addr)?.ok_or_else(|| {
-                    actor_error!(not_found, "caller {} is not a verifier", verifier_addr)
-                })?;
-
-            // Disallow existing verifiers as clients.
-            if st.get_verifier_cap(rt.store(), &client)?.is_some() {
-                return Err(actor_error!(
-                    illegal_argument,
-                    "verifier {} cannot be added as a verified client",
-                    client
-                ));
-            }
-
-            // Compute new verifier allowance.
-            if verifier_cap < params.allowance {
-                return Err(actor_error!(
-                    illegal_argument,
-                    "add more DataCap {} for client than allocated {}",
-                    params.allowance,
-                    verifier_cap
-                ));
-            }
-
-            // Reduce verifier's cap.
-            let new_verifier_cap = verifier_cap - &params.allowance;
-            st.put_verifier(rt.store(), &verifier_addr, &new_verifier_cap)
-                .context("failed to update verifier allowance")?;
-
-            emit::verifier_balance(
-                rt,
-                verifier_addr.id().unwrap(),
-                &new_verifier_cap,
-                Some(client.id().unwrap()),
-            )
+            use_verifier_allowance(rt, st, &client, &params.allowance)
         })?;
 
         // Credit client token allowance.
@@ -221,6 +208,84 @@ impl Actor {
         Ok(())
     }
 
+    /// Grants datacap to a client and creates allocations for that client in the same message,
+    /// without the client having to transfer the datacap back to the registry.
+    /// The calling verifier's allowance is reduced by the client allowance plus the total size
+    /// of the allocations. The datacap backing the allocations is minted directly to the registry.
+    /// All allocations must succeed or the whole grant is aborted.
+    pub fn add_verified_client_with_allocations(
+        rt: &impl Runtime,
+        params: AddVerifiedClientWithAllocationsParams,
+    ) -> Result<AddVerifiedClientWithAllocationsReturn, ActorError> {
+        // The caller will be verified by checking the verifiers table.
+        rt.validate_immediate_caller_accept_any()?;
+        rt.state::<State>()?.check_not_paused()?;
+
+        if params.allocations.is_empty() {
+            return Err(actor_error!(illegal_argument, "no allocations requested"));
+        }
+        if params.allowance.is_negative()
+            || (!params.allowance.is_zero()
+                && params.allowance < rt.policy().minimum_verified_allocation_size)
+        {
+            return Err(actor_error!(
+                illegal_argument,
+                "allowance {} below MinVerifiedDealSize for add verified client {}",
+                params.allowance,
+                params.address
+            ));
+        }
+
+        let client_id = resolve_to_actor_id(rt, &params.address, true)?;
+        let client = Address::new_id(client_id);
+        let curr_epoch = rt.curr_epoch();
+
+        let mut new_allocs = Vec::with_capacity(params.allocations.len());
+        let mut allocations_total = DataCap::zero();
+        for req in &params.allocations {
+            validate_new_allocation(req, rt.policy(), curr_epoch)?;
+            check_miner_id(rt, req.provider)?;
+            new_allocs.push(Allocation {
+                client: client_id,
+                provider: req.provider,
+                data: req.data,
+                size: req.size,
+                term_min: req.term_min,
+                term_max: req.term_max,
+                expiration: req.expiration,
+            });
+            allocations_total += DataCap::from(req.size.0);
+        }
+
+        rt.transaction(|st: &mut State, rt| {
+            use_verifier_allowance(rt, st, &client, &(&params.allowance + &allocations_total))
+        })?;
+
+        if !params.allowance.is_zero() {
+            let operators = vec![STORAGE_MARKET_ACTOR_ADDR];
+            mint(rt, &client, &params.allowance, operators).context(format!(
+                "failed to mint {} data cap to client {}",
+                &params.allowance, client
+            ))?;
+        }
+        // The registry holds the datacap for the allocations, as if the client had transferred it.
+        // It is burnt when the allocations are claimed, or returned to the client on expiry.
+        mint(rt, &rt.message().receiver(), &allocations_total, vec![]).context(format!(
+            "failed to mint {} data cap for allocations to client {}",
+            &allocations_total, client
+        ))?;
+
+        let new_allocations = rt.transaction(|st: &mut State, rt| {
+            let ids = st.insert_allocations(rt.store(), client_id, new_allocs.clone())?;
+            for (id, alloc) in ids.iter().zip(new_allocs.iter()) {
+                emit::allocation(rt, *id, alloc)?;
+            }
+            Ok(ids)
+        })?;
+
+        Ok(AddVerifiedClientWithAllocationsReturn { new_allocations })
+    }
+
     /// Removes DataCap allocated to a verified client.
     pub fn remove_verified_client_data_cap(
         rt: &impl Runtime,
@@ -398,6 +463,7 @@ impl Actor {
         let mut total_claimed_space = DataCap::zero();
 
         rt.transaction(|st: &mut State, rt| {
+            st.check_not_paused()?;
             let mut claims = st.load_claims(rt.store())?;
             let mut allocs = st.load_allocs(rt.store())?;
 
@@ -515,6 +581,44 @@ impl Actor {
         Ok(GetClaimsReturn { batch_info: batch_gen.gen(), claims })
     }
 
+    /// Lists a client's allocations, a page at a time.
+    /// At most `limit` allocations are returned per call. The returned cursor should be passed
+    /// to a subsequent call to resume the listing, and is absent once it is complete.
+    /// Allocations are not listed in order of ID.
+    pub fn list_allocations(
+        rt: &impl Runtime,
+        params: ListAllocationsParams,
+    ) -> Result<ListAllocationsReturn, ActorError> {
+        rt.validate_immediate_caller_accept_any()?;
+        if params.limit == 0 {
+            return Err(actor_error!(illegal_argument, "limit must be positive"));
+        }
+        let st: State = rt.state()?;
+        let mut allocs = st.load_allocs(rt.store())?;
+        let (allocations, next_cursor) =
+            state::list_page(&mut allocs, params.client, params.cursor, params.limit)?;
+        Ok(ListAllocationsReturn { allocations, next_cursor })
+    }
+
+    /// Lists a provider's claims, a page at a time.
+    /// At most `limit` claims are returned per call. The returned cursor should be passed
+    /// to a subsequent call to resume the listing, and is absent once it is complete.
+    /// Claims are not listed in order of ID.
+    pub fn list_claims(
+        rt: &impl Runtime,
+        params: ListClaimsParams,
+    ) -> Result<ListClaimsReturn, ActorError> {
+        rt.validate_immediate_caller_accept_any()?;
+        if params.limit == 0 {
+            return Err(actor_error!(illegal_argument, "limit must be positive"));
+        }
+        let st: State = rt.state()?;
+        let mut claims = st.load_claims(rt.store())?;
+        let (claims, next_cursor) =
+            state::list_page(&mut claims, params.provider, params.cursor, params.limit)?;
+        Ok(ListClaimsReturn { claims, next_cursor })
+    }
+
     /// Extends the maximum term of some claims up to the largest value they could have been
     /// originally allocated.
     /// Callable only by the claims' client.
@@ -584,6 +688,67 @@ impl Actor {
         Ok(batch_gen.gen())
     }
 
+    /// Extends the maximum term of some claims by spending the caller's datacap.
+    /// Callable by any actor holding datacap, not only the claims' client.
+    /// The claims' client is not changed.
+    /// Each extension spends datacap equal to the claim's size, which is destroyed.
+    /// Extensions are subject to the same limits as those requested through a datacap transfer:
+    /// the term may be extended up to the maximum term after the current epoch,
+    /// but an expired claim cannot be extended.
+    pub fn extend_claims_with_datacap(
+        rt: &impl Runtime,
+        params: ExtendClaimsWithDatacapParams,
+    ) -> Result<ExtendClaimsWithDatacapReturn, ActorError> {
+        // Permissions are checked by the datacap actor when the caller's datacap is destroyed.
+        rt.validate_immediate_caller_accept_any()?;
+        let caller = rt.message().caller();
+        let curr_epoch = rt.curr_epoch();
+
+        let st: State = rt.state()?;
+        st.check_not_paused()?;
+        let mut claims = st.load_claims(rt.store())?;
+        let mut batch_gen = BatchReturnGen::new(params.extensions.len());
+        let mut updated_claims = Vec::<(ClaimID, Claim)>::new();
+        let mut datacap_total = DataCap::zero();
+        for req in &params.extensions {
+            // Claim IDs are unique across providers.
+            if updated_claims.iter().any(|(id, _)| *id == req.claim) {
+                batch_gen.add_fail(ExitCode::USR_ILLEGAL_ARGUMENT);
+                info!("duplicate extension of claim {}", req.claim);
+                continue;
+            }
+            let claim = match state::get_claim(&mut claims, req.provider, req.claim)? {
+                Some(claim) => claim,
+                None => {
+                    batch_gen.add_fail(ExitCode::USR_NOT_FOUND);
+                    info!("no claim {} for provider {}", req.claim, req.provider);
+                    continue;
+                }
+            };
+            if let Err(e) = validate_claim_extension(req, claim, rt.policy(), curr_epoch) {
+                batch_gen.add_fail(e.exit_code());
+                info!("{}", e.msg());
+                continue;
+            }
+            datacap_total += DataCap::from(claim.size.0);
+            updated_claims.push((req.claim, Claim { term_max: req.term_max, ..*claim }));
+            batch_gen.add_success();
+        }
+
+        // Destroy the datacap spent on the extensions.
+        // This fails if the caller's balance is insufficient, aborting all extensions.
+        destroy(rt, &caller, &datacap_total)?;
+
+        rt.transaction(|st: &mut State, rt| {
+            st.put_claims(rt.store(), updated_claims.clone())?;
+            for (id, claim) in updated_claims {
+                emit::claim_updated(rt, id, &claim)?;
+            }
+            Ok(())
+        })?;
+        Ok(batch_gen.gen())
+    }
+
     // A claim may be removed after its maximum term has elapsed (by anyone).
     // If no claims are specified, all eligible claims are removed.
     pub fn remove_expired_claims(
@@ -634,6 +799,137 @@ impl Actor {
         Ok(RemoveExpiredClaimsReturn { considered, results: batch_ret })
     }
 
+    /// Suspends or resumes new allocations and claims in the registry, and minting and
+    /// transfers of the data cap token, as an emergency circuit breaker.
+    /// Only the root key can call this method.
+    pub fn set_paused(rt: &impl Runtime, params: SetPausedParams) -> Result<(), ActorError> {
+        rt.transaction(|st: &mut State, rt| {
+            rt.validate_immediate_caller_is(std::iter::once(&st.root_key))?;
+            st.paused = params.registry;
+            Ok(())
+        })?;
+        emit::paused(rt, params.registry)?;
+
+        let datacap_params = ext::datacap::SetPausedParams { paused: params.datacap };
+        extract_send_result(rt.send_simple(
+            &DATACAP_TOKEN_ACTOR_ADDR,
+            ext::datacap::Method::SetPaused as u64,
+            IpldBlock::serialize_cbor(&datacap_params)?,
+            TokenAmount::zero(),
+        ))
+        .context("failed to send set paused to datacap")?;
+        Ok(())
+    }
+
+    /// Returns whether new allocations and claims are suspended.
+    pub fn is_paused(rt: &impl Runtime) -> Result<IsPausedReturn, ActorError> {
+        rt.validate_immediate_caller_accept_any()?;
+        let st: State = rt.state()?;
+        Ok(IsPausedReturn { paused: st.paused })
+    }
+
+    /// Replaces the rules describing which transfers of the data cap token are allowed.
+    /// Only the root key can call this method.
+    pub fn set_datacap_transfer_rules(
+        rt: &impl Runtime,
+        params: SetDatacapTransferRulesParams,
+    ) -> Result<(), ActorError> {
+        let st: State = rt.state()?;
+        rt.validate_immediate_caller_is(std::iter::once(&st.root_key))?;
+
+        let datacap_params = ext::datacap::SetTransferRulesParams { rules: params.rules };
+        extract_send_result(rt.send_simple(
+            &DATACAP_TOKEN_ACTOR_ADDR,
+            ext::datacap::Method::SetTransferRules as u64,
+            IpldBlock::serialize_cbor(&datacap_params)?,
+            TokenAmount::zero(),
+        ))
+        .context("failed to send set transfer rules to datacap")?;
+        Ok(())
+    }
+
+    /// Proposes a new root key, replacing any pending proposal.
+    /// The new key may accept the role after the policy's rotation delay, during which
+    /// the current root key may cancel the proposal.
+    pub fn propose_root_key(
+        rt: &impl Runtime,
+        params: ProposeRootKeyParams,
+    ) -> Result<ProposeRootKeyReturn, ActorError> {
+        let new_root_key = resolve_to_actor_id(rt, &params.new_root_key, true)?;
+        let new_root_key_addr = Address::new_id(new_root_key);
+        let effective_epoch = rt.curr_epoch() + rt.policy().verifreg_root_key_rotation_delay;
+
+        rt.transaction(|st: &mut State, rt| {
+            rt.validate_immediate_caller_is(std::iter::once(&st.root_key))?;
+            if new_root_key_addr == st.root_key {
+                return Err(actor_error!(
+                    illegal_argument,
+                    "{} is already the root key",
+                    new_root_key_addr
+                ));
+            }
+            // The root key cannot also be a verifier.
+            if st.get_verifier_cap(rt.store(), &new_root_key_addr)?.is_some() {
+                return Err(actor_error!(
+                    illegal_argument,
+                    "verifier {} cannot become the root key",
+                    new_root_key_addr
+                ));
+            }
+            st.root_key_proposal =
+                Some(RootKeyProposal { new_root_key: new_root_key_addr, effective_epoch });
+            Ok(())
+        })?;
+
+        emit::root_key_proposed(rt, new_root_key, effective_epoch)?;
+        Ok(ProposeRootKeyReturn { effective_epoch })
+    }
+
+    /// Cancels the pending proposal of a new root key.
+    pub fn cancel_root_key_proposal(rt: &impl Runtime) -> Result<(), ActorError> {
+        let proposal = rt.transaction(|st: &mut State, rt| {
+            rt.validate_immediate_caller_is(std::iter::once(&st.root_key))?;
+            st.root_key_proposal
+                .take()
+                .ok_or_else(|| actor_error!(not_found, "no root key proposal is pending"))
+        })?;
+
+        emit::root_key_proposal_cancelled(rt, proposal.new_root_key.id().unwrap())
+    }
+
+    /// Accepts the root key role on behalf of the proposed new root key,
+    /// once the proposal's timelock has passed.
+    pub fn accept_root_key(rt: &impl Runtime) -> Result<(), ActorError> {
+        let curr_epoch = rt.curr_epoch();
+        let (old_root_key, new_root_key) = rt.transaction(|st: &mut State, rt| {
+            let proposal = st
+                .root_key_proposal
+                .clone()
+                .ok_or_else(|| actor_error!(not_found, "no root key proposal is pending"))?;
+            rt.validate_immediate_caller_is(std::iter::once(&proposal.new_root_key))?;
+            if curr_epoch < proposal.effective_epoch {
+                return Err(actor_error!(
+                    forbidden,
+                    "root key proposal cannot be accepted before epoch {}",
+                    proposal.effective_epoch
+                ));
+            }
+            // The root key cannot also be a verifier.
+            if st.get_verifier_cap(rt.store(), &proposal.new_root_key)?.is_some() {
+                return Err(actor_error!(
+                    illegal_argument,
+                    "verifier {} cannot become the root key",
+                    proposal.new_root_key
+                ));
+            }
+            let old_root_key = std::mem::replace(&mut st.root_key, proposal.new_root_key);
+            st.root_key_proposal = None;
+            Ok((old_root_key, proposal.new_root_key))
+        })?;
+
+        emit::root_key_changed(rt, old_root_key.id().unwrap(), new_root_key.id().unwrap())
+    }
+
     // Receives data cap tokens (only) and creates allocations according to one or more
     // allocation requests specified in the transfer's operator data.
     // The token amount received must exactly correspond to the sum of the requested allocation sizes.
@@ -646,12 +942,25 @@ impl Actor {
     ) -> Result<AllocationsResponse, ActorError> {
         // Accept only the data cap token.
         rt.validate_immediate_caller_is(&[DATACAP_TOKEN_ACTOR_ADDR])?;
+        rt.state::<State>()?.check_not_paused()?;
 
         let my_id = rt.message().receiver().id().unwrap();
         let curr_epoch = rt.curr_epoch();
 
         // Validate receiver hook payload.
         let tokens_received = validate_tokens_received(&params, my_id)?;
+
+        // Datacap minted to the registry by itself backs allocations that were created
+        // directly, by AddVerifiedClientWithAllocations.
+        if tokens_received.operator == my_id
+            && tokens_received.from == DATACAP_TOKEN_ACTOR_ADDR.id().unwrap()
+        {
+            return Ok(AllocationsResponse {
+                allocation_results: BatchReturn::empty(),
+                extension_results: BatchReturn::empty(),
+                new_allocations: vec![],
+            });
+        }
         let client = tokens_received.from;
 
         // Extract and validate allocation request from the operator data.
@@ -739,6 +1048,59 @@ impl Actor {
     }
 }
 
+// Reduces the calling verifier's allowance by an amount granted to a client.
+fn use_verifier_allowance(
+    rt: &impl Runtime,
+    st: &mut State,
+    client: &Address,
+    amount: &DataCap,
+) -> Result<(), ActorError> {
+    if *client == st.root_key {
+        return Err(actor_error!(illegal_argument, "root cannot be added as client"));
+    }
+    // The registry holds datacap only to back allocations.
+    if *client == rt.message().receiver() {
+        return Err(actor_error!(illegal_argument, "registry cannot be added as client"));
+    }
+
+    // Validate caller is one of the verifiers, i.e. has an allowance (even if zero).
+    let verifier_addr = rt.message().caller();
+    let verifier_cap = st
+        .get_verifier_cap(rt.store(), &verifier_addr)?
+        .ok_or_else(|| actor_error!(not_found, "caller {} is not a verifier", verifier_addr))?;
+
+    // Disallow existing verifiers as clients.
+    if st.get_verifier_cap(rt.store(), client)?.is_some() {
+        return Err(actor_error!(
+            illegal_argument,
+            "verifier {} cannot be added as a verified client",
+            client
+        ));
+    }
+
+    // Compute new verifier allowance.
+    if verifier_cap < *amount {
+        return Err(actor_error!(
+            illegal_argument,
+            "add more DataCap {} for client than allocated {}",
+            amount,
+            verifier_cap
+        ));
+    }
+
+    // Reduce verifier's cap.
+    let new_verifier_cap = verifier_cap - amount;
+    st.put_verifier(rt.store(), &verifier_addr, &new_verifier_cap)
+        .context("failed to update verifier allowance")?;
+
+    emit::verifier_balance(
+        rt,
+        verifier_addr.id().unwrap(),
+        &new_verifier_cap,
+        Some(client.id().unwrap()),
+    )
+}
+
 // Checks whether an address has a verifier entry (which could be zero).
 fn is_verifier(rt: &impl Runtime, st: &State, address: Address) -> Result<bool, ActorError> {
     let verifiers = DataCapMap::load(rt.store(), &st.verifiers, DATACAP_MAP_CONFIG, "verifiers")?;
@@ -1098,6 +1460,14 @@ impl ActorCode for Actor {
         "VerifiedRegistry"
     }
 
+    fn on_upgrade<RT>(rt: &RT, params: OnUpgradeParams) -> Result<(), ActorError>
+    where
+        RT: Runtime,
+        RT::Blockstore: Clone,
+    {
+        migrate_state(rt, &params.old_state, |_, legacy: LegacyState| Ok(State::from(legacy)))
+    }
+
     actor_dispatch! {
         Constructor => constructor,
         AddVerifier => add_verifier,
@@ -1109,6 +1479,16 @@ impl ActorCode for Actor {
         GetClaims|GetClaimsExported => get_claims,
         ExtendClaimTerms|ExtendClaimTermsExported => extend_claim_terms,
         RemoveExpiredClaims|RemoveExpiredClaimsExported => remove_expired_claims,
+        SetPausedExported => set_paused,
+        IsPausedExported => is_paused,
+        SetDatacapTransferRulesExported => set_datacap_transfer_rules,
+        ProposeRootKeyExported => propose_root_key,
+        CancelRootKeyProposalExported => cancel_root_key_proposal,
+        AcceptRootKeyExported => accept_root_key,
+        ExtendClaimsWithDatacapExported => extend_claims_with_datacap,
+        ListAllocationsExported => list_allocations,
+        ListClaimsExported => list_claims,
+        AddVerifiedClientWithAllocationsExported => add_verified_client_with_allocations,
         UniversalReceiverHook => universal_receiver_hook,
     }
 }
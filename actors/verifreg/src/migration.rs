@@ -0,0 +1,38 @@
+// Copyright 2019-2022 ChainSafe Systems
+// SPDX-License-Identifier: Apache-2.0, MIT
+
+use cid::Cid;
+use fvm_ipld_encoding::tuple::*;
+use fvm_shared::address::Address;
+
+use fil_actors_runtime::IdSequence;
+
+use crate::State;
+
+/// Verified registry state as stored before the root key could pause the registry.
+#[derive(Serialize_tuple, Deserialize_tuple, Debug, Clone)]
+pub struct LegacyState {
+    pub root_key: Address,
+    pub verifiers: Cid,
+    pub remove_data_cap_proposal_ids: Cid,
+    pub allocations: Cid,
+    pub next_allocation_id: IdSequence,
+    pub claims: Cid,
+}
+
+impl From<LegacyState> for State {
+    /// Migrates to the current state schema, with the registry unpaused and no change of root
+    /// key pending.
+    fn from(legacy: LegacyState) -> Self {
+        State {
+            root_key: legacy.root_key,
+            verifiers: legacy.verifiers,
+            remove_data_cap_proposal_ids: legacy.remove_data_cap_proposal_ids,
+            allocations: legacy.allocations,
+            next_allocation_id: legacy.next_allocation_id,
+            claims: legacy.claims,
+            paused: false,
+            root_key_proposal: None,
+        }
+    }
+}
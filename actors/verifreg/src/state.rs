@@ -11,12 +11,15 @@ use fvm_shared::error::ExitCode;
 use fvm_shared::piece::PaddedPieceSize;
 use fvm_shared::sector::SectorNumber;
 use fvm_shared::{ActorID, HAMT_BIT_WIDTH};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
 
 use fil_actors_runtime::{
-    actor_error, ActorError, AsActorError, Config, Map2, MapMap, DEFAULT_HAMT_CONFIG,
+    actor_error, parse_uint_key, ActorError, ActorState, AsActorError, Config, IdSequence, Map2,
+    MapMap, DEFAULT_HAMT_CONFIG,
 };
 
-use crate::{AddrPairKey, AllocationID, ClaimID};
+use crate::{AddrPairKey, AllocationID, ClaimID, MAX_LIST_PAGE_SIZE};
 use crate::{DataCap, RemoveDataCapProposalID};
 
 pub type DataCapMap<BS> = Map2<BS, Address, BigIntDe>;
@@ -25,7 +28,7 @@ pub const DATACAP_MAP_CONFIG: Config = DEFAULT_HAMT_CONFIG;
 pub type RemoveDataCapProposalMap<BS> = Map2<BS, AddrPairKey, RemoveDataCapProposalID>;
 pub const REMOVE_DATACAP_PROPOSALS_CONFIG: Config = DEFAULT_HAMT_CONFIG;
 
-#[derive(Serialize_tuple, Deserialize_tuple, Debug, Clone)]
+#[derive(Serialize_tuple, Deserialize_tuple, Debug, Clone, ActorState)]
 pub struct State {
     pub root_key: Address,
     // Maps verifier addresses to data cap minting allowance (in bytes).
@@ -35,9 +38,23 @@ pub struct State {
     pub allocations: Cid, // HAMT[ActorID]HAMT[AllocationID]Allocation
     // Next allocation identifier to use.
     // The value 0 is reserved to mean "no allocation".
-    pub next_allocation_id: u64,
+    pub next_allocation_id: IdSequence,
     // Maps provider IDs to allocations claimed by that provider.
     pub claims: Cid, // HAMT[ActorID]HAMT[ClaimID]Claim
+    // Whether new allocations and claims are suspended by the root key.
+    pub paused: bool,
+    // A pending change of the root key, if any.
+    pub root_key_proposal: Option<RootKeyProposal>,
+}
+
+/// A proposed replacement of the root key.
+/// The proposed key may accept the role once the proposal's timelock has passed,
+/// and until then the current root key may cancel it.
+#[derive(Serialize_tuple, Deserialize_tuple, Debug, Clone, PartialEq, Eq)]
+pub struct RootKeyProposal {
+    pub new_root_key: Address,
+    // First epoch at which the new root key may accept the role.
+    pub effective_epoch: ChainEpoch,
 }
 
 impl State {
@@ -55,11 +72,20 @@ impl State {
             verifiers: empty_dcap,
             remove_data_cap_proposal_ids: empty_dcap,
             allocations: empty_allocs_claims,
-            next_allocation_id: 1,
+            next_allocation_id: IdSequence::new(1),
             claims: empty_allocs_claims,
+            paused: false,
+            root_key_proposal: None,
         })
     }
 
+    pub fn check_not_paused(&self) -> Result<(), ActorError> {
+        if self.paused {
+            return Err(actor_error!(forbidden, "verified registry is paused"));
+        }
+        Ok(())
+    }
+
     // Adds a verifier and cap, overwriting any existing cap for that verifier.
     pub fn put_verifier(
         &mut self,
@@ -135,25 +161,12 @@ impl State {
             return Ok(vec![]);
         }
         let mut allocs = self.load_allocs(store)?;
-        // These local variables allow the id-associating map closure to move the allocations
-        // from the iterator rather than clone, without moving self.
-        let first_id = self.next_allocation_id;
-        let mut count = 0;
-        let count_ref = &mut count;
+        let ids = self.next_allocation_id.reserve(new_allocs.len() as u64)?;
         allocs
-            .put_many(
-                client,
-                new_allocs.into_iter().map(move |a| {
-                    let id = first_id + *count_ref;
-                    *count_ref += 1;
-                    (id, a)
-                }),
-            )
+            .put_many(client, ids.clone().zip(new_allocs))
             .context_code(ExitCode::USR_ILLEGAL_STATE, "failed to put allocations")?;
         self.save_allocs(&mut allocs)?;
-        self.next_allocation_id += count;
-        let allocated_ids = (first_id..first_id + count).collect();
-        Ok(allocated_ids)
+        Ok(ids.collect())
     }
 
     pub fn load_claims<'a, BS: Blockstore>(
@@ -250,6 +263,42 @@ where
         .context_code(ExitCode::USR_ILLEGAL_STATE, "HAMT lookup failure getting allocation")
 }
 
+// A page of records with their IDs, and the ID from which to resume listing, if any.
+type Page<T> = (Vec<(u64, T)>, Option<u64>);
+
+// Lists a page of the records in a collection for some owner, beginning at `cursor` (inclusive)
+// if provided, and returning at most `limit` (capped at `MAX_LIST_PAGE_SIZE`) records with
+// their IDs.
+// A cursor whose record has since been removed resumes with the records that followed it.
+// Also returns the ID from which to resume listing, or None if the listing is complete.
+pub fn list_page<T, BS>(
+    collection: &mut MapMap<BS, T, ActorID, u64>,
+    owner: ActorID,
+    cursor: Option<u64>,
+    limit: u64,
+) -> Result<Page<T>, ActorError>
+where
+    T: Serialize + DeserializeOwned + Clone + PartialEq,
+    BS: Blockstore,
+{
+    let limit = usize::try_from(limit.min(MAX_LIST_PAGE_SIZE))
+        .context_code(ExitCode::USR_ILLEGAL_ARGUMENT, "invalid limit")?;
+    let mut page = Vec::new();
+    let (_, next) = collection
+        .for_each_in_ranged(owner, cursor, Some(limit), |key, record| {
+            let id = parse_uint_key(key)
+                .context_code(ExitCode::USR_ILLEGAL_STATE, "failed to parse uint key")?;
+            page.push((id, record.clone()));
+            Ok(())
+        })
+        .context_code(ExitCode::USR_ILLEGAL_STATE, "failed to iterate over allocations/claims")?;
+    let next_cursor = next
+        .map(|key| parse_uint_key(&key))
+        .transpose()
+        .context_code(ExitCode::USR_ILLEGAL_STATE, "failed to parse uint key")?;
+    Ok((page, next_cursor))
+}
+
 pub fn get_claim<'a, BS>(
     claims: &'a mut MapMap<BS, Claim, ActorID, ClaimID>,
     provider: ActorID,
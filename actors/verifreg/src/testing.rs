@@ -11,7 +11,7 @@ use fil_actors_runtime::runtime::policy_constants::{
     MAXIMUM_VERIFIED_ALLOCATION_EXPIRATION, MAXIMUM_VERIFIED_ALLOCATION_TERM,
     MINIMUM_VERIFIED_ALLOCATION_SIZE, MINIMUM_VERIFIED_ALLOCATION_TERM,
 };
-use fil_actors_runtime::{Map2, MessageAccumulator, DEFAULT_HAMT_CONFIG};
+use fil_actors_runtime::{ActorState, Map2, MessageAccumulator, DEFAULT_HAMT_CONFIG};
 
 use crate::{Allocation, AllocationID, Claim, ClaimID, DataCap, State};
 
@@ -28,6 +28,7 @@ pub fn check_state_invariants<BS: Blockstore>(
     prior_epoch: ChainEpoch,
 ) -> (StateSummary, MessageAccumulator) {
     let acc = MessageAccumulator::default();
+    state.check_fields(&store, &acc);
 
     // Load and check verifiers
     let mut all_verifiers = HashMap::new();
@@ -70,7 +71,7 @@ pub fn check_state_invariants<BS: Blockstore>(
                                 allocation_id,
                                 allocation,
                                 client_id,
-                                state.next_allocation_id,
+                                state.next_allocation_id.peek(),
                                 prior_epoch,
                                 &acc,
                             );
@@ -111,7 +112,7 @@ pub fn check_state_invariants<BS: Blockstore>(
                                 claim_id,
                                 claim,
                                 provider_id,
-                                state.next_allocation_id,
+                                state.next_allocation_id.peek(),
                                 prior_epoch,
                                 &acc,
                             );
@@ -14,7 +14,7 @@ use fvm_shared::sector::StoragePower;
 use fvm_shared::ActorID;
 use std::fmt::{Debug, Formatter};
 
-use crate::Claim;
+use crate::{Allocation, Claim};
 
 pub type AllocationID = u64;
 pub type ClaimID = u64;
@@ -36,6 +36,23 @@ pub type AddVerifierParams = VerifierParams;
 
 pub type AddVerifiedClientParams = VerifierParams;
 
+/// Grants datacap to a client and spends some of it on allocations for that client in one step.
+/// The verifier's allowance is reduced by the allowance plus the total size of the allocations.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize_tuple, Deserialize_tuple)]
+pub struct AddVerifiedClientWithAllocationsParams {
+    pub address: Address,
+    // Datacap credited to the client's balance in addition to the allocations (may be zero).
+    #[serde(with = "bigint_ser")]
+    pub allowance: DataCap,
+    pub allocations: Vec<AllocationRequest>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize_tuple, Deserialize_tuple)]
+#[serde(transparent)]
+pub struct AddVerifiedClientWithAllocationsReturn {
+    pub new_allocations: Vec<AllocationID>,
+}
+
 #[derive(Clone, Debug, PartialEq, Eq, Serialize_tuple, Deserialize_tuple)]
 #[serde(transparent)]
 pub struct RemoveVerifierParams {
@@ -187,6 +204,13 @@ pub struct ExtendClaimTermsParams {
 
 pub type ExtendClaimTermsReturn = BatchReturn;
 
+#[derive(Clone, Debug, PartialEq, Eq, Serialize_tuple, Deserialize_tuple)]
+pub struct ExtendClaimsWithDatacapParams {
+    pub extensions: Vec<ClaimExtensionRequest>,
+}
+
+pub type ExtendClaimsWithDatacapReturn = BatchReturn;
+
 //
 // Receiver hook payload
 //
@@ -242,6 +266,43 @@ pub struct GetClaimsReturn {
     pub claims: Vec<Claim>,
 }
 
+/// Maximum number of records returned by one call listing allocations or claims.
+pub const MAX_LIST_PAGE_SIZE: u64 = 1000;
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize_tuple, Deserialize_tuple)]
+pub struct ListAllocationsParams {
+    pub client: ActorID,
+    /// Allocation from which to resume a previous listing, as returned in `next_cursor`.
+    pub cursor: Option<AllocationID>,
+    /// Maximum number of allocations to return, capped at `MAX_LIST_PAGE_SIZE`.
+    pub limit: u64,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize_tuple, Deserialize_tuple)]
+pub struct ListAllocationsReturn {
+    /// Allocations listed, with their IDs.
+    pub allocations: Vec<(AllocationID, Allocation)>,
+    /// Allocation from which to resume the listing, or None if it is complete.
+    pub next_cursor: Option<AllocationID>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize_tuple, Deserialize_tuple)]
+pub struct ListClaimsParams {
+    pub provider: ActorID,
+    /// Claim from which to resume a previous listing, as returned in `next_cursor`.
+    pub cursor: Option<ClaimID>,
+    /// Maximum number of claims to return, capped at `MAX_LIST_PAGE_SIZE`.
+    pub limit: u64,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize_tuple, Deserialize_tuple)]
+pub struct ListClaimsReturn {
+    /// Claims listed, with their IDs.
+    pub claims: Vec<(ClaimID, Claim)>,
+    /// Claim from which to resume the listing, or None if it is complete.
+    pub next_cursor: Option<ClaimID>,
+}
+
 #[derive(Clone, Debug, PartialEq, Eq, Serialize_tuple, Deserialize_tuple)]
 pub struct RemoveExpiredClaimsParams {
     // Provider to clean up (need not be the caller)
@@ -258,3 +319,45 @@ pub struct RemoveExpiredClaimsReturn {
     // Results for each processed claim.
     pub results: BatchReturn,
 }
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize_tuple, Deserialize_tuple)]
+pub struct SetPausedParams {
+    // Whether to suspend new allocations and claims in the registry.
+    pub registry: bool,
+    // Whether to suspend minting and transfers of the data cap token.
+    pub datacap: bool,
+}
+
+/// A pattern of data cap token transfer to be allowed.
+/// Each address matches any address when absent.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize_tuple, Deserialize_tuple)]
+pub struct DatacapTransferRule {
+    pub from: Option<Address>,
+    pub to: Option<Address>,
+    pub operator: Option<Address>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize_tuple, Deserialize_tuple)]
+#[serde(transparent)]
+pub struct SetDatacapTransferRulesParams {
+    pub rules: Vec<DatacapTransferRule>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize_tuple, Deserialize_tuple)]
+#[serde(transparent)]
+pub struct IsPausedReturn {
+    pub paused: bool,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize_tuple, Deserialize_tuple)]
+#[serde(transparent)]
+pub struct ProposeRootKeyParams {
+    pub new_root_key: Address,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize_tuple, Deserialize_tuple)]
+#[serde(transparent)]
+pub struct ProposeRootKeyReturn {
+    // First epoch at which the new root key may accept the role.
+    pub effective_epoch: ChainEpoch,
+}
@@ -20,15 +20,18 @@ use fvm_shared::sys::SendFlags;
 use fvm_shared::{ActorID, MethodNum};
 use num_traits::{ToPrimitive, Zero};
 
+use fil_actor_verifreg::migration::LegacyState;
 use fil_actor_verifreg::state::{DataCapMap, DATACAP_MAP_CONFIG};
 use fil_actor_verifreg::testing::check_state_invariants;
 use fil_actor_verifreg::{
-    ext, Actor as VerifregActor, AddVerifiedClientParams, AddVerifierParams, Allocation,
-    AllocationClaim, AllocationID, AllocationRequest, AllocationRequests, AllocationsResponse,
-    Claim, ClaimAllocationsParams, ClaimAllocationsReturn, ClaimExtensionRequest, ClaimID, DataCap,
-    ExtendClaimTermsParams, ExtendClaimTermsReturn, GetClaimsParams, GetClaimsReturn, Method,
+    ext, Actor as VerifregActor, AddVerifiedClientParams, AddVerifiedClientWithAllocationsParams,
+    AddVerifiedClientWithAllocationsReturn, AddVerifierParams, Allocation, AllocationClaim,
+    AllocationID, AllocationRequest, AllocationRequests, AllocationsResponse, Claim,
+    ClaimAllocationsParams, ClaimAllocationsReturn, ClaimExtensionRequest, ClaimID, DataCap,
+    ExtendClaimTermsParams, ExtendClaimTermsReturn, ExtendClaimsWithDatacapParams,
+    ExtendClaimsWithDatacapReturn, GetClaimsParams, GetClaimsReturn, Method,
     RemoveExpiredAllocationsParams, RemoveExpiredAllocationsReturn, RemoveExpiredClaimsParams,
-    RemoveExpiredClaimsReturn, SectorAllocationClaims, State,
+    RemoveExpiredClaimsReturn, SectorAllocationClaims, SetPausedParams, State,
 };
 use fil_actors_runtime::cbor::serialize;
 use fil_actors_runtime::runtime::builtins::Type;
@@ -184,6 +187,48 @@ impl Harness {
         Ok(())
     }
 
+    // Replaces the state with its layout before the upgrade migration.
+    pub fn replace_with_legacy_state(&self, rt: &MockRuntime) {
+        let st: State = rt.get_state();
+        rt.replace_state(&LegacyState {
+            root_key: st.root_key,
+            verifiers: st.verifiers,
+            remove_data_cap_proposal_ids: st.remove_data_cap_proposal_ids,
+            allocations: st.allocations,
+            next_allocation_id: st.next_allocation_id,
+            claims: st.claims,
+        });
+    }
+
+    pub fn set_paused(
+        &self,
+        rt: &MockRuntime,
+        registry: bool,
+        datacap: bool,
+    ) -> Result<(), ActorError> {
+        rt.expect_validate_caller_addr(vec![self.root]);
+        rt.expect_emitted_event(
+            EventBuilder::new().typ("paused").field("paused", &registry).build()?,
+        );
+        rt.expect_send_simple(
+            DATACAP_TOKEN_ACTOR_ADDR,
+            ext::datacap::Method::SetPaused as MethodNum,
+            IpldBlock::serialize_cbor(&ext::datacap::SetPausedParams { paused: datacap }).unwrap(),
+            TokenAmount::zero(),
+            None,
+            ExitCode::OK,
+        );
+        rt.set_caller(*ACCOUNT_ACTOR_CODE_ID, self.root);
+        let params = SetPausedParams { registry, datacap };
+        let ret = rt.call::<VerifregActor>(
+            Method::SetPausedExported as MethodNum,
+            IpldBlock::serialize_cbor(&params).unwrap(),
+        )?;
+        assert!(ret.is_none());
+        rt.verify();
+        Ok(())
+    }
+
     pub fn assert_verifier_allowance(
         &self,
         rt: &MockRuntime,
@@ -254,6 +299,94 @@ impl Harness {
         Ok(())
     }
 
+    #[allow(clippy::too_many_arguments)]
+    pub fn add_client_with_allocations(
+        &self,
+        rt: &MockRuntime,
+        verifier: &Address,
+        client: &Address,
+        allowance: &DataCap,
+        reqs: Vec<AllocationRequest>,
+        verifier_balance: &DataCap,
+        expected_alloc_ids: Vec<AllocationID>,
+    ) -> Result<(), ActorError> {
+        rt.expect_validate_caller_any();
+        rt.set_caller(*ACCOUNT_ACTOR_CODE_ID, *verifier);
+        let client_id = rt.get_id_address(client).unwrap_or(*client).id().unwrap();
+        let allocations_total: u64 = reqs.iter().map(|r| r.size.0).sum();
+
+        // Expect the client allowance to be minted to the client,
+        // and the allocations' datacap to the registry.
+        if !allowance.is_zero() {
+            let mint_params = ext::datacap::MintParams {
+                to: Address::new_id(client_id),
+                amount: TokenAmount::from_whole(allowance.to_i64().unwrap()),
+                operators: vec![STORAGE_MARKET_ACTOR_ADDR],
+            };
+            rt.expect_send_simple(
+                DATACAP_TOKEN_ACTOR_ADDR,
+                ext::datacap::Method::Mint as MethodNum,
+                IpldBlock::serialize_cbor(&mint_params).unwrap(),
+                TokenAmount::zero(),
+                None,
+                ExitCode::OK,
+            );
+        }
+        let mint_params = ext::datacap::MintParams {
+            to: VERIFIED_REGISTRY_ACTOR_ADDR,
+            amount: TokenAmount::from_whole(allocations_total as i64),
+            operators: vec![],
+        };
+        rt.expect_send_simple(
+            DATACAP_TOKEN_ACTOR_ADDR,
+            ext::datacap::Method::Mint as MethodNum,
+            IpldBlock::serialize_cbor(&mint_params).unwrap(),
+            TokenAmount::zero(),
+            None,
+            ExitCode::OK,
+        );
+
+        rt.expect_emitted_event(
+            EventBuilder::new()
+                .typ("verifier-balance")
+                .field_indexed("verifier", &verifier.id().unwrap())
+                .field("balance", &BigIntSer(&(verifier_balance - allowance - allocations_total)))
+                .field_indexed("client", &client_id)
+                .build()?,
+        );
+        for (req, id) in reqs.iter().zip(expected_alloc_ids.iter()) {
+            expect_allocation_emitted(
+                rt,
+                "allocation",
+                *id,
+                client_id,
+                req.provider,
+                &req.data,
+                req.size.0,
+                req.term_min,
+                req.term_max,
+                req.expiration,
+            )
+        }
+
+        let params = AddVerifiedClientWithAllocationsParams {
+            address: *client,
+            allowance: allowance.clone(),
+            allocations: reqs,
+        };
+        let ret: AddVerifiedClientWithAllocationsReturn = rt
+            .call::<VerifregActor>(
+                Method::AddVerifiedClientWithAllocationsExported as MethodNum,
+                IpldBlock::serialize_cbor(&params).unwrap(),
+            )?
+            .unwrap()
+            .deserialize()
+            .unwrap();
+        assert_eq!(expected_alloc_ids, ret.new_allocations);
+        rt.verify();
+        Ok(())
+    }
+
     pub fn check_state(&self, rt: &MockRuntime) {
         let (_, acc) = check_state_invariants(&rt.get_state(), rt.store(), *rt.epoch.borrow());
         acc.assert_empty();
@@ -267,11 +400,10 @@ impl Harness {
     ) -> Result<AllocationID, ActorError> {
         let mut st: State = rt.get_state();
         let mut allocs = st.load_allocs(rt.store()).unwrap();
-        let alloc_id = st.next_allocation_id;
+        let alloc_id = st.next_allocation_id.next_id()?;
         assert!(allocs
             .put_if_absent(alloc.client, alloc_id, alloc.clone())
             .context_code(ExitCode::USR_ILLEGAL_STATE, "faild to put")?);
-        st.next_allocation_id += 1;
         st.allocations = allocs.flush().expect("failed flushing allocation table");
         rt.replace_state(&st);
         Ok(alloc_id)
@@ -526,11 +658,10 @@ impl Harness {
     pub fn create_claim(&self, rt: &MockRuntime, claim: &Claim) -> Result<ClaimID, ActorError> {
         let mut st: State = rt.get_state();
         let mut claims = st.load_claims(rt.store()).unwrap();
-        let id = st.next_allocation_id;
+        let id = st.next_allocation_id.next_id()?;
         assert!(claims
             .put_if_absent(claim.provider, id, claim.clone())
             .context_code(ExitCode::USR_ILLEGAL_STATE, "faild to put")?);
-        st.next_allocation_id += 1;
         st.claims = claims.flush().expect("failed flushing allocation table");
         rt.replace_state(&st);
         Ok(id)
@@ -592,6 +723,60 @@ impl Harness {
         rt.verify();
         Ok(ret)
     }
+
+    // Extends claims with the caller's datacap, expecting the given amount of it to be destroyed
+    // and the given claims to be updated.
+    pub fn extend_claims_with_datacap(
+        &self,
+        rt: &MockRuntime,
+        caller: ActorID,
+        params: &ExtendClaimsWithDatacapParams,
+        expected_destroy: u64,
+        expected: Vec<(ClaimID, Claim)>,
+    ) -> Result<ExtendClaimsWithDatacapReturn, ActorError> {
+        rt.set_caller(*ACCOUNT_ACTOR_CODE_ID, Address::new_id(caller));
+        rt.expect_validate_caller_any();
+        if !expected_destroy.is_zero() {
+            rt.expect_send_simple(
+                DATACAP_TOKEN_ACTOR_ADDR,
+                ext::datacap::Method::Destroy as MethodNum,
+                IpldBlock::serialize_cbor(&ext::datacap::DestroyParams {
+                    owner: Address::new_id(caller),
+                    amount: TokenAmount::from_whole(expected_destroy),
+                })
+                .unwrap(),
+                TokenAmount::zero(),
+                IpldBlock::serialize_cbor(&BurnReturn { balance: TokenAmount::zero() }).unwrap(),
+                ExitCode::OK,
+            );
+        }
+        for (id, new_claim) in expected {
+            expect_claim_emitted(
+                rt,
+                "claim-updated",
+                id,
+                new_claim.client,
+                new_claim.provider,
+                &new_claim.data,
+                new_claim.size.0,
+                new_claim.sector,
+                new_claim.term_min,
+                new_claim.term_max,
+                new_claim.term_start,
+            )
+        }
+
+        let ret = rt
+            .call::<VerifregActor>(
+                Method::ExtendClaimsWithDatacapExported as MethodNum,
+                IpldBlock::serialize_cbor(&params).unwrap(),
+            )?
+            .unwrap()
+            .deserialize()
+            .expect("failed to deserialize extend claims with datacap return");
+        rt.verify();
+        Ok(ret)
+    }
 }
 
 #[allow(clippy::too_many_arguments)]
@@ -607,20 +792,22 @@ pub fn expect_allocation_emitted(
     term_max: ChainEpoch,
     expiration: ChainEpoch,
 ) {
-    rt.expect_emitted_event(
-        EventBuilder::new()
-            .typ(typ)
-            .field_indexed("id", &id)
-            .field_indexed("client", &client)
-            .field_indexed("provider", &provider)
-            .field_indexed("piece-cid", piece_cid)
-            .field("piece-size", &piece_size)
-            .field("term-min", &term_min)
-            .field("term-max", &term_max)
-            .field("expiration", &expiration)
-            .build()
-            .unwrap(),
-    );
+    for typ in with_successor_event_type(typ) {
+        rt.expect_emitted_event(
+            EventBuilder::new()
+                .typ(typ)
+                .field_indexed("id", &id)
+                .field_indexed("client", &client)
+                .field_indexed("provider", &provider)
+                .field_indexed("piece-cid", piece_cid)
+                .field("piece-size", &piece_size)
+                .field("term-min", &term_min)
+                .field("term-max", &term_max)
+                .field("expiration", &expiration)
+                .build()
+                .unwrap(),
+        );
+    }
 }
 
 #[allow(clippy::too_many_arguments)]
@@ -637,21 +824,34 @@ pub fn expect_claim_emitted(
     term_max: ChainEpoch,
     term_start: ChainEpoch,
 ) {
-    rt.expect_emitted_event(
-        EventBuilder::new()
-            .typ(typ)
-            .field_indexed("id", &id)
-            .field_indexed("client", &client)
-            .field_indexed("provider", &provider)
-            .field_indexed("piece-cid", piece_cid)
-            .field("piece-size", &piece_size)
-            .field("term-min", &term_min)
-            .field("term-max", &term_max)
-            .field("term-start", &term_start)
-            .field_indexed("sector", &sector)
-            .build()
-            .unwrap(),
-    );
+    for typ in with_successor_event_type(typ) {
+        rt.expect_emitted_event(
+            EventBuilder::new()
+                .typ(typ)
+                .field_indexed("id", &id)
+                .field_indexed("client", &client)
+                .field_indexed("provider", &provider)
+                .field_indexed("piece-cid", piece_cid)
+                .field("piece-size", &piece_size)
+                .field("term-min", &term_min)
+                .field("term-max", &term_max)
+                .field("term-start", &term_start)
+                .field_indexed("sector", &sector)
+                .build()
+                .unwrap(),
+        );
+    }
+}
+
+// A legacy event type followed by the type emitted alongside it during its deprecation.
+fn with_successor_event_type(typ: &str) -> Vec<&str> {
+    match typ {
+        "allocation" => vec![typ, "allocation-created"],
+        "claim" => vec![typ, "claim-created"],
+        "claim-updated" => vec![typ, "claim-extended"],
+        "claim-removed" => vec![typ, "claim-expired"],
+        _ => vec![typ],
+    }
 }
 
 pub fn make_alloc(data_id: &str, client: ActorID, provider: ActorID, size: u64) -> Allocation {
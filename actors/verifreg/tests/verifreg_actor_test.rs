@@ -495,6 +495,130 @@ mod clients {
         h.check_state(&rt);
     }
 
+    #[test]
+    fn add_client_with_allocations() {
+        let (h, rt) = new_harness();
+        let provider = PROVIDER.id().unwrap();
+        add_miner(&rt, provider);
+        let size = rt.policy.minimum_verified_allocation_size.to_u64().unwrap();
+        let allowance_client = client_allowance(&rt);
+        let allowance_verifier = &allowance_client + 3 * size;
+        h.add_verifier(&rt, &VERIFIER, &allowance_verifier).unwrap();
+
+        let reqs =
+            vec![make_alloc_req(&rt, provider, size), make_alloc_req(&rt, provider, size * 2)];
+        h.add_client_with_allocations(
+            &rt,
+            &VERIFIER,
+            &CLIENT,
+            &allowance_client,
+            reqs.clone(),
+            &allowance_verifier,
+            vec![1, 2],
+        )
+        .unwrap();
+
+        let client = CLIENT.id().unwrap();
+        assert_allocation(&rt, client, 1, &alloc_from_req(client, &reqs[0]));
+        assert_allocation(&rt, client, 2, &alloc_from_req(client, &reqs[1]));
+        h.assert_verifier_allowance(&rt, &VERIFIER, &DataCap::zero());
+
+        // The client allowance may be zero, spending the whole grant on allocations.
+        h.add_verifier(&rt, &VERIFIER, &allowance_verifier).unwrap();
+        h.add_client_with_allocations(
+            &rt,
+            &VERIFIER,
+            &CLIENT2,
+            &DataCap::zero(),
+            reqs,
+            &allowance_verifier,
+            vec![3, 4],
+        )
+        .unwrap();
+        h.assert_verifier_allowance(&rt, &VERIFIER, &allowance_client);
+        h.check_state(&rt);
+    }
+
+    #[test]
+    fn add_client_with_allocations_rejects_invalid_grants() {
+        let (h, rt) = new_harness();
+        let provider = PROVIDER.id().unwrap();
+        add_miner(&rt, provider);
+        let size = rt.policy.minimum_verified_allocation_size.to_u64().unwrap();
+        let allowance_verifier = verifier_allowance(&rt);
+        h.add_verifier(&rt, &VERIFIER, &allowance_verifier).unwrap();
+        let reqs = vec![make_alloc_req(&rt, provider, size)];
+
+        // No allocations.
+        expect_abort_contains_message(
+            ExitCode::USR_ILLEGAL_ARGUMENT,
+            "no allocations requested",
+            h.add_client_with_allocations(
+                &rt,
+                &VERIFIER,
+                &CLIENT,
+                &DataCap::zero(),
+                vec![],
+                &allowance_verifier,
+                vec![],
+            ),
+        );
+        rt.reset();
+
+        // Allowance plus allocations exceed the verifier's allowance.
+        expect_abort_contains_message(
+            ExitCode::USR_ILLEGAL_ARGUMENT,
+            "add more DataCap",
+            h.add_client_with_allocations(
+                &rt,
+                &VERIFIER,
+                &CLIENT,
+                &allowance_verifier,
+                reqs.clone(),
+                &allowance_verifier,
+                vec![1],
+            ),
+        );
+        rt.reset();
+
+        // Provider is not a miner.
+        let not_miner = PROVIDER2.id().unwrap();
+        rt.set_address_actor_type(*PROVIDER2, *ACCOUNT_ACTOR_CODE_ID);
+        expect_abort_contains_message(
+            ExitCode::USR_ILLEGAL_ARGUMENT,
+            "must be a miner actor",
+            h.add_client_with_allocations(
+                &rt,
+                &VERIFIER,
+                &CLIENT,
+                &DataCap::zero(),
+                vec![make_alloc_req(&rt, not_miner, size)],
+                &allowance_verifier,
+                vec![1],
+            ),
+        );
+        rt.reset();
+
+        // Caller is not a verifier.
+        expect_abort(
+            ExitCode::USR_NOT_FOUND,
+            h.add_client_with_allocations(
+                &rt,
+                &CLIENT2,
+                &CLIENT,
+                &DataCap::zero(),
+                reqs,
+                &allowance_verifier,
+                vec![1],
+            ),
+        );
+        rt.reset();
+
+        h.assert_verifier_allowance(&rt, &VERIFIER, &allowance_verifier);
+        assert!(h.load_alloc(&rt, CLIENT.id().unwrap(), 1).is_none());
+        h.check_state(&rt);
+    }
+
     #[test]
     fn rejects_verifier_as_client() {
         let (h, rt) = new_harness();
@@ -1179,15 +1303,20 @@ mod allocs_claims {
 }
 
 mod datacap {
-    use frc46_token::receiver::FRC46_TOKEN_TYPE;
+    use frc46_token::receiver::{FRC46TokenReceived, FRC46_TOKEN_TYPE};
     use fvm_actor_utils::receiver::UniversalReceiverParams;
     use fvm_ipld_encoding::ipld_block::IpldBlock;
     use fvm_shared::address::Address;
     use fvm_shared::econ::TokenAmount;
     use fvm_shared::error::ExitCode;
     use fvm_shared::{ActorID, MethodNum};
+    use num_traits::Zero;
 
-    use fil_actor_verifreg::{Actor as VerifregActor, Claim, Method, State};
+    use fil_actor_verifreg::{
+        ext, Actor as VerifregActor, AllocationsResponse, Claim, DatacapTransferRule,
+        ExtendClaimsWithDatacapParams, IsPausedReturn, Method, SetDatacapTransferRulesParams,
+        SetPausedParams, State,
+    };
     use fil_actors_runtime::cbor::serialize;
     use fil_actors_runtime::runtime::policy_constants::{
         MAXIMUM_VERIFIED_ALLOCATION_EXPIRATION, MAXIMUM_VERIFIED_ALLOCATION_TERM,
@@ -1196,6 +1325,7 @@ mod datacap {
     use fil_actors_runtime::test_utils::*;
     use fil_actors_runtime::{
         BatchReturn, DATACAP_TOKEN_ACTOR_ADDR, EPOCHS_IN_YEAR, STORAGE_MARKET_ACTOR_ADDR,
+        VERIFIED_REGISTRY_ACTOR_ADDR,
     };
     use harness::*;
 
@@ -1226,7 +1356,7 @@ mod datacap {
             assert_allocation(&rt, CLIENT1, 1, &alloc_from_req(CLIENT1, &reqs[0]));
             assert_allocation(&rt, CLIENT1, 2, &alloc_from_req(CLIENT1, &reqs[1]));
             let st: State = rt.get_state();
-            assert_eq!(3, st.next_allocation_id);
+            assert_eq!(3, st.next_allocation_id.peek());
         }
         {
             // Make another allocation from a different client
@@ -1237,7 +1367,7 @@ mod datacap {
             // Verify allocations in state.
             assert_allocation(&rt, CLIENT2, 3, &alloc_from_req(CLIENT2, &reqs[0]));
             let st: State = rt.get_state();
-            assert_eq!(4, st.next_allocation_id);
+            assert_eq!(4, st.next_allocation_id.peek());
         }
         {
             // Allocations can be identical and will receive distinct IDs.
@@ -1331,7 +1461,231 @@ mod datacap {
         assert_claim(&rt, PROVIDER2, cid2, &Claim { term_max: term_max + 2000, ..claim2 });
 
         let st: State = rt.get_state();
-        assert_eq!(5, st.next_allocation_id);
+        assert_eq!(5, st.next_allocation_id.peek());
+        h.check_state(&rt);
+    }
+
+    #[test]
+    fn extend_claims_with_caller_datacap() {
+        let (h, rt) = new_harness();
+
+        let term_min = MINIMUM_VERIFIED_ALLOCATION_TERM;
+        let term_max = term_min + 100;
+        let term_start = 100;
+        let sector = 1234;
+        rt.set_epoch(term_start);
+        let claim1 =
+            make_claim("1", CLIENT1, PROVIDER1, SIZE, term_min, term_max, term_start, sector);
+        let claim2 =
+            make_claim("2", CLIENT1, PROVIDER2, SIZE * 2, term_min, term_max, term_start, sector);
+        let claim3 =
+            make_claim("3", CLIENT1, PROVIDER1, SIZE * 4, term_min, term_min, term_start, sector);
+        let cid1 = h.create_claim(&rt, &claim1).unwrap();
+        let cid2 = h.create_claim(&rt, &claim2).unwrap();
+        let cid3 = h.create_claim(&rt, &claim3).unwrap();
+
+        // A third party extends the claims, beyond the term originally allowed to the client.
+        // Claim 3 has expired.
+        let now = term_start + term_min + 1;
+        rt.set_epoch(now);
+        let new_term_max = now + MAXIMUM_VERIFIED_ALLOCATION_TERM - term_start;
+        let params = ExtendClaimsWithDatacapParams {
+            extensions: vec![
+                // Past the maximum term.
+                make_extension_req(PROVIDER1, cid1, new_term_max + 1),
+                make_extension_req(PROVIDER1, cid1, new_term_max),
+                make_extension_req(PROVIDER2, cid2, new_term_max),
+                // Duplicate.
+                make_extension_req(PROVIDER1, cid1, new_term_max),
+                // Wrong provider.
+                make_extension_req(PROVIDER2, cid3, new_term_max),
+                // Expired.
+                make_extension_req(PROVIDER1, cid3, new_term_max),
+            ],
+        };
+        let ret = h
+            .extend_claims_with_datacap(
+                &rt,
+                CLIENT2,
+                &params,
+                SIZE * 3,
+                vec![
+                    (cid1, Claim { term_max: new_term_max, ..claim1.clone() }),
+                    (cid2, Claim { term_max: new_term_max, ..claim2.clone() }),
+                ],
+            )
+            .unwrap();
+        assert_eq!(
+            BatchReturn::of(&[
+                ExitCode::USR_ILLEGAL_ARGUMENT,
+                ExitCode::OK,
+                ExitCode::OK,
+                ExitCode::USR_ILLEGAL_ARGUMENT,
+                ExitCode::USR_NOT_FOUND,
+                ExitCode::USR_FORBIDDEN,
+            ]),
+            ret
+        );
+
+        // The claims keep their client.
+        assert_claim(&rt, PROVIDER1, cid1, &Claim { term_max: new_term_max, ..claim1 });
+        assert_claim(&rt, PROVIDER2, cid2, &Claim { term_max: new_term_max, ..claim2 });
+        assert_claim(&rt, PROVIDER1, cid3, &claim3);
+        h.check_state(&rt);
+    }
+
+    #[test]
+    fn extend_claims_with_datacap_requires_balance() {
+        let (h, rt) = new_harness();
+        let term_min = MINIMUM_VERIFIED_ALLOCATION_TERM;
+        let claim = make_claim("1", CLIENT1, PROVIDER1, SIZE, term_min, term_min + 100, 0, 1234);
+        let cid = h.create_claim(&rt, &claim).unwrap();
+
+        let params = ExtendClaimsWithDatacapParams {
+            extensions: vec![make_extension_req(PROVIDER1, cid, term_min + 200)],
+        };
+        rt.set_caller(*ACCOUNT_ACTOR_CODE_ID, Address::new_id(CLIENT2));
+        rt.expect_validate_caller_any();
+        rt.expect_send_simple(
+            DATACAP_TOKEN_ACTOR_ADDR,
+            ext::datacap::Method::Destroy as MethodNum,
+            IpldBlock::serialize_cbor(&ext::datacap::DestroyParams {
+                owner: Address::new_id(CLIENT2),
+                amount: TokenAmount::from_whole(SIZE),
+            })
+            .unwrap(),
+            TokenAmount::zero(),
+            None,
+            ExitCode::USR_INSUFFICIENT_FUNDS,
+        );
+        expect_abort(
+            ExitCode::USR_INSUFFICIENT_FUNDS,
+            rt.call::<VerifregActor>(
+                Method::ExtendClaimsWithDatacapExported as MethodNum,
+                IpldBlock::serialize_cbor(&params).unwrap(),
+            ),
+        );
+        rt.verify();
+        assert_claim(&rt, PROVIDER1, cid, &claim);
+        h.check_state(&rt);
+    }
+
+    #[test]
+    fn receive_rejected_while_paused() {
+        let (h, rt) = new_harness();
+        add_miner(&rt, PROVIDER1);
+
+        h.set_paused(&rt, true, true).unwrap();
+        rt.expect_validate_caller_any();
+        let ret: IsPausedReturn = rt
+            .call::<VerifregActor>(Method::IsPausedExported as MethodNum, None)
+            .unwrap()
+            .unwrap()
+            .deserialize()
+            .unwrap();
+        assert!(ret.paused);
+
+        let reqs = vec![make_alloc_req(&rt, PROVIDER1, SIZE)];
+        let payload = make_receiver_hook_token_payload(CLIENT1, reqs.clone(), vec![], SIZE);
+        expect_abort_contains_message(
+            ExitCode::USR_FORBIDDEN,
+            "verified registry is paused",
+            h.receive_tokens(&rt, payload, BatchReturn::ok(1), BATCH_EMPTY, vec![1], 0),
+        );
+        rt.reset();
+
+        // Only the root key can change the switch.
+        rt.set_caller(*ACCOUNT_ACTOR_CODE_ID, Address::new_id(CLIENT2));
+        rt.expect_validate_caller_addr(vec![h.root]);
+        expect_abort(
+            ExitCode::USR_FORBIDDEN,
+            rt.call::<VerifregActor>(
+                Method::SetPausedExported as MethodNum,
+                IpldBlock::serialize_cbor(&SetPausedParams { registry: false, datacap: false })
+                    .unwrap(),
+            ),
+        );
+        rt.reset();
+
+        h.set_paused(&rt, false, false).unwrap();
+        let payload = make_receiver_hook_token_payload(CLIENT1, reqs.clone(), vec![], SIZE);
+        h.receive_tokens(&rt, payload, BatchReturn::ok(1), BATCH_EMPTY, vec![1], 0).unwrap();
+        assert_allocation(&rt, CLIENT1, 1, &alloc_from_req(CLIENT1, &reqs[0]));
+        h.check_state(&rt);
+    }
+
+    #[test]
+    fn set_datacap_transfer_rules_forwarded_from_root() {
+        let (h, rt) = new_harness();
+        let rules = vec![DatacapTransferRule {
+            from: Some(Address::new_id(CLIENT1)),
+            to: Some(Address::new_id(CLIENT2)),
+            operator: None,
+        }];
+        let params = SetDatacapTransferRulesParams { rules: rules.clone() };
+
+        // Only the root key can change the rules.
+        rt.set_caller(*ACCOUNT_ACTOR_CODE_ID, Address::new_id(CLIENT2));
+        rt.expect_validate_caller_addr(vec![h.root]);
+        expect_abort(
+            ExitCode::USR_FORBIDDEN,
+            rt.call::<VerifregActor>(
+                Method::SetDatacapTransferRulesExported as MethodNum,
+                IpldBlock::serialize_cbor(&params).unwrap(),
+            ),
+        );
+        rt.reset();
+
+        rt.set_caller(*ACCOUNT_ACTOR_CODE_ID, h.root);
+        rt.expect_validate_caller_addr(vec![h.root]);
+        rt.expect_send_simple(
+            DATACAP_TOKEN_ACTOR_ADDR,
+            ext::datacap::Method::SetTransferRules as MethodNum,
+            IpldBlock::serialize_cbor(&ext::datacap::SetTransferRulesParams { rules }).unwrap(),
+            TokenAmount::zero(),
+            None,
+            ExitCode::OK,
+        );
+        rt.call::<VerifregActor>(
+            Method::SetDatacapTransferRulesExported as MethodNum,
+            IpldBlock::serialize_cbor(&params).unwrap(),
+        )
+        .unwrap();
+        rt.verify();
+        h.check_state(&rt);
+    }
+
+    #[test]
+    fn receive_accepts_mint_to_self() {
+        let (h, rt) = new_harness();
+        // Datacap minted to the registry for allocations it creates directly carries
+        // no allocation requests.
+        let payload = FRC46TokenReceived {
+            from: DATACAP_TOKEN_ACTOR_ADDR.id().unwrap(),
+            to: VERIFIED_REGISTRY_ACTOR_ADDR.id().unwrap(),
+            operator: VERIFIED_REGISTRY_ACTOR_ADDR.id().unwrap(),
+            amount: TokenAmount::from_whole(SIZE as i64),
+            operator_data: Default::default(),
+            token_data: Default::default(),
+        };
+        let params = UniversalReceiverParams {
+            type_: FRC46_TOKEN_TYPE,
+            payload: serialize(&payload, "payload").unwrap(),
+        };
+        rt.set_caller(*DATACAP_TOKEN_ACTOR_CODE_ID, DATACAP_TOKEN_ACTOR_ADDR);
+        rt.expect_validate_caller_addr(vec![DATACAP_TOKEN_ACTOR_ADDR]);
+        let ret: AllocationsResponse = rt
+            .call::<VerifregActor>(
+                Method::UniversalReceiverHook as MethodNum,
+                IpldBlock::serialize_cbor(&params).unwrap(),
+            )
+            .unwrap()
+            .unwrap()
+            .deserialize()
+            .unwrap();
+        rt.verify();
+        assert_eq!(BATCH_EMPTY, ret.allocation_results);
+        assert!(ret.new_allocations.is_empty());
         h.check_state(&rt);
     }
 
@@ -1597,6 +1951,417 @@ mod datacap {
     }
 }
 
+mod listing {
+    use fvm_ipld_encoding::ipld_block::IpldBlock;
+    use fvm_shared::address::Address;
+    use fvm_shared::error::ExitCode;
+    use fvm_shared::{ActorID, MethodNum};
+
+    use fil_actor_verifreg::{
+        Actor as VerifregActor, Allocation, AllocationID, Claim, ClaimID, ListAllocationsParams,
+        ListAllocationsReturn, ListClaimsParams, ListClaimsReturn, Method, State,
+    };
+    use fil_actors_runtime::runtime::policy_constants::{
+        MINIMUM_VERIFIED_ALLOCATION_SIZE, MINIMUM_VERIFIED_ALLOCATION_TERM,
+    };
+    use fil_actors_runtime::runtime::Runtime;
+    use fil_actors_runtime::test_utils::*;
+    use fil_actors_runtime::ActorError;
+    use harness::*;
+
+    use crate::*;
+
+    const CLIENT1: ActorID = 101;
+    const CLIENT2: ActorID = 102;
+    const PROVIDER1: ActorID = 301;
+    const PROVIDER2: ActorID = 302;
+    const SIZE: u64 = MINIMUM_VERIFIED_ALLOCATION_SIZE as u64;
+
+    fn list_allocations(
+        rt: &MockRuntime,
+        client: ActorID,
+        cursor: Option<AllocationID>,
+        limit: u64,
+    ) -> Result<ListAllocationsReturn, ActorError> {
+        rt.set_caller(*EVM_ACTOR_CODE_ID, Address::new_id(1234));
+        rt.expect_validate_caller_any();
+        let params = ListAllocationsParams { client, cursor, limit };
+        let ret = rt.call::<VerifregActor>(
+            Method::ListAllocationsExported as MethodNum,
+            IpldBlock::serialize_cbor(&params).unwrap(),
+        );
+        rt.verify();
+        Ok(ret?.unwrap().deserialize().unwrap())
+    }
+
+    fn list_claims(
+        rt: &MockRuntime,
+        provider: ActorID,
+        cursor: Option<ClaimID>,
+        limit: u64,
+    ) -> Result<ListClaimsReturn, ActorError> {
+        rt.set_caller(*EVM_ACTOR_CODE_ID, Address::new_id(1234));
+        rt.expect_validate_caller_any();
+        let params = ListClaimsParams { provider, cursor, limit };
+        let ret = rt.call::<VerifregActor>(
+            Method::ListClaimsExported as MethodNum,
+            IpldBlock::serialize_cbor(&params).unwrap(),
+        );
+        rt.verify();
+        Ok(ret?.unwrap().deserialize().unwrap())
+    }
+
+    #[test]
+    fn list_allocations_in_pages() {
+        let (h, rt) = new_harness();
+        let mut expected: Vec<(AllocationID, Allocation)> = (0..5)
+            .map(|i| {
+                let alloc = make_alloc(&i.to_string(), CLIENT1, PROVIDER1, SIZE);
+                (h.create_alloc(&rt, &alloc).unwrap(), alloc)
+            })
+            .collect();
+        h.create_alloc(&rt, &make_alloc("other", CLIENT2, PROVIDER1, SIZE)).unwrap();
+
+        let mut listed = vec![];
+        let mut cursor = None;
+        let mut pages = 0;
+        loop {
+            let ret = list_allocations(&rt, CLIENT1, cursor, 2).unwrap();
+            assert!(ret.allocations.len() <= 2);
+            listed.extend(ret.allocations);
+            pages += 1;
+            cursor = ret.next_cursor;
+            if cursor.is_none() {
+                break;
+            }
+        }
+        assert_eq!(3, pages);
+        listed.sort_by_key(|(id, _)| *id);
+        expected.sort_by_key(|(id, _)| *id);
+        assert_eq!(expected, listed);
+
+        // An oversized limit is capped rather than rejected.
+        let ret = list_allocations(&rt, CLIENT1, None, u64::MAX).unwrap();
+        assert_eq!(5, ret.allocations.len());
+        assert_eq!(None, ret.next_cursor);
+
+        // A client without allocations has an empty listing.
+        let ret = list_allocations(&rt, 103, None, 10).unwrap();
+        assert_eq!(ListAllocationsReturn { allocations: vec![], next_cursor: None }, ret);
+        h.check_state(&rt);
+    }
+
+    #[test]
+    fn list_resumes_after_cursor_removed() {
+        let (h, rt) = new_harness();
+        let mut expected: Vec<AllocationID> = (0..5)
+            .map(|i| h.create_alloc(&rt, &make_alloc(&i.to_string(), CLIENT1, PROVIDER1, SIZE)))
+            .collect::<Result<_, _>>()
+            .unwrap();
+
+        let first = list_allocations(&rt, CLIENT1, None, 2).unwrap();
+        let cursor = first.next_cursor.unwrap();
+
+        // Remove the allocation at the cursor between pages.
+        let mut st: State = rt.get_state();
+        let mut allocs = st.load_allocs(rt.store()).unwrap();
+        allocs.remove(CLIENT1, cursor).unwrap().unwrap();
+        st.save_allocs(&mut allocs).unwrap();
+        rt.replace_state(&st);
+
+        let second = list_allocations(&rt, CLIENT1, Some(cursor), 10).unwrap();
+        assert_eq!(None, second.next_cursor);
+        let mut listed: Vec<AllocationID> =
+            first.allocations.iter().chain(&second.allocations).map(|(id, _)| *id).collect();
+        listed.sort();
+        expected.retain(|id| *id != cursor);
+        expected.sort();
+        assert_eq!(expected, listed);
+        h.check_state(&rt);
+    }
+
+    #[test]
+    fn list_claims_in_pages() {
+        let (h, rt) = new_harness();
+        let term_min = MINIMUM_VERIFIED_ALLOCATION_TERM;
+        let mut expected: Vec<(ClaimID, Claim)> = (0..3)
+            .map(|i| {
+                let claim =
+                    make_claim(&i.to_string(), CLIENT1, PROVIDER1, SIZE, term_min, term_min, 0, i);
+                (h.create_claim(&rt, &claim).unwrap(), claim)
+            })
+            .collect();
+        h.create_claim(
+            &rt,
+            &make_claim("other", CLIENT1, PROVIDER2, SIZE, term_min, term_min, 0, 9),
+        )
+        .unwrap();
+
+        let first = list_claims(&rt, PROVIDER1, None, 2).unwrap();
+        assert_eq!(2, first.claims.len());
+        assert!(first.next_cursor.is_some());
+        let second = list_claims(&rt, PROVIDER1, first.next_cursor, 2).unwrap();
+        assert_eq!(1, second.claims.len());
+        assert_eq!(None, second.next_cursor);
+
+        let mut listed: Vec<(ClaimID, Claim)> =
+            first.claims.into_iter().chain(second.claims).collect();
+        listed.sort_by_key(|(id, _)| *id);
+        expected.sort_by_key(|(id, _)| *id);
+        assert_eq!(expected, listed);
+
+        // A single page holds the whole listing if the limit allows.
+        let all = list_claims(&rt, PROVIDER1, None, 3).unwrap();
+        assert_eq!(3, all.claims.len());
+        assert_eq!(None, all.next_cursor);
+        h.check_state(&rt);
+    }
+
+    #[test]
+    fn list_rejects_bad_requests() {
+        let (h, rt) = new_harness();
+        h.create_alloc(&rt, &make_alloc("1", CLIENT1, PROVIDER1, SIZE)).unwrap();
+
+        expect_abort(ExitCode::USR_ILLEGAL_ARGUMENT, list_allocations(&rt, CLIENT1, None, 0));
+        expect_abort(ExitCode::USR_ILLEGAL_ARGUMENT, list_claims(&rt, PROVIDER1, None, 0));
+        h.check_state(&rt);
+    }
+}
+
+mod root_key {
+    use fvm_ipld_encoding::ipld_block::IpldBlock;
+    use fvm_shared::address::Address;
+    use fvm_shared::error::ExitCode;
+    use fvm_shared::MethodNum;
+
+    use fil_actor_verifreg::{
+        Actor as VerifregActor, Method, ProposeRootKeyParams, ProposeRootKeyReturn,
+        RootKeyProposal, State,
+    };
+    use fil_actors_runtime::test_utils::*;
+    use fil_actors_runtime::{ActorError, EventBuilder};
+    use harness::*;
+
+    use crate::*;
+
+    const NEW_ROOT: Address = Address::new_id(401);
+
+    fn propose(rt: &MockRuntime, caller: Address, new_root_key: Address) -> ProposeRootKeyReturn {
+        rt.set_address_actor_type(new_root_key, *ACCOUNT_ACTOR_CODE_ID);
+        rt.set_caller(*ACCOUNT_ACTOR_CODE_ID, caller);
+        rt.expect_validate_caller_addr(vec![ROOT_ADDR]);
+        let effective_epoch = *rt.epoch.borrow() + rt.policy.verifreg_root_key_rotation_delay;
+        rt.expect_emitted_event(
+            EventBuilder::new()
+                .typ("root-key-proposed")
+                .field_indexed("new-root-key", &new_root_key.id().unwrap())
+                .field("effective-epoch", &effective_epoch)
+                .build()
+                .unwrap(),
+        );
+        let ret: ProposeRootKeyReturn = rt
+            .call::<VerifregActor>(
+                Method::ProposeRootKeyExported as MethodNum,
+                IpldBlock::serialize_cbor(&ProposeRootKeyParams { new_root_key }).unwrap(),
+            )
+            .unwrap()
+            .unwrap()
+            .deserialize()
+            .unwrap();
+        rt.verify();
+        ret
+    }
+
+    fn accept(rt: &MockRuntime) -> Result<Option<IpldBlock>, ActorError> {
+        rt.set_caller(*ACCOUNT_ACTOR_CODE_ID, NEW_ROOT);
+        rt.expect_validate_caller_addr(vec![NEW_ROOT]);
+        rt.call::<VerifregActor>(Method::AcceptRootKeyExported as MethodNum, None)
+    }
+
+    #[test]
+    fn rotate_root_key_after_timelock() {
+        let (h, rt) = new_harness();
+        rt.set_epoch(100);
+
+        let ret = propose(&rt, ROOT_ADDR, NEW_ROOT);
+        let effective_epoch = 100 + rt.policy.verifreg_root_key_rotation_delay;
+        assert_eq!(effective_epoch, ret.effective_epoch);
+        let st: State = rt.get_state();
+        assert_eq!(
+            Some(RootKeyProposal { new_root_key: NEW_ROOT, effective_epoch }),
+            st.root_key_proposal
+        );
+
+        // The new key cannot accept during the timelock.
+        rt.set_epoch(effective_epoch - 1);
+        expect_abort(ExitCode::USR_FORBIDDEN, accept(&rt));
+        rt.reset();
+
+        // Only the proposed key can accept.
+        rt.set_epoch(effective_epoch);
+        rt.set_caller(*ACCOUNT_ACTOR_CODE_ID, ROOT_ADDR);
+        rt.expect_validate_caller_addr(vec![NEW_ROOT]);
+        expect_abort(
+            ExitCode::USR_FORBIDDEN,
+            rt.call::<VerifregActor>(Method::AcceptRootKeyExported as MethodNum, None),
+        );
+        rt.reset();
+
+        rt.expect_emitted_event(
+            EventBuilder::new()
+                .typ("root-key-changed")
+                .field_indexed("old-root-key", &ROOT_ADDR.id().unwrap())
+                .field_indexed("new-root-key", &NEW_ROOT.id().unwrap())
+                .build()
+                .unwrap(),
+        );
+        accept(&rt).unwrap();
+        rt.verify();
+
+        let st: State = rt.get_state();
+        assert_eq!(NEW_ROOT, st.root_key);
+        assert_eq!(None, st.root_key_proposal);
+
+        // The previous root key no longer governs the registry.
+        rt.set_caller(*ACCOUNT_ACTOR_CODE_ID, ROOT_ADDR);
+        rt.expect_validate_caller_addr(vec![NEW_ROOT]);
+        expect_abort(
+            ExitCode::USR_FORBIDDEN,
+            rt.call::<VerifregActor>(
+                Method::ProposeRootKeyExported as MethodNum,
+                IpldBlock::serialize_cbor(&ProposeRootKeyParams { new_root_key: ROOT_ADDR })
+                    .unwrap(),
+            ),
+        );
+        rt.reset();
+        h.check_state(&rt);
+    }
+
+    #[test]
+    fn root_key_cancels_proposal() {
+        let (h, rt) = new_harness();
+        propose(&rt, ROOT_ADDR, NEW_ROOT);
+
+        // Only the root key can cancel.
+        rt.set_caller(*ACCOUNT_ACTOR_CODE_ID, NEW_ROOT);
+        rt.expect_validate_caller_addr(vec![ROOT_ADDR]);
+        expect_abort(
+            ExitCode::USR_FORBIDDEN,
+            rt.call::<VerifregActor>(Method::CancelRootKeyProposalExported as MethodNum, None),
+        );
+        rt.reset();
+
+        rt.set_caller(*ACCOUNT_ACTOR_CODE_ID, ROOT_ADDR);
+        rt.expect_validate_caller_addr(vec![ROOT_ADDR]);
+        rt.expect_emitted_event(
+            EventBuilder::new()
+                .typ("root-key-proposal-cancelled")
+                .field_indexed("new-root-key", &NEW_ROOT.id().unwrap())
+                .build()
+                .unwrap(),
+        );
+        rt.call::<VerifregActor>(Method::CancelRootKeyProposalExported as MethodNum, None).unwrap();
+        rt.verify();
+        assert_eq!(None, rt.get_state::<State>().root_key_proposal);
+
+        // Nothing remains to accept or cancel.
+        rt.set_epoch(rt.policy.verifreg_root_key_rotation_delay);
+        rt.set_caller(*ACCOUNT_ACTOR_CODE_ID, NEW_ROOT);
+        expect_abort(
+            ExitCode::USR_NOT_FOUND,
+            rt.call::<VerifregActor>(Method::AcceptRootKeyExported as MethodNum, None),
+        );
+        rt.reset();
+        rt.set_caller(*ACCOUNT_ACTOR_CODE_ID, ROOT_ADDR);
+        rt.expect_validate_caller_addr(vec![ROOT_ADDR]);
+        expect_abort(
+            ExitCode::USR_NOT_FOUND,
+            rt.call::<VerifregActor>(Method::CancelRootKeyProposalExported as MethodNum, None),
+        );
+        rt.reset();
+        h.check_state(&rt);
+    }
+
+    #[test]
+    fn cannot_propose_current_root_or_verifier() {
+        let (h, rt) = new_harness();
+        let allowance = rt.policy.minimum_verified_allocation_size.clone();
+        h.add_verifier(&rt, &VERIFIER, &allowance).unwrap();
+
+        for new_root_key in [ROOT_ADDR, *VERIFIER] {
+            rt.set_caller(*ACCOUNT_ACTOR_CODE_ID, ROOT_ADDR);
+            rt.expect_validate_caller_addr(vec![ROOT_ADDR]);
+            expect_abort(
+                ExitCode::USR_ILLEGAL_ARGUMENT,
+                rt.call::<VerifregActor>(
+                    Method::ProposeRootKeyExported as MethodNum,
+                    IpldBlock::serialize_cbor(&ProposeRootKeyParams { new_root_key }).unwrap(),
+                ),
+            );
+            rt.reset();
+        }
+        h.check_state(&rt);
+    }
+
+    #[test]
+    fn cannot_accept_as_verifier() {
+        let (h, rt) = new_harness();
+        propose(&rt, ROOT_ADDR, NEW_ROOT);
+
+        // The proposed key cannot be made a verifier while the proposal is pending.
+        let allowance = rt.policy.minimum_verified_allocation_size.clone();
+        expect_abort_contains_message(
+            ExitCode::USR_ILLEGAL_ARGUMENT,
+            "proposed root key",
+            h.add_verifier(&rt, &NEW_ROOT, &allowance),
+        );
+        rt.reset();
+
+        // A verifier cannot accept the role, even if proposed before becoming a verifier.
+        let mut st: State = rt.get_state();
+        let proposal = st.root_key_proposal.take();
+        rt.replace_state(&st);
+        h.add_verifier(&rt, &NEW_ROOT, &allowance).unwrap();
+        let mut st: State = rt.get_state();
+        st.root_key_proposal = proposal;
+        rt.replace_state(&st);
+        rt.set_epoch(rt.policy.verifreg_root_key_rotation_delay);
+        expect_abort_contains_message(ExitCode::USR_ILLEGAL_ARGUMENT, "cannot become", accept(&rt));
+        rt.reset();
+        assert_eq!(ROOT_ADDR, rt.get_state::<State>().root_key);
+        h.check_state(&rt);
+    }
+}
+
+mod upgrade {
+    use fil_actor_verifreg::{Actor as VerifregActor, State};
+    use harness::*;
+
+    use crate::*;
+
+    #[test]
+    fn upgrade_migrates_legacy_state() {
+        let (h, rt) = new_harness();
+        let allowance = rt.policy.minimum_verified_allocation_size.clone();
+        h.add_verifier(&rt, &VERIFIER, &allowance).unwrap();
+        let st: State = rt.get_state();
+        h.replace_with_legacy_state(&rt);
+
+        rt.upgrade::<VerifregActor>();
+        let migrated: State = rt.get_state();
+        assert_eq!(st.root_key, migrated.root_key);
+        assert_eq!(st.verifiers, migrated.verifiers);
+        assert_eq!(st.allocations, migrated.allocations);
+        assert_eq!(st.next_allocation_id, migrated.next_allocation_id);
+        assert_eq!(st.claims, migrated.claims);
+        assert!(!migrated.paused);
+        assert_eq!(None, migrated.root_key_proposal);
+
+        h.assert_verifier_allowance(&rt, &VERIFIER, &allowance);
+        h.check_state(&rt);
+    }
+}
+
 // Tests to match with Go github.com/filecoin-project/go-state-types/builtin/*/verifreg
 mod serialization {
     use std::str::FromStr;
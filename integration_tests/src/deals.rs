@@ -13,7 +13,8 @@ use fvm_shared::econ::TokenAmount;
 use fvm_shared::error::ExitCode;
 use fvm_shared::piece::PaddedPieceSize;
 use num_traits::Zero;
-use vm_api::util::{apply_ok, serialize_ok};
+use std::collections::BTreeSet;
+use vm_api::util::{apply_ok, assert_aborts_with, serialize_ok};
 use vm_api::VM;
 
 #[derive(Clone)]
@@ -47,13 +48,21 @@ impl Default for DealOptions {
 pub struct DealBatcher<'vm> {
     v: &'vm dyn VM,
     deals: Vec<DealProposal>,
+    // Indices of staged deals to be published with a signature that fails verification.
+    bad_signatures: BTreeSet<usize>,
     default_options: DealOptions,
     published: bool,
 }
 
 impl<'vm> DealBatcher<'vm> {
     pub fn new(v: &'vm dyn VM, opts: DealOptions) -> Self {
-        DealBatcher { v, deals: vec![], default_options: opts, published: false }
+        DealBatcher {
+            v,
+            deals: vec![],
+            bad_signatures: BTreeSet::new(),
+            default_options: opts,
+            published: false,
+        }
     }
 
     pub fn proposals(&self) -> &Vec<DealProposal> {
@@ -74,6 +83,12 @@ impl<'vm> DealBatcher<'vm> {
         self.stage_with_opts_label(client, provider, label, self.default_options.clone())
     }
 
+    // Stages a deal whose client signature will not verify.
+    pub fn stage_with_bad_signature(&mut self, client: Address, provider: Address) {
+        self.bad_signatures.insert(self.deals.len());
+        self.stage(client, provider)
+    }
+
     pub fn stage_with_opts_label(
         &mut self,
         client: Address,
@@ -99,7 +114,7 @@ impl<'vm> DealBatcher<'vm> {
 
     pub fn publish_ok(&mut self, sender: Address) -> PublishStorageDealsReturn {
         assert!(!self.published, "already published");
-        let publish_params = publish_deal_params(&self.deals);
+        let publish_params = publish_deal_params(&self.deals, &self.bad_signatures);
         let ret: PublishStorageDealsReturn = apply_ok(
             self.v,
             &sender,
@@ -115,7 +130,7 @@ impl<'vm> DealBatcher<'vm> {
     }
 
     pub fn publish_fail(&mut self, sender: Address) {
-        let publish_params = publish_deal_params(&self.deals);
+        let publish_params = publish_deal_params(&self.deals, &self.bad_signatures);
         let ret = self
             .v
             .execute_message(
@@ -126,19 +141,27 @@ impl<'vm> DealBatcher<'vm> {
                 Some(serialize_ok(&publish_params)),
             )
             .unwrap();
-        assert_eq!(ExitCode::USR_ILLEGAL_ARGUMENT, ret.code);
+        assert_aborts_with!(ret, ExitCode::USR_ILLEGAL_ARGUMENT);
     }
 }
 
-fn publish_deal_params(proposals: &[DealProposal]) -> PublishStorageDealsParams {
+fn publish_deal_params(
+    proposals: &[DealProposal],
+    bad_signatures: &BTreeSet<usize>,
+) -> PublishStorageDealsParams {
     let params_deals = proposals
         .iter()
-        .map(|deal| ClientDealProposal {
-            proposal: deal.clone(),
-            client_signature: Signature {
-                sig_type: SignatureType::BLS,
-                bytes: serialize(deal, "serializing deal proposal").unwrap().to_vec(),
-            },
+        .enumerate()
+        .map(|(i, deal)| {
+            let bytes = if bad_signatures.contains(&i) {
+                b"very_invalid_sig".to_vec()
+            } else {
+                serialize(deal, "serializing deal proposal").unwrap().to_vec()
+            };
+            ClientDealProposal {
+                proposal: deal.clone(),
+                client_signature: Signature { sig_type: SignatureType::BLS, bytes },
+            }
         })
         .collect();
     PublishStorageDealsParams { deals: params_deals }
@@ -299,8 +299,12 @@ impl Expect {
         message: Vec<u8>,
         signature: Vec<u8>,
     ) -> ExpectInvocation {
-        let params =
-            IpldBlock::serialize_cbor(&AuthenticateMessageParams { message, signature }).unwrap();
+        let params = IpldBlock::serialize_cbor(&AuthenticateMessageParams {
+            message,
+            signature,
+            batch: None,
+        })
+        .unwrap();
         ExpectInvocation {
             from,
             to,
@@ -357,8 +361,10 @@ impl Expect {
         }
     }
 
+    /// Builds the events for an allocation: a legacy event type followed, during its
+    /// deprecation, by the type that succeeds it.
     #[allow(clippy::too_many_arguments)]
-    pub fn build_verifreg_allocation_event(
+    pub fn build_verifreg_allocation_events(
         typ: &str,
         id: u64,
         client: ActorID,
@@ -368,26 +374,31 @@ impl Expect {
         term_min: ChainEpoch,
         term_max: ChainEpoch,
         expiration: ChainEpoch,
-    ) -> EmittedEvent {
-        EmittedEvent {
-            emitter: VERIFIED_REGISTRY_ACTOR_ID,
-            event: EventBuilder::new()
-                .typ(typ)
-                .field_indexed("id", &id)
-                .field_indexed("client", &client)
-                .field_indexed("provider", &provider)
-                .field_indexed("piece-cid", piece_cid)
-                .field("piece-size", &piece_size)
-                .field("term-min", &term_min)
-                .field("term-max", &term_max)
-                .field("expiration", &expiration)
-                .build()
-                .unwrap(),
-        }
+    ) -> Vec<EmittedEvent> {
+        with_successor_event_type(typ)
+            .into_iter()
+            .map(|typ| EmittedEvent {
+                emitter: VERIFIED_REGISTRY_ACTOR_ID,
+                event: EventBuilder::new()
+                    .typ(typ)
+                    .field_indexed("id", &id)
+                    .field_indexed("client", &client)
+                    .field_indexed("provider", &provider)
+                    .field_indexed("piece-cid", piece_cid)
+                    .field("piece-size", &piece_size)
+                    .field("term-min", &term_min)
+                    .field("term-max", &term_max)
+                    .field("expiration", &expiration)
+                    .build()
+                    .unwrap(),
+            })
+            .collect()
     }
 
+    /// Builds the events for a claim: a legacy event type followed, during its deprecation, by
+    /// the type that succeeds it.
     #[allow(clippy::too_many_arguments)]
-    pub fn build_verifreg_claim_event(
+    pub fn build_verifreg_claim_events(
         typ: &str,
         id: u64,
         client: ActorID,
@@ -398,24 +409,28 @@ impl Expect {
         term_max: ChainEpoch,
         term_start: ChainEpoch,
         sector: SectorNumber,
-    ) -> EmittedEvent {
-        EmittedEvent {
-            emitter: VERIFIED_REGISTRY_ACTOR_ID,
-            event: EventBuilder::new()
-                .typ(typ)
-                .field_indexed("id", &id)
-                .field_indexed("client", &client)
-                .field_indexed("provider", &provider)
-                .field_indexed("piece-cid", piece_cid)
-                .field("piece-size", &piece_size)
-                .field("term-min", &term_min)
-                .field("term-max", &term_max)
-                .field("term-start", &term_start)
-                .field_indexed("sector", &sector)
-                .build()
-                .unwrap(),
-        }
+    ) -> Vec<EmittedEvent> {
+        with_successor_event_type(typ)
+            .into_iter()
+            .map(|typ| EmittedEvent {
+                emitter: VERIFIED_REGISTRY_ACTOR_ID,
+                event: EventBuilder::new()
+                    .typ(typ)
+                    .field_indexed("id", &id)
+                    .field_indexed("client", &client)
+                    .field_indexed("provider", &provider)
+                    .field_indexed("piece-cid", piece_cid)
+                    .field("piece-size", &piece_size)
+                    .field("term-min", &term_min)
+                    .field("term-max", &term_max)
+                    .field("term-start", &term_start)
+                    .field_indexed("sector", &sector)
+                    .build()
+                    .unwrap(),
+            })
+            .collect()
     }
+
     #[allow(clippy::too_many_arguments)]
     pub fn build_market_event(
         typ: &str,
@@ -470,3 +485,15 @@ impl Expect {
         EmittedEvent { emitter: miner_id, event: base_event.build().unwrap() }
     }
 }
+
+// A verified registry event type followed by the type emitted alongside it during the legacy
+// type's deprecation.
+fn with_successor_event_type(typ: &str) -> Vec<&str> {
+    match typ {
+        "allocation" => vec![typ, "allocation-created"],
+        "claim" => vec![typ, "claim-created"],
+        "claim-updated" => vec![typ, "claim-extended"],
+        "claim-removed" => vec![typ, "claim-expired"],
+        _ => vec![typ],
+    }
+}
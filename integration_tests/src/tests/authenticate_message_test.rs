@@ -26,6 +26,7 @@ pub fn account_authenticate_message_test(v: &dyn VM) {
     let authenticate_message_params = AuthenticateMessageParams {
         signature: proposal_ser.clone(),
         message: proposal_ser.clone(),
+        batch: None,
     };
     apply_ok(
         v,
@@ -38,7 +39,7 @@ pub fn account_authenticate_message_test(v: &dyn VM) {
 
     // Bad, bad sig! message fails
     let authenticate_message_params =
-        AuthenticateMessageParams { signature: vec![], message: proposal_ser };
+        AuthenticateMessageParams { signature: vec![], message: proposal_ser, batch: None };
     apply_code(
         v,
         &addr,
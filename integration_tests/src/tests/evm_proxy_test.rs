@@ -0,0 +1,177 @@
+use export_macro::vm_test;
+use fil_actors_runtime::{test_utils::EVM_ACTOR_CODE_ID, EAM_ACTOR_ADDR};
+use fvm_ipld_encoding::BytesDe;
+use fvm_shared::{address::Address, econ::TokenAmount};
+use num_traits::Zero;
+use vm_api::util::{assert_ok, serialize_ok};
+use vm_api::VM;
+
+use crate::tests::ContractParams;
+use crate::util::create_accounts;
+
+// Proxy contracts forward every call to an implementation contract with DELEGATECALL, so the
+// implementation's code runs against the proxy's storage. These tests deploy hand-assembled
+// proxies in front of a counter and check that every increment lands in the storage of the
+// contract that was called, no matter how many DELEGATECALL hops its code took to run.
+
+/// Increments the counter in slot 0 and returns its new value.
+///
+/// ```text
+/// PUSH1 0x00 SLOAD PUSH1 0x01 ADD DUP1 PUSH1 0x00 SSTORE
+/// PUSH1 0x00 MSTORE PUSH1 0x20 PUSH1 0x00 RETURN
+/// ```
+const COUNTER_RUNTIME: &str = "6000546001018060005560005260206000f3";
+
+/// The EIP-1967 implementation slot, `keccak256("eip1967.proxy.implementation") - 1`.
+const EIP1967_IMPLEMENTATION_SLOT: &str =
+    "360894a13ba1a3210667c828492db98dca3e2076cc3735a920a3ca505d382bbc";
+
+/// Initcode returning the given runtime code (at most 255 bytes), which must immediately
+/// follow it.
+///
+/// ```text
+/// PUSH1 len DUP1 PUSH1 offset PUSH1 0x00 CODECOPY PUSH1 0x00 RETURN
+/// ```
+fn deploy_code(prefix: &[u8], runtime: &[u8]) -> Vec<u8> {
+    const LOADER_LEN: usize = 11;
+    let len = u8::try_from(runtime.len()).unwrap();
+    let offset = u8::try_from(prefix.len() + LOADER_LEN).unwrap();
+    let mut code = prefix.to_vec();
+    code.extend_from_slice(&[0x60, len, 0x80, 0x60, offset, 0x60, 0x00, 0x39, 0x60, 0x00, 0xf3]);
+    code.extend_from_slice(runtime);
+    code
+}
+
+/// An EIP-1967 proxy whose constructor stores `implementation` in the implementation slot.
+/// Its runtime code loads the implementation from that slot on every call and forwards the
+/// call data to it with DELEGATECALL, returning or reverting with the implementation's output.
+///
+/// ```text
+/// CALLDATASIZE PUSH1 0x00 PUSH1 0x00 CALLDATACOPY
+/// PUSH1 0x00 PUSH1 0x00 CALLDATASIZE PUSH1 0x00 PUSH32 slot SLOAD GAS DELEGATECALL
+/// RETURNDATASIZE PUSH1 0x00 PUSH1 0x00 RETURNDATACOPY PUSH1 0x3e JUMPI
+/// RETURNDATASIZE PUSH1 0x00 REVERT
+/// JUMPDEST RETURNDATASIZE PUSH1 0x00 RETURN
+/// ```
+fn eip1967_proxy_initcode(implementation: &[u8; 20]) -> Vec<u8> {
+    let slot = hex::decode(EIP1967_IMPLEMENTATION_SLOT).unwrap();
+
+    // PUSH20 implementation PUSH32 slot SSTORE
+    let mut constructor = vec![0x73];
+    constructor.extend_from_slice(implementation);
+    constructor.push(0x7f);
+    constructor.extend_from_slice(&slot);
+    constructor.push(0x55);
+
+    let mut runtime = hex::decode("36600060003760006000366000").unwrap();
+    runtime.push(0x7f);
+    runtime.extend_from_slice(&slot);
+    runtime.extend_from_slice(&hex::decode("545af43d600060003e603e573d6000fd5b3d6000f3").unwrap());
+
+    deploy_code(&constructor, &runtime)
+}
+
+/// An EIP-1167 minimal proxy ("clone") of `implementation`, with the standard initcode.
+fn minimal_clone_initcode(implementation: &[u8; 20]) -> Vec<u8> {
+    let mut code = hex::decode("3d602d80600a3d3981f3363d3d373d3d3d363d73").unwrap();
+    code.extend_from_slice(implementation);
+    code.extend_from_slice(&hex::decode("5af43d82803e903d91602b57fd5bf3").unwrap());
+    code
+}
+
+fn create_contract(v: &dyn VM, deployer: &Address, initcode: Vec<u8>) -> (Address, [u8; 20]) {
+    let create_result = v
+        .execute_message(
+            deployer,
+            &EAM_ACTOR_ADDR,
+            &TokenAmount::zero(),
+            fil_actor_eam::Method::CreateExternal as u64,
+            Some(serialize_ok(&fil_actor_eam::CreateExternalParams(initcode))),
+        )
+        .unwrap();
+    assert_ok!(create_result, "failed to create the new actor");
+
+    let create_return: fil_actor_eam::CreateExternalReturn =
+        create_result.ret.unwrap().deserialize().expect("failed to decode results");
+    let id_addr = Address::new_id(create_return.actor_id);
+    assert_eq!(&v.actor(&id_addr).unwrap().code, &*EVM_ACTOR_CODE_ID);
+    (id_addr, create_return.eth_address.0)
+}
+
+/// Calls the contract and returns the counter value it reports.
+fn increment(v: &dyn VM, caller: &Address, contract: &Address) -> u8 {
+    let call_result = v
+        .execute_message(
+            caller,
+            contract,
+            &TokenAmount::zero(),
+            fil_actor_evm::Method::InvokeContract as u64,
+            Some(serialize_ok(&ContractParams(vec![]))),
+        )
+        .unwrap();
+    assert_ok!(call_result, "failed to call the contract");
+    let BytesDe(return_value) =
+        call_result.ret.unwrap().deserialize().expect("failed to deserialize results");
+    assert_eq!(return_value.len(), 32);
+    assert!(return_value[..31].iter().all(|b| *b == 0), "counter overflowed a byte");
+    return_value[31]
+}
+
+#[vm_test]
+pub fn evm_eip1967_proxy_test(v: &dyn VM) {
+    let accounts = create_accounts(v, 1, &TokenAmount::from_whole(10_000));
+    let sender = accounts[0];
+
+    let counter_runtime = hex::decode(COUNTER_RUNTIME).unwrap();
+    let (counter, counter_eth) = create_contract(v, &sender, deploy_code(&[], &counter_runtime));
+    let (proxy, _) = create_contract(v, &sender, eip1967_proxy_initcode(&counter_eth));
+
+    // The counter runs against the proxy's storage.
+    assert_eq!(increment(v, &sender, &proxy), 1);
+    assert_eq!(increment(v, &sender, &proxy), 2);
+
+    // The implementation's own storage is untouched.
+    assert_eq!(increment(v, &sender, &counter), 1);
+    assert_eq!(increment(v, &sender, &proxy), 3);
+}
+
+#[vm_test]
+pub fn evm_minimal_clone_test(v: &dyn VM) {
+    let accounts = create_accounts(v, 1, &TokenAmount::from_whole(10_000));
+    let sender = accounts[0];
+
+    let counter_runtime = hex::decode(COUNTER_RUNTIME).unwrap();
+    let (counter, counter_eth) = create_contract(v, &sender, deploy_code(&[], &counter_runtime));
+    let (clone_a, _) = create_contract(v, &sender, minimal_clone_initcode(&counter_eth));
+    let (clone_b, _) = create_contract(v, &sender, minimal_clone_initcode(&counter_eth));
+
+    // Each clone keeps its own count.
+    assert_eq!(increment(v, &sender, &clone_a), 1);
+    assert_eq!(increment(v, &sender, &clone_a), 2);
+    assert_eq!(increment(v, &sender, &clone_b), 1);
+    assert_eq!(increment(v, &sender, &counter), 1);
+    assert_eq!(increment(v, &sender, &clone_a), 3);
+}
+
+#[vm_test]
+pub fn evm_nested_proxy_test(v: &dyn VM) {
+    // proxy -> delegatecall -> clone code -> delegatecall -> counter code, all of which must
+    // run against the proxy's storage.
+    let accounts = create_accounts(v, 1, &TokenAmount::from_whole(10_000));
+    let sender = accounts[0];
+
+    let counter_runtime = hex::decode(COUNTER_RUNTIME).unwrap();
+    let (counter, counter_eth) = create_contract(v, &sender, deploy_code(&[], &counter_runtime));
+    let (clone, clone_eth) = create_contract(v, &sender, minimal_clone_initcode(&counter_eth));
+    let (proxy, _) = create_contract(v, &sender, eip1967_proxy_initcode(&clone_eth));
+
+    assert_eq!(increment(v, &sender, &proxy), 1);
+    assert_eq!(increment(v, &sender, &proxy), 2);
+
+    // Neither the clone in the middle nor the implementation at the bottom was written.
+    assert_eq!(increment(v, &sender, &clone), 1);
+    assert_eq!(increment(v, &sender, &counter), 1);
+
+    // And writes through them don't leak back into the proxy.
+    assert_eq!(increment(v, &sender, &proxy), 3);
+}
@@ -10,12 +10,13 @@ use fil_actors_runtime::{
 use fvm_ipld_encoding::ipld_block::IpldBlock;
 use fvm_ipld_encoding::RawBytes;
 use fvm_ipld_encoding::{strict_bytes, BytesDe};
+use fvm_shared::error::ExitCode;
 use fvm_shared::ActorID;
 use fvm_shared::METHOD_SEND;
 use fvm_shared::{address::Address, econ::TokenAmount};
 use num_traits::Zero;
 use serde::{Deserialize, Serialize};
-use vm_api::util::{apply_ok, serialize_ok};
+use vm_api::util::{apply_ok, assert_aborts_with, assert_ok, serialize_ok};
 use vm_api::VM;
 
 use crate::util::create_accounts;
@@ -69,11 +70,7 @@ pub fn evm_eth_create_external_test(v: &dyn VM) {
         )
         .unwrap();
 
-    assert!(
-        create_result.code.is_success(),
-        "failed to create the new actor {}",
-        create_result.message
-    );
+    assert_ok!(create_result, "failed to create the new actor");
 
     // and call it
     let create_return: fil_actor_eam::CreateExternalReturn =
@@ -91,7 +88,7 @@ pub fn evm_eth_create_external_test(v: &dyn VM) {
             params,
         )
         .unwrap();
-    assert!(call_result.code.is_success(), "failed to call the new actor {}", call_result.message);
+    assert_ok!(call_result, "failed to call the new actor");
 }
 
 #[vm_test]
@@ -111,11 +108,7 @@ pub fn evm_call_test(v: &dyn VM) {
         )
         .unwrap();
 
-    assert!(
-        create_result.code.is_success(),
-        "failed to create the new actor {}",
-        create_result.message
-    );
+    assert_ok!(create_result, "failed to create the new actor");
 
     let create_return: fil_actor_eam::CreateExternalReturn =
         create_result.ret.unwrap().deserialize().expect("failed to decode results");
@@ -130,7 +123,7 @@ pub fn evm_call_test(v: &dyn VM) {
             Some(serialize_ok(&ContractParams(contract_params.to_vec()))),
         )
         .unwrap();
-    assert!(call_result.code.is_success(), "failed to call the new actor {}", call_result.message);
+    assert_ok!(call_result, "failed to call the new actor");
 
     let BytesDe(return_value) =
         call_result.ret.unwrap().deserialize().expect("failed to deserialize results");
@@ -157,11 +150,7 @@ pub fn evm_create_test(v: &dyn VM) {
         )
         .unwrap();
 
-    assert!(
-        create_result.code.is_success(),
-        "failed to create the new actor {}",
-        create_result.message
-    );
+    assert_ok!(create_result, "failed to create the new actor");
 
     let create_return: fil_actor_eam::CreateExternalReturn =
         create_result.ret.unwrap().deserialize().expect("failed to decode results");
@@ -178,11 +167,7 @@ pub fn evm_create_test(v: &dyn VM) {
                     Some(serialize_ok(&ContractParams(call_params.to_vec()))),
                 )
                 .unwrap();
-            assert!(
-                call_result.code.is_success(),
-                "failed to call the new actor {}",
-                call_result.message,
-            );
+            assert_ok!(call_result, "failed to call the new actor");
             let BytesDe(return_value) =
                 call_result.ret.unwrap().deserialize().expect("failed to deserialize results");
             Factory::createCall::abi_decode_returns(&return_value, true)
@@ -204,11 +189,7 @@ pub fn evm_create_test(v: &dyn VM) {
                     Some(serialize_ok(&ContractParams(call_params.to_vec()))),
                 )
                 .unwrap();
-            assert!(
-                call_result.code.is_success(),
-                "failed to call the new actor {}",
-                call_result.message
-            );
+            assert_ok!(call_result, "failed to call the new actor");
             let BytesDe(return_value) =
                 call_result.ret.unwrap().deserialize().expect("failed to deserialize results");
             let (res,) = FactoryChild::get_valueCall::abi_decode_returns(&return_value, true)
@@ -234,14 +215,10 @@ pub fn evm_create_test(v: &dyn VM) {
                     Some(serialize_ok(&ContractParams(call_params))),
                 )
                 .unwrap();
-            assert!(
-                call_result.code.is_success(),
-                "failed to call the new actor {}",
-                call_result.message
-            );
+            assert_ok!(call_result, "failed to call the new actor");
         }
 
-        // It should now be dead.
+        // It was not created in the same transaction, so it should still be alive.
         {
             let call_params = FactoryChild::get_valueCall::new(()).abi_encode();
             let call_result = v
@@ -253,27 +230,35 @@ pub fn evm_create_test(v: &dyn VM) {
                     Some(serialize_ok(&ContractParams(call_params.to_vec()))),
                 )
                 .unwrap();
-            assert!(
-                call_result.code.is_success(),
-                "failed to call the new actor {}",
-                call_result.message
-            );
+            assert_ok!(call_result, "failed to call the new actor");
             let BytesDe(return_value) =
                 call_result.ret.unwrap().deserialize().expect("failed to deserialize results");
-            assert!(return_value.is_empty());
+            let (res,) = FactoryChild::get_valueCall::abi_decode_returns(&return_value, true)
+                .expect("failed to decode return")
+                .into();
+            assert_eq!(res, if recursive { 1234 } else { 42 });
         }
         child_addr_eth
     };
 
-    // Test CREATE2 twice because we should be able to deploy over an existing contract.
-    let eth_addr1 = test_func(Factory::FactoryCalls::create2(([0; 32].into(), 42).into()), false);
-    let eth_addr2 = test_func(Factory::FactoryCalls::create2(([0; 32].into(), 42).into()), false);
-    assert_eq!(eth_addr1, eth_addr2);
+    // Test CREATE2 twice, expecting the second to fail because the first contract still exists.
+    test_func(Factory::FactoryCalls::create2(([0; 32].into(), 42).into()), false);
+    {
+        let call_params = Factory::create2Call::new(([0; 32].into(), 42)).abi_encode();
+        let call_result = v
+            .execute_message(
+                &account,
+                &create_return.robust_address.unwrap(),
+                &TokenAmount::zero(),
+                fil_actor_evm::Method::InvokeContract as u64,
+                Some(serialize_ok(&ContractParams(call_params.to_vec()))),
+            )
+            .unwrap();
+        assert_aborts_with!(call_result, ExitCode::new(33), "expected contract revert");
+    }
 
-    // Recursive self-destruct should work.
-    let eth_addr1 = test_func(Factory::FactoryCalls::create2(([1; 32].into(), 42).into()), true);
-    let eth_addr2 = test_func(Factory::FactoryCalls::create2(([1; 32].into(), 42).into()), false);
-    assert_eq!(eth_addr1, eth_addr2);
+    // Recursive self-destruct should return to the caller.
+    test_func(Factory::FactoryCalls::create2(([1; 32].into(), 42).into()), true);
 
     // Then test create and expect two different addrs.
     let eth_addr1 = test_func(Factory::FactoryCalls::create((42,).into()), false);
@@ -293,12 +278,7 @@ pub fn evm_create_test(v: &dyn VM) {
                 Some(serialize_ok(&ContractParams(call_params.to_vec()))),
             )
             .unwrap();
-        assert_eq!(
-            call_result.code.value(),
-            33,
-            "expected contract revert {}",
-            call_result.message
-        );
+        assert_aborts_with!(call_result, ExitCode::new(33), "expected contract revert");
         let BytesDe(return_value) =
             call_result.ret.unwrap().deserialize().expect("failed to deserialize results");
         let revert_msg: String = decode_revert_reason(&return_value).expect("expected a revert");
@@ -319,11 +299,7 @@ pub fn evm_empty_initcode_test(v: &dyn VM) {
         )
         .unwrap();
 
-    assert!(
-        create_result.code.is_success(),
-        "failed to create the new actor {}",
-        create_result.message
-    );
+    assert_ok!(create_result, "failed to create the new actor");
 }
 
 #[vm_test]
@@ -354,11 +330,7 @@ pub fn evm_staticcall_test(v: &dyn VM) {
                 )
                 .unwrap();
 
-            assert!(
-                create_result.code.is_success(),
-                "failed to create the new actor {}",
-                create_result.message
-            );
+            assert_ok!(create_result, "failed to create the new actor");
 
             let create_return: fil_actor_eam::CreateExternalReturn =
                 create_result.ret.unwrap().deserialize().expect("failed to decode results");
@@ -391,11 +363,7 @@ pub fn evm_staticcall_test(v: &dyn VM) {
                 Some(serialize_ok(&ContractParams(params.to_vec()))),
             )
             .unwrap();
-        assert!(
-            call_result.code.is_success(),
-            "failed to call the new actor {}",
-            call_result.message
-        );
+        assert_ok!(call_result, "failed to call the new actor");
         let BytesDe(return_value) =
             call_result.ret.unwrap().deserialize().expect("failed to deserialize results");
         assert_eq!(&return_value[12..], &created[1].eth_address.0);
@@ -419,7 +387,7 @@ pub fn evm_staticcall_test(v: &dyn VM) {
                 Some(serialize_ok(&ContractParams(params.to_vec()))),
             )
             .unwrap();
-        assert_eq!(call_result.code.value(), 33, "static call mutation did not revert");
+        assert_aborts_with!(call_result, ExitCode::new(33), "static call mutation did not revert");
     }
 
     // A -> staticcall -> B -> call -> C (read) OK
@@ -442,11 +410,7 @@ pub fn evm_staticcall_test(v: &dyn VM) {
                 Some(serialize_ok(&ContractParams(params.to_vec()))),
             )
             .unwrap();
-        assert!(
-            call_result.code.is_success(),
-            "failed to call the new actor {}",
-            call_result.message
-        );
+        assert_ok!(call_result, "failed to call the new actor");
         let BytesDe(return_value) =
             call_result.ret.unwrap().deserialize().expect("failed to deserialize results");
         assert_eq!(&return_value[12..], &created[2].eth_address.0);
@@ -472,7 +436,7 @@ pub fn evm_staticcall_test(v: &dyn VM) {
                 Some(serialize_ok(&ContractParams(params.to_vec()))),
             )
             .unwrap();
-        assert_eq!(call_result.code.value(), 33, "static call mutation did not revert");
+        assert_aborts_with!(call_result, ExitCode::new(33), "static call mutation did not revert");
     }
 }
 
@@ -504,11 +468,7 @@ pub fn evm_delegatecall_test(v: &dyn VM) {
                 )
                 .unwrap();
 
-            assert!(
-                create_result.code.is_success(),
-                "failed to create the new actor {}",
-                create_result.message
-            );
+            assert_ok!(create_result, "failed to create the new actor");
 
             let create_return: fil_actor_eam::CreateExternalReturn =
                 create_result.ret.unwrap().deserialize().expect("failed to decode results");
@@ -541,11 +501,7 @@ pub fn evm_delegatecall_test(v: &dyn VM) {
                 Some(serialize_ok(&ContractParams(params.to_vec()))),
             )
             .unwrap();
-        assert!(
-            call_result.code.is_success(),
-            "failed to call the new actor {}",
-            call_result.message
-        );
+        assert_ok!(call_result, "failed to call the new actor");
         let BytesDe(return_value) =
             call_result.ret.unwrap().deserialize().expect("failed to deserialize results");
         assert_eq!(&return_value[12..], &created[0].eth_address.0);
@@ -569,11 +525,7 @@ pub fn evm_delegatecall_test(v: &dyn VM) {
                 Some(serialize_ok(&ContractParams(params.to_vec()))),
             )
             .unwrap();
-        assert!(
-            call_result.code.is_success(),
-            "failed to call the new actor {}",
-            call_result.message
-        );
+        assert_ok!(call_result, "failed to call the new actor");
         let BytesDe(return_value) =
             call_result.ret.unwrap().deserialize().expect("failed to deserialize results");
         assert_eq!(&return_value[28..], &[0xff, 0xff, 0xff, 0x42]);
@@ -599,11 +551,7 @@ pub fn evm_delegatecall_test(v: &dyn VM) {
                 Some(serialize_ok(&ContractParams(params.to_vec()))),
             )
             .unwrap();
-        assert!(
-            call_result.code.is_success(),
-            "failed to call the new actor {}",
-            call_result.message
-        );
+        assert_ok!(call_result, "failed to call the new actor");
         let BytesDe(return_value) =
             call_result.ret.unwrap().deserialize().expect("failed to deserialize results");
         assert_eq!(&return_value, &U256::from(&value).to_bytes()[..]);
@@ -639,11 +587,7 @@ pub fn evm_staticcall_delegatecall_test(v: &dyn VM) {
                 )
                 .unwrap();
 
-            assert!(
-                create_result.code.is_success(),
-                "failed to create the new actor {}",
-                create_result.message
-            );
+            assert_ok!(create_result, "failed to create the new actor");
 
             let create_return: fil_actor_eam::CreateExternalReturn =
                 create_result.ret.unwrap().deserialize().expect("failed to decode results");
@@ -678,11 +622,7 @@ pub fn evm_staticcall_delegatecall_test(v: &dyn VM) {
                 Some(serialize_ok(&ContractParams(params.to_vec()))),
             )
             .unwrap();
-        assert!(
-            call_result.code.is_success(),
-            "failed to call the new actor {}",
-            call_result.message
-        );
+        assert_ok!(call_result, "failed to call the new actor");
         let BytesDe(return_value) =
             call_result.ret.unwrap().deserialize().expect("failed to deserialize results");
         assert_eq!(&return_value[12..], &created[1].eth_address.0);
@@ -708,7 +648,7 @@ pub fn evm_staticcall_delegatecall_test(v: &dyn VM) {
                 Some(serialize_ok(&ContractParams(params.to_vec()))),
             )
             .unwrap();
-        assert_eq!(call_result.code.value(), 33, "static call mutation did not revert");
+        assert_aborts_with!(call_result, ExitCode::new(33), "static call mutation did not revert");
     }
 }
 
@@ -762,11 +702,7 @@ pub fn evm_transient_nested_test(v: &dyn VM) {
             )
             .unwrap();
 
-        assert!(
-            create_result.code.is_success(),
-            "failed to create the contract {}",
-            create_result.message
-        );
+        assert_ok!(create_result, "failed to create the contract");
 
         let create_return: fil_actor_eam::CreateExternalReturn =
             create_result.ret.unwrap().deserialize().expect("failed to decode results");
@@ -796,11 +732,7 @@ pub fn evm_transient_nested_test(v: &dyn VM) {
         .unwrap();
 
     // Step 4: Check if the call was successful.
-    assert!(
-        call_result.code.is_success(),
-        "testNestedContracts call failed: {}",
-        call_result.message
-    );
+    assert_ok!(call_result, "testNestedContracts call failed");
 
     // Step 5: Deserialize and verify the event emitted by the contract.
     let BytesDe(return_value) =
@@ -835,11 +767,7 @@ pub fn evm_transient_reentry_test(v: &dyn VM) {
             )
             .unwrap();
 
-        assert!(
-            create_result.code.is_success(),
-            "failed to create the contract {}",
-            create_result.message
-        );
+        assert_ok!(create_result, "failed to create the contract");
 
         let create_return: fil_actor_eam::CreateExternalReturn =
             create_result.ret.unwrap().deserialize().expect("failed to decode results");
@@ -869,7 +797,7 @@ pub fn evm_transient_reentry_test(v: &dyn VM) {
         .unwrap();
 
     // Step 4: Check if the call was successful.
-    assert!(call_result.code.is_success(), "testReentry call failed: {}", call_result.message);
+    assert_ok!(call_result, "testReentry call failed");
 
     // Step 5: Deserialize and verify the return value.
     let BytesDe(return_value) =
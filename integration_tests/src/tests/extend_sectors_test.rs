@@ -700,7 +700,7 @@ pub fn extend_updated_sector_with_claims_test(v: &dyn VM) {
                 from: miner_id,
                 to: VERIFIED_REGISTRY_ACTOR_ADDR,
                 method: VerifregMethod::ClaimAllocations as u64,
-                events: Some(vec![Expect::build_verifreg_claim_event(
+                events: Some(Expect::build_verifreg_claim_events(
                     "claim",
                     claim_id,
                     verified_client.id().unwrap(),
@@ -711,7 +711,7 @@ pub fn extend_updated_sector_with_claims_test(v: &dyn VM) {
                     claim_term + MARKET_DEFAULT_ALLOCATION_TERM_BUFFER,
                     v.epoch(),
                     sector_number,
-                )]),
+                )),
                 ..Default::default()
             },
             Expect::reward_this_epoch(miner_id),
@@ -6,7 +6,11 @@ use fil_actors_runtime::{
 };
 use fvm_shared::{address::Address, econ::TokenAmount, error::ExitCode, METHOD_SEND};
 use num_traits::Zero;
-use vm_api::{builtin::Type, util::serialize_ok, VM};
+use vm_api::{
+    builtin::Type,
+    util::{assert_aborts_with, assert_ok, serialize_ok},
+    VM,
+};
 
 use crate::{FIRST_TEST_USER_ADDR, TEST_FAUCET_ADDR};
 
@@ -47,6 +51,7 @@ pub fn placeholder_deploy_test(v: &dyn VM) {
             num_approvals_threshold: 1,
             unlock_duration: 0,
             start_epoch: 0,
+            signer_weights: vec![],
         },
         "multisig ctor params",
     )
@@ -68,7 +73,7 @@ pub fn placeholder_deploy_test(v: &dyn VM) {
     };
 
     let msig_ctor_res = deploy();
-    assert_eq!(msig_ctor_res.code, ExitCode::OK);
+    assert_ok!(msig_ctor_res);
     let msig_ctor_ret: Exec4Return = msig_ctor_res.ret.unwrap().deserialize().unwrap();
 
     assert_eq!(
@@ -81,5 +86,5 @@ pub fn placeholder_deploy_test(v: &dyn VM) {
 
     // Try to overwrite it.
     let msig_ctor_res = deploy();
-    assert_eq!(ExitCode::USR_FORBIDDEN, msig_ctor_res.code);
+    assert_aborts_with!(msig_ctor_res, ExitCode::USR_FORBIDDEN);
 }
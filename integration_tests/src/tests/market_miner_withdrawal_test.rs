@@ -190,8 +190,11 @@ fn assert_add_collateral_and_withdraw(
             .unwrap()
         }
         x if x == *MARKET_ACTOR_CODE_ID => {
-            let params =
-                MarketWithdrawBalanceParams { provider_or_client: caller, amount: requested };
+            let params = MarketWithdrawBalanceParams {
+                provider_or_client: caller,
+                amount: requested,
+                payee: None,
+            };
             apply_ok(
                 v,
                 &caller,
@@ -3,7 +3,7 @@ use fil_actor_init::Method as InitMethod;
 use fil_actor_miner::{
     max_prove_commit_duration, Method as MinerMethod, MinerConstructorParams, MIN_SECTOR_EXPIRATION,
 };
-use fil_actor_power::{CreateMinerParams, Method as PowerMethod};
+use fil_actor_power::{CreateMinerParams, Method as PowerMethod, ERR_INVALID_PEER_ID};
 use fil_actors_runtime::runtime::Policy;
 
 use fil_actors_runtime::{
@@ -19,7 +19,7 @@ use fvm_shared::sector::{RegisteredPoStProof, RegisteredSealProof};
 use fvm_shared::METHOD_SEND;
 use num_traits::Zero;
 use vm_api::trace::ExpectInvocation;
-use vm_api::util::{apply_ok, serialize_ok};
+use vm_api::util::{apply_ok, assert_aborts_with, serialize_ok};
 use vm_api::VM;
 
 use crate::expects::Expect;
@@ -103,6 +103,37 @@ pub fn power_create_miner_test(v: &dyn VM) {
     assert_invariants(v, &Policy::default(), None);
 }
 
+#[vm_test]
+pub fn power_create_miner_invalid_params_test(v: &dyn VM) {
+    let owner = Address::new_bls(&[1; fvm_shared::address::BLS_PUB_LEN]).unwrap();
+    let balance = TokenAmount::from_atto(10_000u32);
+    v.execute_message(&TEST_FAUCET_ADDR, &owner, &balance, METHOD_SEND, None).unwrap();
+    let params = CreateMinerParams {
+        owner,
+        worker: owner,
+        window_post_proof_type: RegisteredPoStProof::StackedDRGWindow32GiBV1P1,
+        peer: vec![0; Policy::default().max_peer_id_length + 1],
+        multiaddrs: vec![BytesDe("multiaddr".as_bytes().to_vec())],
+    };
+
+    let res = v
+        .execute_message(
+            &owner,
+            &STORAGE_POWER_ACTOR_ADDR,
+            &TokenAmount::from_atto(1000u32),
+            PowerMethod::CreateMiner as u64,
+            Some(serialize_ok(&params)),
+        )
+        .unwrap();
+    assert_aborts_with!(res, ERR_INVALID_PEER_ID);
+
+    // No miner is created and the value is returned to the owner.
+    let invocation = v.take_invocations().pop().unwrap();
+    assert!(invocation.subinvocations.is_empty());
+    assert_eq!(balance, v.balance(&v.resolve_id_address(&owner).unwrap()));
+    assert_invariants(v, &Policy::default(), None);
+}
+
 #[vm_test]
 pub fn cron_tick_test(v: &dyn VM) {
     let addrs = create_accounts(v, 1, &TokenAmount::from_whole(10_000));
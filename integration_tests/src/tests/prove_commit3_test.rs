@@ -118,7 +118,11 @@ pub fn prove_commit_sectors2_test(v: &dyn VM) {
     let first_sector_number: SectorNumber = 100;
     let manifests = vec![
         // Sector 0: no pieces (CC sector)
-        SectorActivationManifest { sector_number: first_sector_number, pieces: vec![] },
+        SectorActivationManifest {
+            sector_number: first_sector_number,
+            pieces: vec![],
+            client_manifests: vec![],
+        },
         // Sector 1: one piece, no claim or deal.
         SectorActivationManifest {
             sector_number: first_sector_number + 1,
@@ -128,6 +132,7 @@ pub fn prove_commit_sectors2_test(v: &dyn VM) {
                 verified_allocation_key: None,
                 notify: vec![],
             }],
+            client_manifests: vec![],
         },
         // Sector 2: two pieces for verified claims.
         SectorActivationManifest {
@@ -145,6 +150,7 @@ pub fn prove_commit_sectors2_test(v: &dyn VM) {
                     notify: vec![],
                 })
                 .collect(),
+            client_manifests: vec![],
         },
         // Sector 3: a full-size, unverified deal
         SectorActivationManifest {
@@ -158,6 +164,7 @@ pub fn prove_commit_sectors2_test(v: &dyn VM) {
                     payload: serialize(&deal_ids_s3[0], "dealid").unwrap(),
                 }],
             }],
+            client_manifests: vec![],
         },
         // Sector 4: a half-sized, verified deal, and implicit empty space
         SectorActivationManifest {
@@ -174,6 +181,7 @@ pub fn prove_commit_sectors2_test(v: &dyn VM) {
                     payload: serialize(&deal_ids_s4[0], "deal id").unwrap(),
                 }],
             }],
+            client_manifests: vec![],
         },
     ];
 
@@ -239,7 +247,7 @@ pub fn prove_commit_sectors2_test(v: &dyn VM) {
         })
         .collect();
 
-    let claim_event_1 = Expect::build_verifreg_claim_event(
+    let claim_events_1 = Expect::build_verifreg_claim_events(
         "claim",
         alloc_ids_s2[0],
         client_id,
@@ -252,7 +260,7 @@ pub fn prove_commit_sectors2_test(v: &dyn VM) {
         first_sector_number + 2,
     );
 
-    let claim_event_2 = Expect::build_verifreg_claim_event(
+    let claim_events_2 = Expect::build_verifreg_claim_events(
         "claim",
         alloc_ids_s2[1],
         client_id,
@@ -265,7 +273,7 @@ pub fn prove_commit_sectors2_test(v: &dyn VM) {
         first_sector_number + 2,
     );
 
-    let claim_event_3 = Expect::build_verifreg_claim_event(
+    let claim_events_3 = Expect::build_verifreg_claim_events(
         "claim",
         alloc_ids_s4[0],
         client_id,
@@ -328,7 +336,7 @@ pub fn prove_commit_sectors2_test(v: &dyn VM) {
                     })
                     .unwrap(),
                 ),
-                events: Some(vec![claim_event_1, claim_event_2, claim_event_3]),
+                events: Some([claim_events_1, claim_events_2, claim_events_3].concat()),
                 ..Default::default()
             },
             Expect::reward_this_epoch(miner_id),
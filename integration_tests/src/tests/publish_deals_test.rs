@@ -21,7 +21,7 @@ use fvm_shared::error::ExitCode;
 use fvm_shared::piece::PaddedPieceSize;
 use fvm_shared::sector::{RegisteredSealProof, StoragePower};
 use vm_api::trace::ExpectInvocation;
-use vm_api::util::{apply_ok, serialize_ok};
+use vm_api::util::{apply_ok, assert_aborts_with, serialize_ok};
 use vm_api::VM;
 
 use crate::deals::{DealBatcher, DealOptions};
@@ -485,7 +485,7 @@ pub fn psd_bad_sig_test(v: &dyn VM) {
             Some(serialize_ok(&publish_params)),
         )
         .unwrap();
-    assert_eq!(ExitCode::USR_ILLEGAL_ARGUMENT, ret.code);
+    assert_aborts_with!(ret, ExitCode::USR_ILLEGAL_ARGUMENT);
 
     ExpectInvocation {
         from: worker_id,
@@ -503,6 +503,7 @@ pub fn psd_bad_sig_test(v: &dyn VM) {
                     IpldBlock::serialize_cbor(&AuthenticateMessageParams {
                         signature: invalid_sig_bytes,
                         message: serialize(&proposal, "deal proposal").unwrap().to_vec(),
+                        batch: None,
                     })
                     .unwrap(),
                 ),
@@ -593,3 +594,121 @@ pub fn psd_deal_duration_too_long_test(v: &dyn VM) {
 
     assert_invariants(v, &Policy::default(), None)
 }
+
+// A reason for PublishStorageDeals to drop a deal from an otherwise valid batch.
+#[derive(Clone, Copy, Debug)]
+enum DealFailure {
+    BadSignature,
+    InsufficientBalance,
+    DatacapShortfall,
+    DuplicateProposal,
+    BadDuration,
+}
+
+const DEAL_FAILURES: [DealFailure; 5] = [
+    DealFailure::BadSignature,
+    DealFailure::InsufficientBalance,
+    DealFailure::DatacapShortfall,
+    DealFailure::DuplicateProposal,
+    DealFailure::BadDuration,
+];
+
+impl DealFailure {
+    // Stages a deal that fails for this reason.
+    // A duplicate repeats the proposal staged or published for `prior` (client, label).
+    fn stage(
+        self,
+        batcher: &mut DealBatcher,
+        a: &Addrs,
+        broke_client: Address,
+        opts: &DealOptions,
+        prior: &(Address, String),
+    ) {
+        match self {
+            DealFailure::BadSignature => batcher.stage_with_bad_signature(a.client1, a.maddr),
+            DealFailure::InsufficientBalance => batcher.stage(broke_client, a.maddr),
+            // More than the verified client's entire allowance.
+            DealFailure::DatacapShortfall => batcher.stage_with_opts(
+                a.verified_client,
+                a.maddr,
+                DealOptions {
+                    piece_size: PaddedPieceSize(1 << 33),
+                    verified: true,
+                    ..opts.clone()
+                },
+            ),
+            DealFailure::DuplicateProposal => {
+                batcher.stage_with_opts_label(prior.0, a.maddr, prior.1.clone(), opts.clone())
+            }
+            DealFailure::BadDuration => batcher.stage_with_opts(
+                a.client1,
+                a.maddr,
+                DealOptions { deal_lifetime: 1279 * EPOCHS_IN_DAY, ..opts.clone() },
+            ),
+        }
+    }
+}
+
+// Generates batches mixing every failure class with good deals (None), one batch per rotation
+// of the failure classes so that each class appears at every offset in the batch.
+// Each batch starts with a good deal and alternates good and bad deals.
+fn failure_matrix() -> Vec<Vec<Option<DealFailure>>> {
+    (0..DEAL_FAILURES.len())
+        .map(|rotation| {
+            let mut batch = vec![None];
+            for i in 0..DEAL_FAILURES.len() {
+                batch.push(Some(DEAL_FAILURES[(rotation + i) % DEAL_FAILURES.len()]));
+                batch.push(None);
+            }
+            batch
+        })
+        .collect()
+}
+
+#[vm_test]
+pub fn psd_failure_matrix_test(v: &dyn VM) {
+    let (a, deal_start) = setup(v);
+    let opts = DealOptions { deal_start, ..DealOptions::default() };
+    let broke_client =
+        create_accounts_seeded(v, 1, &TokenAmount::zero(), 555, &TEST_FAUCET_ADDR)[0];
+
+    for (b, batch) in failure_matrix().into_iter().enumerate() {
+        let mut batcher = DealBatcher::new(v, opts.clone());
+        let mut expected_valid = vec![];
+        let mut prior_good: Option<(Address, String)> = None;
+        for (i, deal) in batch.iter().enumerate() {
+            match deal {
+                None => {
+                    // Alternate clients so that no single client's balance limits the matrix.
+                    let client = if i % 4 == 0 { a.client1 } else { a.client2 };
+                    let label = format!("batch-{}-deal-{}", b, i);
+                    batcher.stage_with_opts_label(client, a.maddr, label.clone(), opts.clone());
+                    expected_valid.push(i as u64);
+                    prior_good = Some((client, label));
+                }
+                Some(failure) => failure.stage(
+                    &mut batcher,
+                    &a,
+                    broke_client,
+                    &opts,
+                    prior_good.as_ref().unwrap(),
+                ),
+            }
+        }
+
+        let deal_ret = batcher.publish_ok(a.worker);
+        assert_eq!(expected_valid, bf_all(deal_ret.valid_deals), "batch {}: {:?}", b, batch);
+        assert_eq!(expected_valid.len(), deal_ret.ids.len(), "batch {}: {:?}", b, batch);
+    }
+
+    // A batch made up only of failures is rejected outright.
+    // The duplicate here repeats the first proposal already published to state.
+    let published = (a.client1, "batch-0-deal-0".to_string());
+    let mut batcher = DealBatcher::new(v, opts.clone());
+    for failure in DEAL_FAILURES {
+        failure.stage(&mut batcher, &a, broke_client, &opts, &published);
+    }
+    batcher.publish_fail(a.worker);
+
+    assert_invariants(v, &Policy::default(), None)
+}
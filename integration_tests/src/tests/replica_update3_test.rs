@@ -71,6 +71,7 @@ pub fn prove_replica_update2_test(v: &dyn VM) {
         .map(|i| SectorActivationManifest {
             sector_number: first_sector_number + i,
             pieces: vec![],
+            client_manifests: vec![],
         })
         .collect();
     let meta: Vec<PrecommitMetadata> = (0..activations.len())
@@ -250,7 +251,7 @@ pub fn prove_replica_update2_test(v: &dyn VM) {
         },
     ];
 
-    let claim_event_1 = Expect::build_verifreg_claim_event(
+    let claim_events_1 = Expect::build_verifreg_claim_events(
         "claim",
         alloc_ids_s2[0],
         client_id,
@@ -262,7 +263,7 @@ pub fn prove_replica_update2_test(v: &dyn VM) {
         v.epoch(),
         first_sector_number + 2,
     );
-    let claim_event_2 = Expect::build_verifreg_claim_event(
+    let claim_events_2 = Expect::build_verifreg_claim_events(
         "claim",
         alloc_ids_s2[1],
         client_id,
@@ -274,7 +275,7 @@ pub fn prove_replica_update2_test(v: &dyn VM) {
         v.epoch(),
         first_sector_number + 2,
     );
-    let claim_event_3 = Expect::build_verifreg_claim_event(
+    let claim_events_3 = Expect::build_verifreg_claim_events(
         "claim",
         alloc_ids_s4[0],
         client_id,
@@ -389,7 +390,7 @@ pub fn prove_replica_update2_test(v: &dyn VM) {
                     })
                     .unwrap(),
                 ),
-                events: Some(vec![claim_event_1, claim_event_2, claim_event_3]),
+                events: Some([claim_events_1, claim_events_2, claim_events_3].concat()),
                 ..Default::default()
             },
             Expect::reward_this_epoch(miner_id),
@@ -1035,7 +1035,7 @@ pub fn replica_update_verified_deal_test(v: &dyn VM) {
     let claim_id = 1_u64;
     let deal_term = proposal.end_epoch - proposal.start_epoch;
     let term_max = deal_term + MARKET_DEFAULT_ALLOCATION_TERM_BUFFER;
-    let claim_event = Expect::build_verifreg_claim_event(
+    let claim_events = Expect::build_verifreg_claim_events(
         "claim",
         claim_id,
         client.id().unwrap(),
@@ -1073,7 +1073,7 @@ pub fn replica_update_verified_deal_test(v: &dyn VM) {
                 from: miner_id,
                 to: VERIFIED_REGISTRY_ACTOR_ADDR,
                 method: VerifregMethod::ClaimAllocations as u64,
-                events: Some(vec![claim_event]),
+                events: Some(claim_events),
                 ..Default::default()
             },
             Expect::reward_this_epoch(miner_id),
@@ -292,6 +292,7 @@ pub fn terminate_sectors_test(v: &dyn VM) {
         Some(WithdrawBalanceParams {
             provider_or_client: verified_client,
             amount: withdrawal.clone(),
+            payee: None,
         }),
     );
     ExpectInvocation {
@@ -313,7 +314,11 @@ pub fn terminate_sectors_test(v: &dyn VM) {
         &STORAGE_MARKET_ACTOR_ADDR,
         &TokenAmount::zero(),
         MarketMethod::WithdrawBalance as u64,
-        Some(WithdrawBalanceParams { provider_or_client: miner_id_addr, amount: miner_collateral }),
+        Some(WithdrawBalanceParams {
+            provider_or_client: miner_id_addr,
+            amount: miner_collateral,
+            payee: None,
+        }),
     );
 
     let value_withdrawn = v.take_invocations().last().unwrap().subinvocations[1].value.clone();
@@ -590,10 +590,12 @@ pub fn deal_passes_claim_fails_test(v: &dyn VM) {
             SectorActivationManifest {
                 sector_number: sector_number_b,
                 pieces: make_piece_manifests_from_deal_ids(v, vec![bad_deal]),
+                client_manifests: vec![],
             },
             SectorActivationManifest {
                 sector_number: sector_number_a,
                 pieces: make_piece_manifests_from_deal_ids(v, vec![deal]),
+                client_manifests: vec![],
             },
         ],
         sector_proofs: vec![vec![].into(), vec![].into()],
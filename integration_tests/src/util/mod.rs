@@ -31,7 +31,7 @@ use num_traits::Zero;
 use regex::Regex;
 use std::collections::HashMap;
 use vm_api::{
-    util::{apply_ok, get_state, pk_addrs_from, DynBlockstore},
+    util::{apply_ok, get_state_cached, pk_addrs_from, DynBlockstore},
     VM,
 };
 pub use workflows::*;
@@ -93,33 +93,33 @@ pub fn expect_invariants(
 }
 
 pub fn miner_balance(v: &dyn VM, m: &Address) -> MinerBalances {
-    let st: MinerState = get_state(v, m).unwrap();
+    let st = get_state_cached::<MinerState>(v, m).unwrap();
     MinerBalances {
         available_balance: st.get_available_balance(&v.balance(m)).unwrap(),
-        vesting_balance: st.locked_funds,
-        initial_pledge: st.initial_pledge,
-        pre_commit_deposit: st.pre_commit_deposits,
+        vesting_balance: st.locked_funds.clone(),
+        initial_pledge: st.initial_pledge.clone(),
+        pre_commit_deposit: st.pre_commit_deposits.clone(),
     }
 }
 
 pub fn miner_info(v: &dyn VM, m: &Address) -> MinerInfo {
-    let st: MinerState = get_state(v, m).unwrap();
+    let st = get_state_cached::<MinerState>(v, m).unwrap();
     DynBlockstore::wrap(v.blockstore()).get_cbor(&st.info).unwrap().unwrap()
 }
 
 pub fn miner_dline_info(v: &dyn VM, m: &Address) -> DeadlineInfo {
-    let st: MinerState = get_state(v, m).unwrap();
+    let st = get_state_cached::<MinerState>(v, m).unwrap();
     new_deadline_info_from_offset_and_epoch(&Policy::default(), st.proving_period_start, v.epoch())
 }
 
 pub fn sector_deadline(v: &dyn VM, m: &Address, s: SectorNumber) -> (u64, u64) {
-    let st: MinerState = get_state(v, m).unwrap();
+    let st = get_state_cached::<MinerState>(v, m).unwrap();
     st.find_sector(&DynBlockstore::wrap(v.blockstore()), s).unwrap()
 }
 
 pub fn check_sector_active(v: &dyn VM, m: &Address, s: SectorNumber) -> bool {
     let (d_idx, p_idx) = sector_deadline(v, m, s);
-    let st: MinerState = get_state(v, m).unwrap();
+    let st = get_state_cached::<MinerState>(v, m).unwrap();
     st.check_sector_active(&DynBlockstore::wrap(v.blockstore()), d_idx, p_idx, s, true).unwrap()
 }
 
@@ -130,7 +130,7 @@ pub fn check_sector_faulty(
     p_idx: u64,
     s: SectorNumber,
 ) -> bool {
-    let st: MinerState = get_state(v, m).unwrap();
+    let st = get_state_cached::<MinerState>(v, m).unwrap();
     let bs = &DynBlockstore::wrap(v.blockstore());
     let deadlines = st.load_deadlines(bs).unwrap();
     let deadline = deadlines.load_deadline(bs, d_idx).unwrap();
@@ -139,24 +139,24 @@ pub fn check_sector_faulty(
 }
 
 pub fn deadline_state(v: &dyn VM, m: &Address, d_idx: u64) -> Deadline {
-    let st: MinerState = get_state(v, m).unwrap();
+    let st = get_state_cached::<MinerState>(v, m).unwrap();
     let bs = &DynBlockstore::wrap(v.blockstore());
     let deadlines = st.load_deadlines(bs).unwrap();
     deadlines.load_deadline(bs, d_idx).unwrap()
 }
 
 pub fn sector_info(v: &dyn VM, m: &Address, s: SectorNumber) -> SectorOnChainInfo {
-    let st: MinerState = get_state(v, m).unwrap();
+    let st = get_state_cached::<MinerState>(v, m).unwrap();
     st.get_sector(&DynBlockstore::wrap(v.blockstore()), s).unwrap().unwrap()
 }
 
 pub fn try_sector_info(v: &dyn VM, m: &Address, s: SectorNumber) -> Option<SectorOnChainInfo> {
-    let st: MinerState = get_state(v, m).unwrap();
+    let st = get_state_cached::<MinerState>(v, m).unwrap();
     st.get_sector(&DynBlockstore::wrap(v.blockstore()), s).unwrap()
 }
 
 pub fn miner_power(v: &dyn VM, m: &Address) -> PowerPair {
-    let st: PowerState = get_state(v, &STORAGE_POWER_ACTOR_ADDR).unwrap();
+    let st = get_state_cached::<PowerState>(v, &STORAGE_POWER_ACTOR_ADDR).unwrap();
     let claim = st.get_claim(&DynBlockstore::wrap(v.blockstore()), m).unwrap().unwrap();
     PowerPair::new(claim.raw_byte_power, claim.quality_adj_power)
 }
@@ -178,7 +178,8 @@ pub fn market_pending_deal_allocations_raw(
     v: &dyn VM,
     deals: &[DealID],
 ) -> Result<Vec<AllocationID>, ActorError> {
-    let mut st: MarketState = get_state(v, &STORAGE_MARKET_ACTOR_ADDR).unwrap();
+    let mut st =
+        get_state_cached::<MarketState>(v, &STORAGE_MARKET_ACTOR_ADDR).unwrap().as_ref().clone();
     let bs = &DynBlockstore::wrap(v.blockstore());
     st.get_pending_deal_allocation_ids(bs, deals)
 }
@@ -188,13 +189,14 @@ pub fn market_pending_deal_allocations(v: &dyn VM, deals: &[DealID]) -> Vec<Allo
 }
 
 pub fn market_maybe_pending_deal_allocations(v: &dyn VM, deals: &[DealID]) -> Vec<AllocationID> {
-    let mut st: MarketState = get_state(v, &STORAGE_MARKET_ACTOR_ADDR).unwrap();
+    let mut st =
+        get_state_cached::<MarketState>(v, &STORAGE_MARKET_ACTOR_ADDR).unwrap().as_ref().clone();
     let bs = &DynBlockstore::wrap(v.blockstore());
     st.get_pending_deal_allocation_ids(bs, deals).unwrap()
 }
 
 pub fn market_list_deals(v: &dyn VM) -> HashMap<DealID, (DealProposal, Option<DealState>)> {
-    let st: MarketState = get_state(v, &STORAGE_MARKET_ACTOR_ADDR).unwrap();
+    let st = get_state_cached::<MarketState>(v, &STORAGE_MARKET_ACTOR_ADDR).unwrap();
     let bs = &DynBlockstore::wrap(v.blockstore());
     let proposals = st.load_proposals(bs).unwrap();
     let states = st.load_deal_states(bs).unwrap();
@@ -213,7 +215,7 @@ pub fn market_list_sectors_deals(
     v: &dyn VM,
     provider: &Address,
 ) -> HashMap<SectorNumber, Vec<DealID>> {
-    let st: MarketState = get_state(v, &STORAGE_MARKET_ACTOR_ADDR).unwrap();
+    let st = get_state_cached::<MarketState>(v, &STORAGE_MARKET_ACTOR_ADDR).unwrap();
     let bs = &DynBlockstore::wrap(v.blockstore());
     let sectors = st.load_provider_sectors(bs).unwrap();
     let sector_deals = load_provider_sector_deals(bs, &sectors, provider.id().unwrap()).unwrap();
@@ -228,7 +230,7 @@ pub fn market_list_sectors_deals(
 }
 
 pub fn verifreg_list_claims(v: &dyn VM, provider: ActorID) -> HashMap<ClaimID, Claim> {
-    let st: VerifregState = get_state(v, &VERIFIED_REGISTRY_ACTOR_ADDR).unwrap();
+    let st = get_state_cached::<VerifregState>(v, &VERIFIED_REGISTRY_ACTOR_ADDR).unwrap();
     let bs = &DynBlockstore::wrap(v.blockstore());
     let mut claims = st.load_claims(bs).unwrap();
     let mut found: HashMap<ClaimID, Claim> = HashMap::new();
@@ -260,28 +262,28 @@ pub mod invariant_failure_patterns {
 }
 
 pub fn get_network_stats(vm: &dyn VM) -> NetworkStats {
-    let power_state: PowerState = get_state(vm, &STORAGE_POWER_ACTOR_ADDR).unwrap();
-    let reward_state: RewardState = get_state(vm, &REWARD_ACTOR_ADDR).unwrap();
-    let market_state: MarketState = get_state(vm, &STORAGE_MARKET_ACTOR_ADDR).unwrap();
+    let power_state = get_state_cached::<PowerState>(vm, &STORAGE_POWER_ACTOR_ADDR).unwrap();
+    let reward_state = get_state_cached::<RewardState>(vm, &REWARD_ACTOR_ADDR).unwrap();
+    let market_state = get_state_cached::<MarketState>(vm, &STORAGE_MARKET_ACTOR_ADDR).unwrap();
 
     NetworkStats {
-        total_raw_byte_power: power_state.total_raw_byte_power,
-        total_bytes_committed: power_state.total_bytes_committed,
-        total_quality_adj_power: power_state.total_quality_adj_power,
-        total_qa_bytes_committed: power_state.total_qa_bytes_committed,
-        total_pledge_collateral: power_state.total_pledge_collateral,
-        this_epoch_raw_byte_power: power_state.this_epoch_raw_byte_power,
-        this_epoch_quality_adj_power: power_state.this_epoch_quality_adj_power,
-        this_epoch_pledge_collateral: power_state.this_epoch_pledge_collateral,
+        total_raw_byte_power: power_state.total_raw_byte_power.clone(),
+        total_bytes_committed: power_state.total_bytes_committed.clone(),
+        total_quality_adj_power: power_state.total_quality_adj_power.clone(),
+        total_qa_bytes_committed: power_state.total_qa_bytes_committed.clone(),
+        total_pledge_collateral: power_state.total_pledge_collateral.clone(),
+        this_epoch_raw_byte_power: power_state.this_epoch_raw_byte_power.clone(),
+        this_epoch_quality_adj_power: power_state.this_epoch_quality_adj_power.clone(),
+        this_epoch_pledge_collateral: power_state.this_epoch_pledge_collateral.clone(),
         miner_count: power_state.miner_count,
         miner_above_min_power_count: power_state.miner_above_min_power_count,
-        this_epoch_reward: reward_state.this_epoch_reward,
-        this_epoch_reward_smoothed: reward_state.this_epoch_reward_smoothed,
-        this_epoch_baseline_power: reward_state.this_epoch_baseline_power,
-        total_storage_power_reward: reward_state.total_storage_power_reward,
-        total_client_locked_collateral: market_state.total_client_locked_collateral,
-        total_provider_locked_collateral: market_state.total_provider_locked_collateral,
-        total_client_storage_fee: market_state.total_client_storage_fee,
+        this_epoch_reward: reward_state.this_epoch_reward.clone(),
+        this_epoch_reward_smoothed: reward_state.this_epoch_reward_smoothed.clone(),
+        this_epoch_baseline_power: reward_state.this_epoch_baseline_power.clone(),
+        total_storage_power_reward: reward_state.total_storage_power_reward.clone(),
+        total_client_locked_collateral: market_state.total_client_locked_collateral.clone(),
+        total_provider_locked_collateral: market_state.total_provider_locked_collateral.clone(),
+        total_client_storage_fee: market_state.total_client_storage_fee.clone(),
     }
 }
 
@@ -78,7 +78,7 @@ use fil_actors_runtime::SYSTEM_ACTOR_ADDR;
 use fil_actors_runtime::VERIFIED_REGISTRY_ACTOR_ADDR;
 use fil_actors_runtime::{DATACAP_TOKEN_ACTOR_ID, VERIFIED_REGISTRY_ACTOR_ID};
 use vm_api::trace::{EmittedEvent, ExpectInvocation};
-use vm_api::util::get_state;
+use vm_api::util::get_state_cached;
 use vm_api::util::DynBlockstore;
 use vm_api::util::{apply_code, apply_ok, apply_ok_implicit};
 use vm_api::VM;
@@ -170,7 +170,11 @@ pub fn miner_prove_sector(
     manifests: Vec<PieceActivationManifest>,
 ) {
     let prove_commit_params = ProveCommitSectors3Params {
-        sector_activations: vec![SectorActivationManifest { sector_number, pieces: manifests }],
+        sector_activations: vec![SectorActivationManifest {
+            sector_number,
+            pieces: manifests,
+            client_manifests: vec![],
+        }],
         sector_proofs: vec![vec![].into()],
         aggregate_proof: RawBytes::default(),
         aggregate_proof_type: None,
@@ -338,7 +342,7 @@ pub fn precommit_sectors_v2(
     );
 
     // extract chain state
-    let mstate: MinerState = get_state(v, &mid).unwrap();
+    let mstate = get_state_cached::<MinerState>(v, &mid).unwrap();
     (0..count)
         .map(|i| {
             mstate
@@ -358,7 +362,7 @@ pub fn precommit_meta_data_from_deals(
     seal_proof: RegisteredSealProof,
     include_ids: bool,
 ) -> PrecommitMetadata {
-    let state: MarketState = get_state(v, &STORAGE_MARKET_ACTOR_ADDR).unwrap();
+    let state = get_state_cached::<MarketState>(v, &STORAGE_MARKET_ACTOR_ADDR).unwrap();
     let pieces: Vec<PieceInfo> = deal_ids
         .iter()
         .map(|id: &u64| {
@@ -409,7 +413,7 @@ pub fn prove_commit_sectors(
             Some(prove_commit_aggregate_params),
         );
 
-        let st: MarketState = get_state(v, &STORAGE_MARKET_ACTOR_ADDR).unwrap();
+        let st = get_state_cached::<MarketState>(v, &STORAGE_MARKET_ACTOR_ADDR).unwrap();
         let store = DynBlockstore::wrap(v.blockstore());
         let events: Vec<EmittedEvent> = to_prove
             .iter()
@@ -802,6 +806,7 @@ pub fn verifreg_add_verifier(v: &dyn VM, verifier: &Address, data_cap: StoragePo
         value: TokenAmount::zero(),
         method: VerifregMethod::AddVerifier as u64,
         params: serialize(&add_verifier_params, "verifreg add verifier params").unwrap(),
+        expiration: None,
     };
 
     apply_ok(
@@ -840,7 +845,7 @@ pub fn verifreg_add_client(
     client: &Address,
     allowance: StoragePower,
 ) {
-    let v_st: VerifregState = get_state(v, &VERIFIED_REGISTRY_ACTOR_ADDR).unwrap();
+    let v_st = get_state_cached::<VerifregState>(v, &VERIFIED_REGISTRY_ACTOR_ADDR).unwrap();
     let store = DynBlockstore::wrap(v.blockstore());
 
     let verifier_cap = v_st.get_verifier_cap(&store, verifier).unwrap().unwrap();
@@ -928,14 +933,14 @@ pub fn verifreg_remove_expired_allocations(
     datacap_refund: u64,
     expected_expirations: Vec<AllocationID>,
 ) {
-    let v_st: VerifregState = get_state(v, &VERIFIED_REGISTRY_ACTOR_ADDR).unwrap();
+    let v_st = get_state_cached::<VerifregState>(v, &VERIFIED_REGISTRY_ACTOR_ADDR).unwrap();
     let store = DynBlockstore::wrap(v.blockstore());
     let mut allocs = v_st.load_allocs(&store).unwrap();
     let expected_events: Vec<EmittedEvent> = expected_expirations
         .iter()
-        .map(|id| {
+        .flat_map(|id| {
             let alloc = allocs.get(client.id().unwrap(), *id).unwrap().unwrap();
-            Expect::build_verifreg_allocation_event(
+            Expect::build_verifreg_allocation_events(
                 "allocation-removed",
                 *id,
                 client.id().unwrap(),
@@ -1036,8 +1041,8 @@ pub fn datacap_create_allocations(
         .new_allocations
         .iter()
         .enumerate()
-        .map(|(i, alloc_id)| {
-            Expect::build_verifreg_allocation_event(
+        .flat_map(|(i, alloc_id)| {
+            Expect::build_verifreg_allocation_events(
                 "allocation",
                 *alloc_id,
                 client.id().unwrap(),
@@ -1072,7 +1077,8 @@ pub fn datacap_extend_claim(
     new_term: ChainEpoch,
 ) {
     // read existing claim with claim id from VerifReg state
-    let v_st: fil_actor_verifreg::State = get_state(v, &VERIFIED_REGISTRY_ACTOR_ADDR).unwrap();
+    let v_st =
+        get_state_cached::<fil_actor_verifreg::State>(v, &VERIFIED_REGISTRY_ACTOR_ADDR).unwrap();
     let store = DynBlockstore::wrap(v.blockstore());
     let mut claims = v_st.load_claims(&store).unwrap();
     let mut existing_claim =
@@ -1105,7 +1111,7 @@ pub fn datacap_extend_claim(
     );
 
     let client_id = v.resolve_id_address(client).unwrap().id().unwrap();
-    let claim_updated_event = Expect::build_verifreg_claim_event(
+    let claim_updated_events = Expect::build_verifreg_claim_events(
         "claim-updated",
         claim,
         existing_claim.client,
@@ -1123,7 +1129,7 @@ pub fn datacap_extend_claim(
         token_amount,
         operator_data,
         true, // Burn
-        vec![claim_updated_event],
+        claim_updated_events,
     )
     .matches(v.take_invocations().last().unwrap());
 }
@@ -1236,10 +1242,11 @@ pub fn market_publish_deal(
             extensions: vec![],
         };
 
-        let v_st: fil_actor_verifreg::State = get_state(v, &VERIFIED_REGISTRY_ACTOR_ADDR).unwrap();
-        let alloc_id = v_st.next_allocation_id - 1;
+        let v_st = get_state_cached::<fil_actor_verifreg::State>(v, &VERIFIED_REGISTRY_ACTOR_ADDR)
+            .unwrap();
+        let alloc_id = v_st.next_allocation_id.peek() - 1;
         let alloc_req = alloc_reqs.allocations[0].clone();
-        let alloc_event = Expect::build_verifreg_allocation_event(
+        let alloc_events = Expect::build_verifreg_allocation_events(
             "allocation",
             alloc_id,
             deal_client.id().unwrap(),
@@ -1286,7 +1293,7 @@ pub fn market_publish_deal(
                     })
                     .unwrap(),
                 ),
-                events: Some(vec![alloc_event]),
+                events: Some(alloc_events),
                 ..Default::default()
             }]),
             ..Default::default()
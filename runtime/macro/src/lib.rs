@@ -0,0 +1,91 @@
+use proc_macro::TokenStream;
+use quote::quote;
+use syn::{Data, DeriveInput, Fields, GenericArgument, PathArguments, Type};
+
+/// Derives `fil_actors_runtime::ActorState` for a struct with named fields.
+/// Each field of type `Cid` or `Option<Cid>` is treated as the root of a state collection,
+/// which invariant checks expect to be present in the store.
+#[proc_macro_derive(ActorState)]
+pub fn derive_actor_state(input: TokenStream) -> TokenStream {
+    let input = syn::parse_macro_input!(input as DeriveInput);
+    let name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+
+    let fields = match &input.data {
+        Data::Struct(data) => match &data.fields {
+            Fields::Named(fields) => &fields.named,
+            _ => {
+                return syn::Error::new_spanned(name, "ActorState requires named fields")
+                    .to_compile_error()
+                    .into()
+            }
+        },
+        _ => {
+            return syn::Error::new_spanned(name, "ActorState can only be derived for a struct")
+                .to_compile_error()
+                .into()
+        }
+    };
+
+    let mut field_names = Vec::new();
+    let mut roots = Vec::new();
+    for field in fields {
+        let ident = field.ident.as_ref().expect("named field");
+        let field_name = ident.to_string();
+        if is_cid(&field.ty) {
+            roots.push(quote! { (#field_name, ::core::option::Option::Some(self.#ident)) });
+        } else if option_inner(&field.ty).map_or(false, is_cid) {
+            roots.push(quote! { (#field_name, self.#ident) });
+        }
+        field_names.push(field_name);
+    }
+    let root_count = roots.len();
+
+    quote! {
+        impl #impl_generics ::fil_actors_runtime::ActorState for #name #ty_generics #where_clause {
+            const FIELDS: &'static [&'static str] = &[#(#field_names),*];
+
+            fn field_roots(
+                &self,
+            ) -> ::std::vec::Vec<(&'static str, ::fil_actors_runtime::cid::Cid)> {
+                let roots: [(
+                    &'static str,
+                    ::core::option::Option<::fil_actors_runtime::cid::Cid>,
+                ); #root_count] = [#(#roots),*];
+                roots.into_iter().filter_map(|(field, root)| root.map(|root| (field, root))).collect()
+            }
+        }
+    }
+    .into()
+}
+
+// Whether a type is (syntactically) a CID.
+fn is_cid(ty: &Type) -> bool {
+    match ty {
+        Type::Path(ty) => {
+            ty.qself.is_none()
+                && ty
+                    .path
+                    .segments
+                    .last()
+                    .map_or(false, |s| s.ident == "Cid" && s.arguments.is_empty())
+        }
+        _ => false,
+    }
+}
+
+// The type parameter of an `Option` type.
+fn option_inner(ty: &Type) -> Option<&Type> {
+    let Type::Path(ty) = ty else { return None };
+    let segment = ty.path.segments.last()?;
+    if segment.ident != "Option" {
+        return None;
+    }
+    match &segment.arguments {
+        PathArguments::AngleBracketed(args) if args.args.len() == 1 => match &args.args[0] {
+            GenericArgument::Type(inner) => Some(inner),
+            _ => None,
+        },
+        _ => None,
+    }
+}
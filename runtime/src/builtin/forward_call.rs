@@ -0,0 +1,111 @@
+// Copyright 2019-2022 ChainSafe Systems
+// SPDX-License-Identifier: Apache-2.0, MIT
+
+//! Types for the `ForwardCall` method of the account and multisig actors, which forwards a call
+//! with parameters in any supported codec to another actor. The forwarding actor needs no
+//! knowledge of the callee's parameter types, so wallets can invoke methods introduced later.
+
+use fvm_ipld_encoding::ipld_block::IpldBlock;
+use fvm_ipld_encoding::tuple::*;
+use fvm_ipld_encoding::{strict_bytes, CBOR, DAG_CBOR, IPLD_RAW};
+use fvm_shared::address::Address;
+use fvm_shared::econ::TokenAmount;
+use fvm_shared::MethodNum;
+
+use crate::runtime::Runtime;
+use crate::{actor_error, extract_send_result, ActorError};
+
+/// A call to forward to another actor.
+#[derive(Serialize_tuple, Deserialize_tuple, Clone, Debug, PartialEq, Eq)]
+pub struct ForwardCallParams {
+    pub to: Address,
+    pub method: MethodNum,
+    /// Codec of the parameters: CBOR, DAG-CBOR or raw, or 0 for no parameters.
+    pub codec: u64,
+    #[serde(with = "strict_bytes")]
+    pub params: Vec<u8>,
+    pub value: TokenAmount,
+}
+
+/// The value returned by a forwarded call.
+#[derive(Serialize_tuple, Deserialize_tuple, Clone, Debug, Default, PartialEq, Eq)]
+pub struct ForwardCallReturn {
+    /// Codec of the return data, as returned by the callee, or 0 if it returned nothing.
+    pub codec: u64,
+    #[serde(with = "strict_bytes")]
+    pub data: Vec<u8>,
+}
+
+impl ForwardCallParams {
+    /// Returns the parameters to send, checking their codec and that they are no larger
+    /// than `max_size` bytes.
+    pub fn params_block(&self, max_size: usize) -> Result<Option<IpldBlock>, ActorError> {
+        if self.params.len() > max_size {
+            return Err(actor_error!(illegal_argument;
+                "forwarded params size {} exceeds maximum {}", self.params.len(), max_size));
+        }
+        match self.codec {
+            CBOR | DAG_CBOR | IPLD_RAW => {
+                Ok(Some(IpldBlock { codec: self.codec, data: self.params.clone() }))
+            }
+            0 if self.params.is_empty() => Ok(None),
+            0 => Err(actor_error!(illegal_argument; "forwarded params must be empty with codec 0")),
+            codec => {
+                Err(actor_error!(illegal_argument; "unsupported forwarded params codec {}", codec))
+            }
+        }
+    }
+}
+
+/// Forwards a call from the current actor, which aborts with the callee's exit code and
+/// return data if the call fails.
+pub fn forward_call(
+    rt: &impl Runtime,
+    call: ForwardCallParams,
+) -> Result<ForwardCallReturn, ActorError> {
+    if call.value.is_negative() {
+        return Err(actor_error!(illegal_argument; "negative forwarded value {}", call.value));
+    }
+    let params = call.params_block(rt.policy().forward_call_max_params_size)?;
+    let ret = extract_send_result(rt.send_simple(&call.to, call.method, params, call.value))?;
+    Ok(ret.map_or_else(ForwardCallReturn::default, |block| ForwardCallReturn {
+        codec: block.codec,
+        data: block.data,
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use fvm_ipld_encoding::{CBOR, DAG_CBOR, IPLD_RAW};
+    use fvm_shared::address::Address;
+    use fvm_shared::econ::TokenAmount;
+    use fvm_shared::error::ExitCode;
+
+    use super::ForwardCallParams;
+
+    fn call(codec: u64, params: Vec<u8>) -> ForwardCallParams {
+        ForwardCallParams {
+            to: Address::new_id(1000),
+            method: 2,
+            codec,
+            params,
+            value: TokenAmount::from_atto(0),
+        }
+    }
+
+    #[test]
+    fn params_block_checks_codec_and_size() {
+        for codec in [CBOR, DAG_CBOR, IPLD_RAW] {
+            let block = call(codec, vec![1, 2]).params_block(2).unwrap().unwrap();
+            assert_eq!((codec, vec![1, 2]), (block.codec, block.data));
+        }
+        assert_eq!(None, call(0, vec![]).params_block(0).unwrap());
+
+        for (codec, params) in [(0, vec![1]), (0x70, vec![1]), (DAG_CBOR, vec![1, 2, 3])] {
+            assert_eq!(
+                ExitCode::USR_ILLEGAL_ARGUMENT,
+                call(codec, params).params_block(2).unwrap_err().exit_code()
+            );
+        }
+    }
+}
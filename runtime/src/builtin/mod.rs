@@ -1,11 +1,16 @@
 // Copyright 2019-2022 ChainSafe Systems
 // SPDX-License-Identifier: Apache-2.0, MIT
 
+pub use self::forward_call::{forward_call, ForwardCallParams, ForwardCallReturn};
 pub use self::network::*;
 pub use self::shared::*;
 pub use self::singletons::*;
+pub use self::upgrade::*;
 
+pub mod forward_call;
 pub mod network;
 pub mod reward;
+pub mod sector_content;
 pub mod shared;
 pub mod singletons;
+pub mod upgrade;
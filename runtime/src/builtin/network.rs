@@ -1,6 +1,8 @@
 // Copyright 2019-2022 ChainSafe Systems
 // SPDX-License-Identifier: Apache-2.0, MIT
 
+use fvm_shared::version::NetworkVersion;
+
 /// Assumed epoch duration. If this changes, a large state-migration will need to be run to update
 /// expirations, etc.
 pub const EPOCH_DURATION_SECONDS: i64 = 30;
@@ -12,6 +14,10 @@ pub const EPOCHS_IN_HOUR: i64 = SECONDS_IN_HOUR / EPOCH_DURATION_SECONDS;
 pub const EPOCHS_IN_DAY: i64 = SECONDS_IN_DAY / EPOCH_DURATION_SECONDS;
 pub const EPOCHS_IN_YEAR: i64 = SECONDS_IN_YEAR / EPOCH_DURATION_SECONDS;
 
+/// The network version from which protocol changes introduced in this version of the actors take
+/// effect. At earlier network versions the actors must behave as the previous version did.
+pub const UPGRADE_NETWORK_VERSION: NetworkVersion = NetworkVersion::V25;
+
 /// This is a protocol constant from Filecoin and depends on expected consensus. Here it is used to
 /// determine expected rewards, fault penalties, etc. This will need to be changed if expected
 /// consensus ever changes (and, likely, so will pledge, etc.).
@@ -0,0 +1,162 @@
+// Copyright 2019-2022 ChainSafe Systems
+// SPDX-License-Identifier: Apache-2.0, MIT
+
+//! Types for the `SectorContentChanged` notification, sent by the miner actor to the receivers
+//! of data committed to its sectors (e.g. the built-in market).
+//! They are shared here so that senders and receivers agree on the encoding.
+
+use std::fmt;
+
+use cid::Cid;
+use fvm_ipld_encoding::tuple::*;
+use fvm_ipld_encoding::RawBytes;
+use fvm_shared::clock::ChainEpoch;
+use fvm_shared::piece::PaddedPieceSize;
+use fvm_shared::sector::SectorNumber;
+use serde::de::{self, SeqAccess, Visitor};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+/// Version of the notification parameters envelope written by senders.
+/// Receivers also accept the legacy, unversioned encoding (a bare list of sector changes)
+/// during the transition to versioned notifications.
+pub const SECTOR_CONTENT_CHANGED_VERSION: u64 = 1;
+
+// Notification of change committed to one or more sectors.
+// The relevant state must be already committed so the receiver can observe any impacts
+// at the sending miner actor.
+// Encoded as the envelope [version, sectors].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SectorContentChangedParams {
+    // Distinct sectors with changed content.
+    pub sectors: Vec<SectorChanges>,
+}
+
+// Description of changes to one sector's content.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize_tuple, Deserialize_tuple)]
+pub struct SectorChanges {
+    // Identifier of sector being updated.
+    pub sector: SectorNumber,
+    // Minimum epoch until which the data is committed to the sector.
+    // Note the sector may later be extended without necessarily another notification.
+    pub minimum_commitment_epoch: ChainEpoch,
+    // Information about some pieces added to (or retained in) the sector.
+    // This may be only a subset of sector content.
+    // Inclusion here does not mean the piece was definitely absent previously.
+    // Exclusion here does not mean a piece has been removed since a prior notification.
+    pub added: Vec<PieceChange>,
+}
+
+// Description of a piece of data committed to a sector.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize_tuple, Deserialize_tuple)]
+pub struct PieceChange {
+    pub data: Cid,
+    pub size: PaddedPieceSize,
+    // A receiver-specific identifier.
+    // E.g. an encoded deal ID which the provider claims this piece satisfies.
+    pub payload: RawBytes,
+}
+
+// For each piece in each sector, the notifee returns an exit code and
+// (possibly-empty) result data.
+// The miner actor will pass through results to its caller.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize_tuple, Deserialize_tuple)]
+#[serde(transparent)]
+pub struct SectorContentChangedReturn {
+    // A result for each sector that was notified, in the same order.
+    pub sectors: Vec<SectorReturn>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize_tuple, Deserialize_tuple)]
+#[serde(transparent)]
+pub struct SectorReturn {
+    // A result for each piece for the sector that was notified, in the same order.
+    pub added: Vec<PieceReturn>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize_tuple, Deserialize_tuple)]
+#[serde(transparent)]
+pub struct PieceReturn {
+    // Indicates whether the receiver accepted the notification.
+    // The caller is free to ignore this, but may chose to abort and roll back.
+    pub accepted: bool,
+}
+
+impl Serialize for SectorContentChangedParams {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        (SECTOR_CONTENT_CHANGED_VERSION, &self.sectors).serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for SectorContentChangedParams {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_seq(ParamsVisitor)
+    }
+}
+
+struct ParamsVisitor;
+
+impl<'de> Visitor<'de> for ParamsVisitor {
+    type Value = SectorContentChangedParams;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("a versioned envelope or a list of sector changes")
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+        match seq.next_element::<EnvelopeHead>()? {
+            // The legacy encoding of a notification for no sectors.
+            None => Ok(SectorContentChangedParams { sectors: vec![] }),
+            Some(EnvelopeHead::Version(SECTOR_CONTENT_CHANGED_VERSION)) => {
+                let sectors =
+                    seq.next_element()?.ok_or_else(|| de::Error::invalid_length(1, &self))?;
+                if seq.next_element::<de::IgnoredAny>()?.is_some() {
+                    return Err(de::Error::invalid_length(3, &self));
+                }
+                Ok(SectorContentChangedParams { sectors })
+            }
+            Some(EnvelopeHead::Version(v)) => {
+                Err(de::Error::custom(format!("unsupported sector content changed version {}", v)))
+            }
+            // The legacy encoding, a bare list of sector changes.
+            Some(EnvelopeHead::Legacy(first)) => {
+                let mut sectors = vec![first];
+                while let Some(sector) = seq.next_element()? {
+                    sectors.push(sector);
+                }
+                Ok(SectorContentChangedParams { sectors })
+            }
+        }
+    }
+}
+
+// The first element of encoded parameters, which distinguishes the versioned envelope
+// (beginning with an integer) from the legacy encoding (beginning with a sector's changes).
+enum EnvelopeHead {
+    Version(u64),
+    Legacy(SectorChanges),
+}
+
+impl<'de> Deserialize<'de> for EnvelopeHead {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct HeadVisitor;
+
+        impl<'de> Visitor<'de> for HeadVisitor {
+            type Value = EnvelopeHead;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                f.write_str("a version number or a sector's changes")
+            }
+
+            fn visit_u64<E: de::Error>(self, v: u64) -> Result<Self::Value, E> {
+                Ok(EnvelopeHead::Version(v))
+            }
+
+            fn visit_seq<A: SeqAccess<'de>>(self, seq: A) -> Result<Self::Value, A::Error> {
+                SectorChanges::deserialize(de::value::SeqAccessDeserializer::new(seq))
+                    .map(EnvelopeHead::Legacy)
+            }
+        }
+
+        deserializer.deserialize_any(HeadVisitor)
+    }
+}
@@ -0,0 +1,78 @@
+// SPDX-License-Identifier: Apache-2.0, MIT
+
+use std::iter;
+
+use cid::Cid;
+use fvm_ipld_encoding::ipld_block::IpldBlock;
+use fvm_ipld_encoding::tuple::*;
+use fvm_ipld_encoding::{CborStore, RawBytes};
+use fvm_shared::error::ExitCode;
+use fvm_shared::MethodNum;
+use multihash_codetable::Code;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+use crate::runtime::{ActorCode, Runtime};
+use crate::{actor_error, deserialize_block, ActorError, AsActorError, SYSTEM_ACTOR_ADDR};
+
+/// Method number of the upgrade hook.
+/// Every built-in actor handles this method through its dispatch macro, which restricts it
+/// to the system actor and forwards the parameters to [`ActorCode::on_upgrade`].
+pub const ON_UPGRADE_METHOD: MethodNum = frc42_dispatch::method_hash!("OnUpgrade");
+
+/// Parameters to the upgrade hook, invoked after an actor's code has been replaced.
+#[derive(Serialize_tuple, Deserialize_tuple, Clone, Debug, PartialEq, Eq)]
+pub struct OnUpgradeParams {
+    /// The actor's state root before the upgrade.
+    pub old_state: Cid,
+    /// Upgrade-specific parameters, interpreted by the new actor code.
+    pub params: RawBytes,
+}
+
+/// Validates that the system actor is the caller, decodes the parameters and invokes the
+/// actor's upgrade hook.
+#[doc(hidden)]
+pub fn dispatch_on_upgrade<A, RT>(
+    rt: &RT,
+    args: Option<IpldBlock>,
+) -> Result<Option<IpldBlock>, ActorError>
+where
+    A: ActorCode,
+    RT: Runtime,
+    RT::Blockstore: Clone,
+{
+    rt.validate_immediate_caller_is(iter::once(&SYSTEM_ACTOR_ADDR))?;
+    let params: OnUpgradeParams = deserialize_block(args)?;
+    A::on_upgrade(rt, params)?;
+    Ok(None)
+}
+
+/// Migrates an actor's state from the schema `Old` it was stored with before an upgrade to the
+/// current schema `New`, for use by [`ActorCode::on_upgrade`] implementations.
+/// State which already decodes as `New` is left unchanged, so the hook may safely run again.
+pub fn migrate_state<RT, Old, New, F>(
+    rt: &RT,
+    old_state: &Cid,
+    migrate: F,
+) -> Result<(), ActorError>
+where
+    RT: Runtime,
+    Old: DeserializeOwned,
+    New: Serialize + DeserializeOwned,
+    F: FnOnce(&RT, Old) -> Result<New, ActorError>,
+{
+    if matches!(rt.store().get_cbor::<New>(old_state), Ok(Some(_))) {
+        return Ok(());
+    }
+    let old: Old = rt
+        .store()
+        .get_cbor(old_state)
+        .context_code(ExitCode::USR_ILLEGAL_STATE, "failed to decode state to migrate")?
+        .ok_or_else(|| actor_error!(illegal_state; "state to migrate {} not found", old_state))?;
+    let new = migrate(rt, old)?;
+    let root = rt
+        .store()
+        .put_cbor(&new, Code::Blake2b256)
+        .context_code(ExitCode::USR_ILLEGAL_STATE, "failed to write migrated state")?;
+    rt.set_state_root(&root)
+}
@@ -59,6 +59,9 @@ macro_rules! actor_dispatch {
             RT::Blockstore: Clone,
         {
             $crate::builtin::shared::restrict_internal_api(rt, method)?;
+            if method == $crate::builtin::upgrade::ON_UPGRADE_METHOD {
+                return $crate::builtin::upgrade::dispatch_on_upgrade::<Self, RT>(rt, args);
+            }
             match <Self::Methods as num_traits::FromPrimitive>::from_u64(method) {
                 $($(#[$m])*
                   $crate::actor_dispatch!(@pattern $($method)|*) =>
@@ -93,6 +96,9 @@ macro_rules! actor_dispatch_unrestricted {
             RT: $crate::runtime::Runtime,
             RT::Blockstore: Clone,
         {
+            if method == $crate::builtin::upgrade::ON_UPGRADE_METHOD {
+                return $crate::builtin::upgrade::dispatch_on_upgrade::<Self, RT>(rt, args);
+            }
             match <Self::Methods as num_traits::FromPrimitive>::from_u64(method) {
                 $($(#[$m])*
                   $crate::actor_dispatch!(@pattern $($method)|*) =>
@@ -13,7 +13,8 @@ use serde::Serialize;
 use unsigned_varint::decode::Error as UVarintError;
 
 pub use dispatch::{dispatch, dispatch_default, WithCodec};
-pub use {fvm_ipld_amt, fvm_ipld_hamt};
+pub use fil_actors_runtime_macro::ActorState;
+pub use {cid, fvm_ipld_amt, fvm_ipld_hamt};
 
 #[cfg(feature = "fil-actor")]
 use crate::runtime::hash_algorithm::FvmHashSha256;
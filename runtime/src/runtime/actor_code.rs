@@ -5,7 +5,7 @@ use fvm_ipld_blockstore::Blockstore;
 use fvm_ipld_encoding::ipld_block::IpldBlock;
 use fvm_shared::MethodNum;
 
-use crate::{ActorError, Runtime};
+use crate::{ActorError, OnUpgradeParams, Runtime};
 
 /// Interface for invoking methods on an Actor
 pub trait ActorCode {
@@ -25,4 +25,12 @@ pub trait ActorCode {
         // https://github.com/filecoin-project/builtin-actors/issues/133
         RT: Runtime,
         RT::Blockstore: Blockstore + Clone;
+
+    /// Performs lazy fixups of the actor's state after its code has been upgraded.
+    /// Invoked only by the system actor, with the state root from before the upgrade.
+    /// Every actor must implement this, even if its state needs no fixups.
+    fn on_upgrade<RT>(rt: &RT, params: OnUpgradeParams) -> Result<(), ActorError>
+    where
+        RT: Runtime,
+        RT::Blockstore: Blockstore + Clone;
 }
@@ -13,7 +13,7 @@ use fvm_shared::clock::ChainEpoch;
 use fvm_shared::consensus::ConsensusFault;
 use fvm_shared::crypto::hash::SupportedHashes;
 use fvm_shared::crypto::signature::{
-    Signature, SECP_PUB_LEN, SECP_SIG_LEN, SECP_SIG_MESSAGE_HASH_SIZE,
+    Signature, BLS_PUB_LEN, BLS_SIG_LEN, SECP_PUB_LEN, SECP_SIG_LEN, SECP_SIG_MESSAGE_HASH_SIZE,
 };
 use fvm_shared::econ::TokenAmount;
 use fvm_shared::error::{ErrorNumber, ExitCode};
@@ -39,7 +39,7 @@ use crate::runtime::actor_blockstore::ActorBlockstore;
 use crate::runtime::builtins::Type;
 use crate::runtime::randomness::draw_randomness;
 use crate::runtime::{
-    ActorCode, DomainSeparationTag, MessageInfo, Policy, Primitives, RuntimePolicy,
+    ActorCode, DomainSeparationTag, MessageInfo, Policy, Primitives, RuntimePolicy, StorageStatus,
 };
 use crate::{actor_error, ActorError, AsActorError, Runtime, SendError};
 
@@ -396,6 +396,23 @@ where
             .context_code(ExitCode::USR_ASSERTION_FAILED, "failed to emit event")
     }
 
+    fn trace_step(&self, pc: u32, opcode: u8, stack_top: Option<[u8; 32]>) {
+        // Logged only when debugging is enabled in the VM, in which case the gas available
+        // reflects the charges for previous log syscalls too.
+        log::trace!(
+            target: "trace",
+            "pc={} op={:#04x} gas={} top={:02x?}",
+            pc,
+            opcode,
+            fvm::gas::available(),
+            stack_top
+        );
+    }
+
+    fn trace_storage_status(&self, status: StorageStatus) {
+        log::trace!(target: "trace", "storage={:?}", status);
+    }
+
     fn read_only(&self) -> bool {
         fvm::vm::read_only()
     }
@@ -417,6 +434,18 @@ where
         }
     }
 
+    fn verify_bls_aggregate(
+        &self,
+        signature: &[u8; BLS_SIG_LEN],
+        pub_keys: &[[u8; BLS_PUB_LEN]],
+        plaintexts: &[&[u8]],
+    ) -> Result<(), Error> {
+        match fvm::crypto::verify_bls_aggregate(signature, pub_keys, plaintexts) {
+            Ok(true) => Ok(()),
+            Ok(false) | Err(_) => Err(Error::msg("invalid aggregate signature")),
+        }
+    }
+
     fn hash_blake2b(&self, data: &[u8]) -> [u8; 32] {
         fvm::crypto::hash_blake2b(data)
     }
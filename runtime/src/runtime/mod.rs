@@ -39,6 +39,7 @@ use fvm_shared::chainid::ChainID;
 use fvm_shared::event::ActorEvent;
 use fvm_shared::sys::SendFlags;
 use multihash_codetable::Code;
+pub use vm_api::trace::StorageStatus;
 pub use vm_api::Primitives;
 
 /// Runtime is the VM's internal runtime object.
@@ -254,6 +255,15 @@ pub trait Runtime: Primitives + RuntimePolicy {
     /// Emits an event denoting that something externally noteworthy has ocurred.
     fn emit_event(&self, event: &ActorEvent) -> Result<(), ActorError>;
 
+    /// Records a step of an interpreter's execution for debugging: the offset of the instruction,
+    /// its opcode, and the value at the top of the stack. Only actors built with tracing enabled
+    /// record steps, and the VM may discard them.
+    fn trace_step(&self, _pc: u32, _opcode: u8, _stack_top: Option<[u8; 32]>) {}
+
+    /// Records the classification of a storage write made by the last traced step.
+    /// Like steps, this is only recorded by actors built with tracing enabled.
+    fn trace_storage_status(&self, _status: StorageStatus) {}
+
     /// Returns true if the call is read_only.
     /// All state updates, including actor creation and balance transfers, are rejected in read_only calls.
     fn read_only(&self) -> bool;
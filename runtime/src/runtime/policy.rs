@@ -1,4 +1,5 @@
 use fvm_shared::clock::ChainEpoch;
+use fvm_shared::econ::TokenAmount;
 use fvm_shared::sector::{RegisteredPoStProof, RegisteredSealProof, StoragePower};
 use num_traits::FromPrimitive;
 use serde::{Deserialize, Serialize};
@@ -102,6 +103,11 @@ pub struct Policy {
     /// The maximum age of a fault before the sector is terminated.
     pub fault_max_age: ChainEpoch,
 
+    /// Numerator and denominator of the fraction of the continued fault fee charged for power
+    /// declared faulty as planned downtime, at the end of the deadline for which it was declared.
+    pub planned_downtime_fault_fee_num: i64,
+    pub planned_downtime_fault_fee_denom: i64,
+
     /// Staging period for a miner worker key change.
     pub worker_key_change_delay: ChainEpoch,
 
@@ -121,9 +127,21 @@ pub struct Policy {
     /// for permissioned actor methods and winning block elections.
     pub consensus_fault_ineligibility_duration: ChainEpoch,
 
+    /// Number of epochs after a consensus fault within which the miner may report the fault
+    /// itself, for a reduced penalty.
+    pub consensus_fault_self_report_window: ChainEpoch,
+
     /// The maximum number of new sectors that may be staged by a miner during a single proving period.
     pub new_sectors_per_period_max: usize,
 
+    /// Maximum number of sectors recorded in a miner's piece index for any one piece.
+    /// Zero disables the piece index.
+    pub piece_index_max_sectors: u64,
+
+    /// Maximum number of epochs by which the activation of a sector proven ahead of time
+    /// ("sealed ahead") may be deferred.
+    pub max_sealed_ahead_delay: ChainEpoch,
+
     /// Epochs after which chain state is final with overwhelming probability
     /// (hence the likelihood of two fork of this size is negligible).
     pub chain_finality: ChainEpoch,
@@ -146,6 +164,9 @@ pub struct Policy {
     pub maximum_verified_allocation_expiration: i64,
     // Period of time at the end of a sector's life during which claims can be dropped
     pub end_of_life_claim_drop_period: ChainEpoch,
+    /// Delay after a new verified registry root key is proposed before it may accept the role,
+    /// during which the current root key may cancel the proposal.
+    pub verifreg_root_key_rotation_delay: ChainEpoch,
 
     //  --- market policy ---
     /// The number of blocks between payouts for deals
@@ -163,9 +184,65 @@ pub struct Policy {
     /// allocation's maximum term.
     pub market_default_allocation_term_buffer: i64,
 
+    /// Minimum duration of a deal (epochs).
+    pub min_deal_duration: ChainEpoch,
+    /// Maximum duration of a deal (epochs).
+    pub max_deal_duration: ChainEpoch,
+    /// Maximum price per epoch of a deal, and maximum client and provider collateral.
+    pub max_deal_token_amount: TokenAmount,
+    /// Maximum number of deals settled synchronously when a provider's sectors are terminated.
+    /// Deals in further terminated sectors are only marked, and settled on their next settlement.
+    pub market_max_synchronous_deal_terminations: u64,
+
     // --- power ---
     /// Minimum miner consensus power
     pub minimum_consensus_power: StoragePower,
+    /// Number of epochs between checkpoints of each miner's claimed power.
+    pub claim_history_interval: ChainEpoch,
+    /// Number of checkpoints of claimed power retained for each miner.
+    pub claim_history_length: u64,
+
+    // --- evm ---
+    /// Maximum size in bytes to which the memory of a single EVM contract invocation may grow.
+    pub evm_max_memory_size: usize,
+    /// Whether EVM contracts consult the call filter registered with the system actor, if any,
+    /// before executing a call. Enabled for permissioned deployments.
+    pub evm_call_filter_enabled: bool,
+    /// Maximum size in bytes of the data attached to a single EVM log.
+    pub evm_max_log_data_size: u64,
+    /// Maximum number of logs a single EVM contract invocation may emit.
+    pub evm_max_logs_per_invocation: u64,
+    /// Bit width of the storage KAMT of newly created EVM contracts. Existing contracts keep the
+    /// bit width they were created with.
+    pub evm_storage_bit_width: u32,
+    /// FVM execution gas per unit of EVM gas, by which the GAS opcode and the gas argument to
+    /// calls are translated between the FVM and the EVM.
+    pub evm_fvm_gas_per_evm_gas: u64,
+    /// FVM execution gas per unit of EVM gas charged by the precompiles, which are priced in EVM
+    /// gas as on Ethereum.
+    pub evm_precompile_fvm_gas_per_evm_gas: u64,
+
+    // --- account & multisig ---
+    /// Maximum size in bytes of the parameters of a call forwarded by an account or multisig.
+    pub forward_call_max_params_size: usize,
+    /// Maximum number of delegates an account may register.
+    pub account_max_delegates: u64,
+    /// Maximum number of method allowances an account may grant a single delegate.
+    pub account_max_delegate_allowances: u64,
+
+    // --- cron ---
+    /// Number of consecutive failed invocations after which a cron entry is skipped,
+    /// until it is re-enabled.
+    pub cron_entry_max_consecutive_failures: u64,
+    /// Number of miner cron callbacks the power actor may invoke in one epoch before it reports
+    /// a cron budget overrun.
+    pub power_cron_entries_budget: u64,
+    /// Number of deal operations the market actor may process in one epoch before it reports
+    /// a cron budget overrun.
+    pub market_cron_entries_budget: u64,
+    /// Number of sectors a miner may expire or terminate in one cron callback before it reports
+    /// a cron budget overrun.
+    pub miner_cron_entries_budget: u64,
 }
 
 impl Default for Policy {
@@ -202,13 +279,19 @@ impl Default for Policy {
             wpost_challenge_lookback: policy_constants::WPOST_CHALLENGE_LOOKBACK,
             fault_declaration_cutoff: policy_constants::FAULT_DECLARATION_CUTOFF,
             fault_max_age: policy_constants::FAULT_MAX_AGE,
+            planned_downtime_fault_fee_num: policy_constants::PLANNED_DOWNTIME_FAULT_FEE_NUM,
+            planned_downtime_fault_fee_denom: policy_constants::PLANNED_DOWNTIME_FAULT_FEE_DENOM,
             worker_key_change_delay: policy_constants::WORKER_KEY_CHANGE_DELAY,
             min_sector_expiration: policy_constants::MIN_SECTOR_EXPIRATION,
             max_sector_expiration_extension: policy_constants::MAX_SECTOR_EXPIRATION_EXTENSION,
             deal_limit_denominator: policy_constants::DEAL_LIMIT_DENOMINATOR,
             consensus_fault_ineligibility_duration:
                 policy_constants::CONSENSUS_FAULT_INELIGIBILITY_DURATION,
+            consensus_fault_self_report_window:
+                policy_constants::CONSENSUS_FAULT_SELF_REPORT_WINDOW,
             new_sectors_per_period_max: policy_constants::NEW_SECTORS_PER_PERIOD_MAX,
+            piece_index_max_sectors: policy_constants::PIECE_INDEX_MAX_SECTORS,
+            max_sealed_ahead_delay: policy_constants::MAX_SEALED_AHEAD_DELAY,
             chain_finality: policy_constants::CHAIN_FINALITY,
 
             valid_post_proof_type: ProofSet::default_post_proofs(),
@@ -222,6 +305,7 @@ impl Default for Policy {
             maximum_verified_allocation_expiration:
                 policy_constants::MAXIMUM_VERIFIED_ALLOCATION_EXPIRATION,
             end_of_life_claim_drop_period: policy_constants::END_OF_LIFE_CLAIM_DROP_PERIOD,
+            verifreg_root_key_rotation_delay: policy_constants::VERIFREG_ROOT_KEY_ROTATION_DELAY,
             deal_updates_interval: policy_constants::DEAL_UPDATES_INTERVAL,
             prov_collateral_percent_supply_num:
                 policy_constants::PROV_COLLATERAL_PERCENT_SUPPLY_NUM,
@@ -229,8 +313,36 @@ impl Default for Policy {
                 policy_constants::PROV_COLLATERAL_PERCENT_SUPPLY_DENOM,
             market_default_allocation_term_buffer:
                 policy_constants::MARKET_DEFAULT_ALLOCATION_TERM_BUFFER,
+            min_deal_duration: policy_constants::MIN_DEAL_DURATION,
+            max_deal_duration: policy_constants::MAX_DEAL_DURATION,
+            max_deal_token_amount: TokenAmount::from_whole(
+                policy_constants::MAX_DEAL_TOKEN_AMOUNT_WHOLE,
+            ),
+            market_max_synchronous_deal_terminations:
+                policy_constants::MARKET_MAX_SYNCHRONOUS_DEAL_TERMINATIONS,
 
             minimum_consensus_power: StoragePower::from(policy_constants::MINIMUM_CONSENSUS_POWER),
+            claim_history_interval: policy_constants::CLAIM_HISTORY_INTERVAL,
+            claim_history_length: policy_constants::CLAIM_HISTORY_LENGTH,
+
+            evm_max_memory_size: policy_constants::EVM_MAX_MEMORY_SIZE,
+            evm_call_filter_enabled: policy_constants::EVM_CALL_FILTER_ENABLED,
+            evm_max_log_data_size: policy_constants::EVM_MAX_LOG_DATA_SIZE,
+            evm_max_logs_per_invocation: policy_constants::EVM_MAX_LOGS_PER_INVOCATION,
+            evm_storage_bit_width: policy_constants::EVM_STORAGE_BIT_WIDTH,
+            evm_fvm_gas_per_evm_gas: policy_constants::EVM_FVM_GAS_PER_EVM_GAS,
+            evm_precompile_fvm_gas_per_evm_gas:
+                policy_constants::EVM_PRECOMPILE_FVM_GAS_PER_EVM_GAS,
+
+            forward_call_max_params_size: policy_constants::FORWARD_CALL_MAX_PARAMS_SIZE,
+            account_max_delegates: policy_constants::ACCOUNT_MAX_DELEGATES,
+            account_max_delegate_allowances: policy_constants::ACCOUNT_MAX_DELEGATE_ALLOWANCES,
+
+            cron_entry_max_consecutive_failures:
+                policy_constants::CRON_ENTRY_MAX_CONSECUTIVE_FAILURES,
+            power_cron_entries_budget: policy_constants::POWER_CRON_ENTRIES_BUDGET,
+            market_cron_entries_budget: policy_constants::MARKET_CRON_ENTRIES_BUDGET,
+            miner_cron_entries_budget: policy_constants::MINER_CRON_ENTRIES_BUDGET,
         }
     }
 }
@@ -328,6 +440,9 @@ pub mod policy_constants {
 
     pub const FAULT_MAX_AGE: ChainEpoch = WPOST_PROVING_PERIOD * 42;
 
+    pub const PLANNED_DOWNTIME_FAULT_FEE_NUM: i64 = 1;
+    pub const PLANNED_DOWNTIME_FAULT_FEE_DENOM: i64 = 2;
+
     // Finality is a harsh delay for a miner who has lost their worker key, as the miner will miss Window PoSts until
     // it can be changed. It's the only safe value, though. We may implement a mitigation mechanism such as a second
     // key or allowing the owner account to submit PoSts while a key change is pending.
@@ -342,8 +457,14 @@ pub mod policy_constants {
 
     pub const CONSENSUS_FAULT_INELIGIBILITY_DURATION: ChainEpoch = CHAIN_FINALITY;
 
+    pub const CONSENSUS_FAULT_SELF_REPORT_WINDOW: ChainEpoch = EPOCHS_IN_HOUR;
+
     pub const NEW_SECTORS_PER_PERIOD_MAX: usize = 128 << 10;
 
+    pub const PIECE_INDEX_MAX_SECTORS: u64 = 16;
+
+    pub const MAX_SEALED_AHEAD_DELAY: ChainEpoch = 90 * EPOCHS_IN_DAY;
+
     /// This is a conservative value that is chosen via simulations of all known attacks.
     pub const CHAIN_FINALITY: ChainEpoch = 900;
 
@@ -355,6 +476,7 @@ pub mod policy_constants {
     pub const MAXIMUM_VERIFIED_ALLOCATION_TERM: i64 = 5 * EPOCHS_IN_YEAR;
     pub const MAXIMUM_VERIFIED_ALLOCATION_EXPIRATION: i64 = 60 * EPOCHS_IN_DAY;
     pub const END_OF_LIFE_CLAIM_DROP_PERIOD: ChainEpoch = 30 * EPOCHS_IN_DAY;
+    pub const VERIFREG_ROOT_KEY_ROTATION_DELAY: ChainEpoch = 14 * EPOCHS_IN_DAY;
 
     pub const DEAL_UPDATES_INTERVAL: i64 = 30 * EPOCHS_IN_DAY;
 
@@ -367,6 +489,16 @@ pub mod policy_constants {
 
     pub const MARKET_DEFAULT_ALLOCATION_TERM_BUFFER: i64 = 90 * EPOCHS_IN_DAY;
 
+    pub const MARKET_MAX_SYNCHRONOUS_DEAL_TERMINATIONS: u64 = 256;
+
+    pub const MIN_DEAL_DURATION: ChainEpoch = 180 * EPOCHS_IN_DAY;
+
+    pub const MAX_DEAL_DURATION: ChainEpoch = 1278 * EPOCHS_IN_DAY;
+
+    /// Total (assumed) Filecoin available to the network in whole FIL, used only to bound
+    /// deal price and collateral.
+    pub const MAX_DEAL_TOKEN_AMOUNT_WHOLE: i64 = 2_000_000_000;
+
     #[cfg(feature = "min-power-2k")]
     pub const MINIMUM_CONSENSUS_POWER: i64 = 2 << 10;
     #[cfg(feature = "min-power-2g")]
@@ -379,6 +511,46 @@ pub mod policy_constants {
         feature = "min-power-32g"
     )))]
     pub const MINIMUM_CONSENSUS_POWER: i64 = 10 << 40;
+
+    pub const CLAIM_HISTORY_INTERVAL: ChainEpoch = EPOCHS_IN_DAY;
+
+    pub const CLAIM_HISTORY_LENGTH: u64 = 30;
+
+    pub const EVM_MAX_MEMORY_SIZE: usize = 32 << 20;
+    #[cfg(not(feature = "evm-call-filter"))]
+    pub const EVM_CALL_FILTER_ENABLED: bool = false;
+    #[cfg(feature = "evm-call-filter")]
+    pub const EVM_CALL_FILTER_ENABLED: bool = true;
+
+    pub const EVM_MAX_LOG_DATA_SIZE: u64 = 8 << 10;
+
+    pub const EVM_MAX_LOGS_PER_INVOCATION: u64 = 256;
+
+    pub const EVM_STORAGE_BIT_WIDTH: u32 = 5;
+
+    /// The gas limit of an Ethereum transaction sent to Filecoin is an FVM gas limit, so a
+    /// contract's view of its gas must be in FVM units for gas-limited subcalls to behave as
+    /// the sender estimated. A contract reading GAS and forwarding it to CALL then passes on
+    /// all but a 64th of the gas actually remaining, as on Ethereum.
+    pub const EVM_FVM_GAS_PER_EVM_GAS: u64 = 1;
+
+    /// Ethereum prices its precompiles at roughly 30M gas per second of computation, while FVM
+    /// execution gas is calibrated at 10 gas per nanosecond.
+    pub const EVM_PRECOMPILE_FVM_GAS_PER_EVM_GAS: u64 = 333;
+
+    pub const FORWARD_CALL_MAX_PARAMS_SIZE: usize = 32 << 10;
+
+    pub const ACCOUNT_MAX_DELEGATES: u64 = 256;
+
+    pub const ACCOUNT_MAX_DELEGATE_ALLOWANCES: u64 = 32;
+
+    pub const CRON_ENTRY_MAX_CONSECUTIVE_FAILURES: u64 = 100;
+
+    pub const POWER_CRON_ENTRIES_BUDGET: u64 = 2_000;
+
+    pub const MARKET_CRON_ENTRIES_BUDGET: u64 = 10_000;
+
+    pub const MINER_CRON_ENTRIES_BUDGET: u64 = 25_000;
 }
 
 /// A set indicating which proofs are considered valid, optimised for lookup of a small number of
@@ -497,4 +669,12 @@ impl ProofSet {
         let index: i64 = proof.into();
         self.0[index as usize] = true;
     }
+
+    /// Removes the requested proof type from the set of valid proofs
+    pub fn remove<P: Into<i64>>(&mut self, proof: P) {
+        let index: i64 = proof.into();
+        if let Some(valid) = self.0.get_mut(index as usize) {
+            *valid = false;
+        }
+    }
 }
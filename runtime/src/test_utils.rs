@@ -18,7 +18,7 @@ use fvm_shared::commcid::{FIL_COMMITMENT_SEALED, FIL_COMMITMENT_UNSEALED};
 use fvm_shared::consensus::ConsensusFault;
 use fvm_shared::crypto::hash::SupportedHashes;
 use fvm_shared::crypto::signature::{
-    Signature, SECP_PUB_LEN, SECP_SIG_LEN, SECP_SIG_MESSAGE_HASH_SIZE,
+    Signature, BLS_PUB_LEN, BLS_SIG_LEN, SECP_PUB_LEN, SECP_SIG_LEN, SECP_SIG_MESSAGE_HASH_SIZE,
 };
 use fvm_shared::econ::TokenAmount;
 use fvm_shared::error::{ErrorNumber, ExitCode};
@@ -38,10 +38,13 @@ use crate::runtime::{
     ActorCode, DomainSeparationTag, MessageInfo, Policy, Primitives, Runtime, RuntimePolicy,
     EMPTY_ARR_CID,
 };
-use crate::{actor_error, ActorError, SendError};
+use crate::{
+    actor_error, ActorError, OnUpgradeParams, SendError, ON_UPGRADE_METHOD, SYSTEM_ACTOR_ADDR,
+};
 use libsecp256k1::{recover, Message, RecoveryId, Signature as EcsdaSignature};
 use rand::prelude::*;
 use serde::Serialize;
+use vm_api::trace::{StorageStatus, TraceStep};
 use vm_api::MockPrimitives;
 
 use crate::test_blockstores::MemoryBlockstore;
@@ -181,6 +184,8 @@ pub struct MockRuntime {
     pub actor_balances: HashMap<ActorID, TokenAmount>,
     pub tipset_timestamp: u64,
     pub tipset_cids: Vec<Cid>,
+    // Interpreter steps traced since tracing started, if tracing
+    pub trace: RefCell<Option<Vec<TraceStep>>>,
 }
 
 #[derive(Default)]
@@ -193,6 +198,7 @@ pub struct Expectations {
     pub expect_create_actor: Option<ExpectCreateActor>,
     pub expect_delete_actor: bool,
     pub expect_verify_sigs: VecDeque<ExpectedVerifySig>,
+    pub expect_verify_bls_aggregates: VecDeque<ExpectVerifyBlsAggregate>,
     pub expect_verify_post: Option<ExpectVerifyPoSt>,
     pub expect_compute_unsealed_sector_cid: VecDeque<ExpectComputeUnsealedSectorCid>,
     pub expect_verify_consensus_fault: Option<ExpectVerifyConsensusFault>,
@@ -259,6 +265,11 @@ impl Expectations {
             "expect_verify_sigs: {:?}, not received",
             this.expect_verify_sigs
         );
+        assert!(
+            this.expect_verify_bls_aggregates.is_empty(),
+            "expect_verify_bls_aggregates: {:?}, not received",
+            this.expect_verify_bls_aggregates
+        );
         assert!(
             this.expect_verify_post.is_none(),
             "expect_verify_post {:?}, not received",
@@ -360,6 +371,7 @@ impl MockRuntime {
             actor_balances: Default::default(),
             tipset_timestamp: Default::default(),
             tipset_cids: Default::default(),
+            trace: Default::default(),
         }
     }
 }
@@ -394,6 +406,14 @@ pub struct ExpectedVerifySig {
     pub result: Result<(), anyhow::Error>,
 }
 
+#[derive(Debug)]
+pub struct ExpectVerifyBlsAggregate {
+    pub sig: [u8; BLS_SIG_LEN],
+    pub pub_keys: Vec<[u8; BLS_PUB_LEN]>,
+    pub plaintexts: Vec<Vec<u8>>,
+    pub result: Result<(), anyhow::Error>,
+}
+
 #[derive(Clone, Debug)]
 pub struct ExpectVerifyPoSt {
     post: WindowPoStVerifyInfo,
@@ -571,6 +591,23 @@ impl MockRuntime {
         res
     }
 
+    /// Upgrades the receiver from its current state root as the system actor does, then upgrades
+    /// again from the resulting root, checking that the repeated upgrade leaves the state
+    /// unchanged. Returns the upgraded state root.
+    pub fn upgrade<A: ActorCode>(&self) -> Cid {
+        let upgrade = |old_state: Cid| {
+            self.set_caller(*SYSTEM_ACTOR_CODE_ID, SYSTEM_ACTOR_ADDR);
+            self.expect_validate_caller_addr(vec![SYSTEM_ACTOR_ADDR]);
+            let params = OnUpgradeParams { old_state, params: Default::default() };
+            self.call::<A>(ON_UPGRADE_METHOD, IpldBlock::serialize_cbor(&params).unwrap()).unwrap();
+            self.verify();
+            self.get_state_root().unwrap()
+        };
+        let upgraded = upgrade(self.get_state_root().unwrap());
+        assert_eq!(upgraded, upgrade(upgraded), "upgrading the upgraded state changed it");
+        upgraded
+    }
+
     /// Verifies that all mock expectations have been met (and resets the expectations).
     pub fn verify(&self) {
         self.expectations.borrow_mut().verify()
@@ -594,6 +631,11 @@ impl MockRuntime {
         self.expectations.borrow_mut().expect_verify_sigs.push_back(exp);
     }
 
+    #[allow(dead_code)]
+    pub fn expect_verify_bls_aggregate(&self, exp: ExpectVerifyBlsAggregate) {
+        self.expectations.borrow_mut().expect_verify_bls_aggregates.push_back(exp);
+    }
+
     #[allow(dead_code)]
     pub fn expect_verify_consensus_fault(
         &self,
@@ -736,6 +778,17 @@ impl MockRuntime {
         epoch
     }
 
+    /// Sets the tipset timestamp, which like the real chain's may never decrease.
+    pub fn set_tipset_timestamp(&mut self, timestamp: u64) {
+        assert!(
+            timestamp >= self.tipset_timestamp,
+            "tipset timestamp may not decrease from {} to {}",
+            self.tipset_timestamp,
+            timestamp
+        );
+        self.tipset_timestamp = timestamp;
+    }
+
     pub fn expect_get_randomness_from_tickets(
         &self,
         tag: DomainSeparationTag,
@@ -812,6 +865,18 @@ impl MockRuntime {
         self.expectations.borrow_mut().expect_emitted_events.push_back(event)
     }
 
+    /// Starts tracing the interpreter steps of actors built with tracing enabled,
+    /// discarding any previous trace.
+    pub fn start_trace(&self) {
+        self.trace.replace(Some(vec![]));
+    }
+
+    /// Stops tracing and returns the steps traced since tracing started.
+    /// Gas isn't metered, so each step reports the gas limit as available.
+    pub fn take_trace(&self) -> Vec<TraceStep> {
+        self.trace.take().expect("runtime is not tracing")
+    }
+
     ///// Private helpers /////
 
     fn require_in_call(&self) {
@@ -1338,6 +1403,25 @@ impl Runtime for MockRuntime {
         self.chain_id
     }
 
+    fn trace_step(&self, pc: u32, opcode: u8, stack_top: Option<[u8; 32]>) {
+        if let Some(trace) = self.trace.borrow_mut().as_mut() {
+            trace.push(TraceStep {
+                actor: self.receiver.id().unwrap(),
+                pc,
+                opcode,
+                stack_top,
+                gas_available: self.gas_limit,
+                storage_status: None,
+            });
+        }
+    }
+
+    fn trace_storage_status(&self, status: StorageStatus) {
+        if let Some(step) = self.trace.borrow_mut().as_mut().and_then(|trace| trace.last_mut()) {
+            step.storage_status = Some(status);
+        }
+    }
+
     fn read_only(&self) -> bool {
         false
     }
@@ -1385,6 +1469,24 @@ impl Primitives for MockRuntime {
         Ok(())
     }
 
+    fn verify_bls_aggregate(
+        &self,
+        signature: &[u8; BLS_SIG_LEN],
+        pub_keys: &[[u8; BLS_PUB_LEN]],
+        plaintexts: &[&[u8]],
+    ) -> anyhow::Result<()> {
+        let exp = self
+            .expectations
+            .borrow_mut()
+            .expect_verify_bls_aggregates
+            .pop_front()
+            .expect("unexpected call to verify BLS aggregate signature");
+        assert_eq!(exp.sig, *signature, "mismatched aggregate signature");
+        assert_eq!(exp.pub_keys, pub_keys, "mismatched public keys");
+        assert_eq!(exp.plaintexts, plaintexts, "mismatched plaintexts");
+        exp.result
+    }
+
     fn hash_blake2b(&self, data: &[u8]) -> [u8; 32] {
         let (digest, _) = (*self.hash_func)(SupportedHashes::Blake2b256, data);
         let mut ret = [0u8; 32];
@@ -1673,6 +1775,9 @@ pub struct FakePrimitives {
     pub verify_aggregate_seals:
         RefCell<Option<fn(&AggregateSealVerifyProofAndInfos) -> Result<(), Error>>>,
     pub verify_signature: RefCell<Option<fn(&Signature, &Address, &[u8]) -> Result<(), Error>>>,
+    pub verify_bls_aggregate: RefCell<
+        Option<fn(&[u8; BLS_SIG_LEN], &[[u8; BLS_PUB_LEN]], &[&[u8]]) -> Result<(), Error>>,
+    >,
     pub verify_replica_update: RefCell<Option<fn(&ReplicaUpdateInfo) -> Result<(), Error>>>,
 }
 
@@ -1751,6 +1856,19 @@ impl Primitives for FakePrimitives {
         Ok(())
     }
 
+    fn verify_bls_aggregate(
+        &self,
+        signature: &[u8; BLS_SIG_LEN],
+        pub_keys: &[[u8; BLS_PUB_LEN]],
+        plaintexts: &[&[u8]],
+    ) -> Result<(), Error> {
+        if let Some(override_fn) = *self.verify_bls_aggregate.borrow() {
+            override_fn(signature, pub_keys, plaintexts)
+        } else {
+            Ok(())
+        }
+    }
+
     fn recover_secp_public_key(
         &self,
         hash: &[u8; SECP_SIG_MESSAGE_HASH_SIZE],
@@ -1875,6 +1993,13 @@ impl MockPrimitives for FakePrimitives {
         self.verify_signature.replace(Some(f));
     }
 
+    fn override_verify_bls_aggregate(
+        &self,
+        f: fn(&[u8; BLS_SIG_LEN], &[[u8; BLS_PUB_LEN]], &[&[u8]]) -> std::result::Result<(), Error>,
+    ) {
+        self.verify_bls_aggregate.replace(Some(f));
+    }
+
     fn override_verify_replica_update(
         &self,
         f: fn(&ReplicaUpdateInfo) -> std::result::Result<(), Error>,
@@ -0,0 +1,59 @@
+use cid::Cid;
+use fvm_ipld_blockstore::Blockstore;
+use fvm_ipld_encoding::CborStore;
+use multihash_codetable::Code;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+use crate::runtime::Runtime;
+use crate::{actor_error, ActorError, MessageAccumulator};
+
+/// An actor's state object.
+///
+/// This is usually implemented with `#[derive(ActorState)]`, which lists the struct's fields
+/// and treats each field of type `Cid` or `Option<Cid>` as the root of a state collection.
+pub trait ActorState: Serialize + DeserializeOwned {
+    /// Names of the state's fields, in serialization order.
+    const FIELDS: &'static [&'static str];
+
+    /// Returns the roots referenced by the state's fields, labelled by field name.
+    fn field_roots(&self) -> Vec<(&'static str, Cid)>;
+
+    /// Loads the receiver's state.
+    fn load(rt: &impl Runtime) -> Result<Self, ActorError> {
+        rt.state()
+    }
+
+    /// Stores the state and sets it as the receiver's state root.
+    /// This must not be called within a transaction, which would overwrite it.
+    fn save(&self, rt: &impl Runtime) -> Result<(), ActorError> {
+        let root = rt.store().put_cbor(self, Code::Blake2b256).map_err(
+            |e| actor_error!(illegal_state; "failed to write actor state: {}", e.to_string()),
+        )?;
+        rt.set_state_root(&root)
+    }
+
+    /// Summarises the state's fields, as a starting point for an actor's `StateSummary`.
+    fn field_summary(&self) -> StateFieldSummary {
+        StateFieldSummary { fields: Self::FIELDS.to_vec(), roots: self.field_roots() }
+    }
+
+    /// Checks that the root referenced by each of the state's fields is present in the store.
+    fn check_fields<BS: Blockstore>(&self, store: &BS, acc: &MessageAccumulator) {
+        for (field, root) in self.field_roots() {
+            match store.has(&root) {
+                Ok(present) => acc.require(present, format!("{field} root {root} not found")),
+                Err(e) => acc.add(format!("failed to check {field} root {root}: {e}")),
+            }
+        }
+    }
+}
+
+/// Describes the fields of an actor's state.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct StateFieldSummary {
+    /// Names of the state's fields, in serialization order.
+    pub fields: Vec<&'static str>,
+    /// Roots referenced by the state's fields, labelled by field name.
+    pub roots: Vec<(&'static str, Cid)>,
+}
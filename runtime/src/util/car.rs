@@ -0,0 +1,108 @@
+//! Synchronous export and import of state subtrees in the CARv1 format.
+//!
+//! These utilities are intended for tooling and tests (e.g. VM snapshots, migration testing
+//! and reproduction bundles) that need to move an actor's state between blockstores without
+//! an async runtime. They are built only with the `test_utils` feature, so are not compiled
+//! into actors. Export is deterministic: blocks are written in depth-first pre-order of
+//! first appearance, so the same state always produces the same bytes.
+
+use std::collections::HashSet;
+
+use anyhow::{anyhow, Context};
+use cid::Cid;
+use fvm_ipld_blockstore::Blockstore;
+use fvm_ipld_encoding::{from_slice, to_vec, DAG_CBOR};
+use integer_encoding::VarInt;
+use ipld_core::ipld::Ipld;
+use multihash_codetable::{Code, MultihashDigest};
+use serde::{Deserialize, Serialize};
+
+const CAR_VERSION: u64 = 1;
+const IDENTITY_HASH: u64 = 0x0;
+
+#[derive(Serialize, Deserialize)]
+struct CarHeader {
+    roots: Vec<Cid>,
+    version: u64,
+}
+
+/// Exports the blocks reachable from a root to CAR bytes with that single root.
+/// Links are followed through DAG-CBOR blocks. Identity-hashed CIDs are traversed but not
+/// written, since their data is carried in the CID itself.
+pub fn export_car<BS: Blockstore>(store: &BS, root: &Cid) -> anyhow::Result<Vec<u8>> {
+    let mut out = write_frame(&to_vec(&CarHeader { roots: vec![*root], version: CAR_VERSION })?);
+    let mut seen = HashSet::new();
+    let mut stack = vec![*root];
+    while let Some(cid) = stack.pop() {
+        if !seen.insert(cid) {
+            continue;
+        }
+        let data = if cid.hash().code() == IDENTITY_HASH {
+            cid.hash().digest().to_vec()
+        } else {
+            let data = store
+                .get(&cid)?
+                .ok_or_else(|| anyhow!("missing block {} reachable from {}", cid, root))?;
+            let mut frame = cid.to_bytes();
+            frame.extend_from_slice(&data);
+            out.extend(write_frame(&frame));
+            data
+        };
+        if cid.codec() == DAG_CBOR {
+            let node: Ipld =
+                from_slice(&data).with_context(|| format!("failed to decode block {}", cid))?;
+            let mut links = vec![];
+            collect_links(&node, &mut links);
+            // Push in reverse so that links are visited in the order they appear.
+            stack.extend(links.into_iter().rev());
+        }
+    }
+    Ok(out)
+}
+
+/// Imports the blocks in CAR bytes into a blockstore, returning the CAR's roots.
+/// Each block is verified against the hash in its CID before it is stored.
+pub fn import_car<BS: Blockstore>(store: &BS, car: &[u8]) -> anyhow::Result<Vec<Cid>> {
+    let (header, mut rest) = read_frame(car).context("failed to read CAR header")?;
+    let header: CarHeader = from_slice(header).context("failed to decode CAR header")?;
+    if header.version != CAR_VERSION {
+        return Err(anyhow!("unsupported CAR version {}", header.version));
+    }
+    while !rest.is_empty() {
+        let (frame, remainder) = read_frame(rest)?;
+        rest = remainder;
+        let mut reader = frame;
+        let cid = Cid::read_bytes(&mut reader).context("failed to read block CID")?;
+        let data = reader;
+        let code = Code::try_from(cid.hash().code())
+            .map_err(|_| anyhow!("unsupported hash function in {}", cid))?;
+        if code.digest(data) != *cid.hash() {
+            return Err(anyhow!("block data does not match {}", cid));
+        }
+        store.put_keyed(&cid, data)?;
+    }
+    Ok(header.roots)
+}
+
+fn collect_links(node: &Ipld, links: &mut Vec<Cid>) {
+    match node {
+        Ipld::Link(cid) => links.push(*cid),
+        Ipld::List(items) => items.iter().for_each(|item| collect_links(item, links)),
+        Ipld::Map(entries) => entries.values().for_each(|value| collect_links(value, links)),
+        _ => {}
+    }
+}
+
+fn write_frame(data: &[u8]) -> Vec<u8> {
+    let mut frame = data.len().encode_var_vec();
+    frame.extend_from_slice(data);
+    frame
+}
+
+fn read_frame(data: &[u8]) -> anyhow::Result<(&[u8], &[u8])> {
+    let (len, prefix) =
+        usize::decode_var(data).ok_or_else(|| anyhow!("invalid CAR frame length"))?;
+    let end = prefix.checked_add(len).filter(|end| *end <= data.len());
+    let end = end.ok_or_else(|| anyhow!("truncated CAR frame"))?;
+    Ok((&data[prefix..end], &data[end..]))
+}
@@ -0,0 +1,60 @@
+use fvm_ipld_encoding::tuple::*;
+use fvm_shared::clock::ChainEpoch;
+
+/// Counts of the work an actor has performed in implicit cron, held as a field of its state.
+///
+/// Cron work is paid for by the network rather than by any message sender, so the counters let
+/// the growth of cron work be observed before it approaches the block gas limit.
+#[derive(Serialize_tuple, Deserialize_tuple, Clone, Debug, Default, PartialEq, Eq)]
+pub struct CronWorkStats {
+    /// Epoch of the most recent cron invocation.
+    pub last_epoch: ChainEpoch,
+    /// Entries processed by the most recent cron invocation.
+    pub last_entries: u64,
+    /// Most entries processed by any single cron invocation.
+    pub max_entries: u64,
+    /// Entries processed by all cron invocations.
+    pub total_entries: u64,
+    /// Number of cron invocations that processed more entries than their budget.
+    pub overruns: u64,
+}
+
+impl CronWorkStats {
+    /// Records a cron invocation at `epoch` which processed `entries` entries,
+    /// returning whether it exceeded `budget`.
+    pub fn record(&mut self, epoch: ChainEpoch, entries: u64, budget: u64) -> bool {
+        self.last_epoch = epoch;
+        self.last_entries = entries;
+        self.max_entries = self.max_entries.max(entries);
+        self.total_entries = self.total_entries.saturating_add(entries);
+        let overrun = entries > budget;
+        if overrun {
+            self.overruns += 1;
+        }
+        overrun
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::CronWorkStats;
+
+    #[test]
+    fn records_invocations() {
+        let mut stats = CronWorkStats::default();
+        assert!(!stats.record(10, 3, 5));
+        assert!(!stats.record(11, 5, 5));
+        assert!(stats.record(12, 6, 5));
+        assert!(!stats.record(13, 0, 5));
+        assert_eq!(
+            CronWorkStats {
+                last_epoch: 13,
+                last_entries: 0,
+                max_entries: 6,
+                total_entries: 14,
+                overruns: 1
+            },
+            stats
+        );
+    }
+}
@@ -0,0 +1,122 @@
+// Copyright 2019-2022 ChainSafe Systems
+// SPDX-License-Identifier: Apache-2.0, MIT
+
+use cid::Cid;
+use fvm_ipld_blockstore::Blockstore;
+use fvm_shared::clock::ChainEpoch;
+
+use crate::{ActorError, MapKey, SetMultimap, SetMultimapConfig};
+
+/// EpochQueue schedules sets of values by epoch, for processing as the chain reaches each epoch.
+///
+/// It is stored as a [`SetMultimap`] keyed by epoch, so can load existing set multimaps with the
+/// same configuration. Epochs are iterated in ascending order. Values within an epoch
+/// are iterated in the order of the underlying set, which is deterministic but unrelated to the
+/// order in which they were enqueued.
+pub struct EpochQueue<BS, V>
+where
+    BS: Blockstore,
+    V: MapKey,
+{
+    epochs: SetMultimap<BS, ChainEpoch, V>,
+}
+
+impl<BS, V> EpochQueue<BS, V>
+where
+    BS: Blockstore,
+    V: MapKey,
+{
+    /// Initializes a new empty EpochQueue.
+    pub fn empty(bs: BS, config: SetMultimapConfig, name: &'static str) -> Self {
+        Self { epochs: SetMultimap::empty(bs, config, name) }
+    }
+
+    /// Initializes an EpochQueue from a root Cid.
+    pub fn load(
+        bs: BS,
+        root: &Cid,
+        config: SetMultimapConfig,
+        name: &'static str,
+    ) -> Result<Self, ActorError> {
+        Ok(Self { epochs: SetMultimap::load(bs, root, config, name)? })
+    }
+
+    /// Retrieve root from the EpochQueue.
+    #[inline]
+    pub fn flush(&mut self) -> Result<Cid, ActorError> {
+        self.epochs.flush()
+    }
+
+    /// Schedules a value at an epoch.
+    pub fn enqueue(&mut self, epoch: ChainEpoch, value: V) -> Result<(), ActorError> {
+        self.epochs.put(&epoch, value)
+    }
+
+    /// Schedules a number of values at an epoch.
+    pub fn enqueue_many(&mut self, epoch: ChainEpoch, values: &[V]) -> Result<(), ActorError> {
+        self.epochs.put_many(&epoch, values)
+    }
+
+    /// Returns the values scheduled at an epoch.
+    pub fn get(&self, epoch: ChainEpoch) -> Result<Vec<V>, ActorError> {
+        let mut values = Vec::new();
+        self.for_each_in(epoch, |v| {
+            values.push(v);
+            Ok(())
+        })?;
+        Ok(values)
+    }
+
+    /// Checks whether a value is scheduled at an epoch.
+    pub fn contains(&self, epoch: ChainEpoch, value: &V) -> Result<bool, ActorError> {
+        match self.epochs.get(&epoch)? {
+            Some(set) => set.has(value),
+            None => Ok(false),
+        }
+    }
+
+    /// Unschedules a value from an epoch, if it was scheduled there.
+    /// The epoch is removed when its last value is.
+    pub fn remove(&mut self, epoch: ChainEpoch, value: V) -> Result<(), ActorError> {
+        self.epochs.remove(&epoch, value)?;
+        if self.get(epoch)?.is_empty() {
+            self.epochs.remove_all(&epoch)?;
+        }
+        Ok(())
+    }
+
+    /// Unschedules all values at an epoch.
+    pub fn remove_epoch(&mut self, epoch: ChainEpoch) -> Result<(), ActorError> {
+        self.epochs.remove_all(&epoch)
+    }
+
+    /// Iterates the values scheduled at an epoch.
+    pub fn for_each_in<F>(&self, epoch: ChainEpoch, f: F) -> Result<(), ActorError>
+    where
+        F: FnMut(V) -> Result<(), ActorError>,
+    {
+        self.epochs.for_each_in(&epoch, f)
+    }
+
+    /// Iterates all scheduled values, in ascending epoch order.
+    pub fn for_each<F>(&self, mut f: F) -> Result<(), ActorError>
+    where
+        F: FnMut(ChainEpoch, V) -> Result<(), ActorError>,
+    {
+        for epoch in self.epochs()? {
+            self.for_each_in(epoch, |v| f(epoch, v))?;
+        }
+        Ok(())
+    }
+
+    /// Returns the epochs at which values are scheduled, in ascending order.
+    pub fn epochs(&self) -> Result<Vec<ChainEpoch>, ActorError> {
+        let mut epochs = Vec::new();
+        self.epochs.for_each(|epoch, _| {
+            epochs.push(epoch);
+            Ok(())
+        })?;
+        epochs.sort_unstable();
+        Ok(epochs)
+    }
+}
@@ -0,0 +1,44 @@
+use std::ops::Range;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{actor_error, ActorError};
+
+/// A monotonic sequence of unique IDs, held as a field of an actor's state.
+///
+/// IDs are unique within the state field that owns the sequence: each ID is issued at most once
+/// and IDs are issued in increasing order. Issuing an ID that would overflow fails rather than
+/// wrapping around to IDs already in use.
+///
+/// The sequence is encoded as the next ID to issue, a CBOR unsigned integer, so it can replace a
+/// plain `u64` counter without changing the encoding of the state.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[serde(transparent)]
+pub struct IdSequence(u64);
+
+impl IdSequence {
+    /// Creates a sequence whose first issued ID is `first`.
+    pub const fn new(first: u64) -> Self {
+        Self(first)
+    }
+
+    /// Returns the next ID to be issued, without issuing it.
+    /// Every ID issued so far is less than this value.
+    pub fn peek(&self) -> u64 {
+        self.0
+    }
+
+    /// Issues the next ID.
+    pub fn next_id(&mut self) -> Result<u64, ActorError> {
+        Ok(self.reserve(1)?.start)
+    }
+
+    /// Issues `count` consecutive IDs, returned as a range.
+    pub fn reserve(&mut self, count: u64) -> Result<Range<u64>, ActorError> {
+        let first = self.0;
+        self.0 = first
+            .checked_add(count)
+            .ok_or_else(|| actor_error!(illegal_state, "ID sequence exhausted at {}", first))?;
+        Ok(first..self.0)
+    }
+}
@@ -21,6 +21,7 @@ where
     V: DeserializeOwned + Serialize,
 {
     hamt: hamt::Hamt<BS, V, hamt::BytesKey, Hasher>,
+    config: Config,
     name: &'static str,
     key_type: PhantomData<K>,
 }
@@ -44,7 +45,8 @@ where
     /// Creates a new, empty map.
     pub fn empty(store: BS, config: Config, name: &'static str) -> Self {
         Self {
-            hamt: hamt::Hamt::new_with_config(store, config),
+            hamt: hamt::Hamt::new_with_config(store, config.clone()),
+            config,
             name,
             key_type: Default::default(),
         }
@@ -69,10 +71,11 @@ where
         name: &'static str,
     ) -> Result<Self, ActorError> {
         Ok(Self {
-            hamt: hamt::Hamt::load_with_config(root, store, config)
+            hamt: hamt::Hamt::load_with_config(root, store, config.clone())
                 .with_context_code(ExitCode::USR_ILLEGAL_STATE, || {
                     format!("failed to load HAMT '{}'", name)
                 })?,
+            config,
             name,
             key_type: Default::default(),
         })
@@ -165,6 +168,95 @@ where
         }
         Ok(())
     }
+
+    /// Iterates over key-value pairs in the map, beginning at `starting_key` (inclusive) if
+    /// provided, and visiting at most `max` entries.
+    /// Returns the number of entries visited and the key from which to resume iteration,
+    /// or None if the traversal reached the end of the map.
+    /// Iteration order is the HAMT's internal order, not the order of keys.
+    pub fn for_each_ranged<F>(
+        &self,
+        starting_key: Option<&K>,
+        max: Option<usize>,
+        mut f: F,
+    ) -> Result<(usize, Option<K>), ActorError>
+    where
+        F: FnMut(K, &V) -> Result<(), ActorError>,
+    {
+        let iter = match starting_key {
+            Some(key) => {
+                let k =
+                    key.to_bytes().context_code(ExitCode::USR_ASSERTION_FAILED, "invalid key")?;
+                self.hamt.iter_from(&k).with_context_code(ExitCode::USR_ILLEGAL_STATE, || {
+                    format!("failed to seek key {key:?} in HAMT '{}'", self.name)
+                })?
+            }
+            None => self.hamt.iter(),
+        };
+        let mut traversed = 0usize;
+        for kv in iter {
+            let (k, v) = kv.with_context_code(ExitCode::USR_ILLEGAL_STATE, || {
+                format!("error traversing HAMT {}", self.name)
+            })?;
+            let k = K::from_bytes(k).with_context_code(ExitCode::USR_ILLEGAL_STATE, || {
+                format!("invalid key in HAMT {}", self.name)
+            })?;
+            if max.is_some_and(|max| traversed >= max) {
+                return Ok((traversed, Some(k)));
+            }
+            f(k, v)?;
+            traversed += 1;
+        }
+        Ok((traversed, None))
+    }
+
+    /// Iterates as [`Self::for_each_ranged`], except that a `starting_key` no longer in the map
+    /// is not an error. Iteration then begins with the entry that would have followed the key,
+    /// so a paged listing can resume after the entry at its cursor is removed.
+    /// The map's contents are unchanged, though any pending changes may be flushed.
+    pub fn for_each_ranged_resuming<F>(
+        &mut self,
+        starting_key: Option<&K>,
+        max: Option<usize>,
+        mut f: F,
+    ) -> Result<(usize, Option<K>), ActorError>
+    where
+        BS: Clone,
+        V: Clone + PartialEq,
+        F: FnMut(K, &V) -> Result<(), ActorError>,
+    {
+        let removed = match starting_key {
+            Some(key) if !self.contains_key(key)? => key,
+            _ => return self.for_each_ranged(starting_key, max, f),
+        };
+        // The removed key is restored in a copy of the map that is never flushed, and the copy
+        // traversed. The structure of a HAMT depends only on its keys, so restoring the key
+        // with any value recovers its position in the iteration order.
+        let placeholder = match self.hamt.iter().next() {
+            Some(kv) => kv
+                .with_context_code(ExitCode::USR_ILLEGAL_STATE, || {
+                    format!("error traversing HAMT {}", self.name)
+                })?
+                .1
+                .clone(),
+            None => return Ok((0, None)),
+        };
+        let root = self.flush()?;
+        let mut scratch = Self::load(self.store().clone(), &root, self.config.clone(), self.name)?;
+        scratch.set(removed, placeholder)?;
+        let mut at_removed = true;
+        let (traversed, next) = scratch.for_each_ranged(
+            Some(removed),
+            max.map(|max| max.saturating_add(1)),
+            |k, v| {
+                if std::mem::take(&mut at_removed) {
+                    return Ok(());
+                }
+                f(k, v)
+            },
+        )?;
+        Ok((traversed.saturating_sub(1), next))
+    }
 }
 
 impl MapKey for Vec<u8> {
@@ -254,4 +346,40 @@ mod tests {
         assert!(res.is_err());
         assert_eq!(res.unwrap_err(), ActorError::forbidden("test".to_string()));
     }
+
+    #[test]
+    fn for_each_ranged_resumes_after_removed_key() {
+        let bs = MemoryBlockstore::new();
+        let mut m = Map2::<_, u64, u64>::empty(&bs, DEFAULT_HAMT_CONFIG, "empty");
+        for i in 0..50 {
+            m.set(&i, i).unwrap();
+        }
+
+        // Remove the entry at the cursor before each page is listed.
+        let mut listed = vec![];
+        let mut removed = vec![];
+        let mut cursor = None;
+        loop {
+            let (_, next) = m
+                .for_each_ranged_resuming(cursor.as_ref(), Some(4), |k, _| {
+                    listed.push(k);
+                    Ok(())
+                })
+                .unwrap();
+            let Some(next) = next else { break };
+            m.delete(&next).unwrap().unwrap();
+            removed.push(next);
+            cursor = Some(next);
+        }
+        assert!(!removed.is_empty());
+        listed.sort();
+        assert_eq!((0..50).filter(|i| !removed.contains(i)).collect::<Vec<_>>(), listed);
+
+        // Listing from a removed key leaves the map unchanged, and writes nothing to the store.
+        let pruned = m.flush().unwrap();
+        let writes = bs.stats.borrow().w;
+        m.for_each_ranged_resuming(Some(&removed[0]), Some(usize::MAX), |_, _| Ok(())).unwrap();
+        assert_eq!(writes, bs.stats.borrow().w);
+        assert_eq!(pruned, m.flush().unwrap());
+    }
 }
@@ -122,6 +122,56 @@ where
         in_map.for_each(f)
     }
 
+    // Runs a function over values for one outer key, beginning at `starting_k` (inclusive) if
+    // provided, and visiting at most `max` values.
+    // Returns the number of values visited and the inner key from which to resume, if any.
+    // If `starting_k` has since been removed, iteration begins where it would have been.
+    // The map's contents are unchanged, though pending changes to the inner map may be flushed.
+    pub fn for_each_in_ranged<F>(
+        &mut self,
+        outside_k: K1,
+        starting_k: Option<K2>,
+        max: Option<usize>,
+        mut f: F,
+    ) -> Result<(usize, Option<BytesKey>), Error>
+    where
+        F: FnMut(&BytesKey, &V) -> anyhow::Result<()>,
+    {
+        let inner_bitwidth = self.inner_bitwidth;
+        let store = *self.outer.store();
+        let (is_empty, in_map) = self.load_inner_map(outside_k)?;
+        if is_empty {
+            return Ok((0, None));
+        }
+        let starting_k = match starting_k.map(|k| k.key()) {
+            Some(k) if !in_map.contains_key(&k)? => k,
+            starting_k => return in_map.for_each_ranged(starting_k.as_ref(), max, f),
+        };
+        // A starting key no longer in the map is restored in a copy of the inner map that is
+        // never flushed, and the copy traversed, so iteration resumes with the entry that
+        // followed it.
+        // The structure of a HAMT depends only on its keys, so any value recovers the position.
+        let placeholder = match in_map.iter().next() {
+            Some(entry) => entry?.1.clone(),
+            None => return Ok((0, None)),
+        };
+        let root = in_map.flush()?;
+        let mut scratch = make_map_with_root_and_bitwidth::<BS, V>(&root, store, inner_bitwidth)?;
+        scratch.set(starting_k.clone(), placeholder)?;
+        let mut at_start = true;
+        let (traversed, next) = scratch.for_each_ranged(
+            Some(&starting_k),
+            max.map(|m| m.saturating_add(1)),
+            |k, v| {
+                if std::mem::take(&mut at_start) {
+                    return Ok(());
+                }
+                f(k, v)
+            },
+        )?;
+        Ok((traversed.saturating_sub(1), next))
+    }
+
     // Puts a key value pair in the MapMap, overwriting any existing value.
     // Returns the previous value, if any.
     pub fn put(&mut self, outside_k: K1, inside_k: K2, value: V) -> Result<Option<V>, Error> {
@@ -1,9 +1,13 @@
 // Copyright 2019-2022 ChainSafe Systems
 // SPDX-License-Identifier: Apache-2.0, MIT
 
+pub use self::actor_state::*;
 pub use self::batch_return::*;
+pub use self::cron_work::CronWorkStats;
 pub use self::downcast::*;
+pub use self::epoch_queue::EpochQueue;
 pub use self::events::*;
+pub use self::id_sequence::IdSequence;
 pub use self::map::*;
 pub use self::mapmap::MapMap;
 pub use self::message_accumulator::MessageAccumulator;
@@ -12,10 +16,16 @@ pub use self::set::Set;
 pub use self::set_multimap::SetMultimap;
 pub use self::set_multimap::SetMultimapConfig;
 
+mod actor_state;
 mod batch_return;
+#[cfg(feature = "test_utils")]
+pub mod car;
 pub mod cbor;
+mod cron_work;
 mod downcast;
+mod epoch_queue;
 mod events;
+mod id_sequence;
 mod map;
 mod mapmap;
 mod message_accumulator;
@@ -0,0 +1,68 @@
+// Copyright 2019-2022 ChainSafe Systems
+// SPDX-License-Identifier: Apache-2.0, MIT
+
+use cid::Cid;
+use fil_actors_runtime::test_blockstores::MemoryBlockstore;
+use fil_actors_runtime::test_utils::MockRuntime;
+use fil_actors_runtime::{ActorState, MessageAccumulator, StateFieldSummary};
+use fvm_ipld_encoding::tuple::*;
+use fvm_ipld_encoding::CborStore;
+use multihash_codetable::Code;
+
+#[derive(Serialize_tuple, Deserialize_tuple, Clone, Debug, PartialEq, ActorState)]
+struct TestState {
+    count: u64,
+    root: Cid,
+    optional_root: Option<Cid>,
+    optional_count: Option<u64>,
+}
+
+fn new_state(store: &MemoryBlockstore) -> TestState {
+    TestState {
+        count: 1,
+        root: store.put_cbor(&"root", Code::Blake2b256).unwrap(),
+        optional_root: Some(store.put_cbor(&"optional", Code::Blake2b256).unwrap()),
+        optional_count: None,
+    }
+}
+
+#[test]
+fn fields_and_roots() {
+    let store = MemoryBlockstore::new();
+    let mut state = new_state(&store);
+    assert_eq!(["count", "root", "optional_root", "optional_count"], TestState::FIELDS);
+    assert_eq!(
+        StateFieldSummary {
+            fields: TestState::FIELDS.to_vec(),
+            roots: vec![("root", state.root), ("optional_root", state.optional_root.unwrap())],
+        },
+        state.field_summary()
+    );
+
+    state.optional_root = None;
+    assert_eq!(vec![("root", state.root)], state.field_roots());
+}
+
+#[test]
+fn check_fields() {
+    let store = MemoryBlockstore::new();
+    let mut state = new_state(&store);
+    let acc = MessageAccumulator::default();
+    state.check_fields(&store, &acc);
+    acc.assert_empty();
+
+    // A root missing from the store is reported.
+    let missing = MemoryBlockstore::new().put_cbor(&"missing", Code::Blake2b256).unwrap();
+    state.optional_root = Some(missing);
+    state.check_fields(&store, &acc);
+    assert_eq!(vec![format!("optional_root root {missing} not found")], acc.messages());
+}
+
+#[test]
+fn load_and_save() {
+    let rt = MockRuntime::default();
+    let state = new_state(&rt.store);
+    state.save(&rt).unwrap();
+    assert_eq!(state, TestState::load(&rt).unwrap());
+    assert_eq!(state, rt.get_state());
+}
@@ -0,0 +1,99 @@
+// Copyright 2019-2022 ChainSafe Systems
+// SPDX-License-Identifier: Apache-2.0, MIT
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use fvm_shared::clock::ChainEpoch;
+use rand::prelude::*;
+
+use fil_actors_runtime::test_blockstores::MemoryBlockstore;
+use fil_actors_runtime::{EpochQueue, SetMultimapConfig, DEFAULT_HAMT_CONFIG};
+
+pub const CONFIG: SetMultimapConfig =
+    SetMultimapConfig { outer: DEFAULT_HAMT_CONFIG, inner: DEFAULT_HAMT_CONFIG };
+
+#[test]
+fn enqueue_remove() {
+    let store = MemoryBlockstore::new();
+    let mut q = EpochQueue::<_, u64>::empty(&store, CONFIG, "t");
+
+    q.enqueue(100, 8).unwrap();
+    q.enqueue_many(100, &[2, 3]).unwrap();
+    q.remove(100, 2).unwrap();
+    assert!(q.contains(100, &8).unwrap());
+    assert!(!q.contains(100, &2).unwrap());
+    assert!(!q.contains(101, &8).unwrap());
+    assert_eq!(BTreeSet::from([3, 8]), q.get(100).unwrap().into_iter().collect());
+
+    q.remove_epoch(100).unwrap();
+    assert!(q.get(100).unwrap().is_empty());
+    assert!(q.epochs().unwrap().is_empty());
+}
+
+#[test]
+fn iterates_in_epoch_order() {
+    let store = MemoryBlockstore::new();
+    let mut q = EpochQueue::<_, u64>::empty(&store, CONFIG, "t");
+    for epoch in [500, 3, 70_000, 42] {
+        q.enqueue(epoch, epoch as u64).unwrap();
+    }
+
+    let root = q.flush().unwrap();
+    let q = EpochQueue::<_, u64>::load(&store, &root, CONFIG, "t").unwrap();
+    assert_eq!(vec![3, 42, 500, 70_000], q.epochs().unwrap());
+
+    let mut visited = vec![];
+    q.for_each(|epoch, v| {
+        visited.push((epoch, v));
+        Ok(())
+    })
+    .unwrap();
+    assert_eq!(vec![(3, 3), (42, 42), (500, 500), (70_000, 70_000)], visited);
+}
+
+// Checks the queue against a model under random operations.
+#[test]
+fn matches_model() {
+    let mut rng = StdRng::seed_from_u64(0);
+    for _ in 0..20 {
+        let store = MemoryBlockstore::new();
+        let mut q = EpochQueue::<_, u64>::empty(&store, CONFIG, "t");
+        let mut model: BTreeMap<ChainEpoch, BTreeSet<u64>> = BTreeMap::new();
+
+        for _ in 0..100 {
+            let epoch = rng.gen_range(0..50);
+            let value = rng.gen_range(0..20);
+            match rng.gen_range(0..10) {
+                0 => {
+                    q.remove_epoch(epoch).unwrap();
+                    model.remove(&epoch);
+                }
+                1..=2 => {
+                    q.remove(epoch, value).unwrap();
+                    if let Some(set) = model.get_mut(&epoch) {
+                        set.remove(&value);
+                    }
+                }
+                _ => {
+                    q.enqueue(epoch, value).unwrap();
+                    model.entry(epoch).or_default().insert(value);
+                }
+            }
+        }
+        model.retain(|_, set| !set.is_empty());
+
+        let root = q.flush().unwrap();
+        let q = EpochQueue::<_, u64>::load(&store, &root, CONFIG, "t").unwrap();
+
+        let mut visited: BTreeMap<ChainEpoch, BTreeSet<u64>> = BTreeMap::new();
+        let mut last_epoch = ChainEpoch::MIN;
+        q.for_each(|epoch, v| {
+            assert!(epoch >= last_epoch);
+            last_epoch = epoch;
+            visited.entry(epoch).or_default().insert(v);
+            Ok(())
+        })
+        .unwrap();
+        assert_eq!(model, visited);
+    }
+}
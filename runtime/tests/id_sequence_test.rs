@@ -0,0 +1,34 @@
+use fil_actors_runtime::IdSequence;
+use fvm_ipld_encoding::{from_slice, to_vec};
+use fvm_shared::error::ExitCode;
+
+#[test]
+fn issues_increasing_ids() {
+    let mut seq = IdSequence::new(1);
+    assert_eq!(1, seq.peek());
+    assert_eq!(1, seq.next_id().unwrap());
+    assert_eq!(2, seq.next_id().unwrap());
+    assert_eq!(3..6, seq.reserve(3).unwrap());
+    assert_eq!(6..6, seq.reserve(0).unwrap());
+    assert_eq!(6, seq.next_id().unwrap());
+    assert_eq!(7, seq.peek());
+}
+
+#[test]
+fn overflow_is_an_error() {
+    let mut seq = IdSequence::new(u64::MAX - 2);
+    assert_eq!(u64::MAX - 2..u64::MAX, seq.reserve(2).unwrap());
+    assert_eq!(ExitCode::USR_ILLEGAL_STATE, seq.next_id().unwrap_err().exit_code());
+    assert_eq!(ExitCode::USR_ILLEGAL_STATE, seq.reserve(2).unwrap_err().exit_code());
+    // A failed reservation issues nothing.
+    assert_eq!(u64::MAX, seq.peek());
+    assert_eq!(u64::MAX..u64::MAX, seq.reserve(0).unwrap());
+}
+
+#[test]
+fn encodes_as_next_id() {
+    let seq = IdSequence::new(42);
+    let bytes = to_vec(&seq).unwrap();
+    assert_eq!(to_vec(&42u64).unwrap(), bytes);
+    assert_eq!(seq, from_slice::<IdSequence>(&bytes).unwrap());
+}
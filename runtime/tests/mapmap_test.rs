@@ -88,3 +88,44 @@ fn mapmap_test() {
         .unwrap();
     assert_eq!(2, count);
 }
+
+#[test]
+fn for_each_in_ranged_resumes_after_removed_key() {
+    let store = MemoryBlockstore::new();
+    let mut mm: MapMap<MemoryBlockstore, u64, u64, u64> =
+        MapMap::new(&store, HAMT_BIT_WIDTH, HAMT_BIT_WIDTH);
+    mm.put_many(1, (0..50).map(|i| (i, i))).unwrap();
+
+    // Page through the map, removing the record at the cursor before each page is listed.
+    let mut listed = vec![];
+    let mut removed = vec![];
+    let mut cursor = None;
+    loop {
+        let (_, next) = mm
+            .for_each_in_ranged(1, cursor, Some(4), |_, v| {
+                listed.push(*v);
+                Ok(())
+            })
+            .unwrap();
+        let Some(next) = next else { break };
+        let next = fil_actors_runtime::parse_uint_key(&next).unwrap();
+        assert_eq!(Some(next), mm.remove(1, next).unwrap());
+        removed.push(next);
+        cursor = Some(next);
+    }
+    assert!(!removed.is_empty());
+
+    // Every record not removed is listed exactly once.
+    listed.sort();
+    let remaining: Vec<u64> = (0..50).filter(|i| !removed.contains(i)).collect();
+    assert_eq!(remaining, listed);
+
+    // Listing from a removed key leaves the map unchanged, and writes nothing to the store.
+    let root = mm.flush().unwrap();
+    let stats = *store.stats.borrow();
+    let mut clean: MapMap<MemoryBlockstore, u64, u64, u64> =
+        MapMap::from_root(&store, &root, HAMT_BIT_WIDTH, HAMT_BIT_WIDTH).unwrap();
+    clean.for_each_in_ranged(1, Some(removed[0]), Some(usize::MAX), |_, _| Ok(())).unwrap();
+    assert_eq!(stats.w, store.stats.borrow().w);
+    assert_eq!(root, clean.flush().unwrap());
+}
@@ -0,0 +1,9 @@
+use fil_actors_runtime::test_utils::MockRuntime;
+
+#[test]
+#[should_panic(expected = "tipset timestamp may not decrease")]
+fn mock_timestamp_is_monotonic() {
+    let mut rt = MockRuntime::default();
+    rt.set_tipset_timestamp(1000);
+    rt.set_tipset_timestamp(999);
+}
@@ -2,9 +2,16 @@
 mod serialization {
     use hex_literal::hex;
 
+    use cid::multihash::Multihash;
+    use cid::Cid;
+    use fil_actors_runtime::sector_content::{
+        PieceChange, SectorChanges, SectorContentChangedParams,
+    };
     use fil_actors_runtime::{BatchReturn, BatchReturnGen};
     use fvm_ipld_encoding::ipld_block::IpldBlock;
+    use fvm_ipld_encoding::{RawBytes, DAG_CBOR};
     use fvm_shared::error::ExitCode;
+    use fvm_shared::piece::PaddedPieceSize;
 
     #[test]
     fn batch_return() {
@@ -53,4 +60,57 @@ mod serialization {
             assert_eq!(params, decoded);
         }
     }
+
+    #[test]
+    fn sector_content_changed_params() {
+        let params = SectorContentChangedParams {
+            sectors: vec![SectorChanges { sector: 1, minimum_commitment_epoch: 2, added: vec![] }],
+        };
+        let encoded = IpldBlock::serialize_cbor(&params).unwrap().unwrap();
+        // [1,[[1,2,[]]]]
+        assert_eq!(encoded.data, &hex!("82018183010280"));
+        assert_eq!(params, IpldBlock::deserialize(&encoded).unwrap());
+
+        // Pieces round-trip through the envelope.
+        let params = SectorContentChangedParams {
+            sectors: vec![SectorChanges {
+                sector: 1,
+                minimum_commitment_epoch: 2,
+                added: vec![PieceChange {
+                    data: Cid::new_v1(DAG_CBOR, Multihash::wrap(0, b"piece").unwrap()),
+                    size: PaddedPieceSize(128),
+                    payload: RawBytes::new(vec![1, 2, 3]),
+                }],
+            }],
+        };
+        let encoded = IpldBlock::serialize_cbor(&params).unwrap().unwrap();
+        assert_eq!(params, IpldBlock::deserialize(&encoded).unwrap());
+
+        // The legacy, unversioned encoding is accepted.
+        let test_cases = vec![
+            // []
+            (&hex!("80")[..], SectorContentChangedParams { sectors: vec![] }),
+            // [[1,2,[]]]
+            (
+                &hex!("8183010280")[..],
+                SectorContentChangedParams {
+                    sectors: vec![SectorChanges {
+                        sector: 1,
+                        minimum_commitment_epoch: 2,
+                        added: vec![],
+                    }],
+                },
+            ),
+        ];
+        for (data, expected) in test_cases {
+            let decoded: SectorContentChangedParams = fvm_ipld_encoding::from_slice(data).unwrap();
+            assert_eq!(expected, decoded);
+        }
+
+        // Unknown versions are rejected.
+        // [2,[]]
+        assert!(
+            fvm_ipld_encoding::from_slice::<SectorContentChangedParams>(&hex!("820280")).is_err()
+        );
+    }
 }
@@ -0,0 +1,47 @@
+#!/usr/bin/env bash
+
+# Checks the public API of each actor crate against the snapshot committed alongside it, so that
+# changes to consensus-critical types are deliberate. The API is taken without the "testing"
+# feature, i.e. as built into the bundle.
+#
+# Usage: scripts/public-api.sh [--bless]
+#
+# With --bless, the snapshots are updated to match the current API instead.
+# Requires cargo-public-api (`cargo install cargo-public-api`) and a nightly toolchain.
+
+set -e
+
+die() {
+    echo "$1"
+    exit 1
+}
+
+bless=false
+if [ "$1" == "--bless" ]; then
+    bless=true
+fi
+
+command -v cargo-public-api > /dev/null || die "cargo-public-api not installed"
+
+failed=()
+for manifest in actors/*/Cargo.toml; do
+    dir=$(dirname "$manifest")
+    crate=$(sed -n 's/^name = "\(.*\)"$/\1/p' "$manifest" | head -n1)
+    snapshot="$dir/public-api.txt"
+
+    current=$(cargo public-api --simplified --manifest-path "$manifest")
+    if $bless; then
+        echo "$current" > "$snapshot"
+        echo "updated $snapshot"
+    elif [ ! -f "$snapshot" ]; then
+        echo "no public API snapshot for $crate at $snapshot"
+        failed+=("$crate")
+    elif ! diff -u "$snapshot" <(echo "$current"); then
+        echo "public API of $crate differs from $snapshot"
+        failed+=("$crate")
+    fi
+done
+
+if [ ${#failed[@]} -ne 0 ]; then
+    die "public API changed for: ${failed[*]}; run 'make public-api-bless' to accept the changes"
+fi
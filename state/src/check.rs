@@ -107,7 +107,7 @@ pub fn check_state_invariants<BS: Blockstore>(
             }
             Some(Type::Account) => {
                 let state = get_state!(store, actor, AccountState);
-                let (summary, msgs) = account::check_state_invariants(&state, key);
+                let (summary, msgs) = account::check_state_invariants(&state, store, key);
                 acc.with_prefix("account: ").add_all(&msgs);
                 account_summaries.push(summary);
             }
@@ -177,6 +177,7 @@ pub fn check_state_invariants<BS: Blockstore>(
     // Perform cross-actor checks from state summaries here.
     if let Some(power_summary) = power_summary {
         check_miner_against_power(&acc, &miner_summaries, &power_summary);
+        check_pledge_against_miners(&acc, &miner_summaries, &power_summary);
     }
 
     if let Some(market_summary) = market_summary.clone() {
@@ -203,6 +204,22 @@ pub fn check_state_invariants<BS: Blockstore>(
     Ok(acc)
 }
 
+fn check_pledge_against_miners(
+    acc: &MessageAccumulator,
+    miner_summaries: &HashMap<Address, miner::StateSummary>,
+    power_summary: &power::StateSummary,
+) {
+    let miner_pledge: TokenAmount =
+        miner_summaries.values().map(|miner_summary| &miner_summary.pledge_obligation).sum();
+    acc.require(
+        power_summary.total_pledge_collateral == miner_pledge,
+        format!(
+            "power total pledge collateral {} does not match sum of miner pledge obligations {}",
+            power_summary.total_pledge_collateral, miner_pledge
+        ),
+    );
+}
+
 fn check_miner_against_power(
     acc: &MessageAccumulator,
     miner_summaries: &HashMap<Address, miner::StateSummary>,
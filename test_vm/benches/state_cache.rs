@@ -0,0 +1,46 @@
+//! Benchmarks of actor state reads through the TestVM state cache, against a state tree built by
+//! the batch onboarding integration test.
+
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use fil_actor_market::State as MarketState;
+use fil_actor_power::State as PowerState;
+use fil_actors_integration_tests::tests::batch_onboarding_test;
+use fil_actors_runtime::test_blockstores::MemoryBlockstore;
+use fil_actors_runtime::{STORAGE_MARKET_ACTOR_ADDR, STORAGE_POWER_ACTOR_ADDR};
+use test_vm::TestVM;
+use vm_api::util::{get_state, get_state_cached};
+
+fn bench_batch_onboarding(c: &mut Criterion) {
+    let mut group = c.benchmark_group("batch_onboarding");
+    group.sample_size(10);
+    group.bench_function("test", |b| {
+        b.iter(|| {
+            let v = TestVM::new_with_singletons(MemoryBlockstore::new());
+            batch_onboarding_test(&v);
+        })
+    });
+    group.finish();
+}
+
+fn bench_state_reads(c: &mut Criterion) {
+    let v = TestVM::new_with_singletons(MemoryBlockstore::new());
+    batch_onboarding_test(&v);
+
+    let mut group = c.benchmark_group("state_reads");
+    group.bench_function("uncached", |b| {
+        b.iter(|| {
+            black_box(get_state::<PowerState>(&v, &STORAGE_POWER_ACTOR_ADDR).unwrap());
+            black_box(get_state::<MarketState>(&v, &STORAGE_MARKET_ACTOR_ADDR).unwrap());
+        })
+    });
+    group.bench_function("cached", |b| {
+        b.iter(|| {
+            black_box(get_state_cached::<PowerState>(&v, &STORAGE_POWER_ACTOR_ADDR).unwrap());
+            black_box(get_state_cached::<MarketState>(&v, &STORAGE_MARKET_ACTOR_ADDR).unwrap());
+        })
+    });
+    group.finish();
+}
+
+criterion_group!(benches, bench_batch_onboarding, bench_state_reads);
+criterion_main!(benches);
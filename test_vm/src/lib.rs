@@ -12,8 +12,11 @@ use fil_actors_runtime::cbor::serialize;
 use fil_actors_runtime::runtime::builtins::Type;
 use fil_actors_runtime::runtime::{Policy, Primitives, EMPTY_ARR_CID};
 use fil_actors_runtime::test_blockstores::MemoryBlockstore;
+use fil_actors_runtime::util::car::{export_car, import_car};
 use fil_actors_runtime::DATACAP_TOKEN_ACTOR_ADDR;
-use fil_actors_runtime::{test_utils::*, Map2, DEFAULT_HAMT_CONFIG};
+use fil_actors_runtime::{
+    test_utils::*, Map2, OnUpgradeParams, DEFAULT_HAMT_CONFIG, ON_UPGRADE_METHOD,
+};
 use fil_actors_runtime::{
     BURNT_FUNDS_ACTOR_ADDR, CRON_ACTOR_ADDR, EAM_ACTOR_ADDR, INIT_ACTOR_ADDR, REWARD_ACTOR_ADDR,
     STORAGE_MARKET_ACTOR_ADDR, STORAGE_POWER_ACTOR_ADDR, SYSTEM_ACTOR_ADDR,
@@ -21,7 +24,7 @@ use fil_actors_runtime::{
 };
 use fvm_ipld_blockstore::Blockstore;
 use fvm_ipld_encoding::ipld_block::IpldBlock;
-use fvm_ipld_encoding::CborStore;
+use fvm_ipld_encoding::{CborStore, RawBytes};
 use fvm_ipld_hamt::{BytesKey, Hamt, Sha256};
 use fvm_shared::address::Address;
 use fvm_shared::bigint::Zero;
@@ -30,21 +33,24 @@ use fvm_shared::econ::TokenAmount;
 use fvm_shared::error::ExitCode;
 use fvm_shared::sector::StoragePower;
 use fvm_shared::version::NetworkVersion;
-use fvm_shared::{MethodNum, METHOD_SEND};
+use fvm_shared::{ActorID, MethodNum, METHOD_SEND};
 use multihash_codetable::Code;
 use serde::ser;
+use std::any::{Any, TypeId};
 use std::cell::{RefCell, RefMut};
-use std::collections::{BTreeMap, HashMap};
+use std::collections::{BTreeMap, HashMap, VecDeque};
 use std::rc::Rc;
-use vm_api::trace::InvocationTrace;
+use vm_api::trace::{InvocationTrace, TraceStep};
 use vm_api::{new_actor, ActorState, MessageResult, MockPrimitives, VMError, VM};
 
-use vm_api::util::{get_state, serialize_ok};
+use vm_api::util::{get_state_cached, serialize_ok};
 
 mod constants;
 pub use constants::*;
 mod messaging;
 pub use messaging::*;
+mod replay;
+pub use replay::*;
 
 /// An in-memory rust-execution VM for testing builtin-actors that yields sensible stack traces and debug info
 pub struct TestVM {
@@ -53,6 +59,7 @@ pub struct TestVM {
     pub state_root: RefCell<Cid>,
     actors_dirty: RefCell<bool>,
     actors_cache: RefCell<HashMap<Address, ActorState>>,
+    state_cache: RefCell<StateCache>,
     invocations: RefCell<Vec<InvocationTrace>>,
     // MachineContext equivalents
     network_version: NetworkVersion,
@@ -60,6 +67,53 @@ pub struct TestVM {
     circulating_supply: RefCell<TokenAmount>,
     base_fee: RefCell<TokenAmount>,
     timestamp: RefCell<u64>,
+    // Pending injected failures for outbound sends
+    send_faults: RefCell<Vec<SendFault>>,
+    // Policy with which all messages are executed
+    policy: Policy,
+    // Messages applied since recording started, if recording
+    recording: RefCell<Option<Vec<ReplayMessage>>>,
+    // Interpreter steps traced since tracing started, if tracing
+    trace: RefCell<Option<Vec<TraceStep>>>,
+}
+
+/// Maximum number of deserialized state objects retained by the state cache.
+const STATE_CACHE_CAPACITY: usize = 1024;
+
+/// Deserialized actor state objects keyed by state CID and concrete type.
+/// Once full, the least recently inserted object is evicted for each new one.
+#[derive(Default)]
+struct StateCache {
+    entries: HashMap<(Cid, TypeId), Rc<dyn Any>>,
+    // Keys of the entries in insertion order
+    order: VecDeque<(Cid, TypeId)>,
+}
+
+impl StateCache {
+    fn get(&self, key: &(Cid, TypeId)) -> Option<Rc<dyn Any>> {
+        self.entries.get(key).cloned()
+    }
+
+    fn insert(&mut self, key: (Cid, TypeId), state: Rc<dyn Any>) {
+        if self.entries.insert(key, state).is_some() {
+            return;
+        }
+        self.order.push_back(key);
+        if self.order.len() > STATE_CACHE_CAPACITY {
+            if let Some(evicted) = self.order.pop_front() {
+                self.entries.remove(&evicted);
+            }
+        }
+    }
+}
+
+/// A pending failure of an outbound send from an actor.
+#[derive(Clone, Debug)]
+struct SendFault {
+    from: ActorID,
+    // Number of further sends from the actor until the fault triggers, inclusive
+    remaining: u64,
+    exit_code: ExitCode,
 }
 
 impl TestVM {
@@ -78,11 +132,16 @@ impl TestVM {
             circulating_supply: RefCell::new(TokenAmount::zero()),
             actors_dirty: RefCell::new(false),
             actors_cache: RefCell::new(HashMap::new()),
+            state_cache: RefCell::new(StateCache::default()),
             network_version: NetworkVersion::V16,
             curr_epoch: RefCell::new(ChainEpoch::zero()),
             invocations: RefCell::new(vec![]),
             base_fee: RefCell::new(TokenAmount::zero()),
             timestamp: RefCell::new(0),
+            send_faults: RefCell::new(vec![]),
+            policy: Policy::default(),
+            recording: RefCell::new(None),
+            trace: RefCell::new(None),
         }
     }
 
@@ -131,7 +190,7 @@ impl TestVM {
                 method_num: MarketMethod::CronTick as u64,
             },
         ];
-        let cron_head = v.put_store(&CronState { entries: builtin_entries });
+        let cron_head = v.put_store(&CronState::new(builtin_entries));
         v.set_actor(
             &CRON_ACTOR_ADDR,
             new_actor(*CRON_ACTOR_CODE_ID, cron_head, 0, TokenAmount::zero(), None),
@@ -171,6 +230,7 @@ impl TestVM {
                 num_approvals_threshold: 1,
                 unlock_duration: 0,
                 start_epoch: 0,
+                signer_weights: vec![],
             },
             "multisig ctor params",
         )
@@ -215,7 +275,7 @@ impl TestVM {
         );
 
         // burnt funds
-        let burnt_funds_head = v.put_store(&AccountState { address: BURNT_FUNDS_ACTOR_ADDR });
+        let burnt_funds_head = v.put_store(&AccountState::new(BURNT_FUNDS_ACTOR_ADDR));
         v.set_actor(
             &BURNT_FUNDS_ACTOR_ADDR,
             new_actor(*ACCOUNT_ACTOR_CODE_ID, burnt_funds_head, 0, TokenAmount::zero(), None),
@@ -262,10 +322,115 @@ impl TestVM {
 
     pub fn rollback(&self, root: Cid) {
         self.actors_cache.replace(HashMap::new());
+        // Cached state is content-addressed so it would remain correct, but states written
+        // after the rollback target are unlikely to be read again.
+        self.state_cache.replace(StateCache::default());
         self.state_root.replace(root);
         self.actors_dirty.replace(false);
     }
 
+    /// Exports the state of the actor at `address`, with all the blocks reachable from it,
+    /// to CAR bytes. This snapshots the actor's state for import into another VM.
+    pub fn export_actor_state(&self, address: &Address) -> Vec<u8> {
+        let actor = self.actor(address).expect("exported actor must exist");
+        export_car(self.store.as_ref(), &actor.state).unwrap()
+    }
+
+    /// Imports an actor state snapshot exported by `export_actor_state`, and sets it as the
+    /// state of the actor at `address`.
+    pub fn import_actor_state(&self, address: &Address, car: &[u8]) {
+        let roots = import_car(self.store.as_ref(), car).unwrap();
+        assert_eq!(1, roots.len(), "actor state snapshot must have a single root");
+        let mut actor = self.actor(address).expect("imported actor must exist");
+        actor.state = roots[0];
+        self.set_actor(address, actor);
+    }
+
+    /// Sets the policy with which subsequent messages are executed, e.g. to run actors with
+    /// custom deal or sector bounds.
+    pub fn set_policy(&mut self, policy: Policy) {
+        self.policy = policy;
+    }
+
+    /// Starts recording the messages applied to this VM, discarding any previous recording.
+    pub fn start_recording(&self) {
+        self.recording.replace(Some(vec![]));
+    }
+
+    /// Stops recording and returns a replay of the messages applied since recording started.
+    pub fn take_recording(&self) -> Replay {
+        Replay::new(self.recording.take().expect("VM is not recording"))
+    }
+
+    /// Starts tracing the interpreter steps of actors built with tracing enabled, such as the
+    /// EVM actor, in all messages applied to this VM. Any previous trace is discarded.
+    pub fn start_trace(&self) {
+        self.trace.replace(Some(vec![]));
+    }
+
+    /// Stops tracing and returns the steps traced since tracing started, in execution order
+    /// across all actors.
+    pub fn take_trace(&self) -> Vec<TraceStep> {
+        self.trace.take().expect("VM is not tracing")
+    }
+
+    /// Configures the `n`th (1-based) outbound send made by the actor at `from` after this call
+    /// to fail with `exit_code`, as if the receiver had aborted. The receiver is not invoked and
+    /// no value is transferred. The send still appears in the invocation trace.
+    pub fn fail_nth_send(&self, from: &Address, n: u64, exit_code: ExitCode) {
+        assert!(n > 0, "send faults are counted from 1");
+        assert!(!exit_code.is_success(), "injected send fault must have an error exit code");
+        let from = self.resolve_id_address(from).expect("faulty actor must exist").id().unwrap();
+        self.send_faults.borrow_mut().push(SendFault { from, remaining: n, exit_code });
+    }
+
+    /// Counts an outbound send from `from` against pending faults, returning the exit code of
+    /// the fault it triggers, if any.
+    pub(crate) fn take_send_fault(&self, from: ActorID) -> Option<ExitCode> {
+        let mut faults = self.send_faults.borrow_mut();
+        let mut triggered = None;
+        faults.retain_mut(|f| {
+            if f.from != from {
+                return true;
+            }
+            f.remaining -= 1;
+            if f.remaining == 0 && triggered.is_none() {
+                triggered = Some(f.exit_code);
+                return false;
+            }
+            // Faults landing on the same send as another are consumed without effect
+            f.remaining > 0
+        });
+        triggered
+    }
+
+    /// Replaces the code of the actor at `address` and invokes its upgrade hook from the system
+    /// actor, as the FVM does when upgrading an actor. The upgrade is reverted if the hook fails.
+    pub fn upgrade_actor(
+        &self,
+        address: &Address,
+        new_code: Cid,
+        params: RawBytes,
+    ) -> Result<MessageResult, VMError> {
+        let prior_root = self.checkpoint();
+        let mut actor = self.actor(address).expect("upgraded actor must exist");
+        let old_state = actor.state;
+        actor.code = new_code;
+        self.set_actor(address, actor);
+
+        let res = self.execute_message_implicit(
+            &SYSTEM_ACTOR_ADDR,
+            address,
+            &TokenAmount::zero(),
+            ON_UPGRADE_METHOD,
+            IpldBlock::serialize_cbor(&OnUpgradeParams { old_state, params }).unwrap(),
+        )?;
+        if !res.code.is_success() {
+            self.rollback(prior_root);
+        }
+        Ok(res)
+    }
+
     fn actor_map(&self) -> Map2<&MemoryBlockstore, Address, ActorState> {
         Map2::load(self.store.as_ref(), &self.checkpoint(), DEFAULT_HAMT_CONFIG, "actors").unwrap()
     }
@@ -284,6 +449,15 @@ impl VM for TestVM {
         method: MethodNum,
         params: Option<IpldBlock>,
     ) -> Result<MessageResult, VMError> {
+        let recorded = self.recording.borrow().is_some().then(|| ReplayMessage {
+            epoch: self.epoch(),
+            from: *from,
+            to: *to,
+            value: value.clone(),
+            method,
+            params: params.clone().map(ReplayParams::from),
+            code: ExitCode::OK,
+        });
         let from_id = &self.resolve_id_address(from).unwrap();
         // TODO: for non-implicit calls validate that from_id is either the
         // account actor or the ethereum account actor and error otherwise
@@ -322,17 +496,24 @@ impl VM for TestVM {
             allow_side_effects: RefCell::new(true),
             caller_validated: RefCell::new(false),
             read_only: false,
-            policy: &Policy::default(),
+            policy: &self.policy,
             subinvocations: RefCell::new(vec![]),
             events: RefCell::new(vec![]),
         };
         let res = new_ctx.invoke();
 
         let invoc = new_ctx.gather_trace(res.clone());
+        let events = invoc.emitted_events();
         RefMut::map(self.invocations.borrow_mut(), |invocs| {
             invocs.push(invoc);
             invocs
         });
+        if let (Some(mut recorded), Some(recording)) =
+            (recorded, self.recording.borrow_mut().as_mut())
+        {
+            recorded.code = res.as_ref().map_or_else(|e| e.exit_code(), |_| ExitCode::OK);
+            recording.push(recorded);
+        }
         match res {
             Err(mut ae) => {
                 self.rollback(prior_root);
@@ -340,11 +521,19 @@ impl VM for TestVM {
                     code: ae.exit_code(),
                     message: ae.msg().to_string(),
                     ret: ae.take_data(),
+                    gas_used: None,
+                    events: vec![],
                 })
             }
             Ok(ret) => {
                 self.checkpoint();
-                Ok(MessageResult { code: ExitCode::OK, message: "OK".to_string(), ret })
+                Ok(MessageResult {
+                    code: ExitCode::OK,
+                    message: "OK".to_string(),
+                    ret,
+                    gas_used: None,
+                    events,
+                })
             }
         }
     }
@@ -360,7 +549,7 @@ impl VM for TestVM {
         self.execute_message(from, to, value, method, params)
     }
     fn resolve_id_address(&self, address: &Address) -> Option<Address> {
-        let st: InitState = get_state(self, &INIT_ACTOR_ADDR).unwrap();
+        let st = get_state_cached::<InitState>(self, &INIT_ACTOR_ADDR).unwrap();
         st.resolve_address(&self.store, address).unwrap()
     }
 
@@ -446,4 +635,12 @@ impl VM for TestVM {
     fn mut_primitives(&self) -> &dyn MockPrimitives {
         &self.primitives
     }
+
+    fn cached_state(&self, head: &Cid, type_id: TypeId) -> Option<Rc<dyn Any>> {
+        self.state_cache.borrow().get(&(*head, type_id))
+    }
+
+    fn cache_state(&self, head: &Cid, type_id: TypeId, state: Rc<dyn Any>) {
+        self.state_cache.borrow_mut().insert((*head, type_id), state);
+    }
 }
@@ -36,7 +36,7 @@ use fvm_shared::clock::ChainEpoch;
 use fvm_shared::consensus::ConsensusFault;
 use fvm_shared::crypto::hash::SupportedHashes;
 use fvm_shared::crypto::signature::{
-    Signature, SECP_PUB_LEN, SECP_SIG_LEN, SECP_SIG_MESSAGE_HASH_SIZE,
+    Signature, BLS_PUB_LEN, BLS_SIG_LEN, SECP_PUB_LEN, SECP_SIG_LEN, SECP_SIG_MESSAGE_HASH_SIZE,
 };
 use fvm_shared::econ::TokenAmount;
 use fvm_shared::error::ExitCode;
@@ -56,8 +56,8 @@ use fvm_shared::{ActorID, MethodNum, Response, IPLD_RAW, METHOD_CONSTRUCTOR, MET
 use serde::de::DeserializeOwned;
 use serde::Serialize;
 use std::cell::{RefCell, RefMut};
-use vm_api::trace::{EmittedEvent, InvocationTrace};
-use vm_api::util::get_state;
+use vm_api::trace::{EmittedEvent, InvocationTrace, StorageStatus, TraceStep};
+use vm_api::util::get_state_cached;
 use vm_api::{new_actor, ActorState, VM};
 
 use fil_actors_runtime::test_blockstores::MemoryBlockstore;
@@ -153,7 +153,8 @@ impl<'invocation> InvocationCtx<'invocation> {
             ));
         }
 
-        let mut st: InitState = get_state(self.v, &INIT_ACTOR_ADDR).unwrap();
+        let mut st =
+            get_state_cached::<InitState>(self.v, &INIT_ACTOR_ADDR).unwrap().as_ref().clone();
         let (target_id, existing) = st.map_addresses_to_id(&self.v.store, target, None).unwrap();
         assert!(!existing, "should never have existing actor when no f4 address is specified");
         let target_id_addr = Address::new_id(target_id);
@@ -506,6 +507,26 @@ impl<'invocation> Runtime for InvocationCtx<'invocation> {
 
         let from_id = self.resolve_address(&self.to()).unwrap();
 
+        if let Some(exit_code) = self.v.take_send_fault(from_id) {
+            let to = self.v.resolve_id_address(to).unwrap_or(*to);
+            RefMut::map(self.subinvocations.borrow_mut(), |subinvocs| {
+                subinvocs.push(InvocationTrace {
+                    from: from_id,
+                    to,
+                    value,
+                    method,
+                    params,
+                    error_number: None,
+                    exit_code,
+                    return_value: None,
+                    subinvocations: vec![],
+                    events: vec![],
+                });
+                subinvocs
+            });
+            return Ok(Response { exit_code, return_data: None });
+        }
+
         let new_actor_msg = InternalMessage { from: from_id, to: *to, value, method, params };
         let mut new_ctx = InvocationCtx {
             v: self.v,
@@ -641,7 +662,7 @@ impl<'invocation> Runtime for InvocationCtx<'invocation> {
     }
 
     fn tipset_timestamp(&self) -> u64 {
-        0
+        self.v.timestamp()
     }
 
     fn tipset_cid(&self, _epoch: i64) -> Result<Cid, ActorError> {
@@ -655,6 +676,25 @@ impl<'invocation> Runtime for InvocationCtx<'invocation> {
         Ok(())
     }
 
+    fn trace_step(&self, pc: u32, opcode: u8, stack_top: Option<[u8; 32]>) {
+        if let Some(trace) = self.v.trace.borrow_mut().as_mut() {
+            trace.push(TraceStep {
+                actor: self.msg.to.id().unwrap(),
+                pc,
+                opcode,
+                stack_top,
+                gas_available: self.gas_available(),
+                storage_status: None,
+            });
+        }
+    }
+
+    fn trace_storage_status(&self, status: StorageStatus) {
+        if let Some(step) = self.v.trace.borrow_mut().as_mut().and_then(|trace| trace.last_mut()) {
+            step.storage_status = Some(status);
+        }
+    }
+
     fn read_only(&self) -> bool {
         self.read_only
     }
@@ -670,6 +710,15 @@ impl Primitives for InvocationCtx<'_> {
         self.v.primitives().verify_signature(signature, signer, plaintext)
     }
 
+    fn verify_bls_aggregate(
+        &self,
+        signature: &[u8; BLS_SIG_LEN],
+        pub_keys: &[[u8; BLS_PUB_LEN]],
+        plaintexts: &[&[u8]],
+    ) -> Result<(), anyhow::Error> {
+        self.v.primitives().verify_bls_aggregate(signature, pub_keys, plaintexts)
+    }
+
     fn hash_blake2b(&self, data: &[u8]) -> [u8; 32] {
         self.v.primitives().hash_blake2b(data)
     }
@@ -0,0 +1,121 @@
+use std::path::Path;
+
+use anyhow::Context;
+use fvm_ipld_encoding::ipld_block::IpldBlock;
+use fvm_ipld_encoding::tuple::*;
+use fvm_ipld_encoding::RawBytes;
+use fvm_shared::address::Address;
+use fvm_shared::clock::ChainEpoch;
+use fvm_shared::econ::TokenAmount;
+use fvm_shared::error::ExitCode;
+use fvm_shared::MethodNum;
+use vm_api::{MessageResult, VM};
+
+/// Version of the replay file format, bumped on incompatible changes.
+pub const REPLAY_FORMAT_VERSION: u64 = 1;
+
+/// A sequence of messages applied to a VM, which can be saved to a file and re-executed later
+/// to reproduce a scenario, e.g. one that failed in an integration test.
+///
+/// Only messages are recorded. A replay reproduces a scenario only when applied to a VM in the
+/// state the recording started from, and when the scenario made no other changes to the VM,
+/// such as setting actors or primitives directly.
+#[derive(Serialize_tuple, Deserialize_tuple, Clone, Debug, PartialEq, Eq)]
+pub struct Replay {
+    pub version: u64,
+    pub messages: Vec<ReplayMessage>,
+}
+
+/// A message applied to a VM, with the epoch at which it was applied and its exit code.
+#[derive(Serialize_tuple, Deserialize_tuple, Clone, Debug, PartialEq, Eq)]
+pub struct ReplayMessage {
+    pub epoch: ChainEpoch,
+    pub from: Address,
+    pub to: Address,
+    pub value: TokenAmount,
+    pub method: MethodNum,
+    pub params: Option<ReplayParams>,
+    pub code: ExitCode,
+}
+
+#[derive(Serialize_tuple, Deserialize_tuple, Clone, Debug, PartialEq, Eq)]
+pub struct ReplayParams {
+    pub codec: u64,
+    pub data: RawBytes,
+}
+
+impl From<IpldBlock> for ReplayParams {
+    fn from(block: IpldBlock) -> Self {
+        ReplayParams { codec: block.codec, data: RawBytes::new(block.data) }
+    }
+}
+
+impl From<ReplayParams> for IpldBlock {
+    fn from(params: ReplayParams) -> Self {
+        IpldBlock { codec: params.codec, data: params.data.into() }
+    }
+}
+
+impl Replay {
+    pub fn new(messages: Vec<ReplayMessage>) -> Self {
+        Replay { version: REPLAY_FORMAT_VERSION, messages }
+    }
+
+    pub fn to_bytes(&self) -> anyhow::Result<Vec<u8>> {
+        Ok(fvm_ipld_encoding::to_vec(self)?)
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> anyhow::Result<Self> {
+        let replay: Replay = fvm_ipld_encoding::from_slice(bytes)?;
+        if replay.version != REPLAY_FORMAT_VERSION {
+            anyhow::bail!(
+                "unsupported replay format version {}, expected {}",
+                replay.version,
+                REPLAY_FORMAT_VERSION
+            );
+        }
+        Ok(replay)
+    }
+
+    pub fn write(&self, path: impl AsRef<Path>) -> anyhow::Result<()> {
+        let path = path.as_ref();
+        std::fs::write(path, self.to_bytes()?)
+            .with_context(|| format!("failed to write replay to {}", path.display()))
+    }
+
+    pub fn read(path: impl AsRef<Path>) -> anyhow::Result<Self> {
+        let path = path.as_ref();
+        let bytes = std::fs::read(path)
+            .with_context(|| format!("failed to read replay from {}", path.display()))?;
+        Self::from_bytes(&bytes)
+            .with_context(|| format!("failed to decode replay from {}", path.display()))
+    }
+
+    /// Re-executes the recorded messages in order, each at its recorded epoch, returning their
+    /// results. Panics if a message's exit code differs from the recorded one, as the replay
+    /// has then diverged from the scenario it was recorded from.
+    pub fn apply(&self, v: &dyn VM) -> Vec<MessageResult> {
+        self.messages
+            .iter()
+            .enumerate()
+            .map(|(i, m)| {
+                v.set_epoch(m.epoch);
+                let res = v
+                    .execute_message(
+                        &m.from,
+                        &m.to,
+                        &m.value,
+                        m.method,
+                        m.params.clone().map(IpldBlock::from),
+                    )
+                    .unwrap();
+                assert_eq!(
+                    m.code, res.code,
+                    "replayed message {} from {} to {} method {} exited with {} ({}), recorded {}",
+                    i, m.from, m.to, m.method, res.code, res.message, m.code
+                );
+                res
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,26 @@
+use fil_actors_integration_tests::tests::{
+    evm_eip1967_proxy_test, evm_minimal_clone_test, evm_nested_proxy_test,
+};
+use fil_actors_runtime::test_blockstores::MemoryBlockstore;
+use test_vm::TestVM;
+
+#[test]
+fn evm_eip1967_proxy() {
+    let store = MemoryBlockstore::new();
+    let v = TestVM::new_with_singletons(store);
+    evm_eip1967_proxy_test(&v);
+}
+
+#[test]
+fn evm_minimal_clone() {
+    let store = MemoryBlockstore::new();
+    let v = TestVM::new_with_singletons(store);
+    evm_minimal_clone_test(&v);
+}
+
+#[test]
+fn evm_nested_proxy() {
+    let store = MemoryBlockstore::new();
+    let v = TestVM::new_with_singletons(store);
+    evm_nested_proxy_test(&v);
+}
@@ -1,10 +1,17 @@
 use fil_actors_integration_tests::tests::{
     evm_call_test, evm_create_test, evm_delegatecall_test, evm_empty_initcode_test,
     evm_eth_create_external_test, evm_init_revert_data_test, evm_staticcall_delegatecall_test,
-    evm_staticcall_test, evm_transient_nested_test, evm_transient_reentry_test,
+    evm_staticcall_test, evm_transient_nested_test, evm_transient_reentry_test, ContractParams,
 };
+use fil_actors_integration_tests::util::create_accounts;
 use fil_actors_runtime::test_blockstores::MemoryBlockstore;
+use fil_actors_runtime::EAM_ACTOR_ADDR;
+use fvm_shared::address::Address;
+use fvm_shared::econ::TokenAmount;
+use num_traits::Zero;
 use test_vm::TestVM;
+use vm_api::util::serialize_ok;
+use vm_api::VM;
 
 #[test]
 fn evm_call() {
@@ -74,3 +81,50 @@ fn evm_transient_reentry() {
     let v = TestVM::new_with_singletons(store);
     evm_transient_reentry_test(&v);
 }
+
+#[test]
+fn evm_trace() {
+    let v = TestVM::new_with_singletons(MemoryBlockstore::new());
+    let sender = create_accounts(&v, 1, &TokenAmount::from_whole(10_000))[0];
+
+    // Initcode returning the runtime code
+    // PUSH1 0x2a PUSH1 0x00 MSTORE PUSH1 0x20 PUSH1 0x00 RETURN
+    let initcode = vec![
+        0x60, 0x0a, 0x80, 0x60, 0x0b, 0x60, 0x00, 0x39, 0x60, 0x00, 0xf3, // loader
+        0x60, 0x2a, 0x60, 0x00, 0x52, 0x60, 0x20, 0x60, 0x00, 0xf3, // runtime
+    ];
+    let create_result = v
+        .execute_message(
+            &sender,
+            &EAM_ACTOR_ADDR,
+            &TokenAmount::zero(),
+            fil_actor_eam::Method::CreateExternal as u64,
+            Some(serialize_ok(&fil_actor_eam::CreateExternalParams(initcode))),
+        )
+        .unwrap();
+    assert!(create_result.code.is_success(), "failed to create the contract");
+    let create_return: fil_actor_eam::CreateExternalReturn =
+        create_result.ret.unwrap().deserialize().unwrap();
+    let contract = Address::new_id(create_return.actor_id);
+
+    v.start_trace();
+    let call_result = v
+        .execute_message(
+            &sender,
+            &contract,
+            &TokenAmount::zero(),
+            fil_actor_evm::Method::InvokeContract as u64,
+            Some(serialize_ok(&ContractParams(vec![]))),
+        )
+        .unwrap();
+    assert!(call_result.code.is_success(), "failed to call the contract");
+    let trace = v.take_trace();
+
+    assert!(trace.iter().all(|step| step.actor == create_return.actor_id));
+    let steps: Vec<_> = trace.iter().map(|step| (step.pc, step.opcode)).collect();
+    assert_eq!(vec![(0, 0x60), (2, 0x60), (4, 0x52), (5, 0x60), (7, 0x60), (9, 0xf3)], steps);
+    let mut word = [0u8; 32];
+    word[31] = 0x2a;
+    assert_eq!(None, trace[0].stack_top);
+    assert_eq!(Some(word), trace[1].stack_top);
+}
@@ -1,4 +1,6 @@
-use fil_actors_integration_tests::tests::{cron_tick_test, power_create_miner_test};
+use fil_actors_integration_tests::tests::{
+    cron_tick_test, power_create_miner_invalid_params_test, power_create_miner_test,
+};
 use fil_actors_runtime::test_blockstores::MemoryBlockstore;
 use test_vm::TestVM;
 
@@ -10,6 +12,14 @@ fn power_create_miner() {
     power_create_miner_test(&v);
 }
 
+#[test]
+fn power_create_miner_invalid_params() {
+    let store = MemoryBlockstore::new();
+    let v = TestVM::new_with_singletons(store);
+
+    power_create_miner_invalid_params_test(&v);
+}
+
 #[test]
 fn cron_tick() {
     let store = MemoryBlockstore::new();
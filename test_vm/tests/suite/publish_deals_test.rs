@@ -1,7 +1,7 @@
 use fil_actors_integration_tests::tests::{
     all_deals_are_good_test, psd_all_deals_are_bad_test, psd_bad_piece_size_test, psd_bad_sig_test,
     psd_client_address_cannot_be_resolved_test, psd_deal_duration_too_long_test,
-    psd_duplicate_deal_in_batch_test, psd_duplicate_deal_in_state_test,
+    psd_duplicate_deal_in_batch_test, psd_duplicate_deal_in_state_test, psd_failure_matrix_test,
     psd_mismatched_provider_test, psd_no_client_lockup_test,
     psd_not_enough_client_lockup_for_batch_test, psd_not_enough_provider_lockup_for_batch_test,
     psd_random_assortment_of_failures_test, psd_start_time_in_past_test,
@@ -121,3 +121,10 @@ fn psd_deal_duration_too_long() {
     let v = TestVM::new_with_singletons(store);
     psd_deal_duration_too_long_test(&v);
 }
+
+#[test]
+fn psd_failure_matrix() {
+    let store = MemoryBlockstore::new();
+    let v = TestVM::new_with_singletons(store);
+    psd_failure_matrix_test(&v);
+}
@@ -1,16 +1,30 @@
 use fil_actor_account::State as AccountState;
-use fil_actors_integration_tests::util::{assert_invariants, check_invariants};
+use fil_actor_miner::Method as MinerMethod;
+use fil_actor_reward::{
+    AwardBlockRewardParams, Method as RewardMethod, ReclaimAwardParams, State as RewardState,
+};
+use fil_actors_integration_tests::util::{
+    assert_invariants, check_invariants, create_accounts, create_miner,
+};
 use fil_actors_runtime::runtime::{Policy, EMPTY_ARR_CID};
 use fil_actors_runtime::test_blockstores::MemoryBlockstore;
 use fil_actors_runtime::test_utils::{ACCOUNT_ACTOR_CODE_ID, PAYCH_ACTOR_CODE_ID};
+use fil_actors_runtime::{
+    OnUpgradeParams, BURNT_FUNDS_ACTOR_ADDR, ON_UPGRADE_METHOD, REWARD_ACTOR_ADDR,
+    SYSTEM_ACTOR_ADDR,
+};
+use fvm_ipld_encoding::ipld_block::IpldBlock;
+use fvm_ipld_encoding::RawBytes;
 use fvm_shared::address::Address;
 use fvm_shared::econ::TokenAmount;
 use fvm_shared::error::ExitCode;
-use fvm_shared::METHOD_SEND;
+use fvm_shared::sector::RegisteredPoStProof;
+use fvm_shared::{METHOD_CONSTRUCTOR, METHOD_SEND};
 use num_traits::Zero;
-use test_vm::{TestVM, FIRST_TEST_USER_ADDR, TEST_FAUCET_ADDR};
-use vm_api::util::{get_state, pk_addrs_from};
-use vm_api::{new_actor, VM};
+use test_vm::{Replay, TestVM, FIRST_TEST_USER_ADDR, TEST_FAUCET_ADDR};
+use vm_api::trace::ExpectInvocation;
+use vm_api::util::{get_state, get_state_cached, mutate_state, pk_addrs_from};
+use vm_api::{new_actor, ExitReason, VM};
 
 #[test]
 fn state_control() {
@@ -42,6 +56,102 @@ fn state_control() {
     assert!(err.to_string().contains("AccountState is empty"), "unexpected error: {err}");
 }
 
+#[test]
+fn actor_state_snapshot_moves_between_vms() {
+    let store = MemoryBlockstore::new();
+    let v = TestVM::new_with_singletons(store);
+    let addrs = create_accounts(&v, 2, &TokenAmount::from_whole(10_000));
+    let snapshot = v.export_actor_state(&REWARD_ACTOR_ADDR);
+    assert_eq!(snapshot, v.export_actor_state(&REWARD_ACTOR_ADDR));
+
+    // Award a block reward so that the reward actor's state diverges from the snapshot.
+    let params = AwardBlockRewardParams {
+        miner: addrs[0],
+        penalty: TokenAmount::zero(),
+        gas_reward: TokenAmount::from_atto(100),
+        win_count: 1,
+    };
+    v.execute_message(
+        &SYSTEM_ACTOR_ADDR,
+        &REWARD_ACTOR_ADDR,
+        &TokenAmount::zero(),
+        RewardMethod::AwardBlockReward as u64,
+        IpldBlock::serialize_cbor(&params).unwrap(),
+    )
+    .unwrap();
+    let awarded: RewardState = get_state(&v, &REWARD_ACTOR_ADDR).unwrap();
+    let awarded_snapshot = v.export_actor_state(&REWARD_ACTOR_ADDR);
+    assert_ne!(snapshot, awarded_snapshot);
+
+    // Importing into a VM with a separate store reproduces the awarded state.
+    let other = TestVM::new_with_singletons(MemoryBlockstore::new());
+    other.import_actor_state(&REWARD_ACTOR_ADDR, &awarded_snapshot);
+    let imported: RewardState = get_state(&other, &REWARD_ACTOR_ADDR).unwrap();
+    assert_eq!(awarded.total_storage_power_reward, imported.total_storage_power_reward);
+    assert_eq!(awarded_snapshot, other.export_actor_state(&REWARD_ACTOR_ADDR));
+}
+
+#[test]
+fn cached_state_tracks_state_root() {
+    let store = MemoryBlockstore::new();
+    let v = TestVM::new(store);
+    let addr = Address::new_id(1000);
+    let pk1 = Address::new_bls(&[1; fvm_shared::address::BLS_PUB_LEN]).unwrap();
+    let pk2 = Address::new_bls(&[2; fvm_shared::address::BLS_PUB_LEN]).unwrap();
+
+    let head = v.put_store(&AccountState::new(pk1));
+    v.set_actor(&addr, new_actor(*ACCOUNT_ACTOR_CODE_ID, head, 0, TokenAmount::zero(), None));
+    let check = v.checkpoint();
+    let st = get_state_cached::<AccountState>(&v, &addr).unwrap();
+    assert_eq!(pk1, st.address);
+
+    // state changes are observed rather than served from the cache
+    mutate_state(&v, &addr, |st: &mut AccountState| st.address = pk2);
+    let st = get_state_cached::<AccountState>(&v, &addr).unwrap();
+    assert_eq!(pk2, st.address);
+
+    // rolling back returns the earlier state
+    v.rollback(check);
+    let st = get_state_cached::<AccountState>(&v, &addr).unwrap();
+    assert_eq!(pk1, st.address);
+    let uncached: AccountState = get_state(&v, &addr).unwrap();
+    assert_eq!(uncached.address, st.address);
+}
+
+#[test]
+fn upgrade_invokes_hook_from_system() {
+    let store = MemoryBlockstore::new();
+    let v = TestVM::new_with_singletons(store);
+    let addr1 = Address::new_bls(&[1; fvm_shared::address::BLS_PUB_LEN]).unwrap();
+    v.execute_message(&TEST_FAUCET_ADDR, &addr1, &TokenAmount::from_atto(42u8), METHOD_SEND, None)
+        .unwrap();
+    let id_addr1 = Address::new_id(FIRST_TEST_USER_ADDR);
+    let old_state = v.actor(&id_addr1).unwrap().state;
+
+    // the default hook leaves state untouched
+    let res = v.upgrade_actor(&id_addr1, *ACCOUNT_ACTOR_CODE_ID, RawBytes::default()).unwrap();
+    assert_eq!(ExitCode::OK, res.code);
+    assert_eq!(ExitReason::Ok, res.exit_reason());
+    assert_eq!(old_state, v.actor(&id_addr1).unwrap().state);
+    assert_account_actor(0, TokenAmount::from_atto(42u8), addr1, &v, id_addr1);
+
+    // only the system actor may invoke the hook
+    let params = OnUpgradeParams { old_state, params: RawBytes::default() };
+    let res = v
+        .execute_message(
+            &addr1,
+            &id_addr1,
+            &TokenAmount::zero(),
+            ON_UPGRADE_METHOD,
+            IpldBlock::serialize_cbor(&params).unwrap(),
+        )
+        .unwrap();
+    assert_eq!(ExitCode::USR_FORBIDDEN, res.code);
+    assert_eq!(ExitReason::User, res.exit_reason());
+
+    assert_invariants(&v, &Policy::default(), None)
+}
+
 fn assert_account_actor(
     exp_call_seq: u64,
     exp_bal: TokenAmount,
@@ -88,6 +198,7 @@ fn test_sent() {
     let mres =
         v.execute_message(&addr2, &addr1, &TokenAmount::from_atto(1u8), METHOD_SEND, None).unwrap();
     assert_eq!(ExitCode::SYS_INSUFFICIENT_FUNDS, mres.code);
+    assert_eq!(ExitReason::System, mres.exit_reason());
     assert_account_actor(2, TokenAmount::from_atto(42u8), addr1, &v, expect_id_addr1);
     assert_account_actor(2, TokenAmount::zero(), addr2, &v, expect_id_addr2);
 
@@ -116,3 +227,137 @@ fn test_pk_gen() {
     let first_addr_seeded_six = addrs[0];
     assert_ne!(second_addr_seeded_five, first_addr_seeded_six);
 }
+
+#[test]
+fn reward_held_when_apply_rewards_send_fails() {
+    let store = MemoryBlockstore::new();
+    let v = TestVM::new_with_singletons(store);
+    let addrs = create_accounts(&v, 1, &TokenAmount::from_whole(10_000));
+    let (owner, worker) = (addrs[0], addrs[0]);
+    let (miner, _) = create_miner(
+        &v,
+        &owner,
+        &worker,
+        RegisteredPoStProof::StackedDRGWindow32GiBV1P1,
+        &TokenAmount::from_whole(1_000),
+    );
+    let miner_balance = v.balance(&miner);
+    let burnt_balance = v.balance(&BURNT_FUNDS_ACTOR_ADDR);
+    let reward_balance = v.balance(&REWARD_ACTOR_ADDR);
+
+    // The miner never sees the reward, so the reward actor holds it for the miner to reclaim.
+    v.fail_nth_send(&REWARD_ACTOR_ADDR, 1, ExitCode::USR_ILLEGAL_STATE);
+    let params = AwardBlockRewardParams {
+        miner,
+        penalty: TokenAmount::zero(),
+        gas_reward: TokenAmount::from_whole(10),
+        win_count: 1,
+    };
+    let res = v
+        .execute_message(
+            &SYSTEM_ACTOR_ADDR,
+            &REWARD_ACTOR_ADDR,
+            &TokenAmount::zero(),
+            RewardMethod::AwardBlockReward as u64,
+            IpldBlock::serialize_cbor(&params).unwrap(),
+        )
+        .unwrap();
+    assert_eq!(ExitCode::OK, res.code);
+
+    let held = get_state::<RewardState>(&v, &REWARD_ACTOR_ADDR).unwrap().failed_award_total();
+    assert!(held >= params.gas_reward);
+    assert_eq!(reward_balance, v.balance(&REWARD_ACTOR_ADDR));
+    assert_eq!(miner_balance, v.balance(&miner));
+    assert_eq!(burnt_balance, v.balance(&BURNT_FUNDS_ACTOR_ADDR));
+    ExpectInvocation {
+        from: SYSTEM_ACTOR_ADDR.id().unwrap(),
+        to: REWARD_ACTOR_ADDR,
+        method: RewardMethod::AwardBlockReward as u64,
+        subinvocs: Some(vec![ExpectInvocation {
+            from: REWARD_ACTOR_ADDR.id().unwrap(),
+            to: miner,
+            method: MinerMethod::ApplyRewards as u64,
+            value: Some(held.clone()),
+            exit_code: ExitCode::USR_ILLEGAL_STATE,
+            subinvocs: Some(vec![]),
+            ..Default::default()
+        }]),
+        ..Default::default()
+    }
+    .matches(v.take_invocations().last().unwrap());
+
+    // Anyone can have the held reward applied to the miner.
+    let res = v
+        .execute_message(
+            &owner,
+            &REWARD_ACTOR_ADDR,
+            &TokenAmount::zero(),
+            RewardMethod::ReclaimAwardExported as u64,
+            IpldBlock::serialize_cbor(&ReclaimAwardParams { miner }).unwrap(),
+        )
+        .unwrap();
+    assert_eq!(ExitCode::OK, res.code);
+    assert_eq!(&miner_balance + &held, v.balance(&miner));
+    assert!(get_state::<RewardState>(&v, &REWARD_ACTOR_ADDR).unwrap().failed_awards.is_empty());
+
+    // The fault is consumed, so the next reward reaches the miner.
+    let res = v
+        .execute_message(
+            &SYSTEM_ACTOR_ADDR,
+            &REWARD_ACTOR_ADDR,
+            &TokenAmount::zero(),
+            RewardMethod::AwardBlockReward as u64,
+            IpldBlock::serialize_cbor(&params).unwrap(),
+        )
+        .unwrap();
+    assert_eq!(ExitCode::OK, res.code);
+    assert!(v.balance(&miner) > &miner_balance + &held);
+}
+
+#[test]
+fn record_and_replay_messages() {
+    let v = TestVM::new_with_singletons(MemoryBlockstore::new());
+    v.start_recording();
+    let addrs = create_accounts(&v, 2, &TokenAmount::from_whole(100));
+    v.set_epoch(10);
+    let res = v
+        .execute_message(&addrs[0], &addrs[1], &TokenAmount::from_whole(1), METHOD_SEND, None)
+        .unwrap();
+    assert_eq!(ExitCode::OK, res.code);
+    // Failed messages are recorded with their exit code.
+    let res = v
+        .execute_message(&addrs[0], &addrs[1], &TokenAmount::zero(), METHOD_CONSTRUCTOR, None)
+        .unwrap();
+    let failed = res.code;
+    assert!(!failed.is_success());
+
+    let replay = v.take_recording();
+    assert_eq!(4, replay.messages.len());
+    assert_eq!(10, replay.messages[2].epoch);
+    assert_eq!(failed, replay.messages[3].code);
+
+    let path = std::env::temp_dir().join(format!("test_vm_replay_{}.cbor", std::process::id()));
+    replay.write(&path).unwrap();
+    let read = Replay::read(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!(replay, read);
+
+    // Replaying in a VM in the same initial state reproduces the final state.
+    let replayed = TestVM::new_with_singletons(MemoryBlockstore::new());
+    let results = read.apply(&replayed);
+    assert_eq!(failed, results[3].code);
+    assert_eq!(10, replayed.epoch());
+    assert_eq!(v.checkpoint(), replayed.checkpoint());
+}
+
+#[test]
+#[should_panic(expected = "replayed message 0")]
+fn replay_detects_divergence() {
+    let v = TestVM::new_with_singletons(MemoryBlockstore::new());
+    v.start_recording();
+    create_accounts(&v, 1, &TokenAmount::from_whole(100));
+    let mut replay = v.take_recording();
+    replay.messages[0].code = ExitCode::USR_FORBIDDEN;
+
+    replay.apply(&TestVM::new_with_singletons(MemoryBlockstore::new()));
+}
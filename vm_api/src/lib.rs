@@ -1,4 +1,6 @@
+use std::any::{Any, TypeId};
 use std::collections::BTreeMap;
+use std::rc::Rc;
 
 use anyhow::Error;
 use cid::Cid;
@@ -13,7 +15,10 @@ use fvm_shared::{
     consensus::ConsensusFault,
     crypto::{
         hash::SupportedHashes,
-        signature::{Signature, SECP_PUB_LEN, SECP_SIG_LEN, SECP_SIG_MESSAGE_HASH_SIZE},
+        signature::{
+            Signature, BLS_PUB_LEN, BLS_SIG_LEN, SECP_PUB_LEN, SECP_SIG_LEN,
+            SECP_SIG_MESSAGE_HASH_SIZE,
+        },
     },
     econ::TokenAmount,
     error::ExitCode,
@@ -113,6 +118,17 @@ pub trait VM {
 
     /// Set the current timestamp
     fn set_timestamp(&self, timestamp: u64);
+
+    // Optional deserialized state caching
+
+    /// Look up a deserialized actor state object previously cached against its state CID.
+    /// VMs that don't cache state always return None.
+    fn cached_state(&self, _head: &Cid, _type_id: TypeId) -> Option<Rc<dyn Any>> {
+        None
+    }
+
+    /// Cache a deserialized actor state object against its state CID
+    fn cache_state(&self, _head: &Cid, _type_id: TypeId, _state: Rc<dyn Any>) {}
 }
 
 #[derive(Clone, PartialEq, Eq, Debug)]
@@ -120,6 +136,34 @@ pub struct MessageResult {
     pub code: ExitCode,
     pub message: String,
     pub ret: Option<IpldBlock>,
+    /// Gas consumed by the message, if the VM meters gas
+    pub gas_used: Option<u64>,
+    /// Events emitted by the message, empty if it failed
+    pub events: Vec<EmittedEvent>,
+}
+
+impl MessageResult {
+    /// Classifies the message's exit code
+    pub fn exit_reason(&self) -> ExitReason {
+        if self.code.is_success() {
+            ExitReason::Ok
+        } else if self.code.is_system_error() {
+            ExitReason::System
+        } else {
+            ExitReason::User
+        }
+    }
+}
+
+/// How a message exited
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ExitReason {
+    /// The message succeeded
+    Ok,
+    /// An actor aborted with a user-defined exit code
+    User,
+    /// The VM aborted the message with a system exit code
+    System,
 }
 
 // Duplicates an internal FVM type (fvm::state_tree::ActorState) that cannot be depended on here
@@ -175,6 +219,15 @@ pub trait Primitives {
         plaintext: &[u8],
     ) -> Result<(), Error>;
 
+    /// Verifies a BLS aggregate signature over a set of distinct plaintexts,
+    /// each signed by the public key at the same index.
+    fn verify_bls_aggregate(
+        &self,
+        signature: &[u8; BLS_SIG_LEN],
+        pub_keys: &[[u8; BLS_PUB_LEN]],
+        plaintexts: &[&[u8]],
+    ) -> Result<(), Error>;
+
     fn recover_secp_public_key(
         &self,
         hash: &[u8; SECP_SIG_MESSAGE_HASH_SIZE],
@@ -258,6 +311,12 @@ pub trait MockPrimitives: Primitives {
     /// Override the primitive verify_signature function
     fn override_verify_signature(&self, f: fn(&Signature, &Address, &[u8]) -> Result<(), Error>);
 
+    /// Override the primitive verify_bls_aggregate function
+    fn override_verify_bls_aggregate(
+        &self,
+        f: fn(&[u8; BLS_SIG_LEN], &[[u8; BLS_PUB_LEN]], &[&[u8]]) -> Result<(), Error>,
+    );
+
     /// Override the primitive verify_replica_update function
     fn override_verify_replica_update(&self, f: fn(&ReplicaUpdateInfo) -> Result<(), Error>);
 
@@ -13,6 +13,41 @@ pub struct EmittedEvent {
     pub event: ActorEvent,
 }
 
+/// A step of an interpreter's execution, traced for debugging.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct TraceStep {
+    /// The actor executing the step.
+    pub actor: ActorID,
+    /// Offset of the instruction in the executing code.
+    pub pc: u32,
+    pub opcode: u8,
+    /// The value at the top of the stack before the step, big-endian, if the stack isn't empty.
+    pub stack_top: Option<[u8; 32]>,
+    /// Gas available before the step, as reported by the VM.
+    pub gas_available: u64,
+    /// The classification of the storage write made by the step, if it wrote to storage.
+    pub storage_status: Option<StorageStatus>,
+}
+
+/// Classification of a storage write, following EIP-2200 and EIP-3529.
+/// A slot's "original" value is its value when the current invocation first wrote to it,
+/// and a slot is "dirty" once its current value differs from the original.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum StorageStatus {
+    /// The write didn't change the slot's current value.
+    Unchanged,
+    /// A clean slot with an original value of zero was set.
+    Added,
+    /// A clean slot with a non-zero original value was changed to another non-zero value.
+    Modified,
+    /// A clean slot with a non-zero original value was cleared.
+    Deleted,
+    /// A dirty slot was set back to its original value.
+    Restored,
+    /// A dirty slot was changed to a value other than its original value.
+    Assigned,
+}
+
 /// A trace of an actor method invocation.
 #[derive(Clone, Debug)]
 pub struct InvocationTrace {
@@ -30,6 +65,22 @@ pub struct InvocationTrace {
     pub events: Vec<EmittedEvent>,
 }
 
+impl InvocationTrace {
+    /// Returns the events emitted by this invocation and its successful subinvocations.
+    /// Events of failed invocations are discarded, as they are by the VM.
+    /// Each invocation's own events are listed before those of its subinvocations.
+    pub fn emitted_events(&self) -> Vec<EmittedEvent> {
+        if !self.exit_code.is_success() || self.error_number.is_some() {
+            return vec![];
+        }
+        let mut events = self.events.clone();
+        for sub in &self.subinvocations {
+            events.extend(sub.emitted_events());
+        }
+        events
+    }
+}
+
 /// An expectation for a method invocation trace.
 /// Non-optional fields must always be specified, and are always checked against any trace.
 /// Optional fields are ignored when checking the expectation against a trace.
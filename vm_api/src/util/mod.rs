@@ -8,6 +8,8 @@ use multihash_codetable::Code;
 use rand::prelude::*;
 use rand_chacha::ChaCha8Rng;
 use serde::Serialize;
+use std::any::TypeId;
+use std::rc::Rc;
 
 mod blockstore;
 pub use blockstore::*;
@@ -15,6 +17,53 @@ use serde::de::DeserializeOwned;
 
 use crate::VM;
 
+/// Asserts that a [`MessageResult`](crate::MessageResult) succeeded, reporting its exit code
+/// and message otherwise. Optional trailing arguments format additional context.
+#[macro_export]
+macro_rules! assert_ok {
+    ($res:expr $(,)?) => {{
+        let res: &$crate::MessageResult = &$res;
+        assert!(res.code.is_success(), "expected success, got {} ({})", res.code, res.message);
+    }};
+    ($res:expr, $($arg:tt)+) => {{
+        let res: &$crate::MessageResult = &$res;
+        assert!(
+            res.code.is_success(),
+            "{}: expected success, got {} ({})",
+            format_args!($($arg)+),
+            res.code,
+            res.message
+        );
+    }};
+}
+
+/// Asserts that a [`MessageResult`](crate::MessageResult) exited with the given code,
+/// reporting the actual exit code and message otherwise.
+/// Optional trailing arguments format additional context.
+#[macro_export]
+macro_rules! assert_aborts_with {
+    ($res:expr, $code:expr $(,)?) => {{
+        let res: &$crate::MessageResult = &$res;
+        let code = $code;
+        assert_eq!(code, res.code, "expected code {}, got {} ({})", code, res.code, res.message);
+    }};
+    ($res:expr, $code:expr, $($arg:tt)+) => {{
+        let res: &$crate::MessageResult = &$res;
+        let code = $code;
+        assert_eq!(
+            code,
+            res.code,
+            "{}: expected code {}, got {} ({})",
+            format_args!($($arg)+),
+            code,
+            res.code,
+            res.message
+        );
+    }};
+}
+
+pub use crate::{assert_aborts_with, assert_ok};
+
 /// Generate count addresses by seeding an rng
 pub fn pk_addrs_from(seed: u64, count: u64) -> Vec<Address> {
     let mut seed_arr = [0u8; 32];
@@ -54,7 +103,7 @@ pub fn apply_code<S: Serialize>(
 ) -> RawBytes {
     let params = params.map(|p| IpldBlock::serialize_cbor(&p).unwrap().unwrap());
     let res = v.execute_message(from, to, value, method, params).unwrap();
-    assert_eq!(code, res.code, "expected code {}, got {} ({})", code, res.code, res.message);
+    assert_aborts_with!(res, code);
     res.ret.map_or(RawBytes::default(), |b| RawBytes::new(b.data))
 }
 
@@ -66,10 +115,9 @@ pub fn apply_ok_implicit<S: Serialize>(
     method: MethodNum,
     params: Option<S>,
 ) -> RawBytes {
-    let code = ExitCode::OK;
     let params = params.map(|p| IpldBlock::serialize_cbor(&p).unwrap().unwrap());
     let res = v.execute_message_implicit(from, to, value, method, params).unwrap();
-    assert_eq!(code, res.code, "expected code {}, got {} ({})", code, res.code, res.message);
+    assert_ok!(res);
     res.ret.map_or(RawBytes::default(), |b| RawBytes::new(b.data))
 }
 pub fn get_state<T: DeserializeOwned>(v: &dyn VM, a: &Address) -> Option<T> {
@@ -77,6 +125,25 @@ pub fn get_state<T: DeserializeOwned>(v: &dyn VM, a: &Address) -> Option<T> {
     v.blockstore().get(&cid).unwrap().map(|slice| fvm_ipld_encoding::from_slice(&slice).unwrap())
 }
 
+/// Like [`get_state`], but reuses a previously deserialized state object if the VM caches them.
+/// Cached objects are keyed by the actor's state CID, so they can't go stale when the VM
+/// state root moves forwards or is rolled back.
+/// The state is shared with the cache rather than cloned, so callers needing to modify it
+/// must clone it themselves.
+pub fn get_state_cached<T: DeserializeOwned + 'static>(v: &dyn VM, a: &Address) -> Option<Rc<T>> {
+    let cid = v.actor(a).unwrap().state;
+    if let Some(st) = v.cached_state(&cid, TypeId::of::<T>()) {
+        return st.downcast::<T>().ok();
+    }
+    let st: Rc<T> = v
+        .blockstore()
+        .get(&cid)
+        .unwrap()
+        .map(|slice| Rc::new(fvm_ipld_encoding::from_slice(&slice).unwrap()))?;
+    v.cache_state(&cid, TypeId::of::<T>(), st.clone());
+    Some(st)
+}
+
 /// Convenience function to create an IpldBlock from a serializable object
 pub fn serialize_ok<S: Serialize>(s: &S) -> IpldBlock {
     IpldBlock::serialize_cbor(s).unwrap().unwrap()